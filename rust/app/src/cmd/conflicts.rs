@@ -0,0 +1,40 @@
+//! 録画予約の競合表示コマンド
+//!
+//! このモジュールは録画予約をチューナーに割り当て、チューナーが足りずに録画できない録画予約を表示するコマンドを提供します。
+
+use anyhow::Result;
+use chrono::Utc;
+use domain::usecases::schedule_conflict_usecase::ScheduleConflictUseCase;
+
+/// 録画できない録画予約を表示する
+pub async fn print_conflicts(usecase: &ScheduleConflictUseCase) -> Result<()> {
+    let analysis = usecase.analyze(Utc::now()).await?;
+    println!(
+        "Assigned: {}, conflicts: {}",
+        analysis.assignments.len(),
+        analysis.conflicts.len()
+    );
+    for conflict in analysis.conflicts {
+        let schedule = &conflict.schedule;
+        println!(
+            "{}\t{}\tpriority={}\t{}\t{}",
+            schedule.start_at.format("%Y-%m-%d %H:%M"),
+            schedule.channel_type,
+            schedule.priority(),
+            schedule.name.as_deref().unwrap_or("-"),
+            schedule.mirakc_url
+        );
+        for blocker in &conflict.blocked_by {
+            println!(
+                "\tblocked by: {}\tpriority={}\t{}",
+                blocker.start_at.format("%Y-%m-%d %H:%M"),
+                blocker.priority,
+                blocker.name.as_deref().unwrap_or("-")
+            );
+        }
+        if let Some(url) = &conflict.suggested_mirakc_url {
+            println!("\tsuggested mirakc: {}", url);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! エンコーダーワーカーコマンド
+//!
+//! このモジュールはジョブエンジンが送ったエンコードジョブを実行するコマンドを提供します。
+
+use anyhow::Result;
+use domain::{
+    events::kurec_events::JobDispatchedEvent, models::job::JobResult,
+    ports::event_source::EventSource, usecases::encoder_usecase::EncoderUseCase,
+};
+use futures::StreamExt;
+use std::sync::Arc;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// エンコーダーワーカーを実行 (手動ループ)
+///
+/// エンコードとその他の処理が同時に実行されて過負荷にならないよう、ジョブは1件ずつ順に実行する。
+/// エンコード中にシャットダウンした場合は、スクリプトを強制終了する。
+pub async fn run_encoder(
+    usecase: Arc<EncoderUseCase>,
+    source: Arc<dyn EventSource<JobDispatchedEvent>>,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!("Starting encoder worker...");
+
+    let mut event_stream = source.subscribe().await?;
+
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping encoder worker.");
+                break;
+            }
+            maybe_event = event_stream.next() => {
+                match maybe_event {
+                    Some(Ok(event)) => {
+                        let result = select! {
+                            _ = shutdown.cancelled() => None,
+                            result = usecase.handle_job_dispatched(&event) => Some(result),
+                        };
+                        let Some(result) = result else {
+                            warn!(record_id = %event.record_id, job = %event.job.name, "Shutdown signal received during encoding, aborting job.");
+                            break;
+                        };
+                        match result {
+                            Ok(None) => {}
+                            Ok(Some(JobResult::Succeeded { outputs })) => {
+                                info!(record_id = %event.record_id, job = %event.job.name, outputs = outputs.len(), "Encode job succeeded")
+                            }
+                            Ok(Some(JobResult::Failed { error })) => {
+                                warn!(record_id = %event.record_id, job = %event.job.name, error = %error, "Encode job failed")
+                            }
+                            Err(e) => error!("Error processing encode job: {:?}. Continuing...", e),
+                        }
+                    }
+                    Some(Err(e)) => {
+                        error!("Error receiving job dispatched event: {}. Continuing...", e);
+                    }
+                    None => match super::resubscribe(&source, "Job dispatched event").await {
+                        Some(stream) => event_stream = stream,
+                        None => break,
+                    },
+                }
+            }
+        }
+    }
+
+    info!("Encoder worker stopped gracefully.");
+    Ok(())
+}
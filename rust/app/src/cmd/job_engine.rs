@@ -0,0 +1,124 @@
+//! ジョブエンジンワーカーコマンド
+//!
+//! このモジュールは録画の完了とジョブの完了を購読し、録画ごとのジョブの実行を進めるコマンドを提供します。
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use domain::{
+    events::{kurec_events::JobCompletedEvent, mirakc_events::RecordingRecordSavedEvent},
+    models::job::{JobGraph, JobsConfig},
+    ports::event_source::EventSource,
+    usecases::job_engine_usecase::JobEngineUseCase,
+};
+use futures::stream::{self, BoxStream, StreamExt};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+
+/// ジョブファイルを読み込み、依存関係を検証したグラフを作成する
+///
+/// 循環依存などがある場合はエラーにして、ワーカーを起動させない。
+pub async fn load_job_graph(file: &Path) -> Result<JobGraph> {
+    let json = tokio::fs::read_to_string(file)
+        .await
+        .with_context(|| format!("Failed to read job file: {}", file.display()))?;
+    let graph = JobGraph::new(&JobsConfig::parse(&json)?)
+        .with_context(|| format!("Invalid job file: {}", file.display()))?;
+    for job in graph.jobs() {
+        info!(job = %job.name, depends_on = ?job.depends_on, "Loaded job");
+    }
+    Ok(graph)
+}
+
+/// ジョブエンジンワーカーが購読するイベントソース
+pub struct JobEngineSources {
+    pub record_saved: Arc<dyn EventSource<RecordingRecordSavedEvent>>,
+    pub job_completed: Arc<dyn EventSource<JobCompletedEvent>>,
+}
+
+/// 購読したイベントをひとつのストリームで扱うための列挙型
+enum JobEngineEvent {
+    RecordSaved(RecordingRecordSavedEvent),
+    JobCompleted(JobCompletedEvent),
+}
+
+impl JobEngineSources {
+    /// すべてのイベントソースを購読し、ひとつのストリームにまとめる
+    async fn subscribe(&self) -> Result<BoxStream<'static, Result<JobEngineEvent>>> {
+        let saved = self
+            .record_saved
+            .subscribe()
+            .await?
+            .map(|r| r.map(JobEngineEvent::RecordSaved));
+        let completed = self
+            .job_completed
+            .subscribe()
+            .await?
+            .map(|r| r.map(JobEngineEvent::JobCompleted));
+        Ok(stream::select(saved, completed).boxed())
+    }
+}
+
+/// ジョブエンジンワーカーを実行 (手動ループ)
+///
+/// 実行状況の更新が競合しないよう、イベントは1件ずつ順に処理する。
+pub async fn run_job_engine(
+    usecase: Arc<JobEngineUseCase>,
+    sources: JobEngineSources,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!("Starting job engine worker...");
+
+    let mut event_stream = sources.subscribe().await?;
+
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping job engine worker.");
+                break;
+            }
+            maybe_event = event_stream.next() => {
+                match maybe_event {
+                    Some(Ok(event)) => {
+                        let result = match &event {
+                            JobEngineEvent::RecordSaved(e) => {
+                                debug!(record_id = %e.record_id, "Received RecordingRecordSavedEvent");
+                                usecase.handle_record_saved(e, Utc::now()).await
+                            }
+                            JobEngineEvent::JobCompleted(e) => {
+                                info!(record_id = %e.record_id, job = %e.job, "Received JobCompletedEvent");
+                                usecase.handle_job_completed(e, Utc::now()).await
+                            }
+                        };
+                        match result {
+                            Ok(0) => {}
+                            Ok(count) => info!(count, "Dispatched jobs"),
+                            Err(e) => error!("Error processing job event: {:?}. Continuing...", e),
+                        }
+                    }
+                    Some(Err(e)) => {
+                        error!("Error receiving job engine event: {}. Continuing...", e);
+                    }
+                    None => {
+                        error!("Job engine event stream ended unexpectedly. Attempting to reconnect...");
+                        match sources.subscribe().await {
+                            Ok(new_stream) => {
+                                info!("Successfully reconnected to job engine event streams");
+                                event_stream = new_stream;
+                            }
+                            Err(e) => {
+                                error!("Failed to reconnect to job engine event streams: {:?}. Exiting.", e);
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    info!("Job engine worker stopped gracefully.");
+    Ok(())
+}
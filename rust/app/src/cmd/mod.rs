@@ -2,5 +2,68 @@
 //!
 //! このモジュールはアプリケーションのコマンド実装を提供します。
 
+pub mod conflicts;
+pub mod encoder;
 pub mod epg_updater;
+pub mod job_engine;
 pub mod mirakc_events;
+pub mod now_playing;
+pub mod ogp;
+pub mod original_saver;
+pub mod query_server;
+pub mod reconciler;
+pub mod record_deleter;
+pub mod record_indexer;
+pub mod record_library;
+pub mod rule_engine;
+pub mod rules;
+pub mod search_indexer;
+pub mod series;
+pub mod service_catalog;
+pub mod tuner_status;
+pub mod version_watch;
+pub mod xmltv;
+
+use anyhow::{Context, Result};
+use domain::ports::event_source::EventSource;
+use domain::ports::storage::StorageRegistry;
+use futures::stream::BoxStream;
+use infra_nats::NatsClient;
+use infra_storage::StoragesConfig;
+use serde::de::DeserializeOwned;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// ストリームが終了した場合に購読し直す。失敗した場合は `None`。
+pub(crate) async fn resubscribe<E>(
+    source: &Arc<dyn EventSource<E>>,
+    name: &str,
+) -> Option<BoxStream<'static, Result<E>>>
+where
+    E: DeserializeOwned + Send + Sync + 'static,
+{
+    error!(
+        "{} stream ended unexpectedly. Attempting to reconnect...",
+        name
+    );
+    match source.subscribe().await {
+        Ok(stream) => Some(stream),
+        Err(e) => {
+            error!("Failed to reconnect to {} stream: {:?}. Exiting.", name, e);
+            None
+        }
+    }
+}
+
+/// ストレージの設定ファイルを読み込み、ジョブの出力の保存先のストレージを作成する
+pub async fn load_storages(file: &Path, nats_client: Arc<NatsClient>) -> Result<StorageRegistry> {
+    let json = tokio::fs::read_to_string(file)
+        .await
+        .with_context(|| format!("Failed to read storage file: {}", file.display()))?;
+    let storages = infra_storage::open_storages(&StoragesConfig::parse(&json)?, Some(nats_client))
+        .await
+        .with_context(|| format!("Invalid storage file: {}", file.display()))?;
+    info!(storages = ?storages.names(), "Loaded storages");
+    Ok(storages)
+}
@@ -0,0 +1,75 @@
+//! 放送中番組ワーカーコマンド
+//!
+//! このモジュールはサービスごとの放送中番組を KV に投影するコマンドを提供します。
+
+use anyhow::Result;
+use domain::{
+    events::mirakc_events::OnairProgramChangedEvent, ports::event_source::EventSource,
+    usecases::now_playing_usecase::NowPlayingUseCase,
+};
+use futures::StreamExt;
+use std::sync::Arc;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+/// 放送中番組ワーカーを実行 (手動ループ)
+///
+/// 起動時に `mirakc_url` のすべてのサービスの放送中番組を取得した後、
+/// 放送中番組変更イベントを購読して反映する。
+pub async fn run_now_playing(
+    mirakc_url: String,
+    usecase: Arc<NowPlayingUseCase>,
+    source: Arc<dyn EventSource<OnairProgramChangedEvent>>,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!(mirakc_url = %mirakc_url, "Starting now playing worker...");
+
+    let mut event_stream = source.subscribe().await?;
+
+    match usecase.sync_all(&mirakc_url).await {
+        Ok(changed) => info!(changed, "Synced on-air programs"),
+        Err(e) => error!(
+            "Failed to sync on-air programs from {}: {:?}",
+            mirakc_url, e
+        ),
+    }
+
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping now playing worker.");
+                break;
+            }
+            maybe_event = event_stream.next() => {
+                match maybe_event {
+                    Some(Ok(event)) => {
+                        info!(service_id = event.service_id, "Received OnairProgramChangedEvent");
+                        if let Err(e) = usecase.handle_onair_program_changed(&event).await {
+                            error!("Error updating on-air program: {:?}. Continuing...", e);
+                        }
+                    }
+                    Some(Err(e)) => {
+                        error!("Error receiving on-air program event: {}. Continuing...", e);
+                    }
+                    None => {
+                        error!("On-air program event stream ended unexpectedly. Attempting to reconnect...");
+                        match source.subscribe().await {
+                            Ok(new_stream) => {
+                                info!("Successfully reconnected to on-air program event stream");
+                                event_stream = new_stream;
+                            }
+                            Err(e) => {
+                                error!("Failed to reconnect to on-air program event stream: {:?}. Exiting.", e);
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    info!("Now playing worker stopped gracefully.");
+    Ok(())
+}
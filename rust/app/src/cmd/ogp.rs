@@ -0,0 +1,60 @@
+//! OGP 画像ワーカーコマンド
+//!
+//! このモジュールは番組の詳細情報で見つかった URL の OGP 画像を取得し、Object Store に保存するコマンドを提供します。
+
+use anyhow::Result;
+use chrono::Utc;
+use domain::{
+    events::kurec_events::ProgramUrlDiscoveredEvent,
+    ports::event_source::EventSource,
+    usecases::ogp_usecase::{OgpOutcome, OgpUseCase},
+};
+use futures::StreamExt;
+use std::sync::Arc;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+
+/// OGP 画像ワーカーを実行 (手動ループ)
+///
+/// URL 発見イベントごとに、キャッシュが期限切れであれば OGP 画像を取得して保存する。
+pub async fn run_ogp_worker(
+    usecase: Arc<OgpUseCase>,
+    source: Arc<dyn EventSource<ProgramUrlDiscoveredEvent>>,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!("Starting OGP worker...");
+
+    let mut event_stream = source.subscribe().await?;
+
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping OGP worker.");
+                break;
+            }
+            maybe_event = event_stream.next() => {
+                match maybe_event {
+                    Some(Ok(event)) => {
+                        debug!(url = %event.url, "Received ProgramUrlDiscoveredEvent");
+                        match usecase.handle_url_discovered(&event, Utc::now()).await {
+                            Ok(OgpOutcome::Cached) => {}
+                            Ok(outcome) => info!(url = %event.url, ?outcome, "OGP image processed"),
+                            Err(e) => error!("Error processing OGP image: {:?}. Continuing...", e),
+                        }
+                    }
+                    Some(Err(e)) => {
+                        error!("Error receiving URL discovered event: {}. Continuing...", e);
+                    }
+                    None => match super::resubscribe(&source, "URL discovered event").await {
+                        Some(stream) => event_stream = stream,
+                        None => break,
+                    },
+                }
+            }
+        }
+    }
+
+    info!("OGP worker stopped gracefully.");
+    Ok(())
+}
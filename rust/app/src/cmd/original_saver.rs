@@ -0,0 +1,71 @@
+//! 元ファイル保存ワーカーコマンド
+//!
+//! このモジュールはジョブエンジンが送った元ファイル保存ジョブを実行するコマンドを提供します。
+
+use anyhow::Result;
+use domain::{
+    events::kurec_events::JobDispatchedEvent, models::job::JobResult,
+    ports::event_source::EventSource, usecases::original_saver_usecase::OriginalSaverUseCase,
+};
+use futures::StreamExt;
+use std::sync::Arc;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// 元ファイル保存ワーカーを実行 (手動ループ)
+///
+/// mirakc とストレージの帯域を使い切らないよう、ジョブは1件ずつ順に実行する。
+/// コピー中にシャットダウンした場合は、コピーを中断する (書きかけのオブジェクトは残らない)。
+pub async fn run_original_saver(
+    usecase: Arc<OriginalSaverUseCase>,
+    source: Arc<dyn EventSource<JobDispatchedEvent>>,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!("Starting original saver worker...");
+
+    let mut event_stream = source.subscribe().await?;
+
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping original saver worker.");
+                break;
+            }
+            maybe_event = event_stream.next() => {
+                match maybe_event {
+                    Some(Ok(event)) => {
+                        let result = select! {
+                            _ = shutdown.cancelled() => None,
+                            result = usecase.handle_job_dispatched(&event) => Some(result),
+                        };
+                        let Some(result) = result else {
+                            warn!(record_id = %event.record_id, job = %event.job.name, "Shutdown signal received during copying, aborting job.");
+                            break;
+                        };
+                        match result {
+                            Ok(None) => {}
+                            Ok(Some(JobResult::Succeeded { .. })) => {
+                                info!(record_id = %event.record_id, job = %event.job.name, "Save original job succeeded")
+                            }
+                            Ok(Some(JobResult::Failed { error })) => {
+                                warn!(record_id = %event.record_id, job = %event.job.name, error = %error, "Save original job failed")
+                            }
+                            Err(e) => error!("Error processing save original job: {:?}. Continuing...", e),
+                        }
+                    }
+                    Some(Err(e)) => {
+                        error!("Error receiving job dispatched event: {}. Continuing...", e);
+                    }
+                    None => match super::resubscribe(&source, "Job dispatched event").await {
+                        Some(stream) => event_stream = stream,
+                        None => break,
+                    },
+                }
+            }
+        }
+    }
+
+    info!("Original saver worker stopped gracefully.");
+    Ok(())
+}
@@ -0,0 +1,23 @@
+//! 問い合わせサーバーワーカー
+//!
+//! このモジュールは Web UI などからの NATS による問い合わせ (リクエスト・リプライ) に応答するワーカーを提供します。
+
+use anyhow::Result;
+use domain::usecases::rule_preview_usecase::RulePreviewQuery;
+use infra_jetstream::NatsQueryResponder;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+/// シャットダウンされるまで問い合わせに応答する
+pub async fn run_query_server(
+    rule_preview: NatsQueryResponder<RulePreviewQuery>,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    tokio::select! {
+        result = rule_preview.serve() => result,
+        _ = shutdown.cancelled() => {
+            info!("問い合わせサーバーを停止します");
+            Ok(())
+        }
+    }
+}
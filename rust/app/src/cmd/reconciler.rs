@@ -0,0 +1,114 @@
+//! 録画予約調整ワーカーコマンド
+//!
+//! このモジュールは録画されるべき番組と mirakc の録画予約を定期的に比較し、差分を解消するコマンドを提供します。
+//! EPG の保存やルールの変更の後は、ルールエンジンが録画予約を更新し終えるのを少し待ってから調整します。
+
+use anyhow::Result;
+use chrono::Utc;
+use domain::{
+    events::kurec_events::{EpgStoredEvent, RecordingRulesChangedEvent},
+    ports::event_source::EventSource,
+    usecases::schedule_reconciler_usecase::ScheduleReconcilerUseCase,
+};
+use futures::StreamExt;
+use std::{sync::Arc, time::Duration};
+use tokio::{select, time::Instant};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+
+/// イベントを受け取ってから調整するまでの待ち時間
+const EVENT_DEBOUNCE: Duration = Duration::from_secs(10);
+
+async fn reconcile(usecase: &ScheduleReconcilerUseCase) {
+    match usecase.reconcile_all(Utc::now()).await {
+        Ok(Some(reports)) => {
+            for report in reports {
+                info!(
+                    mirakc_url = %report.mirakc_url,
+                    created = report.created.len(),
+                    recreated = report.recreated.len(),
+                    deleted = report.deleted.len(),
+                    failed = report.failed.len(),
+                    unchanged = report.unchanged,
+                    foreign = report.foreign,
+                    "Recording schedules reconciled"
+                );
+            }
+        }
+        Ok(None) => debug!("Not the leader. Skipping reconciliation."),
+        Err(e) => error!(
+            "Error reconciling recording schedules: {:?}. Continuing...",
+            e
+        ),
+    }
+}
+
+/// 録画予約調整ワーカーを実行 (手動ループ)
+///
+/// 起動直後に1回、その後は `interval` ごとと、EPG 保存イベント・ルール変更イベントの後に調整する。
+pub async fn run_reconciler(
+    usecase: Arc<ScheduleReconcilerUseCase>,
+    epg_source: Arc<dyn EventSource<EpgStoredEvent>>,
+    rules_source: Arc<dyn EventSource<RecordingRulesChangedEvent>>,
+    interval: Duration,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!(interval = ?interval, "Starting schedule reconciler worker...");
+
+    let mut epg_stream = epg_source.subscribe().await?;
+    let mut rules_stream = rules_source.subscribe().await?;
+    let mut ticker = tokio::time::interval(interval);
+    // イベントによる調整の予定時刻
+    let mut pending: Option<Instant> = None;
+
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping schedule reconciler worker.");
+                break;
+            }
+            _ = ticker.tick() => {
+                pending = None;
+                reconcile(&usecase).await;
+            }
+            _ = tokio::time::sleep_until(pending.unwrap_or_else(Instant::now)), if pending.is_some() => {
+                pending = None;
+                reconcile(&usecase).await;
+            }
+            maybe_event = epg_stream.next() => {
+                match maybe_event {
+                    Some(Ok(_)) => {
+                        pending.get_or_insert_with(|| Instant::now() + EVENT_DEBOUNCE);
+                    }
+                    Some(Err(e)) => {
+                        error!("Error receiving EPG stored event: {}. Continuing...", e);
+                    }
+                    None => match super::resubscribe(&epg_source, "EPG stored event").await {
+                        Some(stream) => epg_stream = stream,
+                        None => break,
+                    },
+                }
+            }
+            maybe_event = rules_stream.next() => {
+                match maybe_event {
+                    Some(Ok(_)) => {
+                        pending.get_or_insert_with(|| Instant::now() + EVENT_DEBOUNCE);
+                    }
+                    Some(Err(e)) => {
+                        error!("Error receiving rules changed event: {}. Continuing...", e);
+                    }
+                    None => match super::resubscribe(&rules_source, "Rules changed event").await {
+                        Some(stream) => rules_stream = stream,
+                        None => break,
+                    },
+                }
+            }
+        }
+    }
+
+    if let Err(e) = usecase.release().await {
+        error!("Failed to release reconciler lease: {:?}", e);
+    }
+    info!("Schedule reconciler worker stopped gracefully.");
+    Ok(())
+}
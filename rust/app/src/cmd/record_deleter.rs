@@ -0,0 +1,56 @@
+//! mirakc の録画の削除ワーカーコマンド
+//!
+//! このモジュールはすべてのジョブが成功した録画を、保存したコピーを検証してから mirakc から削除するコマンドを提供します。
+
+use anyhow::Result;
+use chrono::Utc;
+use domain::usecases::record_deleter_usecase::RecordDeleterUseCase;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+
+async fn delete_due_records(usecase: &RecordDeleterUseCase) {
+    match usecase.delete_due_records(Utc::now()).await {
+        Ok(report) if report.deleted.is_empty() && report.failed.is_empty() => {
+            debug!(waiting = report.waiting, "No records to delete")
+        }
+        Ok(report) => info!(
+            deleted = ?report.deleted,
+            failed = ?report.failed,
+            waiting = report.waiting,
+            "Records deleted"
+        ),
+        Err(e) => error!("Error deleting records: {:?}. Continuing...", e),
+    }
+}
+
+/// mirakc の録画の削除ワーカーを実行 (手動ループ)
+///
+/// 削除ジョブは猶予期間が過ぎてから実行するため、イベントではなく `interval` ごとにジョブの実行状況を確認する。
+/// 同じ録画を同時に削除しないよう、このワーカーは1つだけ実行すること。
+pub async fn run_record_deleter(
+    usecase: Arc<RecordDeleterUseCase>,
+    interval: Duration,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!(interval = ?interval, "Starting record deleter worker...");
+
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping record deleter worker.");
+                break;
+            }
+            _ = ticker.tick() => {
+                delete_due_records(&usecase).await;
+            }
+        }
+    }
+
+    info!("Record deleter worker stopped gracefully.");
+    Ok(())
+}
@@ -0,0 +1,74 @@
+//! 録画ライブラリの検索インデックスワーカーコマンド
+//!
+//! このモジュールは KV に保存された録画レコードを定期的に検索インデックスに反映し、
+//! 録画ライブラリの検索の問い合わせに応答するコマンドを提供します。
+
+use anyhow::Result;
+use domain::{
+    models::search::RecordSearchQuery,
+    usecases::{program_index_usecase::IndexSyncSummary, record_index_usecase::RecordIndexUseCase},
+};
+use infra_jetstream::NatsQueryResponder;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+
+fn log_sync(result: Result<IndexSyncSummary>) {
+    match result {
+        Ok(summary) if summary == IndexSyncSummary::default() => {
+            debug!("Record search index is up to date")
+        }
+        Ok(summary) => info!(
+            upserted = summary.upserted,
+            deleted = summary.deleted,
+            "Record search index updated"
+        ),
+        Err(e) => error!("Error updating record search index: {:?}. Continuing...", e),
+    }
+}
+
+/// 録画ライブラリの検索インデックスワーカーを実行 (手動ループ)
+///
+/// 録画レコードは録画ライブラリワーカーが KV に反映するため、イベントではなく `interval` ごとに
+/// KV の録画レコードとインデックスを比較して反映する。
+/// `rebuild` の場合は起動時にインデックスを作り直す。
+pub async fn run_record_indexer(
+    usecase: Arc<RecordIndexUseCase>,
+    search: NatsQueryResponder<RecordSearchQuery>,
+    interval: Duration,
+    rebuild: bool,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!("Starting record indexer worker...");
+
+    usecase.initialize().await?;
+    if rebuild {
+        log_sync(usecase.rebuild().await);
+    }
+
+    let serve = search.serve();
+    tokio::pin!(serve);
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping record indexer worker.");
+                break;
+            }
+            result = &mut serve => {
+                result?;
+                info!("Record search query subscription ended, stopping record indexer worker.");
+                break;
+            }
+            _ = ticker.tick() => {
+                log_sync(usecase.sync_all().await);
+            }
+        }
+    }
+
+    info!("Record indexer worker stopped gracefully.");
+    Ok(())
+}
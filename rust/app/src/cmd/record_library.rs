@@ -0,0 +1,125 @@
+//! 録画ライブラリワーカーコマンド
+//!
+//! このモジュールは mirakc の録画レコードを KV に投影し続けるコマンドを提供します。
+
+use anyhow::Result;
+use domain::{
+    events::mirakc_events::{
+        RecordingContentRemovedEvent, RecordingRecordRemovedEvent, RecordingRecordSavedEvent,
+    },
+    ports::event_source::EventSource,
+    usecases::record_library_usecase::RecordLibraryUseCase,
+};
+use futures::stream::{self, BoxStream, StreamExt};
+use std::sync::Arc;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+/// 録画ライブラリワーカーが購読するイベントソース
+pub struct RecordLibrarySources {
+    pub record_saved: Arc<dyn EventSource<RecordingRecordSavedEvent>>,
+    pub record_removed: Arc<dyn EventSource<RecordingRecordRemovedEvent>>,
+    pub content_removed: Arc<dyn EventSource<RecordingContentRemovedEvent>>,
+}
+
+/// 購読したイベントをひとつのストリームで扱うための列挙型
+enum RecordEvent {
+    Saved(RecordingRecordSavedEvent),
+    Removed(RecordingRecordRemovedEvent),
+    ContentRemoved(RecordingContentRemovedEvent),
+}
+
+impl RecordLibrarySources {
+    /// すべてのイベントソースを購読し、ひとつのストリームにまとめる
+    async fn subscribe(&self) -> Result<BoxStream<'static, Result<RecordEvent>>> {
+        let saved = self
+            .record_saved
+            .subscribe()
+            .await?
+            .map(|r| r.map(RecordEvent::Saved));
+        let removed = self
+            .record_removed
+            .subscribe()
+            .await?
+            .map(|r| r.map(RecordEvent::Removed));
+        let content_removed = self
+            .content_removed
+            .subscribe()
+            .await?
+            .map(|r| r.map(RecordEvent::ContentRemoved));
+        Ok(stream::select(stream::select(saved, removed), content_removed).boxed())
+    }
+}
+
+/// 録画ライブラリワーカーを実行 (手動ループ)
+///
+/// 起動時に `mirakc_url` の録画レコードを全件同期した後、
+/// 録画レコードのイベントを購読して差分を反映する。
+pub async fn run_record_library(
+    mirakc_url: String,
+    usecase: Arc<RecordLibraryUseCase>,
+    sources: RecordLibrarySources,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!(mirakc_url = %mirakc_url, "Starting record library worker...");
+
+    // 先に購読を開始し、同期中に発生したイベントを取りこぼさないようにする
+    let mut event_stream = sources.subscribe().await?;
+
+    if let Err(e) = usecase.sync_all(&mirakc_url).await {
+        // 同期に失敗してもイベントによる差分反映は継続する
+        error!("Failed to sync records from {}: {:?}", mirakc_url, e);
+    }
+
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping record library worker.");
+                break;
+            }
+            maybe_event = event_stream.next() => {
+                match maybe_event {
+                    Some(Ok(event)) => {
+                        let result = match &event {
+                            RecordEvent::Saved(e) => {
+                                info!(record_id = %e.record_id, "Received RecordingRecordSavedEvent");
+                                usecase.handle_record_saved(e).await
+                            }
+                            RecordEvent::Removed(e) => {
+                                info!(record_id = %e.record_id, "Received RecordingRecordRemovedEvent");
+                                usecase.handle_record_removed(e).await
+                            }
+                            RecordEvent::ContentRemoved(e) => {
+                                info!(record_id = %e.record_id, "Received RecordingContentRemovedEvent");
+                                usecase.handle_content_removed(e).await
+                            }
+                        };
+                        if let Err(e) = result {
+                            error!("Error updating record library: {:?}. Continuing...", e);
+                        }
+                    }
+                    Some(Err(e)) => {
+                        error!("Error receiving record event: {}. Continuing...", e);
+                    }
+                    None => {
+                        error!("Record event stream ended unexpectedly. Attempting to reconnect...");
+                        match sources.subscribe().await {
+                            Ok(new_stream) => {
+                                info!("Successfully reconnected to record event streams");
+                                event_stream = new_stream;
+                            }
+                            Err(e) => {
+                                error!("Failed to reconnect to record event streams: {:?}. Exiting.", e);
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    info!("Record library worker stopped gracefully.");
+    Ok(())
+}
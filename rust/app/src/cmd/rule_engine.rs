@@ -0,0 +1,150 @@
+//! 自動録画ルールエンジンワーカーコマンド
+//!
+//! このモジュールは EPG の保存やルールの変更をきっかけに番組をルールとシリーズ録画で評価し、
+//! 録画されるべき番組を更新するコマンドを提供します。
+//! 両方が同じ録画予約を更新するため、1つのワーカーで順番に処理します。
+//! 録画予約を更新するたびに、チューナーが足りない録画予約がないかを分析します。
+
+use anyhow::Result;
+use chrono::Utc;
+use domain::{
+    events::{
+        kurec_events::{EpgStoredEvent, RecordingRulesChangedEvent},
+        mirakc_events::RecordingRecordSavedEvent,
+    },
+    ports::event_source::EventSource,
+    usecases::{
+        rule_engine_usecase::{RuleApplySummary, RuleEngineUseCase},
+        schedule_conflict_usecase::ScheduleConflictUseCase,
+        series_tracking_usecase::{SeriesApplySummary, SeriesTrackingUseCase},
+    },
+};
+use futures::StreamExt;
+use std::sync::Arc;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+fn log_rules(result: Result<RuleApplySummary>) {
+    match result {
+        Ok(summary) => info!(
+            matched = summary.matched,
+            added = summary.added,
+            updated = summary.updated,
+            removed = summary.removed,
+            "Recording rules applied"
+        ),
+        Err(e) => error!("Error applying recording rules: {:?}. Continuing...", e),
+    }
+}
+
+fn log_series(result: Result<SeriesApplySummary>) {
+    match result {
+        Ok(summary) => info!(
+            scheduled = summary.scheduled,
+            unscheduled = summary.unscheduled,
+            "Series subscriptions applied"
+        ),
+        Err(e) => error!(
+            "Error applying series subscriptions: {:?}. Continuing...",
+            e
+        ),
+    }
+}
+
+async fn detect_conflicts(conflicts: &ScheduleConflictUseCase) {
+    match conflicts.detect(Utc::now()).await {
+        Ok(analysis) => info!(
+            assigned = analysis.assignments.len(),
+            conflicts = analysis.conflicts.len(),
+            "Schedule conflicts analyzed"
+        ),
+        Err(e) => error!("Error analyzing schedule conflicts: {:?}. Continuing...", e),
+    }
+}
+
+/// ルールエンジンワーカーを実行 (手動ループ)
+///
+/// 起動時に保存済みのすべての番組を評価した後、EPG 保存イベントでそのサービスの番組を、
+/// ルール変更イベントですべての番組を再評価する。録画レコード保存イベントでシリーズの録画済みの話数を更新する。
+pub async fn run_rule_engine(
+    rules: Arc<RuleEngineUseCase>,
+    series: Arc<SeriesTrackingUseCase>,
+    conflicts: Arc<ScheduleConflictUseCase>,
+    epg_source: Arc<dyn EventSource<EpgStoredEvent>>,
+    rules_source: Arc<dyn EventSource<RecordingRulesChangedEvent>>,
+    record_source: Arc<dyn EventSource<RecordingRecordSavedEvent>>,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!("Starting rule engine worker...");
+
+    let mut epg_stream = epg_source.subscribe().await?;
+    let mut rules_stream = rules_source.subscribe().await?;
+    let mut record_stream = record_source.subscribe().await?;
+
+    log_rules(rules.apply_all(Utc::now()).await);
+    log_series(series.apply_all(Utc::now()).await);
+    detect_conflicts(&conflicts).await;
+
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping rule engine worker.");
+                break;
+            }
+            maybe_event = epg_stream.next() => {
+                match maybe_event {
+                    Some(Ok(event)) => {
+                        info!(service_id = event.service_id, "Received EpgStoredEvent");
+                        log_rules(rules.handle_epg_stored(&event, Utc::now()).await);
+                        log_series(series.handle_epg_stored(&event, Utc::now()).await);
+                        detect_conflicts(&conflicts).await;
+                    }
+                    Some(Err(e)) => {
+                        error!("Error receiving EPG stored event: {}. Continuing...", e);
+                    }
+                    None => match super::resubscribe(&epg_source, "EPG stored event").await {
+                        Some(stream) => epg_stream = stream,
+                        None => break,
+                    },
+                }
+            }
+            maybe_event = rules_stream.next() => {
+                match maybe_event {
+                    Some(Ok(event)) => {
+                        info!(rule_ids = ?event.rule_ids, "Received RecordingRulesChangedEvent");
+                        log_rules(rules.apply_all(Utc::now()).await);
+                        detect_conflicts(&conflicts).await;
+                    }
+                    Some(Err(e)) => {
+                        error!("Error receiving rules changed event: {}. Continuing...", e);
+                    }
+                    None => match super::resubscribe(&rules_source, "Rules changed event").await {
+                        Some(stream) => rules_stream = stream,
+                        None => break,
+                    },
+                }
+            }
+            maybe_event = record_stream.next() => {
+                match maybe_event {
+                    Some(Ok(event)) => {
+                        match series.handle_record_saved(&event).await {
+                            Ok(()) => detect_conflicts(&conflicts).await,
+                            Err(e) => error!("Error updating series subscription: {:?}. Continuing...", e),
+                        }
+                    }
+                    Some(Err(e)) => {
+                        error!("Error receiving record saved event: {}. Continuing...", e);
+                    }
+                    None => match super::resubscribe(&record_source, "Record saved event").await {
+                        Some(stream) => record_stream = stream,
+                        None => break,
+                    },
+                }
+            }
+        }
+    }
+
+    info!("Rule engine worker stopped gracefully.");
+    Ok(())
+}
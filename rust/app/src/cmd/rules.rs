@@ -0,0 +1,136 @@
+//! 自動録画ルール管理コマンド
+//!
+//! このモジュールはルールファイルの登録、ルールの一覧表示・削除・プレビューを行うコマンドを提供します。
+//! ルールを変更した場合は `RecordingRulesChangedEvent` を発行し、ルールエンジンに再評価させます。
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use domain::{
+    events::kurec_events::RecordingRulesChangedEvent,
+    models::{epg::KurecProgram, rule::parse_rules},
+    ports::{event_sink::EventSink, repositories::RecordingRuleRepository},
+    usecases::rule_preview_usecase::{RulePreview, RulePreviewUseCase},
+};
+use std::path::Path;
+
+/// ルールファイルを読み込んで保存する
+pub async fn apply_rules(
+    repository: &dyn RecordingRuleRepository,
+    sink: &dyn EventSink<RecordingRulesChangedEvent>,
+    file: &Path,
+) -> Result<()> {
+    let json = tokio::fs::read_to_string(file)
+        .await
+        .with_context(|| format!("Failed to read rule file: {}", file.display()))?;
+    let rules = parse_rules(&json)?;
+    for rule in &rules {
+        repository.save_rule(rule).await?;
+        println!("Saved rule: {} ({})", rule.id, rule.name);
+    }
+    sink.publish(RecordingRulesChangedEvent {
+        rule_ids: rules.into_iter().map(|r| r.id).collect(),
+    })
+    .await
+}
+
+/// ルールの一覧を表示する
+pub async fn list_rules(repository: &dyn RecordingRuleRepository) -> Result<()> {
+    let mut rules = repository.list_rules().await?;
+    rules.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.id.cmp(&b.id)));
+    for rule in rules {
+        println!(
+            "{}\t{}\tpriority={}\t{}",
+            rule.id,
+            if rule.enabled { "enabled" } else { "disabled" },
+            rule.priority,
+            rule.name
+        );
+    }
+    Ok(())
+}
+
+/// ルールを削除する
+pub async fn delete_rule(
+    repository: &dyn RecordingRuleRepository,
+    sink: &dyn EventSink<RecordingRulesChangedEvent>,
+    rule_id: &str,
+) -> Result<()> {
+    if repository.get_rule(rule_id).await?.is_none() {
+        anyhow::bail!("ルールが見つかりません: {}", rule_id);
+    }
+    repository.delete_rule(rule_id).await?;
+    println!("Deleted rule: {}", rule_id);
+    sink.publish(RecordingRulesChangedEvent {
+        rule_ids: vec![rule_id.to_string()],
+    })
+    .await
+}
+
+/// ルールをプレビューする。`target` がファイルとして存在する場合はルールファイル、それ以外はルールIDとして扱う。
+pub async fn preview_rules(usecase: &RulePreviewUseCase, target: &str) -> Result<()> {
+    let now = Utc::now();
+    let path = Path::new(target);
+    if !path.is_file() {
+        print_preview(&usecase.preview_rule(target, now).await?);
+        return Ok(());
+    }
+    let json = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read rule file: {}", path.display()))?;
+    for rule in parse_rules(&json)? {
+        print_preview(&usecase.preview(rule, now).await?);
+    }
+    Ok(())
+}
+
+fn format_program(program: &KurecProgram) -> String {
+    format!(
+        "{}\t{}\t{}\t{}",
+        program.start_at.format("%Y-%m-%d %H:%M"),
+        program.channel_name,
+        program.name.as_deref().unwrap_or("-"),
+        program.mirakc_url
+    )
+}
+
+fn print_preview(preview: &RulePreview) {
+    println!(
+        "Rule: {} ({})\tmatched: {}, excluded: {}, new conflicts: {}",
+        preview.rule.id,
+        preview.rule.name,
+        preview.matched.len(),
+        preview.excluded.len(),
+        preview.conflicts.len()
+    );
+    for m in &preview.matched {
+        println!("  record\t{}", format_program(&m.program));
+        for reason in &m.reasons {
+            println!("\t{}", reason);
+        }
+    }
+    for e in &preview.excluded {
+        println!("  exclude\t{}", format_program(&e.program));
+        println!("\t{}", e.reason);
+    }
+    for conflict in &preview.conflicts {
+        let schedule = &conflict.schedule;
+        println!(
+            "  conflict\t{}\tpriority={}\t{}\t{}",
+            schedule.start_at.format("%Y-%m-%d %H:%M"),
+            schedule.priority(),
+            schedule.name.as_deref().unwrap_or("-"),
+            schedule.mirakc_url
+        );
+        for blocker in &conflict.blocked_by {
+            println!(
+                "\tblocked by: {}\tpriority={}\t{}",
+                blocker.start_at.format("%Y-%m-%d %H:%M"),
+                blocker.priority,
+                blocker.name.as_deref().unwrap_or("-")
+            );
+        }
+        if let Some(url) = &conflict.suggested_mirakc_url {
+            println!("\tsuggested mirakc: {}", url);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! 番組検索インデックスワーカーコマンド
+//!
+//! このモジュールは EPG の保存をきっかけに番組を検索インデックス (Meilisearch または組み込みのインデックス) に反映し、
+//! 番組検索の問い合わせに応答するコマンドを提供します。
+
+use anyhow::Result;
+use chrono::Utc;
+use domain::{
+    events::kurec_events::EpgStoredEvent,
+    models::search::ProgramSearchQuery,
+    ports::event_source::EventSource,
+    usecases::program_index_usecase::{IndexSyncSummary, ProgramIndexUseCase},
+};
+use futures::StreamExt;
+use infra_jetstream::NatsQueryResponder;
+use std::sync::Arc;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+fn log_sync(result: Result<IndexSyncSummary>) {
+    match result {
+        Ok(summary) => info!(
+            upserted = summary.upserted,
+            deleted = summary.deleted,
+            "Program search index updated"
+        ),
+        Err(e) => error!(
+            "Error updating program search index: {:?}. Continuing...",
+            e
+        ),
+    }
+}
+
+/// 番組検索インデックスワーカーを実行 (手動ループ)
+///
+/// 起動時にインデックスの設定を反映して保存済みのすべての番組を反映した後、
+/// EPG 保存イベントでそのサービスの番組を反映する。
+/// `rebuild` の場合は起動時にインデックスを作り直す。
+pub async fn run_search_indexer(
+    usecase: Arc<ProgramIndexUseCase>,
+    source: Arc<dyn EventSource<EpgStoredEvent>>,
+    search: NatsQueryResponder<ProgramSearchQuery>,
+    rebuild: bool,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!("Starting search indexer worker...");
+
+    usecase.initialize().await?;
+    let mut event_stream = source.subscribe().await?;
+
+    if rebuild {
+        log_sync(usecase.rebuild(Utc::now()).await);
+    } else {
+        log_sync(usecase.sync_all(Utc::now()).await);
+    }
+
+    let serve = search.serve();
+    tokio::pin!(serve);
+
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping search indexer worker.");
+                break;
+            }
+            result = &mut serve => {
+                result?;
+                info!("Program search query subscription ended, stopping search indexer worker.");
+                break;
+            }
+            maybe_event = event_stream.next() => {
+                match maybe_event {
+                    Some(Ok(event)) => {
+                        info!(service_id = event.service_id, "Received EpgStoredEvent");
+                        log_sync(usecase.handle_epg_stored(&event, Utc::now()).await);
+                    }
+                    Some(Err(e)) => {
+                        error!("Error receiving EPG stored event: {}. Continuing...", e);
+                    }
+                    None => match super::resubscribe(&source, "EPG stored event").await {
+                        Some(stream) => event_stream = stream,
+                        None => break,
+                    },
+                }
+            }
+        }
+    }
+
+    info!("Search indexer worker stopped gracefully.");
+    Ok(())
+}
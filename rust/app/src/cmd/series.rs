@@ -0,0 +1,59 @@
+//! シリーズ録画管理コマンド
+//!
+//! このモジュールは番組のシリーズの購読、購読の一覧表示・解除を行うコマンドを提供します。
+
+use anyhow::Result;
+use chrono::Utc;
+use domain::{
+    models::series::SeriesStatus, ports::repositories::SeriesSubscriptionRepository,
+    usecases::series_tracking_usecase::SeriesTrackingUseCase,
+};
+
+/// 番組のシリーズを購読する
+pub async fn subscribe(
+    usecase: &SeriesTrackingUseCase,
+    mirakc_url: &str,
+    program_id: i64,
+) -> Result<()> {
+    let (subscription, summary) = usecase
+        .subscribe(mirakc_url, program_id, Utc::now())
+        .await?;
+    println!(
+        "Subscribed series: {} ({}), recorded episodes: {:?}, scheduled: {}",
+        subscription.id, subscription.name, subscription.recorded_episodes, summary.scheduled
+    );
+    Ok(())
+}
+
+/// 購読しているシリーズの一覧を表示する
+pub async fn list_subscriptions(repository: &dyn SeriesSubscriptionRepository) -> Result<()> {
+    let mut subscriptions = repository.list_subscriptions().await?;
+    subscriptions.sort_by(|a, b| a.id.cmp(&b.id));
+    for subscription in subscriptions {
+        let last_episode = subscription
+            .last_episode
+            .map_or_else(|| "?".to_string(), |e| e.to_string());
+        println!(
+            "{}\t{}\trecorded={}/{}\t{}",
+            subscription.id,
+            match subscription.status {
+                SeriesStatus::Active => "active",
+                SeriesStatus::Completed => "completed",
+            },
+            subscription.recorded_episodes.len(),
+            last_episode,
+            subscription.name
+        );
+    }
+    Ok(())
+}
+
+/// シリーズの購読を解除する
+pub async fn unsubscribe(usecase: &SeriesTrackingUseCase, subscription_id: &str) -> Result<()> {
+    let summary = usecase.unsubscribe(subscription_id).await?;
+    println!(
+        "Unsubscribed series: {}, unscheduled: {}",
+        subscription_id, summary.unscheduled
+    );
+    Ok(())
+}
@@ -0,0 +1,47 @@
+//! サービスカタログ同期ワーカーコマンド
+//!
+//! このモジュールは mirakc のサービス・チャンネル一覧とロゴを定期的に同期し、
+//! チャンネルスキャンなどによるサービスの追加・削除・名前変更をイベントとして発行するコマンドを提供します。
+
+use anyhow::Result;
+use domain::usecases::service_catalog_usecase::ServiceCatalogUseCase;
+use std::{sync::Arc, time::Duration};
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+/// サービスカタログ同期ワーカーを実行 (手動ループ)
+///
+/// 起動直後に1回、その後は `interval` ごとに同期する。
+pub async fn run_service_catalog(
+    mirakc_url: String,
+    usecase: Arc<ServiceCatalogUseCase>,
+    interval: Duration,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!(%mirakc_url, interval = ?interval, "Starting service catalog worker...");
+
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping service catalog worker.");
+                break;
+            }
+            _ = ticker.tick() => {
+                match usecase.sync(&mirakc_url).await {
+                    Ok(diff) => info!(
+                        added = diff.added.len(),
+                        removed = diff.removed.len(),
+                        renamed = diff.renamed.len(),
+                        "Service catalog synced"
+                    ),
+                    Err(e) => error!("Error syncing service catalog: {:?}. Continuing...", e),
+                }
+            }
+        }
+    }
+
+    info!("Service catalog worker stopped gracefully.");
+    Ok(())
+}
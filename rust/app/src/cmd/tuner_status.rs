@@ -0,0 +1,106 @@
+//! チューナー状態ワーカーコマンド
+//!
+//! このモジュールはチューナー状態の投影と使用履歴の記録を行うコマンド、
+//! および使用履歴を問い合わせるコマンドを提供します。
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use domain::{
+    events::mirakc_events::TunerStatusChangedEvent, ports::event_source::EventSource,
+    usecases::tuner_status_usecase::TunerStatusUseCase,
+};
+use futures::StreamExt;
+use std::sync::Arc;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+/// チューナー状態ワーカーを実行 (手動ループ)
+///
+/// 起動時に `mirakc_url` のすべてのチューナー状態を取得した後、
+/// チューナー状態変更イベントを購読して反映する。
+pub async fn run_tuner_status(
+    mirakc_url: String,
+    usecase: Arc<TunerStatusUseCase>,
+    source: Arc<dyn EventSource<TunerStatusChangedEvent>>,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!(mirakc_url = %mirakc_url, "Starting tuner status worker...");
+
+    let mut event_stream = source.subscribe().await?;
+
+    match usecase.sync_all(&mirakc_url, Utc::now()).await {
+        Ok(count) => info!(count, "Synced tuner statuses"),
+        Err(e) => error!("Failed to sync tuners from {}: {:?}", mirakc_url, e),
+    }
+
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping tuner status worker.");
+                break;
+            }
+            maybe_event = event_stream.next() => {
+                match maybe_event {
+                    Some(Ok(event)) => {
+                        info!(tuner_index = event.tuner_index, "Received TunerStatusChangedEvent");
+                        if let Err(e) = usecase.handle_status_changed(&event).await {
+                            error!("Error updating tuner status: {:?}. Continuing...", e);
+                        }
+                    }
+                    Some(Err(e)) => {
+                        error!("Error receiving tuner status event: {}. Continuing...", e);
+                    }
+                    None => {
+                        error!("Tuner status event stream ended unexpectedly. Attempting to reconnect...");
+                        match source.subscribe().await {
+                            Ok(new_stream) => {
+                                info!("Successfully reconnected to tuner status event stream");
+                                event_stream = new_stream;
+                            }
+                            Err(e) => {
+                                error!("Failed to reconnect to tuner status event stream: {:?}. Exiting.", e);
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    info!("Tuner status worker stopped gracefully.");
+    Ok(())
+}
+
+/// 指定時刻に使用中だったチューナーを表示する
+pub async fn print_tuner_usage(
+    usecase: &TunerStatusUseCase,
+    at: DateTime<Utc>,
+    channel_type: Option<&str>,
+) -> Result<()> {
+    let busy = usecase.busy_tuners_at(at, channel_type).await?;
+    println!(
+        "{} 時点で使用中のチューナー ({}): {} 台",
+        at.with_timezone(&chrono::Local),
+        channel_type.unwrap_or("全タイプ"),
+        busy.len()
+    );
+    for interval in busy {
+        let users: Vec<&str> = interval.users.iter().map(|u| u.id.as_str()).collect();
+        println!(
+            "  {} #{} {} [{}] {} - {} ({})",
+            interval.mirakc_url,
+            interval.tuner_index,
+            interval.tuner_name,
+            interval.types.join(","),
+            interval.started_at.with_timezone(&chrono::Local),
+            interval
+                .ended_at
+                .map(|t| t.with_timezone(&chrono::Local).to_string())
+                .unwrap_or_else(|| "使用中".to_string()),
+            users.join(", ")
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,43 @@
+//! mirakcバージョン監視ワーカーコマンド
+//!
+//! このモジュールは mirakc のバージョンを定期的に確認し、
+//! 変更を検出した場合にイベントを発行するコマンドを提供します。
+
+use anyhow::Result;
+use domain::{
+    ports::repositories::version_repository::VersionRepository,
+    usecases::version_watch_usecase::VersionWatchUseCase,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+/// mirakcバージョン監視ワーカーを実行 (手動ループ)
+///
+/// 起動直後に1回、その後は `interval` ごとにバージョンを確認する。
+pub async fn run_version_watch<R: VersionRepository>(
+    usecase: Arc<VersionWatchUseCase<R>>,
+    interval: Duration,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    info!(interval = ?interval, "Starting mirakc version watch worker...");
+
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutdown signal received, stopping mirakc version watch worker.");
+                break;
+            }
+            _ = ticker.tick() => {
+                if let Err(e) = usecase.check().await {
+                    error!("Error checking mirakc version: {:?}. Continuing...", e);
+                }
+            }
+        }
+    }
+
+    info!("mirakc version watch worker stopped gracefully.");
+    Ok(())
+}
@@ -0,0 +1,76 @@
+//! XMLTVエクスポートコマンド
+//!
+//! このモジュールは保存済みの番組情報を XMLTV として出力するコマンドと、
+//! Jellyfin / Plex / Kodi などのガイド取り込み用に XMLTV を配信する HTTP サーバーを提供します。
+
+use anyhow::{Context, Result};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use chrono::Utc;
+use domain::usecases::xmltv_export_usecase::XmltvExportUseCase;
+use std::{net::SocketAddr, path::Path, sync::Arc};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+/// XMLTV をファイルまたは標準出力に書き出す
+pub async fn export_xmltv(usecase: &XmltvExportUseCase, output: Option<&Path>) -> Result<()> {
+    let xml = usecase.export(Utc::now()).await?;
+    match output {
+        Some(path) => {
+            tokio::fs::write(path, xml)
+                .await
+                .with_context(|| format!("Failed to write XMLTV: {}", path.display()))?;
+            info!(path = %path.display(), "XMLTV を出力しました");
+        }
+        None => print!("{}", xml),
+    }
+    Ok(())
+}
+
+/// XMLTV 配信サーバーを実行
+///
+/// `GET /xmltv` と `GET /xmltv.xml` で、リクエスト時点の XMLTV を返す。
+pub async fn run_xmltv_server(
+    usecase: Arc<XmltvExportUseCase>,
+    listen: SocketAddr,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    let app = router(usecase);
+    let listener = tokio::net::TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to bind XMLTV server: {}", listen))?;
+    info!(%listen, "Starting XMLTV server...");
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { shutdown.cancelled().await })
+        .await?;
+
+    info!("XMLTV server stopped gracefully.");
+    Ok(())
+}
+
+fn router(usecase: Arc<XmltvExportUseCase>) -> Router {
+    Router::new()
+        .route("/xmltv", get(get_xmltv))
+        .route("/xmltv.xml", get(get_xmltv))
+        .with_state(usecase)
+}
+
+async fn get_xmltv(State(usecase): State<Arc<XmltvExportUseCase>>) -> Response {
+    match usecase.export(Utc::now()).await {
+        Ok(xml) => (
+            [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+            xml,
+        )
+            .into_response(),
+        Err(e) => {
+            error!("Error exporting XMLTV: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
@@ -1,13 +1,70 @@
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use domain::events::MirakcEventInput; // MirakcEventInput をインポート
 use domain::{
-    events::{kurec_events::EpgStoredEvent, mirakc_events::EpgProgramsUpdatedEvent},
+    events::{
+        kurec_events::{
+            EncodeFailedEvent, EpgStoredEvent, JobCompletedEvent, JobDispatchedEvent,
+            MirakcVersionChangedEvent, NowPlayingChangedEvent, ProgramUrlDiscoveredEvent,
+            RecordCopyProgressEvent, RecordingRuleMatchedEvent, RecordingRulesChangedEvent,
+            ScheduleConflictDetectedEvent, ScheduleDriftDetectedEvent, SeriesCompletedEvent,
+            ServiceAddedEvent, ServiceRemovedEvent, ServiceRenamedEvent,
+        },
+        mirakc_events::{
+            EpgProgramsUpdatedEvent, OnairProgramChangedEvent, RecordingContentRemovedEvent,
+            RecordingRecordRemovedEvent, RecordingRecordSavedEvent, TunerStatusChangedEvent,
+        },
+    },
     handlers::mirakc_event_handler::MirakcEventSinks,
-    ports::{event_sink::EventSink, event_source::EventSource},
+    models::search::{ProgramSearchQuery, RecordSearchQuery},
+    models::{
+        ogp::OgpCachePolicy,
+        version::{VersionGatePolicy, SUPPORTED_MIRAKC_VERSION_REQ},
+        xmltv::XmltvOptions,
+    },
+    ports::{
+        event_sink::EventSink,
+        event_source::EventSource,
+        program_search_index::ProgramSearchIndex,
+        repositories::{DesiredScheduleRepository, KurecProgramRepository},
+    },
+    usecases::{
+        encoder_usecase::{EncoderSinks, EncoderUseCase},
+        job_engine_usecase::JobEngineUseCase,
+        now_playing_usecase::NowPlayingUseCase,
+        ogp_usecase::OgpUseCase,
+        original_saver_usecase::{OriginalSaverConfig, OriginalSaverSinks, OriginalSaverUseCase},
+        program_index_usecase::ProgramIndexUseCase,
+        record_deleter_usecase::{RecordDeleterConfig, RecordDeleterUseCase},
+        record_index_usecase::RecordIndexUseCase,
+        record_library_usecase::RecordLibraryUseCase,
+        rule_engine_usecase::RuleEngineUseCase,
+        rule_preview_usecase::{RulePreviewQuery, RulePreviewUseCase},
+        schedule_conflict_usecase::ScheduleConflictUseCase,
+        schedule_reconciler_usecase::ScheduleReconcilerUseCase,
+        series_tracking_usecase::SeriesTrackingUseCase,
+        service_catalog_usecase::{ServiceCatalogSinks, ServiceCatalogUseCase},
+        tuner_status_usecase::TunerStatusUseCase,
+        version_usecase::VersionUseCase,
+        version_watch_usecase::VersionWatchUseCase,
+        xmltv_export_usecase::XmltvExportUseCase,
+    },
 };
-use infra_jetstream::{self, JsPublisher, JsSubscriber}; // infra_jetstream とその要素をインポート
-use infra_mirakc::MirakcSseSource; // MirakcSseSource をインポート
+use infra_encoder::{EncoderConfig, ScriptEncodeExecutor};
+use infra_jetstream::{self, JsPublisher, JsSubscriber, NatsQueryResponder}; // infra_jetstream とその要素をインポート
+use infra_kvs::{
+    NatsKvDesiredScheduleRepository, NatsKvJobRunRepository, NatsKvLeaseRepository,
+    NatsKvNowPlayingRepository, NatsKvProgramRepository, NatsKvRecordDeletionRepository,
+    NatsKvRecordRepository, NatsKvRecordingRuleRepository, NatsKvSeriesSubscriptionRepository,
+    NatsKvServiceRepository, NatsKvTunerHistoryRepository, NatsKvTunerStatusRepository,
+    NatsObjectJobLogRepository, NatsObjectOgpImageRepository, NatsObjectServiceLogoRepository,
+};
+use infra_meilisearch::{MeilisearchConfig, MeilisearchProgramIndex};
+use infra_mirakc::repositories::domain_version_repository::DomainVersionRepositoryImpl;
+use infra_mirakc::{MirakcApiClientImpl, MirakcSseSource}; // MirakcSseSource をインポート
+use infra_nats::NatsClient;
+use infra_ogp::{HttpOgpFetcher, OgpConfig};
+use infra_tantivy::{TantivyConfig, TantivyProgramIndex};
 use std::{env, sync::Arc}; // Arc をインポート
 use tokio::signal;
 use tokio_util::sync::CancellationToken;
@@ -28,6 +85,29 @@ struct Cli {
     /// 起動するワーカーの種類
     #[command(subcommand)]
     worker: WorkerType,
+    /// mirakcのバージョンゲート設定
+    #[command(flatten)]
+    version_gate: VersionGateArgs,
+}
+
+/// 起動時に mirakc のバージョンを確認するための設定
+///
+/// mirakc の API を呼び出すワーカーは、起動時に接続先の mirakc を確認する。
+/// 接続先がイベントや録画レコードから決まるワーカー (encoder, original-saver,
+/// record-deleter, rule-engine) は `--mirakc-url` で確認する mirakc を指定する。
+///
+/// 次のコマンドは mirakc に接続しないため確認しない。
+/// - epg-updater, job-engine: JetStream のイベントだけを扱う
+/// - series, rules, conflicts, tuner-usage, export-xmltv など: KV に保存済みの情報だけを扱う
+///   (`series subscribe --mirakc-url` は番組を特定するためのキーとして使う)
+#[derive(Args, Debug)]
+struct VersionGateArgs {
+    /// サポートする mirakc のバージョン範囲 (semver の要件形式)
+    #[arg(long, global = true, default_value = SUPPORTED_MIRAKC_VERSION_REQ)]
+    mirakc_version_req: semver::VersionReq,
+    /// サポート範囲外の場合の動作 (strict: 起動を拒否, warn: 警告を出して続行)
+    #[arg(long, global = true, default_value_t = VersionGatePolicy::Strict)]
+    mirakc_version_policy: VersionGatePolicy,
 }
 
 /// 起動可能なワーカーの種類
@@ -47,7 +127,465 @@ enum WorkerType {
     },
     /// EPG更新イベントを処理するワーカー
     EpgUpdater, // mirakc_url 引数を削除
-                // 将来的に他のワーカーを追加する場合はここに追加
+    /// 録画レコードをKVに投影する録画ライブラリワーカー
+    RecordLibrary {
+        /// mirakcサーバーのURL (起動時の全件同期に使用)
+        #[arg(long, default_value = "http://localhost:40772")]
+        mirakc_url: String,
+    },
+    /// チューナー状態をKVに投影し、使用履歴を記録するワーカー
+    TunerStatus {
+        /// mirakcサーバーのURL (起動時の全件同期に使用)
+        #[arg(long, default_value = "http://localhost:40772")]
+        mirakc_url: String,
+    },
+    /// 指定時刻に使用中だったチューナーを表示
+    TunerUsage {
+        /// 対象の時刻 (RFC 3339 形式。例: 2025-01-01T21:00:00+09:00)
+        #[arg(long)]
+        at: chrono::DateTime<chrono::Utc>,
+        /// チャンネルタイプ (GR, BS, CS など)。省略時はすべて
+        #[arg(long)]
+        channel_type: Option<String>,
+    },
+    /// mirakcのバージョンを定期的に確認し、変更を通知するワーカー
+    VersionWatch {
+        /// mirakcサーバーのURL
+        #[arg(long, default_value = "http://localhost:40772")]
+        mirakc_url: String,
+        /// 確認間隔 (例: 30m, 1h)
+        #[arg(long, default_value = "1h", value_parser = humantime::parse_duration)]
+        interval: std::time::Duration,
+    },
+    /// サービスごとの放送中番組をKVに投影するワーカー
+    NowPlaying {
+        /// mirakcサーバーのURL (起動時の全件同期に使用)
+        #[arg(long, default_value = "http://localhost:40772")]
+        mirakc_url: String,
+    },
+    /// サービス・チャンネル一覧とロゴを同期し、サービスの追加・削除・名前変更を通知するワーカー
+    ServiceCatalog {
+        /// mirakcサーバーのURL
+        #[arg(long, default_value = "http://localhost:40772")]
+        mirakc_url: String,
+        /// 同期間隔 (例: 30m, 1h)
+        #[arg(long, default_value = "1h", value_parser = humantime::parse_duration)]
+        interval: std::time::Duration,
+    },
+    /// 保存済みの番組情報を XMLTV として出力
+    ExportXmltv {
+        /// 出力先のファイル。省略時は標準出力
+        #[arg(long)]
+        output: Option<std::path::PathBuf>,
+        /// チャンネルロゴの URL を出力しない
+        #[arg(long)]
+        no_logos: bool,
+    },
+    /// XMLTV を HTTP で配信するサーバー (GET /xmltv)
+    XmltvServer {
+        /// 待ち受けアドレス
+        #[arg(long, default_value = "0.0.0.0:8080")]
+        listen: std::net::SocketAddr,
+        /// チャンネルロゴの URL を出力しない
+        #[arg(long)]
+        no_logos: bool,
+    },
+    /// 自動録画ルールを管理
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommand,
+    },
+    /// 番組を自動録画ルールとシリーズ録画で評価し、録画されるべき番組を更新するワーカー
+    RuleEngine {
+        /// 起動時にバージョンを確認する mirakc の URL (複数指定可)
+        #[arg(long = "mirakc-url", default_value = "http://localhost:40772")]
+        mirakc_urls: Vec<String>,
+    },
+    /// シリーズ録画を管理
+    Series {
+        #[command(subcommand)]
+        command: SeriesCommand,
+    },
+    /// チューナーが足りずに録画できない録画予約を表示
+    Conflicts,
+    /// 録画されるべき番組を mirakc の録画予約に反映するワーカー
+    ///
+    /// 複数起動した場合は、リースを保持している1つだけが調整を行う。
+    Reconciler {
+        /// 録画されるべき番組がなくても調整する mirakc の URL (複数指定可)
+        #[arg(long = "mirakc-url", default_value = "http://localhost:40772")]
+        mirakc_urls: Vec<String>,
+        /// 調整間隔 (例: 5m, 1h)。リースの有効期間はこの3倍
+        #[arg(long, default_value = "5m", value_parser = humantime::parse_duration)]
+        interval: std::time::Duration,
+    },
+    /// Web UI などからの問い合わせ (ルールのプレビューなど) に応答するワーカー
+    QueryServer,
+    /// 番組を検索インデックスに反映し、番組検索の問い合わせに応答するワーカー
+    SearchIndexer {
+        /// 検索インデックスの種類
+        #[arg(long, value_enum, default_value_t = SearchBackend::Meilisearch)]
+        backend: SearchBackend,
+        /// Meilisearch の URL
+        #[arg(long, default_value = "http://localhost:7700")]
+        meilisearch_url: String,
+        /// Meilisearch の API キー
+        #[arg(long)]
+        meilisearch_api_key: Option<String>,
+        /// 組み込みのインデックス (tantivy) を保存するディレクトリ
+        #[arg(long, default_value = "./data/search")]
+        index_path: std::path::PathBuf,
+        /// 組み込みのインデックスで使う lindera の辞書のディレクトリ (指定しない場合は bi-gram で分割する)
+        #[arg(long)]
+        dictionary: Option<std::path::PathBuf>,
+        /// 起動時にインデックスのドキュメントをすべて削除し、KV に保存されている番組から作り直す
+        #[arg(long)]
+        rebuild: bool,
+    },
+    /// 録画ライブラリを検索インデックスに反映し、録画ライブラリの検索の問い合わせに応答するワーカー
+    RecordIndexer {
+        /// 検索インデックスの種類
+        #[arg(long, value_enum, default_value_t = SearchBackend::Meilisearch)]
+        backend: SearchBackend,
+        /// Meilisearch の URL
+        #[arg(long, default_value = "http://localhost:7700")]
+        meilisearch_url: String,
+        /// Meilisearch の API キー
+        #[arg(long)]
+        meilisearch_api_key: Option<String>,
+        /// 組み込みのインデックス (tantivy) を保存するディレクトリ (番組検索とは別のディレクトリ)
+        #[arg(long, default_value = "./data/records")]
+        index_path: std::path::PathBuf,
+        /// 組み込みのインデックスで使う lindera の辞書のディレクトリ (指定しない場合は bi-gram で分割する)
+        #[arg(long)]
+        dictionary: Option<std::path::PathBuf>,
+        /// KV の録画レコードをインデックスに反映する間隔 (例: 1m)
+        #[arg(long, default_value = "1m", value_parser = humantime::parse_duration)]
+        interval: std::time::Duration,
+        /// 起動時にインデックスのドキュメントをすべて削除し、KV に保存されている録画レコードから作り直す
+        #[arg(long)]
+        rebuild: bool,
+    },
+    /// 番組の詳細情報で見つかった URL の OGP 画像を取得し、Object Store に保存するワーカー
+    Ogp {
+        /// 縮小後の画像の幅
+        #[arg(long, default_value_t = 300)]
+        width: u32,
+        /// 1回のリクエストのタイムアウト (例: 10s)
+        #[arg(long, default_value = "10s", value_parser = humantime::parse_duration)]
+        timeout: std::time::Duration,
+        /// 画像を取得できた URL を取得し直すまでの期間 (例: 30d)
+        #[arg(long, default_value = "30d", value_parser = humantime::parse_duration)]
+        refetch_ttl: std::time::Duration,
+        /// 画像がなかった、または取得に失敗した URL を取得し直すまでの期間 (例: 1d)
+        #[arg(long, default_value = "1d", value_parser = humantime::parse_duration)]
+        negative_ttl: std::time::Duration,
+    },
+    /// 録画が完了したら録画ごとにジョブを作成し、依存関係の順にジョブを送るワーカー
+    JobEngine {
+        /// ジョブファイル (JSON)
+        #[arg(long, default_value = "jobs.json")]
+        jobs: std::path::PathBuf,
+    },
+    /// ジョブエンジンが送ったエンコードジョブのスクリプトを実行するワーカー
+    Encoder {
+        /// 起動時にバージョンを確認する mirakc の URL (複数指定可)
+        #[arg(long = "mirakc-url", default_value = "http://localhost:40772")]
+        mirakc_urls: Vec<String>,
+        /// 作業ディレクトリを作成するディレクトリ (録画ファイル全体を書き出せる空きが必要)
+        #[arg(long, default_value = "./data/encode")]
+        work_dir: std::path::PathBuf,
+        /// スクリプトを実行するシェル
+        #[arg(long, default_value = "bash")]
+        shell: String,
+        /// これを超えるとスクリプトを強制終了する (例: 6h)
+        #[arg(long, default_value = "6h", value_parser = humantime::parse_duration)]
+        timeout: std::time::Duration,
+        /// 実行中のスクリプトのログを保存する間隔 (例: 10s)
+        #[arg(long, default_value = "10s", value_parser = humantime::parse_duration)]
+        log_interval: std::time::Duration,
+        /// 出力の保存先を定義するストレージの設定ファイル (JSON)
+        #[arg(long, default_value = "storages.json")]
+        storages: std::path::PathBuf,
+    },
+    /// ジョブエンジンが送った元ファイル保存ジョブで、mirakc の録画ファイルをストレージにコピーするワーカー
+    OriginalSaver {
+        /// 起動時にバージョンを確認する mirakc の URL (複数指定可)
+        #[arg(long = "mirakc-url", default_value = "http://localhost:40772")]
+        mirakc_urls: Vec<String>,
+        /// 保存先を定義するストレージの設定ファイル (JSON)
+        #[arg(long, default_value = "storages.json")]
+        storages: std::path::PathBuf,
+        /// 接続が切れた場合に続けて再試行する回数
+        #[arg(long, default_value_t = 5)]
+        max_retries: u32,
+        /// 再試行するまでの待ち時間 (例: 5s)
+        #[arg(long, default_value = "5s", value_parser = humantime::parse_duration)]
+        retry_delay: std::time::Duration,
+        /// 1回の接続でこのバイト数以上コピーできたら、続けて失敗した回数を数え直す
+        #[arg(long, default_value_t = 16 * 1024 * 1024)]
+        retry_reset_bytes: u64,
+        /// 進捗を発行する間隔 (例: 10s)
+        #[arg(long, default_value = "10s", value_parser = humantime::parse_duration)]
+        progress_interval: std::time::Duration,
+    },
+    /// すべてのジョブが成功した録画を、保存したコピーを検証してから mirakc から削除するワーカー (1つだけ実行する)
+    RecordDeleter {
+        /// 起動時にバージョンを確認する mirakc の URL (複数指定可)
+        #[arg(long = "mirakc-url", default_value = "http://localhost:40772")]
+        mirakc_urls: Vec<String>,
+        /// 保存先を定義するストレージの設定ファイル (JSON)
+        #[arg(long, default_value = "storages.json")]
+        storages: std::path::PathBuf,
+        /// すべてのジョブが成功してから削除するまでの猶予期間 (例: 24h)
+        #[arg(long, default_value = "24h", value_parser = humantime::parse_duration)]
+        grace_period: std::time::Duration,
+        /// 削除するジョブを確認する間隔 (例: 5m)
+        #[arg(long, default_value = "5m", value_parser = humantime::parse_duration)]
+        interval: std::time::Duration,
+        /// 検証と監査記録だけを行い、削除しない
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// 自動録画ルールの管理コマンド
+#[derive(Subcommand, Debug)]
+enum RulesCommand {
+    /// ルールファイル (JSON。1つのルールまたはルールの配列) を登録する
+    Apply {
+        /// ルールファイルのパス
+        file: std::path::PathBuf,
+    },
+    /// 登録されているルールを一覧表示する
+    List,
+    /// ルールを削除する
+    Delete {
+        /// ルールID
+        id: String,
+    },
+    /// ルールを保存済みの番組で評価し、録画される番組・除外される番組・競合を表示する (録画予約は変更しない)
+    Preview {
+        /// ルールファイルのパス、またはルールID
+        target: String,
+    },
+}
+
+/// シリーズ録画の管理コマンド
+#[derive(Subcommand, Debug)]
+enum SeriesCommand {
+    /// 番組のシリーズを購読し、今後の放送をすべて録画する
+    Subscribe {
+        /// 番組を取得した mirakc の URL
+        #[arg(long, default_value = "http://localhost:40772")]
+        mirakc_url: String,
+        /// シリーズの番組ID
+        #[arg(long)]
+        program_id: i64,
+    },
+    /// 購読しているシリーズを一覧表示する
+    List,
+    /// シリーズの購読を解除する
+    Unsubscribe {
+        /// 購読ID (`{network_id}_{series_id}`)
+        id: String,
+    },
+}
+
+/// 番組検索インデックスの種類
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum SearchBackend {
+    /// 外部の Meilisearch
+    Meilisearch,
+    /// ローカルディスクに保存する組み込みのインデックス (tantivy)
+    Tantivy,
+}
+
+/// 検索インデックスを開く。`records` の場合は録画ライブラリのインデックスを開く。
+fn open_search_index(
+    backend: SearchBackend,
+    meilisearch: MeilisearchConfig,
+    tantivy: TantivyConfig,
+    records: bool,
+) -> Result<Arc<dyn ProgramSearchIndex>> {
+    Ok(match backend {
+        SearchBackend::Meilisearch => {
+            println!("Using Meilisearch URL: {}", meilisearch.url);
+            if records {
+                Arc::new(MeilisearchProgramIndex::records(meilisearch))
+            } else {
+                Arc::new(MeilisearchProgramIndex::new(meilisearch))
+            }
+        }
+        SearchBackend::Tantivy => {
+            println!("Using embedded index: {}", tantivy.path.display());
+            Arc::new(TantivyProgramIndex::open(&tantivy).context("検索インデックスを開けません")?)
+        }
+    })
+}
+
+/// mirakc のバージョンがサポート範囲内か確認する (起動時のバージョンゲート)
+///
+/// ポリシーが `strict` でサポート範囲外の場合はエラーを返す。
+async fn ensure_mirakc_version(mirakc_url: &str, gate: &VersionGateArgs) -> Result<()> {
+    VersionUseCase::new(DomainVersionRepositoryImpl::new(mirakc_url))
+        .ensure_compatible(&gate.mirakc_version_req, gate.mirakc_version_policy)
+        .await
+        .context("mirakc のバージョン確認により起動を中止しました")
+}
+
+/// チューナー状態ユースケースを作成する
+async fn tuner_status_usecase(nats_client: &Arc<NatsClient>) -> Result<TunerStatusUseCase> {
+    let status_repository = NatsKvTunerStatusRepository::new(nats_client.clone())
+        .await
+        .context("チューナー状態用 KV ストアの初期化に失敗しました")?;
+    let history_repository = NatsKvTunerHistoryRepository::new(nats_client.clone())
+        .await
+        .context("チューナー使用履歴用 KV ストアの初期化に失敗しました")?;
+    Ok(TunerStatusUseCase::new(
+        Arc::new(MirakcApiClientImpl::new()),
+        Arc::new(status_repository),
+        Arc::new(history_repository),
+    ))
+}
+
+/// シリーズ録画ユースケースを作成する
+async fn series_tracking_usecase(
+    nats_client: &Arc<NatsClient>,
+    programs: Arc<dyn KurecProgramRepository>,
+    schedules: Arc<dyn DesiredScheduleRepository>,
+) -> Result<SeriesTrackingUseCase> {
+    let record_repository = NatsKvRecordRepository::new(nats_client.clone())
+        .await
+        .context("録画ライブラリ用 KV ストアの初期化に失敗しました")?;
+    let subscription_repository = NatsKvSeriesSubscriptionRepository::new(nats_client.clone())
+        .await
+        .context("シリーズ録画用 KV ストアの初期化に失敗しました")?;
+    let sink: Arc<dyn EventSink<SeriesCompletedEvent>> = Arc::new(JsPublisher::new(
+        nats_client.clone(),
+        streams_def::kurec_event_stream(),
+    ));
+    Ok(SeriesTrackingUseCase::new(
+        Arc::new(MirakcApiClientImpl::new()),
+        programs,
+        Arc::new(record_repository),
+        Arc::new(subscription_repository),
+        schedules,
+        sink,
+    ))
+}
+
+/// 録画予約の競合検出ユースケースを作成する
+async fn schedule_conflict_usecase(
+    nats_client: &Arc<NatsClient>,
+    schedules: Arc<dyn DesiredScheduleRepository>,
+) -> Result<ScheduleConflictUseCase> {
+    let tuner_repository = NatsKvTunerStatusRepository::new(nats_client.clone())
+        .await
+        .context("チューナー状態用 KV ストアの初期化に失敗しました")?;
+    let service_repository = NatsKvServiceRepository::new(nats_client.clone())
+        .await
+        .context("サービス情報用 KV ストアの初期化に失敗しました")?;
+    let sink: Arc<dyn EventSink<ScheduleConflictDetectedEvent>> = Arc::new(JsPublisher::new(
+        nats_client.clone(),
+        streams_def::kurec_event_stream(),
+    ));
+    Ok(ScheduleConflictUseCase::new(
+        schedules,
+        Arc::new(tuner_repository),
+        Arc::new(service_repository),
+        sink,
+    ))
+}
+
+/// 自動録画ルールのプレビューユースケースを作成する
+async fn rule_preview_usecase(nats_client: &Arc<NatsClient>) -> Result<RulePreviewUseCase> {
+    let rule_repository = NatsKvRecordingRuleRepository::new(nats_client.clone())
+        .await
+        .context("自動録画ルール用 KV ストアの初期化に失敗しました")?;
+    let program_repository = NatsKvProgramRepository::new(nats_client.clone())
+        .await
+        .context("番組情報用 KV ストアの初期化に失敗しました")?;
+    let schedule_repository = NatsKvDesiredScheduleRepository::new(nats_client.clone())
+        .await
+        .context("録画予約用 KV ストアの初期化に失敗しました")?;
+    let tuner_repository = NatsKvTunerStatusRepository::new(nats_client.clone())
+        .await
+        .context("チューナー状態用 KV ストアの初期化に失敗しました")?;
+    let service_repository = NatsKvServiceRepository::new(nats_client.clone())
+        .await
+        .context("サービス情報用 KV ストアの初期化に失敗しました")?;
+    Ok(RulePreviewUseCase::new(
+        Arc::new(rule_repository),
+        Arc::new(program_repository),
+        Arc::new(schedule_repository),
+        Arc::new(tuner_repository),
+        Arc::new(service_repository),
+    ))
+}
+
+/// XMLTVエクスポートユースケースを作成する
+async fn xmltv_export_usecase(
+    nats_client: &Arc<NatsClient>,
+    no_logos: bool,
+) -> Result<XmltvExportUseCase> {
+    let repository = NatsKvProgramRepository::new(nats_client.clone())
+        .await
+        .context("番組情報用 KV ストアの初期化に失敗しました")?;
+    Ok(XmltvExportUseCase::new(
+        Arc::new(repository),
+        XmltvOptions {
+            include_logos: !no_logos,
+        },
+    ))
+}
+
+/// mirakcイベントを mirakc-events ストリームへ発行する Sink を作成する
+fn mirakc_event_sinks(nats_client: &Arc<NatsClient>) -> MirakcEventSinks {
+    let stream = streams_def::mirakc_event_stream();
+    MirakcEventSinks {
+        tuner_status_changed: Some(Arc::new(JsPublisher::new(
+            nats_client.clone(),
+            stream.clone(),
+        ))),
+        epg_programs_updated: Some(Arc::new(JsPublisher::new(
+            nats_client.clone(),
+            stream.clone(),
+        ))),
+        recording_started: Some(Arc::new(JsPublisher::new(
+            nats_client.clone(),
+            stream.clone(),
+        ))),
+        recording_stopped: Some(Arc::new(JsPublisher::new(
+            nats_client.clone(),
+            stream.clone(),
+        ))),
+        recording_failed: Some(Arc::new(JsPublisher::new(
+            nats_client.clone(),
+            stream.clone(),
+        ))),
+        recording_rescheduled: Some(Arc::new(JsPublisher::new(
+            nats_client.clone(),
+            stream.clone(),
+        ))),
+        recording_record_saved: Some(Arc::new(JsPublisher::new(
+            nats_client.clone(),
+            stream.clone(),
+        ))),
+        recording_record_removed: Some(Arc::new(JsPublisher::new(
+            nats_client.clone(),
+            stream.clone(),
+        ))),
+        recording_content_removed: Some(Arc::new(JsPublisher::new(
+            nats_client.clone(),
+            stream.clone(),
+        ))),
+        recording_record_broken: Some(Arc::new(JsPublisher::new(
+            nats_client.clone(),
+            stream.clone(),
+        ))),
+        onair_program_changed: Some(Arc::new(JsPublisher::new(nats_client.clone(), stream))),
+    }
 }
 
 /// 環境変数NATS_URLからNATS接続URLを取得する
@@ -139,6 +677,14 @@ async fn main() -> Result<()> {
                         }
                     }
 
+                    // サポート範囲の確認
+                    let requirement = &cli.version_gate.mirakc_version_req;
+                    match version.is_compatible_with(requirement) {
+                        Ok(true) => println!("✅ KuRecのサポート範囲 ({}) 内です", requirement),
+                        Ok(false) => println!("❌ KuRecのサポート範囲 ({}) 外です", requirement),
+                        Err(e) => println!("❌ バージョンを解析できません: {}", e),
+                    }
+
                     // 正常終了
                     shutdown.cancel();
                 }
@@ -150,16 +696,14 @@ async fn main() -> Result<()> {
         }
         WorkerType::MirakcEvents { mirakc_url } => {
             println!("Starting mirakc events worker with URL: {}...", mirakc_url);
+            ensure_mirakc_version(&mirakc_url, &cli.version_gate).await?;
 
             // 依存関係の初期化
             // EventSource の型パラメータを MirakcEventInput に変更
             let mirakc_source: Arc<dyn EventSource<MirakcEventInput>> =
                 Arc::new(MirakcSseSource::new(mirakc_url.clone()));
 
-            // TODO: MirakcEventSinks の初期化 (必要な Sink を作成して渡す)
-            // 例: let epg_updated_sink = Arc::new(JsPublisher::new(nats_client.clone(), "mirakc-events"));
-            //     let sinks = MirakcEventSinks { epg_programs_updated: Some(epg_updated_sink), ... };
-            let sinks = MirakcEventSinks::default(); // 仮実装
+            let sinks = mirakc_event_sinks(&nats_client);
 
             // シャットダウントークンのクローンを作成
             let worker_shutdown = shutdown.clone();
@@ -209,6 +753,793 @@ async fn main() -> Result<()> {
                 }
             });
         }
+        WorkerType::RecordLibrary { mirakc_url } => {
+            println!("Starting record library worker with URL: {}...", mirakc_url);
+            ensure_mirakc_version(&mirakc_url, &cli.version_gate).await?;
+
+            // 依存関係の初期化
+            let mirakc_stream = streams_def::mirakc_event_stream();
+            let record_repository = Arc::new(
+                NatsKvRecordRepository::new(nats_client.clone())
+                    .await
+                    .context("録画ライブラリ用 KV ストアの初期化に失敗しました")?,
+            );
+            let usecase = Arc::new(RecordLibraryUseCase::new(
+                Arc::new(MirakcApiClientImpl::new()),
+                record_repository,
+            ));
+
+            // 他のワーカーと競合しないよう、専用のコンシューマで購読する
+            let sources = cmd::record_library::RecordLibrarySources {
+                record_saved: Arc::new(
+                    JsSubscriber::<RecordingRecordSavedEvent>::new(
+                        nats_client.clone(),
+                        mirakc_stream.clone(),
+                    )
+                    .with_durable_name("record_library_recording_record_saved"),
+                ),
+                record_removed: Arc::new(
+                    JsSubscriber::<RecordingRecordRemovedEvent>::new(
+                        nats_client.clone(),
+                        mirakc_stream.clone(),
+                    )
+                    .with_durable_name("record_library_recording_record_removed"),
+                ),
+                content_removed: Arc::new(
+                    JsSubscriber::<RecordingContentRemovedEvent>::new(
+                        nats_client.clone(),
+                        mirakc_stream,
+                    )
+                    .with_durable_name("record_library_recording_content_removed"),
+                ),
+            };
+
+            let worker_shutdown = shutdown.clone();
+            let _record_library_handle = tokio::spawn(async move {
+                if let Err(e) = cmd::record_library::run_record_library(
+                    mirakc_url,
+                    usecase,
+                    sources,
+                    worker_shutdown,
+                )
+                .await
+                {
+                    eprintln!("Record library worker error: {}", e);
+                }
+            });
+        }
+        WorkerType::TunerStatus { mirakc_url } => {
+            println!("Starting tuner status worker with URL: {}...", mirakc_url);
+            ensure_mirakc_version(&mirakc_url, &cli.version_gate).await?;
+
+            let usecase = Arc::new(tuner_status_usecase(&nats_client).await?);
+            let source: Arc<dyn EventSource<TunerStatusChangedEvent>> = Arc::new(
+                JsSubscriber::<TunerStatusChangedEvent>::new(
+                    nats_client.clone(),
+                    streams_def::mirakc_event_stream(),
+                )
+                .with_durable_name("tuner_status_tuner_status_changed"),
+            );
+
+            let worker_shutdown = shutdown.clone();
+            let _tuner_status_handle = tokio::spawn(async move {
+                if let Err(e) = cmd::tuner_status::run_tuner_status(
+                    mirakc_url,
+                    usecase,
+                    source,
+                    worker_shutdown,
+                )
+                .await
+                {
+                    eprintln!("Tuner status worker error: {}", e);
+                }
+            });
+        }
+        WorkerType::TunerUsage { at, channel_type } => {
+            let usecase = tuner_status_usecase(&nats_client).await?;
+            if let Err(e) =
+                cmd::tuner_status::print_tuner_usage(&usecase, at, channel_type.as_deref()).await
+            {
+                eprintln!("チューナー使用履歴の取得エラー: {}", e);
+                std::process::exit(1);
+            }
+            shutdown.cancel();
+        }
+        WorkerType::VersionWatch {
+            mirakc_url,
+            interval,
+        } => {
+            println!(
+                "Starting mirakc version watch worker with URL: {}...",
+                mirakc_url
+            );
+            ensure_mirakc_version(&mirakc_url, &cli.version_gate).await?;
+
+            let sink: Arc<dyn EventSink<MirakcVersionChangedEvent>> = Arc::new(JsPublisher::new(
+                nats_client.clone(),
+                streams_def::kurec_event_stream(),
+            ));
+            let usecase = Arc::new(VersionWatchUseCase::new(
+                mirakc_url.clone(),
+                DomainVersionRepositoryImpl::new(&mirakc_url),
+                cli.version_gate.mirakc_version_req.clone(),
+                sink,
+            ));
+
+            let worker_shutdown = shutdown.clone();
+            let _version_watch_handle = tokio::spawn(async move {
+                if let Err(e) =
+                    cmd::version_watch::run_version_watch(usecase, interval, worker_shutdown).await
+                {
+                    eprintln!("mirakc version watch worker error: {}", e);
+                }
+            });
+        }
+        WorkerType::NowPlaying { mirakc_url } => {
+            println!("Starting now playing worker with URL: {}...", mirakc_url);
+            ensure_mirakc_version(&mirakc_url, &cli.version_gate).await?;
+
+            let repository = NatsKvNowPlayingRepository::new(nats_client.clone())
+                .await
+                .context("放送中番組用 KV ストアの初期化に失敗しました")?;
+            let sink: Arc<dyn EventSink<NowPlayingChangedEvent>> = Arc::new(JsPublisher::new(
+                nats_client.clone(),
+                streams_def::kurec_event_stream(),
+            ));
+            let usecase = Arc::new(NowPlayingUseCase::new(
+                Arc::new(MirakcApiClientImpl::new()),
+                Arc::new(repository),
+                sink,
+            ));
+            let source: Arc<dyn EventSource<OnairProgramChangedEvent>> = Arc::new(
+                JsSubscriber::<OnairProgramChangedEvent>::new(
+                    nats_client.clone(),
+                    streams_def::mirakc_event_stream(),
+                )
+                .with_durable_name("now_playing_onair_program_changed"),
+            );
+
+            let worker_shutdown = shutdown.clone();
+            let _now_playing_handle = tokio::spawn(async move {
+                if let Err(e) =
+                    cmd::now_playing::run_now_playing(mirakc_url, usecase, source, worker_shutdown)
+                        .await
+                {
+                    eprintln!("Now playing worker error: {}", e);
+                }
+            });
+        }
+        WorkerType::ServiceCatalog {
+            mirakc_url,
+            interval,
+        } => {
+            println!(
+                "Starting service catalog worker with URL: {}...",
+                mirakc_url
+            );
+            ensure_mirakc_version(&mirakc_url, &cli.version_gate).await?;
+
+            let repository = NatsKvServiceRepository::new(nats_client.clone())
+                .await
+                .context("サービスカタログ用 KV ストアの初期化に失敗しました")?;
+            let logo_repository = NatsObjectServiceLogoRepository::new(nats_client.clone())
+                .await
+                .context("サービスロゴ用 Object Store の初期化に失敗しました")?;
+            let stream = streams_def::kurec_event_stream();
+            let sinks = ServiceCatalogSinks {
+                service_added: Arc::new(JsPublisher::<ServiceAddedEvent>::new(
+                    nats_client.clone(),
+                    stream.clone(),
+                )),
+                service_removed: Arc::new(JsPublisher::<ServiceRemovedEvent>::new(
+                    nats_client.clone(),
+                    stream.clone(),
+                )),
+                service_renamed: Arc::new(JsPublisher::<ServiceRenamedEvent>::new(
+                    nats_client.clone(),
+                    stream,
+                )),
+            };
+            let usecase = Arc::new(ServiceCatalogUseCase::new(
+                Arc::new(MirakcApiClientImpl::new()),
+                Arc::new(repository),
+                Arc::new(logo_repository),
+                sinks,
+            ));
+
+            let worker_shutdown = shutdown.clone();
+            let _service_catalog_handle = tokio::spawn(async move {
+                if let Err(e) = cmd::service_catalog::run_service_catalog(
+                    mirakc_url,
+                    usecase,
+                    interval,
+                    worker_shutdown,
+                )
+                .await
+                {
+                    eprintln!("Service catalog worker error: {}", e);
+                }
+            });
+        }
+        WorkerType::ExportXmltv { output, no_logos } => {
+            let usecase = xmltv_export_usecase(&nats_client, no_logos).await?;
+            if let Err(e) = cmd::xmltv::export_xmltv(&usecase, output.as_deref()).await {
+                eprintln!("XMLTV の出力エラー: {}", e);
+                std::process::exit(1);
+            }
+            // 標準出力を XMLTV だけにするため、終了メッセージを出さずに終了する
+            return Ok(());
+        }
+        WorkerType::XmltvServer { listen, no_logos } => {
+            println!("Starting XMLTV server on {}...", listen);
+
+            let usecase = Arc::new(xmltv_export_usecase(&nats_client, no_logos).await?);
+            let worker_shutdown = shutdown.clone();
+            let _xmltv_server_handle = tokio::spawn(async move {
+                if let Err(e) = cmd::xmltv::run_xmltv_server(usecase, listen, worker_shutdown).await
+                {
+                    eprintln!("XMLTV server error: {}", e);
+                }
+            });
+        }
+        WorkerType::Rules { command } => {
+            let repository = NatsKvRecordingRuleRepository::new(nats_client.clone())
+                .await
+                .context("自動録画ルール用 KV ストアの初期化に失敗しました")?;
+            let sink = JsPublisher::<RecordingRulesChangedEvent>::new(
+                nats_client.clone(),
+                streams_def::kurec_event_stream(),
+            );
+            let result = match command {
+                RulesCommand::Apply { file } => {
+                    cmd::rules::apply_rules(&repository, &sink, &file).await
+                }
+                RulesCommand::List => cmd::rules::list_rules(&repository).await,
+                RulesCommand::Delete { id } => {
+                    cmd::rules::delete_rule(&repository, &sink, &id).await
+                }
+                RulesCommand::Preview { target } => {
+                    let usecase = rule_preview_usecase(&nats_client).await?;
+                    cmd::rules::preview_rules(&usecase, &target).await
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("ルールの操作に失敗しました: {:#}", e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        WorkerType::RuleEngine { mirakc_urls } => {
+            println!("Starting rule engine worker...");
+            for mirakc_url in &mirakc_urls {
+                ensure_mirakc_version(mirakc_url, &cli.version_gate).await?;
+            }
+
+            let rule_repository = NatsKvRecordingRuleRepository::new(nats_client.clone())
+                .await
+                .context("自動録画ルール用 KV ストアの初期化に失敗しました")?;
+            let program_repository = NatsKvProgramRepository::new(nats_client.clone())
+                .await
+                .context("番組情報用 KV ストアの初期化に失敗しました")?;
+            let schedule_repository = NatsKvDesiredScheduleRepository::new(nats_client.clone())
+                .await
+                .context("録画予約用 KV ストアの初期化に失敗しました")?;
+            let sink: Arc<dyn EventSink<RecordingRuleMatchedEvent>> = Arc::new(JsPublisher::new(
+                nats_client.clone(),
+                streams_def::kurec_event_stream(),
+            ));
+            let program_repository: Arc<dyn KurecProgramRepository> = Arc::new(program_repository);
+            let schedule_repository: Arc<dyn DesiredScheduleRepository> =
+                Arc::new(schedule_repository);
+            let rules = Arc::new(RuleEngineUseCase::new(
+                Arc::new(rule_repository),
+                program_repository.clone(),
+                schedule_repository.clone(),
+                sink,
+            ));
+            let series = Arc::new(
+                series_tracking_usecase(
+                    &nats_client,
+                    program_repository,
+                    schedule_repository.clone(),
+                )
+                .await?,
+            );
+            let conflicts =
+                Arc::new(schedule_conflict_usecase(&nats_client, schedule_repository).await?);
+            let epg_source: Arc<dyn EventSource<EpgStoredEvent>> = Arc::new(
+                JsSubscriber::<EpgStoredEvent>::new(
+                    nats_client.clone(),
+                    streams_def::kurec_event_stream(),
+                )
+                .with_durable_name("rule_engine_epg_stored"),
+            );
+            let rules_source: Arc<dyn EventSource<RecordingRulesChangedEvent>> = Arc::new(
+                JsSubscriber::<RecordingRulesChangedEvent>::new(
+                    nats_client.clone(),
+                    streams_def::kurec_event_stream(),
+                )
+                .with_durable_name("rule_engine_rules_changed"),
+            );
+            let record_source: Arc<dyn EventSource<RecordingRecordSavedEvent>> = Arc::new(
+                JsSubscriber::<RecordingRecordSavedEvent>::new(
+                    nats_client.clone(),
+                    streams_def::mirakc_event_stream(),
+                )
+                .with_durable_name("rule_engine_record_saved"),
+            );
+
+            let worker_shutdown = shutdown.clone();
+            let _rule_engine_handle = tokio::spawn(async move {
+                if let Err(e) = cmd::rule_engine::run_rule_engine(
+                    rules,
+                    series,
+                    conflicts,
+                    epg_source,
+                    rules_source,
+                    record_source,
+                    worker_shutdown,
+                )
+                .await
+                {
+                    eprintln!("Rule engine worker error: {}", e);
+                }
+            });
+        }
+        WorkerType::Series { command } => {
+            let program_repository = NatsKvProgramRepository::new(nats_client.clone())
+                .await
+                .context("番組情報用 KV ストアの初期化に失敗しました")?;
+            let schedule_repository = NatsKvDesiredScheduleRepository::new(nats_client.clone())
+                .await
+                .context("録画予約用 KV ストアの初期化に失敗しました")?;
+            let subscription_repository =
+                NatsKvSeriesSubscriptionRepository::new(nats_client.clone())
+                    .await
+                    .context("シリーズ録画用 KV ストアの初期化に失敗しました")?;
+            let usecase = series_tracking_usecase(
+                &nats_client,
+                Arc::new(program_repository),
+                Arc::new(schedule_repository),
+            )
+            .await?;
+            let result = match command {
+                SeriesCommand::Subscribe {
+                    mirakc_url,
+                    program_id,
+                } => cmd::series::subscribe(&usecase, &mirakc_url, program_id).await,
+                SeriesCommand::List => {
+                    cmd::series::list_subscriptions(&subscription_repository).await
+                }
+                SeriesCommand::Unsubscribe { id } => cmd::series::unsubscribe(&usecase, &id).await,
+            };
+            if let Err(e) = result {
+                eprintln!("シリーズ録画の操作に失敗しました: {:#}", e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        WorkerType::Reconciler {
+            mirakc_urls,
+            interval,
+        } => {
+            println!("Starting schedule reconciler worker...");
+            for mirakc_url in &mirakc_urls {
+                ensure_mirakc_version(mirakc_url, &cli.version_gate).await?;
+            }
+
+            let schedule_repository = NatsKvDesiredScheduleRepository::new(nats_client.clone())
+                .await
+                .context("録画予約用 KV ストアの初期化に失敗しました")?;
+            let lease_repository = NatsKvLeaseRepository::new(nats_client.clone())
+                .await
+                .context("リース用 KV ストアの初期化に失敗しました")?;
+            let sink: Arc<dyn EventSink<ScheduleDriftDetectedEvent>> = Arc::new(JsPublisher::new(
+                nats_client.clone(),
+                streams_def::kurec_event_stream(),
+            ));
+            let holder = format!(
+                "{}-{}",
+                env::var("HOSTNAME").unwrap_or_else(|_| "kurec".to_string()),
+                std::process::id()
+            );
+            let lease_ttl =
+                chrono::Duration::from_std(interval * 3).context("調整間隔が長すぎます")?;
+            let usecase = Arc::new(ScheduleReconcilerUseCase::new(
+                Arc::new(MirakcApiClientImpl::new()),
+                Arc::new(schedule_repository),
+                Arc::new(lease_repository),
+                sink,
+                holder,
+                lease_ttl,
+                mirakc_urls,
+            ));
+            let epg_source: Arc<dyn EventSource<EpgStoredEvent>> = Arc::new(
+                JsSubscriber::<EpgStoredEvent>::new(
+                    nats_client.clone(),
+                    streams_def::kurec_event_stream(),
+                )
+                .with_durable_name("reconciler_epg_stored"),
+            );
+            let rules_source: Arc<dyn EventSource<RecordingRulesChangedEvent>> = Arc::new(
+                JsSubscriber::<RecordingRulesChangedEvent>::new(
+                    nats_client.clone(),
+                    streams_def::kurec_event_stream(),
+                )
+                .with_durable_name("reconciler_rules_changed"),
+            );
+
+            // 終了時にリースを解放するため、完了まで待つ
+            if let Err(e) = cmd::reconciler::run_reconciler(
+                usecase,
+                epg_source,
+                rules_source,
+                interval,
+                shutdown.clone(),
+            )
+            .await
+            {
+                eprintln!("Schedule reconciler worker error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        WorkerType::Conflicts => {
+            let schedule_repository = NatsKvDesiredScheduleRepository::new(nats_client.clone())
+                .await
+                .context("録画予約用 KV ストアの初期化に失敗しました")?;
+            let usecase =
+                schedule_conflict_usecase(&nats_client, Arc::new(schedule_repository)).await?;
+            if let Err(e) = cmd::conflicts::print_conflicts(&usecase).await {
+                eprintln!("録画予約の競合の分析に失敗しました: {:#}", e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        WorkerType::QueryServer => {
+            println!("Starting query server worker...");
+
+            let usecase = Arc::new(rule_preview_usecase(&nats_client).await?);
+            let rule_preview =
+                NatsQueryResponder::<RulePreviewQuery>::new(nats_client.clone(), usecase);
+
+            let worker_shutdown = shutdown.clone();
+            let _query_server_handle = tokio::spawn(async move {
+                if let Err(e) =
+                    cmd::query_server::run_query_server(rule_preview, worker_shutdown).await
+                {
+                    eprintln!("Query server worker error: {}", e);
+                }
+            });
+        }
+        WorkerType::SearchIndexer {
+            backend,
+            meilisearch_url,
+            meilisearch_api_key,
+            index_path,
+            dictionary,
+            rebuild,
+        } => {
+            println!("Starting search indexer worker...");
+            let index = open_search_index(
+                backend,
+                MeilisearchConfig::new(meilisearch_url, meilisearch_api_key),
+                TantivyConfig::new(index_path).with_dictionary(dictionary),
+                false,
+            )?;
+
+            let program_repository = NatsKvProgramRepository::new(nats_client.clone())
+                .await
+                .context("番組情報用 KV ストアの初期化に失敗しました")?;
+            let url_sink: Arc<dyn EventSink<ProgramUrlDiscoveredEvent>> = Arc::new(
+                JsPublisher::new(nats_client.clone(), streams_def::kurec_event_stream()),
+            );
+            let usecase = Arc::new(
+                ProgramIndexUseCase::new(Arc::new(program_repository), index)
+                    .with_url_sink(url_sink),
+            );
+            let source: Arc<dyn EventSource<EpgStoredEvent>> = Arc::new(
+                JsSubscriber::<EpgStoredEvent>::new(
+                    nats_client.clone(),
+                    streams_def::kurec_event_stream(),
+                )
+                .with_durable_name("search_indexer_epg_stored"),
+            );
+            let search =
+                NatsQueryResponder::<ProgramSearchQuery>::new(nats_client.clone(), usecase.clone());
+
+            let worker_shutdown = shutdown.clone();
+            let _search_indexer_handle = tokio::spawn(async move {
+                if let Err(e) = cmd::search_indexer::run_search_indexer(
+                    usecase,
+                    source,
+                    search,
+                    rebuild,
+                    worker_shutdown,
+                )
+                .await
+                {
+                    eprintln!("Search indexer worker error: {}", e);
+                }
+            });
+        }
+        WorkerType::RecordIndexer {
+            backend,
+            meilisearch_url,
+            meilisearch_api_key,
+            index_path,
+            dictionary,
+            interval,
+            rebuild,
+        } => {
+            println!("Starting record indexer worker...");
+            let index = open_search_index(
+                backend,
+                MeilisearchConfig::new(meilisearch_url, meilisearch_api_key),
+                TantivyConfig::new(index_path).with_dictionary(dictionary),
+                true,
+            )?;
+
+            let record_repository = NatsKvRecordRepository::new(nats_client.clone())
+                .await
+                .context("録画ライブラリ用 KV ストアの初期化に失敗しました")?;
+            let usecase = Arc::new(RecordIndexUseCase::new(Arc::new(record_repository), index));
+            let search =
+                NatsQueryResponder::<RecordSearchQuery>::new(nats_client.clone(), usecase.clone());
+
+            let worker_shutdown = shutdown.clone();
+            let _record_indexer_handle = tokio::spawn(async move {
+                if let Err(e) = cmd::record_indexer::run_record_indexer(
+                    usecase,
+                    search,
+                    interval,
+                    rebuild,
+                    worker_shutdown,
+                )
+                .await
+                {
+                    eprintln!("Record indexer worker error: {}", e);
+                }
+            });
+        }
+        WorkerType::Ogp {
+            width,
+            timeout,
+            refetch_ttl,
+            negative_ttl,
+        } => {
+            println!("Starting OGP worker...");
+
+            let fetcher = HttpOgpFetcher::new(OgpConfig {
+                width,
+                timeout,
+                ..Default::default()
+            })?;
+            let repository = NatsObjectOgpImageRepository::new(nats_client.clone())
+                .await
+                .context("OGP 画像用 Object Store の初期化に失敗しました")?;
+            let policy = OgpCachePolicy {
+                refetch_ttl: chrono::Duration::from_std(refetch_ttl)
+                    .context("再取得までの期間が長すぎます")?,
+                negative_ttl: chrono::Duration::from_std(negative_ttl)
+                    .context("再取得までの期間が長すぎます")?,
+            };
+            let usecase = Arc::new(OgpUseCase::new(
+                Arc::new(fetcher),
+                Arc::new(repository),
+                policy,
+            ));
+            let source: Arc<dyn EventSource<ProgramUrlDiscoveredEvent>> = Arc::new(
+                JsSubscriber::<ProgramUrlDiscoveredEvent>::new(
+                    nats_client.clone(),
+                    streams_def::kurec_event_stream(),
+                )
+                .with_durable_name("ogp_url_discovered"),
+            );
+
+            let worker_shutdown = shutdown.clone();
+            let _ogp_handle = tokio::spawn(async move {
+                if let Err(e) = cmd::ogp::run_ogp_worker(usecase, source, worker_shutdown).await {
+                    eprintln!("OGP worker error: {}", e);
+                }
+            });
+        }
+        WorkerType::JobEngine { jobs } => {
+            println!(
+                "Starting job engine worker with jobs: {}...",
+                jobs.display()
+            );
+            let graph = cmd::job_engine::load_job_graph(&jobs).await?;
+
+            let repository = NatsKvJobRunRepository::new(nats_client.clone())
+                .await
+                .context("ジョブの実行状況用 KV ストアの初期化に失敗しました")?;
+            let kurec_stream = streams_def::kurec_event_stream();
+            let dispatcher: Arc<dyn EventSink<JobDispatchedEvent>> =
+                Arc::new(JsPublisher::<JobDispatchedEvent>::new(
+                    nats_client.clone(),
+                    kurec_stream.clone(),
+                ));
+            let usecase = Arc::new(JobEngineUseCase::new(
+                Arc::new(graph),
+                Arc::new(repository),
+                dispatcher,
+            ));
+            let sources = cmd::job_engine::JobEngineSources {
+                record_saved: Arc::new(
+                    JsSubscriber::<RecordingRecordSavedEvent>::new(
+                        nats_client.clone(),
+                        streams_def::mirakc_event_stream(),
+                    )
+                    .with_durable_name("job_engine_recording_record_saved"),
+                ),
+                job_completed: Arc::new(
+                    JsSubscriber::<JobCompletedEvent>::new(nats_client.clone(), kurec_stream)
+                        .with_durable_name("job_engine_job_completed"),
+                ),
+            };
+
+            let worker_shutdown = shutdown.clone();
+            let _job_engine_handle = tokio::spawn(async move {
+                if let Err(e) =
+                    cmd::job_engine::run_job_engine(usecase, sources, worker_shutdown).await
+                {
+                    eprintln!("Job engine worker error: {}", e);
+                }
+            });
+        }
+        WorkerType::Encoder {
+            mirakc_urls,
+            work_dir,
+            shell,
+            timeout,
+            log_interval,
+            storages,
+        } => {
+            println!("Starting encoder worker in {}...", work_dir.display());
+            for mirakc_url in &mirakc_urls {
+                ensure_mirakc_version(mirakc_url, &cli.version_gate).await?;
+            }
+
+            let storages = cmd::load_storages(&storages, nats_client.clone()).await?;
+
+            let executor = ScriptEncodeExecutor::new(EncoderConfig {
+                work_dir,
+                shell,
+                log_interval,
+            });
+            let logs = NatsObjectJobLogRepository::new(nats_client.clone())
+                .await
+                .context("ジョブのログ用 Object Store の初期化に失敗しました")?;
+            let kurec_stream = streams_def::kurec_event_stream();
+            let sinks = EncoderSinks {
+                completed: Arc::new(JsPublisher::<JobCompletedEvent>::new(
+                    nats_client.clone(),
+                    kurec_stream.clone(),
+                )),
+                failed: Arc::new(JsPublisher::<EncodeFailedEvent>::new(
+                    nats_client.clone(),
+                    kurec_stream.clone(),
+                )),
+            };
+            let usecase = Arc::new(EncoderUseCase::new(
+                Arc::new(MirakcApiClientImpl::new()),
+                Arc::new(executor),
+                Arc::new(logs),
+                storages,
+                sinks,
+                timeout,
+            ));
+            let source: Arc<dyn EventSource<JobDispatchedEvent>> = Arc::new(
+                JsSubscriber::<JobDispatchedEvent>::new(nats_client.clone(), kurec_stream)
+                    .with_durable_name("encoder_job_dispatched"),
+            );
+
+            let worker_shutdown = shutdown.clone();
+            let _encoder_handle = tokio::spawn(async move {
+                if let Err(e) = cmd::encoder::run_encoder(usecase, source, worker_shutdown).await {
+                    eprintln!("Encoder worker error: {}", e);
+                }
+            });
+        }
+        WorkerType::OriginalSaver {
+            mirakc_urls,
+            storages,
+            max_retries,
+            retry_delay,
+            retry_reset_bytes,
+            progress_interval,
+        } => {
+            println!("Starting original saver worker...");
+            for mirakc_url in &mirakc_urls {
+                ensure_mirakc_version(mirakc_url, &cli.version_gate).await?;
+            }
+
+            let storages = cmd::load_storages(&storages, nats_client.clone()).await?;
+
+            let kurec_stream = streams_def::kurec_event_stream();
+            let sinks = OriginalSaverSinks {
+                completed: Arc::new(JsPublisher::<JobCompletedEvent>::new(
+                    nats_client.clone(),
+                    kurec_stream.clone(),
+                )),
+                progress: Arc::new(JsPublisher::<RecordCopyProgressEvent>::new(
+                    nats_client.clone(),
+                    kurec_stream.clone(),
+                )),
+            };
+            let usecase = Arc::new(OriginalSaverUseCase::new(
+                Arc::new(MirakcApiClientImpl::new()),
+                storages,
+                sinks,
+                OriginalSaverConfig {
+                    max_retries,
+                    retry_delay,
+                    retry_reset_bytes,
+                    progress_interval,
+                },
+            ));
+            let source: Arc<dyn EventSource<JobDispatchedEvent>> = Arc::new(
+                JsSubscriber::<JobDispatchedEvent>::new(nats_client.clone(), kurec_stream)
+                    .with_durable_name("original_saver_job_dispatched"),
+            );
+
+            let worker_shutdown = shutdown.clone();
+            let _original_saver_handle = tokio::spawn(async move {
+                if let Err(e) =
+                    cmd::original_saver::run_original_saver(usecase, source, worker_shutdown).await
+                {
+                    eprintln!("Original saver worker error: {}", e);
+                }
+            });
+        }
+        WorkerType::RecordDeleter {
+            mirakc_urls,
+            storages,
+            grace_period,
+            interval,
+            dry_run,
+        } => {
+            println!(
+                "Starting record deleter worker{}...",
+                if dry_run { " (dry-run)" } else { "" }
+            );
+            for mirakc_url in &mirakc_urls {
+                ensure_mirakc_version(mirakc_url, &cli.version_gate).await?;
+            }
+
+            let storages = cmd::load_storages(&storages, nats_client.clone()).await?;
+
+            let runs = NatsKvJobRunRepository::new(nats_client.clone())
+                .await
+                .context("ジョブの実行状況用 KV ストアの初期化に失敗しました")?;
+            let deletions = NatsKvRecordDeletionRepository::new(nats_client.clone())
+                .await
+                .context("録画の削除の記録用 KV ストアの初期化に失敗しました")?;
+            let usecase = Arc::new(RecordDeleterUseCase::new(
+                Arc::new(runs),
+                Arc::new(MirakcApiClientImpl::new()),
+                storages,
+                Arc::new(deletions),
+                Arc::new(JsPublisher::<JobCompletedEvent>::new(
+                    nats_client.clone(),
+                    streams_def::kurec_event_stream(),
+                )),
+                RecordDeleterConfig {
+                    grace_period,
+                    dry_run,
+                },
+            ));
+
+            let worker_shutdown = shutdown.clone();
+            let _record_deleter_handle = tokio::spawn(async move {
+                if let Err(e) =
+                    cmd::record_deleter::run_record_deleter(usecase, interval, worker_shutdown)
+                        .await
+                {
+                    eprintln!("Record deleter worker error: {}", e);
+                }
+            });
+        }
     }
 
     // シャットダウンを待機
@@ -288,4 +1619,469 @@ mod tests {
             panic!("Expected WorkerType::EpgUpdater");
         }
     }
+
+    #[test]
+    fn test_cli_record_library() {
+        let args = vec![
+            "app",
+            "record-library",
+            "--mirakc-url",
+            "http://example.com",
+        ];
+        let cli = Cli::parse_from(args);
+
+        if let WorkerType::RecordLibrary { mirakc_url } = cli.worker {
+            assert_eq!(mirakc_url, "http://example.com");
+        } else {
+            panic!("Expected WorkerType::RecordLibrary");
+        }
+    }
+
+    #[test]
+    fn test_cli_tuner_status() {
+        let args = vec!["app", "tuner-status", "--mirakc-url", "http://example.com"];
+        let cli = Cli::parse_from(args);
+
+        if let WorkerType::TunerStatus { mirakc_url } = cli.worker {
+            assert_eq!(mirakc_url, "http://example.com");
+        } else {
+            panic!("Expected WorkerType::TunerStatus");
+        }
+    }
+
+    #[test]
+    fn test_cli_tuner_usage() {
+        let args = vec![
+            "app",
+            "tuner-usage",
+            "--at",
+            "2025-01-01T21:00:00+09:00",
+            "--channel-type",
+            "GR",
+        ];
+        let cli = Cli::parse_from(args);
+
+        if let WorkerType::TunerUsage { at, channel_type } = cli.worker {
+            assert_eq!(at.to_rfc3339(), "2025-01-01T12:00:00+00:00");
+            assert_eq!(channel_type.as_deref(), Some("GR"));
+        } else {
+            panic!("Expected WorkerType::TunerUsage");
+        }
+    }
+
+    #[test]
+    fn test_cli_now_playing() {
+        let args = vec!["app", "now-playing", "--mirakc-url", "http://example.com"];
+        let cli = Cli::parse_from(args);
+
+        if let WorkerType::NowPlaying { mirakc_url } = cli.worker {
+            assert_eq!(mirakc_url, "http://example.com");
+        } else {
+            panic!("Expected WorkerType::NowPlaying");
+        }
+    }
+
+    #[test]
+    fn test_cli_version_watch() {
+        let args = vec![
+            "app",
+            "version-watch",
+            "--mirakc-url",
+            "http://example.com",
+            "--interval",
+            "30m",
+        ];
+        let cli = Cli::parse_from(args);
+
+        if let WorkerType::VersionWatch {
+            mirakc_url,
+            interval,
+        } = cli.worker
+        {
+            assert_eq!(mirakc_url, "http://example.com");
+            assert_eq!(interval, std::time::Duration::from_secs(30 * 60));
+        } else {
+            panic!("Expected WorkerType::VersionWatch");
+        }
+    }
+
+    #[test]
+    fn test_cli_version_gate() {
+        let cli = Cli::parse_from(vec!["app", "record-library"]);
+        assert_eq!(
+            cli.version_gate.mirakc_version_req.to_string(),
+            SUPPORTED_MIRAKC_VERSION_REQ
+        );
+        assert_eq!(
+            cli.version_gate.mirakc_version_policy,
+            VersionGatePolicy::Strict
+        );
+
+        let cli = Cli::parse_from(vec![
+            "app",
+            "record-library",
+            "--mirakc-version-req",
+            ">=3.4.0, <5.0.0",
+            "--mirakc-version-policy",
+            "warn",
+        ]);
+        assert!(cli
+            .version_gate
+            .mirakc_version_req
+            .matches(&semver::Version::new(3, 4, 1)));
+        assert_eq!(
+            cli.version_gate.mirakc_version_policy,
+            VersionGatePolicy::Warn
+        );
+    }
+
+    #[test]
+    fn test_cli_export_xmltv() {
+        let cli = Cli::parse_from(vec!["app", "export-xmltv"]);
+        if let WorkerType::ExportXmltv { output, no_logos } = cli.worker {
+            assert!(output.is_none());
+            assert!(!no_logos);
+        } else {
+            panic!("Expected WorkerType::ExportXmltv");
+        }
+
+        let cli = Cli::parse_from(vec![
+            "app",
+            "export-xmltv",
+            "--output",
+            "/tmp/guide.xml",
+            "--no-logos",
+        ]);
+        if let WorkerType::ExportXmltv { output, no_logos } = cli.worker {
+            assert_eq!(output, Some(std::path::PathBuf::from("/tmp/guide.xml")));
+            assert!(no_logos);
+        } else {
+            panic!("Expected WorkerType::ExportXmltv");
+        }
+    }
+
+    #[test]
+    fn test_cli_xmltv_server() {
+        let cli = Cli::parse_from(vec!["app", "xmltv-server", "--listen", "127.0.0.1:9000"]);
+        if let WorkerType::XmltvServer { listen, no_logos } = cli.worker {
+            assert_eq!(
+                listen,
+                "127.0.0.1:9000".parse::<std::net::SocketAddr>().unwrap()
+            );
+            assert!(!no_logos);
+        } else {
+            panic!("Expected WorkerType::XmltvServer");
+        }
+    }
+
+    #[test]
+    fn test_cli_service_catalog() {
+        let cli = Cli::parse_from(vec!["app", "service-catalog", "--interval", "10m"]);
+        if let WorkerType::ServiceCatalog {
+            mirakc_url,
+            interval,
+        } = cli.worker
+        {
+            assert_eq!(mirakc_url, "http://localhost:40772");
+            assert_eq!(interval, std::time::Duration::from_secs(10 * 60));
+        } else {
+            panic!("Expected WorkerType::ServiceCatalog");
+        }
+    }
+
+    #[test]
+    fn test_cli_rules() {
+        let cli = Cli::parse_from(vec!["app", "rules", "apply", "rules.json"]);
+        if let WorkerType::Rules {
+            command: RulesCommand::Apply { file },
+        } = cli.worker
+        {
+            assert_eq!(file, std::path::PathBuf::from("rules.json"));
+        } else {
+            panic!("Expected RulesCommand::Apply");
+        }
+
+        let cli = Cli::parse_from(vec!["app", "rules", "delete", "gundam"]);
+        if let WorkerType::Rules {
+            command: RulesCommand::Delete { id },
+        } = cli.worker
+        {
+            assert_eq!(id, "gundam");
+        } else {
+            panic!("Expected RulesCommand::Delete");
+        }
+
+        let cli = Cli::parse_from(vec!["app", "rules", "preview", "gundam"]);
+        if let WorkerType::Rules {
+            command: RulesCommand::Preview { target },
+        } = cli.worker
+        {
+            assert_eq!(target, "gundam");
+        } else {
+            panic!("Expected RulesCommand::Preview");
+        }
+
+        let cli = Cli::parse_from(vec!["app", "rule-engine"]);
+        if let WorkerType::RuleEngine { mirakc_urls } = cli.worker {
+            assert_eq!(mirakc_urls, vec!["http://localhost:40772"]);
+        } else {
+            panic!("Expected WorkerType::RuleEngine");
+        }
+    }
+
+    #[test]
+    fn test_cli_series() {
+        let cli = Cli::parse_from(vec!["app", "series", "subscribe", "--program-id", "123"]);
+        if let WorkerType::Series {
+            command:
+                SeriesCommand::Subscribe {
+                    mirakc_url,
+                    program_id,
+                },
+        } = cli.worker
+        {
+            assert_eq!(mirakc_url, "http://localhost:40772");
+            assert_eq!(program_id, 123);
+        } else {
+            panic!("Expected SeriesCommand::Subscribe");
+        }
+
+        let cli = Cli::parse_from(vec!["app", "series", "unsubscribe", "32736_100"]);
+        if let WorkerType::Series {
+            command: SeriesCommand::Unsubscribe { id },
+        } = cli.worker
+        {
+            assert_eq!(id, "32736_100");
+        } else {
+            panic!("Expected SeriesCommand::Unsubscribe");
+        }
+    }
+
+    #[test]
+    fn test_cli_reconciler() {
+        let cli = Cli::parse_from(vec!["app", "reconciler"]);
+        if let WorkerType::Reconciler {
+            mirakc_urls,
+            interval,
+        } = cli.worker
+        {
+            assert_eq!(mirakc_urls, vec!["http://localhost:40772"]);
+            assert_eq!(interval, std::time::Duration::from_secs(5 * 60));
+        } else {
+            panic!("Expected WorkerType::Reconciler");
+        }
+
+        let cli = Cli::parse_from(vec![
+            "app",
+            "reconciler",
+            "--mirakc-url",
+            "http://tuner-a:40772",
+            "--mirakc-url",
+            "http://tuner-b:40772",
+            "--interval",
+            "1m",
+        ]);
+        if let WorkerType::Reconciler { mirakc_urls, .. } = cli.worker {
+            assert_eq!(
+                mirakc_urls,
+                vec!["http://tuner-a:40772", "http://tuner-b:40772"]
+            );
+        } else {
+            panic!("Expected WorkerType::Reconciler");
+        }
+    }
+
+    #[test]
+    fn test_cli_conflicts() {
+        let cli = Cli::parse_from(vec!["app", "conflicts"]);
+        assert!(matches!(cli.worker, WorkerType::Conflicts));
+    }
+
+    #[test]
+    fn test_cli_query_server() {
+        let cli = Cli::parse_from(vec!["app", "query-server"]);
+        assert!(matches!(cli.worker, WorkerType::QueryServer));
+    }
+
+    #[test]
+    fn test_cli_search_indexer() {
+        let cli = Cli::parse_from(vec!["app", "search-indexer"]);
+        if let WorkerType::SearchIndexer {
+            backend,
+            meilisearch_url,
+            meilisearch_api_key,
+            index_path,
+            dictionary,
+            rebuild,
+        } = cli.worker
+        {
+            assert_eq!(backend, SearchBackend::Meilisearch);
+            assert_eq!(meilisearch_url, "http://localhost:7700");
+            assert_eq!(meilisearch_api_key, None);
+            assert_eq!(index_path, std::path::PathBuf::from("./data/search"));
+            assert_eq!(dictionary, None);
+            assert!(!rebuild);
+        } else {
+            panic!("Expected WorkerType::SearchIndexer");
+        }
+
+        let cli = Cli::parse_from(vec![
+            "app",
+            "search-indexer",
+            "--backend",
+            "tantivy",
+            "--index-path",
+            "/var/lib/kurec/search",
+            "--rebuild",
+        ]);
+        if let WorkerType::SearchIndexer {
+            backend,
+            index_path,
+            rebuild,
+            ..
+        } = cli.worker
+        {
+            assert_eq!(backend, SearchBackend::Tantivy);
+            assert_eq!(
+                index_path,
+                std::path::PathBuf::from("/var/lib/kurec/search")
+            );
+            assert!(rebuild);
+        } else {
+            panic!("Expected WorkerType::SearchIndexer");
+        }
+    }
+
+    #[test]
+    fn test_cli_record_indexer() {
+        let cli = Cli::parse_from(vec![
+            "app",
+            "record-indexer",
+            "--backend",
+            "tantivy",
+            "--interval",
+            "30s",
+        ]);
+        if let WorkerType::RecordIndexer {
+            backend,
+            index_path,
+            interval,
+            rebuild,
+            ..
+        } = cli.worker
+        {
+            assert_eq!(backend, SearchBackend::Tantivy);
+            assert_eq!(index_path, std::path::PathBuf::from("./data/records"));
+            assert_eq!(interval, std::time::Duration::from_secs(30));
+            assert!(!rebuild);
+        } else {
+            panic!("Expected WorkerType::RecordIndexer");
+        }
+    }
+
+    #[test]
+    fn test_cli_ogp() {
+        let cli = Cli::parse_from(vec!["app", "ogp", "--negative-ttl", "6h"]);
+        if let WorkerType::Ogp {
+            width,
+            timeout,
+            refetch_ttl,
+            negative_ttl,
+        } = cli.worker
+        {
+            assert_eq!(width, 300);
+            assert_eq!(timeout, std::time::Duration::from_secs(10));
+            assert_eq!(
+                refetch_ttl,
+                std::time::Duration::from_secs(30 * 24 * 60 * 60)
+            );
+            assert_eq!(negative_ttl, std::time::Duration::from_secs(6 * 60 * 60));
+        } else {
+            panic!("Expected WorkerType::Ogp");
+        }
+    }
+
+    #[test]
+    fn test_cli_job_engine() {
+        let cli = Cli::parse_from(vec!["app", "job-engine", "--jobs", "/etc/kurec/jobs.json"]);
+        if let WorkerType::JobEngine { jobs } = cli.worker {
+            assert_eq!(jobs, std::path::PathBuf::from("/etc/kurec/jobs.json"));
+        } else {
+            panic!("Expected WorkerType::JobEngine");
+        }
+    }
+
+    #[test]
+    fn test_cli_encoder() {
+        let cli = Cli::parse_from(vec!["app", "encoder", "--timeout", "2h"]);
+        if let WorkerType::Encoder {
+            mirakc_urls,
+            work_dir,
+            shell,
+            timeout,
+            log_interval,
+            storages,
+        } = cli.worker
+        {
+            assert_eq!(mirakc_urls, vec!["http://localhost:40772"]);
+            assert_eq!(work_dir, std::path::PathBuf::from("./data/encode"));
+            assert_eq!(shell, "bash");
+            assert_eq!(timeout, std::time::Duration::from_secs(2 * 60 * 60));
+            assert_eq!(log_interval, std::time::Duration::from_secs(10));
+            assert_eq!(storages, std::path::PathBuf::from("storages.json"));
+        } else {
+            panic!("Expected WorkerType::Encoder");
+        }
+    }
+
+    #[test]
+    fn test_cli_original_saver() {
+        let cli = Cli::parse_from(vec![
+            "app",
+            "original-saver",
+            "--mirakc-url",
+            "http://tuner-a:40772",
+            "--retry-delay",
+            "1m",
+        ]);
+        if let WorkerType::OriginalSaver {
+            mirakc_urls,
+            storages,
+            max_retries,
+            retry_delay,
+            retry_reset_bytes,
+            progress_interval,
+        } = cli.worker
+        {
+            assert_eq!(mirakc_urls, vec!["http://tuner-a:40772"]);
+            assert_eq!(storages, std::path::PathBuf::from("storages.json"));
+            assert_eq!(max_retries, 5);
+            assert_eq!(retry_delay, std::time::Duration::from_secs(60));
+            assert_eq!(retry_reset_bytes, 16 * 1024 * 1024);
+            assert_eq!(progress_interval, std::time::Duration::from_secs(10));
+        } else {
+            panic!("Expected WorkerType::OriginalSaver");
+        }
+    }
+
+    #[test]
+    fn test_cli_record_deleter() {
+        let cli = Cli::parse_from(vec!["app", "record-deleter", "--dry-run"]);
+        if let WorkerType::RecordDeleter {
+            mirakc_urls,
+            storages,
+            grace_period,
+            interval,
+            dry_run,
+        } = cli.worker
+        {
+            assert_eq!(mirakc_urls, vec!["http://localhost:40772"]);
+            assert_eq!(storages, std::path::PathBuf::from("storages.json"));
+            assert_eq!(grace_period, std::time::Duration::from_secs(24 * 60 * 60));
+            assert_eq!(interval, std::time::Duration::from_secs(5 * 60));
+            assert!(dry_run);
+        } else {
+            panic!("Expected WorkerType::RecordDeleter");
+        }
+    }
 }
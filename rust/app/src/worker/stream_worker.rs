@@ -24,6 +24,10 @@ where
     async fn handle(&self, event: I, next: StreamNext<'_, I, O, E>) -> Result<Option<O>, E>;
 }
 
+/// ストリームハンドラ関数の型
+pub type StreamHandlerFn<I, O, E> =
+    Arc<dyn Fn(I) -> BoxFuture<'static, Result<Option<O>, E>> + Send + Sync + 'static>;
+
 /// ミドルウェアチェーンの次の処理を表す構造体
 pub struct StreamNext<'a, I, O, E>
 where
@@ -32,8 +36,7 @@ where
     E: ClassifyError + Send + Sync + 'static,
 {
     // ハンドラの型と戻り値を Option<O> に変更
-    pub(crate) handler:
-        Arc<dyn Fn(I) -> BoxFuture<'static, Result<Option<O>, E>> + Send + Sync + 'static>,
+    pub(crate) handler: StreamHandlerFn<I, O, E>,
     _phantom: PhantomData<&'a ()>,
 }
 
@@ -44,9 +47,7 @@ where
     E: ClassifyError + Send + Sync + 'static,
 {
     // ハンドラの型と戻り値を Option<O> に変更
-    pub fn new(
-        handler: Arc<dyn Fn(I) -> BoxFuture<'static, Result<Option<O>, E>> + Send + Sync + 'static>,
-    ) -> Self {
+    pub fn new(handler: StreamHandlerFn<I, O, E>) -> Self {
         Self {
             handler,
             _phantom: PhantomData,
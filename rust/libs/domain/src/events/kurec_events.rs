@@ -1,4 +1,11 @@
 use crate::event::Event;
+use crate::models::conflict::ConflictingSchedule;
+use crate::models::job::{JobDefinition, JobResult};
+use crate::models::onair::OnairProgram;
+use crate::models::record::RecordOutput;
+use crate::models::rule::MatchReason;
+use crate::models::service::Service;
+use chrono::{DateTime, Utc};
 use infra_macros::define_event_stream;
 use serde::{Deserialize, Serialize};
 
@@ -14,6 +21,241 @@ pub struct EpgStoredEvent {
 }
 impl Event for EpgStoredEvent {}
 
+/// サービスの放送中番組 (現在または次の番組) が変わったことを示すイベント。
+/// Web UI や通知ワーカーが現在の番組を把握するために使用される。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct NowPlayingChangedEvent {
+    /// 番組情報を取得したmirakcのベースURL
+    pub mirakc_url: String,
+    /// サービスID (Mirakurun Service ID)
+    pub service_id: i64,
+    /// 放送中の番組
+    pub current: Option<OnairProgram>,
+    /// 次に放送される番組
+    pub next: Option<OnairProgram>,
+}
+impl Event for NowPlayingChangedEvent {}
+
+/// 稼働中に mirakc のバージョンが変わったことを示すイベント。
+/// 定期的なバージョン確認で検出される。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct MirakcVersionChangedEvent {
+    /// 対象のmirakcのベースURL
+    pub mirakc_url: String,
+    /// 変更前のバージョン
+    pub previous: String,
+    /// 変更後のバージョン
+    pub current: String,
+    /// 変更後のバージョンがサポート範囲内かどうか
+    pub compatible: bool,
+}
+impl Event for MirakcVersionChangedEvent {}
+
+/// チャンネルスキャンなどでサービスが追加されたことを示すイベント。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct ServiceAddedEvent {
+    /// 追加されたサービス
+    pub service: Service,
+}
+impl Event for ServiceAddedEvent {}
+
+/// チャンネルスキャンなどでサービスが削除されたことを示すイベント。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct ServiceRemovedEvent {
+    /// 削除されたサービス (削除前の情報)
+    pub service: Service,
+}
+impl Event for ServiceRemovedEvent {}
+
+/// サービス名が変わったことを示すイベント。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct ServiceRenamedEvent {
+    /// 変更前のサービス名
+    pub previous_name: String,
+    /// 変更後のサービス
+    pub service: Service,
+}
+impl Event for ServiceRenamedEvent {}
+
+/// 自動録画ルールが追加・変更・削除されたことを示すイベント。
+/// ルールエンジンが保存済みの番組を再評価するために使用される。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct RecordingRulesChangedEvent {
+    /// 変更されたルールのID
+    pub rule_ids: Vec<String>,
+}
+impl Event for RecordingRulesChangedEvent {}
+
+/// 番組が自動録画ルールに新たに一致したことを示すイベント。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct RecordingRuleMatchedEvent {
+    /// 番組情報を取得したmirakcのベースURL
+    pub mirakc_url: String,
+    /// Mirakurun Program ID
+    pub program_id: i64,
+    /// 番組名
+    pub program_name: Option<String>,
+    /// ルールID
+    pub rule_id: String,
+    /// 一致した理由
+    pub reasons: Vec<MatchReason>,
+}
+impl Event for RecordingRuleMatchedEvent {}
+
+/// シリーズ録画で最終話が録画され、購読が完了したことを示すイベント。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct SeriesCompletedEvent {
+    /// 購読ID
+    pub subscription_id: String,
+    /// シリーズ名
+    pub name: String,
+    /// 録画済みの話数
+    pub recorded_episodes: Vec<i64>,
+}
+impl Event for SeriesCompletedEvent {}
+
+/// チューナーが足りずに録画予約が録画できないことを新たに検出したことを示すイベント。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct ScheduleConflictDetectedEvent {
+    /// 録画に使用するmirakcのベースURL
+    pub mirakc_url: String,
+    /// Mirakurun Program ID
+    pub program_id: i64,
+    /// 番組名
+    pub program_name: Option<String>,
+    /// チャンネルタイプ
+    pub channel_type: String,
+    /// 開始時刻
+    pub start_at: DateTime<Utc>,
+    /// 終了時刻
+    pub end_at: DateTime<Utc>,
+    /// 録画予約の優先度
+    pub priority: i32,
+    /// チューナーを使用している録画予約
+    pub blocked_by: Vec<ConflictingSchedule>,
+    /// 空きチューナーがある別の mirakc のベースURL
+    pub suggested_mirakc_url: Option<String>,
+}
+impl Event for ScheduleConflictDetectedEvent {}
+
+/// mirakc の録画予約と録画されるべき番組の差分 (ドリフト) を検出し、解消したことを示すイベント。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct ScheduleDriftDetectedEvent {
+    /// 対象のmirakcのベースURL
+    pub mirakc_url: String,
+    /// 録画予約がなかったため作成した番組の Program ID
+    pub created: Vec<i64>,
+    /// タグや優先度が異なっていたため作成し直した番組の Program ID
+    pub recreated: Vec<i64>,
+    /// 録画されるべきでなくなったため削除した番組の Program ID
+    pub deleted: Vec<i64>,
+    /// 作成または削除に失敗した番組の Program ID
+    pub failed: Vec<i64>,
+}
+impl Event for ScheduleDriftDetectedEvent {}
+
+/// 番組の詳細情報から OGP 画像を取得する URL が見つかったことを示すイベント。
+/// 番組検索インデックスに追加・更新した番組について発行され、OGP ワーカーが画像を取得する。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct ProgramUrlDiscoveredEvent {
+    /// 番組ID
+    pub program_id: i64,
+    /// OGP 画像を取得する URL
+    pub url: String,
+    /// URL の SHA-1 (Web UI が OGP 画像を取得するキー)
+    pub url_hash: String,
+}
+impl Event for ProgramUrlDiscoveredEvent {}
+
+/// 録画後処理のジョブを実行できるようになったことを示すイベント。
+/// ジョブエンジンが依存するジョブの成功後に発行し、ジョブの種類を処理するワーカー (エンコーダーなど) が実行する。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct JobDispatchedEvent {
+    /// 録画を行った mirakc のベースURL
+    pub mirakc_url: String,
+    /// mirakc の Record ID
+    pub record_id: String,
+    /// 実行するジョブの定義
+    pub job: JobDefinition,
+    /// 依存するジョブが保存した入力 (保存されていない mirakc の録画ファイルは含まない)
+    pub inputs: Vec<RecordOutput>,
+}
+impl Event for JobDispatchedEvent {}
+
+/// 録画後処理のジョブが終わったことを示すイベント。
+/// ジョブを実行したワーカーが発行し、ジョブエンジンが後続のジョブを実行する。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct JobCompletedEvent {
+    /// 録画を行った mirakc のベースURL
+    pub mirakc_url: String,
+    /// mirakc の Record ID
+    pub record_id: String,
+    /// ジョブ名
+    pub job: String,
+    /// ジョブの結果
+    pub result: JobResult,
+}
+impl Event for JobCompletedEvent {}
+
+/// エンコードジョブが失敗したことを示すイベント。
+/// エンコーダーが `JobCompletedEvent` と合わせて発行し、通知などに使用する。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct EncodeFailedEvent {
+    /// 録画を行った mirakc のベースURL
+    pub mirakc_url: String,
+    /// mirakc の Record ID
+    pub record_id: String,
+    /// ジョブ名
+    pub job: String,
+    /// スクリプトの終了コード (スクリプトを実行できなかった場合や強制終了した場合は `None`)
+    pub exit_code: Option<i32>,
+    /// タイムアウトで強制終了したかどうか
+    pub timed_out: bool,
+    /// 失敗した理由
+    pub error: String,
+    /// 保存したログの名前
+    pub log: Option<String>,
+}
+impl Event for EncodeFailedEvent {}
+
+/// mirakc の録画ファイルをストレージにコピーしている進捗を示すイベント。
+/// 元ファイル保存ワーカーがコピー中に一定間隔で発行し、Web UI などで進捗を表示する。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[define_event_stream(stream = "kurec-events")]
+pub struct RecordCopyProgressEvent {
+    /// 録画を行った mirakc のベースURL
+    pub mirakc_url: String,
+    /// mirakc の Record ID
+    pub record_id: String,
+    /// ジョブ名
+    pub job: String,
+    /// 保存先のストレージ名
+    pub storage: String,
+    /// ストレージ内の位置
+    pub location: String,
+    /// コピーしたサイズ (バイト)
+    pub copied_bytes: u64,
+    /// mirakc の録画情報のファイルサイズ (バイト)
+    pub total_bytes: u64,
+    /// 接続が切れて再開した回数
+    pub resumed: u32,
+}
+impl Event for RecordCopyProgressEvent {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -30,4 +272,50 @@ mod tests {
 
         assert_eq!(event, deserialized);
     }
+
+    #[test]
+    fn test_now_playing_changed_event_serialization_deserialization() {
+        let event = NowPlayingChangedEvent {
+            mirakc_url: "http://mirakc.local:40772".to_string(),
+            service_id: 3273601024,
+            current: Some(OnairProgram {
+                program_id: 327360102412345,
+                event_id: 12345,
+                name: Some("ニュース".to_string()),
+                start_at: chrono::DateTime::from_timestamp(1735722000, 0).unwrap(),
+                duration_millis: 1800000,
+            }),
+            next: None,
+        };
+
+        let serialized = serde_json::to_string(&event).unwrap();
+        let deserialized: NowPlayingChangedEvent = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(event, deserialized);
+    }
+
+    #[test]
+    fn test_service_renamed_event_serialization_deserialization() {
+        let event = ServiceRenamedEvent {
+            previous_name: "ＮＨＫ総合".to_string(),
+            service: Service {
+                mirakc_url: "http://mirakc.local:40772".to_string(),
+                id: 3273601024,
+                service_id: 1024,
+                network_id: 32736,
+                name: "ＮＨＫ総合１・東京".to_string(),
+                service_type: 1,
+                logo_id: Some(0),
+                remote_control_key_id: Some(1),
+                has_logo_data: true,
+                channel_type: "GR".to_string(),
+                channel: "27".to_string(),
+            },
+        };
+
+        let serialized = serde_json::to_string(&event).unwrap();
+        let deserialized: ServiceRenamedEvent = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(event, deserialized);
+    }
 }
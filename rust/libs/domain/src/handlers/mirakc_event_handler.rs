@@ -7,7 +7,7 @@ use anyhow::Result; // Context は未使用なので削除
 use serde::Deserialize; // Deserialize をインポート
 use shared_core::error_handling::{ClassifyError, ErrorAction};
 use std::sync::Arc;
-use tracing::{debug, error, info};
+use tracing::{debug, info};
 
 // ローカルの MirakcEventInput 定義は削除
 
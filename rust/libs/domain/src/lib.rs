@@ -8,4 +8,5 @@ pub mod events; // イベント構造体を定義するモジュール
 pub mod handlers; // 追加
 pub mod models;
 pub mod ports;
+pub mod query;
 pub mod usecases;
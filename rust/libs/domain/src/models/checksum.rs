@@ -0,0 +1,98 @@
+//! 録画ファイルや出力のチェックサム
+//!
+//! ストレージに保存する内容のサイズと SHA-256 を、ストリームを流しながら計算します。
+//! 保存した出力の `RecordOutput::sha256` に記録し、mirakc の録画を削除する前の検証に使います。
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use bytes::Bytes;
+use futures::stream::{BoxStream, StreamExt, TryStreamExt};
+use sha2::{Digest, Sha256};
+
+/// 内容のサイズと SHA-256
+#[derive(Debug, Clone, Default)]
+pub struct Checksum {
+    hasher: Sha256,
+    size: u64,
+}
+
+impl Checksum {
+    /// 内容を追加する。
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+        self.size += data.len() as u64;
+    }
+
+    /// これまでに追加した内容のサイズ (バイト)
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// これまでに追加した内容の SHA-256 (16進数の小文字)
+    pub fn sha256(&self) -> String {
+        self.hasher
+            .clone()
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// ストリームの内容を最後まで読み、チェックサムを計算する。
+    pub async fn of_stream(mut data: BoxStream<'static, Result<Bytes>>) -> Result<Self> {
+        let mut checksum = Self::default();
+        while let Some(chunk) = data.try_next().await? {
+            checksum.update(&chunk);
+        }
+        Ok(checksum)
+    }
+
+    /// 流れた内容のチェックサムを計算するストリームを作成する。
+    ///
+    /// チェックサムはストリームを読み終えてから取り出す。
+    pub fn tap(
+        data: BoxStream<'static, Result<Bytes>>,
+    ) -> (BoxStream<'static, Result<Bytes>>, Arc<Mutex<Self>>) {
+        let checksum = Arc::new(Mutex::new(Self::default()));
+        let shared = checksum.clone();
+        let data = data
+            .inspect_ok(move |chunk| shared.lock().unwrap().update(chunk))
+            .boxed();
+        (data, checksum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream;
+
+    #[tokio::test]
+    async fn test_checksum() -> Result<()> {
+        assert_eq!(
+            Checksum::default().sha256(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+
+        let chunks = || {
+            stream::iter(vec![
+                Ok(Bytes::from_static(b"a")),
+                Ok(Bytes::from_static(b"bc")),
+            ])
+            .boxed()
+        };
+        let checksum = Checksum::of_stream(chunks()).await?;
+        assert_eq!(checksum.size(), 3);
+        assert_eq!(
+            checksum.sha256(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        let (data, tapped) = Checksum::tap(chunks());
+        let read: Vec<Bytes> = data.try_collect().await?;
+        assert_eq!(read.concat(), b"abc");
+        assert_eq!(tapped.lock().unwrap().sha256(), checksum.sha256());
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+//! 録画予約の競合のドメインモデル
+//!
+//! 録画されるべき番組 (`DesiredSchedule`) を mirakc ごとのチューナーに割り当て、
+//! チューナーが足りずに録画できない番組を優先度の低いものから選びます。
+//! 録画できない番組には、同じサービスを受信でき、空きチューナーがある別の mirakc を提案します。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::schedule::DesiredSchedule;
+use crate::models::service::Service;
+use crate::models::tuner::TunerStatus;
+
+/// 競合の相手 (同じ時間帯にチューナーを使用する録画予約)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConflictingSchedule {
+    /// 録画に使用するmirakcのベースURL
+    pub mirakc_url: String,
+    /// Mirakurun Program ID
+    pub program_id: i64,
+    /// 番組名
+    pub name: Option<String>,
+    /// 優先度
+    pub priority: i32,
+    /// 開始時刻
+    pub start_at: DateTime<Utc>,
+    /// 終了時刻
+    pub end_at: DateTime<Utc>,
+}
+
+impl ConflictingSchedule {
+    fn from_schedule(schedule: &DesiredSchedule) -> Self {
+        Self {
+            mirakc_url: schedule.mirakc_url.clone(),
+            program_id: schedule.program_id,
+            name: schedule.name.clone(),
+            priority: schedule.priority(),
+            start_at: schedule.start_at,
+            end_at: schedule.end_at(),
+        }
+    }
+}
+
+/// チューナーが足りずに録画できない録画予約
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleConflict {
+    /// 録画できない録画予約
+    pub schedule: DesiredSchedule,
+    /// チューナーを使用している録画予約。対応するチューナーがない場合は空
+    pub blocked_by: Vec<ConflictingSchedule>,
+    /// 同じサービスを受信でき、空きチューナーがある別の mirakc のベースURL
+    pub suggested_mirakc_url: Option<String>,
+}
+
+/// 録画予約のチューナーへの割り当て
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TunerAssignment {
+    /// 録画に使用するmirakcのベースURL
+    pub mirakc_url: String,
+    /// Mirakurun Program ID
+    pub program_id: i64,
+    /// チューナーのインデックス
+    pub tuner_index: i64,
+}
+
+/// 競合の分析結果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConflictAnalysis {
+    /// チューナーに割り当てられた録画予約
+    pub assignments: Vec<TunerAssignment>,
+    /// 録画できない録画予約 (優先度の高い順)
+    pub conflicts: Vec<ScheduleConflict>,
+}
+
+/// チューナーごとの割り当て状況
+struct TunerPlan<'a> {
+    tuner: &'a TunerStatus,
+    /// 割り当てた録画予約 (`schedules` のインデックス)
+    booked: Vec<usize>,
+}
+
+impl TunerPlan<'_> {
+    fn overlapping<'a>(
+        &'a self,
+        schedules: &'a [DesiredSchedule],
+        schedule: &DesiredSchedule,
+    ) -> impl Iterator<Item = &'a DesiredSchedule> + 'a {
+        let (start_at, end_at) = (schedule.start_at, schedule.end_at());
+        self.booked
+            .iter()
+            .map(move |&i| &schedules[i])
+            .filter(move |s| s.start_at < end_at && start_at < s.end_at())
+    }
+
+    fn accepts(&self, schedules: &[DesiredSchedule], schedule: &DesiredSchedule) -> bool {
+        self.tuner.supports(&schedule.channel_type)
+            && self.overlapping(schedules, schedule).next().is_none()
+    }
+}
+
+/// 録画予約をチューナーに割り当て、録画できない録画予約を求める。
+///
+/// 優先度の高い順 (同じ優先度では開始時刻の早い順) に、録画予約の mirakc のチューナーへ割り当てる。
+/// 対応するチャンネルタイプが少ないチューナーから使い、多くのタイプに対応するチューナーを残す。
+/// mirakc 以外の利用者 (視聴など) によるチューナーの使用は考慮しない。
+pub fn analyze_conflicts(
+    schedules: &[DesiredSchedule],
+    tuners: &[TunerStatus],
+    services: &[Service],
+) -> ConflictAnalysis {
+    let mut order: Vec<usize> = (0..schedules.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&schedules[a], &schedules[b]);
+        b.priority()
+            .cmp(&a.priority())
+            .then(a.start_at.cmp(&b.start_at))
+            .then_with(|| a.mirakc_url.cmp(&b.mirakc_url))
+            .then(a.program_id.cmp(&b.program_id))
+    });
+
+    let mut plans: Vec<TunerPlan> = tuners
+        .iter()
+        .map(|tuner| TunerPlan {
+            tuner,
+            booked: Vec::new(),
+        })
+        .collect();
+    plans.sort_by(|a, b| {
+        a.tuner
+            .types
+            .len()
+            .cmp(&b.tuner.types.len())
+            .then_with(|| a.tuner.mirakc_url.cmp(&b.tuner.mirakc_url))
+            .then(a.tuner.index.cmp(&b.tuner.index))
+    });
+
+    let mut analysis = ConflictAnalysis::default();
+    let mut losers = Vec::new();
+    for i in order {
+        let schedule = &schedules[i];
+        let plan = plans
+            .iter_mut()
+            .find(|p| p.tuner.mirakc_url == schedule.mirakc_url && p.accepts(schedules, schedule));
+        match plan {
+            Some(plan) => {
+                plan.booked.push(i);
+                analysis.assignments.push(TunerAssignment {
+                    mirakc_url: schedule.mirakc_url.clone(),
+                    program_id: schedule.program_id,
+                    tuner_index: plan.tuner.index,
+                });
+            }
+            None => {
+                let mut blocked_by: Vec<ConflictingSchedule> = plans
+                    .iter()
+                    .filter(|p| {
+                        p.tuner.mirakc_url == schedule.mirakc_url
+                            && p.tuner.supports(&schedule.channel_type)
+                    })
+                    .flat_map(|p| p.overlapping(schedules, schedule))
+                    .map(ConflictingSchedule::from_schedule)
+                    .collect();
+                blocked_by.sort_by_key(|s| (s.start_at, s.program_id));
+                blocked_by.dedup();
+                losers.push((i, blocked_by));
+            }
+        }
+    }
+
+    // 割り当てが終わった後の空きチューナーで、録画できない録画予約の移動先を探す
+    for (i, blocked_by) in losers {
+        let schedule = &schedules[i];
+        let receivable = |url: &str| {
+            services
+                .iter()
+                .any(|s| s.mirakc_url == url && s.id == schedule.service_id)
+        };
+        let suggested_mirakc_url = plans
+            .iter_mut()
+            .find(|p| {
+                p.tuner.mirakc_url != schedule.mirakc_url
+                    && receivable(&p.tuner.mirakc_url)
+                    && p.accepts(schedules, schedule)
+            })
+            .map(|plan| {
+                // 同じ空きを複数の録画予約に提案しないように予約しておく
+                plan.booked.push(i);
+                plan.tuner.mirakc_url.clone()
+            });
+        analysis.conflicts.push(ScheduleConflict {
+            schedule: schedule.clone(),
+            blocked_by,
+            suggested_mirakc_url,
+        });
+    }
+    analysis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::rule::{MatchReason, RuleMatch};
+    use chrono::TimeZone;
+
+    const TUNER_A: &str = "http://tuner-a:40772";
+    const TUNER_B: &str = "http://tuner-b:40772";
+
+    fn tuner(mirakc_url: &str, index: i64, types: &[&str]) -> TunerStatus {
+        let at = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        TunerStatus {
+            mirakc_url: mirakc_url.to_string(),
+            index,
+            name: format!("tuner{}", index),
+            types: types.iter().map(|t| t.to_string()).collect(),
+            in_use: false,
+            users: vec![],
+            command: None,
+            since: at,
+            updated_at: at,
+        }
+    }
+
+    fn schedule(program_id: i64, channel_type: &str, hour: u32, priority: i32) -> DesiredSchedule {
+        DesiredSchedule {
+            mirakc_url: TUNER_A.to_string(),
+            program_id,
+            service_id: 3273601024,
+            channel_type: channel_type.to_string(),
+            name: Some(format!("番組{}", program_id)),
+            start_at: Utc.with_ymd_and_hms(2025, 1, 4, hour, 0, 0).unwrap(),
+            duration_millis: 3600000,
+            rule_matches: vec![RuleMatch {
+                rule_id: format!("rule{}", priority),
+                rule_name: format!("rule{}", priority),
+                priority,
+                reasons: vec![MatchReason::Keyword {
+                    keyword: "番組".to_string(),
+                }],
+            }],
+            manual: false,
+            series: None,
+        }
+    }
+
+    fn service(mirakc_url: &str) -> Service {
+        Service {
+            mirakc_url: mirakc_url.to_string(),
+            id: 3273601024,
+            service_id: 1024,
+            network_id: 32736,
+            name: "ＮＨＫ総合１・東京".to_string(),
+            service_type: 1,
+            logo_id: None,
+            remote_control_key_id: Some(1),
+            has_logo_data: false,
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_lowest_priority_loses() {
+        let tuners = [tuner(TUNER_A, 0, &["GR"]), tuner(TUNER_A, 1, &["GR"])];
+        let schedules = [
+            schedule(1, "GR", 9, 1),
+            schedule(2, "GR", 9, 10),
+            schedule(3, "GR", 9, 5),
+            // 重ならない時間帯はチューナーを使い回せる
+            schedule(4, "GR", 10, 0),
+        ];
+        let analysis = analyze_conflicts(&schedules, &tuners, &[]);
+        assert_eq!(analysis.assignments.len(), 3);
+        assert_eq!(analysis.conflicts.len(), 1);
+
+        let conflict = &analysis.conflicts[0];
+        assert_eq!(conflict.schedule.program_id, 1);
+        let blockers: Vec<_> = conflict.blocked_by.iter().map(|s| s.program_id).collect();
+        assert_eq!(blockers, vec![2, 3]);
+        assert_eq!(conflict.suggested_mirakc_url, None);
+    }
+
+    #[test]
+    fn test_prefers_specialized_tuners() {
+        let tuners = [
+            tuner(TUNER_A, 0, &["GR", "BS", "CS"]),
+            tuner(TUNER_A, 1, &["GR"]),
+        ];
+        let schedules = [schedule(1, "GR", 9, 10), schedule(2, "BS", 9, 1)];
+        let analysis = analyze_conflicts(&schedules, &tuners, &[]);
+        assert!(analysis.conflicts.is_empty());
+        assert!(analysis.assignments.contains(&TunerAssignment {
+            mirakc_url: TUNER_A.to_string(),
+            program_id: 1,
+            tuner_index: 1,
+        }));
+    }
+
+    #[test]
+    fn test_no_tuner_for_channel_type() {
+        let tuners = [tuner(TUNER_A, 0, &["GR"])];
+        let analysis = analyze_conflicts(&[schedule(1, "CS", 9, 1)], &tuners, &[]);
+        assert_eq!(analysis.conflicts.len(), 1);
+        assert!(analysis.conflicts[0].blocked_by.is_empty());
+    }
+
+    #[test]
+    fn test_suggests_other_mirakc() {
+        let tuners = [tuner(TUNER_A, 0, &["GR"]), tuner(TUNER_B, 0, &["GR"])];
+        let schedules = [
+            schedule(1, "GR", 9, 10),
+            schedule(2, "GR", 9, 5),
+            schedule(3, "GR", 9, 1),
+        ];
+
+        // 別の mirakc でサービスを受信できない場合は提案しない
+        let analysis = analyze_conflicts(&schedules, &tuners, &[service(TUNER_A)]);
+        assert!(analysis
+            .conflicts
+            .iter()
+            .all(|c| c.suggested_mirakc_url.is_none()));
+
+        let services = [service(TUNER_A), service(TUNER_B)];
+        let analysis = analyze_conflicts(&schedules, &tuners, &services);
+        let suggestions: Vec<_> = analysis
+            .conflicts
+            .iter()
+            .map(|c| (c.schedule.program_id, c.suggested_mirakc_url.as_deref()))
+            .collect();
+        // 空きは1つなので、優先度の高い録画予約にだけ提案する
+        assert_eq!(suggestions, vec![(2, Some(TUNER_B)), (3, None)]);
+    }
+}
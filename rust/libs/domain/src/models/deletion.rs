@@ -0,0 +1,52 @@
+//! mirakc の録画の削除の監査記録
+//!
+//! 削除ワーカーが mirakc の録画を削除した (dry-run では削除しようとした) 録画ごとに、
+//! 削除した録画ファイルと、削除する前に検証した保存済みのコピーを記録します。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 削除する前に検証した、保存済みのコピー
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifiedCopy {
+    /// 出力名
+    pub name: String,
+    /// 保存先のストレージ名
+    pub storage: String,
+    /// ストレージ内の位置
+    pub location: String,
+    /// 読み直したサイズ (バイト)
+    pub size: u64,
+    /// 読み直した内容の SHA-256 (16進数の小文字)
+    pub sha256: String,
+}
+
+/// mirakc の録画を削除した記録
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordDeletion {
+    /// 録画を行った mirakc のベースURL
+    pub mirakc_url: String,
+    /// mirakc の Record ID
+    pub record_id: String,
+    /// 番組名
+    pub program_name: Option<String>,
+    /// 録画開始時刻
+    pub recording_start_time: DateTime<Utc>,
+    /// 削除した mirakc 上のファイルパス
+    pub content_path: String,
+    /// 削除した録画ファイルのサイズ (バイト)
+    pub content_length: u64,
+    /// すべてのジョブが成功した (削除ジョブを送った) 時刻
+    pub jobs_succeeded_at: DateTime<Utc>,
+    /// 削除した時刻
+    pub deleted_at: DateTime<Utc>,
+    /// dry-run で、実際には削除していないかどうか
+    pub dry_run: bool,
+    /// mirakc に削除を要求する前に保存した記録で、削除の完了をまだ確認していないかどうか
+    #[serde(default)]
+    pub pending: bool,
+    /// 削除した理由
+    pub reason: String,
+    /// 削除する前に検証したコピー
+    pub copies: Vec<VerifiedCopy>,
+}
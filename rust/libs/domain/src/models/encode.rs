@@ -0,0 +1,222 @@
+//! エンコードジョブのドメインモデル
+//!
+//! エンコードジョブのスクリプトは `{{title}}` のような変数を含むテンプレートで、
+//! 録画レコードのメタデータ (`EncodeVariables`) で展開してから実行します。
+//! 変数の値はシェルの単一引用符で囲むため、番組名に記号が含まれていてもそのまま引数として渡せます。
+//! 同じ値は `KUREC_TITLE` のような環境変数でもスクリプトに渡します。
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+
+use crate::models::record::Record;
+use crate::models::rule::jst;
+
+/// mirakc の録画ファイル (`original`) を作業ディレクトリに書き出すファイル名
+pub const ORIGINAL_INPUT_FILE: &str = "input.ts";
+
+/// 作業ディレクトリに書き出すスクリプトのファイル名
+pub const SCRIPT_FILE: &str = "encode.sh";
+
+/// 作業ディレクトリに書き出すログのファイル名
+pub const LOG_FILE: &str = "encode.log";
+
+/// スクリプトの実行に使う作業ディレクトリのファイル名 (出力名には使えない)
+pub const WORK_FILES: [&str; 3] = [ORIGINAL_INPUT_FILE, SCRIPT_FILE, LOG_FILE];
+
+/// スクリプトに渡す録画レコードのメタデータ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodeVariables {
+    values: BTreeMap<&'static str, String>,
+}
+
+impl EncodeVariables {
+    /// 録画レコードから変数を作成する。
+    ///
+    /// 日時は日本時間で、話数が不明な場合は空文字列。
+    pub fn from_record(record: &Record) -> Self {
+        let program = &record.program;
+        let start_at = jst(program.start_at);
+        let episode = program.episode();
+        let values = BTreeMap::from([
+            ("record_id", record.id.clone()),
+            ("mirakc_url", record.mirakc_url.clone()),
+            ("program_id", program.id.to_string()),
+            ("service_id", program.service_id.to_string()),
+            ("service", program.channel_name.clone()),
+            ("channel", program.channel.clone()),
+            ("title", program.name.clone().unwrap_or_default()),
+            (
+                "episode",
+                if episode > 0 {
+                    episode.to_string()
+                } else {
+                    String::new()
+                },
+            ),
+            ("start_time", start_at.to_rfc3339()),
+            ("date", start_at.format("%Y%m%d").to_string()),
+        ]);
+        Self { values }
+    }
+
+    /// スクリプトに渡す環境変数 (`KUREC_` + 変数名の大文字)
+    pub fn env(&self) -> Vec<(String, String)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (format!("KUREC_{}", name.to_uppercase()), value.clone()))
+            .collect()
+    }
+
+    /// テンプレートの `{{name}}` を、シェルの単一引用符で囲んだ変数の値に置き換える。
+    ///
+    /// 存在しない変数や閉じていない `{{` はエラーにする。
+    pub fn render(&self, template: &str) -> Result<String> {
+        let mut rendered = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            rendered.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find("}}") else {
+                bail!("スクリプトの {{{{ が閉じていません");
+            };
+            let name = rest[start + 2..start + end].trim();
+            let Some(value) = self.values.get(name) else {
+                bail!("スクリプトの変数 {} はありません", name);
+            };
+            rendered.push_str(&shell_quote(value));
+            rest = &rest[start + end + 2..];
+        }
+        rendered.push_str(rest);
+        Ok(rendered)
+    }
+}
+
+/// シェルの単一引用符で囲む。
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// エンコーダーが実行するスクリプト
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodeTask {
+    /// 録画を行った mirakc のベースURL
+    pub mirakc_url: String,
+    /// mirakc の Record ID
+    pub record_id: String,
+    /// ジョブ名
+    pub job: String,
+    /// 変数を展開したスクリプト
+    pub script: String,
+    /// スクリプトに渡す環境変数
+    pub env: Vec<(String, String)>,
+    /// スクリプトが作業ディレクトリに作成する出力のファイル名
+    pub outputs: Vec<String>,
+    /// これを超えるとスクリプトを強制終了する
+    pub timeout: Duration,
+}
+
+/// スクリプトの実行結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodeExecution {
+    /// 終了コード (強制終了した場合やシグナルで終了した場合は `None`)
+    pub exit_code: Option<i32>,
+    /// タイムアウトで強制終了したかどうか
+    pub timed_out: bool,
+    /// 作成された出力 (`EncodeTask::outputs` のうち、ファイルが存在したもの)
+    pub outputs: Vec<EncodedFile>,
+}
+
+impl EncodeExecution {
+    /// スクリプトが正常に終了したかどうか
+    pub fn succeeded(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0)
+    }
+}
+
+/// スクリプトが作成した出力ファイル
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedFile {
+    /// 出力名 (ファイル名)
+    pub name: String,
+    /// ファイルの位置
+    pub location: String,
+    /// ファイルサイズ (バイト)
+    pub size: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::mirakc_events::RecordingStatus;
+    use crate::models::epg::KurecProgram;
+    use crate::models::record::{RecordContent, RecordingInfo};
+    use chrono::{TimeZone, Utc};
+
+    fn record(name: &str) -> Record {
+        let start_at = Utc.with_ymd_and_hms(2025, 1, 1, 15, 30, 0).unwrap();
+        Record {
+            id: "0000000000000001".to_string(),
+            mirakc_url: "http://tuner:40772".to_string(),
+            program: KurecProgram {
+                id: 327360102401010,
+                mirakc_url: "http://tuner:40772".to_string(),
+                service_id: 1024,
+                network_id: 32736,
+                event_id: 1010,
+                channel_name: "ＮＨＫ総合１・東京".to_string(),
+                channel_type: "GR".to_string(),
+                channel: "27".to_string(),
+                name: Some(name.to_string()),
+                description: None,
+                extended: None,
+                start_at,
+                duration_millis: 1800000,
+                is_free: true,
+                genres: vec![],
+                video_info: None,
+                audio_infos: vec![],
+                series_info: None,
+                markers: Default::default(),
+                episode_number: Some(12),
+            },
+            recording: RecordingInfo {
+                status: RecordingStatus::Finished,
+                start_time: start_at,
+                end_time: None,
+                duration_millis: None,
+                failed_reason: None,
+            },
+            content: RecordContent {
+                path: "1.m2ts".to_string(),
+                content_type: "video/MP2T".to_string(),
+                length: Some(1024),
+            },
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let variables = EncodeVariables::from_record(&record("アニメ「彼女's」#12"));
+        assert_eq!(
+            variables
+                .render("ffmpeg -i input.ts -metadata title={{title}} -metadata date={{ date }} {{episode}}.mp4")
+                .unwrap(),
+            r"ffmpeg -i input.ts -metadata title='アニメ「彼女'\''s」#12' -metadata date='20250102' '12'.mp4"
+        );
+        assert_eq!(
+            variables.render("echo {{start_time}}").unwrap(),
+            "echo '2025-01-02T00:30:00+09:00'"
+        );
+        assert!(variables.render("echo {{unknown}}").is_err());
+        assert!(variables.render("echo {{title").is_err());
+
+        let env = variables.env();
+        assert!(env.contains(&(
+            "KUREC_SERVICE".to_string(),
+            "ＮＨＫ総合１・東京".to_string()
+        )));
+        assert!(env.contains(&("KUREC_EPISODE".to_string(), "12".to_string())));
+    }
+}
@@ -1,6 +1,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::models::extended::{parse_extended, ProgramDetails};
+use crate::models::title::BroadcastMarkers;
+
 /// ビデオタイプの列挙型
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum VideoType {
@@ -101,6 +104,32 @@ pub struct KurecProgram {
     pub audio_infos: Vec<String>,
     /// シリーズ情報 (変換後)
     pub series_info: Option<KurecSeriesInfo>,
+    /// 番組名に含まれる放送マーカー (【字】【再】など)
+    #[serde(default)]
+    pub markers: BroadcastMarkers,
+    /// 番組名から取り出した話数 (`#12`、`第12話`、`(12)`)
+    #[serde(default)]
+    pub episode_number: Option<i64>,
+}
+
+impl KurecProgram {
+    /// 話数。シリーズ情報の話数、なければ番組名から取り出した話数 (不明な場合は 0)
+    pub fn episode(&self) -> i64 {
+        self.series_info
+            .as_ref()
+            .map(|s| s.episode)
+            .filter(|&episode| episode > 0)
+            .or(self.episode_number)
+            .unwrap_or(0)
+    }
+
+    /// 詳細情報を解析した出演者・スタッフ・URL など
+    pub fn details(&self) -> ProgramDetails {
+        self.extended
+            .as_ref()
+            .map(parse_extended)
+            .unwrap_or_default()
+    }
 }
 
 /// Kurecで扱うシリーズ情報
@@ -151,6 +180,8 @@ mod tests {
                 last_episode: 10i64,
                 name: "テストシリーズ".to_string(),
             }),
+            markers: Default::default(),
+            episode_number: None,
         };
 
         let serialized = serde_json::to_string(&program).unwrap();
@@ -0,0 +1,387 @@
+//! 番組の詳細情報 (`extended`) の解析
+//!
+//! mirakc の番組の詳細情報は「番組内容」「出演者」「原作」などの見出しと本文の組です。
+//! 本文を番組内容・出演者 (役名付き)・スタッフ・URL に分け、検索のファセットやルールの条件に使えるようにします。
+//! EPG の本文は一定の長さで改行されるため、読点や閉じていない括弧で終わる行は次の行とつなげて扱います。
+
+use linkify::{LinkFinder, LinkKind};
+use serde::{Deserialize, Serialize};
+
+use crate::models::title::nfkc;
+
+/// 出演者
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CastMember {
+    /// 名前
+    pub name: String,
+    /// 役名・担当 (`アムロ・レイ`、`声`、`ナレーション` など)
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+/// スタッフ
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaffCredit {
+    /// 担当 (`原作`、`脚本`、`音楽` など)
+    pub role: String,
+    /// 名前
+    pub name: String,
+}
+
+/// 番組内容・出演者・スタッフのいずれでもない見出しと本文
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtendedSection {
+    /// 見出し
+    pub heading: String,
+    /// 本文
+    pub text: String,
+}
+
+/// 番組の詳細情報を解析した結果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramDetails {
+    /// 番組内容
+    pub content: Option<String>,
+    /// 出演者
+    pub cast: Vec<CastMember>,
+    /// スタッフ
+    pub staff: Vec<StaffCredit>,
+    /// 本文に含まれる URL (スキームがない場合は `https://` を補う)
+    pub urls: Vec<String>,
+    /// その他の見出しと本文
+    pub sections: Vec<ExtendedSection>,
+}
+
+/// 見出しの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Heading {
+    Content,
+    Cast,
+    Staff,
+    Other,
+}
+
+const CONTENT_WORDS: &[&str] = &["内容", "あらすじ", "みどころ"];
+const CAST_WORDS: &[&str] = &[
+    "出演",
+    "キャスト",
+    "声",
+    "ゲスト",
+    "語り",
+    "ナレーション",
+    "ナレーター",
+    "司会",
+];
+/// 見出しそのものを出演者の担当とする見出し
+const CAST_ROLE_HEADINGS: &[&str] = &["ゲスト", "語り", "ナレーション", "ナレーター", "司会"];
+const STAFF_WORDS: &[&str] = &[
+    "原作",
+    "脚本",
+    "脚色",
+    "音楽",
+    "制作",
+    "製作",
+    "監督",
+    "演出",
+    "プロデューサー",
+    "スタッフ",
+    "作詞",
+    "作曲",
+    "編曲",
+    "主題歌",
+    "構成",
+    "企画",
+    "撮影",
+    "美術",
+    "デザイン",
+    "著作",
+    "編集",
+];
+
+fn classify(heading: &str) -> Heading {
+    let has = |words: &[&str]| words.iter().any(|w| heading.contains(w));
+    if has(CONTENT_WORDS) {
+        Heading::Content
+    } else if has(CAST_WORDS) {
+        Heading::Cast
+    } else if has(STAFF_WORDS) {
+        Heading::Staff
+    } else {
+        Heading::Other
+    }
+}
+
+/// 括弧が閉じていないかどうか
+fn has_open_bracket(s: &str) -> bool {
+    [('(', ')'), ('「', '」'), ('【', '】'), ('[', ']')]
+        .iter()
+        .any(|&(open, close)| s.matches(open).count() > s.matches(close).count())
+}
+
+/// 本文を論理的な行に分ける。
+///
+/// 読点・区切り記号で終わる行、括弧が閉じていない行、閉じ括弧や読点で始まる行は前後の行とつなげる。
+fn logical_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut continues = false;
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            continues = false;
+            continue;
+        }
+        let joins = continues || line.starts_with([')', '」', '】', ']', '、', ',']);
+        match lines.last_mut() {
+            Some(last) if joins => last.push_str(line),
+            _ => lines.push(line.to_string()),
+        }
+        let last = lines.last().unwrap();
+        continues = last.ends_with(['、', ',', '・', ':', '/']) || has_open_bracket(last);
+    }
+    lines
+}
+
+/// 役名と名前の区切り (`役名:名前`、`役名...名前`) で分ける。
+fn split_role(line: &str) -> Option<(&str, &str)> {
+    ["...", "..", ":", "="].iter().find_map(|sep| {
+        line.split_once(sep)
+            .map(|(role, names)| (role.trim(), names.trim()))
+            .filter(|(role, names)| !role.is_empty() && !names.is_empty())
+    })
+}
+
+/// 行頭の `【声】`・`[ゲスト]`・`<司会>` を担当として取り出す。
+fn split_bracket_role(line: &str) -> Option<(&str, &str)> {
+    let close = match line.chars().next()? {
+        '【' => '】',
+        '[' => ']',
+        '<' => '>',
+        '〈' => '〉',
+        '≪' => '≫',
+        _ => return None,
+    };
+    let open_len = line.chars().next()?.len_utf8();
+    let end = line.find(close)?;
+    let role = line[open_len..end].trim();
+    (!role.is_empty()).then(|| (role, line[end + close.len_utf8()..].trim()))
+}
+
+/// 名前の一覧を分ける。`名前(役名)` の形式は役名を取り出し、`ほか` は取り除く。
+fn split_names(names: &str) -> Vec<(String, Option<String>)> {
+    names
+        .split([',', '、', '/', ';'])
+        .filter_map(|name| {
+            let name = name.trim();
+            let name = name
+                .strip_suffix("ほか")
+                .or_else(|| name.strip_suffix("他"))
+                .unwrap_or(name)
+                .trim();
+            if name.is_empty() {
+                return None;
+            }
+            match name.strip_suffix(')').and_then(|n| n.split_once('(')) {
+                Some((name, role)) if !name.trim().is_empty() => {
+                    Some((name.trim().to_string(), Some(role.trim().to_string())))
+                }
+                _ => Some((name.to_string(), None)),
+            }
+        })
+        .collect()
+}
+
+/// 1行に `役名:名前` が複数並んでいる場合 (`アムロ:古谷徹 シャア:池田秀一`) は組ごとに分ける。
+fn split_pairs(line: &str) -> Vec<String> {
+    if line.matches(':').count() <= 1 {
+        return vec![line.to_string()];
+    }
+    let mut groups: Vec<String> = Vec::new();
+    for token in line.split_whitespace() {
+        match groups.last_mut() {
+            Some(group) if !token.contains(':') => {
+                group.push(' ');
+                group.push_str(token);
+            }
+            _ => groups.push(token.to_string()),
+        }
+    }
+    groups
+}
+
+fn parse_cast(heading: &str, text: &str, cast: &mut Vec<CastMember>) {
+    let default_role = CAST_ROLE_HEADINGS
+        .iter()
+        .find(|h| heading.contains(*h))
+        .map(|h| h.to_string());
+    for line in logical_lines(text) {
+        let (line_role, rest) = match split_bracket_role(&line) {
+            Some((role, rest)) => (Some(role.to_string()), rest.to_string()),
+            None => (None, line.clone()),
+        };
+        for pair in split_pairs(&rest) {
+            let (pair_role, names) = match split_role(&pair) {
+                Some((role, names)) => (Some(role.to_string()), names),
+                None => (None, pair.as_str()),
+            };
+            for (name, name_role) in split_names(names) {
+                let role = name_role
+                    .or_else(|| pair_role.clone())
+                    .or_else(|| line_role.clone())
+                    .or_else(|| default_role.clone());
+                cast.push(CastMember { name, role });
+            }
+        }
+    }
+}
+
+fn parse_staff(heading: &str, text: &str, staff: &mut Vec<StaffCredit>) {
+    for line in logical_lines(text) {
+        let (role, names) = split_role(&line)
+            .or_else(|| split_bracket_role(&line))
+            .unwrap_or((heading, &line));
+        for (name, _) in split_names(names) {
+            staff.push(StaffCredit {
+                role: role.to_string(),
+                name,
+            });
+        }
+    }
+}
+
+/// 本文から URL を探す。
+fn find_urls(text: &str, urls: &mut Vec<String>) {
+    let mut finder = LinkFinder::new();
+    finder.url_must_have_scheme(false);
+    finder.kinds(&[LinkKind::Url]);
+    for link in finder.links(text) {
+        let url = if link.as_str().starts_with("http://") || link.as_str().starts_with("https://") {
+            link.as_str().to_string()
+        } else {
+            format!("https://{}", link.as_str())
+        };
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+}
+
+/// 番組の詳細情報 (見出しと本文の JSON オブジェクト) を解析する。
+///
+/// 見出しと本文は NFKC で正規化する。オブジェクトでない場合は空の結果を返す。
+pub fn parse_extended(extended: &serde_json::Value) -> ProgramDetails {
+    let mut details = ProgramDetails::default();
+    let Some(items) = extended.as_object() else {
+        return details;
+    };
+    for (heading, text) in items {
+        let Some(text) = text.as_str() else {
+            continue;
+        };
+        let heading = nfkc(heading).trim().to_string();
+        let text = nfkc(text).trim().to_string();
+        find_urls(&text, &mut details.urls);
+        match classify(&heading) {
+            Heading::Content => match &mut details.content {
+                Some(content) => {
+                    content.push('\n');
+                    content.push_str(&text);
+                }
+                None => details.content = Some(text),
+            },
+            Heading::Cast => parse_cast(&heading, &text, &mut details.cast),
+            Heading::Staff => parse_staff(&heading, &text, &mut details.staff),
+            Heading::Other => details.sections.push(ExtendedSection { heading, text }),
+        }
+    }
+    details
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cast(name: &str, role: Option<&str>) -> CastMember {
+        CastMember {
+            name: name.to_string(),
+            role: role.map(str::to_string),
+        }
+    }
+
+    fn staff(role: &str, name: &str) -> StaffCredit {
+        StaffCredit {
+            role: role.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_extended() {
+        let details = parse_extended(&json!({
+            "番組内容": "宇宙世紀0079、ジオン公国は地球連邦に独立戦争を挑んだ。",
+            "出演者": "【声】\nアムロ・レイ：古谷徹\nシャア・アズナブル…池田秀一\nナレーション：永井一郎、\n　ほか",
+            "原作": "矢立肇、富野由悠季",
+            "スタッフ": "総監督：富野由悠季\n音楽：渡辺岳夫／松山祐士",
+            "ご案内": "番組ホームページ　ｗｗｗ．ｅｘａｍｐｌｅ．ｃｏｍ／ｇｕｎｄａｍ\nhttps://example.com/gundam/news"
+        }));
+        assert_eq!(
+            details.content.as_deref(),
+            Some("宇宙世紀0079、ジオン公国は地球連邦に独立戦争を挑んだ。")
+        );
+        assert_eq!(
+            details.cast,
+            vec![
+                cast("古谷徹", Some("アムロ・レイ")),
+                cast("池田秀一", Some("シャア・アズナブル")),
+                cast("永井一郎", Some("ナレーション")),
+            ]
+        );
+        // 見出しは serde_json のマップの順 (キーの辞書順) に処理される
+        assert_eq!(
+            details.staff,
+            vec![
+                staff("総監督", "富野由悠季"),
+                staff("音楽", "渡辺岳夫"),
+                staff("音楽", "松山祐士"),
+                staff("原作", "矢立肇"),
+                staff("原作", "富野由悠季"),
+            ]
+        );
+        assert_eq!(
+            details.urls,
+            vec![
+                "https://www.example.com/gundam",
+                "https://example.com/gundam/news"
+            ]
+        );
+        assert_eq!(details.sections.len(), 1);
+        assert_eq!(details.sections[0].heading, "ご案内");
+    }
+
+    #[test]
+    fn test_cast_formats() {
+        let details = parse_extended(&json!({
+            "出演者": "【司会】タモリ\n【ゲスト】山田太郎（俳優）、鈴木花子\nアムロ：古谷徹　シャア：池田秀一",
+            "語り": "市原悦子"
+        }));
+        assert_eq!(
+            details.cast,
+            vec![
+                cast("タモリ", Some("司会")),
+                cast("山田太郎", Some("俳優")),
+                cast("鈴木花子", Some("ゲスト")),
+                cast("古谷徹", Some("アムロ")),
+                cast("池田秀一", Some("シャア")),
+                cast("市原悦子", Some("語り")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_logical_lines() {
+        assert_eq!(
+            logical_lines("山田太郎、\n鈴木花子\n佐藤(ゲスト\n出演)\n\n田中"),
+            vec!["山田太郎、鈴木花子", "佐藤(ゲスト出演)", "田中"]
+        );
+        assert!(parse_extended(&json!("text")).cast.is_empty());
+    }
+}
@@ -0,0 +1,711 @@
+//! 録画後処理のジョブのドメインモデル
+//!
+//! ジョブの定義 (`JobDefinition`) は依存するジョブ・必要な入力・出力を宣言し、
+//! 検証済みの依存関係グラフ (`JobGraph`) から録画ごとの実行状況 (`JobRun`) を作成します。
+//! 元ファイル保存と mirakc の録画の削除はシステムが自動的に追加するジョブで、
+//! 削除ジョブは他のすべてのジョブに依存するため、すべてが成功した場合だけ実行されます。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::encode::WORK_FILES;
+use crate::models::record::{RecordOutput, ORIGINAL_OUTPUT};
+
+/// 元ファイル保存ジョブの名前 (システムが自動的に追加する)
+pub const SAVE_ORIGINAL_JOB: &str = "save-original";
+
+/// mirakc の録画を削除するジョブの名前 (システムが自動的に追加する)
+pub const DELETE_RECORD_JOB: &str = "delete-record";
+
+/// ジョブの種類
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobKind {
+    /// mirakc の録画ファイルをストレージに保存する
+    SaveOriginal,
+    /// スクリプトでエンコードする
+    Encode {
+        /// 実行するスクリプト
+        script: String,
+    },
+    /// mirakc の録画を削除する
+    DeleteRecord,
+}
+
+/// ジョブの出力
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobOutputSpec {
+    /// 出力名 (すべてのジョブで一意なファイル名。後続のジョブの入力名になる)
+    pub name: String,
+    /// MIME タイプ
+    pub content_type: String,
+    /// 保存先のストレージ名
+    pub storage: String,
+    /// 説明
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// ジョブの定義
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobDefinition {
+    /// ジョブ名 (英数字、`-`、`_` のみ)
+    pub name: String,
+    /// ジョブの種類
+    #[serde(flatten)]
+    pub kind: JobKind,
+    /// 依存するジョブの名前 (すべて成功してから実行する)
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// 必要な入力。mirakc の録画ファイル (`original`) か、依存するジョブ (間接的な依存を含む) の出力名
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    /// 出力
+    #[serde(default)]
+    pub outputs: Vec<JobOutputSpec>,
+}
+
+/// ジョブファイル (JSON) の内容
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobsConfig {
+    /// 元ファイルを保存するストレージ名 (指定した場合は元ファイル保存ジョブを追加する)
+    pub original_storage: Option<String>,
+    /// すべてのジョブが成功した後に mirakc の録画を削除するかどうか (`original_storage` が必要)
+    pub delete_record: bool,
+    /// ユーザが定義するジョブ
+    pub jobs: Vec<JobDefinition>,
+}
+
+impl JobsConfig {
+    /// ジョブファイル (JSON) を読み込む。
+    pub fn parse(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("ジョブファイルを解析できません")
+    }
+}
+
+/// 検証済みのジョブの依存関係グラフ
+///
+/// ジョブは依存するジョブより後になるように並べてある。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobGraph {
+    jobs: Vec<JobDefinition>,
+}
+
+impl JobGraph {
+    /// システムのジョブを追加し、依存関係・入力・出力を検証してグラフを作成する。
+    pub fn new(config: &JobsConfig) -> Result<Self> {
+        if config.delete_record && config.original_storage.is_none() {
+            bail!("元ファイルを保存せずに mirakc の録画は削除できません (delete_record には original_storage が必要です)");
+        }
+        let mut jobs = Vec::new();
+        if let Some(storage) = &config.original_storage {
+            jobs.push(JobDefinition {
+                name: SAVE_ORIGINAL_JOB.to_string(),
+                kind: JobKind::SaveOriginal,
+                depends_on: vec![],
+                inputs: vec![ORIGINAL_OUTPUT.to_string()],
+                outputs: vec![JobOutputSpec {
+                    name: ORIGINAL_OUTPUT.to_string(),
+                    content_type: "video/MP2T".to_string(),
+                    storage: storage.clone(),
+                    description: Some("元TS".to_string()),
+                }],
+            });
+        }
+        for job in &config.jobs {
+            if matches!(job.kind, JobKind::SaveOriginal | JobKind::DeleteRecord) {
+                bail!(
+                    "ジョブ {} の種類はシステムが追加するため指定できません (original_storage・delete_record を設定してください)",
+                    job.name
+                );
+            }
+            jobs.push(job.clone());
+        }
+        if config.delete_record {
+            jobs.push(JobDefinition {
+                name: DELETE_RECORD_JOB.to_string(),
+                kind: JobKind::DeleteRecord,
+                depends_on: jobs.iter().map(|job| job.name.clone()).collect(),
+                inputs: vec![],
+                outputs: vec![],
+            });
+        }
+
+        let jobs = sort_jobs(jobs)?;
+        validate_outputs(&jobs)?;
+        Ok(Self { jobs })
+    }
+
+    /// 依存するジョブより後になるように並べたジョブ
+    pub fn jobs(&self) -> &[JobDefinition] {
+        &self.jobs
+    }
+
+    /// 録画の実行状況を作成する。
+    pub fn instantiate(&self, mirakc_url: &str, record_id: &str, now: DateTime<Utc>) -> JobRun {
+        JobRun {
+            mirakc_url: mirakc_url.to_string(),
+            record_id: record_id.to_string(),
+            created_at: now,
+            jobs: self
+                .jobs
+                .iter()
+                .map(|definition| JobState {
+                    definition: definition.clone(),
+                    status: JobStatus::Pending,
+                    updated_at: now,
+                    error: None,
+                    outputs: vec![],
+                })
+                .collect(),
+        }
+    }
+}
+
+/// ジョブ名を検証し、依存するジョブより後になるように並べる (トポロジカルソート)。
+fn sort_jobs(jobs: Vec<JobDefinition>) -> Result<Vec<JobDefinition>> {
+    let mut names = HashSet::new();
+    for job in &jobs {
+        if job.name.is_empty()
+            || !job
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!(
+                "ジョブ名には英数字、'-'、'_' のみ使用できます: {:?}",
+                job.name
+            );
+        }
+        if !names.insert(job.name.as_str()) {
+            bail!("ジョブ名が重複しています: {}", job.name);
+        }
+    }
+    for job in &jobs {
+        if let Some(unknown) = job
+            .depends_on
+            .iter()
+            .find(|name| !names.contains(name.as_str()))
+        {
+            bail!(
+                "ジョブ {} が依存するジョブ {} がありません",
+                job.name,
+                unknown
+            );
+        }
+    }
+
+    // 依存するジョブがすべて並んだジョブを、定義の順に並べていく
+    let mut sorted: Vec<JobDefinition> = Vec::new();
+    let mut rest = jobs;
+    while !rest.is_empty() {
+        let placed: HashSet<String> = sorted.iter().map(|job| job.name.clone()).collect();
+        let (ready, waiting): (Vec<_>, Vec<_>) = rest
+            .into_iter()
+            .partition(|job| job.depends_on.iter().all(|name| placed.contains(name)));
+        if ready.is_empty() {
+            bail!("ジョブの依存関係が循環しています: {}", find_cycle(&waiting));
+        }
+        sorted.extend(ready);
+        rest = waiting;
+    }
+    Ok(sorted)
+}
+
+/// 循環している依存関係を `a -> b -> a` の形式で返す。
+///
+/// `jobs` はすべて、`jobs` のいずれかに依存している。
+fn find_cycle(jobs: &[JobDefinition]) -> String {
+    let by_name: HashMap<&str, &JobDefinition> =
+        jobs.iter().map(|job| (job.name.as_str(), job)).collect();
+    let mut path: Vec<&str> = vec![jobs[0].name.as_str()];
+    loop {
+        let current = by_name[path.last().unwrap()];
+        let next = current
+            .depends_on
+            .iter()
+            .map(String::as_str)
+            .find(|name| by_name.contains_key(name))
+            .expect("循環しているジョブは循環しているジョブに依存している");
+        if let Some(start) = path.iter().position(|name| *name == next) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(next);
+            return cycle.join(" -> ");
+        }
+        path.push(next);
+    }
+}
+
+/// 出力名が作業ファイルと重ならない一意なファイル名で、入力がすべて依存するジョブから得られることを検証する。
+///
+/// `jobs` は依存するジョブより後になるように並んでいること。
+fn validate_outputs(jobs: &[JobDefinition]) -> Result<()> {
+    let mut producers: HashMap<&str, &str> = HashMap::new();
+    for job in jobs {
+        for output in &job.outputs {
+            // 出力名は作業ディレクトリのファイル名としても使う
+            if output.name.starts_with('.')
+                || !output
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            {
+                bail!(
+                    "出力名には英数字、'-'、'_'、'.' のみ使用でき、'.' で始めることはできません: {:?}",
+                    output.name
+                );
+            }
+            if WORK_FILES.contains(&output.name.as_str()) {
+                bail!(
+                    "出力名 {} はスクリプトの作業ファイル ({}) と同じ名前のため使用できません",
+                    output.name,
+                    WORK_FILES.join(", ")
+                );
+            }
+            if let Some(other) = producers.insert(output.name.as_str(), job.name.as_str()) {
+                bail!(
+                    "出力名 {} がジョブ {} と {} で重複しています",
+                    output.name,
+                    other,
+                    job.name
+                );
+            }
+        }
+    }
+
+    // 各ジョブが (間接的に) 依存するジョブ
+    let mut upstream: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for job in jobs {
+        let mut ancestors = HashSet::new();
+        for dependency in &job.depends_on {
+            ancestors.insert(dependency.as_str());
+            ancestors.extend(upstream[dependency.as_str()].iter().copied());
+        }
+        for input in &job.inputs {
+            if input == ORIGINAL_OUTPUT {
+                continue;
+            }
+            match producers.get(input.as_str()) {
+                Some(producer) if ancestors.contains(producer) => {}
+                Some(producer) => bail!(
+                    "ジョブ {} の入力 {} を出力するジョブ {} に依存していません",
+                    job.name,
+                    input,
+                    producer
+                ),
+                None => bail!(
+                    "ジョブ {} の入力 {} を出力するジョブがありません",
+                    job.name,
+                    input
+                ),
+            }
+        }
+        upstream.insert(job.name.as_str(), ancestors);
+    }
+    Ok(())
+}
+
+/// ジョブの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// 依存するジョブの完了待ち
+    Pending,
+    /// ワーカーに送った
+    Queued,
+    /// 成功した
+    Succeeded,
+    /// 失敗した
+    Failed,
+    /// 依存するジョブが失敗したため実行しない
+    Skipped,
+}
+
+impl JobStatus {
+    /// これ以上状態が変わらないかどうか
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed | Self::Skipped)
+    }
+}
+
+/// ジョブの結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobResult {
+    /// 成功した
+    Succeeded {
+        /// 保存した出力
+        outputs: Vec<RecordOutput>,
+    },
+    /// 失敗した
+    Failed {
+        /// 失敗した理由
+        error: String,
+    },
+}
+
+/// 録画ごとのジョブの状態
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobState {
+    /// 作成時点のジョブの定義
+    pub definition: JobDefinition,
+    /// 状態
+    pub status: JobStatus,
+    /// 状態が変わった時刻
+    pub updated_at: DateTime<Utc>,
+    /// 失敗した理由
+    pub error: Option<String>,
+    /// 保存した出力
+    pub outputs: Vec<RecordOutput>,
+}
+
+/// 録画ごとのジョブの実行状況 (依存関係グラフのインスタンス)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobRun {
+    /// 録画を行った mirakc のベースURL
+    pub mirakc_url: String,
+    /// mirakc の Record ID
+    pub record_id: String,
+    /// 作成した時刻
+    pub created_at: DateTime<Utc>,
+    /// ジョブ (依存するジョブより後になるように並んでいる)
+    pub jobs: Vec<JobState>,
+}
+
+impl JobRun {
+    /// ジョブの状態
+    pub fn job(&self, name: &str) -> Option<&JobState> {
+        self.jobs.iter().find(|job| job.definition.name == name)
+    }
+
+    /// 依存するジョブがすべて成功し、実行できるジョブの名前
+    pub fn runnable(&self) -> Vec<String> {
+        let statuses: HashMap<&str, JobStatus> = self
+            .jobs
+            .iter()
+            .map(|job| (job.definition.name.as_str(), job.status))
+            .collect();
+        self.jobs
+            .iter()
+            .filter(|job| job.status == JobStatus::Pending)
+            .filter(|job| {
+                job.definition
+                    .depends_on
+                    .iter()
+                    .all(|name| statuses.get(name.as_str()) == Some(&JobStatus::Succeeded))
+            })
+            .map(|job| job.definition.name.clone())
+            .collect()
+    }
+
+    /// すべてのジョブが終わったかどうか
+    pub fn is_finished(&self) -> bool {
+        self.jobs.iter().all(|job| job.status.is_finished())
+    }
+
+    /// ジョブの入力になる、依存するジョブ (間接的な依存を含む) の出力
+    ///
+    /// mirakc の録画ファイル (`original`) は元ファイル保存ジョブが保存していればその出力を返す。
+    pub fn inputs(&self, name: &str) -> Vec<RecordOutput> {
+        let Some(job) = self.job(name) else {
+            return vec![];
+        };
+        let outputs: BTreeMap<&str, &RecordOutput> = self
+            .jobs
+            .iter()
+            .flat_map(|job| job.outputs.iter())
+            .map(|output| (output.name.as_str(), output))
+            .collect();
+        job.definition
+            .inputs
+            .iter()
+            .filter_map(|input| outputs.get(input.as_str()).map(|o| (*o).clone()))
+            .collect()
+    }
+
+    /// ジョブをワーカーに送ったことを記録する。
+    pub fn mark_queued(&mut self, name: &str, now: DateTime<Utc>) {
+        if let Some(job) = self.job_mut(name) {
+            job.status = JobStatus::Queued;
+            job.updated_at = now;
+        }
+    }
+
+    /// ワーカーに送れなかったジョブを待ちに戻す。
+    pub fn mark_pending(&mut self, name: &str, updated_at: DateTime<Utc>) {
+        if let Some(job) = self.job_mut(name) {
+            job.status = JobStatus::Pending;
+            job.updated_at = updated_at;
+        }
+    }
+
+    /// ジョブの結果を記録する。失敗した場合は、そのジョブに (間接的に) 依存するジョブを実行しない。
+    ///
+    /// 記録したジョブの数 (失敗して実行しないジョブを含む) を返す。
+    /// 終わったジョブの結果は変えない。
+    pub fn complete(&mut self, name: &str, result: JobResult, now: DateTime<Utc>) -> Result<usize> {
+        let Some(job) = self.job_mut(name) else {
+            bail!("ジョブ {} がありません", name);
+        };
+        if job.status.is_finished() {
+            return Ok(0);
+        }
+        job.updated_at = now;
+        match result {
+            JobResult::Succeeded { outputs } => {
+                job.status = JobStatus::Succeeded;
+                job.outputs = outputs;
+                Ok(1)
+            }
+            JobResult::Failed { error } => {
+                job.status = JobStatus::Failed;
+                job.error = Some(error);
+                let mut failed: HashSet<String> = HashSet::from([name.to_string()]);
+                for job in &mut self.jobs {
+                    if job.status.is_finished()
+                        || !job.definition.depends_on.iter().any(|d| failed.contains(d))
+                    {
+                        continue;
+                    }
+                    job.status = JobStatus::Skipped;
+                    job.updated_at = now;
+                    job.error = Some(format!("依存するジョブ {} が失敗しました", name));
+                    failed.insert(job.definition.name.clone());
+                }
+                Ok(failed.len())
+            }
+        }
+    }
+
+    fn job_mut(&mut self, name: &str) -> Option<&mut JobState> {
+        self.jobs.iter_mut().find(|job| job.definition.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn encode(name: &str, depends_on: &[&str], inputs: &[&str], outputs: &[&str]) -> JobDefinition {
+        JobDefinition {
+            name: name.to_string(),
+            kind: JobKind::Encode {
+                script: "ffmpeg -i input.ts output.mp4".to_string(),
+            },
+            depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+            outputs: outputs
+                .iter()
+                .map(|name| JobOutputSpec {
+                    name: name.to_string(),
+                    content_type: "video/mp4".to_string(),
+                    storage: "local1".to_string(),
+                    description: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn config(jobs: Vec<JobDefinition>) -> JobsConfig {
+        JobsConfig {
+            original_storage: Some("local1".to_string()),
+            delete_record: true,
+            jobs,
+        }
+    }
+
+    fn output(name: &str) -> RecordOutput {
+        RecordOutput {
+            name: name.to_string(),
+            storage: "local1".to_string(),
+            location: format!("2025/01/01/1/{}", name),
+            content_type: "video/mp4".to_string(),
+            size: Some(1),
+            encode_status: crate::models::record::EncodeStatus::Encoded,
+            url: None,
+            sha256: None,
+        }
+    }
+
+    fn succeeded(outputs: Vec<RecordOutput>) -> JobResult {
+        JobResult::Succeeded { outputs }
+    }
+
+    #[test]
+    fn test_parse_jobs_config() {
+        let config = JobsConfig::parse(
+            &json!({
+                "original_storage": "local1",
+                "delete_record": true,
+                "jobs": [{
+                    "name": "mp4",
+                    "type": "encode",
+                    "script": "ffmpeg -i input.ts output.mp4",
+                    "inputs": ["original"],
+                    "outputs": [{ "name": "output.mp4", "content_type": "video/mp4", "storage": "local1" }]
+                }]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            config.jobs[0],
+            encode("mp4", &[], &["original"], &["output.mp4"])
+        );
+    }
+
+    #[test]
+    fn test_job_graph() {
+        // 定義の順に関係なく、依存するジョブより後に並べる
+        let graph = JobGraph::new(&config(vec![
+            encode("thumbnail", &["mp4"], &["output.mp4"], &["thumbnail.jpg"]),
+            encode("mp4", &["save-original"], &["original"], &["output.mp4"]),
+            encode("subtitles", &[], &["original"], &["subtitles.vtt"]),
+        ]))
+        .unwrap();
+        let names: Vec<&str> = graph.jobs().iter().map(|j| j.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "save-original",
+                "subtitles",
+                "mp4",
+                "thumbnail",
+                "delete-record"
+            ]
+        );
+        // 削除ジョブは他のすべてのジョブに依存する
+        let delete = graph.jobs().last().unwrap();
+        assert_eq!(delete.kind, JobKind::DeleteRecord);
+        assert_eq!(delete.depends_on.len(), 4);
+
+        // システムのジョブを追加しない
+        let graph = JobGraph::new(&JobsConfig {
+            jobs: vec![encode("mp4", &[], &["original"], &["output.mp4"])],
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(graph.jobs().len(), 1);
+    }
+
+    #[test]
+    fn test_job_graph_errors() {
+        let error =
+            |jobs: Vec<JobDefinition>| JobGraph::new(&config(jobs)).unwrap_err().to_string();
+        assert_eq!(
+            error(vec![
+                encode("a", &["c"], &[], &[]),
+                encode("b", &["a"], &[], &[]),
+                encode("c", &["b"], &[], &[]),
+                encode("d", &[], &[], &[]),
+            ]),
+            "ジョブの依存関係が循環しています: a -> c -> b -> a"
+        );
+        assert_eq!(
+            error(vec![encode("a", &["a"], &[], &[])]),
+            "ジョブの依存関係が循環しています: a -> a"
+        );
+        assert_eq!(
+            error(vec![encode("a", &["missing"], &[], &[])]),
+            "ジョブ a が依存するジョブ missing がありません"
+        );
+        assert_eq!(
+            error(vec![encode("a", &[], &[], &[]), encode("a", &[], &[], &[])]),
+            "ジョブ名が重複しています: a"
+        );
+        assert_eq!(
+            error(vec![
+                encode("a", &[], &[], &["output.mp4"]),
+                encode("b", &[], &["output.mp4"], &[]),
+            ]),
+            "ジョブ b の入力 output.mp4 を出力するジョブ a に依存していません"
+        );
+        assert_eq!(
+            error(vec![encode("b", &[], &["output.mp4"], &[])]),
+            "ジョブ b の入力 output.mp4 を出力するジョブがありません"
+        );
+        assert_eq!(
+            error(vec![encode("a", &[], &[], &["original"])]),
+            "出力名 original がジョブ save-original と a で重複しています"
+        );
+        assert!(error(vec![encode("a", &[], &[], &["../output.mp4"])]).starts_with("出力名には"));
+        for name in ["input.ts", "encode.sh", "encode.log"] {
+            assert_eq!(
+                error(vec![encode("a", &[], &[], &[name])]),
+                format!(
+                    "出力名 {} はスクリプトの作業ファイル (input.ts, encode.sh, encode.log) と同じ名前のため使用できません",
+                    name
+                )
+            );
+        }
+        let mut delete = encode("delete", &[], &[], &[]);
+        delete.kind = JobKind::DeleteRecord;
+        assert!(error(vec![delete]).contains("システムが追加する"));
+
+        assert!(JobGraph::new(&JobsConfig {
+            delete_record: true,
+            ..Default::default()
+        })
+        .unwrap_err()
+        .to_string()
+        .contains("original_storage が必要です"));
+    }
+
+    #[test]
+    fn test_job_run() {
+        let graph = JobGraph::new(&config(vec![
+            encode("mp4", &["save-original"], &["original"], &["output.mp4"]),
+            encode("thumbnail", &["mp4"], &["output.mp4"], &["thumbnail.jpg"]),
+            encode("subtitles", &[], &["original"], &["subtitles.vtt"]),
+        ]))
+        .unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let mut run = graph.instantiate("http://tuner:40772", "1", now);
+        assert_eq!(run.runnable(), vec!["save-original", "subtitles"]);
+
+        run.mark_queued("save-original", now);
+        run.mark_queued("subtitles", now);
+        assert!(run.runnable().is_empty());
+        run.complete("save-original", succeeded(vec![output("original")]), now)
+            .unwrap();
+        assert_eq!(run.runnable(), vec!["mp4"]);
+        run.mark_queued("mp4", now);
+        run.complete("mp4", succeeded(vec![output("output.mp4")]), now)
+            .unwrap();
+        assert_eq!(run.runnable(), vec!["thumbnail"]);
+        assert_eq!(run.inputs("thumbnail"), vec![output("output.mp4")]);
+        assert_eq!(run.inputs("mp4"), vec![output("original")]);
+
+        // 失敗すると削除ジョブは実行しない
+        assert_eq!(
+            run.complete(
+                "subtitles",
+                JobResult::Failed {
+                    error: "exit code 1".to_string()
+                },
+                now
+            )
+            .unwrap(),
+            2
+        );
+        assert_eq!(
+            run.job(DELETE_RECORD_JOB).unwrap().status,
+            JobStatus::Skipped
+        );
+        assert_eq!(run.runnable(), vec!["thumbnail"]);
+        assert!(!run.is_finished());
+
+        // 終わったジョブの結果は変えない
+        assert_eq!(
+            run.complete("subtitles", succeeded(vec![]), now).unwrap(),
+            0
+        );
+        assert!(run.complete("missing", succeeded(vec![]), now).is_err());
+    }
+}
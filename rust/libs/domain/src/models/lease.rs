@@ -0,0 +1,40 @@
+//! リーダー選出のためのリースのドメインモデル
+//!
+//! 同じワーカーを複数起動した場合に、リースを保持している1つのインスタンスだけが処理を行うために使用します。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// リース
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lease {
+    /// リースを保持しているインスタンスの識別子
+    pub holder: String,
+    /// 有効期限。更新されないまま過ぎた場合は他のインスタンスが取得できる
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Lease {
+    /// `holder` がこのリースを取得 (または更新) できるかどうか
+    pub fn can_acquire(&self, holder: &str, now: DateTime<Utc>) -> bool {
+        self.holder == holder || self.expires_at <= now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_can_acquire() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 4, 9, 0, 0).unwrap();
+        let lease = Lease {
+            holder: "worker-a".to_string(),
+            expires_at: now + chrono::Duration::seconds(30),
+        };
+        assert!(lease.can_acquire("worker-a", now));
+        assert!(!lease.can_acquire("worker-b", now));
+        assert!(lease.can_acquire("worker-b", lease.expires_at));
+    }
+}
@@ -2,5 +2,24 @@
 //!
 //! このモジュールはドメインモデルを定義します。
 
+pub mod checksum;
+pub mod conflict;
+pub mod deletion;
+pub mod encode;
 pub mod epg;
+pub mod extended;
+pub mod genre;
+pub mod job;
+pub mod lease;
+pub mod ogp;
+pub mod onair;
+pub mod record;
+pub mod rule;
+pub mod schedule;
+pub mod search;
+pub mod series;
+pub mod service;
+pub mod title;
+pub mod tuner;
 pub mod version;
+pub mod xmltv;
@@ -0,0 +1,99 @@
+//! OGP 画像のキャッシュ
+//!
+//! 番組の詳細情報に含まれる URL の og:image を WebP に縮小して保存します。
+//! 画像がなかった場合や取得に失敗した場合もその結果を保存し (ネガティブキャッシュ)、
+//! 期限が切れるまで取得し直さないようにします。
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// OGP 画像の取得結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OgpStatus {
+    /// 画像を取得した
+    Found,
+    /// ページに og:image がない、または画像を取得・変換できなかった
+    NotFound,
+}
+
+/// OGP 画像のキャッシュエントリ
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OgpEntry {
+    /// ページの URL
+    pub url: String,
+    /// URL の SHA-1 (キャッシュのキー)
+    pub url_hash: String,
+    /// 取得結果
+    pub status: OgpStatus,
+    /// og:image の URL
+    pub image_url: Option<String>,
+    /// 画像がない理由 (`NotFound` の場合)
+    pub reason: Option<String>,
+    /// 取得した時刻
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl OgpEntry {
+    /// 取得し直す必要がないか
+    pub fn is_fresh(&self, policy: &OgpCachePolicy, now: DateTime<Utc>) -> bool {
+        let ttl = match self.status {
+            OgpStatus::Found => policy.refetch_ttl,
+            OgpStatus::NotFound => policy.negative_ttl,
+        };
+        now < self.fetched_at + ttl
+    }
+}
+
+/// OGP 画像を取得し直すまでの期間
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OgpCachePolicy {
+    /// 画像を取得できた場合
+    pub refetch_ttl: Duration,
+    /// 画像がなかった場合・取得に失敗した場合
+    pub negative_ttl: Duration,
+}
+
+impl Default for OgpCachePolicy {
+    fn default() -> Self {
+        Self {
+            refetch_ttl: Duration::days(30),
+            negative_ttl: Duration::days(1),
+        }
+    }
+}
+
+/// 取得した OGP 画像
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OgpImage {
+    /// og:image の URL
+    pub image_url: String,
+    /// 縮小した画像 (WebP)
+    pub webp: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_is_fresh() {
+        let fetched_at = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let mut entry = OgpEntry {
+            url: "https://example.com/".to_string(),
+            url_hash: "hash".to_string(),
+            status: OgpStatus::Found,
+            image_url: Some("https://example.com/og.png".to_string()),
+            reason: None,
+            fetched_at,
+        };
+        let policy = OgpCachePolicy::default();
+        assert!(entry.is_fresh(&policy, fetched_at + Duration::days(29)));
+        assert!(!entry.is_fresh(&policy, fetched_at + Duration::days(30)));
+
+        entry.status = OgpStatus::NotFound;
+        assert!(entry.is_fresh(&policy, fetched_at + Duration::hours(23)));
+        assert!(!entry.is_fresh(&policy, fetched_at + Duration::days(1)));
+    }
+}
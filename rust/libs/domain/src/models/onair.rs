@@ -0,0 +1,50 @@
+//! 放送中番組のドメインモデル
+//!
+//! mirakc の EIT[p/f] から得られるサービスごとの「現在」と「次」の番組を定義します。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 放送中 (または次に放送される) 番組の概要
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnairProgram {
+    /// Mirakurun Program ID
+    pub program_id: i64,
+    /// Event ID
+    pub event_id: i64,
+    /// 番組名
+    pub name: Option<String>,
+    /// 開始時刻
+    pub start_at: DateTime<Utc>,
+    /// 長さ (ミリ秒)
+    pub duration_millis: i64,
+}
+
+impl OnairProgram {
+    /// 終了時刻
+    pub fn end_at(&self) -> DateTime<Utc> {
+        self.start_at + chrono::Duration::milliseconds(self.duration_millis)
+    }
+}
+
+/// サービスごとの「現在」と「次」の番組
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NowPlaying {
+    /// 番組情報を取得した mirakc のベースURL
+    pub mirakc_url: String,
+    /// Mirakurun Service ID
+    pub service_id: i64,
+    /// 放送中の番組。放送休止中などは `None`
+    pub current: Option<OnairProgram>,
+    /// 次に放送される番組
+    pub next: Option<OnairProgram>,
+    /// 最後に更新した時刻
+    pub updated_at: DateTime<Utc>,
+}
+
+impl NowPlaying {
+    /// 「現在」と「次」の番組が同じかどうか (更新時刻は比較しない)
+    pub fn same_programs(&self, other: &NowPlaying) -> bool {
+        self.current == other.current && self.next == other.next
+    }
+}
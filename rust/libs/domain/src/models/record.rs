@@ -0,0 +1,275 @@
+//! 録画ライブラリのドメインモデル
+//!
+//! mirakc の録画レコード (`/recording/records`) を KuRec で扱う形式に変換したもの。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::events::mirakc_events::{RecordingFailedReason, RecordingStatus};
+use crate::models::epg::KurecProgram;
+use crate::models::rule::jst;
+
+/// mirakc が保存した録画レコード
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    /// mirakc の Record ID
+    pub id: String,
+    /// 録画を行った mirakc のベースURL
+    pub mirakc_url: String,
+    /// 録画対象の番組情報
+    pub program: KurecProgram,
+    /// 録画の実行状況
+    pub recording: RecordingInfo,
+    /// 録画ファイルの情報
+    pub content: RecordContent,
+    /// 録画予約時に付与されたタグ
+    pub tags: Vec<String>,
+}
+
+/// 録画の実行状況
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingInfo {
+    /// 録画ステータス
+    pub status: RecordingStatus,
+    /// 録画開始時刻
+    pub start_time: DateTime<Utc>,
+    /// 録画終了時刻 (録画中は `None`)
+    pub end_time: Option<DateTime<Utc>>,
+    /// 録画時間 (ミリ秒、録画中は `None`)
+    pub duration_millis: Option<i64>,
+    /// 録画失敗理由
+    pub failed_reason: Option<RecordingFailedReason>,
+}
+
+/// 録画ファイルの情報
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordContent {
+    /// mirakc 上のファイルパス (録画ディレクトリからの相対パス)
+    pub path: String,
+    /// MIME タイプ
+    pub content_type: String,
+    /// ファイルサイズ (バイト)。ファイルが削除されている場合は `None`
+    pub length: Option<i64>,
+}
+
+/// mirakc 上の録画ファイルを表すストレージ名
+pub const MIRAKC_STORAGE: &str = "mirakc";
+
+/// mirakc 上の録画ファイルの出力名
+pub const ORIGINAL_OUTPUT: &str = "original";
+
+/// 出力のエンコード状況
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EncodeStatus {
+    /// 録画したままの TS
+    Original,
+    /// エンコード待ち
+    Pending,
+    /// エンコード中
+    Encoding,
+    /// エンコード済み
+    Encoded,
+    /// エンコードに失敗した
+    Failed,
+}
+
+impl EncodeStatus {
+    /// シリアライズしたときの名前 (検索の絞り込みに使う)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Original => "original",
+            Self::Pending => "pending",
+            Self::Encoding => "encoding",
+            Self::Encoded => "encoded",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// 録画の出力 (録画ファイルやエンコードしたファイル) と保存先
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordOutput {
+    /// 出力名
+    pub name: String,
+    /// 保存先のストレージ名
+    pub storage: String,
+    /// ストレージ内の位置
+    pub location: String,
+    /// MIME タイプ
+    pub content_type: String,
+    /// ファイルサイズ (バイト)。不明な場合は `None`
+    pub size: Option<i64>,
+    /// エンコード状況
+    pub encode_status: EncodeStatus,
+    /// 再生できる URL
+    pub url: Option<String>,
+    /// 内容の SHA-256 (16進数の小文字)。計算していない場合は `None`
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+impl Record {
+    /// 録画ファイルが mirakc 上に存在するかどうか
+    pub fn has_content(&self) -> bool {
+        self.content.length.is_some()
+    }
+
+    /// mirakc の録画ファイルのストリーム URL
+    pub fn stream_url(&self) -> String {
+        format!(
+            "{}/api/recording/records/{}/stream",
+            self.mirakc_url.trim_end_matches('/'),
+            self.id
+        )
+    }
+
+    /// 録画の出力。mirakc 上に録画ファイルがあれば、それを `original` として含む。
+    pub fn outputs(&self) -> Vec<RecordOutput> {
+        if !self.has_content() {
+            return Vec::new();
+        }
+        vec![RecordOutput {
+            name: ORIGINAL_OUTPUT.to_string(),
+            storage: MIRAKC_STORAGE.to_string(),
+            location: self.content.path.clone(),
+            content_type: self.content.content_type.clone(),
+            size: self.content.length,
+            encode_status: EncodeStatus::Original,
+            url: Some(self.stream_url()),
+            sha256: None,
+        }]
+    }
+
+    /// 出力をストレージに保存する位置。
+    ///
+    /// `{YYYY}/{MM}/{DD}/{record_id}/{name}` で、日付は日本時間の録画開始日。
+    pub fn output_location(&self, name: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            jst(self.recording.start_time).format("%Y/%m/%d"),
+            self.id,
+            name
+        )
+    }
+}
+
+/// `Record::output_location` で作った位置から、Record ID と出力名を取り出す。
+///
+/// 形式が異なる場合は `None`。
+pub fn parse_output_location(location: &str) -> Option<(&str, &str)> {
+    let segments: Vec<&str> = location.split('/').collect();
+    let [year, month, day, record_id, name] = segments.as_slice() else {
+        return None;
+    };
+    let is_number = |s: &str, len: usize| s.len() == len && s.bytes().all(|b| b.is_ascii_digit());
+    if !is_number(year, 4) || !is_number(month, 2) || !is_number(day, 2) {
+        return None;
+    }
+    if record_id.is_empty() || name.is_empty() {
+        return None;
+    }
+    Some((record_id, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sample_record() -> Record {
+        let start_at = Utc.timestamp_millis_opt(1700000000000).unwrap();
+        Record {
+            id: "0000000000000001".to_string(),
+            mirakc_url: "http://tuner:40772".to_string(),
+            program: KurecProgram {
+                id: 327360817301010,
+                mirakc_url: "http://tuner:40772".to_string(),
+                service_id: 3273601024,
+                network_id: 32736,
+                event_id: 1010,
+                channel_name: "ＮＨＫ総合１・東京".to_string(),
+                channel_type: "GR".to_string(),
+                channel: "27".to_string(),
+                name: Some("ニュース".to_string()),
+                description: None,
+                extended: None,
+                start_at,
+                duration_millis: 1800000,
+                is_free: true,
+                genres: vec![],
+                video_info: None,
+                audio_infos: vec![],
+                series_info: None,
+                markers: Default::default(),
+                episode_number: None,
+            },
+            recording: RecordingInfo {
+                status: RecordingStatus::Finished,
+                start_time: start_at,
+                end_time: Some(start_at + chrono::Duration::minutes(30)),
+                duration_millis: Some(1800000),
+                failed_reason: None,
+            },
+            content: RecordContent {
+                path: "0000000000000001.m2ts".to_string(),
+                content_type: "video/MP2T".to_string(),
+                length: Some(1024),
+            },
+            tags: vec!["kurec".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_record_serde_roundtrip() {
+        let record = sample_record();
+        let json = serde_json::to_string(&record).unwrap();
+        let decoded: Record = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, record);
+    }
+
+    #[test]
+    fn test_has_content() {
+        let mut record = sample_record();
+        assert!(record.has_content());
+        record.content.length = None;
+        assert!(!record.has_content());
+    }
+
+    #[test]
+    fn test_outputs() {
+        let mut record = sample_record();
+        let outputs = record.outputs();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].storage, MIRAKC_STORAGE);
+        assert_eq!(outputs[0].encode_status, EncodeStatus::Original);
+        assert_eq!(
+            outputs[0].url.as_deref(),
+            Some("http://tuner:40772/api/recording/records/0000000000000001/stream")
+        );
+        assert_eq!(
+            serde_json::to_value(EncodeStatus::Original).unwrap(),
+            EncodeStatus::Original.as_str()
+        );
+
+        // 録画ファイルが削除されていれば出力はない
+        record.content.length = None;
+        assert!(record.outputs().is_empty());
+    }
+
+    #[test]
+    fn test_output_location() {
+        // 2023-11-14T22:13:20Z は日本時間で 2023-11-15
+        assert_eq!(
+            sample_record().output_location("output.mp4"),
+            "2023/11/15/0000000000000001/output.mp4"
+        );
+        assert_eq!(
+            parse_output_location("2023/11/15/0000000000000001/output.mp4"),
+            Some(("0000000000000001", "output.mp4"))
+        );
+        assert_eq!(parse_output_location("2023/11/15/output.mp4"), None);
+        assert_eq!(parse_output_location("logs/11/15/1/output.mp4"), None);
+        assert_eq!(parse_output_location("2023/11/15/1/"), None);
+    }
+}
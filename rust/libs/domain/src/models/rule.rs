@@ -0,0 +1,718 @@
+//! 自動録画ルールのドメインモデル
+//!
+//! クエリ・キーワード・ジャンル・チャンネル・曜日・時間帯・番組の長さ・無料放送の条件で番組を選ぶ
+//! `RecordingRule` と、番組に対する判定結果 (一致した理由、または一致しなかった理由) を定義します。
+//! 曜日と時間帯は日本時間で判定します。
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Datelike, FixedOffset, Timelike, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::models::epg::KurecProgram;
+use crate::models::title::normalize_text;
+use crate::query::Query;
+
+/// 自動録画ルール
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingRule {
+    /// ルールID (英数字、`-`、`_` のみ)
+    pub id: String,
+    /// ルール名
+    pub name: String,
+    /// 有効かどうか
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 優先度 (大きいほど優先)。チューナーが不足した場合の判断に使用する
+    #[serde(default)]
+    pub priority: i32,
+    /// 番組の条件
+    #[serde(default)]
+    pub conditions: RuleConditions,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 自動録画ルールの条件
+///
+/// 指定された条件はすべて満たす必要がある (AND)。リスト形式の条件は、いずれかに一致すればよい (OR)。
+/// ただし `keywords` はすべてのキーワードを含む必要がある。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleConditions {
+    /// KuRec クエリ言語による条件 (例: `title:ガンダム -title:再放送 weekday:sat..sun`)
+    pub query: Option<Query>,
+    /// 番組名に含まれるべきキーワード (全角・半角、カタカナ・ひらがな、大文字・小文字を区別しない)
+    pub keywords: Vec<String>,
+    /// 番組名に含まれていれば除外するキーワード
+    pub exclude_keywords: Vec<String>,
+    /// キーワードを番組説明からも探すかどうか
+    pub search_description: bool,
+    /// ジャンル。大分類 (`アニメ・特撮`) または「大分類／中分類」(`アニメ・特撮／国内アニメ`)
+    pub genres: Vec<String>,
+    /// チャンネル名 (サービス名) または Mirakurun Service ID
+    pub channels: Vec<String>,
+    /// チャンネルタイプ (GR, BS, CS など)
+    pub channel_types: Vec<String>,
+    /// 放送開始の曜日 (日本時間)
+    pub weekdays: Vec<Weekday>,
+    /// 放送開始の時間帯 (日本時間)
+    pub time_window: Option<TimeWindow>,
+    /// 番組の最短の長さ (分)
+    pub min_duration_minutes: Option<i64>,
+    /// 番組の最長の長さ (分)
+    pub max_duration_minutes: Option<i64>,
+    /// 無料放送のみを対象にするかどうか
+    pub free_only: bool,
+}
+
+impl RuleConditions {
+    /// 番組を絞り込む条件 (クエリ・キーワード・ジャンル・チャンネル) が1つもないかどうか
+    fn has_no_selector(&self) -> bool {
+        self.query.is_none()
+            && self.keywords.is_empty()
+            && self.genres.is_empty()
+            && self.channels.is_empty()
+            && self.channel_types.is_empty()
+    }
+}
+
+/// 放送開始の時間帯 (日本時間)
+///
+/// `18:00-24:00` のように表記する。終了が開始より前の場合は日付をまたぐ時間帯 (`23:00-02:00`) として扱う。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeWindow {
+    /// 開始 (0時からの分)
+    pub start_minute: u32,
+    /// 終了 (0時からの分、24:00 は 1440)
+    pub end_minute: u32,
+}
+
+impl TimeWindow {
+    /// 指定された時刻 (0時からの分) が時間帯に含まれるかどうか
+    pub fn contains(&self, minute: u32) -> bool {
+        if self.start_minute <= self.end_minute {
+            self.start_minute <= minute && minute < self.end_minute
+        } else {
+            minute >= self.start_minute || minute < self.end_minute
+        }
+    }
+}
+
+fn parse_hhmm(s: &str) -> Result<u32> {
+    let (h, m) = s
+        .split_once(':')
+        .with_context(|| format!("時刻は HH:MM 形式で指定してください: {}", s))?;
+    let h: u32 = h
+        .parse()
+        .with_context(|| format!("不正な時刻です: {}", s))?;
+    let m: u32 = m
+        .parse()
+        .with_context(|| format!("不正な時刻です: {}", s))?;
+    if m >= 60 || h * 60 + m > 24 * 60 {
+        bail!("不正な時刻です: {}", s);
+    }
+    Ok(h * 60 + m)
+}
+
+impl FromStr for TimeWindow {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (start, end) = s
+            .split_once('-')
+            .with_context(|| format!("時間帯は HH:MM-HH:MM 形式で指定してください: {}", s))?;
+        Ok(Self {
+            start_minute: parse_hhmm(start.trim())?,
+            end_minute: parse_hhmm(end.trim())?,
+        })
+    }
+}
+
+impl TryFrom<String> for TimeWindow {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<TimeWindow> for String {
+    fn from(value: TimeWindow) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}-{:02}:{:02}",
+            self.start_minute / 60,
+            self.start_minute % 60,
+            self.end_minute / 60,
+            self.end_minute % 60
+        )
+    }
+}
+
+/// ルールに一致した理由
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MatchReason {
+    /// クエリに一致
+    Query { query: String },
+    /// キーワードを含む
+    Keyword { keyword: String },
+    /// ジャンルが一致
+    Genre { genre: String },
+    /// チャンネルが一致
+    Channel { channel: String },
+    /// チャンネルタイプが一致
+    ChannelType { channel_type: String },
+    /// 曜日が一致
+    Weekday { weekday: Weekday },
+    /// 時間帯に含まれる
+    TimeWindow { window: TimeWindow },
+    /// 長さの条件を満たす
+    Duration { minutes: i64 },
+    /// 無料放送
+    Free,
+}
+
+impl fmt::Display for MatchReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Query { query } => write!(f, "クエリ「{}」に一致", query),
+            Self::Keyword { keyword } => write!(f, "キーワード「{}」を含む", keyword),
+            Self::Genre { genre } => write!(f, "ジャンル「{}」", genre),
+            Self::Channel { channel } => write!(f, "チャンネル「{}」", channel),
+            Self::ChannelType { channel_type } => write!(f, "チャンネルタイプ {}", channel_type),
+            Self::Weekday { weekday } => write!(f, "曜日 {}", weekday),
+            Self::TimeWindow { window } => write!(f, "時間帯 {}", window),
+            Self::Duration { minutes } => write!(f, "長さ {}分", minutes),
+            Self::Free => write!(f, "無料放送"),
+        }
+    }
+}
+
+/// ルールに一致しなかった理由 (最初に満たさなかった条件)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RejectReason {
+    /// ルールが無効
+    Disabled,
+    /// 番組を絞り込む条件がない
+    NoSelector,
+    /// クエリに一致しない
+    Query,
+    /// キーワードを含まない
+    MissingKeyword { keyword: String },
+    /// 除外キーワードを含む
+    ExcludedKeyword { keyword: String },
+    /// ジャンルが一致しない
+    Genre,
+    /// チャンネルが一致しない
+    Channel,
+    /// チャンネルタイプが一致しない
+    ChannelType,
+    /// 曜日が一致しない
+    Weekday { weekday: Weekday },
+    /// 時間帯に含まれない
+    TimeWindow { window: TimeWindow },
+    /// 短すぎる
+    TooShort { minutes: i64 },
+    /// 長すぎる
+    TooLong { minutes: i64 },
+    /// 有料放送
+    NotFree,
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Disabled => write!(f, "ルールが無効"),
+            Self::NoSelector => write!(f, "クエリ・キーワード・ジャンル・チャンネルの条件がない"),
+            Self::Query => write!(f, "クエリに一致しない"),
+            Self::MissingKeyword { keyword } => write!(f, "キーワード「{}」を含まない", keyword),
+            Self::ExcludedKeyword { keyword } => {
+                write!(f, "除外キーワード「{}」を含む", keyword)
+            }
+            Self::Genre => write!(f, "ジャンルが一致しない"),
+            Self::Channel => write!(f, "チャンネルが一致しない"),
+            Self::ChannelType => write!(f, "チャンネルタイプが一致しない"),
+            Self::Weekday { weekday } => write!(f, "曜日 {} は対象外", weekday),
+            Self::TimeWindow { window } => write!(f, "時間帯 {} の外", window),
+            Self::TooShort { minutes } => write!(f, "短すぎる ({}分)", minutes),
+            Self::TooLong { minutes } => write!(f, "長すぎる ({}分)", minutes),
+            Self::NotFree => write!(f, "有料放送"),
+        }
+    }
+}
+
+impl RejectReason {
+    /// 番組を選ぶ条件 (クエリ・キーワード・ジャンル・チャンネル) ではなく、
+    /// 除外の条件 (除外キーワード・曜日・時間帯・長さ・無料放送) で外れたかどうか
+    pub fn is_exclusion(&self) -> bool {
+        matches!(
+            self,
+            Self::ExcludedKeyword { .. }
+                | Self::Weekday { .. }
+                | Self::TimeWindow { .. }
+                | Self::TooShort { .. }
+                | Self::TooLong { .. }
+                | Self::NotFree
+        )
+    }
+}
+
+/// 番組に対するルールの判定結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleVerdict {
+    /// 一致した (一致した理由のリスト)
+    Matched(Vec<MatchReason>),
+    /// 一致しなかった
+    Rejected(RejectReason),
+}
+
+impl RuleVerdict {
+    /// 一致したかどうか
+    pub fn is_matched(&self) -> bool {
+        matches!(self, Self::Matched(_))
+    }
+}
+
+/// 番組がルールに一致したことの記録
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleMatch {
+    /// ルールID
+    pub rule_id: String,
+    /// ルール名
+    pub rule_name: String,
+    /// ルールの優先度
+    pub priority: i32,
+    /// 一致した理由
+    pub reasons: Vec<MatchReason>,
+}
+
+pub(crate) fn jst(at: DateTime<Utc>) -> DateTime<FixedOffset> {
+    at.with_timezone(&FixedOffset::east_opt(9 * 3600).unwrap())
+}
+
+fn find_keyword<'a>(haystacks: &[String], keywords: &'a [String]) -> Option<&'a String> {
+    keywords.iter().find(|k| {
+        let k = normalize_text(k);
+        haystacks.iter().any(|h| h.contains(&k))
+    })
+}
+
+/// ルールファイル (JSON) を読み込む。
+///
+/// 1つのルールのオブジェクト、またはルールの配列を受け付け、すべてのルールを検証する。
+pub fn parse_rules(json: &str) -> Result<Vec<RecordingRule>> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(Box<RecordingRule>),
+        Many(Vec<RecordingRule>),
+    }
+
+    let rules = match serde_json::from_str(json).context("ルールファイルを解析できません")?
+    {
+        OneOrMany::One(rule) => vec![*rule],
+        OneOrMany::Many(rules) => rules,
+    };
+    for rule in &rules {
+        rule.validate()?;
+    }
+    Ok(rules)
+}
+
+impl RecordingRule {
+    /// ルールの内容を検証する。
+    pub fn validate(&self) -> Result<()> {
+        if self.id.is_empty()
+            || !self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!(
+                "ルールIDには英数字、'-'、'_' のみ使用できます: {:?}",
+                self.id
+            );
+        }
+        if self.conditions.has_no_selector() {
+            bail!(
+                "ルール {} にはクエリ・キーワード・ジャンル・チャンネルのいずれかの条件が必要です",
+                self.id
+            );
+        }
+        if let (Some(min), Some(max)) = (
+            self.conditions.min_duration_minutes,
+            self.conditions.max_duration_minutes,
+        ) {
+            if min > max {
+                bail!("ルール {} の最短の長さが最長の長さを超えています", self.id);
+            }
+        }
+        Ok(())
+    }
+
+    /// 番組がルールに一致するか判定する。
+    pub fn evaluate(&self, program: &KurecProgram) -> RuleVerdict {
+        match self.collect_reasons(program) {
+            Ok(reasons) => RuleVerdict::Matched(reasons),
+            Err(reject) => RuleVerdict::Rejected(reject),
+        }
+    }
+
+    /// 一致した場合は `RuleMatch` を返す。
+    pub fn match_program(&self, program: &KurecProgram) -> Option<RuleMatch> {
+        match self.evaluate(program) {
+            RuleVerdict::Matched(reasons) => Some(RuleMatch {
+                rule_id: self.id.clone(),
+                rule_name: self.name.clone(),
+                priority: self.priority,
+                reasons,
+            }),
+            RuleVerdict::Rejected(_) => None,
+        }
+    }
+
+    fn collect_reasons(&self, program: &KurecProgram) -> Result<Vec<MatchReason>, RejectReason> {
+        let c = &self.conditions;
+        if !self.enabled {
+            return Err(RejectReason::Disabled);
+        }
+        if c.has_no_selector() {
+            return Err(RejectReason::NoSelector);
+        }
+        let mut reasons = Vec::new();
+
+        if let Some(query) = &c.query {
+            if !query.matches(program) {
+                return Err(RejectReason::Query);
+            }
+            reasons.push(MatchReason::Query {
+                query: query.to_string(),
+            });
+        }
+
+        let mut haystacks = vec![normalize_text(program.name.as_deref().unwrap_or(""))];
+        if c.search_description {
+            if let Some(description) = &program.description {
+                haystacks.push(normalize_text(description));
+            }
+        }
+        for keyword in &c.keywords {
+            if find_keyword(&haystacks, std::slice::from_ref(keyword)).is_none() {
+                return Err(RejectReason::MissingKeyword {
+                    keyword: keyword.clone(),
+                });
+            }
+            reasons.push(MatchReason::Keyword {
+                keyword: keyword.clone(),
+            });
+        }
+        if let Some(keyword) = find_keyword(&haystacks, &c.exclude_keywords) {
+            return Err(RejectReason::ExcludedKeyword {
+                keyword: keyword.clone(),
+            });
+        }
+
+        if !c.genres.is_empty() {
+            let genre = c
+                .genres
+                .iter()
+                .find(|g| {
+                    program
+                        .genres
+                        .iter()
+                        .any(|pg| pg == *g || pg.split('／').next() == Some(g.as_str()))
+                })
+                .ok_or(RejectReason::Genre)?;
+            reasons.push(MatchReason::Genre {
+                genre: genre.clone(),
+            });
+        }
+
+        if !c.channels.is_empty() {
+            let service_id = program.service_id.to_string();
+            let channel = c
+                .channels
+                .iter()
+                .find(|ch| **ch == program.channel_name || **ch == service_id)
+                .ok_or(RejectReason::Channel)?;
+            reasons.push(MatchReason::Channel {
+                channel: channel.clone(),
+            });
+        }
+
+        if !c.channel_types.is_empty() {
+            let channel_type = c
+                .channel_types
+                .iter()
+                .find(|t| t.eq_ignore_ascii_case(&program.channel_type))
+                .ok_or(RejectReason::ChannelType)?;
+            reasons.push(MatchReason::ChannelType {
+                channel_type: channel_type.clone(),
+            });
+        }
+
+        let start = jst(program.start_at);
+        if !c.weekdays.is_empty() {
+            let weekday = start.weekday();
+            if !c.weekdays.contains(&weekday) {
+                return Err(RejectReason::Weekday { weekday });
+            }
+            reasons.push(MatchReason::Weekday { weekday });
+        }
+
+        if let Some(window) = c.time_window {
+            if !window.contains(start.hour() * 60 + start.minute()) {
+                return Err(RejectReason::TimeWindow { window });
+            }
+            reasons.push(MatchReason::TimeWindow { window });
+        }
+
+        if c.min_duration_minutes.is_some() || c.max_duration_minutes.is_some() {
+            let minutes = program.duration_millis / 60_000;
+            if c.min_duration_minutes.is_some_and(|min| minutes < min) {
+                return Err(RejectReason::TooShort { minutes });
+            }
+            if c.max_duration_minutes.is_some_and(|max| minutes > max) {
+                return Err(RejectReason::TooLong { minutes });
+            }
+            reasons.push(MatchReason::Duration { minutes });
+        }
+
+        if c.free_only {
+            if !program.is_free {
+                return Err(RejectReason::NotFree);
+            }
+            reasons.push(MatchReason::Free);
+        }
+
+        Ok(reasons)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn program(name: &str) -> KurecProgram {
+        KurecProgram {
+            id: 327360102400101,
+            mirakc_url: "http://tuner:40772".to_string(),
+            service_id: 3273601024,
+            network_id: 32736,
+            event_id: 101,
+            channel_name: "ＮＨＫ総合１・東京".to_string(),
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+            name: Some(name.to_string()),
+            description: Some("宇宙世紀を舞台にしたロボットアニメ".to_string()),
+            extended: None,
+            // 2025-01-04 (土) 18:00 JST
+            start_at: Utc.with_ymd_and_hms(2025, 1, 4, 9, 0, 0).unwrap(),
+            duration_millis: 30 * 60_000,
+            is_free: true,
+            genres: vec!["アニメ・特撮／国内アニメ".to_string()],
+            video_info: None,
+            audio_infos: vec![],
+            series_info: None,
+            markers: Default::default(),
+            episode_number: None,
+        }
+    }
+
+    fn rule(conditions: RuleConditions) -> RecordingRule {
+        RecordingRule {
+            id: "gundam".to_string(),
+            name: "ガンダム".to_string(),
+            enabled: true,
+            priority: 10,
+            conditions,
+        }
+    }
+
+    #[test]
+    fn test_matches_with_reasons() {
+        let rule = rule(RuleConditions {
+            keywords: vec!["ガンダム".to_string()],
+            exclude_keywords: vec!["再放送".to_string()],
+            genres: vec!["アニメ・特撮".to_string()],
+            channels: vec!["ＮＨＫ総合１・東京".to_string()],
+            channel_types: vec!["gr".to_string()],
+            weekdays: vec![Weekday::Sat, Weekday::Sun],
+            time_window: Some("18:00-24:00".parse().unwrap()),
+            min_duration_minutes: Some(25),
+            max_duration_minutes: Some(60),
+            free_only: true,
+            ..Default::default()
+        });
+
+        let verdict = rule.evaluate(&program("機動戦士ガンダム"));
+
+        let RuleVerdict::Matched(reasons) = verdict else {
+            panic!("expected match: {:?}", verdict);
+        };
+        assert_eq!(reasons.len(), 8);
+        assert!(reasons.contains(&MatchReason::Weekday {
+            weekday: Weekday::Sat
+        }));
+        assert!(reasons.contains(&MatchReason::Duration { minutes: 30 }));
+    }
+
+    #[test]
+    fn test_rejections() {
+        let keyword = rule(RuleConditions {
+            keywords: vec!["ガンダム".to_string()],
+            exclude_keywords: vec!["再放送".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(
+            keyword.evaluate(&program("ニュース")),
+            RuleVerdict::Rejected(RejectReason::MissingKeyword {
+                keyword: "ガンダム".to_string()
+            })
+        );
+        assert_eq!(
+            keyword.evaluate(&program("機動戦士ガンダム（再放送）")),
+            RuleVerdict::Rejected(RejectReason::ExcludedKeyword {
+                keyword: "再放送".to_string()
+            })
+        );
+
+        let weekday = rule(RuleConditions {
+            genres: vec!["アニメ・特撮".to_string()],
+            weekdays: vec![Weekday::Mon],
+            ..Default::default()
+        });
+        assert_eq!(
+            weekday.evaluate(&program("機動戦士ガンダム")),
+            RuleVerdict::Rejected(RejectReason::Weekday {
+                weekday: Weekday::Sat
+            })
+        );
+
+        let empty = rule(RuleConditions::default());
+        assert_eq!(
+            empty.evaluate(&program("機動戦士ガンダム")),
+            RuleVerdict::Rejected(RejectReason::NoSelector)
+        );
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn test_description_keywords() {
+        let mut conditions = RuleConditions {
+            keywords: vec!["宇宙世紀".to_string()],
+            ..Default::default()
+        };
+        assert!(!rule(conditions.clone())
+            .evaluate(&program("機動戦士ガンダム"))
+            .is_matched());
+
+        conditions.search_description = true;
+        assert!(rule(conditions)
+            .evaluate(&program("機動戦士ガンダム"))
+            .is_matched());
+    }
+
+    #[test]
+    fn test_keywords_ignore_width_and_kana() {
+        let rule = rule(RuleConditions {
+            keywords: vec!["がんだむ seed".to_string()],
+            ..Default::default()
+        });
+        assert!(rule
+            .evaluate(&program("機動戦士ｶﾞﾝﾀﾞﾑ　ＳＥＥＤ【再】"))
+            .is_matched());
+    }
+
+    #[test]
+    fn test_time_window() {
+        let window: TimeWindow = "23:00-02:00".parse().unwrap();
+        assert!(window.contains(23 * 60 + 30));
+        assert!(window.contains(60));
+        assert!(!window.contains(2 * 60));
+        assert_eq!(window.to_string(), "23:00-02:00");
+
+        let window: TimeWindow = "18:00-24:00".parse().unwrap();
+        assert!(window.contains(23 * 60 + 59));
+        assert!(!window.contains(0));
+
+        assert!("25:00-26:00".parse::<TimeWindow>().is_err());
+        assert!("18:00".parse::<TimeWindow>().is_err());
+    }
+
+    #[test]
+    fn test_rule_deserialization() {
+        let json = r#"{
+            "id": "gundam",
+            "name": "ガンダム",
+            "conditions": {
+                "keywords": ["ガンダム"],
+                "weekdays": ["Sat"],
+                "time_window": "18:00-24:00"
+            }
+        }"#;
+        let rule: RecordingRule = serde_json::from_str(json).unwrap();
+
+        assert!(rule.enabled);
+        assert_eq!(rule.priority, 0);
+        assert_eq!(rule.conditions.weekdays, vec![Weekday::Sat]);
+        assert_eq!(rule.conditions.time_window.unwrap().start_minute, 18 * 60);
+        assert!(rule.validate().is_ok());
+    }
+
+    #[test]
+    fn test_query_condition() {
+        let json = r#"{
+            "id": "gundam",
+            "name": "ガンダム",
+            "conditions": {"query": "ガンダム -再放送 weekday:sat..sun"}
+        }"#;
+        let rule = &parse_rules(json).unwrap()[0];
+
+        assert_eq!(
+            rule.evaluate(&program("機動戦士ガンダム")),
+            RuleVerdict::Matched(vec![MatchReason::Query {
+                query: "ガンダム -再放送 weekday:sat..sun".to_string()
+            }])
+        );
+        assert_eq!(
+            rule.evaluate(&program("機動戦士ガンダム 再放送")),
+            RuleVerdict::Rejected(RejectReason::Query)
+        );
+
+        let invalid = r#"{"id": "bad", "name": "bad", "conditions": {"query": "foo:bar"}}"#;
+        assert!(parse_rules(invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let one =
+            r#"{"id": "gundam", "name": "ガンダム", "conditions": {"keywords": ["ガンダム"]}}"#;
+        assert_eq!(parse_rules(one).unwrap().len(), 1);
+
+        let many = r#"[
+            {"id": "gundam", "name": "ガンダム", "conditions": {"keywords": ["ガンダム"]}},
+            {"id": "nhk", "name": "NHK", "conditions": {"channels": ["3273601024"]}}
+        ]"#;
+        assert_eq!(parse_rules(many).unwrap().len(), 2);
+
+        let invalid = r#"[{"id": "all", "name": "すべて"}]"#;
+        assert!(parse_rules(invalid).is_err());
+        assert!(parse_rules("not json").is_err());
+    }
+}
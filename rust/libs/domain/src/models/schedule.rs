@@ -0,0 +1,224 @@
+//! 録画予約の望ましい状態 (Desired State) のドメインモデル
+//!
+//! ルールや手動の予約から「録画されるべき番組」を `DesiredSchedule` として表します。
+//! mirakc の録画予約 (`MirakcSchedule`) は、この望ましい状態に合わせて後から反映されます。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::epg::KurecProgram;
+use crate::models::rule::RuleMatch;
+
+/// KuRec が作成した録画予約に付けるタグ
+pub const KUREC_SCHEDULE_TAG: &str = "kurec";
+
+/// 録画されるべき番組
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DesiredSchedule {
+    /// 録画に使用するmirakcのベースURL
+    pub mirakc_url: String,
+    /// Mirakurun Program ID
+    pub program_id: i64,
+    /// Mirakurun Service ID
+    pub service_id: i64,
+    /// チャンネルタイプ (GR, BS, CS など)
+    pub channel_type: String,
+    /// 番組名
+    pub name: Option<String>,
+    /// 開始時刻
+    pub start_at: DateTime<Utc>,
+    /// 長さ (ミリ秒)
+    pub duration_millis: i64,
+    /// 一致したルール
+    #[serde(default)]
+    pub rule_matches: Vec<RuleMatch>,
+    /// 手動で予約されたかどうか
+    #[serde(default)]
+    pub manual: bool,
+    /// シリーズ録画の購読ID
+    #[serde(default)]
+    pub series: Option<String>,
+}
+
+impl DesiredSchedule {
+    /// 番組から録画予約を作成する。
+    pub fn from_program(program: &KurecProgram) -> Self {
+        Self {
+            mirakc_url: program.mirakc_url.clone(),
+            program_id: program.id,
+            service_id: program.service_id,
+            channel_type: program.channel_type.clone(),
+            name: program.name.clone(),
+            start_at: program.start_at,
+            duration_millis: program.duration_millis,
+            rule_matches: Vec::new(),
+            manual: false,
+            series: None,
+        }
+    }
+
+    /// 終了時刻
+    pub fn end_at(&self) -> DateTime<Utc> {
+        self.start_at + chrono::Duration::milliseconds(self.duration_millis)
+    }
+
+    /// 優先度。一致したルールの優先度の最大値 (ルールに一致していない場合は 0)
+    pub fn priority(&self) -> i32 {
+        self.rule_matches
+            .iter()
+            .map(|m| m.priority)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// mirakc の録画予約に付けるタグ (`kurec`、一致したルールごとの `rule:{id}`、シリーズ録画の `series:{id}`)
+    pub fn tags(&self) -> Vec<String> {
+        std::iter::once(KUREC_SCHEDULE_TAG.to_string())
+            .chain(
+                self.rule_matches
+                    .iter()
+                    .map(|m| format!("rule:{}", m.rule_id)),
+            )
+            .chain(self.series.iter().map(|id| format!("series:{}", id)))
+            .collect()
+    }
+
+    /// 録画予約が必要な理由 (ルール、シリーズ録画または手動) が残っているかどうか
+    pub fn is_wanted(&self) -> bool {
+        self.manual || self.series.is_some() || !self.rule_matches.is_empty()
+    }
+}
+
+/// mirakc の録画予約の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MirakcScheduleState {
+    Scheduled,
+    Tracking,
+    Recording,
+    Rescheduling,
+    Finished,
+    Failed,
+}
+
+impl MirakcScheduleState {
+    /// 録画が始まっていない (削除しても録画を中断しない) かどうか
+    pub fn is_pending(self) -> bool {
+        matches!(self, Self::Scheduled | Self::Tracking | Self::Rescheduling)
+    }
+}
+
+/// mirakc に登録されている録画予約
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirakcSchedule {
+    /// 録画予約が登録されている mirakc のベースURL
+    pub mirakc_url: String,
+    /// Mirakurun Program ID
+    pub program_id: i64,
+    /// 番組名
+    pub name: Option<String>,
+    /// 開始時刻
+    pub start_at: DateTime<Utc>,
+    /// 状態
+    pub state: MirakcScheduleState,
+    /// タグ
+    pub tags: Vec<String>,
+    /// チューナー使用の優先度
+    pub priority: Option<i32>,
+}
+
+impl MirakcSchedule {
+    /// KuRec が作成した録画予約かどうか
+    pub fn is_owned(&self) -> bool {
+        self.tags.iter().any(|t| t == KUREC_SCHEDULE_TAG)
+    }
+
+    /// タグと優先度が録画されるべき番組と一致しているかどうか
+    pub fn matches(&self, desired: &DesiredSchedule) -> bool {
+        let mut tags = self.tags.clone();
+        tags.sort_unstable();
+        let mut desired_tags = desired.tags();
+        desired_tags.sort_unstable();
+        tags == desired_tags && self.priority.unwrap_or(0) == desired.priority()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::rule::MatchReason;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_priority_and_tags() {
+        let mut schedule = DesiredSchedule {
+            mirakc_url: "http://tuner:40772".to_string(),
+            program_id: 327360102400101,
+            service_id: 3273601024,
+            channel_type: "GR".to_string(),
+            name: Some("機動戦士ガンダム".to_string()),
+            start_at: Utc.with_ymd_and_hms(2025, 1, 4, 9, 0, 0).unwrap(),
+            duration_millis: 1800000,
+            rule_matches: vec![],
+            manual: false,
+            series: None,
+        };
+        assert_eq!(schedule.priority(), 0);
+        assert_eq!(schedule.tags(), vec!["kurec"]);
+        assert!(!schedule.is_wanted());
+
+        for (id, priority) in [("gundam", 10), ("anime", 5)] {
+            schedule.rule_matches.push(RuleMatch {
+                rule_id: id.to_string(),
+                rule_name: id.to_string(),
+                priority,
+                reasons: vec![MatchReason::Free],
+            });
+        }
+        assert_eq!(schedule.priority(), 10);
+        assert_eq!(schedule.tags(), vec!["kurec", "rule:gundam", "rule:anime"]);
+        assert!(schedule.is_wanted());
+
+        schedule.rule_matches.clear();
+        schedule.series = Some("32736_100".to_string());
+        assert_eq!(schedule.tags(), vec!["kurec", "series:32736_100"]);
+        assert!(schedule.is_wanted());
+        assert_eq!(
+            schedule.end_at(),
+            Utc.with_ymd_and_hms(2025, 1, 4, 9, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_mirakc_schedule_matches() {
+        let mut desired = DesiredSchedule {
+            mirakc_url: "http://tuner:40772".to_string(),
+            program_id: 327360102400101,
+            service_id: 3273601024,
+            channel_type: "GR".to_string(),
+            name: None,
+            start_at: Utc.with_ymd_and_hms(2025, 1, 4, 9, 0, 0).unwrap(),
+            duration_millis: 1800000,
+            rule_matches: vec![],
+            manual: true,
+            series: Some("32736_100".to_string()),
+        };
+        let mut actual = MirakcSchedule {
+            mirakc_url: desired.mirakc_url.clone(),
+            program_id: desired.program_id,
+            name: None,
+            start_at: desired.start_at,
+            state: MirakcScheduleState::Scheduled,
+            tags: vec!["series:32736_100".to_string(), "kurec".to_string()],
+            priority: None,
+        };
+        assert!(actual.is_owned());
+        assert!(actual.matches(&desired));
+
+        desired.series = None;
+        assert!(!actual.matches(&desired));
+
+        actual.tags = vec!["manual".to_string()];
+        assert!(!actual.is_owned());
+    }
+}
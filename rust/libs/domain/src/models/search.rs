@@ -0,0 +1,390 @@
+//! 番組検索インデックスのドキュメント
+//!
+//! Web UI (`web/src/app/search/epg`) は旧実装の Meilisearch の EPG インデックスを前提にしているため、
+//! フィールド名は旧実装の `ProgramDocument` と同じ日本語の名前にしています。
+
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use crate::events::mirakc_events::RecordingStatus;
+use crate::models::epg::KurecProgram;
+use crate::models::record::{Record, RecordOutput};
+use crate::models::rule::jst;
+
+/// OGP を持たないため、OGP の取得対象にしない SNS のホスト
+const OGP_EXCLUDED_HOSTS: &[&str] = &[
+    "x.com",
+    "twitter.com",
+    "tiktok.com",
+    "instagram.com",
+    "www.instagram.com",
+    "facebook.com",
+];
+
+/// 番組検索インデックスのドキュメント
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramDocument {
+    /// 番組ID (主キー)
+    pub program_id: i64,
+    /// サービスID
+    pub service_id: i64,
+    /// 番組名
+    #[serde(rename = "タイトル")]
+    pub title: String,
+    /// 番組説明
+    #[serde(rename = "番組情報")]
+    pub description: String,
+    /// 詳細情報 (`見出し: 本文` を改行でつないだもの)
+    #[serde(rename = "その他情報")]
+    pub extended: String,
+    /// チャンネル名
+    #[serde(rename = "放送局")]
+    pub channel: String,
+    /// ジャンル
+    #[serde(rename = "ジャンル")]
+    pub genres: Vec<String>,
+    /// 出演者の名前
+    #[serde(rename = "出演者", default)]
+    pub cast: Vec<String>,
+    /// 開始時刻
+    #[serde(rename = "開始時刻")]
+    pub start_at: DateTime<Utc>,
+    /// 開始時刻の Unix 時間 (秒)。範囲での絞り込みに使う
+    #[serde(default)]
+    pub start_timestamp: i64,
+    /// 終了時刻
+    #[serde(rename = "終了時刻")]
+    pub end_at: DateTime<Utc>,
+    /// 放送開始の曜日 (日本時間。`月`〜`日`)
+    #[serde(rename = "放送曜日")]
+    pub day_of_week: String,
+    /// 番組の長さ (分)
+    #[serde(rename = "放送時間")]
+    pub duration: i64,
+    /// 詳細情報に含まれる URL
+    #[serde(rename = "公式サイト等")]
+    pub urls: Vec<String>,
+    /// OGP 画像を取得する URL (SNS を除いた最初の URL)
+    pub ogp_url: Option<String>,
+    /// `ogp_url` の SHA-1 (Web UI が OGP 画像を取得するキー)
+    pub ogp_url_hash: Option<String>,
+    /// 録画ライブラリのインデックスのドキュメントの場合は録画の情報
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record: Option<RecordDocument>,
+}
+
+/// 録画ライブラリのドキュメントの録画の情報
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordDocument {
+    /// mirakc の Record ID
+    pub record_id: String,
+    /// 録画を行った mirakc のベースURL
+    pub mirakc_url: String,
+    /// 録画開始時刻
+    pub recorded_at: DateTime<Utc>,
+    /// 録画開始時刻の Unix 時間 (秒)。範囲での絞り込みに使う
+    pub recorded_timestamp: i64,
+    /// 録画ステータス
+    pub status: RecordingStatus,
+    /// 出力と保存先
+    pub outputs: Vec<RecordOutput>,
+    /// 出力の保存先のストレージ名 (絞り込みに使う)
+    pub storages: Vec<String>,
+    /// 出力のエンコード状況 (絞り込みに使う)
+    pub encode_statuses: Vec<String>,
+    /// 録画予約時に付与されたタグ
+    pub tags: Vec<String>,
+}
+
+impl ProgramDocument {
+    /// 番組からドキュメントを作成する。
+    pub fn from_program(program: &KurecProgram) -> Self {
+        let details = program.details();
+        let extended = program
+            .extended
+            .as_ref()
+            .and_then(|e| e.as_object())
+            .map(|items| {
+                items
+                    .iter()
+                    .map(|(heading, text)| format!("{}: {}", heading, text.as_str().unwrap_or("")))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .unwrap_or_default();
+        let ogp_url = details
+            .urls
+            .iter()
+            .find(|url| !OGP_EXCLUDED_HOSTS.contains(&url_host(url)))
+            .cloned();
+        let ogp_url_hash = ogp_url.as_deref().map(url_hash);
+        let end_at = program.start_at + chrono::Duration::milliseconds(program.duration_millis);
+        let day_of_week = ["月", "火", "水", "木", "金", "土", "日"]
+            [jst(program.start_at).weekday().num_days_from_monday() as usize];
+
+        Self {
+            program_id: program.id,
+            service_id: program.service_id,
+            title: program
+                .name
+                .clone()
+                .unwrap_or_else(|| "＜不明な番組＞".to_string()),
+            description: program
+                .description
+                .clone()
+                .unwrap_or_else(|| "＜不明＞".to_string()),
+            extended,
+            channel: program.channel_name.clone(),
+            genres: program.genres.clone(),
+            cast: details.cast.into_iter().map(|c| c.name).collect(),
+            start_at: program.start_at,
+            start_timestamp: program.start_at.timestamp(),
+            end_at,
+            day_of_week: day_of_week.to_string(),
+            duration: program.duration_millis / 60_000,
+            urls: details.urls,
+            ogp_url,
+            ogp_url_hash,
+            record: None,
+        }
+    }
+
+    /// 録画レコードから録画ライブラリのドキュメントを作成する。番組の情報は録画時点のもの。
+    pub fn from_record(record: &Record) -> Self {
+        let outputs = record.outputs();
+        let mut storages: Vec<String> = outputs.iter().map(|o| o.storage.clone()).collect();
+        storages.sort();
+        storages.dedup();
+        let mut encode_statuses: Vec<String> = outputs
+            .iter()
+            .map(|o| o.encode_status.as_str().to_string())
+            .collect();
+        encode_statuses.sort();
+        encode_statuses.dedup();
+
+        Self {
+            record: Some(RecordDocument {
+                record_id: record.id.clone(),
+                mirakc_url: record.mirakc_url.clone(),
+                recorded_at: record.recording.start_time,
+                recorded_timestamp: record.recording.start_time.timestamp(),
+                status: record.recording.status.clone(),
+                outputs,
+                storages,
+                encode_statuses,
+                tags: record.tags.clone(),
+            }),
+            ..Self::from_program(&record.program)
+        }
+    }
+}
+
+/// 検索結果の並び順
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSort {
+    /// 関連度の高い順 (キーワードがない場合は開始時刻の早い順)
+    #[default]
+    Relevance,
+    /// 開始時刻の早い順
+    StartAt,
+    /// 録画開始時刻の新しい順 (録画ライブラリのみ)
+    RecordedAt,
+}
+
+/// 番組検索の条件
+///
+/// 絞り込みの各項目は、いずれかの値に一致すればよい。項目の間はすべてを満たす必要がある。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProgramSearchQuery {
+    /// キーワード (空白区切りのすべてを番組名・番組説明・詳細情報のいずれかに含む)
+    pub text: String,
+    /// ジャンル
+    pub genres: Vec<String>,
+    /// チャンネル名
+    pub channels: Vec<String>,
+    /// 放送開始の曜日 (日本時間。`月`〜`日`)
+    pub weekdays: Vec<String>,
+    /// この時刻以降に開始する番組
+    pub start_from: Option<DateTime<Utc>>,
+    /// この時刻より前に開始する番組
+    pub start_to: Option<DateTime<Utc>>,
+    /// この時刻以降に録画を開始した番組 (録画ライブラリのみ)
+    pub recorded_from: Option<DateTime<Utc>>,
+    /// この時刻より前に録画を開始した番組 (録画ライブラリのみ)
+    pub recorded_to: Option<DateTime<Utc>>,
+    /// 出力の保存先のストレージ名 (録画ライブラリのみ)
+    pub storages: Vec<String>,
+    /// 出力のエンコード状況 (録画ライブラリのみ。`EncodeStatus` の名前)
+    pub encode_statuses: Vec<String>,
+    /// 並び順
+    pub sort: SearchSort,
+    /// 読み飛ばす件数
+    pub offset: usize,
+    /// 最大件数 (0 の場合は `DEFAULT_SEARCH_LIMIT`)
+    pub limit: usize,
+}
+
+/// 録画ライブラリの検索の条件
+///
+/// 番組検索と同じ条件を、録画ライブラリのインデックスに対して問い合わせる。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RecordSearchQuery(pub ProgramSearchQuery);
+
+/// 検索結果の既定の最大件数
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+impl ProgramSearchQuery {
+    /// 最大件数
+    pub fn limit(&self) -> usize {
+        if self.limit == 0 {
+            DEFAULT_SEARCH_LIMIT
+        } else {
+            self.limit
+        }
+    }
+}
+
+/// キーワードに一致した箇所を `<em>` で囲んだテキスト (一致しなかった項目は `None`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchHighlights {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub extended: Option<String>,
+}
+
+/// 検索に一致した番組
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramSearchHit {
+    pub document: ProgramDocument,
+    pub highlights: SearchHighlights,
+}
+
+/// 番組検索の結果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramSearchResult {
+    /// 条件に一致した番組の数 (検索エンジンによっては推定値)
+    pub total: usize,
+    pub hits: Vec<ProgramSearchHit>,
+}
+
+/// URL のホスト部分
+fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.split(['/', '?', '#', ':']).next().unwrap_or(rest)
+}
+
+/// URL の SHA-1 (16進数の小文字)
+pub fn url_hash(url: &str) -> String {
+    Sha1::digest(url.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::record::{RecordContent, RecordingInfo};
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_program() {
+        let program = KurecProgram {
+            id: 327360102400101,
+            mirakc_url: "http://tuner:40772".to_string(),
+            service_id: 3273601024,
+            network_id: 32736,
+            event_id: 101,
+            channel_name: "ＮＨＫ総合１・東京".to_string(),
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+            name: Some("機動戦士ガンダム".to_string()),
+            description: None,
+            extended: Some(json!({
+                "出演者": "アムロ・レイ：古谷徹",
+                "ご案内": "https://x.com/gundam\nwww.example.com/gundam"
+            })),
+            // 2025-01-04 (土) 18:00 JST
+            start_at: Utc.with_ymd_and_hms(2025, 1, 4, 9, 0, 0).unwrap(),
+            duration_millis: 30 * 60_000,
+            is_free: true,
+            genres: vec!["アニメ・特撮／国内アニメ".to_string()],
+            video_info: None,
+            audio_infos: vec![],
+            series_info: None,
+            markers: Default::default(),
+            episode_number: None,
+        };
+
+        let document = ProgramDocument::from_program(&program);
+        assert_eq!(document.description, "＜不明＞");
+        assert_eq!(document.cast, vec!["古谷徹"]);
+        assert_eq!(document.day_of_week, "土");
+        assert_eq!(document.duration, 30);
+        assert_eq!(
+            document.end_at,
+            Utc.with_ymd_and_hms(2025, 1, 4, 9, 30, 0).unwrap()
+        );
+        assert_eq!(
+            document.urls,
+            vec!["https://x.com/gundam", "https://www.example.com/gundam"]
+        );
+        assert_eq!(
+            document.ogp_url.as_deref(),
+            Some("https://www.example.com/gundam")
+        );
+        assert_eq!(
+            document.ogp_url_hash.as_deref(),
+            Some(url_hash("https://www.example.com/gundam").as_str())
+        );
+        assert!(document.extended.contains("出演者: アムロ・レイ：古谷徹"));
+
+        let value = serde_json::to_value(&document).unwrap();
+        assert_eq!(value["タイトル"], "機動戦士ガンダム");
+        assert_eq!(value["放送局"], "ＮＨＫ総合１・東京");
+        assert_eq!(value["program_id"], 327360102400101i64);
+        assert!(value.get("record").is_none());
+
+        // 録画ライブラリのドキュメント
+        let recorded_at = Utc.with_ymd_and_hms(2025, 1, 4, 8, 59, 0).unwrap();
+        let record = Record {
+            id: "0000000000000001".to_string(),
+            mirakc_url: "http://tuner:40772".to_string(),
+            program: program.clone(),
+            recording: RecordingInfo {
+                status: RecordingStatus::Finished,
+                start_time: recorded_at,
+                end_time: None,
+                duration_millis: None,
+                failed_reason: None,
+            },
+            content: RecordContent {
+                path: "0000000000000001.m2ts".to_string(),
+                content_type: "video/MP2T".to_string(),
+                length: Some(1024),
+            },
+            tags: vec![],
+        };
+        let document = ProgramDocument::from_record(&record);
+        assert_eq!(document.title, "機動戦士ガンダム");
+        let info = document.record.unwrap();
+        assert_eq!(info.recorded_timestamp, recorded_at.timestamp());
+        assert_eq!(info.storages, vec!["mirakc"]);
+        assert_eq!(info.encode_statuses, vec!["original"]);
+        assert_eq!(info.outputs.len(), 1);
+    }
+
+    #[test]
+    fn test_url_hash() {
+        assert_eq!(url_hash("abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(
+            url_host("https://www.instagram.com:443/x"),
+            "www.instagram.com"
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! シリーズ録画のドメインモデル
+//!
+//! 番組のシリーズ情報 (`KurecSeriesInfo`) をもとに、同じシリーズの今後の放送をすべて録画するための
+//! 購読 (`SeriesSubscription`) を定義します。再放送と録画済みの話数は録画しません。
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::epg::{KurecProgram, KurecSeriesInfo};
+
+/// シリーズ録画の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeriesStatus {
+    /// 今後の放送を録画する
+    Active,
+    /// 最終話を録画した
+    Completed,
+}
+
+/// シリーズ録画の購読
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeriesSubscription {
+    /// 購読ID (`{network_id}_{series_id}`)
+    pub id: String,
+    /// Original Network ID
+    pub network_id: i64,
+    /// シリーズID
+    pub series_id: i64,
+    /// シリーズ名 (シリーズ情報に名前がない場合は番組名)
+    pub name: String,
+    /// シリーズ情報の有効期限。これより後に始まる放送は録画しない
+    pub expire_at: Option<DateTime<Utc>>,
+    /// 最終話の話数 (不明な場合は `None`)
+    pub last_episode: Option<i64>,
+    /// 録画済みの話数
+    #[serde(default)]
+    pub recorded_episodes: Vec<i64>,
+    /// 状態
+    pub status: SeriesStatus,
+    /// 購読した時刻
+    pub subscribed_at: DateTime<Utc>,
+}
+
+/// シリーズの放送を録画しない理由
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SeriesSkipReason {
+    /// 購読しているシリーズの番組ではない
+    NotInSeries,
+    /// 最終話を録画済み
+    Completed,
+    /// シリーズ情報の有効期限後の放送
+    Expired,
+    /// 再放送 (回数が不明な場合は 0)
+    Rerun { repeat: i64 },
+    /// 録画済みの話数
+    AlreadyRecorded { episode: i64 },
+    /// 最終話より後の話数
+    BeyondLastEpisode { episode: i64 },
+}
+
+impl fmt::Display for SeriesSkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInSeries => write!(f, "シリーズの番組ではない"),
+            Self::Completed => write!(f, "最終話を録画済み"),
+            Self::Expired => write!(f, "シリーズ情報の有効期限後の放送"),
+            Self::Rerun { repeat: 0 } => write!(f, "再放送"),
+            Self::Rerun { repeat } => write!(f, "再放送 ({}回目)", repeat),
+            Self::AlreadyRecorded { episode } => write!(f, "第{}話は録画済み", episode),
+            Self::BeyondLastEpisode { episode } => write!(f, "第{}話は最終話より後", episode),
+        }
+    }
+}
+
+/// シリーズの放送に対する判定結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeriesVerdict {
+    /// 録画する (話数が不明な場合は 0)
+    Record { episode: i64 },
+    /// 録画しない
+    Skip(SeriesSkipReason),
+}
+
+impl SeriesSubscription {
+    /// 購読IDを生成する。
+    pub fn key(network_id: i64, series_id: i64) -> String {
+        format!("{}_{}", network_id, series_id)
+    }
+
+    /// 番組のシリーズを購読する。番組にシリーズ情報がない場合は `None`。
+    pub fn from_program(program: &KurecProgram, now: DateTime<Utc>) -> Option<Self> {
+        let series = program.series_info.as_ref()?;
+        let name = if series.name.is_empty() {
+            program.name.clone().unwrap_or_default()
+        } else {
+            series.name.clone()
+        };
+        Some(Self {
+            id: Self::key(program.network_id, series.id),
+            network_id: program.network_id,
+            series_id: series.id,
+            name,
+            expire_at: series.expire_at,
+            last_episode: (series.last_episode > 0).then_some(series.last_episode),
+            recorded_episodes: Vec::new(),
+            status: SeriesStatus::Active,
+            subscribed_at: now,
+        })
+    }
+
+    /// 番組が購読しているシリーズの番組であれば、そのシリーズ情報を返す。
+    pub fn series_of<'a>(&self, program: &'a KurecProgram) -> Option<&'a KurecSeriesInfo> {
+        program
+            .series_info
+            .as_ref()
+            .filter(|s| program.network_id == self.network_id && s.id == self.series_id)
+    }
+
+    /// 番組を録画するか判定する。
+    pub fn evaluate(&self, program: &KurecProgram) -> SeriesVerdict {
+        let Some(series) = self.series_of(program) else {
+            return SeriesVerdict::Skip(SeriesSkipReason::NotInSeries);
+        };
+        if self.status == SeriesStatus::Completed {
+            return SeriesVerdict::Skip(SeriesSkipReason::Completed);
+        }
+        if self
+            .expire_at
+            .is_some_and(|expire_at| program.start_at > expire_at)
+        {
+            return SeriesVerdict::Skip(SeriesSkipReason::Expired);
+        }
+        // シリーズ情報に再放送の回数がなくても、番組名の【再】は再放送として扱う
+        if series.repeat > 0 || program.markers.rerun {
+            return SeriesVerdict::Skip(SeriesSkipReason::Rerun {
+                repeat: series.repeat,
+            });
+        }
+        let episode = program.episode();
+        if episode > 0 {
+            if self.recorded_episodes.contains(&episode) {
+                return SeriesVerdict::Skip(SeriesSkipReason::AlreadyRecorded { episode });
+            }
+            if self.last_episode.is_some_and(|last| episode > last) {
+                return SeriesVerdict::Skip(SeriesSkipReason::BeyondLastEpisode { episode });
+            }
+        }
+        SeriesVerdict::Record { episode }
+    }
+
+    /// 新しい番組情報のシリーズ情報で有効期限と最終話を更新する。変更があった場合は `true`。
+    pub fn update_from(&mut self, series: &KurecSeriesInfo) -> bool {
+        let mut changed = false;
+        if series.expire_at.is_some() && series.expire_at != self.expire_at {
+            self.expire_at = series.expire_at;
+            changed = true;
+        }
+        if series.last_episode > 0 && self.last_episode != Some(series.last_episode) {
+            self.last_episode = Some(series.last_episode);
+            changed = true;
+        }
+        changed
+    }
+
+    /// 話数を録画済みにする。最終話の録画により完了した場合は `true`。
+    pub fn mark_recorded(&mut self, episode: i64) -> bool {
+        if episode <= 0 {
+            return false;
+        }
+        if !self.recorded_episodes.contains(&episode) {
+            self.recorded_episodes.push(episode);
+            self.recorded_episodes.sort_unstable();
+        }
+        if self.status == SeriesStatus::Active && self.last_episode.is_some_and(|l| episode >= l) {
+            self.status = SeriesStatus::Completed;
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn program(episode: i64, repeat: i64, day: u32) -> KurecProgram {
+        KurecProgram {
+            id: 327360102400000 + episode,
+            mirakc_url: "http://tuner:40772".to_string(),
+            service_id: 3273601024,
+            network_id: 32736,
+            event_id: episode,
+            channel_name: "ＮＨＫ総合１・東京".to_string(),
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+            name: Some(format!("機動戦士ガンダム #{}", episode)),
+            description: None,
+            extended: None,
+            start_at: Utc.with_ymd_and_hms(2025, 1, day, 9, 0, 0).unwrap(),
+            duration_millis: 1800000,
+            is_free: true,
+            genres: vec![],
+            video_info: None,
+            audio_infos: vec![],
+            series_info: Some(KurecSeriesInfo {
+                id: 100,
+                repeat,
+                pattern: 1,
+                expire_at: Some(Utc.with_ymd_and_hms(2025, 3, 31, 0, 0, 0).unwrap()),
+                episode,
+                last_episode: 12,
+                name: "機動戦士ガンダム".to_string(),
+            }),
+            markers: Default::default(),
+            episode_number: None,
+        }
+    }
+
+    fn subscription() -> SeriesSubscription {
+        SeriesSubscription::from_program(&program(1, 0, 4), Utc::now()).unwrap()
+    }
+
+    #[test]
+    fn test_from_program() {
+        let sub = subscription();
+        assert_eq!(sub.id, "32736_100");
+        assert_eq!(sub.name, "機動戦士ガンダム");
+        assert_eq!(sub.last_episode, Some(12));
+        assert_eq!(sub.status, SeriesStatus::Active);
+
+        let mut no_series = program(1, 0, 4);
+        no_series.series_info = None;
+        assert!(SeriesSubscription::from_program(&no_series, Utc::now()).is_none());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut sub = subscription();
+        assert_eq!(
+            sub.evaluate(&program(2, 0, 11)),
+            SeriesVerdict::Record { episode: 2 }
+        );
+        assert_eq!(
+            sub.evaluate(&program(2, 1, 11)),
+            SeriesVerdict::Skip(SeriesSkipReason::Rerun { repeat: 1 })
+        );
+        assert_eq!(
+            sub.evaluate(&program(13, 0, 11)),
+            SeriesVerdict::Skip(SeriesSkipReason::BeyondLastEpisode { episode: 13 })
+        );
+
+        let mut other = program(2, 0, 11);
+        other.network_id = 4;
+        assert_eq!(
+            sub.evaluate(&other),
+            SeriesVerdict::Skip(SeriesSkipReason::NotInSeries)
+        );
+
+        // シリーズ情報に話数や再放送の回数がない場合は番組名から判断する
+        let mut from_title = program(0, 0, 18);
+        from_title.episode_number = Some(2);
+        assert_eq!(
+            sub.evaluate(&from_title),
+            SeriesVerdict::Record { episode: 2 }
+        );
+        from_title.markers.rerun = true;
+        assert_eq!(
+            sub.evaluate(&from_title),
+            SeriesVerdict::Skip(SeriesSkipReason::Rerun { repeat: 0 })
+        );
+
+        let mut expired = program(3, 0, 11);
+        expired.start_at = Utc.with_ymd_and_hms(2025, 4, 1, 9, 0, 0).unwrap();
+        assert_eq!(
+            sub.evaluate(&expired),
+            SeriesVerdict::Skip(SeriesSkipReason::Expired)
+        );
+
+        assert!(!sub.mark_recorded(2));
+        assert_eq!(
+            sub.evaluate(&program(2, 0, 18)),
+            SeriesVerdict::Skip(SeriesSkipReason::AlreadyRecorded { episode: 2 })
+        );
+    }
+
+    #[test]
+    fn test_mark_recorded_completes_series() {
+        let mut sub = subscription();
+        assert!(!sub.mark_recorded(0));
+        assert!(!sub.mark_recorded(11));
+        assert!(sub.mark_recorded(12));
+        assert_eq!(sub.status, SeriesStatus::Completed);
+        assert_eq!(sub.recorded_episodes, vec![11, 12]);
+        // 2回目の完了は通知しない
+        assert!(!sub.mark_recorded(12));
+        assert_eq!(
+            sub.evaluate(&program(5, 0, 11)),
+            SeriesVerdict::Skip(SeriesSkipReason::Completed)
+        );
+    }
+
+    #[test]
+    fn test_update_from() {
+        let mut sub = subscription();
+        let mut series = program(1, 0, 4).series_info.unwrap();
+        assert!(!sub.update_from(&series));
+
+        series.last_episode = 24;
+        series.expire_at = Some(Utc.with_ymd_and_hms(2025, 6, 30, 0, 0, 0).unwrap());
+        assert!(sub.update_from(&series));
+        assert_eq!(sub.last_episode, Some(24));
+    }
+}
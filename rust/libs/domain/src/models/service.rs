@@ -0,0 +1,142 @@
+//! サービス・チャンネルのドメインモデル
+//!
+//! mirakc の `/services` と `/channels` から得られるサービスカタログと、
+//! チャンネルスキャン前後のカタログの差分を定義します。
+
+use serde::{Deserialize, Serialize};
+
+/// 放送サービス
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Service {
+    /// サービスを取得したmirakcのベースURL
+    pub mirakc_url: String,
+    /// Mirakurun Service ID (`network_id * 100000 + service_id`)
+    pub id: i64,
+    /// Service ID
+    pub service_id: i64,
+    /// Network ID
+    pub network_id: i64,
+    /// サービス名
+    pub name: String,
+    /// サービス形式 (1: デジタルTV など)
+    pub service_type: i64,
+    /// ロゴID
+    pub logo_id: Option<i64>,
+    /// リモコンキーID
+    pub remote_control_key_id: Option<i64>,
+    /// mirakc がロゴ画像を持っているかどうか
+    pub has_logo_data: bool,
+    /// チャンネルタイプ (GR, BS, CS など)
+    pub channel_type: String,
+    /// チャンネル
+    pub channel: String,
+}
+
+/// 物理チャンネル
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Channel {
+    /// チャンネルを取得したmirakcのベースURL
+    pub mirakc_url: String,
+    /// チャンネルタイプ (GR, BS, CS など)
+    pub channel_type: String,
+    /// チャンネル
+    pub channel: String,
+    /// チャンネル名
+    pub name: String,
+    /// このチャンネルで放送されるサービスの Mirakurun Service ID
+    pub service_ids: Vec<i64>,
+}
+
+/// サービス名の変更
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceRename {
+    /// 変更前のサービス名
+    pub previous_name: String,
+    /// 変更後のサービス
+    pub service: Service,
+}
+
+/// サービスカタログの差分
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServiceCatalogDiff {
+    /// 追加されたサービス
+    pub added: Vec<Service>,
+    /// 削除されたサービス
+    pub removed: Vec<Service>,
+    /// 名前が変わったサービス
+    pub renamed: Vec<ServiceRename>,
+}
+
+impl ServiceCatalogDiff {
+    /// 2つのサービスカタログの差分を計算する。
+    ///
+    /// サービスは Mirakurun Service ID で同定する。
+    pub fn between(previous: &[Service], current: &[Service]) -> Self {
+        let mut diff = Self::default();
+        for service in current {
+            match previous.iter().find(|p| p.id == service.id) {
+                None => diff.added.push(service.clone()),
+                Some(p) if p.name != service.name => diff.renamed.push(ServiceRename {
+                    previous_name: p.name.clone(),
+                    service: service.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        diff.removed = previous
+            .iter()
+            .filter(|p| !current.iter().any(|s| s.id == p.id))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// 差分がないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.renamed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(service_id: i64, name: &str) -> Service {
+        Service {
+            mirakc_url: "http://tuner:40772".to_string(),
+            id: 32736 * 100000 + service_id,
+            service_id,
+            network_id: 32736,
+            name: name.to_string(),
+            service_type: 1,
+            logo_id: None,
+            remote_control_key_id: Some(1),
+            has_logo_data: false,
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff_between_catalogs() {
+        let previous = vec![service(1024, "ＮＨＫ総合"), service(1025, "ＮＨＫ総合２")];
+        let current = vec![
+            service(1024, "ＮＨＫ総合１・東京"),
+            service(1032, "ＮＨＫＥテレ"),
+        ];
+
+        let diff = ServiceCatalogDiff::between(&previous, &current);
+
+        assert_eq!(diff.added, vec![service(1032, "ＮＨＫＥテレ")]);
+        assert_eq!(diff.removed, vec![service(1025, "ＮＨＫ総合２")]);
+        assert_eq!(diff.renamed.len(), 1);
+        assert_eq!(diff.renamed[0].previous_name, "ＮＨＫ総合");
+        assert_eq!(diff.renamed[0].service.name, "ＮＨＫ総合１・東京");
+    }
+
+    #[test]
+    fn test_same_catalog_has_no_diff() {
+        let catalog = vec![service(1024, "ＮＨＫ総合")];
+        assert!(ServiceCatalogDiff::between(&catalog, &catalog).is_empty());
+    }
+}
@@ -0,0 +1,264 @@
+//! 番組名の正規化と放送マーカーの抽出
+//!
+//! EPG の番組名は全角・半角が混在し、`【字】`・`[再]`・`🈑` のような ARIB の放送マーカーを含みます。
+//! 比較用の正規化 (NFKC、カタカナのひらがなへの統一、小文字化) と、
+//! 放送マーカー (`BroadcastMarkers`) および話数の抽出を行います。
+
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+/// 番組名に含まれる放送マーカー
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BroadcastMarkers {
+    /// 字幕放送 (`字`)
+    pub subtitled: bool,
+    /// 再放送 (`再`)
+    pub rerun: bool,
+    /// 新番組 (`新`)
+    pub new: bool,
+    /// 最終回 (`終`)
+    pub finale: bool,
+    /// 二か国語放送 (`二`)
+    pub bilingual: bool,
+    /// データ放送 (`デ`)
+    pub data_broadcast: bool,
+    /// 解説放送 (`解`)
+    pub audio_description: bool,
+}
+
+/// 番組名から取り除く放送マーカー (フラグを持たないものを含む)
+const MARKER_CODES: &[&str] = &[
+    "字", "再", "新", "終", "二", "デ", "解", "多", "双", "手", "天", "交", "映", "無", "料", "前",
+    "後", "初", "生", "販", "声", "吹", "演", "移", "他", "N", "S", "SS", "B", "W", "P", "HV",
+    "SD", "MV", "PPV", "5.1", "4K", "8K",
+];
+
+impl BroadcastMarkers {
+    /// いずれかのフラグが立っているかどうか
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 放送マーカーのコードに対応するフラグを立てる。
+    fn set(&mut self, code: &str) {
+        match code {
+            "字" => self.subtitled = true,
+            "再" => self.rerun = true,
+            "新" => self.new = true,
+            "終" => self.finale = true,
+            "二" => self.bilingual = true,
+            "デ" => self.data_broadcast = true,
+            "解" => self.audio_description = true,
+            _ => {}
+        }
+    }
+}
+
+/// 番組名を解析した結果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedTitle {
+    /// 放送マーカーを取り除き、NFKC で正規化した番組名
+    pub title: String,
+    /// 放送マーカー
+    pub markers: BroadcastMarkers,
+    /// 話数 (`#12`、`第12話`、`(12)` の形式)
+    pub episode: Option<i64>,
+}
+
+/// 囲み文字 (`🈑` など) を `[字]` の形式に展開してから NFKC で正規化する。
+pub(crate) fn nfkc(s: &str) -> String {
+    let mut expanded = String::with_capacity(s.len());
+    for c in s.chars() {
+        if ('\u{1F130}'..='\u{1F2FF}').contains(&c) {
+            let inner: String = c.to_string().nfkc().collect();
+            if inner != c.to_string() {
+                expanded.push('[');
+                expanded.push_str(&inner);
+                expanded.push(']');
+                continue;
+            }
+        }
+        expanded.push(c);
+    }
+    expanded.nfkc().collect()
+}
+
+/// 連続する空白を1つにし、前後の空白を取り除く。
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 比較のために文字列を正規化する。
+///
+/// NFKC で全角英数字・半角カナなどの幅を揃え、カタカナをひらがなにし、小文字にして空白をまとめる。
+pub fn normalize_text(s: &str) -> String {
+    let folded: String = nfkc(s)
+        .chars()
+        .map(|c| match c {
+            'ァ'..='ヶ' | 'ヽ' | 'ヾ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+            _ => c,
+        })
+        .flat_map(char::to_lowercase)
+        .collect();
+    collapse_whitespace(&folded)
+}
+
+/// 括弧の中身が放送マーカーであれば、そのコードのリストを返す。`【字・再】` のような併記も受け付ける。
+fn marker_codes(inner: &str) -> Option<Vec<&str>> {
+    let codes: Vec<&str> = inner.split(['・', ',']).map(str::trim).collect();
+    codes
+        .iter()
+        .all(|code| MARKER_CODES.contains(code))
+        .then_some(codes)
+}
+
+/// 番組名から放送マーカーを取り除き、フラグとして集める。
+fn strip_markers(s: &str, markers: &mut BroadcastMarkers) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find(['【', '[', '(']) {
+        let open = rest[pos..].chars().next().unwrap();
+        let close = match open {
+            '【' => '】',
+            '[' => ']',
+            _ => ')',
+        };
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + open.len_utf8()..];
+        match after.find(close) {
+            Some(end) if marker_codes(&after[..end]).is_some() => {
+                for code in marker_codes(&after[..end]).unwrap() {
+                    markers.set(code);
+                }
+                out.push(' ');
+                rest = &after[end + close.len_utf8()..];
+            }
+            _ => {
+                out.push(open);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 漢数字 (`十二`、`百五` など) を数値にする。
+fn parse_kanji_number(s: &str) -> Option<i64> {
+    let digit = |c: char| "〇一二三四五六七八九".chars().position(|d| d == c);
+    let mut total = 0;
+    let mut current = None;
+    for c in s.chars() {
+        match c {
+            '十' | '百' => {
+                let unit = if c == '十' { 10 } else { 100 };
+                total += current.unwrap_or(1) * unit;
+                current = None;
+            }
+            _ => current = Some(current.unwrap_or(0) * 10 + digit(c)? as i64),
+        }
+    }
+    let n = total + current.unwrap_or(0);
+    (n > 0).then_some(n)
+}
+
+/// 番組名から最初に現れる話数を取り出す。
+fn find_episode(s: &str) -> Option<i64> {
+    let is_kanji_digit = |c: char| "〇一二三四五六七八九十百".contains(c);
+    for (i, c) in s.char_indices() {
+        let after = &s[i + c.len_utf8()..];
+        let digits = |pred: &dyn Fn(char) -> bool| {
+            let end = after.find(|c: char| !pred(c)).unwrap_or(after.len());
+            (&after[..end], &after[end..])
+        };
+        let episode = match c {
+            '#' => {
+                let (n, _) = digits(&|c| c.is_ascii_digit());
+                n.parse().ok()
+            }
+            // `(2023)` のような年と区別するため、3桁まで
+            '(' => match digits(&|c| c.is_ascii_digit()) {
+                (n, rest) if n.len() <= 3 && rest.starts_with(')') => n.parse().ok(),
+                _ => None,
+            },
+            '第' => match digits(&|c| c.is_ascii_digit()) {
+                (n, rest) if !n.is_empty() && rest.starts_with('話') => n.parse().ok(),
+                _ => match digits(&is_kanji_digit) {
+                    (n, rest) if rest.starts_with('話') => parse_kanji_number(n),
+                    _ => None,
+                },
+            },
+            _ => None,
+        };
+        if let Some(episode) = episode.filter(|&n: &i64| n > 0) {
+            return Some(episode);
+        }
+    }
+    None
+}
+
+/// 番組名を正規化し、放送マーカーと話数を取り出す。
+pub fn parse_title(name: &str) -> ParsedTitle {
+    let mut markers = BroadcastMarkers::default();
+    let title = collapse_whitespace(&strip_markers(&nfkc(name), &mut markers));
+    let episode = find_episode(&title);
+    ParsedTitle {
+        title,
+        markers,
+        episode,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_text() {
+        assert_eq!(normalize_text("ＮＨＫ総合１"), "nhk総合1");
+        assert_eq!(normalize_text("ｶﾞﾝﾀﾞﾑ　ＳＥＥＤ"), "がんだむ seed");
+        assert_eq!(
+            normalize_text("ガンダム  seed"),
+            normalize_text("がんだむ ＳＥＥＤ")
+        );
+    }
+
+    #[test]
+    fn test_parse_markers() {
+        let parsed = parse_title("【新】アニメ「タイトル」【字】【デ】[解][二]");
+        assert_eq!(parsed.title, "アニメ「タイトル」");
+        assert_eq!(
+            parsed.markers,
+            BroadcastMarkers {
+                subtitled: true,
+                new: true,
+                bilingual: true,
+                data_broadcast: true,
+                audio_description: true,
+                ..Default::default()
+            }
+        );
+
+        // 囲み文字・全角の括弧・併記
+        let parsed = parse_title("ドラマ🈞［終］【字・多】");
+        assert_eq!(parsed.title, "ドラマ");
+        assert!(parsed.markers.rerun && parsed.markers.finale && parsed.markers.subtitled);
+
+        // 放送マーカーでない括弧は残す
+        let parsed = parse_title("ニュース【全国】");
+        assert_eq!(parsed.title, "ニュース【全国】");
+        assert!(parsed.markers.is_empty());
+    }
+
+    #[test]
+    fn test_parse_episode() {
+        assert_eq!(parse_title("アニメ ＃１２「タイトル」").episode, Some(12));
+        assert_eq!(parse_title("ドラマ 第3話").episode, Some(3));
+        assert_eq!(parse_title("ドラマ 第十二話").episode, Some(12));
+        assert_eq!(parse_title("アニメ（12）[字]").episode, Some(12));
+        assert_eq!(parse_title("ニュース 第1部").episode, None);
+        assert_eq!(parse_title("映画(2023)").episode, None);
+        assert_eq!(parse_title("#なし").episode, None);
+    }
+}
@@ -0,0 +1,192 @@
+//! チューナーのドメインモデル
+//!
+//! mirakc のチューナー情報と、KuRec で管理するチューナーの状態・使用履歴を定義します。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// mirakc から取得したチューナー情報
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tuner {
+    /// mirakc の config.yml で定義されたチューナーのインデックス
+    pub index: i64,
+    /// チューナー名
+    pub name: String,
+    /// 対応するチャンネルタイプ (例: "GR", "BS", "CS")
+    pub types: Vec<String>,
+    /// 使用中かどうか
+    pub is_using: bool,
+    /// チューナーの利用者
+    pub users: Vec<TunerUser>,
+    /// 実行中のコマンド (チャンネル指定を含む)。未使用時は `None`
+    pub command: Option<String>,
+}
+
+/// チューナーの利用者
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TunerUser {
+    /// 利用者ID
+    pub id: String,
+    /// User-Agent
+    pub agent: Option<String>,
+    /// 優先度
+    pub priority: i64,
+}
+
+/// KV に保存するチューナーの現在の状態
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TunerStatus {
+    /// チューナーを管理する mirakc のベースURL
+    pub mirakc_url: String,
+    /// チューナーのインデックス
+    pub index: i64,
+    /// チューナー名
+    pub name: String,
+    /// 対応するチャンネルタイプ
+    pub types: Vec<String>,
+    /// 使用中かどうか
+    pub in_use: bool,
+    /// チューナーの利用者
+    pub users: Vec<TunerUser>,
+    /// 実行中のコマンド (チャンネル指定を含む)
+    pub command: Option<String>,
+    /// 現在の状態 (使用中/未使用と利用者の組み合わせ) になった時刻
+    pub since: DateTime<Utc>,
+    /// 最後に状態を取得した時刻
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TunerStatus {
+    /// mirakc のチューナー情報から状態を作成する
+    pub fn from_tuner(mirakc_url: &str, tuner: Tuner, at: DateTime<Utc>) -> Self {
+        Self {
+            mirakc_url: mirakc_url.to_string(),
+            index: tuner.index,
+            name: tuner.name,
+            types: tuner.types,
+            in_use: tuner.is_using,
+            users: tuner.users,
+            command: tuner.command,
+            since: at,
+            updated_at: at,
+        }
+    }
+
+    /// 使用状況 (使用中かどうかと利用者の集合) が同じかどうか
+    pub fn same_usage(&self, other: &TunerStatus) -> bool {
+        self.in_use == other.in_use && self.user_ids() == other.user_ids()
+    }
+
+    /// 指定されたチャンネルタイプに対応するチューナーかどうか
+    pub fn supports(&self, channel_type: &str) -> bool {
+        self.types
+            .iter()
+            .any(|t| t.eq_ignore_ascii_case(channel_type))
+    }
+
+    fn user_ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self.users.iter().map(|u| u.id.as_str()).collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+/// チューナーの使用区間
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TunerUsageInterval {
+    /// チューナーを管理する mirakc のベースURL
+    pub mirakc_url: String,
+    /// チューナーのインデックス
+    pub tuner_index: i64,
+    /// チューナー名
+    pub tuner_name: String,
+    /// 対応するチャンネルタイプ
+    pub types: Vec<String>,
+    /// 使用していた利用者
+    pub users: Vec<TunerUser>,
+    /// 使用開始時刻
+    pub started_at: DateTime<Utc>,
+    /// 使用終了時刻 (使用中の場合は `None`)
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl TunerUsageInterval {
+    /// 使用中のチューナー状態から区間を作成する (終了時刻は `ended_at`)
+    pub fn from_status(status: &TunerStatus, ended_at: Option<DateTime<Utc>>) -> Self {
+        Self {
+            mirakc_url: status.mirakc_url.clone(),
+            tuner_index: status.index,
+            tuner_name: status.name.clone(),
+            types: status.types.clone(),
+            users: status.users.clone(),
+            started_at: status.since,
+            ended_at,
+        }
+    }
+
+    /// 指定時刻に使用中だったかどうか
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.started_at <= at && self.ended_at.is_none_or(|end| at < end)
+    }
+
+    /// 期間 `[from, to)` と重なるかどうか
+    pub fn overlaps(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> bool {
+        self.started_at < to && self.ended_at.is_none_or(|end| from < end)
+    }
+
+    /// 指定されたチャンネルタイプに対応するチューナーかどうか
+    pub fn supports(&self, channel_type: &str) -> bool {
+        self.types
+            .iter()
+            .any(|t| t.eq_ignore_ascii_case(channel_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).unwrap()
+    }
+
+    fn interval(start: u32, end: Option<u32>) -> TunerUsageInterval {
+        TunerUsageInterval {
+            mirakc_url: "http://tuner:40772".to_string(),
+            tuner_index: 0,
+            tuner_name: "PX4-S1".to_string(),
+            types: vec!["BS".to_string(), "CS".to_string()],
+            users: vec![],
+            started_at: at(start),
+            ended_at: end.map(at),
+        }
+    }
+
+    #[test]
+    fn test_interval_contains() {
+        let closed = interval(10, Some(12));
+        assert!(!closed.contains(at(9)));
+        assert!(closed.contains(at(10)));
+        assert!(closed.contains(at(11)));
+        assert!(!closed.contains(at(12)));
+
+        let open = interval(10, None);
+        assert!(open.contains(at(23)));
+    }
+
+    #[test]
+    fn test_interval_overlaps() {
+        let closed = interval(10, Some(12));
+        assert!(closed.overlaps(at(11), at(13)));
+        assert!(!closed.overlaps(at(12), at(13)));
+        assert!(!closed.overlaps(at(8), at(10)));
+    }
+
+    #[test]
+    fn test_interval_supports() {
+        let i = interval(10, None);
+        assert!(i.supports("bs"));
+        assert!(!i.supports("GR"));
+    }
+}
@@ -1,5 +1,12 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// KuRec がサポートする mirakc のバージョン範囲 (semver の要件形式)
+///
+/// 録画レコード API など mirakc 4 系で追加された API を使用するため、4 系のみをサポートする。
+pub const SUPPORTED_MIRAKC_VERSION_REQ: &str = ">=4.0.0, <5.0.0";
 
 /// mirakcのバージョン情報
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -25,6 +32,37 @@ pub enum VersionStatus {
     Development,
 }
 
+/// サポート範囲外の mirakc に接続したときの動作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VersionGatePolicy {
+    /// 起動を拒否する
+    #[default]
+    Strict,
+    /// 警告を出力して起動を続ける
+    Warn,
+}
+
+impl FromStr for VersionGatePolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "strict" => Ok(Self::Strict),
+            "warn" => Ok(Self::Warn),
+            _ => bail!("unknown version gate policy: {} (strict または warn)", s),
+        }
+    }
+}
+
+impl fmt::Display for VersionGatePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Strict => write!(f, "strict"),
+            Self::Warn => write!(f, "warn"),
+        }
+    }
+}
+
 impl Version {
     /// バージョン情報を解析
     pub fn parse_versions(&self) -> Result<(semver::Version, semver::Version)> {
@@ -44,6 +82,15 @@ impl Version {
         Ok(current >= latest)
     }
 
+    /// 現在のバージョンが要件を満たすかどうかを確認
+    ///
+    /// 開発版 (`4.0.0-dev.0` など) はプレリリース部分を除いたバージョンで判定する。
+    pub fn is_compatible_with(&self, requirement: &semver::VersionReq) -> Result<bool> {
+        let mut current = semver::Version::parse(&self.current)?;
+        current.pre = semver::Prerelease::EMPTY;
+        Ok(requirement.matches(&current))
+    }
+
     /// バージョン状態を取得
     pub fn version_status(&self) -> Result<VersionStatus> {
         // 開発版の場合
@@ -117,7 +164,7 @@ mod tests {
         assert_eq!(current.patch, 3);
 
         // プレリリース部分を文字列として検証
-        assert!(current.pre.len() > 0);
+        assert!(!current.pre.is_empty());
         assert_eq!(current.pre.to_string(), "dev.4");
     }
 
@@ -187,4 +234,34 @@ mod tests {
         assert!(invalid_version.parse_versions().is_err());
         assert!(invalid_version.version_status().is_err());
     }
+
+    #[test]
+    fn test_is_compatible_with() {
+        let requirement = semver::VersionReq::parse(SUPPORTED_MIRAKC_VERSION_REQ).unwrap();
+        let version = |current: &str| Version {
+            current: current.to_string(),
+            latest: "4.0.0".to_string(),
+        };
+
+        assert!(version("4.0.0").is_compatible_with(&requirement).unwrap());
+        assert!(version("4.0.0-dev.0")
+            .is_compatible_with(&requirement)
+            .unwrap());
+        assert!(!version("3.4.1").is_compatible_with(&requirement).unwrap());
+        assert!(!version("5.0.0").is_compatible_with(&requirement).unwrap());
+        assert!(version("invalid").is_compatible_with(&requirement).is_err());
+    }
+
+    #[test]
+    fn test_version_gate_policy_from_str() {
+        assert_eq!(
+            "strict".parse::<VersionGatePolicy>().unwrap(),
+            VersionGatePolicy::Strict
+        );
+        assert_eq!(
+            "warn".parse::<VersionGatePolicy>().unwrap(),
+            VersionGatePolicy::Warn
+        );
+        assert!("ignore".parse::<VersionGatePolicy>().is_err());
+    }
 }
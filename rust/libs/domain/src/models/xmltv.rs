@@ -0,0 +1,415 @@
+//! XMLTV 形式の番組表
+//!
+//! 保存済みの `KurecProgram` から、Jellyfin / Plex / Kodi などに取り込める XMLTV 文書を生成します。
+//! 複数の mirakc から取得した番組は番組IDで重複を除いて1つの番組表にまとめ、チャンネルはネットワークIDとサービスIDで区別します。
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use chrono::{DateTime, FixedOffset, Utc};
+
+use crate::models::epg::KurecProgram;
+
+/// XMLTV の生成オプション
+#[derive(Debug, Clone, Default)]
+pub struct XmltvOptions {
+    /// チャンネルロゴ (mirakc の `/api/services/{id}/logo`) を出力するかどうか
+    pub include_logos: bool,
+}
+
+/// XMLTV のチャンネル
+#[derive(Debug, Clone, PartialEq)]
+struct XmltvChannel {
+    id: String,
+    service_id: i64,
+    name: String,
+    channel_type: String,
+    mirakc_url: String,
+}
+
+/// 番組のリストから XMLTV 文書を生成する。
+///
+/// 同じ番組ID・サービスIDが複数の mirakc に存在する場合は、
+/// mirakc のURLが辞書順で最初のものを採用する。
+pub fn build_xmltv(programs: Vec<KurecProgram>, options: &XmltvOptions) -> String {
+    let mut programs = programs;
+    programs.sort_by(|a, b| {
+        a.id.cmp(&b.id)
+            .then_with(|| a.mirakc_url.cmp(&b.mirakc_url))
+    });
+    programs.dedup_by_key(|p| p.id);
+    programs.sort_by(|a, b| {
+        a.network_id
+            .cmp(&b.network_id)
+            .then_with(|| a.service_id.cmp(&b.service_id))
+            .then_with(|| a.start_at.cmp(&b.start_at))
+    });
+
+    let mut channels: BTreeMap<(usize, i64, i64), XmltvChannel> = BTreeMap::new();
+    let mut seen = BTreeMap::new();
+    for program in &programs {
+        let key = (
+            channel_type_order(&program.channel_type),
+            program.network_id,
+            program.service_id,
+        );
+        let channel = XmltvChannel {
+            id: channel_id(program),
+            service_id: program.service_id,
+            name: program.channel_name.clone(),
+            channel_type: program.channel_type.clone(),
+            mirakc_url: program.mirakc_url.clone(),
+        };
+        match seen.get(&channel.id) {
+            Some(url) if url <= &program.mirakc_url => {}
+            _ => {
+                seen.insert(channel.id.clone(), program.mirakc_url.clone());
+                channels.insert(key, channel);
+            }
+        }
+    }
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<!DOCTYPE tv SYSTEM \"xmltv.dtd\">\n");
+    xml.push_str("<tv generator-info-name=\"KuRec\">\n");
+    for channel in channels.values() {
+        write_channel(&mut xml, channel, options);
+    }
+    for program in &programs {
+        write_programme(&mut xml, program);
+    }
+    xml.push_str("</tv>\n");
+    xml
+}
+
+/// XMLTV のチャンネルID (`{network_id}.{service_id}`)
+///
+/// サービスIDはネットワークごとに割り当てられるため、ネットワークIDと組み合わせて一意にする。
+fn channel_id(program: &KurecProgram) -> String {
+    format!("{}.{}", program.network_id, program.service_id)
+}
+
+/// チャンネルの並び順 (地上波、BS、CS、その他の順)
+fn channel_type_order(channel_type: &str) -> usize {
+    match channel_type {
+        "GR" => 0,
+        "BS" => 1,
+        "CS" => 2,
+        _ => 3,
+    }
+}
+
+fn write_channel(xml: &mut String, channel: &XmltvChannel, options: &XmltvOptions) {
+    let _ = writeln!(xml, "  <channel id=\"{}\">", channel.id);
+    let _ = writeln!(
+        xml,
+        "    <display-name lang=\"ja\">{}</display-name>",
+        escape(&channel.name)
+    );
+    let _ = writeln!(
+        xml,
+        "    <display-name>{} {}</display-name>",
+        escape(&channel.channel_type),
+        channel.service_id
+    );
+    if options.include_logos {
+        let _ = writeln!(
+            xml,
+            "    <icon src=\"{}/api/services/{}/logo\" />",
+            escape(channel.mirakc_url.trim_end_matches('/')),
+            channel.service_id
+        );
+    }
+    xml.push_str("  </channel>\n");
+}
+
+fn write_programme(xml: &mut String, program: &KurecProgram) {
+    let stop = program.start_at + chrono::Duration::milliseconds(program.duration_millis);
+    let _ = writeln!(
+        xml,
+        "  <programme start=\"{}\" stop=\"{}\" channel=\"{}\">",
+        format_time(program.start_at),
+        format_time(stop),
+        channel_id(program)
+    );
+    let _ = writeln!(
+        xml,
+        "    <title lang=\"ja\">{}</title>",
+        escape(program.name.as_deref().unwrap_or(""))
+    );
+    if let Some(desc) = description(program) {
+        let _ = writeln!(xml, "    <desc lang=\"ja\">{}</desc>", escape(&desc));
+    }
+    for category in categories(program) {
+        let _ = writeln!(xml, "    {}", category);
+    }
+    if let Some(series) = program.series_info.as_ref().filter(|s| s.episode > 0) {
+        let total = if series.last_episode > 0 {
+            format!("/{}", series.last_episode)
+        } else {
+            String::new()
+        };
+        let _ = writeln!(
+            xml,
+            "    <episode-num system=\"xmltv_ns\">.{}{}.</episode-num>",
+            series.episode - 1,
+            total
+        );
+        let _ = writeln!(
+            xml,
+            "    <episode-num system=\"onscreen\">#{}</episode-num>",
+            series.episode
+        );
+    }
+    if let Some(quality) = program.video_info.as_deref().and_then(video_quality) {
+        let _ = writeln!(xml, "    <video><quality>{}</quality></video>", quality);
+    }
+    if let Some(stereo) = program
+        .audio_infos
+        .first()
+        .and_then(|a| audio_stereo(a.as_str()))
+    {
+        let _ = writeln!(xml, "    <audio><stereo>{}</stereo></audio>", stereo);
+    }
+    if program.series_info.as_ref().is_some_and(|s| s.repeat > 0) {
+        xml.push_str("    <previously-shown />\n");
+    }
+    xml.push_str("  </programme>\n");
+}
+
+/// XMLTV の日時形式 (`YYYYMMDDhhmmss +0900`)
+fn format_time(at: DateTime<Utc>) -> String {
+    let jst = FixedOffset::east_opt(9 * 3600).unwrap();
+    at.with_timezone(&jst).format("%Y%m%d%H%M%S %z").to_string()
+}
+
+/// 番組説明と詳細情報 (`extended`) をまとめた説明文
+fn description(program: &KurecProgram) -> Option<String> {
+    let mut parts: Vec<String> = program
+        .description
+        .iter()
+        .filter(|d| !d.is_empty())
+        .cloned()
+        .collect();
+    if let Some(extended) = program.extended.as_ref().and_then(|e| e.as_object()) {
+        for (heading, text) in extended {
+            let text = text
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| text.to_string());
+            parts.push(format!("{}\n{}", heading, text));
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+/// ジャンル (「大分類／中分類」) を XMLTV の category 要素に変換する。
+///
+/// 大分類・中分類を日本語で出力し、メディアサーバーが番組の種類を判定できるよう
+/// 主要な大分類には英語の category も付ける。
+fn categories(program: &KurecProgram) -> Vec<String> {
+    let mut ja: Vec<&str> = Vec::new();
+    let mut en: Vec<&str> = Vec::new();
+    for genre in &program.genres {
+        let mut parts = genre.splitn(2, '／');
+        if let Some(main) = parts.next() {
+            ja.push(main);
+            en.extend(english_category(main));
+        }
+        if let Some(sub) = parts.next().filter(|s| *s != "？") {
+            ja.push(sub);
+        }
+    }
+    let mut seen = Vec::new();
+    ja.into_iter()
+        .map(|c| ("ja", c))
+        .chain(en.into_iter().map(|c| ("en", c)))
+        .filter(|entry| {
+            let new = !seen.contains(entry);
+            seen.push(*entry);
+            new
+        })
+        .map(|(lang, c)| format!("<category lang=\"{}\">{}</category>", lang, escape(c)))
+        .collect()
+}
+
+fn english_category(main: &str) -> Option<&'static str> {
+    match main {
+        "ニュース・報道" => Some("News"),
+        "スポーツ" => Some("Sports"),
+        "ドラマ" => Some("Drama"),
+        "音楽" => Some("Music"),
+        "映画" => Some("Movie"),
+        "アニメ・特撮" => Some("Animation"),
+        "ドキュメンタリー・教養" => Some("Documentary"),
+        _ => None,
+    }
+}
+
+fn video_quality(resolution: &str) -> Option<&'static str> {
+    match resolution {
+        "2160p" | "4320p" => Some("UHD"),
+        "1080i" | "1080p" | "720p" => Some("HDTV"),
+        "480i" | "480p" | "240p" => Some("SDTV"),
+        _ => None,
+    }
+}
+
+fn audio_stereo(audio: &str) -> Option<&'static str> {
+    match audio {
+        "モノラル" => Some("mono"),
+        "デュアルモノ" => Some("bilingual"),
+        "ステレオ" => Some("stereo"),
+        "2/1モード" | "3/0モード" | "2/2モード" | "3/1モード" | "3/2モード" | "3/2.1モード" => {
+            Some("surround")
+        }
+        _ => None,
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::epg::KurecSeriesInfo;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn program(id: i64, service_id: i64, mirakc_url: &str, hour: u32) -> KurecProgram {
+        KurecProgram {
+            id,
+            mirakc_url: mirakc_url.to_string(),
+            service_id,
+            network_id: 32736,
+            event_id: id % 100000,
+            channel_name: format!("チャンネル{}", service_id),
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+            name: Some(format!("番組{}", id)),
+            description: None,
+            extended: None,
+            start_at: Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).unwrap(),
+            duration_millis: 1800000,
+            is_free: true,
+            genres: vec![],
+            video_info: None,
+            audio_infos: vec![],
+            series_info: None,
+            markers: Default::default(),
+            episode_number: None,
+        }
+    }
+
+    #[test]
+    fn test_merges_hosts_and_deduplicates() {
+        let programs = vec![
+            program(2, 1024, "http://tuner2:40772", 11),
+            program(1, 1024, "http://tuner2:40772", 10),
+            program(1, 1024, "http://tuner1:40772", 10),
+            program(3, 2048, "http://tuner2:40772", 10),
+        ];
+        let xml = build_xmltv(
+            programs,
+            &XmltvOptions {
+                include_logos: true,
+            },
+        );
+
+        assert_eq!(xml.matches("<channel id=").count(), 2);
+        assert_eq!(xml.matches("<programme ").count(), 3);
+        // 同じサービスのロゴは辞書順で最初の mirakc のものを使う
+        assert!(xml.contains("<icon src=\"http://tuner1:40772/api/services/1024/logo\" />"));
+        assert!(xml.contains(
+            "<programme start=\"20250101190000 +0900\" stop=\"20250101193000 +0900\" channel=\"32736.1024\">"
+        ));
+        let first = xml.find("番組1<").unwrap();
+        let second = xml.find("番組2<").unwrap();
+        assert!(first < second);
+    }
+
+    #[test]
+    fn test_programme_details() {
+        let mut p = program(1, 1024, "http://tuner:40772", 10);
+        p.name = Some("アニメ <特別編> & 総集編".to_string());
+        p.description = Some("第12話".to_string());
+        p.extended = Some(json!({"出演者": "山田太郎"}));
+        p.genres = vec![
+            "アニメ・特撮／国内アニメ".to_string(),
+            "アニメ・特撮／特撮".to_string(),
+        ];
+        p.video_info = Some("1080i".to_string());
+        p.audio_infos = vec!["デュアルモノ".to_string()];
+        p.series_info = Some(KurecSeriesInfo {
+            id: 1,
+            repeat: 1,
+            pattern: 1,
+            expire_at: None,
+            episode: 12,
+            last_episode: 24,
+            name: "アニメ".to_string(),
+        });
+
+        let xml = build_xmltv(vec![p], &XmltvOptions::default());
+
+        assert!(xml.contains("<title lang=\"ja\">アニメ &lt;特別編&gt; &amp; 総集編</title>"));
+        assert!(xml.contains("<desc lang=\"ja\">第12話\n\n出演者\n山田太郎</desc>"));
+        assert_eq!(
+            xml.matches("<category lang=\"ja\">アニメ・特撮</category>")
+                .count(),
+            1
+        );
+        assert!(xml.contains("<category lang=\"ja\">国内アニメ</category>"));
+        assert!(xml.contains("<category lang=\"en\">Animation</category>"));
+        assert!(xml.contains("<episode-num system=\"xmltv_ns\">.11/24.</episode-num>"));
+        assert!(xml.contains("<episode-num system=\"onscreen\">#12</episode-num>"));
+        assert!(xml.contains("<video><quality>HDTV</quality></video>"));
+        assert!(xml.contains("<audio><stereo>bilingual</stereo></audio>"));
+        assert!(xml.contains("<previously-shown />"));
+        assert!(!xml.contains("<icon"));
+    }
+
+    #[test]
+    fn test_channels_are_ordered_by_type() {
+        let mut bs = program(1, 400101, "http://tuner:40772", 10);
+        bs.channel_type = "BS".to_string();
+        bs.network_id = 4;
+        let gr = program(2, 1024, "http://tuner:40772", 10);
+
+        let xml = build_xmltv(vec![bs, gr], &XmltvOptions::default());
+
+        assert!(xml.find("<channel id=\"32736.1024\">") < xml.find("<channel id=\"4.400101\">"));
+    }
+
+    #[test]
+    fn test_same_service_id_on_different_networks() {
+        let tokyo = program(1, 1024, "http://tuner:40772", 10);
+        let mut osaka = program(2, 1024, "http://tuner:40772", 10);
+        osaka.network_id = 32296;
+        osaka.channel_name = "ＮＨＫ総合・大阪".to_string();
+
+        let xml = build_xmltv(vec![tokyo, osaka], &XmltvOptions::default());
+
+        // サービスIDが同じでもネットワークが違えば別のチャンネルになる
+        assert_eq!(xml.matches("<channel id=").count(), 2);
+        assert!(xml.contains("<channel id=\"32736.1024\">"));
+        assert!(xml.contains("<channel id=\"32296.1024\">"));
+        assert!(xml.contains("<display-name lang=\"ja\">ＮＨＫ総合・大阪</display-name>"));
+        assert!(xml.contains("channel=\"32736.1024\">\n    <title lang=\"ja\">番組1</title>"));
+        assert!(xml.contains("channel=\"32296.1024\">\n    <title lang=\"ja\">番組2</title>"));
+    }
+}
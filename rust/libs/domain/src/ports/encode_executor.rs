@@ -0,0 +1,69 @@
+//! エンコードスクリプト実行のポート
+//!
+//! 録画ファイルや依存するジョブの出力を入力にエンコードジョブのスクリプトを実行し、
+//! 出力ファイルを読み込むインターフェースです。
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::encode::{EncodeExecution, EncodeTask, EncodedFile, ORIGINAL_INPUT_FILE};
+use crate::models::record::ORIGINAL_OUTPUT;
+use crate::ports::storage::ByteStream;
+
+/// スクリプトの入力
+pub struct EncodeInput {
+    /// 作業ディレクトリに書き出すファイル名
+    pub file_name: String,
+    /// 内容
+    pub data: ByteStream,
+}
+
+impl EncodeInput {
+    /// 入力名から入力を作成する。
+    ///
+    /// `original` は `input.ts` に、依存するジョブの出力は出力名のファイルに書き出す。
+    pub fn new(name: &str, data: ByteStream) -> Self {
+        let file_name = if name == ORIGINAL_OUTPUT {
+            ORIGINAL_INPUT_FILE
+        } else {
+            name
+        };
+        Self {
+            file_name: file_name.to_string(),
+            data,
+        }
+    }
+}
+
+/// スクリプトのログ (標準出力と標準エラー出力) の保存先
+#[async_trait]
+pub trait EncodeLogSink: Send + Sync {
+    /// それまでのログ全体を保存する。
+    ///
+    /// 実行中は出力が増えるたびに定期的に、終了時に最後の内容で呼ばれる。
+    async fn save(&self, log: &[u8]) -> Result<()>;
+}
+
+/// エンコードスクリプトの実行
+#[async_trait]
+pub trait EncodeExecutor: Send + Sync + 'static {
+    /// 入力 (`inputs`) をそれぞれ作業ディレクトリに書き出し、スクリプトを実行する。
+    ///
+    /// スクリプトのログは実行中から `log` に保存する。ログを保存できなくても実行は続ける。
+    /// スクリプトが失敗した場合やタイムアウトした場合も `Ok` で結果を返す。
+    /// 入力を書き出せない場合やスクリプトを起動できない場合はエラーを返す。
+    async fn execute(
+        &self,
+        task: &EncodeTask,
+        inputs: Vec<EncodeInput>,
+        log: Arc<dyn EncodeLogSink>,
+    ) -> Result<EncodeExecution>;
+
+    /// 出力ファイルの内容を読み込む。
+    async fn read_output(&self, file: &EncodedFile) -> Result<ByteStream>;
+
+    /// 作業ディレクトリを削除する。
+    async fn cleanup(&self, task: &EncodeTask) -> Result<()>;
+}
@@ -4,6 +4,13 @@ use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value; // 具体的な型ではなく汎用的な Value を使う
 
+use crate::models::onair::NowPlaying;
+use crate::models::record::Record;
+use crate::models::schedule::{DesiredSchedule, MirakcSchedule};
+use crate::models::service::{Channel, Service};
+use crate::models::tuner::Tuner;
+use crate::ports::storage::ByteStream;
+
 /// mirakc API との通信を行うためのトレイト。
 #[async_trait]
 pub trait MirakcApi: Send + Sync {
@@ -37,3 +44,199 @@ pub trait MirakcApi: Send + Sync {
 
     // 必要に応じて他のAPIメソッドを追加 (例: get_version)
 }
+
+/// mirakc の録画レコード API (`/recording/records`) へアクセスするためのトレイト。
+#[async_trait]
+pub trait MirakcRecordsApi: Send + Sync {
+    /// 録画レコードの一覧を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    ///
+    /// # Returns
+    ///
+    /// 録画レコードのリスト。エラー時は `Err`。
+    async fn get_records(&self, mirakc_url: &str) -> Result<Vec<Record>>;
+
+    /// 指定された録画レコードを取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    /// * `record_id` - mirakc の Record ID
+    ///
+    /// # Returns
+    ///
+    /// 録画レコード。存在しない場合は `Ok(None)`、エラー時は `Err`。
+    async fn get_record(&self, mirakc_url: &str, record_id: &str) -> Result<Option<Record>>;
+
+    /// 録画ファイルの内容をストリームで取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    /// * `record_id` - mirakc の Record ID
+    /// * `offset` - 取得を始める位置 (バイト)。0 以外の場合は Range リクエストで続きから取得する
+    ///
+    /// # Returns
+    ///
+    /// 録画ファイルの内容のストリーム。録画ファイルがない場合やエラー時は `Err`。
+    /// 続きから取得する場合に、返ってきた内容が `offset` から始まっていなければ `Err`。
+    async fn get_record_stream(
+        &self,
+        mirakc_url: &str,
+        record_id: &str,
+        offset: u64,
+    ) -> Result<RecordStream>;
+
+    /// 録画レコードを録画ファイルと合わせて削除する。存在しない場合は何もしない。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    /// * `record_id` - mirakc の Record ID
+    async fn remove_record(&self, mirakc_url: &str, record_id: &str) -> Result<()>;
+}
+
+/// 録画ファイルの内容のストリーム
+pub type RecordStream = ByteStream;
+
+/// mirakc のチューナー API (`/tuners`) へアクセスするためのトレイト。
+#[async_trait]
+pub trait MirakcTunersApi: Send + Sync {
+    /// チューナーの一覧を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    ///
+    /// # Returns
+    ///
+    /// チューナー情報のリスト。エラー時は `Err`。
+    async fn get_tuners(&self, mirakc_url: &str) -> Result<Vec<Tuner>>;
+
+    /// 指定されたインデックスのチューナー情報を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    /// * `index` - チューナーのインデックス
+    ///
+    /// # Returns
+    ///
+    /// チューナー情報。見つからない場合やエラー時は `Err`。
+    async fn get_tuner(&self, mirakc_url: &str, index: i64) -> Result<Tuner>;
+}
+
+/// mirakc の放送中番組 API (`/onair`) へアクセスするためのトレイト。
+///
+/// 戻り値の `NowPlaying::updated_at` には取得した時刻が設定される。
+#[async_trait]
+pub trait MirakcOnairApi: Send + Sync {
+    /// すべてのサービスの放送中番組を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    ///
+    /// # Returns
+    ///
+    /// サービスごとの放送中番組のリスト。エラー時は `Err`。
+    async fn get_onair_programs(&self, mirakc_url: &str) -> Result<Vec<NowPlaying>>;
+
+    /// 指定されたサービスの放送中番組を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    /// * `service_id` - Mirakurun Service ID
+    ///
+    /// # Returns
+    ///
+    /// 放送中番組。サービスが存在しない場合は `Ok(None)`、エラー時は `Err`。
+    async fn get_onair_program(
+        &self,
+        mirakc_url: &str,
+        service_id: i64,
+    ) -> Result<Option<NowPlaying>>;
+}
+
+/// mirakc のサービス・チャンネル API (`/services`, `/channels`) へアクセスするためのトレイト。
+#[async_trait]
+pub trait MirakcServicesApi: Send + Sync {
+    /// サービスの一覧を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    ///
+    /// # Returns
+    ///
+    /// サービスのリスト。エラー時は `Err`。
+    async fn get_services(&self, mirakc_url: &str) -> Result<Vec<Service>>;
+
+    /// チャンネルの一覧を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    ///
+    /// # Returns
+    ///
+    /// チャンネルのリスト。エラー時は `Err`。
+    async fn get_channels(&self, mirakc_url: &str) -> Result<Vec<Channel>>;
+
+    /// サービスのロゴ画像を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    /// * `id` - Mirakurun Service ID
+    ///
+    /// # Returns
+    ///
+    /// ロゴ画像 (PNG)。ロゴが存在しない場合は `Ok(None)`、エラー時は `Err`。
+    async fn get_logo_image(&self, mirakc_url: &str, id: i64) -> Result<Option<Vec<u8>>>;
+}
+
+/// mirakc の録画予約 API (`/recording/schedules`) へアクセスするためのトレイト。
+#[async_trait]
+pub trait MirakcSchedulesApi: Send + Sync {
+    /// 録画予約の一覧を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    ///
+    /// # Returns
+    ///
+    /// 録画予約のリスト。エラー時は `Err`。
+    async fn get_schedules(&self, mirakc_url: &str) -> Result<Vec<MirakcSchedule>>;
+
+    /// 録画されるべき番組の録画予約を作成する。
+    ///
+    /// 録画予約には `DesiredSchedule::tags` のタグと `DesiredSchedule::priority` の優先度を設定する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    /// * `schedule` - 録画されるべき番組
+    ///
+    /// # Returns
+    ///
+    /// 作成された録画予約。エラー時は `Err`。
+    async fn create_schedule(
+        &self,
+        mirakc_url: &str,
+        schedule: &DesiredSchedule,
+    ) -> Result<MirakcSchedule>;
+
+    /// 録画予約を削除する。存在しない場合は何もしない。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 接続先の mirakc のベースURL
+    /// * `program_id` - Mirakurun Program ID
+    async fn delete_schedule(&self, mirakc_url: &str, program_id: i64) -> Result<()>;
+}
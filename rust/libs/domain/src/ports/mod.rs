@@ -2,14 +2,24 @@
 //!
 //! このモジュールはドメイン層とインフラ層の間のインターフェースを定義します。
 
+pub mod encode_executor;
 pub mod event_sink; // 追加
 pub mod event_source; // 追加
 pub mod mirakc_api; // 追加
 pub mod notifiers;
+pub mod ogp_fetcher;
+pub mod program_search_index;
+pub mod query_bus;
 pub mod repositories;
+pub mod storage;
 
+pub use encode_executor::*;
 pub use event_sink::*; // 追加
 pub use event_source::*; // 追加
 pub use mirakc_api::*; // 追加
 pub use notifiers::*;
+pub use ogp_fetcher::*;
+pub use program_search_index::*;
+pub use query_bus::*;
 pub use repositories::*;
+pub use storage::*;
@@ -0,0 +1,18 @@
+//! OGP 画像取得のポート
+//!
+//! ページの og:image を取得して WebP に縮小するインターフェースです。
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::ogp::OgpImage;
+
+/// OGP 画像の取得
+#[async_trait]
+pub trait OgpFetcher: Send + Sync + 'static {
+    /// ページの og:image を取得して縮小する。
+    ///
+    /// ページに og:image がない場合は `Ok(None)`。
+    /// タイムアウトやサイズの上限を超えた場合、画像を変換できない場合はエラーを返す。
+    async fn fetch(&self, url: &str) -> Result<Option<OgpImage>>;
+}
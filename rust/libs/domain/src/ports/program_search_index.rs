@@ -0,0 +1,30 @@
+//! 番組検索インデックスのポート
+//!
+//! Web UI の番組検索で使う全文検索インデックス (Meilisearch など) へのインターフェースです。
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::search::{ProgramDocument, ProgramSearchQuery, ProgramSearchResult};
+
+/// 番組検索インデックス
+#[async_trait]
+pub trait ProgramSearchIndex: Send + Sync + 'static {
+    /// インデックスがなければ作成し、検索対象・絞り込み対象などの設定を反映する。
+    async fn ensure_index(&self) -> Result<()>;
+
+    /// サービスの番組のドキュメントをすべて取得する。
+    async fn get_service_documents(&self, service_id: i64) -> Result<Vec<ProgramDocument>>;
+
+    /// ドキュメントを追加・更新する (番組IDが同じドキュメントは置き換える)。
+    async fn upsert_documents(&self, documents: &[ProgramDocument]) -> Result<()>;
+
+    /// 番組IDを指定してドキュメントを削除する。
+    async fn delete_documents(&self, program_ids: &[i64]) -> Result<()>;
+
+    /// すべてのドキュメントを削除する。
+    async fn delete_all_documents(&self) -> Result<()>;
+
+    /// 番組を検索する。
+    async fn search(&self, query: &ProgramSearchQuery) -> Result<ProgramSearchResult>;
+}
@@ -0,0 +1,30 @@
+//! 問い合わせ (リクエスト・リプライ) のポート
+//!
+//! Web UI などからの問い合わせと、その応答を定義するためのインターフェースです。
+//! イベントと異なり、問い合わせは記録されず、応答を待つ呼び出し元に直接返されます。
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// 問い合わせ
+pub trait QueryRequest: Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// 問い合わせを受け付けるサブジェクト
+    const SUBJECT: &'static str;
+    /// 応答の型
+    type Response: Serialize + DeserializeOwned + Send + Sync + 'static;
+}
+
+/// 問い合わせに応答するトレイト
+#[async_trait]
+pub trait QueryHandler<Q: QueryRequest>: Send + Sync + 'static {
+    /// 問い合わせに応答する
+    async fn handle(&self, query: Q) -> Result<Q::Response>;
+}
+
+/// 問い合わせを送るトレイト
+#[async_trait]
+pub trait QueryClient<Q: QueryRequest>: Send + Sync + 'static {
+    /// 問い合わせを送り、応答を待つ
+    async fn request(&self, query: Q) -> Result<Q::Response>;
+}
@@ -0,0 +1,32 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::schedule::DesiredSchedule;
+
+/// 録画予約の望ましい状態 (`DesiredSchedule`) を永続化するためのリポジトリトレイト。
+///
+/// ルールエンジンや手動予約はこのリポジトリに録画されるべき番組を書き込み、
+/// mirakc への反映はこのリポジトリの内容をもとに行う。
+#[async_trait]
+pub trait DesiredScheduleRepository: Send + Sync {
+    /// 録画予約を保存する。同じ番組の録画予約は上書きされる。
+    async fn save_schedule(&self, schedule: &DesiredSchedule) -> Result<()>;
+
+    /// 指定された番組の録画予約を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 録画に使用する mirakc のベースURL
+    /// * `program_id` - Mirakurun Program ID
+    async fn get_schedule(
+        &self,
+        mirakc_url: &str,
+        program_id: i64,
+    ) -> Result<Option<DesiredSchedule>>;
+
+    /// 保存されているすべての録画予約を取得する。
+    async fn list_schedules(&self) -> Result<Vec<DesiredSchedule>>;
+
+    /// 録画予約を削除する。存在しない場合は何もしない。
+    async fn delete_schedule(&self, mirakc_url: &str, program_id: i64) -> Result<()>;
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// ジョブのログ (スクリプトの標準出力と標準エラー出力) を保存するためのリポジトリトレイト。
+#[async_trait]
+pub trait JobLogRepository: Send + Sync {
+    /// ジョブのログを保存する。既存のログは上書きされる。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 録画を行った mirakc のベースURL
+    /// * `record_id` - mirakc の Record ID
+    /// * `job` - ジョブ名
+    /// * `log` - ログの内容
+    ///
+    /// # Returns
+    ///
+    /// 保存したログの名前。保存に失敗した場合は `Err`。
+    async fn save_log(
+        &self,
+        mirakc_url: &str,
+        record_id: &str,
+        job: &str,
+        log: Vec<u8>,
+    ) -> Result<String>;
+
+    /// ジョブのログを取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 録画を行った mirakc のベースURL
+    /// * `record_id` - mirakc の Record ID
+    /// * `job` - ジョブ名
+    ///
+    /// # Returns
+    ///
+    /// ログが存在する場合は `Ok(Some(Vec<u8>))`、存在しない場合は `Ok(None)`、
+    /// 取得に失敗した場合は `Err`。
+    async fn get_log(
+        &self,
+        mirakc_url: &str,
+        record_id: &str,
+        job: &str,
+    ) -> Result<Option<Vec<u8>>>;
+}
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::job::JobRun;
+
+/// 録画ごとのジョブの実行状況 (`JobRun`) を永続化するためのリポジトリトレイト。
+#[async_trait]
+pub trait JobRunRepository: Send + Sync {
+    /// ジョブの実行状況を保存する。既存のデータは上書きされる。
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - 保存するジョブの実行状況
+    ///
+    /// # Returns
+    ///
+    /// 保存に成功した場合は `Ok(())`、失敗した場合は `Err`。
+    async fn save_run(&self, run: &JobRun) -> Result<()>;
+
+    /// ジョブの実行状況を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 録画を行った mirakc のベースURL
+    /// * `record_id` - mirakc の Record ID
+    ///
+    /// # Returns
+    ///
+    /// データが存在する場合は `Ok(Some(JobRun))`、存在しない場合は `Ok(None)`、
+    /// 取得に失敗した場合は `Err`。
+    async fn get_run(&self, mirakc_url: &str, record_id: &str) -> Result<Option<JobRun>>;
+
+    /// 保存されているすべてのジョブの実行状況を取得する。
+    ///
+    /// # Returns
+    ///
+    /// ジョブの実行状況のリスト。取得に失敗した場合は `Err`。
+    async fn list_runs(&self) -> Result<Vec<JobRun>>;
+}
@@ -43,5 +43,10 @@ pub trait KurecProgramRepository: Send + Sync {
         service_id: i64, // i32 -> i64
     ) -> Result<Option<Vec<KurecProgram>>>;
 
+    /// 保存されているすべてのmirakc・サービスの番組情報を取得する。
+    ///
+    /// 複数のmirakcから同じサービスの番組を取得している場合、重複はそのまま含まれる。
+    async fn list_all_programs(&self) -> Result<Vec<KurecProgram>>;
+
     // 必要に応じて他のメソッドを追加 (例: delete_service_programs)
 }
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/// リーダー選出のためのリース (`Lease`) を管理するリポジトリトレイト。
+///
+/// 取得と更新は、他のインスタンスと同時に実行しても1つのインスタンスだけが成功するように
+/// アトミックに行わなければならない。
+#[async_trait]
+pub trait LeaseRepository: Send + Sync {
+    /// リースを取得 (または更新) する。
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - リース名
+    /// * `holder` - インスタンスの識別子
+    /// * `expires_at` - 取得した場合の有効期限
+    /// * `now` - 現在時刻。他のインスタンスのリースの有効期限と比較する
+    ///
+    /// # Returns
+    ///
+    /// リースを保持している場合は `Ok(true)`、他のインスタンスが保持している場合は `Ok(false)`。
+    async fn try_acquire(
+        &self,
+        name: &str,
+        holder: &str,
+        expires_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<bool>;
+
+    /// 保持しているリースを解放する。他のインスタンスが保持している場合は何もしない。
+    async fn release(&self, name: &str, holder: &str) -> Result<()>;
+}
@@ -2,10 +2,34 @@
 //!
 //! このモジュールはデータアクセスのためのリポジトリインターフェースを定義します。
 
+pub mod desired_schedule_repository;
+pub mod job_log_repository;
+pub mod job_run_repository;
 pub mod kurec_program_repository;
+pub mod lease_repository;
 pub mod mirakc_event_repository;
+pub mod now_playing_repository;
+pub mod ogp_image_repository;
+pub mod record_deletion_repository;
+pub mod record_repository;
+pub mod recording_rule_repository;
+pub mod series_subscription_repository;
+pub mod service_repository;
+pub mod tuner_repository;
 pub mod version_repository;
 
+pub use desired_schedule_repository::*;
+pub use job_log_repository::*;
+pub use job_run_repository::*;
 pub use kurec_program_repository::*;
+pub use lease_repository::*;
 pub use mirakc_event_repository::*;
+pub use now_playing_repository::*;
+pub use ogp_image_repository::*;
+pub use record_deletion_repository::*;
+pub use record_repository::*;
+pub use recording_rule_repository::*;
+pub use series_subscription_repository::*;
+pub use service_repository::*;
+pub use tuner_repository::*;
 pub use version_repository::*;
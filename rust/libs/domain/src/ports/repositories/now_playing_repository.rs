@@ -0,0 +1,34 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::onair::NowPlaying;
+
+/// サービスごとの放送中番組 (`NowPlaying`) を永続化するためのリポジトリトレイト。
+#[async_trait]
+pub trait NowPlayingRepository: Send + Sync {
+    /// 放送中番組を保存する。既存のデータは上書きされる。
+    ///
+    /// # Arguments
+    ///
+    /// * `now_playing` - 保存する放送中番組
+    async fn save_now_playing(&self, now_playing: &NowPlaying) -> Result<()>;
+
+    /// 指定されたサービスの放送中番組を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 番組情報を取得した mirakc のベースURL
+    /// * `service_id` - Mirakurun Service ID
+    ///
+    /// # Returns
+    ///
+    /// データが存在する場合は `Ok(Some(NowPlaying))`、存在しない場合は `Ok(None)`。
+    async fn get_now_playing(
+        &self,
+        mirakc_url: &str,
+        service_id: i64,
+    ) -> Result<Option<NowPlaying>>;
+
+    /// 保存されているすべてのサービスの放送中番組を取得する。
+    async fn list_now_playing(&self) -> Result<Vec<NowPlaying>>;
+}
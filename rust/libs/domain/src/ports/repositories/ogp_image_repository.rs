@@ -0,0 +1,30 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::ogp::OgpEntry;
+
+/// URL のハッシュごとの OGP 画像 (`OgpEntry` と WebP 画像) を永続化するためのリポジトリトレイト。
+#[async_trait]
+pub trait OgpImageRepository: Send + Sync {
+    /// キャッシュエントリを取得する。
+    ///
+    /// # Returns
+    ///
+    /// データが存在する場合は `Ok(Some(OgpEntry))`、存在しない場合は `Ok(None)`。
+    async fn get_entry(&self, url_hash: &str) -> Result<Option<OgpEntry>>;
+
+    /// キャッシュエントリと画像を保存する。既存のデータは上書きされる。
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - 保存するキャッシュエントリ
+    /// * `webp` - 縮小した画像 (WebP)。画像がない場合は空
+    async fn save(&self, entry: &OgpEntry, webp: Vec<u8>) -> Result<()>;
+
+    /// 画像を取得する。
+    ///
+    /// # Returns
+    ///
+    /// 画像が存在する場合は `Ok(Some(Vec<u8>))`、存在しない場合や画像がなかった場合は `Ok(None)`。
+    async fn get_image(&self, url_hash: &str) -> Result<Option<Vec<u8>>>;
+}
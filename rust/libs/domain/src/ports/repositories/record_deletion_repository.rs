@@ -0,0 +1,43 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::deletion::RecordDeletion;
+
+/// mirakc の録画の削除の監査記録 (`RecordDeletion`) を永続化するためのリポジトリトレイト。
+#[async_trait]
+pub trait RecordDeletionRepository: Send + Sync {
+    /// 削除の記録を保存する。同じ録画の既存のデータは上書きされる。
+    ///
+    /// # Arguments
+    ///
+    /// * `deletion` - 保存する削除の記録
+    ///
+    /// # Returns
+    ///
+    /// 保存に成功した場合は `Ok(())`、失敗した場合は `Err`。
+    async fn save_deletion(&self, deletion: &RecordDeletion) -> Result<()>;
+
+    /// 削除の記録を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 録画を行った mirakc のベースURL
+    /// * `record_id` - mirakc の Record ID
+    ///
+    /// # Returns
+    ///
+    /// データが存在する場合は `Ok(Some(RecordDeletion))`、存在しない場合は `Ok(None)`、
+    /// 取得に失敗した場合は `Err`。
+    async fn get_deletion(
+        &self,
+        mirakc_url: &str,
+        record_id: &str,
+    ) -> Result<Option<RecordDeletion>>;
+
+    /// 保存されているすべての削除の記録を取得する。
+    ///
+    /// # Returns
+    ///
+    /// 削除の記録のリスト。取得に失敗した場合は `Err`。
+    async fn list_deletions(&self) -> Result<Vec<RecordDeletion>>;
+}
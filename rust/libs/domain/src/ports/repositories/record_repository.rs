@@ -0,0 +1,52 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::record::Record;
+
+/// 録画ライブラリ (`Record`) を永続化するためのリポジトリトレイト。
+/// mirakc の録画レコードを KVS に投影したものを想定。
+#[async_trait]
+pub trait RecordRepository: Send + Sync {
+    /// 録画レコードを保存する。既存のデータは上書きされる。
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - 保存する録画レコード
+    ///
+    /// # Returns
+    ///
+    /// 保存に成功した場合は `Ok(())`、失敗した場合は `Err`。
+    async fn save_record(&self, record: &Record) -> Result<()>;
+
+    /// 録画レコードを取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 録画を行った mirakc のベースURL
+    /// * `record_id` - mirakc の Record ID
+    ///
+    /// # Returns
+    ///
+    /// データが存在する場合は `Ok(Some(Record))`、存在しない場合は `Ok(None)`、
+    /// 取得に失敗した場合は `Err`。
+    async fn get_record(&self, mirakc_url: &str, record_id: &str) -> Result<Option<Record>>;
+
+    /// 保存されているすべての録画レコードを取得する。
+    ///
+    /// # Returns
+    ///
+    /// 録画レコードのリスト。取得に失敗した場合は `Err`。
+    async fn list_records(&self) -> Result<Vec<Record>>;
+
+    /// 録画レコードを削除する。存在しない場合も成功とする。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - 録画を行った mirakc のベースURL
+    /// * `record_id` - mirakc の Record ID
+    ///
+    /// # Returns
+    ///
+    /// 削除に成功した場合は `Ok(())`、失敗した場合は `Err`。
+    async fn remove_record(&self, mirakc_url: &str, record_id: &str) -> Result<()>;
+}
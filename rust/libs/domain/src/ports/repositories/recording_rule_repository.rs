@@ -0,0 +1,24 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::rule::RecordingRule;
+
+/// 自動録画ルール (`RecordingRule`) を永続化するためのリポジトリトレイト。
+#[async_trait]
+pub trait RecordingRuleRepository: Send + Sync {
+    /// ルールを保存する。同じIDのルールは上書きされる。
+    async fn save_rule(&self, rule: &RecordingRule) -> Result<()>;
+
+    /// 指定されたIDのルールを取得する。
+    ///
+    /// # Returns
+    ///
+    /// データが存在する場合は `Ok(Some(RecordingRule))`、存在しない場合は `Ok(None)`。
+    async fn get_rule(&self, rule_id: &str) -> Result<Option<RecordingRule>>;
+
+    /// 保存されているすべてのルールを取得する。
+    async fn list_rules(&self) -> Result<Vec<RecordingRule>>;
+
+    /// ルールを削除する。存在しない場合は何もしない。
+    async fn delete_rule(&self, rule_id: &str) -> Result<()>;
+}
@@ -0,0 +1,24 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::series::SeriesSubscription;
+
+/// シリーズ録画の購読 (`SeriesSubscription`) を永続化するためのリポジトリトレイト。
+#[async_trait]
+pub trait SeriesSubscriptionRepository: Send + Sync {
+    /// 購読を保存する。同じIDの購読は上書きされる。
+    async fn save_subscription(&self, subscription: &SeriesSubscription) -> Result<()>;
+
+    /// 指定されたIDの購読を取得する。
+    ///
+    /// # Returns
+    ///
+    /// データが存在する場合は `Ok(Some(SeriesSubscription))`、存在しない場合は `Ok(None)`。
+    async fn get_subscription(&self, id: &str) -> Result<Option<SeriesSubscription>>;
+
+    /// 保存されているすべての購読を取得する。
+    async fn list_subscriptions(&self) -> Result<Vec<SeriesSubscription>>;
+
+    /// 購読を削除する。存在しない場合は何もしない。
+    async fn delete_subscription(&self, id: &str) -> Result<()>;
+}
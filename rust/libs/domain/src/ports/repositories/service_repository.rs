@@ -0,0 +1,64 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::service::{Channel, Service};
+
+/// mirakc ごとのサービスカタログ (`Service`, `Channel`) を永続化するためのリポジトリトレイト。
+#[async_trait]
+pub trait ServiceRepository: Send + Sync {
+    /// 指定された mirakc のサービス一覧を保存する。既存のデータは上書きされる。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - サービスを取得した mirakc のベースURL
+    /// * `services` - 保存するサービスのリスト
+    async fn save_services(&self, mirakc_url: &str, services: &[Service]) -> Result<()>;
+
+    /// 指定された mirakc のサービス一覧を取得する。
+    ///
+    /// # Returns
+    ///
+    /// 保存されたサービスのリスト。まだ保存されていない場合は空のリスト。
+    async fn list_services(&self, mirakc_url: &str) -> Result<Vec<Service>>;
+
+    /// 保存されているすべての mirakc のサービスを取得する。
+    async fn list_all_services(&self) -> Result<Vec<Service>>;
+
+    /// 指定された mirakc のチャンネル一覧を保存する。既存のデータは上書きされる。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - チャンネルを取得した mirakc のベースURL
+    /// * `channels` - 保存するチャンネルのリスト
+    async fn save_channels(&self, mirakc_url: &str, channels: &[Channel]) -> Result<()>;
+
+    /// 指定された mirakc のチャンネル一覧を取得する。
+    ///
+    /// # Returns
+    ///
+    /// 保存されたチャンネルのリスト。まだ保存されていない場合は空のリスト。
+    async fn list_channels(&self, mirakc_url: &str) -> Result<Vec<Channel>>;
+}
+
+/// サービスのロゴ画像を保存するためのリポジトリトレイト。
+#[async_trait]
+pub trait ServiceLogoRepository: Send + Sync {
+    /// ロゴ画像を保存する。既存のデータは上書きされる。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - ロゴを取得した mirakc のベースURL
+    /// * `id` - Mirakurun Service ID
+    /// * `image` - ロゴ画像 (PNG)
+    async fn save_logo(&self, mirakc_url: &str, id: i64, image: Vec<u8>) -> Result<()>;
+
+    /// ロゴ画像を取得する。
+    ///
+    /// # Returns
+    ///
+    /// データが存在する場合は `Ok(Some(Vec<u8>))`、存在しない場合は `Ok(None)`。
+    async fn get_logo(&self, mirakc_url: &str, id: i64) -> Result<Option<Vec<u8>>>;
+
+    /// ロゴ画像を削除する。存在しない場合は何もしない。
+    async fn delete_logo(&self, mirakc_url: &str, id: i64) -> Result<()>;
+}
@@ -0,0 +1,58 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::models::tuner::{TunerStatus, TunerUsageInterval};
+
+/// チューナーの現在の状態 (`TunerStatus`) を永続化するためのリポジトリトレイト。
+#[async_trait]
+pub trait TunerStatusRepository: Send + Sync {
+    /// チューナーの状態を保存する。既存のデータは上書きされる。
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - 保存するチューナーの状態
+    async fn save_status(&self, status: &TunerStatus) -> Result<()>;
+
+    /// チューナーの状態を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `mirakc_url` - チューナーを管理する mirakc のベースURL
+    /// * `index` - チューナーのインデックス
+    ///
+    /// # Returns
+    ///
+    /// データが存在する場合は `Ok(Some(TunerStatus))`、存在しない場合は `Ok(None)`。
+    async fn get_status(&self, mirakc_url: &str, index: i64) -> Result<Option<TunerStatus>>;
+
+    /// 保存されているすべてのチューナーの状態を取得する。
+    async fn list_statuses(&self) -> Result<Vec<TunerStatus>>;
+}
+
+/// チューナーの使用履歴 (`TunerUsageInterval`) を保存するためのリポジトリトレイト。
+#[async_trait]
+pub trait TunerUsageHistoryRepository: Send + Sync {
+    /// 終了した使用区間を追記する。
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - 追記する使用区間
+    async fn append_interval(&self, interval: &TunerUsageInterval) -> Result<()>;
+
+    /// 期間 `[from, to)` と重なる使用区間を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - 期間の開始時刻
+    /// * `to` - 期間の終了時刻
+    ///
+    /// # Returns
+    ///
+    /// 使用区間のリスト (開始時刻順)。
+    async fn list_intervals(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<TunerUsageInterval>>;
+}
@@ -0,0 +1,103 @@
+//! ストレージのポート
+//!
+//! 録画ファイルやエンコードした出力を保存する、名前付きのストレージのインターフェースです。
+//! 位置 (`location`) は `/` 区切りの相対パスで、ストレージのルートからの位置を表します。
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+
+/// ストレージに保存する、またはストレージから読み込む内容のストリーム
+pub type ByteStream = BoxStream<'static, Result<Bytes>>;
+
+/// ストレージに保存されたオブジェクトの情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredObject {
+    /// ストレージ内の位置
+    pub location: String,
+    /// サイズ (バイト)
+    pub size: u64,
+    /// MIME タイプ (ストレージが保持していない場合は `None`)
+    pub content_type: Option<String>,
+    /// 更新日時 (ストレージが保持していない場合は `None`)
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+/// 名前付きのストレージ
+#[async_trait]
+pub trait Storage: Send + Sync + 'static {
+    /// ストレージ名
+    fn name(&self) -> &str;
+
+    /// 内容を保存する。既存のオブジェクトは上書きされる。
+    ///
+    /// 途中で失敗した場合、書きかけのオブジェクトは残さない。
+    async fn put(
+        &self,
+        location: &str,
+        content_type: &str,
+        data: ByteStream,
+    ) -> Result<StoredObject>;
+
+    /// 内容を読み込む。存在しない場合は `Ok(None)`。
+    async fn get(&self, location: &str) -> Result<Option<ByteStream>>;
+
+    /// オブジェクトを削除する。存在しない場合も `Ok(())`。
+    async fn delete(&self, location: &str) -> Result<()>;
+
+    /// 位置が `prefix` で始まるオブジェクトの一覧を、位置の順に返す。
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>>;
+
+    /// オブジェクトの情報を取得する。存在しない場合は `Ok(None)`。
+    async fn stat(&self, location: &str) -> Result<Option<StoredObject>>;
+}
+
+/// 設定されたストレージを名前で引く
+#[derive(Clone, Default)]
+pub struct StorageRegistry {
+    storages: BTreeMap<String, Arc<dyn Storage>>,
+}
+
+impl StorageRegistry {
+    /// ストレージの一覧から作成する。名前が重複している場合はエラー。
+    pub fn new(storages: Vec<Arc<dyn Storage>>) -> Result<Self> {
+        let mut registry = BTreeMap::new();
+        for storage in storages {
+            let name = storage.name().to_string();
+            if registry.insert(name.clone(), storage).is_some() {
+                bail!("ストレージ名が重複しています: {}", name);
+            }
+        }
+        Ok(Self { storages: registry })
+    }
+
+    /// 名前でストレージを取得する。
+    pub fn get(&self, name: &str) -> Result<Arc<dyn Storage>> {
+        self.storages
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("ストレージ {} が設定されていません", name))
+    }
+
+    /// 設定されているストレージ名
+    pub fn names(&self) -> Vec<&str> {
+        self.storages.keys().map(String::as_str).collect()
+    }
+}
+
+/// 位置が `/` 区切りの相対パスで、`.` や `..` を含まないことを検証する。
+pub fn validate_location(location: &str) -> Result<()> {
+    if location.is_empty()
+        || location
+            .split('/')
+            .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+    {
+        bail!("ストレージ内の位置が不正です: {:?}", location);
+    }
+    Ok(())
+}
@@ -0,0 +1,255 @@
+//! クエリの構文木
+
+use std::fmt;
+
+use chrono::Weekday;
+
+use crate::models::rule::TimeWindow;
+use crate::query::normalize;
+
+/// クエリ文字列中の位置 (バイトオフセットの範囲)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// `self` の先頭から `other` の末尾までの範囲
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start, other.end)
+    }
+}
+
+/// 式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    /// すべての式を満たす
+    And(Vec<Expr>),
+    /// いずれかの式を満たす
+    Or(Vec<Expr>),
+    /// 式を満たさない
+    Not(Box<Expr>),
+    /// 条件
+    Predicate { predicate: Predicate, span: Span },
+}
+
+/// 番組に対する条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Predicate {
+    /// 番組名
+    Title(TextPattern),
+    /// 番組説明と詳細情報
+    Description(TextPattern),
+    /// ジャンル
+    Genre(TextPattern),
+    /// 出演者の名前 (詳細情報から取り出したもの)
+    Cast(TextPattern),
+    /// スタッフの名前 (詳細情報から取り出したもの)
+    Staff(TextPattern),
+    /// チャンネル名 (サービス名) または Service ID
+    Channel(TextPattern),
+    /// チャンネルタイプ (GR, BS, CS など)
+    ChannelType(String),
+    /// 放送開始の曜日 (日本時間)
+    Weekday(Vec<Weekday>),
+    /// 放送開始の時間帯 (日本時間)
+    Time(TimeWindow),
+    /// 番組の長さ
+    Duration(DurationRange),
+    /// 無料放送かどうか
+    Free(bool),
+}
+
+/// 文字列のパターン
+///
+/// `*` を含まない場合は部分一致、含む場合は `*` を任意の文字列としたワイルドカードで全体に一致させる。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextPattern {
+    raw: String,
+    parts: Vec<String>,
+}
+
+impl TextPattern {
+    pub fn new(raw: &str) -> Self {
+        Self {
+            raw: raw.to_string(),
+            parts: normalize(raw).split('*').map(str::to_string).collect(),
+        }
+    }
+
+    /// 入力されたままのパターン
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// 文字列がパターンに一致するかどうか
+    pub fn matches(&self, text: &str) -> bool {
+        let text = normalize(text);
+        let (first, rest) = self
+            .parts
+            .split_first()
+            .expect("split は1つ以上の要素を返す");
+        let Some((last, middle)) = rest.split_last() else {
+            return text.contains(first.as_str());
+        };
+
+        let Some(mut remaining) = text.strip_prefix(first.as_str()) else {
+            return false;
+        };
+        for part in middle {
+            match remaining.find(part.as_str()) {
+                Some(i) => remaining = &remaining[i + part.len()..],
+                None => return false,
+            }
+        }
+        remaining.ends_with(last.as_str())
+    }
+}
+
+/// 番組の長さの範囲 (分、両端を含む)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurationRange {
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+}
+
+impl DurationRange {
+    pub fn contains(&self, minutes: i64) -> bool {
+        self.min.is_none_or(|min| minutes >= min) && self.max.is_none_or(|max| minutes <= max)
+    }
+}
+
+fn write_text(f: &mut fmt::Formatter<'_>, text: &str) -> fmt::Result {
+    let needs_quote = text.is_empty()
+        || text.starts_with('-')
+        || text
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '(' | ')' | '"' | ':'))
+        || matches!(text, "OR" | "AND" | "NOT");
+    if needs_quote {
+        write!(f, "\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        f.write_str(text)
+    }
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "mon",
+        Weekday::Tue => "tue",
+        Weekday::Wed => "wed",
+        Weekday::Thu => "thu",
+        Weekday::Fri => "fri",
+        Weekday::Sat => "sat",
+        Weekday::Sun => "sun",
+    }
+}
+
+/// 正規化した形式でクエリ文字列に戻す。
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Title(p) => {
+                f.write_str("title:")?;
+                write_text(f, p.raw())
+            }
+            Self::Description(p) => {
+                f.write_str("desc:")?;
+                write_text(f, p.raw())
+            }
+            Self::Genre(p) => {
+                f.write_str("genre:")?;
+                write_text(f, p.raw())
+            }
+            Self::Cast(p) => {
+                f.write_str("cast:")?;
+                write_text(f, p.raw())
+            }
+            Self::Staff(p) => {
+                f.write_str("staff:")?;
+                write_text(f, p.raw())
+            }
+            Self::Channel(p) => {
+                f.write_str("ch:")?;
+                write_text(f, p.raw())
+            }
+            Self::ChannelType(t) => write!(f, "type:{}", t),
+            Self::Weekday(days) => {
+                let days: Vec<_> = days.iter().map(|d| weekday_name(*d)).collect();
+                write!(f, "weekday:{}", days.join(","))
+            }
+            Self::Time(window) => write!(f, "time:{}", window),
+            Self::Duration(range) => match (range.min, range.max) {
+                (Some(min), Some(max)) if min == max => write!(f, "duration:{}", min),
+                (Some(min), Some(max)) => write!(f, "duration:{}..{}", min, max),
+                (Some(min), None) => write!(f, "duration:>={}", min),
+                (None, Some(max)) => write!(f, "duration:<={}", max),
+                (None, None) => write!(f, "duration:>=0"),
+            },
+            Self::Free(free) => write!(f, "free:{}", if *free { "yes" } else { "no" }),
+        }
+    }
+}
+
+/// 正規化した形式でクエリ文字列に戻す。
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::And(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    match item {
+                        Self::Or(_) => write!(f, "({})", item)?,
+                        _ => write!(f, "{}", item)?,
+                    }
+                }
+                Ok(())
+            }
+            Self::Or(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" OR ")?;
+                    }
+                    match item {
+                        Self::Or(_) => write!(f, "({})", item)?,
+                        _ => write!(f, "{}", item)?,
+                    }
+                }
+                Ok(())
+            }
+            Self::Not(inner) => match inner.as_ref() {
+                Self::Predicate { .. } | Self::Not(_) => write!(f, "-{}", inner),
+                _ => write!(f, "-({})", inner),
+            },
+            Self::Predicate { predicate, .. } => write!(f, "{}", predicate),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_pattern() {
+        let contains = TextPattern::new("ガンダム");
+        assert!(contains.matches("機動戦士ガンダム"));
+        assert!(!contains.matches("ガンタンク"));
+
+        let prefix = TextPattern::new("NHK*");
+        assert!(prefix.matches("ＮＨＫ総合１・東京"));
+        assert!(!prefix.matches("ＢＳ１ ＮＨＫ"));
+
+        let glob = TextPattern::new("*ガンダム*SEED");
+        assert!(glob.matches("機動戦士ガンダムSEED"));
+        assert!(!glob.matches("機動戦士ガンダムSEED DESTINY"));
+        assert!(TextPattern::new("*").matches(""));
+    }
+}
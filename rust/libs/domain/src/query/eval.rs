@@ -0,0 +1,128 @@
+//! 番組に対するクエリの評価
+
+use chrono::{Datelike, Timelike};
+
+use crate::models::epg::KurecProgram;
+use crate::models::rule::jst;
+use crate::query::ast::{Expr, Predicate};
+
+impl Expr {
+    /// 番組が式を満たすかどうか
+    pub fn matches(&self, program: &KurecProgram) -> bool {
+        match self {
+            Self::And(items) => items.iter().all(|e| e.matches(program)),
+            Self::Or(items) => items.iter().any(|e| e.matches(program)),
+            Self::Not(inner) => !inner.matches(program),
+            Self::Predicate { predicate, .. } => predicate.matches(program),
+        }
+    }
+}
+
+impl Predicate {
+    /// 番組が条件を満たすかどうか
+    pub fn matches(&self, program: &KurecProgram) -> bool {
+        match self {
+            Self::Title(p) => program.name.as_deref().is_some_and(|name| p.matches(name)),
+            Self::Description(p) => {
+                program.description.as_deref().is_some_and(|d| p.matches(d))
+                    || program
+                        .extended
+                        .as_ref()
+                        .and_then(|e| e.as_object())
+                        .is_some_and(|extended| {
+                            extended.iter().any(|(heading, text)| {
+                                p.matches(heading) || text.as_str().is_some_and(|t| p.matches(t))
+                            })
+                        })
+            }
+            Self::Genre(p) => program.genres.iter().any(|g| p.matches(g)),
+            Self::Cast(p) => program.details().cast.iter().any(|c| p.matches(&c.name)),
+            Self::Staff(p) => program.details().staff.iter().any(|s| p.matches(&s.name)),
+            Self::Channel(p) => {
+                p.matches(&program.channel_name) || p.raw() == program.service_id.to_string()
+            }
+            Self::ChannelType(t) => t.eq_ignore_ascii_case(&program.channel_type),
+            Self::Weekday(days) => days.contains(&jst(program.start_at).weekday()),
+            Self::Time(window) => {
+                let start = jst(program.start_at);
+                window.contains(start.hour() * 60 + start.minute())
+            }
+            Self::Duration(range) => range.contains(program.duration_millis / 60_000),
+            Self::Free(free) => program.is_free == *free,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::epg::KurecProgram;
+    use crate::query::Query;
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    fn program(name: &str) -> KurecProgram {
+        KurecProgram {
+            id: 327360102400101,
+            mirakc_url: "http://tuner:40772".to_string(),
+            service_id: 3273601024,
+            network_id: 32736,
+            event_id: 101,
+            channel_name: "ＮＨＫ総合１・東京".to_string(),
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+            name: Some(name.to_string()),
+            description: Some("宇宙世紀を舞台にしたロボットアニメ".to_string()),
+            extended: Some(json!({"出演者": "古谷徹", "原作": "矢立肇、富野由悠季"})),
+            // 2025-01-04 (土) 18:00 JST
+            start_at: Utc.with_ymd_and_hms(2025, 1, 4, 9, 0, 0).unwrap(),
+            duration_millis: 30 * 60_000,
+            is_free: true,
+            genres: vec!["アニメ・特撮／国内アニメ".to_string()],
+            video_info: None,
+            audio_infos: vec![],
+            series_info: None,
+            markers: Default::default(),
+            episode_number: None,
+        }
+    }
+
+    fn matches(query: &str, program: &KurecProgram) -> bool {
+        Query::parse(query).unwrap().matches(program)
+    }
+
+    #[test]
+    fn test_example_query() {
+        let query = r#"title:"ガンダム" genre:アニメ ch:NHK* -title:再放送 weekday:sat..sun time:18:00-24:00"#;
+        assert!(matches(query, &program("機動戦士ガンダム")));
+        assert!(!matches(query, &program("機動戦士ガンダム（再放送）")));
+        assert!(!matches(query, &program("ニュース")));
+
+        let mut weekday = program("機動戦士ガンダム");
+        weekday.start_at = Utc.with_ymd_and_hms(2025, 1, 6, 9, 0, 0).unwrap();
+        assert!(!matches(query, &weekday));
+    }
+
+    #[test]
+    fn test_predicates() {
+        let p = program("機動戦士ガンダム");
+        assert!(matches("desc:宇宙世紀", &p));
+        assert!(matches("desc:古谷", &p));
+        assert!(matches("desc:出演者", &p));
+        assert!(matches("cast:古谷徹", &p));
+        assert!(!matches("cast:矢立肇", &p));
+        assert!(matches("staff:富野*", &p));
+        assert!(matches(r#"genre:"アニメ・特撮／国内アニメ""#, &p));
+        assert!(!matches("genre:ドラマ", &p));
+        assert!(matches("ch:3273601024", &p));
+        assert!(matches("ch:*東京", &p));
+        assert!(matches("type:gr", &p));
+        assert!(!matches("type:BS", &p));
+        assert!(matches("time:17:00-02:00", &p));
+        assert!(!matches("time:19:00-24:00", &p));
+        assert!(matches("dur:>=30", &p));
+        assert!(!matches("dur:>30", &p));
+        assert!(matches("free:yes", &p));
+        assert!(matches("ニュース OR ガンダム", &p));
+        assert!(!matches("NOT (ニュース OR ガンダム)", &p));
+    }
+}
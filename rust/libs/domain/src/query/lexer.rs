@@ -0,0 +1,191 @@
+//! クエリの字句解析
+
+use crate::query::{ParseError, Span};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum TokenKind {
+    LParen,
+    RParen,
+    /// 否定 (`-`)
+    Minus,
+    Or,
+    And,
+    Not,
+    /// `name:` のフィールド名
+    Field(String),
+    /// 値 (引用符で囲まれた文字列を含む)
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || c == '(' || c == ')'
+}
+
+fn is_field_name(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphabetic() || c == '_')
+}
+
+/// クエリ文字列をトークンに分割する。
+///
+/// フィールド名の直後は値として読むため、値には `-` や `:` を含められる (`time:18:00-24:00`)。
+pub(super) fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    // 値を待っているフィールド名の位置
+    let mut field: Option<Span> = None;
+
+    while let Some(c) = input[pos..].chars().next() {
+        let start = pos;
+        if let Some(field_span) = field {
+            if is_separator(c) {
+                return Err(ParseError::new("フィールドの後に値が必要です", field_span));
+            }
+        } else {
+            if c.is_whitespace() {
+                pos += c.len_utf8();
+                continue;
+            }
+            let kind = match c {
+                '(' => Some(TokenKind::LParen),
+                ')' => Some(TokenKind::RParen),
+                '-' => Some(TokenKind::Minus),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                pos += c.len_utf8();
+                tokens.push(Token {
+                    kind,
+                    span: Span::new(start, pos),
+                });
+                continue;
+            }
+        }
+
+        if c == '"' {
+            let (text, end) = read_quoted(input, start)?;
+            pos = end;
+            tokens.push(Token {
+                kind: TokenKind::Text(text),
+                span: Span::new(start, end),
+            });
+            field = None;
+            continue;
+        }
+
+        let mut end = start;
+        let mut is_field = false;
+        for (i, c) in input[start..].char_indices() {
+            if is_separator(c) {
+                break;
+            }
+            if c == ':' && field.is_none() && is_field_name(&input[start..start + i]) {
+                is_field = true;
+                break;
+            }
+            end = start + i + c.len_utf8();
+        }
+
+        let word = &input[start..end];
+        let span = Span::new(start, end);
+        if is_field {
+            // ':' を読み飛ばす
+            pos = end + 1;
+            tokens.push(Token {
+                kind: TokenKind::Field(word.to_string()),
+                span,
+            });
+            field = Some(span);
+            continue;
+        }
+        pos = end;
+        let kind = match word {
+            "OR" if field.is_none() => TokenKind::Or,
+            "AND" if field.is_none() => TokenKind::And,
+            "NOT" if field.is_none() => TokenKind::Not,
+            _ => TokenKind::Text(word.to_string()),
+        };
+        tokens.push(Token { kind, span });
+        field = None;
+    }
+
+    if let Some(field_span) = field {
+        return Err(ParseError::new("フィールドの後に値が必要です", field_span));
+    }
+    Ok(tokens)
+}
+
+/// `start` の `"` から始まる引用符で囲まれた文字列を読み、内容と終端の位置を返す。
+///
+/// `\"` と `\\` でそれぞれ `"` と `\` を表す。
+fn read_quoted(input: &str, start: usize) -> Result<(String, usize), ParseError> {
+    let mut text = String::new();
+    let mut chars = input[start + 1..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((text, start + 1 + i + 1)),
+            '\\' => match chars.next() {
+                Some((_, c @ ('"' | '\\'))) => text.push(c),
+                Some((_, c)) => {
+                    text.push('\\');
+                    text.push(c);
+                }
+                None => break,
+            },
+            c => text.push(c),
+        }
+    }
+    Err(ParseError::new(
+        "引用符が閉じられていません",
+        Span::new(start, input.len()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(input: &str) -> Vec<TokenKind> {
+        tokenize(input)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds(r#"title:"機動戦士 ガンダム" -(ch:NHK* OR time:18:00-24:00) ガンダム:逆襲"#),
+            vec![
+                Field("title".into()),
+                Text("機動戦士 ガンダム".into()),
+                Minus,
+                LParen,
+                Field("ch".into()),
+                Text("NHK*".into()),
+                Or,
+                Field("time".into()),
+                Text("18:00-24:00".into()),
+                RParen,
+                Text("ガンダム:逆襲".into()),
+            ]
+        );
+        assert_eq!(kinds(r#""say \"hi\"""#), vec![Text(r#"say "hi""#.into())]);
+    }
+
+    #[test]
+    fn test_tokenize_errors() {
+        let err = tokenize("title: ガンダム").unwrap_err();
+        assert_eq!(err.span, Span::new(0, 5));
+
+        let err = tokenize(r#"ガンダム "unterminated"#).unwrap_err();
+        assert_eq!(err.span, Span::new(13, 26));
+    }
+}
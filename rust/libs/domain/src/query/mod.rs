@@ -0,0 +1,193 @@
+//! KuRec クエリ言語
+//!
+//! 番組を検索・選択するための、検索バックエンドに依存しない小さなクエリ言語です。
+//! ルールエンジン、CLI の検索、Web UI で同じ構文を使用します。
+//!
+//! ```text
+//! title:"ガンダム" genre:アニメ ch:NHK* -title:再放送 weekday:sat..sun time:18:00-24:00
+//! ```
+//!
+//! - 空白で区切った条件はすべて満たす必要がある (AND)。`OR` でいずれかを満たせばよい条件を、
+//!   `( )` でグループを作れる。`-` または `NOT` で条件を否定する。
+//! - フィールドのない値は番組名 (`title:`) の条件として扱う。
+//! - 文字列の条件は部分一致。`*` を含む場合はワイルドカードとして全体に一致させる。
+//!   大文字・小文字、全角・半角、カタカナ・ひらがなは区別しない。
+//!
+//! | フィールド | 別名 | 値の例 |
+//! |---|---|---|
+//! | `title` | `t` | `ガンダム`, `"機動戦士 ガンダム"`, `ガンダム*` |
+//! | `desc` | `description` | `宇宙世紀` |
+//! | `genre` | `g` | `アニメ`, `"アニメ・特撮／国内アニメ"` |
+//! | `cast` | `actor` | `古谷徹` (詳細情報の出演者の名前) |
+//! | `staff` | | `富野*` (詳細情報のスタッフの名前) |
+//! | `ch` | `channel` | `NHK*`, `3273601024` (Service ID) |
+//! | `type` | `chtype` | `GR`, `BS`, `CS` |
+//! | `weekday` | `wd` | `sat`, `sat,sun`, `sat..sun`, `土` |
+//! | `time` | | `18:00-24:00`, `23:00-02:00` (日本時間の放送開始時刻) |
+//! | `duration` | `dur` | `30` (分), `>=30`, `<60`, `30..60`, `1h` |
+//! | `free` | | `yes`, `no` |
+
+pub mod ast;
+mod eval;
+mod lexer;
+mod parser;
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::epg::KurecProgram;
+use crate::models::title::normalize_text;
+
+pub use ast::{DurationRange, Expr, Predicate, Span, TextPattern};
+
+/// クエリの構文エラー
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{message}")]
+pub struct ParseError {
+    /// エラーの内容
+    pub message: String,
+    /// エラーの位置
+    pub span: Span,
+}
+
+impl ParseError {
+    pub(crate) fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// エラーの位置を `^` で示した、複数行のメッセージを作成する。
+    pub fn render(&self, input: &str) -> String {
+        let start = self.span.start.min(input.len());
+        let end = self.span.end.clamp(start, input.len());
+        let indent = display_width(&input[..start]);
+        let carets = display_width(&input[start..end]).max(1);
+        format!(
+            "{}\n  {}\n  {}{}",
+            self.message,
+            input,
+            " ".repeat(indent),
+            "^".repeat(carets)
+        )
+    }
+}
+
+/// 端末での表示幅 (全角文字は2)
+fn display_width(s: &str) -> usize {
+    s.chars()
+        .map(|c| match c as u32 {
+            0xFF61..=0xFF9F => 1,
+            0x1100.. => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+/// 比較のために文字列を正規化する。
+///
+/// 番組名の正規化 (`normalize_text`) と同じく、全角・半角とカタカナ・ひらがなの違いを無視し、小文字にする。
+pub fn normalize(s: &str) -> String {
+    normalize_text(s)
+}
+
+/// 解析済みのクエリ
+///
+/// 文字列として直列化され、元のクエリ文字列を保持する。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Query {
+    source: String,
+    expr: Expr,
+}
+
+impl Query {
+    /// クエリ文字列を解析する。
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            source: source.to_string(),
+            expr: parser::parse(source)?,
+        })
+    }
+
+    /// 元のクエリ文字列
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// 構文木
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// 番組がクエリに一致するかどうか
+    pub fn matches(&self, program: &KurecProgram) -> bool {
+        self.expr.matches(program)
+    }
+}
+
+impl FromStr for Query {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for Query {
+    type Error = ParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<Query> for String {
+    fn from(value: Query) -> Self {
+        value.source
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("ＮＨＫ総合１"), "nhk総合1");
+        assert_eq!(normalize("Ｅテレ　ＡＢＣ"), "eてれ abc");
+    }
+
+    #[test]
+    fn test_render_error() {
+        let input = "title:ガンダム foo:bar";
+        let err = Query::parse(input).unwrap_err();
+        assert_eq!(
+            err.render(input),
+            format!(
+                "{}\n  title:ガンダム foo:bar\n                 ^^^",
+                err.message
+            )
+        );
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let query: Query = serde_json::from_str(r#""ガンダム -再放送""#).unwrap();
+        assert_eq!(query.source(), "ガンダム -再放送");
+        assert_eq!(
+            serde_json::to_string(&query).unwrap(),
+            r#""ガンダム -再放送""#
+        );
+        assert!(serde_json::from_str::<Query>(r#""title:""#).is_err());
+    }
+}
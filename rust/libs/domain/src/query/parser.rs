@@ -0,0 +1,401 @@
+//! クエリの構文解析
+//!
+//! ```text
+//! or      := and ("OR" and)*
+//! and     := unary (["AND"] unary)*
+//! unary   := ("-" | "NOT") unary | primary
+//! primary := "(" or ")" | FIELD TEXT | TEXT
+//! ```
+
+use chrono::Weekday;
+
+use crate::models::rule::TimeWindow;
+use crate::query::ast::{DurationRange, Expr, Predicate, Span, TextPattern};
+use crate::query::lexer::{tokenize, Token, TokenKind};
+use crate::query::ParseError;
+
+/// クエリ文字列を構文木に変換する。
+pub(super) fn parse(input: &str) -> Result<Expr, ParseError> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Err(ParseError::new("クエリが空です", Span::new(0, input.len())));
+    }
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        end: input.len(),
+    };
+    let expr = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(ParseError::new("対応する '(' がありません", token.span));
+    }
+    Ok(expr)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// 入力の末尾の位置
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// 条件が続くことを確認する。
+    fn expect_operand(&self, after: &Token, message: &str) -> Result<(), ParseError> {
+        match self.peek().map(|t| &t.kind) {
+            None | Some(TokenKind::RParen | TokenKind::Or | TokenKind::And) => {
+                Err(ParseError::new(message, after.span))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        let mut items = vec![self.parse_and()?];
+        while let Some(token) = self.peek().filter(|t| t.kind == TokenKind::Or).cloned() {
+            self.next();
+            self.expect_operand(&token, "OR の後に条件が必要です")?;
+            items.push(self.parse_and()?);
+        }
+        Ok(if items.len() == 1 {
+            items.pop().unwrap()
+        } else {
+            Expr::Or(items)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        let mut items = vec![self.parse_unary()?];
+        while let Some(token) = self.peek().cloned() {
+            match token.kind {
+                TokenKind::RParen | TokenKind::Or => break,
+                TokenKind::And => {
+                    self.next();
+                    self.expect_operand(&token, "AND の後に条件が必要です")?;
+                }
+                _ => {}
+            }
+            items.push(self.parse_unary()?);
+        }
+        Ok(if items.len() == 1 {
+            items.pop().unwrap()
+        } else {
+            Expr::And(items)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        if let Some(token) = self
+            .peek()
+            .filter(|t| matches!(t.kind, TokenKind::Minus | TokenKind::Not))
+            .cloned()
+        {
+            self.next();
+            self.expect_operand(&token, "否定する条件が必要です")?;
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        let Some(token) = self.next() else {
+            return Err(ParseError::new(
+                "条件が必要です",
+                Span::new(self.end, self.end),
+            ));
+        };
+        match token.kind {
+            TokenKind::LParen => {
+                self.expect_operand(&token, "括弧の中に条件が必要です")?;
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token {
+                        kind: TokenKind::RParen,
+                        ..
+                    }) => Ok(expr),
+                    _ => Err(ParseError::new("括弧が閉じられていません", token.span)),
+                }
+            }
+            TokenKind::Field(name) => {
+                let Some(Token {
+                    kind: TokenKind::Text(value),
+                    span: value_span,
+                }) = self.next()
+                else {
+                    unreachable!("字句解析でフィールドの後には値が続く");
+                };
+                let predicate = build_predicate(&name, token.span, &value, value_span)?;
+                Ok(Expr::Predicate {
+                    predicate,
+                    span: token.span.to(value_span),
+                })
+            }
+            TokenKind::Text(value) => Ok(Expr::Predicate {
+                predicate: Predicate::Title(TextPattern::new(&value)),
+                span: token.span,
+            }),
+            TokenKind::RParen => Err(ParseError::new("対応する '(' がありません", token.span)),
+            TokenKind::Or | TokenKind::And => {
+                Err(ParseError::new("OR / AND の前に条件が必要です", token.span))
+            }
+            TokenKind::Minus | TokenKind::Not => unreachable!("parse_unary で処理される"),
+        }
+    }
+}
+
+fn build_predicate(
+    name: &str,
+    name_span: Span,
+    value: &str,
+    span: Span,
+) -> Result<Predicate, ParseError> {
+    let text = |value: &str| {
+        if value.is_empty() {
+            Err(ParseError::new("値が空です", span))
+        } else {
+            Ok(TextPattern::new(value))
+        }
+    };
+    match name.to_ascii_lowercase().as_str() {
+        "title" | "t" => Ok(Predicate::Title(text(value)?)),
+        "desc" | "description" => Ok(Predicate::Description(text(value)?)),
+        "genre" | "g" => Ok(Predicate::Genre(text(value)?)),
+        "cast" | "actor" => Ok(Predicate::Cast(text(value)?)),
+        "staff" => Ok(Predicate::Staff(text(value)?)),
+        "ch" | "channel" => Ok(Predicate::Channel(text(value)?)),
+        "type" | "chtype" => {
+            if value.is_empty() || !value.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(ParseError::new(
+                    format!(
+                        "チャンネルタイプは GR, BS, CS などで指定してください: {}",
+                        value
+                    ),
+                    span,
+                ));
+            }
+            Ok(Predicate::ChannelType(value.to_ascii_uppercase()))
+        }
+        "weekday" | "wd" => parse_weekdays(value)
+            .map(Predicate::Weekday)
+            .map_err(|message| ParseError::new(message, span)),
+        "time" => value
+            .parse::<TimeWindow>()
+            .map(Predicate::Time)
+            .map_err(|e| ParseError::new(e.to_string(), span)),
+        "duration" | "dur" => parse_duration(value)
+            .map(Predicate::Duration)
+            .map_err(|message| ParseError::new(message, span)),
+        "free" => match value.to_ascii_lowercase().as_str() {
+            "yes" | "true" => Ok(Predicate::Free(true)),
+            "no" | "false" => Ok(Predicate::Free(false)),
+            _ => Err(ParseError::new(
+                format!("free には yes または no を指定してください: {}", value),
+                span,
+            )),
+        },
+        _ => Err(ParseError::new(
+            format!(
+                "不明なフィールドです: {} (値に ':' を含む場合は \"...\" で囲んでください)",
+                name
+            ),
+            name_span,
+        )),
+    }
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    let s = s.trim_end_matches("曜日").trim_end_matches("曜");
+    match s {
+        "月" => Some(Weekday::Mon),
+        "火" => Some(Weekday::Tue),
+        "水" => Some(Weekday::Wed),
+        "木" => Some(Weekday::Thu),
+        "金" => Some(Weekday::Fri),
+        "土" => Some(Weekday::Sat),
+        "日" => Some(Weekday::Sun),
+        _ => s.parse().ok(),
+    }
+}
+
+/// `sat`、`sat,sun`、`fri..mon` (日曜をまたぐ範囲) の形式の曜日を解析する。
+fn parse_weekdays(value: &str) -> Result<Vec<Weekday>, String> {
+    let invalid = |s: &str| format!("不正な曜日です: {}", s);
+    let mut days = Vec::new();
+    for item in value.split(',') {
+        match item.split_once("..") {
+            Some((from, to)) => {
+                let from = parse_weekday(from).ok_or_else(|| invalid(from))?;
+                let to = parse_weekday(to).ok_or_else(|| invalid(to))?;
+                let mut day = from;
+                loop {
+                    days.push(day);
+                    if day == to {
+                        break;
+                    }
+                    day = day.succ();
+                }
+            }
+            None => days.push(parse_weekday(item).ok_or_else(|| invalid(item))?),
+        }
+    }
+    let mut unique = Vec::new();
+    for day in days {
+        if !unique.contains(&day) {
+            unique.push(day);
+        }
+    }
+    Ok(unique)
+}
+
+/// `30` (分)、`1h`、`90m` の形式の長さを分で返す。
+fn parse_minutes(s: &str) -> Result<i64, String> {
+    let invalid = || format!("不正な長さです: {}", s);
+    let (number, scale) = if let Some(n) = s.strip_suffix('h') {
+        (n, 60)
+    } else if let Some(n) = s.strip_suffix("min") {
+        (n, 1)
+    } else if let Some(n) = s.strip_suffix('m') {
+        (n, 1)
+    } else {
+        (s, 1)
+    };
+    number
+        .parse::<i64>()
+        .ok()
+        .filter(|n| *n >= 0)
+        .map(|n| n * scale)
+        .ok_or_else(invalid)
+}
+
+/// `30`、`>=30`、`>30`、`<=60`、`<60`、`30..60` の形式の長さの範囲を解析する。
+fn parse_duration(value: &str) -> Result<DurationRange, String> {
+    let range = if let Some(v) = value.strip_prefix(">=") {
+        DurationRange {
+            min: Some(parse_minutes(v)?),
+            max: None,
+        }
+    } else if let Some(v) = value.strip_prefix('>') {
+        DurationRange {
+            min: Some(parse_minutes(v)? + 1),
+            max: None,
+        }
+    } else if let Some(v) = value.strip_prefix("<=") {
+        DurationRange {
+            min: None,
+            max: Some(parse_minutes(v)?),
+        }
+    } else if let Some(v) = value.strip_prefix('<') {
+        DurationRange {
+            min: None,
+            max: Some(parse_minutes(v)? - 1),
+        }
+    } else if let Some((min, max)) = value.split_once("..") {
+        DurationRange {
+            min: Some(parse_minutes(min)?),
+            max: Some(parse_minutes(max)?),
+        }
+    } else {
+        let minutes = parse_minutes(value)?;
+        DurationRange {
+            min: Some(minutes),
+            max: Some(minutes),
+        }
+    };
+    if let (Some(min), Some(max)) = (range.min, range.max) {
+        if min > max {
+            return Err(format!("長さの範囲が空です: {}", value));
+        }
+    }
+    Ok(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(input: &str) -> String {
+        parse(input).unwrap().to_string()
+    }
+
+    fn error(input: &str) -> (String, Span) {
+        let err = parse(input).unwrap_err();
+        (err.message, err.span)
+    }
+
+    #[test]
+    fn test_parse_example() {
+        let expr = parse(
+            r#"title:"ガンダム" genre:アニメ ch:NHK* -title:再放送 weekday:sat..sun time:18:00-24:00"#,
+        )
+        .unwrap();
+        let Expr::And(items) = &expr else {
+            panic!("expected And: {:?}", expr);
+        };
+        assert_eq!(items.len(), 6);
+        assert_eq!(
+            items[0],
+            Expr::Predicate {
+                predicate: Predicate::Title(TextPattern::new("ガンダム")),
+                span: Span::new(0, 20),
+            }
+        );
+        assert!(matches!(&items[3], Expr::Not(_)));
+        assert_eq!(
+            expr.to_string(),
+            "title:ガンダム genre:アニメ ch:NHK* -title:再放送 weekday:sat,sun time:18:00-24:00"
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(canonical("a b OR c"), "title:a title:b OR title:c");
+        assert_eq!(canonical("a (b OR c)"), "title:a (title:b OR title:c)");
+        assert_eq!(
+            canonical("NOT (a OR b) AND c"),
+            "-(title:a OR title:b) title:c"
+        );
+        assert_eq!(canonical("--a"), "--title:a");
+        assert_eq!(canonical(r#""a b" "-c""#), r#"title:"a b" title:"-c""#);
+    }
+
+    #[test]
+    fn test_field_values() {
+        assert_eq!(canonical("wd:fri..mon"), "weekday:fri,sat,sun,mon");
+        assert_eq!(canonical("wd:土,日曜日,sat"), "weekday:sat,sun");
+        assert_eq!(canonical("dur:>30"), "duration:>=31");
+        assert_eq!(canonical("dur:<1h"), "duration:<=59");
+        assert_eq!(canonical("dur:30..2h"), "duration:30..120");
+        assert_eq!(canonical("type:gr free:no"), "type:GR free:no");
+        assert_eq!(canonical("time:23:00-02:00"), "time:23:00-02:00");
+        assert_eq!(
+            canonical("actor:古谷徹 staff:富野*"),
+            "cast:古谷徹 staff:富野*"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(error("").1, Span::new(0, 0));
+        assert_eq!(error("a foo:b").1, Span::new(2, 5));
+        assert_eq!(error("weekday:someday").1, Span::new(8, 15));
+        assert_eq!(error("time:25:00-26:00").1, Span::new(5, 16));
+        assert_eq!(error("dur:60..30").1, Span::new(4, 10));
+        assert_eq!(error("(a OR b").1, Span::new(0, 1));
+        assert_eq!(error("a)").1, Span::new(1, 2));
+        assert_eq!(error("a OR").1, Span::new(2, 4));
+        assert_eq!(error("OR a").1, Span::new(0, 2));
+        assert_eq!(error("a -").1, Span::new(2, 3));
+        assert_eq!(error("()").1, Span::new(0, 1));
+        assert_eq!(error(r#"title:"""#).1, Span::new(6, 8));
+    }
+}
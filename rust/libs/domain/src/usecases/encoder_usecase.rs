@@ -0,0 +1,819 @@
+//! エンコーダーユースケース
+//!
+//! ジョブエンジンが送ったエンコードジョブ (`JobDispatchedEvent`) を実行します。
+//! 録画レコードのメタデータでスクリプトを展開し、ジョブの入力 (保存済みの元ファイルや
+//! 依存するジョブの出力) をストレージから読み込んで `EncodeExecutor` でスクリプトを実行します。
+//! 元ファイルを保存していない場合は、mirakc の録画ファイルのストリームを入力にします。
+//! スクリプトのログは実行中から `JobLogRepository` に保存します。
+//! 宣言された出力がすべて作成されていれば、出力ごとに設定されたストレージに保存して成功とし、
+//! 結果を `JobCompletedEvent` で返します。
+//! 失敗した場合は `EncodeFailedEvent` も発行します。
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use tracing::{debug, info, warn};
+
+use crate::events::kurec_events::{EncodeFailedEvent, JobCompletedEvent, JobDispatchedEvent};
+use crate::models::checksum::Checksum;
+use crate::models::encode::{EncodeExecution, EncodeTask, EncodeVariables, EncodedFile};
+use crate::models::job::{JobKind, JobOutputSpec, JobResult};
+use crate::models::record::{EncodeStatus, Record, RecordOutput, MIRAKC_STORAGE, ORIGINAL_OUTPUT};
+use crate::ports::encode_executor::{EncodeExecutor, EncodeInput, EncodeLogSink};
+use crate::ports::event_sink::EventSink;
+use crate::ports::mirakc_api::MirakcRecordsApi;
+use crate::ports::repositories::job_log_repository::JobLogRepository;
+use crate::ports::storage::StorageRegistry;
+
+/// エンコーダーが発行するイベントの送信先
+pub struct EncoderSinks {
+    pub completed: Arc<dyn EventSink<JobCompletedEvent>>,
+    pub failed: Arc<dyn EventSink<EncodeFailedEvent>>,
+}
+
+/// エンコードジョブの失敗
+struct EncodeFailure {
+    error: String,
+    exit_code: Option<i32>,
+    timed_out: bool,
+}
+
+impl From<anyhow::Error> for EncodeFailure {
+    fn from(e: anyhow::Error) -> Self {
+        Self {
+            error: format!("{:#}", e),
+            exit_code: None,
+            timed_out: false,
+        }
+    }
+}
+
+/// スクリプトのログを `JobLogRepository` に保存する `EncodeLogSink`
+struct JobLogSink {
+    logs: Arc<dyn JobLogRepository>,
+    mirakc_url: String,
+    record_id: String,
+    job: String,
+    /// 保存したログの名前
+    name: Mutex<Option<String>>,
+}
+
+#[async_trait]
+impl EncodeLogSink for JobLogSink {
+    async fn save(&self, log: &[u8]) -> Result<()> {
+        let name = self
+            .logs
+            .save_log(&self.mirakc_url, &self.record_id, &self.job, log.to_vec())
+            .await?;
+        *self.name.lock().unwrap() = Some(name);
+        Ok(())
+    }
+}
+
+/// エンコーダーユースケース
+pub struct EncoderUseCase {
+    records: Arc<dyn MirakcRecordsApi>,
+    executor: Arc<dyn EncodeExecutor>,
+    logs: Arc<dyn JobLogRepository>,
+    storages: StorageRegistry,
+    sinks: EncoderSinks,
+    timeout: Duration,
+}
+
+impl EncoderUseCase {
+    /// 新しいEncoderUseCaseを作成
+    pub fn new(
+        records: Arc<dyn MirakcRecordsApi>,
+        executor: Arc<dyn EncodeExecutor>,
+        logs: Arc<dyn JobLogRepository>,
+        storages: StorageRegistry,
+        sinks: EncoderSinks,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            records,
+            executor,
+            logs,
+            storages,
+            sinks,
+            timeout,
+        }
+    }
+
+    /// エンコードジョブを実行し、結果を発行する。
+    ///
+    /// エンコード以外のジョブは無視して `Ok(None)` を返す。
+    /// ジョブの失敗は `JobResult::Failed` として発行し、発行に失敗した場合だけ `Err` を返す。
+    pub async fn handle_job_dispatched(
+        &self,
+        event: &JobDispatchedEvent,
+    ) -> Result<Option<JobResult>> {
+        let JobKind::Encode { script } = &event.job.kind else {
+            debug!(job = %event.job.name, "エンコードジョブではないため無視します");
+            return Ok(None);
+        };
+        info!(record_id = %event.record_id, job = %event.job.name, "エンコードを開始します");
+
+        let mut log = None;
+        let result = match self.encode(event, script, &mut log).await {
+            Ok(outputs) => JobResult::Succeeded { outputs },
+            Err(failure) => {
+                warn!(record_id = %event.record_id, job = %event.job.name, error = %failure.error, "エンコードに失敗しました");
+                self.sinks
+                    .failed
+                    .publish(EncodeFailedEvent {
+                        mirakc_url: event.mirakc_url.clone(),
+                        record_id: event.record_id.clone(),
+                        job: event.job.name.clone(),
+                        exit_code: failure.exit_code,
+                        timed_out: failure.timed_out,
+                        error: failure.error.clone(),
+                        log,
+                    })
+                    .await?;
+                JobResult::Failed {
+                    error: failure.error,
+                }
+            }
+        };
+
+        self.sinks
+            .completed
+            .publish(JobCompletedEvent {
+                mirakc_url: event.mirakc_url.clone(),
+                record_id: event.record_id.clone(),
+                job: event.job.name.clone(),
+                result: result.clone(),
+            })
+            .await?;
+        Ok(Some(result))
+    }
+
+    /// スクリプトを実行し、出力を検証してストレージに保存する。保存したログの名前を `log` に設定する。
+    async fn encode(
+        &self,
+        event: &JobDispatchedEvent,
+        script: &str,
+        log: &mut Option<String>,
+    ) -> std::result::Result<Vec<RecordOutput>, EncodeFailure> {
+        let record = self
+            .records
+            .get_record(&event.mirakc_url, &event.record_id)
+            .await?
+            .ok_or_else(|| anyhow!("録画レコード {} がありません", event.record_id))?;
+        let variables = EncodeVariables::from_record(&record);
+        let task = EncodeTask {
+            mirakc_url: event.mirakc_url.clone(),
+            record_id: event.record_id.clone(),
+            job: event.job.name.clone(),
+            script: variables.render(script)?,
+            env: variables.env(),
+            outputs: event
+                .job
+                .outputs
+                .iter()
+                .map(|output| output.name.clone())
+                .collect(),
+            timeout: self.timeout,
+        };
+        let inputs = self.open_inputs(event).await?;
+        let sink = Arc::new(JobLogSink {
+            logs: self.logs.clone(),
+            mirakc_url: event.mirakc_url.clone(),
+            record_id: event.record_id.clone(),
+            job: event.job.name.clone(),
+            name: Mutex::new(None),
+        });
+        let execution = self.executor.execute(&task, inputs, sink.clone()).await;
+        *log = sink.name.lock().unwrap().clone();
+        let execution = execution?;
+
+        let result = self
+            .store_outputs(&record, &event.job.outputs, &execution)
+            .await;
+        if let Err(e) = self.executor.cleanup(&task).await {
+            warn!(job = %event.job.name, "作業ディレクトリを削除できません: {:?}", e);
+        }
+        result
+    }
+
+    /// ジョブの入力を開く。
+    ///
+    /// 依存するジョブの出力と保存済みの元ファイルはストレージから読み込む。
+    /// 元ファイル (`original`) を保存していない場合だけ mirakc から読み込む。
+    async fn open_inputs(&self, event: &JobDispatchedEvent) -> Result<Vec<EncodeInput>> {
+        let mut inputs = Vec::new();
+        for name in &event.job.inputs {
+            let output = event.inputs.iter().find(|output| &output.name == name);
+            let data = match output {
+                Some(output) if output.storage != MIRAKC_STORAGE => {
+                    let storage = self.storages.get(&output.storage)?;
+                    storage.get(&output.location).await?.ok_or_else(|| {
+                        anyhow!(
+                            "入力 {} がストレージ {} の {} にありません",
+                            name,
+                            output.storage,
+                            output.location
+                        )
+                    })?
+                }
+                _ if name == ORIGINAL_OUTPUT => {
+                    self.records
+                        .get_record_stream(&event.mirakc_url, &event.record_id, 0)
+                        .await?
+                }
+                _ => bail!("入力 {} がありません", name),
+            };
+            debug!(job = %event.job.name, input = %name, storage = ?output.map(|o| &o.storage), "入力を開きました");
+            inputs.push(EncodeInput::new(name, data));
+        }
+        Ok(inputs)
+    }
+
+    /// スクリプトが成功していれば、出力を検証してストレージに保存する。
+    async fn store_outputs(
+        &self,
+        record: &Record,
+        specs: &[JobOutputSpec],
+        execution: &EncodeExecution,
+    ) -> std::result::Result<Vec<RecordOutput>, EncodeFailure> {
+        if !execution.succeeded() {
+            return Err(EncodeFailure {
+                error: if execution.timed_out {
+                    format!("{:?} でタイムアウトしました", self.timeout)
+                } else {
+                    match execution.exit_code {
+                        Some(code) => format!("スクリプトが終了コード {} で終了しました", code),
+                        None => "スクリプトがシグナルで終了しました".to_string(),
+                    }
+                },
+                exit_code: execution.exit_code,
+                timed_out: execution.timed_out,
+            });
+        }
+
+        let mut outputs = Vec::new();
+        for (spec, file) in validate_outputs(specs, execution).map_err(|e| EncodeFailure {
+            exit_code: execution.exit_code,
+            ..e.into()
+        })? {
+            let output =
+                self.store_output(record, spec, file)
+                    .await
+                    .map_err(|e| EncodeFailure {
+                        exit_code: execution.exit_code,
+                        ..e.into()
+                    })?;
+            outputs.push(output);
+        }
+        Ok(outputs)
+    }
+
+    /// 出力を設定されたストレージに保存する。
+    async fn store_output(
+        &self,
+        record: &Record,
+        spec: &JobOutputSpec,
+        file: &EncodedFile,
+    ) -> Result<RecordOutput> {
+        let storage = self.storages.get(&spec.storage)?;
+        let location = record.output_location(&spec.name);
+        let (data, checksum) = Checksum::tap(self.executor.read_output(file).await?);
+        let stored = storage
+            .put(&location, &spec.content_type, data)
+            .await
+            .with_context(|| {
+                format!(
+                    "出力 {} をストレージ {} に保存できません",
+                    spec.name, spec.storage
+                )
+            })?;
+        if stored.size != file.size {
+            bail!(
+                "ストレージ {} に保存した出力 {} のサイズ {} が出力のサイズ {} と一致しません",
+                spec.storage,
+                spec.name,
+                stored.size,
+                file.size
+            );
+        }
+        debug!(storage = %spec.storage, location = %location, size = stored.size, "出力を保存しました");
+        let sha256 = checksum.lock().unwrap().sha256();
+        Ok(RecordOutput {
+            name: spec.name.clone(),
+            storage: spec.storage.clone(),
+            location,
+            content_type: spec.content_type.clone(),
+            size: i64::try_from(stored.size).ok(),
+            encode_status: EncodeStatus::Encoded,
+            url: None,
+            sha256: Some(sha256),
+        })
+    }
+}
+
+/// 宣言された出力がすべて作成され、空でないことを検証する。
+fn validate_outputs<'a>(
+    specs: &'a [JobOutputSpec],
+    execution: &'a EncodeExecution,
+) -> Result<Vec<(&'a JobOutputSpec, &'a EncodedFile)>> {
+    specs
+        .iter()
+        .map(|spec| {
+            let file = execution
+                .outputs
+                .iter()
+                .find(|file| file.name == spec.name)
+                .ok_or_else(|| anyhow!("出力 {} が作成されていません", spec.name))?;
+            if file.size == 0 {
+                bail!("出力 {} が空です", spec.name);
+            }
+            Ok((spec, file))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::mirakc_events::RecordingStatus;
+    use crate::models::encode::EncodedFile;
+    use crate::models::encode::ORIGINAL_INPUT_FILE;
+    use crate::models::epg::KurecProgram;
+    use crate::models::job::{JobDefinition, SAVE_ORIGINAL_JOB};
+    use crate::models::record::{Record, RecordContent, RecordingInfo};
+    use crate::ports::mirakc_api::RecordStream;
+    use crate::ports::storage::{ByteStream, Storage, StoredObject};
+    use bytes::Bytes;
+    use chrono::{TimeZone, Utc};
+    use futures::stream::{self, StreamExt};
+    use std::collections::BTreeMap;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+
+    struct MockRecordsApi;
+
+    #[async_trait]
+    impl MirakcRecordsApi for MockRecordsApi {
+        async fn get_records(&self, _mirakc_url: &str) -> Result<Vec<Record>> {
+            unimplemented!()
+        }
+
+        async fn get_record(&self, mirakc_url: &str, record_id: &str) -> Result<Option<Record>> {
+            if record_id != "1" {
+                return Ok(None);
+            }
+            let start_at = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+            Ok(Some(Record {
+                id: record_id.to_string(),
+                mirakc_url: mirakc_url.to_string(),
+                program: KurecProgram {
+                    id: 1,
+                    mirakc_url: mirakc_url.to_string(),
+                    service_id: 1024,
+                    network_id: 32736,
+                    event_id: 1,
+                    channel_name: "テスト".to_string(),
+                    channel_type: "GR".to_string(),
+                    channel: "27".to_string(),
+                    name: Some("番組".to_string()),
+                    description: None,
+                    extended: None,
+                    start_at,
+                    duration_millis: 1800000,
+                    is_free: true,
+                    genres: vec![],
+                    video_info: None,
+                    audio_infos: vec![],
+                    series_info: None,
+                    markers: Default::default(),
+                    episode_number: None,
+                },
+                recording: RecordingInfo {
+                    status: RecordingStatus::Finished,
+                    start_time: start_at,
+                    end_time: None,
+                    duration_millis: None,
+                    failed_reason: None,
+                },
+                content: RecordContent {
+                    path: "1.m2ts".to_string(),
+                    content_type: "video/MP2T".to_string(),
+                    length: Some(4),
+                },
+                tags: vec![],
+            }))
+        }
+
+        async fn get_record_stream(
+            &self,
+            _mirakc_url: &str,
+            _record_id: &str,
+            _offset: u64,
+        ) -> Result<RecordStream> {
+            Ok(stream::iter(vec![Ok(Bytes::from_static(b"TS"))]).boxed())
+        }
+
+        async fn remove_record(&self, _mirakc_url: &str, _record_id: &str) -> Result<()> {
+            unimplemented!()
+        }
+    }
+
+    /// スクリプトに渡した入力 (ファイル名と内容)
+    type Inputs = Vec<(String, Vec<u8>)>;
+
+    struct MockExecutor {
+        execution: EncodeExecution,
+        tasks: Mutex<Vec<(EncodeTask, Inputs)>>,
+        cleaned: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl EncodeExecutor for MockExecutor {
+        async fn execute(
+            &self,
+            task: &EncodeTask,
+            inputs: Vec<EncodeInput>,
+            log: Arc<dyn EncodeLogSink>,
+        ) -> Result<EncodeExecution> {
+            log.save(b"frame=1\n").await?;
+            let mut written = Vec::new();
+            for input in inputs {
+                let chunks: Vec<Bytes> = input.data.map(|chunk| chunk.unwrap()).collect().await;
+                written.push((input.file_name, chunks.concat()));
+            }
+            self.tasks.lock().unwrap().push((task.clone(), written));
+            Ok(self.execution.clone())
+        }
+
+        async fn read_output(&self, file: &EncodedFile) -> Result<ByteStream> {
+            let data = vec![0u8; file.size as usize];
+            Ok(stream::iter(vec![Ok(Bytes::from(data))]).boxed())
+        }
+
+        async fn cleanup(&self, task: &EncodeTask) -> Result<()> {
+            self.cleaned.lock().unwrap().push(task.job.clone());
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockStorage {
+        objects: Mutex<BTreeMap<String, Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl Storage for MockStorage {
+        fn name(&self) -> &str {
+            "local1"
+        }
+
+        async fn put(
+            &self,
+            location: &str,
+            content_type: &str,
+            data: ByteStream,
+        ) -> Result<StoredObject> {
+            let chunks: Vec<Bytes> = data.map(|chunk| chunk.unwrap()).collect().await;
+            let data = chunks.concat();
+            let size = data.len() as u64;
+            self.objects
+                .lock()
+                .unwrap()
+                .insert(location.to_string(), data);
+            Ok(StoredObject {
+                location: location.to_string(),
+                size,
+                content_type: Some(content_type.to_string()),
+                modified_at: None,
+            })
+        }
+
+        async fn get(&self, location: &str) -> Result<Option<ByteStream>> {
+            let data = self.objects.lock().unwrap().get(location).cloned();
+            Ok(data.map(|data| stream::iter(vec![Ok(Bytes::from(data))]).boxed()))
+        }
+
+        async fn delete(&self, _location: &str) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn list(&self, _prefix: &str) -> Result<Vec<StoredObject>> {
+            unimplemented!()
+        }
+
+        async fn stat(&self, _location: &str) -> Result<Option<StoredObject>> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(Default)]
+    struct MockLogs {
+        logs: Mutex<Vec<(String, Vec<u8>)>>,
+    }
+
+    #[async_trait]
+    impl JobLogRepository for MockLogs {
+        async fn save_log(
+            &self,
+            _mirakc_url: &str,
+            record_id: &str,
+            job: &str,
+            log: Vec<u8>,
+        ) -> Result<String> {
+            let name = format!("{}/{}", record_id, job);
+            self.logs.lock().unwrap().push((name.clone(), log));
+            Ok(name)
+        }
+
+        async fn get_log(
+            &self,
+            _mirakc_url: &str,
+            _record_id: &str,
+            _job: &str,
+        ) -> Result<Option<Vec<u8>>> {
+            unimplemented!()
+        }
+    }
+
+    struct MockSink<E> {
+        events: Mutex<Vec<E>>,
+    }
+
+    impl<E> Default for MockSink<E> {
+        fn default() -> Self {
+            Self {
+                events: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl<E> EventSink<E> for MockSink<E>
+    where
+        E: serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        async fn publish(&self, event: E) -> Result<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        usecase: EncoderUseCase,
+        executor: Arc<MockExecutor>,
+        logs: Arc<MockLogs>,
+        storage: Arc<MockStorage>,
+        completed: Arc<MockSink<JobCompletedEvent>>,
+        failed: Arc<MockSink<EncodeFailedEvent>>,
+    }
+
+    fn fixture(execution: EncodeExecution) -> Fixture {
+        let executor = Arc::new(MockExecutor {
+            execution,
+            tasks: Mutex::new(Vec::new()),
+            cleaned: Mutex::new(Vec::new()),
+        });
+        let logs = Arc::new(MockLogs::default());
+        let storage = Arc::new(MockStorage::default());
+        let completed = Arc::new(MockSink::default());
+        let failed = Arc::new(MockSink::default());
+        let usecase = EncoderUseCase::new(
+            Arc::new(MockRecordsApi),
+            executor.clone(),
+            logs.clone(),
+            StorageRegistry::new(vec![storage.clone()]).unwrap(),
+            EncoderSinks {
+                completed: completed.clone(),
+                failed: failed.clone(),
+            },
+            Duration::from_secs(60),
+        );
+        Fixture {
+            usecase,
+            executor,
+            logs,
+            storage,
+            completed,
+            failed,
+        }
+    }
+
+    fn execution(exit_code: Option<i32>, outputs: Vec<EncodedFile>) -> EncodeExecution {
+        EncodeExecution {
+            exit_code,
+            timed_out: false,
+            outputs,
+        }
+    }
+
+    fn mp4(size: u64) -> EncodedFile {
+        EncodedFile {
+            name: "output.mp4".to_string(),
+            location: "/work/1/mp4/output.mp4".to_string(),
+            size,
+        }
+    }
+
+    fn dispatched(record_id: &str, kind: JobKind) -> JobDispatchedEvent {
+        JobDispatchedEvent {
+            mirakc_url: MIRAKC_URL.to_string(),
+            record_id: record_id.to_string(),
+            job: JobDefinition {
+                name: "mp4".to_string(),
+                kind,
+                depends_on: vec![],
+                inputs: vec!["original".to_string()],
+                outputs: vec![JobOutputSpec {
+                    name: "output.mp4".to_string(),
+                    content_type: "video/mp4".to_string(),
+                    storage: "local1".to_string(),
+                    description: None,
+                }],
+            },
+            inputs: vec![],
+        }
+    }
+
+    fn encode_job() -> JobKind {
+        JobKind::Encode {
+            script: "ffmpeg -i input.ts -metadata title={{title}} output.mp4".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encode_succeeded() {
+        let f = fixture(execution(Some(0), vec![mp4(1024)]));
+        let result = f
+            .usecase
+            .handle_job_dispatched(&dispatched("1", encode_job()))
+            .await
+            .unwrap();
+
+        let expected = JobResult::Succeeded {
+            outputs: vec![RecordOutput {
+                name: "output.mp4".to_string(),
+                storage: "local1".to_string(),
+                location: "2025/01/01/1/output.mp4".to_string(),
+                content_type: "video/mp4".to_string(),
+                size: Some(1024),
+                encode_status: EncodeStatus::Encoded,
+                url: None,
+                sha256: Some(
+                    "5f70bf18a086007016e948b04aed3b82103a36bea41755b6cddfaf10ace3c6ef".to_string(),
+                ),
+            }],
+        };
+        assert_eq!(result, Some(expected.clone()));
+        let (task, input) = f.executor.tasks.lock().unwrap()[0].clone();
+        assert_eq!(
+            task.script,
+            "ffmpeg -i input.ts -metadata title='番組' output.mp4"
+        );
+        assert_eq!(task.outputs, vec!["output.mp4"]);
+        // 元ファイルを保存していない場合は mirakc から読み込む
+        assert_eq!(
+            input,
+            vec![(ORIGINAL_INPUT_FILE.to_string(), b"TS".to_vec())]
+        );
+        assert_eq!(
+            *f.logs.logs.lock().unwrap(),
+            vec![("1/mp4".to_string(), b"frame=1\n".to_vec())]
+        );
+        assert_eq!(
+            f.storage.objects.lock().unwrap()["2025/01/01/1/output.mp4"].len(),
+            1024
+        );
+        assert_eq!(*f.executor.cleaned.lock().unwrap(), vec!["mp4"]);
+        assert_eq!(f.completed.events.lock().unwrap()[0].result, expected);
+        assert!(f.failed.events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_encode_inputs_from_storage() {
+        // 1段目: 元ファイルを保存済みなら mirakc ではなくストレージから読み込む
+        let f = fixture(execution(Some(0), vec![mp4(1024)]));
+        f.storage
+            .objects
+            .lock()
+            .unwrap()
+            .insert("2025/01/01/1/original".to_string(), b"STORED TS".to_vec());
+        let mut event = dispatched("1", encode_job());
+        event.inputs = vec![RecordOutput {
+            name: "original".to_string(),
+            storage: "local1".to_string(),
+            location: "2025/01/01/1/original".to_string(),
+            content_type: "video/MP2T".to_string(),
+            size: Some(9),
+            encode_status: EncodeStatus::Original,
+            url: None,
+            sha256: None,
+        }];
+        let Some(JobResult::Succeeded { outputs }) =
+            f.usecase.handle_job_dispatched(&event).await.unwrap()
+        else {
+            panic!("Expected JobResult::Succeeded");
+        };
+        assert_eq!(
+            f.executor.tasks.lock().unwrap()[0].1,
+            vec![(ORIGINAL_INPUT_FILE.to_string(), b"STORED TS".to_vec())]
+        );
+
+        // 2段目: 1段目の出力を入力にする
+        let mut event = dispatched("1", encode_job());
+        event.job.name = "thumbnail".to_string();
+        event.job.depends_on = vec!["mp4".to_string()];
+        event.job.inputs = vec!["output.mp4".to_string()];
+        event.job.outputs = vec![];
+        event.inputs = outputs;
+        assert!(matches!(
+            f.usecase.handle_job_dispatched(&event).await.unwrap(),
+            Some(JobResult::Succeeded { .. })
+        ));
+        let (task, inputs) = f.executor.tasks.lock().unwrap()[1].clone();
+        assert_eq!(task.job, "thumbnail");
+        assert_eq!(inputs, vec![("output.mp4".to_string(), vec![0u8; 1024])]);
+
+        // 依存するジョブの出力がない場合は mirakc から読み込まずに失敗にする
+        event.inputs = vec![];
+        assert_eq!(
+            f.usecase.handle_job_dispatched(&event).await.unwrap(),
+            Some(JobResult::Failed {
+                error: "入力 output.mp4 がありません".to_string()
+            })
+        );
+        assert_eq!(f.executor.tasks.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_storage() {
+        let f = fixture(execution(Some(0), vec![mp4(1024)]));
+        let mut event = dispatched("1", encode_job());
+        event.job.outputs[0].storage = "s3".to_string();
+        let result = f.usecase.handle_job_dispatched(&event).await.unwrap();
+
+        assert_eq!(
+            result,
+            Some(JobResult::Failed {
+                error: "ストレージ s3 が設定されていません".to_string()
+            })
+        );
+        assert!(f.storage.objects.lock().unwrap().is_empty());
+        // 保存に失敗しても作業ディレクトリは削除する
+        assert_eq!(*f.executor.cleaned.lock().unwrap(), vec!["mp4"]);
+        assert_eq!(f.failed.events.lock().unwrap()[0].exit_code, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_encode_failed() {
+        let f = fixture(execution(Some(1), vec![]));
+        let result = f
+            .usecase
+            .handle_job_dispatched(&dispatched("1", encode_job()))
+            .await
+            .unwrap();
+        assert!(matches!(result, Some(JobResult::Failed { .. })));
+        let failed = f.failed.events.lock().unwrap()[0].clone();
+        assert_eq!(failed.exit_code, Some(1));
+        assert!(!failed.timed_out);
+        assert_eq!(failed.log.as_deref(), Some("1/mp4"));
+        assert_eq!(
+            f.completed.events.lock().unwrap()[0].result,
+            JobResult::Failed {
+                error: "スクリプトが終了コード 1 で終了しました".to_string()
+            }
+        );
+
+        // 正常に終了しても空の出力は失敗にする
+        let f = fixture(execution(Some(0), vec![mp4(0)]));
+        f.usecase
+            .handle_job_dispatched(&dispatched("1", encode_job()))
+            .await
+            .unwrap();
+        let failed = f.failed.events.lock().unwrap()[0].clone();
+        assert_eq!(failed.exit_code, Some(0));
+        assert_eq!(failed.error, "出力 output.mp4 が空です");
+
+        // 録画レコードがない場合はスクリプトを実行しない
+        let f = fixture(execution(Some(0), vec![mp4(1024)]));
+        f.usecase
+            .handle_job_dispatched(&dispatched("2", encode_job()))
+            .await
+            .unwrap();
+        assert!(f.executor.tasks.lock().unwrap().is_empty());
+        let failed = f.failed.events.lock().unwrap()[0].clone();
+        assert_eq!(failed.exit_code, None);
+        assert_eq!(failed.log, None);
+    }
+
+    #[tokio::test]
+    async fn test_ignore_other_jobs() {
+        let f = fixture(execution(Some(0), vec![]));
+        let mut event = dispatched("1", JobKind::SaveOriginal);
+        event.job.name = SAVE_ORIGINAL_JOB.to_string();
+        assert_eq!(f.usecase.handle_job_dispatched(&event).await.unwrap(), None);
+        assert!(f.completed.events.lock().unwrap().is_empty());
+    }
+}
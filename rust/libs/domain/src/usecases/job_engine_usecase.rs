@@ -0,0 +1,356 @@
+//! ジョブエンジンユースケース
+//!
+//! 録画が完了すると、ジョブの依存関係グラフ (`JobGraph`) から録画ごとの実行状況 (`JobRun`) を作成し、
+//! 実行できるジョブを `JobDispatchedEvent` でワーカーに送ります。
+//! ジョブの完了 (`JobCompletedEvent`) を受け取るたびに実行状況を更新し、後続のジョブを送ります。
+//! イベントは1つのワーカーが順に処理することを前提にしています。
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use tracing::{debug, info, warn};
+
+use crate::events::kurec_events::{JobCompletedEvent, JobDispatchedEvent};
+use crate::events::mirakc_events::{RecordingRecordSavedEvent, RecordingStatus};
+use crate::models::job::{JobGraph, JobRun};
+use crate::ports::event_sink::EventSink;
+use crate::ports::repositories::job_run_repository::JobRunRepository;
+
+/// ジョブエンジンユースケース
+pub struct JobEngineUseCase {
+    graph: Arc<JobGraph>,
+    repository: Arc<dyn JobRunRepository>,
+    dispatcher: Arc<dyn EventSink<JobDispatchedEvent>>,
+}
+
+impl JobEngineUseCase {
+    /// 新しいJobEngineUseCaseを作成
+    pub fn new(
+        graph: Arc<JobGraph>,
+        repository: Arc<dyn JobRunRepository>,
+        dispatcher: Arc<dyn EventSink<JobDispatchedEvent>>,
+    ) -> Self {
+        Self {
+            graph,
+            repository,
+            dispatcher,
+        }
+    }
+
+    /// 録画が完了した場合に実行状況を作成し、最初に実行できるジョブを送る。
+    ///
+    /// 録画中などのイベントは無視する。すでに実行状況がある場合は、送れていないジョブだけを送る。
+    /// 送ったジョブの数を返す。
+    pub async fn handle_record_saved(
+        &self,
+        event: &RecordingRecordSavedEvent,
+        now: DateTime<Utc>,
+    ) -> Result<usize> {
+        if event.recording_status != RecordingStatus::Finished {
+            debug!(record_id = %event.record_id, status = ?event.recording_status, "録画が完了していないためジョブを作成しません");
+            return Ok(0);
+        }
+        if let Some(mut run) = self
+            .repository
+            .get_run(&event.mirakc_url, &event.record_id)
+            .await?
+        {
+            debug!(record_id = %event.record_id, "ジョブは作成済みです");
+            return self.dispatch_runnable(&mut run, now).await;
+        }
+        if self.graph.jobs().is_empty() {
+            return Ok(0);
+        }
+
+        let mut run = self
+            .graph
+            .instantiate(&event.mirakc_url, &event.record_id, now);
+        self.repository.save_run(&run).await?;
+        info!(record_id = %event.record_id, jobs = run.jobs.len(), "ジョブを作成しました");
+        self.dispatch_runnable(&mut run, now).await
+    }
+
+    /// ジョブの結果を記録し、実行できるようになったジョブを送る。送ったジョブの数を返す。
+    pub async fn handle_job_completed(
+        &self,
+        event: &JobCompletedEvent,
+        now: DateTime<Utc>,
+    ) -> Result<usize> {
+        let Some(mut run) = self
+            .repository
+            .get_run(&event.mirakc_url, &event.record_id)
+            .await?
+        else {
+            warn!(record_id = %event.record_id, job = %event.job, "ジョブの実行状況がありません");
+            return Ok(0);
+        };
+        let updated = run.complete(&event.job, event.result.clone(), now)?;
+        if updated == 0 {
+            // 前回送れなかったジョブがあれば送り直す
+            debug!(record_id = %event.record_id, job = %event.job, "ジョブの結果は記録済みです");
+            return self.dispatch_runnable(&mut run, now).await;
+        }
+        self.repository.save_run(&run).await?;
+        info!(record_id = %event.record_id, job = %event.job, result = ?event.result, "ジョブが終わりました");
+        self.dispatch_runnable(&mut run, now).await
+    }
+
+    /// 実行できるジョブを送る。
+    ///
+    /// 送る前に送ったことを保存し、送れなかったジョブとそれより後のジョブだけを待ちに戻す。
+    /// 送れたジョブは送ったままにして、2回実行されないようにする。
+    async fn dispatch_runnable(&self, run: &mut JobRun, now: DateTime<Utc>) -> Result<usize> {
+        let runnable = run.runnable();
+        if runnable.is_empty() {
+            if run.is_finished() {
+                info!(record_id = %run.record_id, "すべてのジョブが終わりました");
+            }
+            return Ok(0);
+        }
+        let previous = run.clone();
+        for name in &runnable {
+            run.mark_queued(name, now);
+        }
+        self.repository.save_run(run).await?;
+
+        for (index, name) in runnable.iter().enumerate() {
+            let job = run.job(name).expect("実行できるジョブは存在する");
+            let event = JobDispatchedEvent {
+                mirakc_url: run.mirakc_url.clone(),
+                record_id: run.record_id.clone(),
+                job: job.definition.clone(),
+                inputs: run.inputs(name),
+            };
+            if let Err(e) = self.dispatcher.publish(event).await {
+                for unsent in &runnable[index..] {
+                    let updated_at = previous.job(unsent).map_or(now, |job| job.updated_at);
+                    run.mark_pending(unsent, updated_at);
+                }
+                self.repository.save_run(run).await?;
+                return Err(e).with_context(|| format!("ジョブ {} を送れません", name));
+            }
+            debug!(record_id = %run.record_id, job = %name, "ジョブを送りました");
+        }
+        Ok(runnable.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::job::{
+        JobDefinition, JobKind, JobResult, JobStatus, JobsConfig, DELETE_RECORD_JOB,
+        SAVE_ORIGINAL_JOB,
+    };
+    use crate::models::record::{EncodeStatus, RecordOutput};
+    use async_trait::async_trait;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+
+    #[derive(Default)]
+    struct MockJobRunRepository {
+        runs: Mutex<HashMap<String, JobRun>>,
+    }
+
+    #[async_trait]
+    impl JobRunRepository for MockJobRunRepository {
+        async fn save_run(&self, run: &JobRun) -> Result<()> {
+            self.runs
+                .lock()
+                .unwrap()
+                .insert(run.record_id.clone(), run.clone());
+            Ok(())
+        }
+
+        async fn get_run(&self, _mirakc_url: &str, record_id: &str) -> Result<Option<JobRun>> {
+            Ok(self.runs.lock().unwrap().get(record_id).cloned())
+        }
+
+        async fn list_runs(&self) -> Result<Vec<JobRun>> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(Default)]
+    struct MockDispatcher {
+        events: Mutex<Vec<JobDispatchedEvent>>,
+        /// 指定した回数だけ送れたあと、送れなくなる
+        fail_after: Mutex<Option<usize>>,
+    }
+
+    #[async_trait]
+    impl EventSink<JobDispatchedEvent> for MockDispatcher {
+        async fn publish(&self, event: JobDispatchedEvent) -> Result<()> {
+            let mut fail_after = self.fail_after.lock().unwrap();
+            match fail_after.as_mut() {
+                Some(0) => anyhow::bail!("publish failed"),
+                Some(remaining) => *remaining -= 1,
+                None => {}
+            }
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    fn encode_job(name: &str) -> JobDefinition {
+        JobDefinition {
+            name: name.to_string(),
+            kind: JobKind::Encode {
+                script: format!("ffmpeg -i input.ts {}", name),
+            },
+            depends_on: vec![SAVE_ORIGINAL_JOB.to_string()],
+            inputs: vec!["original".to_string()],
+            outputs: vec![],
+        }
+    }
+
+    fn graph_with(jobs: Vec<JobDefinition>) -> Arc<JobGraph> {
+        Arc::new(
+            JobGraph::new(&JobsConfig {
+                original_storage: Some("local1".to_string()),
+                delete_record: true,
+                jobs,
+            })
+            .unwrap(),
+        )
+    }
+
+    fn graph() -> Arc<JobGraph> {
+        graph_with(vec![encode_job("mp4")])
+    }
+
+    fn saved(status: RecordingStatus) -> RecordingRecordSavedEvent {
+        RecordingRecordSavedEvent {
+            mirakc_url: MIRAKC_URL.to_string(),
+            record_id: "1".to_string(),
+            recording_status: status,
+            received_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+        }
+    }
+
+    fn completed(job: &str, result: JobResult) -> JobCompletedEvent {
+        JobCompletedEvent {
+            mirakc_url: MIRAKC_URL.to_string(),
+            record_id: "1".to_string(),
+            job: job.to_string(),
+            result,
+        }
+    }
+
+    fn dispatched(dispatcher: &MockDispatcher) -> Vec<String> {
+        dispatcher
+            .events
+            .lock()
+            .unwrap()
+            .drain(..)
+            .map(|e| e.job.name)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_job_engine() {
+        let repository = Arc::new(MockJobRunRepository::default());
+        let dispatcher = Arc::new(MockDispatcher::default());
+        let usecase = JobEngineUseCase::new(graph(), repository.clone(), dispatcher.clone());
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+
+        // 録画中は作成しない
+        let count = usecase
+            .handle_record_saved(&saved(RecordingStatus::Recording), now)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+        assert!(repository.runs.lock().unwrap().is_empty());
+
+        let saved = saved(RecordingStatus::Finished);
+        assert_eq!(usecase.handle_record_saved(&saved, now).await.unwrap(), 1);
+        assert_eq!(dispatched(&dispatcher), vec![SAVE_ORIGINAL_JOB]);
+        // 同じ録画で2回作成しない
+        assert_eq!(usecase.handle_record_saved(&saved, now).await.unwrap(), 0);
+
+        let original = RecordOutput {
+            name: "original".to_string(),
+            storage: "local1".to_string(),
+            location: "2025/01/01/1/original".to_string(),
+            content_type: "video/MP2T".to_string(),
+            size: Some(1024),
+            encode_status: EncodeStatus::Original,
+            url: None,
+            sha256: None,
+        };
+        let event = completed(
+            SAVE_ORIGINAL_JOB,
+            JobResult::Succeeded {
+                outputs: vec![original.clone()],
+            },
+        );
+        assert_eq!(usecase.handle_job_completed(&event, now).await.unwrap(), 1);
+        let events = dispatcher.events.lock().unwrap().clone();
+        assert_eq!(events[0].job.name, "mp4");
+        assert_eq!(events[0].inputs, vec![original]);
+        dispatched(&dispatcher);
+        // 重複したイベントは無視する
+        assert_eq!(usecase.handle_job_completed(&event, now).await.unwrap(), 0);
+
+        // 失敗すると削除ジョブは送らない
+        let event = completed(
+            "mp4",
+            JobResult::Failed {
+                error: "exit code 1".to_string(),
+            },
+        );
+        assert_eq!(usecase.handle_job_completed(&event, now).await.unwrap(), 0);
+        assert!(dispatched(&dispatcher).is_empty());
+        let run = repository.runs.lock().unwrap()["1"].clone();
+        assert!(run.is_finished());
+        assert_eq!(
+            run.job(DELETE_RECORD_JOB).unwrap().status,
+            JobStatus::Skipped
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_failure() {
+        let repository = Arc::new(MockJobRunRepository::default());
+        let dispatcher = Arc::new(MockDispatcher::default());
+        *dispatcher.fail_after.lock().unwrap() = Some(0);
+        let usecase = JobEngineUseCase::new(
+            graph_with(vec![encode_job("mp4"), encode_job("thumbnail")]),
+            repository.clone(),
+            dispatcher.clone(),
+        );
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+
+        let saved = saved(RecordingStatus::Finished);
+        assert!(usecase.handle_record_saved(&saved, now).await.is_err());
+        // 送れなかったジョブは待ちに戻す
+        let run = repository.runs.lock().unwrap()["1"].clone();
+        assert_eq!(
+            run.job(SAVE_ORIGINAL_JOB).unwrap().status,
+            JobStatus::Pending
+        );
+
+        // 同じイベントを再び受け取ったら送り直す
+        *dispatcher.fail_after.lock().unwrap() = None;
+        assert_eq!(usecase.handle_record_saved(&saved, now).await.unwrap(), 1);
+        assert_eq!(dispatched(&dispatcher), vec![SAVE_ORIGINAL_JOB]);
+
+        // 2つ目のジョブだけ送れなかった場合は、送れた1つ目のジョブを送ったままにする
+        *dispatcher.fail_after.lock().unwrap() = Some(1);
+        let event = completed(SAVE_ORIGINAL_JOB, JobResult::Succeeded { outputs: vec![] });
+        assert!(usecase.handle_job_completed(&event, now).await.is_err());
+        assert_eq!(dispatched(&dispatcher), vec!["mp4"]);
+        let run = repository.runs.lock().unwrap()["1"].clone();
+        assert_eq!(run.job("mp4").unwrap().status, JobStatus::Queued);
+        assert_eq!(run.job("thumbnail").unwrap().status, JobStatus::Pending);
+
+        // 再び受け取ったら送れなかったジョブだけを送る
+        *dispatcher.fail_after.lock().unwrap() = None;
+        assert_eq!(usecase.handle_job_completed(&event, now).await.unwrap(), 1);
+        assert_eq!(dispatched(&dispatcher), vec!["thumbnail"]);
+    }
+}
@@ -1,2 +1,20 @@
+pub mod encoder_usecase;
+pub mod job_engine_usecase;
 pub mod mirakc_event_usecase;
+pub mod now_playing_usecase;
+pub mod ogp_usecase;
+pub mod original_saver_usecase;
+pub mod program_index_usecase;
+pub mod record_deleter_usecase;
+pub mod record_index_usecase;
+pub mod record_library_usecase;
+pub mod rule_engine_usecase;
+pub mod rule_preview_usecase;
+pub mod schedule_conflict_usecase;
+pub mod schedule_reconciler_usecase;
+pub mod series_tracking_usecase;
+pub mod service_catalog_usecase;
+pub mod tuner_status_usecase;
 pub mod version_usecase;
+pub mod version_watch_usecase;
+pub mod xmltv_export_usecase;
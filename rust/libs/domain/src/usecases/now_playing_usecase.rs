@@ -0,0 +1,288 @@
+//! 放送中番組ユースケース
+//!
+//! mirakc の放送中番組をサービスごとに `NowPlayingRepository` に投影し、
+//! 変化があった場合に `NowPlayingChangedEvent` を発行します。
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use tracing::debug;
+
+use crate::events::kurec_events::NowPlayingChangedEvent;
+use crate::events::mirakc_events::OnairProgramChangedEvent;
+use crate::models::onair::NowPlaying;
+use crate::ports::event_sink::EventSink;
+use crate::ports::mirakc_api::MirakcOnairApi;
+use crate::ports::repositories::now_playing_repository::NowPlayingRepository;
+
+/// 放送中番組ユースケース
+pub struct NowPlayingUseCase {
+    api: Arc<dyn MirakcOnairApi>,
+    repository: Arc<dyn NowPlayingRepository>,
+    sink: Arc<dyn EventSink<NowPlayingChangedEvent>>,
+}
+
+impl NowPlayingUseCase {
+    /// 新しいNowPlayingUseCaseを作成
+    pub fn new(
+        api: Arc<dyn MirakcOnairApi>,
+        repository: Arc<dyn NowPlayingRepository>,
+        sink: Arc<dyn EventSink<NowPlayingChangedEvent>>,
+    ) -> Self {
+        Self {
+            api,
+            repository,
+            sink,
+        }
+    }
+
+    /// mirakc のすべてのサービスの放送中番組を取得して反映する。
+    ///
+    /// # Returns
+    ///
+    /// 放送中番組が変化したサービスの数
+    pub async fn sync_all(&self, mirakc_url: &str) -> Result<usize> {
+        let mut changed = 0;
+        for now_playing in self.api.get_onair_programs(mirakc_url).await? {
+            if self.apply(now_playing).await? {
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// 放送中番組変更イベントを反映する。
+    pub async fn handle_onair_program_changed(
+        &self,
+        event: &OnairProgramChangedEvent,
+    ) -> Result<()> {
+        match self
+            .api
+            .get_onair_program(&event.mirakc_url, event.service_id)
+            .await?
+        {
+            Some(now_playing) => {
+                self.apply(now_playing).await?;
+            }
+            None => {
+                debug!(
+                    service_id = event.service_id,
+                    "放送中番組の情報が存在しないため無視します"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// 放送中番組を保存し、変化があればイベントを発行する。
+    ///
+    /// # Returns
+    ///
+    /// 放送中番組が変化した場合は `true`
+    async fn apply(&self, now_playing: NowPlaying) -> Result<bool> {
+        let previous = self
+            .repository
+            .get_now_playing(&now_playing.mirakc_url, now_playing.service_id)
+            .await?;
+        let changed = previous.is_none_or(|p| !p.same_programs(&now_playing));
+
+        self.repository.save_now_playing(&now_playing).await?;
+
+        if changed {
+            debug!(
+                service_id = now_playing.service_id,
+                current = ?now_playing.current.as_ref().map(|p| p.program_id),
+                "放送中番組が変わりました"
+            );
+            self.sink
+                .publish(NowPlayingChangedEvent {
+                    mirakc_url: now_playing.mirakc_url,
+                    service_id: now_playing.service_id,
+                    current: now_playing.current,
+                    next: now_playing.next,
+                })
+                .await?;
+        }
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::onair::OnairProgram;
+    use async_trait::async_trait;
+    use chrono::{DateTime, TimeZone, Utc};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+
+    fn program(id: i64, hour: u32) -> OnairProgram {
+        OnairProgram {
+            program_id: id,
+            event_id: id,
+            name: Some(format!("番組{}", id)),
+            start_at: Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).unwrap(),
+            duration_millis: 3600000,
+        }
+    }
+
+    fn now_playing(service_id: i64, current: i64, next: i64) -> NowPlaying {
+        NowPlaying {
+            mirakc_url: MIRAKC_URL.to_string(),
+            service_id,
+            current: Some(program(current, 20)),
+            next: Some(program(next, 21)),
+            updated_at: DateTime::<Utc>::default(),
+        }
+    }
+
+    #[derive(Default)]
+    struct MockOnairApi {
+        programs: Mutex<HashMap<i64, NowPlaying>>,
+    }
+
+    impl MockOnairApi {
+        fn set(&self, now_playing: NowPlaying) {
+            self.programs
+                .lock()
+                .unwrap()
+                .insert(now_playing.service_id, now_playing);
+        }
+    }
+
+    #[async_trait]
+    impl MirakcOnairApi for MockOnairApi {
+        async fn get_onair_programs(&self, _mirakc_url: &str) -> Result<Vec<NowPlaying>> {
+            Ok(self.programs.lock().unwrap().values().cloned().collect())
+        }
+
+        async fn get_onair_program(
+            &self,
+            _mirakc_url: &str,
+            service_id: i64,
+        ) -> Result<Option<NowPlaying>> {
+            Ok(self.programs.lock().unwrap().get(&service_id).cloned())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockNowPlayingRepository {
+        items: Mutex<HashMap<(String, i64), NowPlaying>>,
+    }
+
+    #[async_trait]
+    impl NowPlayingRepository for MockNowPlayingRepository {
+        async fn save_now_playing(&self, now_playing: &NowPlaying) -> Result<()> {
+            self.items.lock().unwrap().insert(
+                (now_playing.mirakc_url.clone(), now_playing.service_id),
+                now_playing.clone(),
+            );
+            Ok(())
+        }
+
+        async fn get_now_playing(
+            &self,
+            mirakc_url: &str,
+            service_id: i64,
+        ) -> Result<Option<NowPlaying>> {
+            Ok(self
+                .items
+                .lock()
+                .unwrap()
+                .get(&(mirakc_url.to_string(), service_id))
+                .cloned())
+        }
+
+        async fn list_now_playing(&self) -> Result<Vec<NowPlaying>> {
+            Ok(self.items.lock().unwrap().values().cloned().collect())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSink {
+        events: Mutex<Vec<NowPlayingChangedEvent>>,
+    }
+
+    #[async_trait]
+    impl EventSink<NowPlayingChangedEvent> for MockSink {
+        async fn publish(&self, event: NowPlayingChangedEvent) -> Result<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    fn event(service_id: i64) -> OnairProgramChangedEvent {
+        OnairProgramChangedEvent {
+            mirakc_url: MIRAKC_URL.to_string(),
+            service_id,
+            received_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publishes_only_when_programs_change() {
+        let api = Arc::new(MockOnairApi::default());
+        let repo = Arc::new(MockNowPlayingRepository::default());
+        let sink = Arc::new(MockSink::default());
+        let usecase = NowPlayingUseCase::new(api.clone(), repo.clone(), sink.clone());
+
+        api.set(now_playing(1, 10, 11));
+        usecase
+            .handle_onair_program_changed(&event(1))
+            .await
+            .unwrap();
+        // 同じ番組の再通知ではイベントを発行しない
+        usecase
+            .handle_onair_program_changed(&event(1))
+            .await
+            .unwrap();
+        api.set(now_playing(1, 11, 12));
+        usecase
+            .handle_onair_program_changed(&event(1))
+            .await
+            .unwrap();
+
+        let events = sink.events.lock().unwrap().clone();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].current.as_ref().unwrap().program_id, 11);
+        assert_eq!(events[1].next.as_ref().unwrap().program_id, 12);
+
+        let saved = repo.get_now_playing(MIRAKC_URL, 1).await.unwrap().unwrap();
+        assert_eq!(saved.current.unwrap().program_id, 11);
+    }
+
+    #[tokio::test]
+    async fn test_sync_all_counts_changed_services() {
+        let api = Arc::new(MockOnairApi::default());
+        let repo = Arc::new(MockNowPlayingRepository::default());
+        let sink = Arc::new(MockSink::default());
+        let usecase = NowPlayingUseCase::new(api.clone(), repo.clone(), sink.clone());
+
+        api.set(now_playing(1, 10, 11));
+        api.set(now_playing(2, 20, 21));
+        repo.save_now_playing(&now_playing(1, 10, 11))
+            .await
+            .unwrap();
+
+        assert_eq!(usecase.sync_all(MIRAKC_URL).await.unwrap(), 1);
+        assert_eq!(sink.events.lock().unwrap()[0].service_id, 2);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_service_is_ignored() {
+        let api = Arc::new(MockOnairApi::default());
+        let repo = Arc::new(MockNowPlayingRepository::default());
+        let sink = Arc::new(MockSink::default());
+        let usecase = NowPlayingUseCase::new(api, repo.clone(), sink.clone());
+
+        usecase
+            .handle_onair_program_changed(&event(99))
+            .await
+            .unwrap();
+
+        assert!(sink.events.lock().unwrap().is_empty());
+        assert!(repo.list_now_playing().await.unwrap().is_empty());
+    }
+}
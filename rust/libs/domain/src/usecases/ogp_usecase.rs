@@ -0,0 +1,211 @@
+//! OGP 画像ユースケース
+//!
+//! 番組の詳細情報で見つかった URL の og:image を取得し、`OgpImageRepository` に保存します。
+//! 保存済みのエントリが期限内であれば取得し直しません。
+//! og:image がない場合や取得に失敗した場合もその結果を保存し、`negative_ttl` の間は取得し直しません。
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use tracing::{debug, warn};
+
+use crate::events::kurec_events::ProgramUrlDiscoveredEvent;
+use crate::models::ogp::{OgpCachePolicy, OgpEntry, OgpStatus};
+use crate::ports::ogp_fetcher::OgpFetcher;
+use crate::ports::repositories::ogp_image_repository::OgpImageRepository;
+
+/// URL の処理結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OgpOutcome {
+    /// 保存済みのエントリが期限内のため取得しなかった
+    Cached,
+    /// 画像を取得して保存した
+    Fetched,
+    /// 画像がなかった、または取得に失敗した
+    NotFound,
+}
+
+/// OGP 画像ユースケース
+pub struct OgpUseCase {
+    fetcher: Arc<dyn OgpFetcher>,
+    repository: Arc<dyn OgpImageRepository>,
+    policy: OgpCachePolicy,
+}
+
+impl OgpUseCase {
+    /// 新しいOgpUseCaseを作成
+    pub fn new(
+        fetcher: Arc<dyn OgpFetcher>,
+        repository: Arc<dyn OgpImageRepository>,
+        policy: OgpCachePolicy,
+    ) -> Self {
+        Self {
+            fetcher,
+            repository,
+            policy,
+        }
+    }
+
+    /// 見つかった URL の OGP 画像を取得して保存する。
+    pub async fn handle_url_discovered(
+        &self,
+        event: &ProgramUrlDiscoveredEvent,
+        now: DateTime<Utc>,
+    ) -> Result<OgpOutcome> {
+        if let Some(entry) = self.repository.get_entry(&event.url_hash).await? {
+            if entry.is_fresh(&self.policy, now) {
+                debug!(url = %event.url, "OGP 画像は取得済みです");
+                return Ok(OgpOutcome::Cached);
+            }
+        }
+
+        let mut entry = OgpEntry {
+            url: event.url.clone(),
+            url_hash: event.url_hash.clone(),
+            status: OgpStatus::NotFound,
+            image_url: None,
+            reason: None,
+            fetched_at: now,
+        };
+        let (webp, outcome) = match self.fetcher.fetch(&event.url).await {
+            Ok(Some(image)) => {
+                entry.status = OgpStatus::Found;
+                entry.image_url = Some(image.image_url);
+                (image.webp, OgpOutcome::Fetched)
+            }
+            Ok(None) => {
+                entry.reason = Some("og:image がありません".to_string());
+                (Vec::new(), OgpOutcome::NotFound)
+            }
+            Err(e) => {
+                warn!(url = %event.url, "OGP 画像を取得できません: {:#}", e);
+                entry.reason = Some(format!("{:#}", e));
+                (Vec::new(), OgpOutcome::NotFound)
+            }
+        };
+        self.repository.save(&entry, webp).await?;
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ogp::OgpImage;
+    use anyhow::bail;
+    use async_trait::async_trait;
+    use chrono::{Duration, TimeZone};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MockFetcher {
+        fetched: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl OgpFetcher for MockFetcher {
+        async fn fetch(&self, url: &str) -> Result<Option<OgpImage>> {
+            self.fetched.lock().unwrap().push(url.to_string());
+            match url {
+                "https://example.com/found" => Ok(Some(OgpImage {
+                    image_url: "https://example.com/og.png".to_string(),
+                    webp: vec![1, 2, 3],
+                })),
+                "https://example.com/none" => Ok(None),
+                _ => bail!("timed out"),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct MockRepository {
+        entries: Mutex<HashMap<String, (OgpEntry, Vec<u8>)>>,
+    }
+
+    #[async_trait]
+    impl OgpImageRepository for MockRepository {
+        async fn get_entry(&self, url_hash: &str) -> Result<Option<OgpEntry>> {
+            Ok(self
+                .entries
+                .lock()
+                .unwrap()
+                .get(url_hash)
+                .map(|(entry, _)| entry.clone()))
+        }
+
+        async fn save(&self, entry: &OgpEntry, webp: Vec<u8>) -> Result<()> {
+            self.entries
+                .lock()
+                .unwrap()
+                .insert(entry.url_hash.clone(), (entry.clone(), webp));
+            Ok(())
+        }
+
+        async fn get_image(&self, _url_hash: &str) -> Result<Option<Vec<u8>>> {
+            unimplemented!()
+        }
+    }
+
+    fn event(path: &str) -> ProgramUrlDiscoveredEvent {
+        ProgramUrlDiscoveredEvent {
+            program_id: 1,
+            url: format!("https://example.com/{}", path),
+            url_hash: path.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_url_discovered() {
+        let fetcher = Arc::new(MockFetcher::default());
+        let repository = Arc::new(MockRepository::default());
+        let usecase = OgpUseCase::new(
+            fetcher.clone(),
+            repository.clone(),
+            OgpCachePolicy::default(),
+        );
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+
+        for (path, expected) in [
+            ("found", OgpOutcome::Fetched),
+            ("none", OgpOutcome::NotFound),
+            ("error", OgpOutcome::NotFound),
+        ] {
+            let outcome = usecase.handle_url_discovered(&event(path), now).await;
+            assert_eq!(outcome.unwrap(), expected);
+        }
+        {
+            let entries = repository.entries.lock().unwrap();
+            let (found, webp) = &entries["found"];
+            assert_eq!(found.status, OgpStatus::Found);
+            assert_eq!(webp, &vec![1, 2, 3]);
+            let (error, webp) = &entries["error"];
+            assert_eq!(error.status, OgpStatus::NotFound);
+            assert_eq!(error.reason.as_deref(), Some("timed out"));
+            assert!(webp.is_empty());
+        }
+
+        // 期限内は取得し直さない
+        fetcher.fetched.lock().unwrap().clear();
+        let later = now + Duration::hours(12);
+        for path in ["found", "none", "error"] {
+            let outcome = usecase.handle_url_discovered(&event(path), later).await;
+            assert_eq!(outcome.unwrap(), OgpOutcome::Cached);
+        }
+        assert!(fetcher.fetched.lock().unwrap().is_empty());
+
+        // 画像がなかった URL は negative_ttl を過ぎたら取得し直す
+        let later = now + Duration::days(2);
+        for path in ["found", "none"] {
+            usecase
+                .handle_url_discovered(&event(path), later)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            *fetcher.fetched.lock().unwrap(),
+            vec!["https://example.com/none"]
+        );
+    }
+}
@@ -0,0 +1,708 @@
+//! 元ファイル保存ユースケース
+//!
+//! ジョブエンジンが送った元ファイル保存ジョブ (`JobDispatchedEvent`) を実行します。
+//! mirakc の録画ファイルのストリームを一時ファイルを作らずにそのままストレージへ流し、
+//! 接続が切れた場合はコピー済みの位置から Range リクエストで再開します。
+//! 再開してもほとんど進まずに切れる場合は続けて失敗したとみなし、再試行の上限で諦めます。
+//! コピーしながらサイズと SHA-256 を計算して `RecordCopyProgressEvent` で進捗を発行し、
+//! mirakc の録画情報のファイルサイズと一致すれば成功として、結果を `JobCompletedEvent` で返します。
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use bytes::Bytes;
+use futures::stream::{self, StreamExt};
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+use crate::events::kurec_events::{JobCompletedEvent, JobDispatchedEvent, RecordCopyProgressEvent};
+use crate::models::checksum::Checksum;
+use crate::models::job::{JobKind, JobResult};
+use crate::models::record::{EncodeStatus, RecordOutput, ORIGINAL_OUTPUT};
+use crate::ports::event_sink::EventSink;
+use crate::ports::mirakc_api::{MirakcRecordsApi, RecordStream};
+use crate::ports::storage::StorageRegistry;
+
+/// 元ファイル保存ワーカーが発行するイベントの送信先
+pub struct OriginalSaverSinks {
+    pub completed: Arc<dyn EventSink<JobCompletedEvent>>,
+    pub progress: Arc<dyn EventSink<RecordCopyProgressEvent>>,
+}
+
+/// 元ファイル保存の設定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OriginalSaverConfig {
+    /// 接続が切れた場合に続けて再試行する回数
+    pub max_retries: u32,
+    /// 再試行するまでの待ち時間
+    pub retry_delay: Duration,
+    /// 1回の接続でこのバイト数以上コピーできたら、続けて失敗した回数を数え直す
+    pub retry_reset_bytes: u64,
+    /// 進捗を発行する間隔
+    pub progress_interval: Duration,
+}
+
+impl Default for OriginalSaverConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            retry_delay: Duration::from_secs(5),
+            retry_reset_bytes: 16 * 1024 * 1024,
+            progress_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+/// 元ファイル保存ユースケース
+pub struct OriginalSaverUseCase {
+    records: Arc<dyn MirakcRecordsApi>,
+    storages: StorageRegistry,
+    sinks: OriginalSaverSinks,
+    config: OriginalSaverConfig,
+}
+
+impl OriginalSaverUseCase {
+    /// 新しいOriginalSaverUseCaseを作成
+    pub fn new(
+        records: Arc<dyn MirakcRecordsApi>,
+        storages: StorageRegistry,
+        sinks: OriginalSaverSinks,
+        config: OriginalSaverConfig,
+    ) -> Self {
+        Self {
+            records,
+            storages,
+            sinks,
+            config,
+        }
+    }
+
+    /// 元ファイル保存ジョブを実行し、結果を発行する。
+    ///
+    /// 元ファイル保存以外のジョブは無視して `Ok(None)` を返す。
+    /// ジョブの失敗は `JobResult::Failed` として発行し、発行に失敗した場合だけ `Err` を返す。
+    pub async fn handle_job_dispatched(
+        &self,
+        event: &JobDispatchedEvent,
+    ) -> Result<Option<JobResult>> {
+        if event.job.kind != JobKind::SaveOriginal {
+            debug!(job = %event.job.name, "元ファイル保存ジョブではないため無視します");
+            return Ok(None);
+        }
+        info!(record_id = %event.record_id, job = %event.job.name, "元ファイルの保存を開始します");
+
+        let result = match self.save(event).await {
+            Ok(outputs) => JobResult::Succeeded { outputs },
+            Err(e) => {
+                let error = format!("{:#}", e);
+                warn!(record_id = %event.record_id, job = %event.job.name, error = %error, "元ファイルを保存できません");
+                JobResult::Failed { error }
+            }
+        };
+
+        self.sinks
+            .completed
+            .publish(JobCompletedEvent {
+                mirakc_url: event.mirakc_url.clone(),
+                record_id: event.record_id.clone(),
+                job: event.job.name.clone(),
+                result: result.clone(),
+            })
+            .await?;
+        Ok(Some(result))
+    }
+
+    /// 録画ファイルをストレージにコピーし、サイズを検証する。
+    async fn save(&self, event: &JobDispatchedEvent) -> Result<Vec<RecordOutput>> {
+        let record = self
+            .records
+            .get_record(&event.mirakc_url, &event.record_id)
+            .await?
+            .ok_or_else(|| anyhow!("録画レコード {} がありません", event.record_id))?;
+        let total_bytes = record
+            .content
+            .length
+            .and_then(|length| u64::try_from(length).ok())
+            .ok_or_else(|| anyhow!("録画 {} の録画ファイルがありません", record.id))?;
+        let spec = event
+            .job
+            .outputs
+            .iter()
+            .find(|output| output.name == ORIGINAL_OUTPUT)
+            .ok_or_else(|| {
+                anyhow!(
+                    "ジョブ {} に出力 {} がありません",
+                    event.job.name,
+                    ORIGINAL_OUTPUT
+                )
+            })?;
+        let storage = self.storages.get(&spec.storage)?;
+        let location = record.output_location(&spec.name);
+
+        let copy = RecordCopy::new(
+            self.records.clone(),
+            self.sinks.progress.clone(),
+            self.config.clone(),
+            RecordCopyProgressEvent {
+                mirakc_url: event.mirakc_url.clone(),
+                record_id: event.record_id.clone(),
+                job: event.job.name.clone(),
+                storage: spec.storage.clone(),
+                location: location.clone(),
+                copied_bytes: 0,
+                total_bytes,
+                resumed: 0,
+            },
+        );
+        let checksum = copy.checksum.clone();
+        let data = stream::try_unfold(copy, RecordCopy::next).boxed();
+        let stored = storage
+            .put(&location, &spec.content_type, data)
+            .await
+            .with_context(|| format!("元ファイルをストレージ {} に保存できません", spec.storage))?;
+
+        let checksum = checksum.lock().unwrap().clone();
+        if checksum.size() != total_bytes {
+            bail!(
+                "コピーしたサイズ {} が mirakc の録画情報のサイズ {} と一致しません",
+                checksum.size(),
+                total_bytes
+            );
+        }
+        if stored.size != total_bytes {
+            bail!(
+                "ストレージ {} に保存したサイズ {} が mirakc の録画情報のサイズ {} と一致しません",
+                spec.storage,
+                stored.size,
+                total_bytes
+            );
+        }
+        info!(storage = %spec.storage, location = %location, size = stored.size, "元ファイルを保存しました");
+        Ok(vec![RecordOutput {
+            name: spec.name.clone(),
+            storage: spec.storage.clone(),
+            location,
+            content_type: spec.content_type.clone(),
+            size: record.content.length,
+            encode_status: EncodeStatus::Original,
+            url: None,
+            sha256: Some(checksum.sha256()),
+        }])
+    }
+}
+
+/// 接続が切れても再開する、録画ファイルのコピーの状態
+struct RecordCopy {
+    records: Arc<dyn MirakcRecordsApi>,
+    progress_sink: Arc<dyn EventSink<RecordCopyProgressEvent>>,
+    config: OriginalSaverConfig,
+    progress: RecordCopyProgressEvent,
+    checksum: Arc<Mutex<Checksum>>,
+    stream: Option<RecordStream>,
+    connected: bool,
+    /// 現在の接続を開始した位置
+    connected_at: u64,
+    failures: u32,
+    last_progress: Instant,
+}
+
+impl RecordCopy {
+    fn new(
+        records: Arc<dyn MirakcRecordsApi>,
+        progress_sink: Arc<dyn EventSink<RecordCopyProgressEvent>>,
+        config: OriginalSaverConfig,
+        progress: RecordCopyProgressEvent,
+    ) -> Self {
+        Self {
+            records,
+            progress_sink,
+            config,
+            progress,
+            checksum: Arc::new(Mutex::new(Checksum::default())),
+            stream: None,
+            connected: false,
+            connected_at: 0,
+            failures: 0,
+            last_progress: Instant::now(),
+        }
+    }
+
+    /// 次のチャンクを読む。ファイルサイズまで読んだら `None`。
+    async fn next(mut self) -> Result<Option<(Bytes, Self)>> {
+        loop {
+            let copied = self.checksum.lock().unwrap().size();
+            if copied == self.progress.total_bytes {
+                if self.progress.copied_bytes != copied {
+                    self.publish_progress(copied).await;
+                }
+                return Ok(None);
+            }
+
+            let Some(stream) = self.stream.as_mut() else {
+                match self
+                    .records
+                    .get_record_stream(&self.progress.mirakc_url, &self.progress.record_id, copied)
+                    .await
+                {
+                    Ok(stream) => {
+                        if self.connected {
+                            self.progress.resumed += 1;
+                            info!(record_id = %self.progress.record_id, offset = copied, "録画ファイルのコピーを再開します");
+                        }
+                        self.connected = true;
+                        self.connected_at = copied;
+                        self.stream = Some(stream);
+                    }
+                    Err(e) => self.retry(e).await?,
+                }
+                continue;
+            };
+
+            match stream.next().await {
+                Some(Ok(chunk)) => {
+                    if copied + chunk.len() as u64 > self.progress.total_bytes {
+                        bail!(
+                            "録画ファイルが mirakc の録画情報のサイズ {} より大きくなりました",
+                            self.progress.total_bytes
+                        );
+                    }
+                    self.checksum.lock().unwrap().update(&chunk);
+                    // 接続してすぐ切れることを繰り返す場合は数え直さない
+                    if copied + chunk.len() as u64 - self.connected_at
+                        >= self.config.retry_reset_bytes
+                    {
+                        self.failures = 0;
+                    }
+                    if self.last_progress.elapsed() >= self.config.progress_interval {
+                        self.publish_progress(copied + chunk.len() as u64).await;
+                    }
+                    return Ok(Some((chunk, self)));
+                }
+                Some(Err(e)) => {
+                    self.stream = None;
+                    self.retry(e).await?;
+                }
+                None => {
+                    self.stream = None;
+                    self.retry(anyhow!(
+                        "録画ファイルのストリームが {} バイトで終わりました",
+                        copied
+                    ))
+                    .await?;
+                }
+            }
+        }
+    }
+
+    /// 続けて失敗した回数が上限を超えていなければ、待ってから再試行する。
+    async fn retry(&mut self, e: anyhow::Error) -> Result<()> {
+        self.failures += 1;
+        if self.failures > self.config.max_retries {
+            return Err(e.context(format!(
+                "{} 回再試行しても録画ファイルを取得できません",
+                self.config.max_retries
+            )));
+        }
+        warn!(record_id = %self.progress.record_id, failures = self.failures, "録画ファイルを取得できません: {:#}", e);
+        tokio::time::sleep(self.config.retry_delay).await;
+        Ok(())
+    }
+
+    /// 進捗を発行する。発行に失敗してもコピーは続ける。
+    async fn publish_progress(&mut self, copied: u64) {
+        self.last_progress = Instant::now();
+        self.progress.copied_bytes = copied;
+        if let Err(e) = self.progress_sink.publish(self.progress.clone()).await {
+            warn!(record_id = %self.progress.record_id, "進捗を発行できません: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::mirakc_events::RecordingStatus;
+    use crate::models::epg::KurecProgram;
+    use crate::models::job::{JobDefinition, JobOutputSpec, SAVE_ORIGINAL_JOB};
+    use crate::models::record::{Record, RecordContent, RecordingInfo};
+    use crate::ports::storage::{ByteStream, Storage, StoredObject};
+    use async_trait::async_trait;
+    use chrono::{TimeZone, Utc};
+    use std::collections::BTreeMap;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+    const DATA: &[u8] = b"0123456789";
+
+    /// 録画ファイルを 3 バイトずつ返し、`disconnects` の位置で接続を切る (1 回ずつ) mirakc
+    struct MockRecordsApi {
+        length: Option<i64>,
+        disconnects: Mutex<Vec<u64>>,
+        offsets: Mutex<Vec<u64>>,
+    }
+
+    impl MockRecordsApi {
+        fn new(length: Option<i64>, disconnects: Vec<u64>) -> Self {
+            Self {
+                length,
+                disconnects: Mutex::new(disconnects),
+                offsets: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl MirakcRecordsApi for MockRecordsApi {
+        async fn get_records(&self, _mirakc_url: &str) -> Result<Vec<Record>> {
+            unimplemented!()
+        }
+
+        async fn get_record(&self, mirakc_url: &str, record_id: &str) -> Result<Option<Record>> {
+            if record_id != "1" {
+                return Ok(None);
+            }
+            let start_at = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+            Ok(Some(Record {
+                id: record_id.to_string(),
+                mirakc_url: mirakc_url.to_string(),
+                program: KurecProgram {
+                    id: 1,
+                    mirakc_url: mirakc_url.to_string(),
+                    service_id: 1024,
+                    network_id: 32736,
+                    event_id: 1,
+                    channel_name: "テスト".to_string(),
+                    channel_type: "GR".to_string(),
+                    channel: "27".to_string(),
+                    name: Some("番組".to_string()),
+                    description: None,
+                    extended: None,
+                    start_at,
+                    duration_millis: 1800000,
+                    is_free: true,
+                    genres: vec![],
+                    video_info: None,
+                    audio_infos: vec![],
+                    series_info: None,
+                    markers: Default::default(),
+                    episode_number: None,
+                },
+                recording: RecordingInfo {
+                    status: RecordingStatus::Finished,
+                    start_time: start_at,
+                    end_time: None,
+                    duration_millis: None,
+                    failed_reason: None,
+                },
+                content: RecordContent {
+                    path: "1.m2ts".to_string(),
+                    content_type: "video/MP2T".to_string(),
+                    length: self.length,
+                },
+                tags: vec![],
+            }))
+        }
+
+        async fn get_record_stream(
+            &self,
+            _mirakc_url: &str,
+            _record_id: &str,
+            offset: u64,
+        ) -> Result<RecordStream> {
+            self.offsets.lock().unwrap().push(offset);
+            let mut disconnects = self.disconnects.lock().unwrap();
+            let end = match disconnects.iter().position(|&at| at >= offset) {
+                Some(index) => disconnects.remove(index),
+                None => DATA.len() as u64,
+            };
+            let mut chunks: Vec<Result<Bytes>> = DATA[offset as usize..end as usize]
+                .chunks(3)
+                .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+                .collect();
+            if end < DATA.len() as u64 {
+                chunks.push(Err(anyhow!("接続が切れました")));
+            }
+            Ok(stream::iter(chunks).boxed())
+        }
+
+        async fn remove_record(&self, _mirakc_url: &str, _record_id: &str) -> Result<()> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(Default)]
+    struct MockStorage {
+        objects: Mutex<BTreeMap<String, Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl Storage for MockStorage {
+        fn name(&self) -> &str {
+            "local1"
+        }
+
+        async fn put(
+            &self,
+            location: &str,
+            content_type: &str,
+            mut data: ByteStream,
+        ) -> Result<StoredObject> {
+            let mut content = Vec::new();
+            while let Some(chunk) = data.next().await {
+                content.extend_from_slice(&chunk?);
+            }
+            let size = content.len() as u64;
+            self.objects
+                .lock()
+                .unwrap()
+                .insert(location.to_string(), content);
+            Ok(StoredObject {
+                location: location.to_string(),
+                size,
+                content_type: Some(content_type.to_string()),
+                modified_at: None,
+            })
+        }
+
+        async fn get(&self, _location: &str) -> Result<Option<ByteStream>> {
+            unimplemented!()
+        }
+
+        async fn delete(&self, _location: &str) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn list(&self, _prefix: &str) -> Result<Vec<StoredObject>> {
+            unimplemented!()
+        }
+
+        async fn stat(&self, _location: &str) -> Result<Option<StoredObject>> {
+            unimplemented!()
+        }
+    }
+
+    struct MockSink<E> {
+        events: Mutex<Vec<E>>,
+    }
+
+    impl<E> Default for MockSink<E> {
+        fn default() -> Self {
+            Self {
+                events: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl<E> EventSink<E> for MockSink<E>
+    where
+        E: serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        async fn publish(&self, event: E) -> Result<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        usecase: OriginalSaverUseCase,
+        records: Arc<MockRecordsApi>,
+        storage: Arc<MockStorage>,
+        completed: Arc<MockSink<JobCompletedEvent>>,
+        progress: Arc<MockSink<RecordCopyProgressEvent>>,
+    }
+
+    fn fixture(records: MockRecordsApi) -> Fixture {
+        let records = Arc::new(records);
+        let storage = Arc::new(MockStorage::default());
+        let completed = Arc::new(MockSink::default());
+        let progress = Arc::new(MockSink::default());
+        let usecase = OriginalSaverUseCase::new(
+            records.clone(),
+            StorageRegistry::new(vec![storage.clone()]).unwrap(),
+            OriginalSaverSinks {
+                completed: completed.clone(),
+                progress: progress.clone(),
+            },
+            OriginalSaverConfig {
+                max_retries: 2,
+                retry_delay: Duration::ZERO,
+                retry_reset_bytes: 4,
+                progress_interval: Duration::ZERO,
+            },
+        );
+        Fixture {
+            usecase,
+            records,
+            storage,
+            completed,
+            progress,
+        }
+    }
+
+    fn dispatched(kind: JobKind) -> JobDispatchedEvent {
+        JobDispatchedEvent {
+            mirakc_url: MIRAKC_URL.to_string(),
+            record_id: "1".to_string(),
+            job: JobDefinition {
+                name: SAVE_ORIGINAL_JOB.to_string(),
+                kind,
+                depends_on: vec![],
+                inputs: vec![ORIGINAL_OUTPUT.to_string()],
+                outputs: vec![JobOutputSpec {
+                    name: ORIGINAL_OUTPUT.to_string(),
+                    content_type: "video/MP2T".to_string(),
+                    storage: "local1".to_string(),
+                    description: None,
+                }],
+            },
+            inputs: vec![],
+        }
+    }
+
+    fn expected_output() -> RecordOutput {
+        RecordOutput {
+            name: "original".to_string(),
+            storage: "local1".to_string(),
+            location: "2025/01/01/1/original".to_string(),
+            content_type: "video/MP2T".to_string(),
+            size: Some(10),
+            encode_status: EncodeStatus::Original,
+            url: None,
+            sha256: Some(
+                "84d89877f0d4041efb6bf91a16f0248f2fd573e6af05c19f96bedb9f882f7882".to_string(),
+            ),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_original() {
+        let f = fixture(MockRecordsApi::new(Some(10), vec![]));
+        let result = f
+            .usecase
+            .handle_job_dispatched(&dispatched(JobKind::SaveOriginal))
+            .await
+            .unwrap();
+
+        let expected = JobResult::Succeeded {
+            outputs: vec![expected_output()],
+        };
+        assert_eq!(result, Some(expected.clone()));
+        assert_eq!(f.completed.events.lock().unwrap()[0].result, expected);
+        assert_eq!(
+            f.storage.objects.lock().unwrap()["2025/01/01/1/original"],
+            DATA
+        );
+        assert_eq!(*f.records.offsets.lock().unwrap(), vec![0]);
+        let copied: Vec<u64> = f
+            .progress
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|progress| progress.copied_bytes)
+            .collect();
+        assert_eq!(copied, vec![3, 6, 9, 10]);
+    }
+
+    #[tokio::test]
+    async fn test_resume_after_disconnect() {
+        let f = fixture(MockRecordsApi::new(Some(10), vec![4, 8]));
+        let result = f
+            .usecase
+            .handle_job_dispatched(&dispatched(JobKind::SaveOriginal))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result,
+            Some(JobResult::Succeeded {
+                outputs: vec![expected_output()],
+            })
+        );
+        // コピー済みの位置から再開する
+        assert_eq!(*f.records.offsets.lock().unwrap(), vec![0, 4, 8]);
+        assert_eq!(
+            f.storage.objects.lock().unwrap()["2025/01/01/1/original"],
+            DATA
+        );
+        let last = f.progress.events.lock().unwrap().last().cloned().unwrap();
+        assert_eq!(last.copied_bytes, 10);
+        assert_eq!(last.total_bytes, 10);
+        assert_eq!(last.resumed, 2);
+    }
+
+    #[tokio::test]
+    async fn test_too_many_disconnects() {
+        // 進まないまま続けて切れた場合は再試行の上限で失敗する
+        let f = fixture(MockRecordsApi::new(Some(10), vec![4, 4, 4]));
+        let result = f
+            .usecase
+            .handle_job_dispatched(&dispatched(JobKind::SaveOriginal))
+            .await
+            .unwrap();
+        let Some(JobResult::Failed { error }) = result else {
+            panic!("失敗していません: {:?}", result);
+        };
+        assert!(error.contains("2 回再試行しても録画ファイルを取得できません"));
+        assert_eq!(*f.records.offsets.lock().unwrap(), vec![0, 4, 4]);
+        assert!(f.storage.objects.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_flapping_stream() {
+        // 接続するたびに少しだけ進んで切れる場合も、続けて失敗したとみなす
+        let f = fixture(MockRecordsApi::new(Some(10), vec![1, 2, 3, 4, 5, 6]));
+        let result = f
+            .usecase
+            .handle_job_dispatched(&dispatched(JobKind::SaveOriginal))
+            .await
+            .unwrap();
+        let Some(JobResult::Failed { error }) = result else {
+            panic!("失敗していません: {:?}", result);
+        };
+        assert!(error.contains("2 回再試行しても録画ファイルを取得できません"));
+        assert_eq!(*f.records.offsets.lock().unwrap(), vec![0, 1, 2]);
+        assert!(f.storage.objects.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_size_mismatch() {
+        // mirakc の録画情報より大きいファイルは失敗にする
+        let f = fixture(MockRecordsApi::new(Some(8), vec![]));
+        let result = f
+            .usecase
+            .handle_job_dispatched(&dispatched(JobKind::SaveOriginal))
+            .await
+            .unwrap();
+        let Some(JobResult::Failed { error }) = result else {
+            panic!("失敗していません: {:?}", result);
+        };
+        assert!(error.contains("録画ファイルが mirakc の録画情報のサイズ 8 より大きくなりました"));
+
+        // 録画ファイルがない場合はコピーしない
+        let f = fixture(MockRecordsApi::new(None, vec![]));
+        let result = f
+            .usecase
+            .handle_job_dispatched(&dispatched(JobKind::SaveOriginal))
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            Some(JobResult::Failed {
+                error: "録画 1 の録画ファイルがありません".to_string()
+            })
+        );
+        assert!(f.records.offsets.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ignore_other_jobs() {
+        let f = fixture(MockRecordsApi::new(Some(10), vec![]));
+        let event = dispatched(JobKind::Encode {
+            script: "ffmpeg -i input.ts output.mp4".to_string(),
+        });
+        assert_eq!(f.usecase.handle_job_dispatched(&event).await.unwrap(), None);
+        assert!(f.completed.events.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,446 @@
+//! 番組検索インデックスユースケース
+//!
+//! KVS に保存された番組を `ProgramSearchIndex` に反映します。
+//! サービスごとにインデックスのドキュメントと比較し、変化した番組だけを追加・更新し、
+//! 放送が終了した番組や EPG からなくなった番組を削除します。
+//! 追加・更新した番組に OGP 画像を取得する URL があれば `ProgramUrlDiscoveredEvent` を発行します。
+//! 検索の問い合わせ (`ProgramSearchQuery`) にも応答します。
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::{debug, info, warn};
+
+use crate::events::kurec_events::{EpgStoredEvent, ProgramUrlDiscoveredEvent};
+use crate::models::epg::KurecProgram;
+use crate::models::search::{ProgramDocument, ProgramSearchQuery, ProgramSearchResult};
+use crate::ports::event_sink::EventSink;
+use crate::ports::program_search_index::ProgramSearchIndex;
+use crate::ports::query_bus::{QueryHandler, QueryRequest};
+use crate::ports::repositories::kurec_program_repository::KurecProgramRepository;
+
+/// インデックスへの反映結果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexSyncSummary {
+    /// 追加・更新したドキュメントの数
+    pub upserted: usize,
+    /// 削除したドキュメントの数
+    pub deleted: usize,
+}
+
+impl QueryRequest for ProgramSearchQuery {
+    const SUBJECT: &'static str = "kurec.query.programs.search";
+    type Response = ProgramSearchResult;
+}
+
+/// 番組検索インデックスユースケース
+pub struct ProgramIndexUseCase {
+    programs: Arc<dyn KurecProgramRepository>,
+    index: Arc<dyn ProgramSearchIndex>,
+    url_sink: Option<Arc<dyn EventSink<ProgramUrlDiscoveredEvent>>>,
+}
+
+impl ProgramIndexUseCase {
+    /// 新しいProgramIndexUseCaseを作成
+    pub fn new(
+        programs: Arc<dyn KurecProgramRepository>,
+        index: Arc<dyn ProgramSearchIndex>,
+    ) -> Self {
+        Self {
+            programs,
+            index,
+            url_sink: None,
+        }
+    }
+
+    /// OGP 画像を取得する URL の発行先を設定
+    pub fn with_url_sink(mut self, sink: Arc<dyn EventSink<ProgramUrlDiscoveredEvent>>) -> Self {
+        self.url_sink = Some(sink);
+        self
+    }
+
+    /// インデックスを作成し、設定を反映する。
+    pub async fn initialize(&self) -> Result<()> {
+        self.index.ensure_index().await
+    }
+
+    /// EPG が保存されたサービスの番組をインデックスに反映する。
+    pub async fn handle_epg_stored(
+        &self,
+        event: &EpgStoredEvent,
+        now: DateTime<Utc>,
+    ) -> Result<IndexSyncSummary> {
+        let programs = self
+            .programs
+            .get_service_programs(&event.mirakc_url, event.service_id)
+            .await?
+            .unwrap_or_default();
+        self.sync_service(event.service_id, &programs, now).await
+    }
+
+    /// 保存されているすべての番組をサービスごとにインデックスに反映する。
+    pub async fn sync_all(&self, now: DateTime<Utc>) -> Result<IndexSyncSummary> {
+        let mut services: BTreeMap<i64, Vec<KurecProgram>> = BTreeMap::new();
+        for program in self.programs.list_all_programs().await? {
+            services
+                .entry(program.service_id)
+                .or_default()
+                .push(program);
+        }
+        let mut summary = IndexSyncSummary::default();
+        for (service_id, programs) in services {
+            let result = self.sync_service(service_id, &programs, now).await?;
+            summary.upserted += result.upserted;
+            summary.deleted += result.deleted;
+        }
+        Ok(summary)
+    }
+
+    /// インデックスのドキュメントをすべて削除し、保存されているすべての番組から作り直す。
+    pub async fn rebuild(&self, now: DateTime<Utc>) -> Result<IndexSyncSummary> {
+        self.index.delete_all_documents().await?;
+        info!("番組検索インデックスを削除しました。保存されている番組から作り直します");
+        self.sync_all(now).await
+    }
+
+    /// 番組を検索する。
+    pub async fn search(&self, query: &ProgramSearchQuery) -> Result<ProgramSearchResult> {
+        self.index.search(query).await
+    }
+
+    /// サービスの番組とインデックスのドキュメントを比較して反映する。
+    async fn sync_service(
+        &self,
+        service_id: i64,
+        programs: &[KurecProgram],
+        now: DateTime<Utc>,
+    ) -> Result<IndexSyncSummary> {
+        // 複数の mirakc から同じサービスの番組を取得している場合は1つにまとめる
+        let documents: BTreeMap<i64, ProgramDocument> = programs
+            .iter()
+            .map(ProgramDocument::from_program)
+            .filter(|document| document.end_at > now)
+            .map(|document| (document.program_id, document))
+            .collect();
+        let existing = self.index.get_service_documents(service_id).await?;
+        let (changed, removed) = diff_documents(&documents, &existing);
+
+        if !changed.is_empty() {
+            self.index.upsert_documents(&changed).await?;
+            self.publish_urls(&changed).await;
+        }
+        if !removed.is_empty() {
+            self.index.delete_documents(&removed).await?;
+        }
+        debug!(
+            service_id,
+            upserted = changed.len(),
+            deleted = removed.len(),
+            "番組検索インデックスを更新しました"
+        );
+        Ok(IndexSyncSummary {
+            upserted: changed.len(),
+            deleted: removed.len(),
+        })
+    }
+
+    /// 追加・更新した番組の OGP 画像を取得する URL を発行する。
+    ///
+    /// 発行に失敗してもインデックスへの反映は続ける。
+    async fn publish_urls(&self, documents: &[ProgramDocument]) {
+        let Some(sink) = &self.url_sink else {
+            return;
+        };
+        let mut published = HashSet::new();
+        for document in documents {
+            let (Some(url), Some(url_hash)) = (&document.ogp_url, &document.ogp_url_hash) else {
+                continue;
+            };
+            if !published.insert(url_hash.clone()) {
+                continue;
+            }
+            let event = ProgramUrlDiscoveredEvent {
+                program_id: document.program_id,
+                url: url.clone(),
+                url_hash: url_hash.clone(),
+            };
+            if let Err(e) = sink.publish(event).await {
+                warn!(url = %url, "ProgramUrlDiscoveredEvent を発行できません: {:?}", e);
+            }
+        }
+    }
+}
+
+/// 反映するドキュメントとインデックスのドキュメントを比較し、
+/// 追加・更新するドキュメントと削除する番組IDを返す。
+pub(crate) fn diff_documents(
+    documents: &BTreeMap<i64, ProgramDocument>,
+    existing: &[ProgramDocument],
+) -> (Vec<ProgramDocument>, Vec<i64>) {
+    let existing_ids: HashSet<i64> = existing.iter().map(|d| d.program_id).collect();
+    let changed = documents
+        .values()
+        .filter(|document| !existing.contains(document))
+        .cloned()
+        .collect();
+    let removed = existing_ids
+        .into_iter()
+        .filter(|id| !documents.contains_key(id))
+        .collect();
+    (changed, removed)
+}
+
+#[async_trait]
+impl QueryHandler<ProgramSearchQuery> for ProgramIndexUseCase {
+    async fn handle(&self, query: ProgramSearchQuery) -> Result<ProgramSearchResult> {
+        self.search(&query).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::sync::Mutex;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+
+    struct MockKurecProgramRepository {
+        programs: Mutex<Vec<KurecProgram>>,
+    }
+
+    #[async_trait]
+    impl KurecProgramRepository for MockKurecProgramRepository {
+        async fn save_service_programs(
+            &self,
+            _mirakc_url: &str,
+            _service_id: i64,
+            _programs: Vec<KurecProgram>,
+        ) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn get_service_programs(
+            &self,
+            _mirakc_url: &str,
+            service_id: i64,
+        ) -> Result<Option<Vec<KurecProgram>>> {
+            Ok(Some(
+                self.programs
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|p| p.service_id == service_id)
+                    .cloned()
+                    .collect(),
+            ))
+        }
+
+        async fn list_all_programs(&self) -> Result<Vec<KurecProgram>> {
+            Ok(self.programs.lock().unwrap().clone())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSearchIndex {
+        documents: Mutex<BTreeMap<i64, ProgramDocument>>,
+        upserts: Mutex<Vec<i64>>,
+    }
+
+    #[async_trait]
+    impl ProgramSearchIndex for MockSearchIndex {
+        async fn ensure_index(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn get_service_documents(&self, service_id: i64) -> Result<Vec<ProgramDocument>> {
+            Ok(self
+                .documents
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.service_id == service_id)
+                .cloned()
+                .collect())
+        }
+
+        async fn upsert_documents(&self, documents: &[ProgramDocument]) -> Result<()> {
+            let mut stored = self.documents.lock().unwrap();
+            for document in documents {
+                self.upserts.lock().unwrap().push(document.program_id);
+                stored.insert(document.program_id, document.clone());
+            }
+            Ok(())
+        }
+
+        async fn delete_documents(&self, program_ids: &[i64]) -> Result<()> {
+            let mut stored = self.documents.lock().unwrap();
+            for id in program_ids {
+                stored.remove(id);
+            }
+            Ok(())
+        }
+
+        async fn delete_all_documents(&self) -> Result<()> {
+            self.documents.lock().unwrap().clear();
+            Ok(())
+        }
+
+        async fn search(&self, _query: &ProgramSearchQuery) -> Result<ProgramSearchResult> {
+            unimplemented!()
+        }
+    }
+
+    fn program(id: i64, service_id: i64, hour: u32) -> KurecProgram {
+        KurecProgram {
+            id,
+            mirakc_url: MIRAKC_URL.to_string(),
+            service_id,
+            network_id: 32736,
+            event_id: id,
+            channel_name: "テスト".to_string(),
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+            name: Some(format!("番組{}", id)),
+            description: None,
+            extended: None,
+            start_at: Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).unwrap(),
+            duration_millis: 3600000,
+            is_free: true,
+            genres: vec![],
+            video_info: None,
+            audio_infos: vec![],
+            series_info: None,
+            markers: Default::default(),
+            episode_number: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_programs() {
+        let repository = Arc::new(MockKurecProgramRepository {
+            programs: Mutex::new(vec![
+                program(1, 1024, 10),
+                program(2, 1024, 12),
+                program(3, 1024, 13),
+                program(4, 1025, 12),
+            ]),
+        });
+        let index = Arc::new(MockSearchIndex::default());
+        let usecase = ProgramIndexUseCase::new(repository.clone(), index.clone());
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 11, 30, 0).unwrap();
+
+        // 放送が終了した番組1は登録しない
+        let summary = usecase.sync_all(now).await.unwrap();
+        assert_eq!(
+            summary,
+            IndexSyncSummary {
+                upserted: 3,
+                deleted: 0
+            }
+        );
+        assert_eq!(
+            index.documents.lock().unwrap().keys().collect::<Vec<_>>(),
+            vec![&2, &3, &4]
+        );
+
+        // 番組2が終了し、番組3の番組名が変わり、番組5が追加された
+        {
+            let mut programs = repository.programs.lock().unwrap();
+            programs[2].name = Some("番組3 (変更)".to_string());
+            programs.push(program(5, 1024, 14));
+        }
+        index.upserts.lock().unwrap().clear();
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 13, 0, 0).unwrap();
+        let event = EpgStoredEvent {
+            mirakc_url: MIRAKC_URL.to_string(),
+            service_id: 1024,
+        };
+        let summary = usecase.handle_epg_stored(&event, now).await.unwrap();
+        assert_eq!(
+            summary,
+            IndexSyncSummary {
+                upserted: 2,
+                deleted: 1
+            }
+        );
+        assert_eq!(*index.upserts.lock().unwrap(), vec![3, 5]);
+        let documents = index.documents.lock().unwrap();
+        assert_eq!(documents.keys().collect::<Vec<_>>(), vec![&3, &4, &5]);
+        assert_eq!(documents[&3].title, "番組3 (変更)");
+    }
+
+    #[derive(Default)]
+    struct MockUrlSink {
+        events: Mutex<Vec<ProgramUrlDiscoveredEvent>>,
+    }
+
+    #[async_trait]
+    impl EventSink<ProgramUrlDiscoveredEvent> for MockUrlSink {
+        async fn publish(&self, event: ProgramUrlDiscoveredEvent) -> Result<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_urls() {
+        let with_url = |id: i64, url: &str| {
+            let mut program = program(id, 1024, 12);
+            program.extended = Some(serde_json::json!({ "番組内容": url }));
+            program
+        };
+        let repository = Arc::new(MockKurecProgramRepository {
+            programs: Mutex::new(vec![
+                with_url(1, "https://example.com/a"),
+                with_url(2, "https://example.com/a"),
+                with_url(3, "https://x.com/kurec"),
+                program(4, 1024, 12),
+            ]),
+        });
+        let sink = Arc::new(MockUrlSink::default());
+        let usecase = ProgramIndexUseCase::new(repository, Arc::new(MockSearchIndex::default()))
+            .with_url_sink(sink.clone());
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 11, 0, 0).unwrap();
+
+        usecase.sync_all(now).await.unwrap();
+        // 同じ URL は1回だけ、SNS の URL は発行しない
+        let events = sink.events.lock().unwrap().clone();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].program_id, 1);
+        assert_eq!(events[0].url, "https://example.com/a");
+        assert_eq!(
+            events[0].url_hash,
+            crate::models::search::url_hash("https://example.com/a")
+        );
+
+        // 変化のない番組では発行しない
+        sink.events.lock().unwrap().clear();
+        usecase.sync_all(now).await.unwrap();
+        assert!(sink.events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rebuild() {
+        let repository = Arc::new(MockKurecProgramRepository {
+            programs: Mutex::new(vec![program(2, 1024, 12)]),
+        });
+        let index = Arc::new(MockSearchIndex::default());
+        // KV にない古いサービスのドキュメントも削除される
+        let mut stale = ProgramDocument::from_program(&program(9, 2048, 12));
+        stale.title = "古い番組".to_string();
+        index.documents.lock().unwrap().insert(9, stale);
+        let usecase = ProgramIndexUseCase::new(repository, index.clone());
+
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 11, 0, 0).unwrap();
+        let summary = usecase.rebuild(now).await.unwrap();
+        assert_eq!(summary.upserted, 1);
+        assert_eq!(
+            index.documents.lock().unwrap().keys().collect::<Vec<_>>(),
+            vec![&2]
+        );
+    }
+}
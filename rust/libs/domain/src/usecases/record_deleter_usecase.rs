@@ -0,0 +1,783 @@
+//! mirakc の録画の削除ユースケース
+//!
+//! ジョブエンジンは他のすべてのジョブが成功した録画に削除ジョブ (`delete-record`) を送ります。
+//! このユースケースはジョブの実行状況を定期的に確認し、削除ジョブを送ってから猶予期間が過ぎた録画について、
+//! 保存したすべての出力がストレージに存在し、読み直したサイズと SHA-256 が記録と一致することを検証してから
+//! mirakc の録画を録画ファイルと合わせて削除します。
+//! 削除を要求する前に `RecordDeletionRepository` に監査記録を保存し、削除が完了したら記録を更新して
+//! 結果を `JobCompletedEvent` で返します。削除の途中で止まった場合は、次の確認で記録から削除をやり直します。
+//! dry-run では検証と記録だけを行い、削除ジョブは送られたままにします。
+//! 同じ録画を同時に削除しないよう、1つのワーカーで実行することを前提にしています。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use tracing::{debug, info, warn};
+
+use crate::events::kurec_events::JobCompletedEvent;
+use crate::events::mirakc_events::RecordingStatus;
+use crate::models::checksum::Checksum;
+use crate::models::deletion::{RecordDeletion, VerifiedCopy};
+use crate::models::job::{JobResult, JobRun, JobStatus, DELETE_RECORD_JOB};
+use crate::models::record::{RecordOutput, MIRAKC_STORAGE, ORIGINAL_OUTPUT};
+use crate::ports::event_sink::EventSink;
+use crate::ports::mirakc_api::MirakcRecordsApi;
+use crate::ports::repositories::job_run_repository::JobRunRepository;
+use crate::ports::repositories::record_deletion_repository::RecordDeletionRepository;
+use crate::ports::storage::StorageRegistry;
+
+/// mirakc の録画の削除の設定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordDeleterConfig {
+    /// すべてのジョブが成功してから削除するまでの猶予期間
+    pub grace_period: Duration,
+    /// 検証と記録だけを行い、削除しないかどうか
+    pub dry_run: bool,
+}
+
+/// 1回の確認の結果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordDeleterReport {
+    /// 削除した (dry-run では削除できることを確認した) 録画の Record ID
+    pub deleted: Vec<String>,
+    /// 検証や削除に失敗した録画の Record ID
+    pub failed: Vec<String>,
+    /// 猶予期間が過ぎていない録画の数
+    pub waiting: usize,
+}
+
+/// mirakc の録画の削除ユースケース
+pub struct RecordDeleterUseCase {
+    runs: Arc<dyn JobRunRepository>,
+    records: Arc<dyn MirakcRecordsApi>,
+    storages: StorageRegistry,
+    deletions: Arc<dyn RecordDeletionRepository>,
+    completed: Arc<dyn EventSink<JobCompletedEvent>>,
+    config: RecordDeleterConfig,
+}
+
+impl RecordDeleterUseCase {
+    /// 新しいRecordDeleterUseCaseを作成
+    pub fn new(
+        runs: Arc<dyn JobRunRepository>,
+        records: Arc<dyn MirakcRecordsApi>,
+        storages: StorageRegistry,
+        deletions: Arc<dyn RecordDeletionRepository>,
+        completed: Arc<dyn EventSink<JobCompletedEvent>>,
+        config: RecordDeleterConfig,
+    ) -> Self {
+        Self {
+            runs,
+            records,
+            storages,
+            deletions,
+            completed,
+            config,
+        }
+    }
+
+    /// 猶予期間が過ぎた削除ジョブを実行する。
+    ///
+    /// 検証や削除に失敗した場合は `JobResult::Failed` を発行し、録画は削除しない (dry-run では発行しない)。
+    /// 実行状況や記録の取得・保存、結果の発行に失敗した場合は `Err` を返す。
+    pub async fn delete_due_records(&self, now: DateTime<Utc>) -> Result<RecordDeleterReport> {
+        let grace_period =
+            chrono::Duration::from_std(self.config.grace_period).unwrap_or(chrono::Duration::MAX);
+        let mut report = RecordDeleterReport::default();
+        for run in self.runs.list_runs().await? {
+            let Some(job) = run.job(DELETE_RECORD_JOB) else {
+                continue;
+            };
+            if job.status != JobStatus::Queued {
+                continue;
+            }
+            if now < job.updated_at + grace_period {
+                report.waiting += 1;
+                continue;
+            }
+
+            if let Some(deletion) = self
+                .deletions
+                .get_deletion(&run.mirakc_url, &run.record_id)
+                .await?
+            {
+                if deletion.pending {
+                    // 削除を要求した後に止まった
+                    if self.config.dry_run {
+                        continue;
+                    }
+                    info!(record_id = %run.record_id, "削除の途中で止まった録画の削除をやり直します");
+                    match self.remove(&run).await {
+                        Ok(()) => {
+                            self.complete(&run, deletion).await?;
+                            report.deleted.push(run.record_id.clone());
+                        }
+                        Err(e) => self.fail(&run, e, &mut report).await?,
+                    }
+                    continue;
+                }
+                if !deletion.dry_run {
+                    // 削除した後に結果を発行できなかった
+                    debug!(record_id = %run.record_id, "録画は削除済みです");
+                    self.publish(&run, JobResult::Succeeded { outputs: vec![] })
+                        .await?;
+                    continue;
+                }
+                if self.config.dry_run {
+                    continue;
+                }
+            }
+
+            let deletion = match self.verify_copies(&run, job.updated_at, now).await {
+                Ok(deletion) => deletion,
+                Err(e) => {
+                    self.fail(&run, e, &mut report).await?;
+                    continue;
+                }
+            };
+            // 削除した後に記録を保存できずに監査記録が失われないよう、削除する前に保存する
+            self.deletions.save_deletion(&deletion).await?;
+            if deletion.dry_run {
+                info!(record_id = %run.record_id, reason = %deletion.reason, "dry-run のため録画を削除しません");
+                report.deleted.push(run.record_id.clone());
+                continue;
+            }
+            match self.remove(&run).await {
+                Ok(()) => {
+                    self.complete(&run, deletion).await?;
+                    report.deleted.push(run.record_id.clone());
+                }
+                Err(e) => self.fail(&run, e, &mut report).await?,
+            }
+        }
+        Ok(report)
+    }
+
+    /// 記録を保存した録画を mirakc から削除する。
+    ///
+    /// 録画がすでにない場合は、前回の削除が完了したものとして扱う。
+    async fn remove(&self, run: &JobRun) -> Result<()> {
+        if self
+            .records
+            .get_record(&run.mirakc_url, &run.record_id)
+            .await?
+            .is_some()
+        {
+            self.records
+                .remove_record(&run.mirakc_url, &run.record_id)
+                .await?;
+        } else {
+            debug!(record_id = %run.record_id, "録画は削除済みです");
+        }
+        Ok(())
+    }
+
+    /// 削除の記録を完了にして結果を発行する。
+    async fn complete(&self, run: &JobRun, mut deletion: RecordDeletion) -> Result<()> {
+        deletion.pending = false;
+        self.deletions.save_deletion(&deletion).await?;
+        info!(record_id = %run.record_id, reason = %deletion.reason, "録画を削除しました");
+        self.publish(run, JobResult::Succeeded { outputs: vec![] })
+            .await
+    }
+
+    /// 削除できなかったことを記録し、結果を発行する (dry-run では発行しない)。
+    async fn fail(
+        &self,
+        run: &JobRun,
+        e: anyhow::Error,
+        report: &mut RecordDeleterReport,
+    ) -> Result<()> {
+        let error = format!("{:#}", e);
+        warn!(record_id = %run.record_id, error = %error, "録画を削除できません");
+        report.failed.push(run.record_id.clone());
+        if !self.config.dry_run {
+            self.publish(run, JobResult::Failed { error }).await?;
+        }
+        Ok(())
+    }
+
+    /// 保存したコピーを検証し、削除の記録を作成する。
+    async fn verify_copies(
+        &self,
+        run: &JobRun,
+        jobs_succeeded_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<RecordDeletion> {
+        if let Some(job) = run.jobs.iter().find(|job| {
+            job.definition.name != DELETE_RECORD_JOB && job.status != JobStatus::Succeeded
+        }) {
+            bail!("ジョブ {} が成功していません", job.definition.name);
+        }
+        let outputs: Vec<&RecordOutput> = run.jobs.iter().flat_map(|job| &job.outputs).collect();
+        let original = outputs
+            .iter()
+            .find(|output| output.name == ORIGINAL_OUTPUT && output.storage != MIRAKC_STORAGE)
+            .ok_or_else(|| anyhow!("元ファイルがストレージに保存されていません"))?;
+
+        let record = self
+            .records
+            .get_record(&run.mirakc_url, &run.record_id)
+            .await?
+            .ok_or_else(|| anyhow!("録画レコード {} がありません", run.record_id))?;
+        if record.recording.status != RecordingStatus::Finished {
+            bail!("録画が完了していません ({:?})", record.recording.status);
+        }
+        let content_length = record
+            .content
+            .length
+            .and_then(|length| u64::try_from(length).ok())
+            .ok_or_else(|| anyhow!("録画 {} の録画ファイルがありません", record.id))?;
+        if original.size != record.content.length {
+            bail!(
+                "保存した元ファイルのサイズ {:?} が mirakc の録画ファイルのサイズ {} と一致しません",
+                original.size,
+                content_length
+            );
+        }
+
+        let mut copies = Vec::with_capacity(outputs.len());
+        for output in outputs {
+            copies.push(self.verify(output).await.with_context(|| {
+                format!(
+                    "ストレージ {} の出力 {} を検証できません",
+                    output.storage, output.name
+                )
+            })?);
+        }
+
+        let jobs: Vec<&str> = run
+            .jobs
+            .iter()
+            .map(|job| job.definition.name.as_str())
+            .filter(|name| *name != DELETE_RECORD_JOB)
+            .collect();
+        Ok(RecordDeletion {
+            mirakc_url: run.mirakc_url.clone(),
+            record_id: run.record_id.clone(),
+            program_name: record.program.name.clone(),
+            recording_start_time: record.recording.start_time,
+            content_path: record.content.path.clone(),
+            content_length,
+            jobs_succeeded_at,
+            deleted_at: now,
+            dry_run: self.config.dry_run,
+            pending: !self.config.dry_run,
+            reason: format!(
+                "すべてのジョブ ({}) が成功し、猶予期間 {:?} が過ぎ、保存した {} 個の出力のサイズと SHA-256 を検証しました",
+                jobs.join(", "),
+                self.config.grace_period,
+                copies.len()
+            ),
+            copies,
+        })
+    }
+
+    /// 出力がストレージに存在し、読み直したサイズと SHA-256 が記録と一致することを検証する。
+    async fn verify(&self, output: &RecordOutput) -> Result<VerifiedCopy> {
+        let size = output
+            .size
+            .and_then(|size| u64::try_from(size).ok())
+            .ok_or_else(|| anyhow!("サイズが記録されていません"))?;
+        let sha256 = output
+            .sha256
+            .as_deref()
+            .ok_or_else(|| anyhow!("SHA-256 が記録されていません"))?;
+        let storage = self.storages.get(&output.storage)?;
+        let stored = storage
+            .stat(&output.location)
+            .await?
+            .ok_or_else(|| anyhow!("{} がありません", output.location))?;
+        if stored.size != size {
+            bail!(
+                "{} のサイズ {} が記録 {} と一致しません",
+                output.location,
+                stored.size,
+                size
+            );
+        }
+        let data = storage
+            .get(&output.location)
+            .await?
+            .ok_or_else(|| anyhow!("{} がありません", output.location))?;
+        let checksum = Checksum::of_stream(data).await?;
+        if checksum.size() != size || checksum.sha256() != sha256 {
+            bail!(
+                "{} の内容が記録と一致しません (サイズ {}、SHA-256 {})",
+                output.location,
+                checksum.size(),
+                checksum.sha256()
+            );
+        }
+        Ok(VerifiedCopy {
+            name: output.name.clone(),
+            storage: output.storage.clone(),
+            location: output.location.clone(),
+            size,
+            sha256: checksum.sha256(),
+        })
+    }
+
+    async fn publish(&self, run: &JobRun, result: JobResult) -> Result<()> {
+        self.completed
+            .publish(JobCompletedEvent {
+                mirakc_url: run.mirakc_url.clone(),
+                record_id: run.record_id.clone(),
+                job: DELETE_RECORD_JOB.to_string(),
+                result,
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::epg::KurecProgram;
+    use crate::models::job::{
+        JobDefinition, JobGraph, JobKind, JobOutputSpec, JobsConfig, SAVE_ORIGINAL_JOB,
+    };
+    use crate::models::record::{EncodeStatus, Record, RecordContent, RecordingInfo};
+    use crate::ports::mirakc_api::RecordStream;
+    use crate::ports::storage::{ByteStream, Storage, StoredObject};
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use chrono::TimeZone;
+    use futures::stream::{self, StreamExt};
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+    const ORIGINAL: &[u8] = b"0123456789";
+    const MP4: &[u8] = b"MP4";
+
+    struct MockJobRunRepository {
+        runs: Vec<JobRun>,
+    }
+
+    #[async_trait]
+    impl JobRunRepository for MockJobRunRepository {
+        async fn save_run(&self, _run: &JobRun) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn get_run(&self, _mirakc_url: &str, _record_id: &str) -> Result<Option<JobRun>> {
+            unimplemented!()
+        }
+
+        async fn list_runs(&self) -> Result<Vec<JobRun>> {
+            Ok(self.runs.clone())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockRecordsApi {
+        removed: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl MirakcRecordsApi for MockRecordsApi {
+        async fn get_records(&self, _mirakc_url: &str) -> Result<Vec<Record>> {
+            unimplemented!()
+        }
+
+        async fn get_record(&self, mirakc_url: &str, record_id: &str) -> Result<Option<Record>> {
+            if self
+                .removed
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|id| id == record_id)
+            {
+                return Ok(None);
+            }
+            let start_at = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+            Ok(Some(Record {
+                id: record_id.to_string(),
+                mirakc_url: mirakc_url.to_string(),
+                program: KurecProgram {
+                    id: 1,
+                    mirakc_url: mirakc_url.to_string(),
+                    service_id: 1024,
+                    network_id: 32736,
+                    event_id: 1,
+                    channel_name: "テスト".to_string(),
+                    channel_type: "GR".to_string(),
+                    channel: "27".to_string(),
+                    name: Some("番組".to_string()),
+                    description: None,
+                    extended: None,
+                    start_at,
+                    duration_millis: 1800000,
+                    is_free: true,
+                    genres: vec![],
+                    video_info: None,
+                    audio_infos: vec![],
+                    series_info: None,
+                    markers: Default::default(),
+                    episode_number: None,
+                },
+                recording: RecordingInfo {
+                    status: RecordingStatus::Finished,
+                    start_time: start_at,
+                    end_time: None,
+                    duration_millis: None,
+                    failed_reason: None,
+                },
+                content: RecordContent {
+                    path: format!("{}.m2ts", record_id),
+                    content_type: "video/MP2T".to_string(),
+                    length: Some(ORIGINAL.len() as i64),
+                },
+                tags: vec![],
+            }))
+        }
+
+        async fn get_record_stream(
+            &self,
+            _mirakc_url: &str,
+            _record_id: &str,
+            _offset: u64,
+        ) -> Result<RecordStream> {
+            unimplemented!()
+        }
+
+        async fn remove_record(&self, _mirakc_url: &str, record_id: &str) -> Result<()> {
+            self.removed.lock().unwrap().push(record_id.to_string());
+            Ok(())
+        }
+    }
+
+    struct MockStorage {
+        objects: Mutex<BTreeMap<String, Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl Storage for MockStorage {
+        fn name(&self) -> &str {
+            "local1"
+        }
+
+        async fn put(
+            &self,
+            _location: &str,
+            _content_type: &str,
+            _data: ByteStream,
+        ) -> Result<StoredObject> {
+            unimplemented!()
+        }
+
+        async fn get(&self, location: &str) -> Result<Option<ByteStream>> {
+            Ok(self
+                .objects
+                .lock()
+                .unwrap()
+                .get(location)
+                .map(|data| stream::iter(vec![Ok(Bytes::from(data.clone()))]).boxed()))
+        }
+
+        async fn delete(&self, _location: &str) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn list(&self, _prefix: &str) -> Result<Vec<StoredObject>> {
+            unimplemented!()
+        }
+
+        async fn stat(&self, location: &str) -> Result<Option<StoredObject>> {
+            Ok(self
+                .objects
+                .lock()
+                .unwrap()
+                .get(location)
+                .map(|data| StoredObject {
+                    location: location.to_string(),
+                    size: data.len() as u64,
+                    content_type: None,
+                    modified_at: None,
+                }))
+        }
+    }
+
+    #[derive(Default)]
+    struct MockDeletions {
+        deletions: Mutex<Vec<RecordDeletion>>,
+        /// 指定した回数だけ保存できたあと、1回だけ保存に失敗する
+        fail_after: Mutex<Option<usize>>,
+    }
+
+    #[async_trait]
+    impl RecordDeletionRepository for MockDeletions {
+        async fn save_deletion(&self, deletion: &RecordDeletion) -> Result<()> {
+            let mut fail_after = self.fail_after.lock().unwrap();
+            match fail_after.as_mut() {
+                Some(0) => {
+                    *fail_after = None;
+                    anyhow::bail!("save failed");
+                }
+                Some(remaining) => *remaining -= 1,
+                None => {}
+            }
+            let mut deletions = self.deletions.lock().unwrap();
+            deletions.retain(|d| d.record_id != deletion.record_id);
+            deletions.push(deletion.clone());
+            Ok(())
+        }
+
+        async fn get_deletion(
+            &self,
+            _mirakc_url: &str,
+            record_id: &str,
+        ) -> Result<Option<RecordDeletion>> {
+            Ok(self
+                .deletions
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|d| d.record_id == record_id)
+                .cloned())
+        }
+
+        async fn list_deletions(&self) -> Result<Vec<RecordDeletion>> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSink {
+        events: Mutex<Vec<JobCompletedEvent>>,
+    }
+
+    #[async_trait]
+    impl EventSink<JobCompletedEvent> for MockSink {
+        async fn publish(&self, event: JobCompletedEvent) -> Result<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        usecase: RecordDeleterUseCase,
+        records: Arc<MockRecordsApi>,
+        storage: Arc<MockStorage>,
+        deletions: Arc<MockDeletions>,
+        completed: Arc<MockSink>,
+    }
+
+    fn queued_at() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, 13, 0, 0).unwrap()
+    }
+
+    fn output(name: &str, data: &[u8], encode_status: EncodeStatus) -> RecordOutput {
+        let mut checksum = Checksum::default();
+        checksum.update(data);
+        RecordOutput {
+            name: name.to_string(),
+            storage: "local1".to_string(),
+            location: format!("2025/01/01/1/{}", name),
+            content_type: "video/MP2T".to_string(),
+            size: Some(data.len() as i64),
+            encode_status,
+            url: None,
+            sha256: Some(checksum.sha256()),
+        }
+    }
+
+    /// 元ファイル保存とエンコードが成功し、削除ジョブを送った実行状況
+    fn run() -> JobRun {
+        let graph = JobGraph::new(&JobsConfig {
+            original_storage: Some("local1".to_string()),
+            delete_record: true,
+            jobs: vec![JobDefinition {
+                name: "mp4".to_string(),
+                kind: JobKind::Encode {
+                    script: "ffmpeg -i input.ts output.mp4".to_string(),
+                },
+                depends_on: vec![SAVE_ORIGINAL_JOB.to_string()],
+                inputs: vec!["original".to_string()],
+                outputs: vec![JobOutputSpec {
+                    name: "output.mp4".to_string(),
+                    content_type: "video/mp4".to_string(),
+                    storage: "local1".to_string(),
+                    description: None,
+                }],
+            }],
+        })
+        .unwrap();
+        let mut run = graph.instantiate(MIRAKC_URL, "1", queued_at());
+        let original = output("original", ORIGINAL, EncodeStatus::Original);
+        let mp4 = output("output.mp4", MP4, EncodeStatus::Encoded);
+        for (job, outputs) in [(SAVE_ORIGINAL_JOB, vec![original]), ("mp4", vec![mp4])] {
+            run.complete(job, JobResult::Succeeded { outputs }, queued_at())
+                .unwrap();
+        }
+        run.mark_queued(DELETE_RECORD_JOB, queued_at());
+        run
+    }
+
+    fn fixture(dry_run: bool) -> Fixture {
+        let records = Arc::new(MockRecordsApi::default());
+        let storage = Arc::new(MockStorage {
+            objects: Mutex::new(BTreeMap::from([
+                ("2025/01/01/1/original".to_string(), ORIGINAL.to_vec()),
+                ("2025/01/01/1/output.mp4".to_string(), MP4.to_vec()),
+            ])),
+        });
+        let deletions = Arc::new(MockDeletions::default());
+        let completed = Arc::new(MockSink::default());
+        let usecase = RecordDeleterUseCase::new(
+            Arc::new(MockJobRunRepository { runs: vec![run()] }),
+            records.clone(),
+            StorageRegistry::new(vec![storage.clone()]).unwrap(),
+            deletions.clone(),
+            completed.clone(),
+            RecordDeleterConfig {
+                grace_period: Duration::from_secs(60 * 60),
+                dry_run,
+            },
+        );
+        Fixture {
+            usecase,
+            records,
+            storage,
+            deletions,
+            completed,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_after_grace_period() {
+        let f = fixture(false);
+
+        // 猶予期間が過ぎるまでは削除しない
+        let report = f
+            .usecase
+            .delete_due_records(queued_at() + chrono::Duration::minutes(59))
+            .await
+            .unwrap();
+        assert_eq!(report.waiting, 1);
+        assert!(f.records.removed.lock().unwrap().is_empty());
+
+        let now = queued_at() + chrono::Duration::hours(1);
+        let report = f.usecase.delete_due_records(now).await.unwrap();
+        assert_eq!(report.deleted, vec!["1"]);
+        assert_eq!(*f.records.removed.lock().unwrap(), vec!["1"]);
+        let deletion = f.deletions.deletions.lock().unwrap()[0].clone();
+        assert!(!deletion.dry_run);
+        assert!(!deletion.pending);
+        assert_eq!(deletion.content_path, "1.m2ts");
+        assert_eq!(deletion.content_length, 10);
+        assert_eq!(deletion.jobs_succeeded_at, queued_at());
+        assert_eq!(deletion.deleted_at, now);
+        assert_eq!(deletion.program_name.as_deref(), Some("番組"));
+        let names: Vec<&str> = deletion.copies.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["original", "output.mp4"]);
+        assert!(deletion.reason.contains("save-original, mp4"));
+        assert_eq!(
+            f.completed.events.lock().unwrap()[0].result,
+            JobResult::Succeeded { outputs: vec![] }
+        );
+
+        // 結果がまだ反映されていなければ、削除し直さずに結果を発行し直す
+        let report = f.usecase.delete_due_records(now).await.unwrap();
+        assert!(report.deleted.is_empty());
+        assert_eq!(f.records.removed.lock().unwrap().len(), 1);
+        assert_eq!(f.completed.events.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_save_deletion_failed() {
+        let now = queued_at() + chrono::Duration::hours(2);
+
+        // 削除する前の記録を保存できなければ削除しない
+        let f = fixture(false);
+        *f.deletions.fail_after.lock().unwrap() = Some(0);
+        assert!(f.usecase.delete_due_records(now).await.is_err());
+        assert!(f.records.removed.lock().unwrap().is_empty());
+        assert!(f.completed.events.lock().unwrap().is_empty());
+        let report = f.usecase.delete_due_records(now).await.unwrap();
+        assert_eq!(report.deleted, vec!["1"]);
+        assert_eq!(*f.records.removed.lock().unwrap(), vec!["1"]);
+
+        // 削除した後に記録を完了にできなくても、監査記録は残っていて次の確認で完了にする
+        let f = fixture(false);
+        *f.deletions.fail_after.lock().unwrap() = Some(1);
+        assert!(f.usecase.delete_due_records(now).await.is_err());
+        assert_eq!(*f.records.removed.lock().unwrap(), vec!["1"]);
+        let deletion = f.deletions.deletions.lock().unwrap()[0].clone();
+        assert!(deletion.pending);
+        assert_eq!(deletion.copies.len(), 2);
+        assert!(f.completed.events.lock().unwrap().is_empty());
+
+        let report = f.usecase.delete_due_records(now).await.unwrap();
+        assert_eq!(report.deleted, vec!["1"]);
+        assert!(report.failed.is_empty());
+        // 録画はすでにないため削除し直さない
+        assert_eq!(f.records.removed.lock().unwrap().len(), 1);
+        assert!(!f.deletions.deletions.lock().unwrap()[0].pending);
+        assert_eq!(
+            f.completed.events.lock().unwrap()[0].result,
+            JobResult::Succeeded { outputs: vec![] }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dry_run() {
+        let f = fixture(true);
+        let now = queued_at() + chrono::Duration::hours(2);
+        let report = f.usecase.delete_due_records(now).await.unwrap();
+        assert_eq!(report.deleted, vec!["1"]);
+        assert!(f.records.removed.lock().unwrap().is_empty());
+        assert!(f.deletions.deletions.lock().unwrap()[0].dry_run);
+        // 削除ジョブは送られたままにする
+        assert!(f.completed.events.lock().unwrap().is_empty());
+
+        // 記録済みの録画は検証し直さない
+        let report = f.usecase.delete_due_records(now).await.unwrap();
+        assert!(report.deleted.is_empty());
+        assert_eq!(f.deletions.deletions.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_verification_failed() {
+        let now = queued_at() + chrono::Duration::hours(2);
+
+        // サイズが同じでも内容が異なる場合は削除しない
+        let f = fixture(false);
+        f.storage
+            .objects
+            .lock()
+            .unwrap()
+            .insert("2025/01/01/1/output.mp4".to_string(), b"MP5".to_vec());
+        let report = f.usecase.delete_due_records(now).await.unwrap();
+        assert_eq!(report.failed, vec!["1"]);
+        assert!(f.records.removed.lock().unwrap().is_empty());
+        assert!(f.deletions.deletions.lock().unwrap().is_empty());
+        let JobResult::Failed { error } = f.completed.events.lock().unwrap()[0].result.clone()
+        else {
+            panic!("失敗していません");
+        };
+        assert!(error.contains("ストレージ local1 の出力 output.mp4 を検証できません"));
+        assert!(error.contains("内容が記録と一致しません"));
+
+        // コピーがない場合も削除しない
+        let f = fixture(false);
+        f.storage
+            .objects
+            .lock()
+            .unwrap()
+            .remove("2025/01/01/1/original");
+        let report = f.usecase.delete_due_records(now).await.unwrap();
+        assert_eq!(report.failed, vec!["1"]);
+        assert!(f.records.removed.lock().unwrap().is_empty());
+
+        // dry-run では結果を発行しない
+        let f = fixture(true);
+        f.storage.objects.lock().unwrap().clear();
+        let report = f.usecase.delete_due_records(now).await.unwrap();
+        assert_eq!(report.failed, vec!["1"]);
+        assert!(f.completed.events.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,332 @@
+//! 録画ライブラリの検索インデックスユースケース
+//!
+//! `RecordRepository` に保存された録画レコードを、番組検索とは別の `ProgramSearchIndex` に反映します。
+//! 放送が終わって番組検索から消えた番組も、録画していれば録画ライブラリから検索できます。
+//! ドキュメントは録画時点の番組情報に録画の情報と出力を加えたもので、主キーは番組IDです。
+//! 同じ番組を複数回録画した場合は、録画開始が最も新しいレコードを登録します。
+//! 録画ライブラリの検索の問い合わせ (`RecordSearchQuery`) にも応答します。
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use tracing::{debug, info};
+
+use crate::models::search::{ProgramDocument, ProgramSearchResult, RecordSearchQuery};
+use crate::ports::program_search_index::ProgramSearchIndex;
+use crate::ports::query_bus::{QueryHandler, QueryRequest};
+use crate::ports::repositories::record_repository::RecordRepository;
+use crate::usecases::program_index_usecase::{diff_documents, IndexSyncSummary};
+
+impl QueryRequest for RecordSearchQuery {
+    const SUBJECT: &'static str = "kurec.query.records.search";
+    type Response = ProgramSearchResult;
+}
+
+/// 録画ライブラリの検索インデックスユースケース
+pub struct RecordIndexUseCase {
+    records: Arc<dyn RecordRepository>,
+    index: Arc<dyn ProgramSearchIndex>,
+    /// 前回の反映でドキュメントがあったサービス (録画がすべて削除されたサービスを反映するため)
+    indexed_services: Mutex<HashSet<i64>>,
+}
+
+impl RecordIndexUseCase {
+    /// 新しいRecordIndexUseCaseを作成
+    pub fn new(records: Arc<dyn RecordRepository>, index: Arc<dyn ProgramSearchIndex>) -> Self {
+        Self {
+            records,
+            index,
+            indexed_services: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// インデックスを作成し、設定を反映する。
+    pub async fn initialize(&self) -> Result<()> {
+        self.index.ensure_index().await
+    }
+
+    /// 保存されているすべての録画レコードをサービスごとにインデックスに反映する。
+    ///
+    /// 起動前に録画がすべて削除されたサービスのドキュメントは残るため、`rebuild` で作り直す。
+    pub async fn sync_all(&self) -> Result<IndexSyncSummary> {
+        let mut services: BTreeMap<i64, BTreeMap<i64, ProgramDocument>> = BTreeMap::new();
+        let mut records = self.records.list_records().await?;
+        // 録画開始の古い順に登録し、同じ番組は新しいレコードで上書きする
+        records.sort_by_key(|record| record.recording.start_time);
+        for record in &records {
+            let document = ProgramDocument::from_record(record);
+            services
+                .entry(document.service_id)
+                .or_default()
+                .insert(document.program_id, document);
+        }
+
+        let previous = self.lock_indexed_services()?.clone();
+        let service_ids: BTreeSet<i64> = services.keys().chain(previous.iter()).copied().collect();
+        let mut summary = IndexSyncSummary::default();
+        for service_id in service_ids {
+            let documents = services.remove(&service_id).unwrap_or_default();
+            let existing = self.index.get_service_documents(service_id).await?;
+            let (changed, removed) = diff_documents(&documents, &existing);
+            if !changed.is_empty() {
+                self.index.upsert_documents(&changed).await?;
+            }
+            if !removed.is_empty() {
+                self.index.delete_documents(&removed).await?;
+            }
+            debug!(
+                service_id,
+                upserted = changed.len(),
+                deleted = removed.len(),
+                "録画ライブラリの検索インデックスを更新しました"
+            );
+            summary.upserted += changed.len();
+            summary.deleted += removed.len();
+        }
+
+        *self.lock_indexed_services()? = records
+            .iter()
+            .map(|record| record.program.service_id)
+            .collect();
+        Ok(summary)
+    }
+
+    /// インデックスのドキュメントをすべて削除し、保存されているすべての録画レコードから作り直す。
+    pub async fn rebuild(&self) -> Result<IndexSyncSummary> {
+        self.index.delete_all_documents().await?;
+        self.lock_indexed_services()?.clear();
+        info!("録画ライブラリの検索インデックスを削除しました。保存されている録画レコードから作り直します");
+        self.sync_all().await
+    }
+
+    /// 録画ライブラリを検索する。
+    pub async fn search(&self, query: &RecordSearchQuery) -> Result<ProgramSearchResult> {
+        self.index.search(&query.0).await
+    }
+
+    fn lock_indexed_services(&self) -> Result<std::sync::MutexGuard<'_, HashSet<i64>>> {
+        self.indexed_services
+            .lock()
+            .map_err(|_| anyhow!("反映済みのサービスのロックを取得できません"))
+    }
+}
+
+#[async_trait]
+impl QueryHandler<RecordSearchQuery> for RecordIndexUseCase {
+    async fn handle(&self, query: RecordSearchQuery) -> Result<ProgramSearchResult> {
+        self.search(&query).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::mirakc_events::RecordingStatus;
+    use crate::models::epg::KurecProgram;
+    use crate::models::record::{Record, RecordContent, RecordingInfo};
+    use crate::models::search::ProgramSearchQuery;
+    use chrono::{Duration, TimeZone, Utc};
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+
+    #[derive(Default)]
+    struct MockRecordRepository {
+        records: Mutex<Vec<Record>>,
+    }
+
+    #[async_trait]
+    impl RecordRepository for MockRecordRepository {
+        async fn save_record(&self, _record: &Record) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn get_record(&self, _mirakc_url: &str, _record_id: &str) -> Result<Option<Record>> {
+            unimplemented!()
+        }
+
+        async fn list_records(&self) -> Result<Vec<Record>> {
+            Ok(self.records.lock().unwrap().clone())
+        }
+
+        async fn remove_record(&self, _mirakc_url: &str, _record_id: &str) -> Result<()> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSearchIndex {
+        documents: Mutex<BTreeMap<i64, ProgramDocument>>,
+        queries: Mutex<Vec<ProgramSearchQuery>>,
+    }
+
+    #[async_trait]
+    impl ProgramSearchIndex for MockSearchIndex {
+        async fn ensure_index(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn get_service_documents(&self, service_id: i64) -> Result<Vec<ProgramDocument>> {
+            Ok(self
+                .documents
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.service_id == service_id)
+                .cloned()
+                .collect())
+        }
+
+        async fn upsert_documents(&self, documents: &[ProgramDocument]) -> Result<()> {
+            let mut stored = self.documents.lock().unwrap();
+            for document in documents {
+                stored.insert(document.program_id, document.clone());
+            }
+            Ok(())
+        }
+
+        async fn delete_documents(&self, program_ids: &[i64]) -> Result<()> {
+            let mut stored = self.documents.lock().unwrap();
+            for id in program_ids {
+                stored.remove(id);
+            }
+            Ok(())
+        }
+
+        async fn delete_all_documents(&self) -> Result<()> {
+            self.documents.lock().unwrap().clear();
+            Ok(())
+        }
+
+        async fn search(&self, query: &ProgramSearchQuery) -> Result<ProgramSearchResult> {
+            self.queries.lock().unwrap().push(query.clone());
+            Ok(ProgramSearchResult::default())
+        }
+    }
+
+    fn record(id: &str, program_id: i64, service_id: i64, day: u32) -> Record {
+        let start_at = Utc.with_ymd_and_hms(2025, 1, day, 12, 0, 0).unwrap();
+        Record {
+            id: id.to_string(),
+            mirakc_url: MIRAKC_URL.to_string(),
+            program: KurecProgram {
+                id: program_id,
+                mirakc_url: MIRAKC_URL.to_string(),
+                service_id,
+                network_id: 32736,
+                event_id: program_id,
+                channel_name: "テスト".to_string(),
+                channel_type: "GR".to_string(),
+                channel: "27".to_string(),
+                name: Some(format!("番組{}", program_id)),
+                description: None,
+                extended: None,
+                start_at,
+                duration_millis: 1800000,
+                is_free: true,
+                genres: vec![],
+                video_info: None,
+                audio_infos: vec![],
+                series_info: None,
+                markers: Default::default(),
+                episode_number: None,
+            },
+            recording: RecordingInfo {
+                status: RecordingStatus::Finished,
+                start_time: start_at,
+                end_time: Some(start_at + Duration::minutes(30)),
+                duration_millis: Some(1800000),
+                failed_reason: None,
+            },
+            content: RecordContent {
+                path: format!("{}.m2ts", id),
+                content_type: "video/MP2T".to_string(),
+                length: Some(1024),
+            },
+            tags: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_records() {
+        let repository = Arc::new(MockRecordRepository::default());
+        *repository.records.lock().unwrap() = vec![
+            record("b", 1, 1024, 8),
+            record("a", 1, 1024, 1),
+            record("c", 2, 1024, 1),
+            record("d", 3, 1025, 1),
+        ];
+        let index = Arc::new(MockSearchIndex::default());
+        let usecase = RecordIndexUseCase::new(repository.clone(), index.clone());
+
+        let summary = usecase.sync_all().await.unwrap();
+        assert_eq!(
+            summary,
+            IndexSyncSummary {
+                upserted: 3,
+                deleted: 0
+            }
+        );
+        {
+            let documents = index.documents.lock().unwrap();
+            assert_eq!(documents.keys().collect::<Vec<_>>(), vec![&1, &2, &3]);
+            // 同じ番組は録画開始が新しいレコードを登録する
+            let record = documents[&1].record.as_ref().unwrap();
+            assert_eq!(record.record_id, "b");
+            assert_eq!(
+                record.outputs[0].url.as_deref(),
+                Some("http://tuner:40772/api/recording/records/b/stream")
+            );
+        }
+
+        // 番組2の録画ファイルが削除され、サービス 1025 の録画がすべて削除された
+        {
+            let mut records = repository.records.lock().unwrap();
+            records[2].content.length = None;
+            records.pop();
+        }
+        let summary = usecase.sync_all().await.unwrap();
+        assert_eq!(
+            summary,
+            IndexSyncSummary {
+                upserted: 1,
+                deleted: 1
+            }
+        );
+        let documents = index.documents.lock().unwrap();
+        assert_eq!(documents.keys().collect::<Vec<_>>(), vec![&1, &2]);
+        let record = documents[&2].record.as_ref().unwrap();
+        assert!(record.outputs.is_empty());
+        assert!(record.storages.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_and_search() {
+        let repository = Arc::new(MockRecordRepository::default());
+        *repository.records.lock().unwrap() = vec![record("a", 1, 1024, 1)];
+        let index = Arc::new(MockSearchIndex::default());
+        // 起動前に録画が削除されたサービスのドキュメントも削除される
+        index
+            .documents
+            .lock()
+            .unwrap()
+            .insert(9, ProgramDocument::from_record(&record("z", 9, 2048, 1)));
+        let usecase = RecordIndexUseCase::new(repository, index.clone());
+
+        let summary = usecase.rebuild().await.unwrap();
+        assert_eq!(summary.upserted, 1);
+        assert_eq!(
+            index.documents.lock().unwrap().keys().collect::<Vec<_>>(),
+            vec![&1]
+        );
+
+        let query = RecordSearchQuery(ProgramSearchQuery {
+            text: "番組".to_string(),
+            storages: vec!["mirakc".to_string()],
+            ..Default::default()
+        });
+        usecase.handle(query.clone()).await.unwrap();
+        assert_eq!(*index.queries.lock().unwrap(), vec![query.0]);
+    }
+}
@@ -0,0 +1,350 @@
+//! 録画ライブラリユースケース
+//!
+//! mirakc の録画レコードを `RecordRepository` に投影し、最新の状態に保ちます。
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::Result;
+use tracing::{debug, info};
+
+use crate::events::mirakc_events::{
+    RecordingContentRemovedEvent, RecordingRecordRemovedEvent, RecordingRecordSavedEvent,
+};
+use crate::ports::mirakc_api::MirakcRecordsApi;
+use crate::ports::repositories::record_repository::RecordRepository;
+
+/// 全件同期の結果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordSyncSummary {
+    /// 保存 (追加・更新) したレコード数
+    pub saved: usize,
+    /// mirakc 側に存在しないため削除したレコード数
+    pub removed: usize,
+}
+
+/// 録画ライブラリユースケース
+pub struct RecordLibraryUseCase {
+    api: Arc<dyn MirakcRecordsApi>,
+    repository: Arc<dyn RecordRepository>,
+}
+
+impl RecordLibraryUseCase {
+    /// 新しいRecordLibraryUseCaseを作成
+    pub fn new(api: Arc<dyn MirakcRecordsApi>, repository: Arc<dyn RecordRepository>) -> Self {
+        Self { api, repository }
+    }
+
+    /// mirakc の録画レコードを全件取得し、リポジトリと同期する。
+    ///
+    /// mirakc 側に存在しないレコードはリポジトリから削除する。
+    pub async fn sync_all(&self, mirakc_url: &str) -> Result<RecordSyncSummary> {
+        let records = self.api.get_records(mirakc_url).await?;
+        let ids: HashSet<String> = records.iter().map(|r| r.id.clone()).collect();
+
+        let mut summary = RecordSyncSummary::default();
+        for record in &records {
+            self.repository.save_record(record).await?;
+            summary.saved += 1;
+        }
+
+        for stale in self
+            .repository
+            .list_records()
+            .await?
+            .into_iter()
+            .filter(|r| r.mirakc_url == mirakc_url && !ids.contains(&r.id))
+        {
+            self.repository
+                .remove_record(&stale.mirakc_url, &stale.id)
+                .await?;
+            summary.removed += 1;
+        }
+
+        info!(
+            mirakc_url,
+            saved = summary.saved,
+            removed = summary.removed,
+            "録画ライブラリを同期しました"
+        );
+        Ok(summary)
+    }
+
+    /// 録画レコード保存イベントを反映する。
+    pub async fn handle_record_saved(&self, event: &RecordingRecordSavedEvent) -> Result<()> {
+        self.refresh_record(&event.mirakc_url, &event.record_id)
+            .await
+    }
+
+    /// 録画レコード削除イベントを反映する。
+    pub async fn handle_record_removed(&self, event: &RecordingRecordRemovedEvent) -> Result<()> {
+        debug!(record_id = %event.record_id, "録画レコードを削除します");
+        self.repository
+            .remove_record(&event.mirakc_url, &event.record_id)
+            .await
+    }
+
+    /// 録画ファイル削除イベントを反映する。
+    ///
+    /// レコード自体は mirakc に残るため、最新の状態を取得し直す。
+    pub async fn handle_content_removed(&self, event: &RecordingContentRemovedEvent) -> Result<()> {
+        self.refresh_record(&event.mirakc_url, &event.record_id)
+            .await
+    }
+
+    /// mirakc から録画レコードを取得し直してリポジトリに反映する。
+    async fn refresh_record(&self, mirakc_url: &str, record_id: &str) -> Result<()> {
+        match self.api.get_record(mirakc_url, record_id).await? {
+            Some(record) => {
+                debug!(record_id, "録画レコードを保存します");
+                self.repository.save_record(&record).await
+            }
+            None => {
+                // イベント受信後に削除された場合
+                debug!(record_id, "mirakc に録画レコードが存在しないため削除します");
+                self.repository.remove_record(mirakc_url, record_id).await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::mirakc_events::RecordingStatus;
+    use crate::models::epg::KurecProgram;
+    use crate::models::record::{Record, RecordContent, RecordingInfo};
+    use crate::ports::mirakc_api::RecordStream;
+    use async_trait::async_trait;
+    use chrono::{TimeZone, Utc};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+
+    fn record(mirakc_url: &str, id: &str, length: Option<i64>) -> Record {
+        let start_at = Utc.timestamp_millis_opt(1700000000000).unwrap();
+        Record {
+            id: id.to_string(),
+            mirakc_url: mirakc_url.to_string(),
+            program: KurecProgram {
+                id: 1,
+                mirakc_url: mirakc_url.to_string(),
+                service_id: 1,
+                network_id: 1,
+                event_id: 1,
+                channel_name: "テスト".to_string(),
+                channel_type: "GR".to_string(),
+                channel: "27".to_string(),
+                name: Some(format!("番組{}", id)),
+                description: None,
+                extended: None,
+                start_at,
+                duration_millis: 1800000,
+                is_free: true,
+                genres: vec![],
+                video_info: None,
+                audio_infos: vec![],
+                series_info: None,
+                markers: Default::default(),
+                episode_number: None,
+            },
+            recording: RecordingInfo {
+                status: RecordingStatus::Finished,
+                start_time: start_at,
+                end_time: None,
+                duration_millis: None,
+                failed_reason: None,
+            },
+            content: RecordContent {
+                path: format!("{}.m2ts", id),
+                content_type: "video/MP2T".to_string(),
+                length,
+            },
+            tags: vec![],
+        }
+    }
+
+    #[derive(Default)]
+    struct MockRecordsApi {
+        records: Mutex<Vec<Record>>,
+    }
+
+    #[async_trait]
+    impl MirakcRecordsApi for MockRecordsApi {
+        async fn get_records(&self, mirakc_url: &str) -> Result<Vec<Record>> {
+            Ok(self
+                .records
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|r| r.mirakc_url == mirakc_url)
+                .cloned()
+                .collect())
+        }
+
+        async fn get_record(&self, mirakc_url: &str, record_id: &str) -> Result<Option<Record>> {
+            Ok(self
+                .records
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|r| r.mirakc_url == mirakc_url && r.id == record_id)
+                .cloned())
+        }
+
+        async fn get_record_stream(
+            &self,
+            _mirakc_url: &str,
+            _record_id: &str,
+            _offset: u64,
+        ) -> Result<RecordStream> {
+            unimplemented!()
+        }
+
+        async fn remove_record(&self, _mirakc_url: &str, _record_id: &str) -> Result<()> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(Default)]
+    struct MockRecordRepository {
+        records: Mutex<HashMap<(String, String), Record>>,
+    }
+
+    #[async_trait]
+    impl RecordRepository for MockRecordRepository {
+        async fn save_record(&self, record: &Record) -> Result<()> {
+            self.records.lock().unwrap().insert(
+                (record.mirakc_url.clone(), record.id.clone()),
+                record.clone(),
+            );
+            Ok(())
+        }
+
+        async fn get_record(&self, mirakc_url: &str, record_id: &str) -> Result<Option<Record>> {
+            Ok(self
+                .records
+                .lock()
+                .unwrap()
+                .get(&(mirakc_url.to_string(), record_id.to_string()))
+                .cloned())
+        }
+
+        async fn list_records(&self) -> Result<Vec<Record>> {
+            Ok(self.records.lock().unwrap().values().cloned().collect())
+        }
+
+        async fn remove_record(&self, mirakc_url: &str, record_id: &str) -> Result<()> {
+            self.records
+                .lock()
+                .unwrap()
+                .remove(&(mirakc_url.to_string(), record_id.to_string()));
+            Ok(())
+        }
+    }
+
+    fn setup() -> (
+        Arc<MockRecordsApi>,
+        Arc<MockRecordRepository>,
+        RecordLibraryUseCase,
+    ) {
+        let api = Arc::new(MockRecordsApi::default());
+        let repo = Arc::new(MockRecordRepository::default());
+        let usecase = RecordLibraryUseCase::new(api.clone(), repo.clone());
+        (api, repo, usecase)
+    }
+
+    #[tokio::test]
+    async fn test_sync_all_saves_and_removes_stale_records() {
+        let (api, repo, usecase) = setup();
+        *api.records.lock().unwrap() = vec![
+            record(MIRAKC_URL, "1", Some(100)),
+            record(MIRAKC_URL, "2", Some(200)),
+        ];
+        // mirakc 側で既に削除されたレコード
+        repo.save_record(&record(MIRAKC_URL, "old", Some(1)))
+            .await
+            .unwrap();
+        // 別の mirakc のレコードは削除されない
+        repo.save_record(&record("http://other:40772", "x", Some(1)))
+            .await
+            .unwrap();
+
+        let summary = usecase.sync_all(MIRAKC_URL).await.unwrap();
+
+        assert_eq!(
+            summary,
+            RecordSyncSummary {
+                saved: 2,
+                removed: 1
+            }
+        );
+        assert!(repo.get_record(MIRAKC_URL, "old").await.unwrap().is_none());
+        assert!(repo.get_record(MIRAKC_URL, "1").await.unwrap().is_some());
+        assert!(repo
+            .get_record("http://other:40772", "x")
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_handle_record_saved_fetches_latest_record() {
+        let (api, repo, usecase) = setup();
+        api.records
+            .lock()
+            .unwrap()
+            .push(record(MIRAKC_URL, "1", Some(100)));
+
+        let event = RecordingRecordSavedEvent {
+            mirakc_url: MIRAKC_URL.to_string(),
+            record_id: "1".to_string(),
+            recording_status: RecordingStatus::Finished,
+            received_at: Utc::now(),
+        };
+        usecase.handle_record_saved(&event).await.unwrap();
+
+        let saved = repo.get_record(MIRAKC_URL, "1").await.unwrap().unwrap();
+        assert_eq!(saved.content.length, Some(100));
+    }
+
+    #[tokio::test]
+    async fn test_handle_record_removed() {
+        let (_api, repo, usecase) = setup();
+        repo.save_record(&record(MIRAKC_URL, "1", Some(100)))
+            .await
+            .unwrap();
+
+        let event = RecordingRecordRemovedEvent {
+            mirakc_url: MIRAKC_URL.to_string(),
+            record_id: "1".to_string(),
+            received_at: Utc::now(),
+        };
+        usecase.handle_record_removed(&event).await.unwrap();
+
+        assert!(repo.get_record(MIRAKC_URL, "1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_handle_content_removed_updates_record() {
+        let (api, repo, usecase) = setup();
+        repo.save_record(&record(MIRAKC_URL, "1", Some(100)))
+            .await
+            .unwrap();
+        api.records
+            .lock()
+            .unwrap()
+            .push(record(MIRAKC_URL, "1", None));
+
+        let event = RecordingContentRemovedEvent {
+            mirakc_url: MIRAKC_URL.to_string(),
+            record_id: "1".to_string(),
+            received_at: Utc::now(),
+        };
+        usecase.handle_content_removed(&event).await.unwrap();
+
+        let saved = repo.get_record(MIRAKC_URL, "1").await.unwrap().unwrap();
+        assert!(!saved.has_content());
+    }
+}
@@ -0,0 +1,546 @@
+//! 自動録画ルールエンジンユースケース
+//!
+//! 保存済みの番組 (`KurecProgram`) を自動録画ルールで評価し、
+//! 録画されるべき番組を `DesiredScheduleRepository` に反映します。
+//! 新たにルールに一致した番組は、一致した理由とともに `RecordingRuleMatchedEvent` として発行します。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use tracing::{debug, info, warn};
+
+use crate::events::kurec_events::{EpgStoredEvent, RecordingRuleMatchedEvent};
+use crate::models::epg::KurecProgram;
+use crate::models::rule::RecordingRule;
+use crate::models::schedule::DesiredSchedule;
+use crate::ports::event_sink::EventSink;
+use crate::ports::repositories::desired_schedule_repository::DesiredScheduleRepository;
+use crate::ports::repositories::kurec_program_repository::KurecProgramRepository;
+use crate::ports::repositories::recording_rule_repository::RecordingRuleRepository;
+
+/// ルールの適用結果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleApplySummary {
+    /// いずれかのルールに一致した番組の数
+    pub matched: usize,
+    /// 新たに追加した録画予約の数
+    pub added: usize,
+    /// 内容を更新した録画予約の数
+    pub updated: usize,
+    /// 削除した (またはルールの一致を外した) 録画予約の数
+    pub removed: usize,
+}
+
+/// 自動録画ルールエンジンユースケース
+pub struct RuleEngineUseCase {
+    rules: Arc<dyn RecordingRuleRepository>,
+    programs: Arc<dyn KurecProgramRepository>,
+    schedules: Arc<dyn DesiredScheduleRepository>,
+    sink: Arc<dyn EventSink<RecordingRuleMatchedEvent>>,
+}
+
+impl RuleEngineUseCase {
+    /// 新しいRuleEngineUseCaseを作成
+    pub fn new(
+        rules: Arc<dyn RecordingRuleRepository>,
+        programs: Arc<dyn KurecProgramRepository>,
+        schedules: Arc<dyn DesiredScheduleRepository>,
+        sink: Arc<dyn EventSink<RecordingRuleMatchedEvent>>,
+    ) -> Self {
+        Self {
+            rules,
+            programs,
+            schedules,
+            sink,
+        }
+    }
+
+    /// 評価に使用するルール (有効で、検証に通るもの) を取得する。
+    pub async fn active_rules(&self) -> Result<Vec<RecordingRule>> {
+        Ok(self
+            .rules
+            .list_rules()
+            .await?
+            .into_iter()
+            .filter(|rule| match rule.validate() {
+                Ok(()) => rule.enabled,
+                Err(e) => {
+                    warn!(rule_id = %rule.id, "不正なルールを無視します: {:?}", e);
+                    false
+                }
+            })
+            .collect())
+    }
+
+    /// EPG が保存されたサービスの番組をルールで評価する。
+    pub async fn handle_epg_stored(
+        &self,
+        event: &EpgStoredEvent,
+        now: DateTime<Utc>,
+    ) -> Result<RuleApplySummary> {
+        let programs = self
+            .programs
+            .get_service_programs(&event.mirakc_url, event.service_id)
+            .await?
+            .unwrap_or_default();
+        self.apply(programs, now, |s| {
+            s.mirakc_url == event.mirakc_url && s.service_id == event.service_id
+        })
+        .await
+    }
+
+    /// 保存されているすべての番組をルールで評価する。ルールが変わった場合に使用する。
+    pub async fn apply_all(&self, now: DateTime<Utc>) -> Result<RuleApplySummary> {
+        let programs = self.programs.list_all_programs().await?;
+        self.apply(programs, now, |_| true).await
+    }
+
+    /// 番組をルールで評価し、`in_scope` の範囲の録画予約を評価結果に合わせる。
+    ///
+    /// 終了した番組の録画予約と、EPG から消えた番組のルールによる録画予約は削除する。
+    /// 手動やシリーズ録画による録画予約はルールに一致しなくなっても残す。
+    async fn apply(
+        &self,
+        programs: Vec<KurecProgram>,
+        now: DateTime<Utc>,
+        in_scope: impl Fn(&DesiredSchedule) -> bool,
+    ) -> Result<RuleApplySummary> {
+        let rules = self.active_rules().await?;
+        let mut summary = RuleApplySummary::default();
+
+        let mut existing = HashMap::new();
+        for schedule in self.schedules.list_schedules().await? {
+            if !in_scope(&schedule) {
+                continue;
+            }
+            if schedule.end_at() <= now {
+                self.schedules
+                    .delete_schedule(&schedule.mirakc_url, schedule.program_id)
+                    .await?;
+                continue;
+            }
+            existing.insert((schedule.mirakc_url.clone(), schedule.program_id), schedule);
+        }
+
+        for program in programs {
+            let mut desired = DesiredSchedule::from_program(&program);
+            if desired.end_at() <= now {
+                continue;
+            }
+            desired.rule_matches = rules
+                .iter()
+                .filter_map(|rule| rule.match_program(&program))
+                .collect();
+            let previous = existing.remove(&(program.mirakc_url.clone(), program.id));
+            if let Some(p) = &previous {
+                desired.manual = p.manual;
+                desired.series = p.series.clone();
+            }
+
+            if !desired.rule_matches.is_empty() {
+                summary.matched += 1;
+            }
+            match &previous {
+                Some(p) if *p == desired => continue,
+                Some(p) if !desired.is_wanted() => {
+                    self.schedules
+                        .delete_schedule(&p.mirakc_url, p.program_id)
+                        .await?;
+                    summary.removed += 1;
+                    continue;
+                }
+                None if !desired.is_wanted() => continue,
+                Some(p) if p.rule_matches.len() > desired.rule_matches.len() => {
+                    summary.removed += 1
+                }
+                Some(_) => summary.updated += 1,
+                None => summary.added += 1,
+            }
+            self.schedules.save_schedule(&desired).await?;
+            self.report_new_matches(previous.as_ref(), &desired).await?;
+        }
+
+        // EPG から消えた番組 (放送の取りやめなど)
+        for schedule in existing.into_values() {
+            if schedule.manual {
+                continue;
+            }
+            debug!(
+                program_id = schedule.program_id,
+                "EPG から消えた番組の録画予約を削除します"
+            );
+            self.schedules
+                .delete_schedule(&schedule.mirakc_url, schedule.program_id)
+                .await?;
+            summary.removed += 1;
+        }
+
+        Ok(summary)
+    }
+
+    /// 前回の評価で一致していなかったルールの一致を報告する。
+    async fn report_new_matches(
+        &self,
+        previous: Option<&DesiredSchedule>,
+        desired: &DesiredSchedule,
+    ) -> Result<()> {
+        for rule_match in &desired.rule_matches {
+            let known = previous.is_some_and(|p| {
+                p.rule_matches
+                    .iter()
+                    .any(|m| m.rule_id == rule_match.rule_id)
+            });
+            if known {
+                continue;
+            }
+            info!(
+                program_id = desired.program_id,
+                name = ?desired.name,
+                rule_id = %rule_match.rule_id,
+                reasons = %rule_match
+                    .reasons
+                    .iter()
+                    .map(|r| r.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                "番組が自動録画ルールに一致しました"
+            );
+            self.sink
+                .publish(RecordingRuleMatchedEvent {
+                    mirakc_url: desired.mirakc_url.clone(),
+                    program_id: desired.program_id,
+                    program_name: desired.name.clone(),
+                    rule_id: rule_match.rule_id.clone(),
+                    reasons: rule_match.reasons.clone(),
+                })
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::rule::RuleConditions;
+    use async_trait::async_trait;
+    use chrono::TimeZone;
+    use std::sync::Mutex;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+    const SERVICE_ID: i64 = 3273601024;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn program(event_id: i64, name: &str, day: u32) -> KurecProgram {
+        KurecProgram {
+            id: SERVICE_ID * 100000 + event_id,
+            mirakc_url: MIRAKC_URL.to_string(),
+            service_id: SERVICE_ID,
+            network_id: 32736,
+            event_id,
+            channel_name: "ＮＨＫ総合１・東京".to_string(),
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+            name: Some(name.to_string()),
+            description: None,
+            extended: None,
+            start_at: Utc.with_ymd_and_hms(2025, 1, day, 9, 0, 0).unwrap(),
+            duration_millis: 1800000,
+            is_free: true,
+            genres: vec!["アニメ・特撮／国内アニメ".to_string()],
+            video_info: None,
+            audio_infos: vec![],
+            series_info: None,
+            markers: Default::default(),
+            episode_number: None,
+        }
+    }
+
+    fn keyword_rule(id: &str, keyword: &str, priority: i32) -> RecordingRule {
+        RecordingRule {
+            id: id.to_string(),
+            name: keyword.to_string(),
+            enabled: true,
+            priority,
+            conditions: RuleConditions {
+                keywords: vec![keyword.to_string()],
+                ..Default::default()
+            },
+        }
+    }
+
+    #[derive(Default)]
+    struct MockRuleRepository {
+        rules: Mutex<Vec<RecordingRule>>,
+    }
+
+    #[async_trait]
+    impl RecordingRuleRepository for MockRuleRepository {
+        async fn save_rule(&self, rule: &RecordingRule) -> Result<()> {
+            let mut rules = self.rules.lock().unwrap();
+            rules.retain(|r| r.id != rule.id);
+            rules.push(rule.clone());
+            Ok(())
+        }
+
+        async fn get_rule(&self, rule_id: &str) -> Result<Option<RecordingRule>> {
+            Ok(self
+                .rules
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|r| r.id == rule_id)
+                .cloned())
+        }
+
+        async fn list_rules(&self) -> Result<Vec<RecordingRule>> {
+            Ok(self.rules.lock().unwrap().clone())
+        }
+
+        async fn delete_rule(&self, rule_id: &str) -> Result<()> {
+            self.rules.lock().unwrap().retain(|r| r.id != rule_id);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockProgramRepository {
+        programs: Mutex<Vec<KurecProgram>>,
+    }
+
+    #[async_trait]
+    impl KurecProgramRepository for MockProgramRepository {
+        async fn save_service_programs(
+            &self,
+            _mirakc_url: &str,
+            _service_id: i64,
+            programs: Vec<KurecProgram>,
+        ) -> Result<()> {
+            *self.programs.lock().unwrap() = programs;
+            Ok(())
+        }
+
+        async fn get_service_programs(
+            &self,
+            _mirakc_url: &str,
+            _service_id: i64,
+        ) -> Result<Option<Vec<KurecProgram>>> {
+            Ok(Some(self.programs.lock().unwrap().clone()))
+        }
+
+        async fn list_all_programs(&self) -> Result<Vec<KurecProgram>> {
+            Ok(self.programs.lock().unwrap().clone())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockScheduleRepository {
+        schedules: Mutex<HashMap<(String, i64), DesiredSchedule>>,
+    }
+
+    #[async_trait]
+    impl DesiredScheduleRepository for MockScheduleRepository {
+        async fn save_schedule(&self, schedule: &DesiredSchedule) -> Result<()> {
+            self.schedules.lock().unwrap().insert(
+                (schedule.mirakc_url.clone(), schedule.program_id),
+                schedule.clone(),
+            );
+            Ok(())
+        }
+
+        async fn get_schedule(
+            &self,
+            mirakc_url: &str,
+            program_id: i64,
+        ) -> Result<Option<DesiredSchedule>> {
+            Ok(self
+                .schedules
+                .lock()
+                .unwrap()
+                .get(&(mirakc_url.to_string(), program_id))
+                .cloned())
+        }
+
+        async fn list_schedules(&self) -> Result<Vec<DesiredSchedule>> {
+            Ok(self.schedules.lock().unwrap().values().cloned().collect())
+        }
+
+        async fn delete_schedule(&self, mirakc_url: &str, program_id: i64) -> Result<()> {
+            self.schedules
+                .lock()
+                .unwrap()
+                .remove(&(mirakc_url.to_string(), program_id));
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSink {
+        events: Mutex<Vec<RecordingRuleMatchedEvent>>,
+    }
+
+    #[async_trait]
+    impl EventSink<RecordingRuleMatchedEvent> for MockSink {
+        async fn publish(&self, event: RecordingRuleMatchedEvent) -> Result<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        rules: Arc<MockRuleRepository>,
+        programs: Arc<MockProgramRepository>,
+        schedules: Arc<MockScheduleRepository>,
+        sink: Arc<MockSink>,
+        usecase: RuleEngineUseCase,
+    }
+
+    fn fixture() -> Fixture {
+        let rules = Arc::new(MockRuleRepository::default());
+        let programs = Arc::new(MockProgramRepository::default());
+        let schedules = Arc::new(MockScheduleRepository::default());
+        let sink = Arc::new(MockSink::default());
+        let usecase = RuleEngineUseCase::new(
+            rules.clone(),
+            programs.clone(),
+            schedules.clone(),
+            sink.clone(),
+        );
+        Fixture {
+            rules,
+            programs,
+            schedules,
+            sink,
+            usecase,
+        }
+    }
+
+    fn epg_stored() -> EpgStoredEvent {
+        EpgStoredEvent {
+            mirakc_url: MIRAKC_URL.to_string(),
+            service_id: SERVICE_ID,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_epg_stored_creates_desired_schedules() {
+        let f = fixture();
+        f.rules
+            .save_rule(&keyword_rule("gundam", "ガンダム", 10))
+            .await
+            .unwrap();
+        f.rules
+            .save_rule(&keyword_rule("anime", "機動", 5))
+            .await
+            .unwrap();
+        *f.programs.programs.lock().unwrap() = vec![
+            program(1, "機動戦士ガンダム #1", 4),
+            program(2, "ニュース", 4),
+        ];
+
+        let summary = f
+            .usecase
+            .handle_epg_stored(&epg_stored(), now())
+            .await
+            .unwrap();
+        assert_eq!(summary.matched, 1);
+        assert_eq!(summary.added, 1);
+
+        let schedule = f
+            .schedules
+            .get_schedule(MIRAKC_URL, SERVICE_ID * 100000 + 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(schedule.priority(), 10);
+        assert_eq!(schedule.tags(), vec!["kurec", "rule:gundam", "rule:anime"]);
+
+        let events = f.sink.events.lock().unwrap().clone();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].rule_id, "gundam");
+        assert!(!events[0].reasons.is_empty());
+
+        // 同じ EPG の再評価では何も変わらず、イベントも発行しない
+        let summary = f
+            .usecase
+            .handle_epg_stored(&epg_stored(), now())
+            .await
+            .unwrap();
+        assert_eq!(summary.added + summary.updated + summary.removed, 0);
+        assert_eq!(f.sink.events.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_program_diffs_update_and_remove_schedules() {
+        let f = fixture();
+        f.rules
+            .save_rule(&keyword_rule("gundam", "ガンダム", 10))
+            .await
+            .unwrap();
+        *f.programs.programs.lock().unwrap() = vec![
+            program(1, "機動戦士ガンダム #1", 4),
+            program(2, "機動戦士ガンダム #2", 11),
+        ];
+        f.usecase.apply_all(now()).await.unwrap();
+
+        // 手動で予約した番組はルールに一致しなくなっても残る
+        let mut manual = f
+            .schedules
+            .get_schedule(MIRAKC_URL, SERVICE_ID * 100000 + 2)
+            .await
+            .unwrap()
+            .unwrap();
+        manual.manual = true;
+        f.schedules.save_schedule(&manual).await.unwrap();
+
+        // #1 は放送時間が変わり、#2 はタイトルが変わってルールに一致しなくなった
+        let mut moved = program(1, "機動戦士ガンダム #1", 5);
+        moved.duration_millis = 3600000;
+        *f.programs.programs.lock().unwrap() = vec![moved, program(2, "特別番組", 11)];
+        let summary = f.usecase.apply_all(now()).await.unwrap();
+        assert_eq!(summary.updated, 1);
+        assert_eq!(summary.removed, 1);
+
+        let schedules = f.schedules.list_schedules().await.unwrap();
+        assert_eq!(schedules.len(), 2);
+        let kept = schedules.iter().find(|s| s.manual).unwrap();
+        assert!(kept.rule_matches.is_empty());
+
+        // EPG から消えたルールの予約は削除される
+        *f.programs.programs.lock().unwrap() = vec![];
+        let summary = f.usecase.apply_all(now()).await.unwrap();
+        assert_eq!(summary.removed, 1);
+        assert_eq!(f.schedules.list_schedules().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ended_programs_and_invalid_rules_are_ignored() {
+        let f = fixture();
+        f.rules
+            .save_rule(&keyword_rule("gundam", "ガンダム", 10))
+            .await
+            .unwrap();
+        f.rules
+            .save_rule(&RecordingRule {
+                id: "everything".to_string(),
+                name: "すべて".to_string(),
+                enabled: true,
+                priority: 0,
+                conditions: RuleConditions::default(),
+            })
+            .await
+            .unwrap();
+        *f.programs.programs.lock().unwrap() = vec![program(1, "機動戦士ガンダム #1", 4)];
+
+        let later = Utc.with_ymd_and_hms(2025, 1, 10, 0, 0, 0).unwrap();
+        let summary = f.usecase.apply_all(later).await.unwrap();
+        assert_eq!(summary, RuleApplySummary::default());
+
+        assert_eq!(f.usecase.active_rules().await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,456 @@
+//! 自動録画ルールのプレビューユースケース
+//!
+//! ルールを保存済みの番組 (`KurecProgram`) で評価し、録画される番組、除外される番組とその理由、
+//! ルールを適用した場合に新たに生じるチューナーの競合を求めます。録画予約は変更しません。
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::conflict::{analyze_conflicts, ScheduleConflict};
+use crate::models::epg::KurecProgram;
+use crate::models::rule::{MatchReason, RecordingRule, RejectReason, RuleMatch, RuleVerdict};
+use crate::models::schedule::DesiredSchedule;
+use crate::ports::query_bus::{QueryHandler, QueryRequest};
+use crate::ports::repositories::desired_schedule_repository::DesiredScheduleRepository;
+use crate::ports::repositories::kurec_program_repository::KurecProgramRepository;
+use crate::ports::repositories::recording_rule_repository::RecordingRuleRepository;
+use crate::ports::repositories::service_repository::ServiceRepository;
+use crate::ports::repositories::tuner_repository::TunerStatusRepository;
+
+/// ルールに一致した番組
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreviewMatch {
+    /// 番組
+    pub program: KurecProgram,
+    /// 一致した理由
+    pub reasons: Vec<MatchReason>,
+}
+
+/// ルールの除外条件で外れた番組
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreviewExclusion {
+    /// 番組
+    pub program: KurecProgram,
+    /// 除外された理由
+    pub reason: RejectReason,
+}
+
+/// ルールのプレビュー結果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RulePreview {
+    /// 評価したルール
+    pub rule: RecordingRule,
+    /// 録画される番組 (開始時刻の順)
+    pub matched: Vec<PreviewMatch>,
+    /// 番組を選ぶ条件には一致したが、除外の条件で外れた番組 (開始時刻の順)
+    pub excluded: Vec<PreviewExclusion>,
+    /// ルールを適用した場合に新たに録画できなくなる録画予約
+    pub conflicts: Vec<ScheduleConflict>,
+}
+
+/// ルールのプレビューの問い合わせ
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RulePreviewQuery {
+    /// 保存済みのルールをIDで指定する
+    RuleId { rule_id: String },
+    /// 保存していないルールを指定する
+    Rule { rule: Box<RecordingRule> },
+}
+
+impl QueryRequest for RulePreviewQuery {
+    const SUBJECT: &'static str = "kurec.query.rules.preview";
+    type Response = RulePreview;
+}
+
+/// 自動録画ルールのプレビューユースケース
+pub struct RulePreviewUseCase {
+    rules: Arc<dyn RecordingRuleRepository>,
+    programs: Arc<dyn KurecProgramRepository>,
+    schedules: Arc<dyn DesiredScheduleRepository>,
+    tuners: Arc<dyn TunerStatusRepository>,
+    services: Arc<dyn ServiceRepository>,
+}
+
+impl RulePreviewUseCase {
+    /// 新しいRulePreviewUseCaseを作成
+    pub fn new(
+        rules: Arc<dyn RecordingRuleRepository>,
+        programs: Arc<dyn KurecProgramRepository>,
+        schedules: Arc<dyn DesiredScheduleRepository>,
+        tuners: Arc<dyn TunerStatusRepository>,
+        services: Arc<dyn ServiceRepository>,
+    ) -> Self {
+        Self {
+            rules,
+            programs,
+            schedules,
+            tuners,
+            services,
+        }
+    }
+
+    /// 保存済みのルールをプレビューする。
+    pub async fn preview_rule(&self, rule_id: &str, now: DateTime<Utc>) -> Result<RulePreview> {
+        let rule = self
+            .rules
+            .get_rule(rule_id)
+            .await?
+            .with_context(|| format!("ルールが見つかりません: {}", rule_id))?;
+        self.preview(rule, now).await
+    }
+
+    /// ルールを終了していない番組で評価する。
+    ///
+    /// 無効なルールも有効なものとして評価する。保存済みの同じIDのルールによる一致は、
+    /// このルールの評価結果で置き換えたものとして競合を求める。
+    pub async fn preview(&self, rule: RecordingRule, now: DateTime<Utc>) -> Result<RulePreview> {
+        rule.validate()?;
+        let enabled = RecordingRule {
+            enabled: true,
+            ..rule.clone()
+        };
+
+        let mut programs: Vec<KurecProgram> = self
+            .programs
+            .list_all_programs()
+            .await?
+            .into_iter()
+            .filter(|p| DesiredSchedule::from_program(p).end_at() > now)
+            .collect();
+        programs.sort_by(|a, b| a.start_at.cmp(&b.start_at).then(a.id.cmp(&b.id)));
+
+        let mut matched = Vec::new();
+        let mut excluded = Vec::new();
+        for program in programs {
+            match enabled.evaluate(&program) {
+                RuleVerdict::Matched(reasons) => matched.push(PreviewMatch { program, reasons }),
+                RuleVerdict::Rejected(reason) if reason.is_exclusion() => {
+                    excluded.push(PreviewExclusion { program, reason })
+                }
+                RuleVerdict::Rejected(_) => {}
+            }
+        }
+
+        let conflicts = self.new_conflicts(&enabled, &matched, now).await?;
+        Ok(RulePreview {
+            rule,
+            matched,
+            excluded,
+            conflicts,
+        })
+    }
+
+    /// ルールを適用した後の録画予約で競合を分析し、適用前にはなかった競合を求める。
+    async fn new_conflicts(
+        &self,
+        rule: &RecordingRule,
+        matched: &[PreviewMatch],
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ScheduleConflict>> {
+        let current: Vec<DesiredSchedule> = self
+            .schedules
+            .list_schedules()
+            .await?
+            .into_iter()
+            .filter(|s| s.end_at() > now)
+            .collect();
+
+        let mut planned: Vec<DesiredSchedule> = current
+            .iter()
+            .cloned()
+            .map(|mut s| {
+                s.rule_matches.retain(|m| m.rule_id != rule.id);
+                s
+            })
+            .filter(|s| s.is_wanted())
+            .collect();
+        for m in matched {
+            let rule_match = RuleMatch {
+                rule_id: rule.id.clone(),
+                rule_name: rule.name.clone(),
+                priority: rule.priority,
+                reasons: m.reasons.clone(),
+            };
+            let existing = planned
+                .iter_mut()
+                .find(|s| s.mirakc_url == m.program.mirakc_url && s.program_id == m.program.id);
+            match existing {
+                Some(schedule) => schedule.rule_matches.push(rule_match),
+                None => {
+                    let mut schedule = DesiredSchedule::from_program(&m.program);
+                    schedule.rule_matches.push(rule_match);
+                    planned.push(schedule);
+                }
+            }
+        }
+
+        let tuners = self.tuners.list_statuses().await?;
+        let services = self.services.list_all_services().await?;
+        let before: HashSet<(String, i64)> = analyze_conflicts(&current, &tuners, &services)
+            .conflicts
+            .into_iter()
+            .map(|c| (c.schedule.mirakc_url, c.schedule.program_id))
+            .collect();
+        Ok(analyze_conflicts(&planned, &tuners, &services)
+            .conflicts
+            .into_iter()
+            .filter(|c| !before.contains(&(c.schedule.mirakc_url.clone(), c.schedule.program_id)))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl QueryHandler<RulePreviewQuery> for RulePreviewUseCase {
+    async fn handle(&self, query: RulePreviewQuery) -> Result<RulePreview> {
+        match query {
+            RulePreviewQuery::RuleId { rule_id } => self.preview_rule(&rule_id, Utc::now()).await,
+            RulePreviewQuery::Rule { rule } => self.preview(*rule, Utc::now()).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::rule::RuleConditions;
+    use crate::models::service::{Channel, Service};
+    use crate::models::tuner::TunerStatus;
+    use chrono::TimeZone;
+    use std::sync::Mutex;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+    const SERVICE_ID: i64 = 3273601024;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn program(event_id: i64, name: &str, hour: u32) -> KurecProgram {
+        KurecProgram {
+            id: SERVICE_ID * 100000 + event_id,
+            mirakc_url: MIRAKC_URL.to_string(),
+            service_id: SERVICE_ID,
+            network_id: 32736,
+            event_id,
+            channel_name: "ＮＨＫ総合１・東京".to_string(),
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+            name: Some(name.to_string()),
+            description: None,
+            extended: None,
+            start_at: Utc.with_ymd_and_hms(2025, 1, 4, hour, 0, 0).unwrap(),
+            duration_millis: 1800000,
+            is_free: true,
+            genres: vec![],
+            video_info: None,
+            audio_infos: vec![],
+            series_info: None,
+            markers: Default::default(),
+            episode_number: None,
+        }
+    }
+
+    fn rule() -> RecordingRule {
+        RecordingRule {
+            id: "anime".to_string(),
+            name: "アニメ".to_string(),
+            enabled: false,
+            priority: 1,
+            conditions: RuleConditions {
+                keywords: vec!["アニメ".to_string()],
+                exclude_keywords: vec!["再放送".to_string()],
+                ..Default::default()
+            },
+        }
+    }
+
+    struct MockRuleRepository;
+
+    #[async_trait]
+    impl RecordingRuleRepository for MockRuleRepository {
+        async fn save_rule(&self, _rule: &RecordingRule) -> Result<()> {
+            Ok(())
+        }
+
+        async fn get_rule(&self, rule_id: &str) -> Result<Option<RecordingRule>> {
+            Ok(Some(rule()).filter(|r| r.id == rule_id))
+        }
+
+        async fn list_rules(&self) -> Result<Vec<RecordingRule>> {
+            Ok(vec![rule()])
+        }
+
+        async fn delete_rule(&self, _rule_id: &str) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    struct MockProgramRepository;
+
+    #[async_trait]
+    impl KurecProgramRepository for MockProgramRepository {
+        async fn save_service_programs(
+            &self,
+            _mirakc_url: &str,
+            _service_id: i64,
+            _programs: Vec<KurecProgram>,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        async fn get_service_programs(
+            &self,
+            _mirakc_url: &str,
+            _service_id: i64,
+        ) -> Result<Option<Vec<KurecProgram>>> {
+            Ok(None)
+        }
+
+        async fn list_all_programs(&self) -> Result<Vec<KurecProgram>> {
+            Ok(vec![
+                program(3, "アニメC", 10),
+                program(1, "アニメA", 9),
+                program(2, "アニメB(再放送)", 9),
+                program(4, "ニュース", 9),
+            ])
+        }
+    }
+
+    #[derive(Default)]
+    struct MockScheduleRepository {
+        schedules: Mutex<Vec<DesiredSchedule>>,
+    }
+
+    #[async_trait]
+    impl DesiredScheduleRepository for MockScheduleRepository {
+        async fn save_schedule(&self, schedule: &DesiredSchedule) -> Result<()> {
+            self.schedules.lock().unwrap().push(schedule.clone());
+            Ok(())
+        }
+
+        async fn get_schedule(
+            &self,
+            _mirakc_url: &str,
+            _program_id: i64,
+        ) -> Result<Option<DesiredSchedule>> {
+            Ok(None)
+        }
+
+        async fn list_schedules(&self) -> Result<Vec<DesiredSchedule>> {
+            Ok(self.schedules.lock().unwrap().clone())
+        }
+
+        async fn delete_schedule(&self, _mirakc_url: &str, _program_id: i64) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    struct MockTunerRepository;
+
+    #[async_trait]
+    impl TunerStatusRepository for MockTunerRepository {
+        async fn save_status(&self, _status: &TunerStatus) -> Result<()> {
+            Ok(())
+        }
+
+        async fn get_status(&self, _mirakc_url: &str, _index: i64) -> Result<Option<TunerStatus>> {
+            Ok(None)
+        }
+
+        async fn list_statuses(&self) -> Result<Vec<TunerStatus>> {
+            Ok(vec![TunerStatus {
+                mirakc_url: MIRAKC_URL.to_string(),
+                index: 0,
+                name: "tuner0".to_string(),
+                types: vec!["GR".to_string()],
+                in_use: false,
+                users: vec![],
+                command: None,
+                since: now(),
+                updated_at: now(),
+            }])
+        }
+    }
+
+    struct MockServiceRepository;
+
+    #[async_trait]
+    impl ServiceRepository for MockServiceRepository {
+        async fn save_services(&self, _mirakc_url: &str, _services: &[Service]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn list_services(&self, _mirakc_url: &str) -> Result<Vec<Service>> {
+            Ok(vec![])
+        }
+
+        async fn list_all_services(&self) -> Result<Vec<Service>> {
+            Ok(vec![])
+        }
+
+        async fn save_channels(&self, _mirakc_url: &str, _channels: &[Channel]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn list_channels(&self, _mirakc_url: &str) -> Result<Vec<Channel>> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preview_rule() -> Result<()> {
+        let schedules = Arc::new(MockScheduleRepository::default());
+        // 優先度の高い手動予約がチューナーを使っている
+        let mut news = DesiredSchedule::from_program(&program(4, "ニュース", 9));
+        news.manual = true;
+        news.rule_matches.push(RuleMatch {
+            rule_id: "news".to_string(),
+            rule_name: "ニュース".to_string(),
+            priority: 10,
+            reasons: vec![],
+        });
+        schedules.save_schedule(&news).await?;
+        let usecase = RulePreviewUseCase::new(
+            Arc::new(MockRuleRepository),
+            Arc::new(MockProgramRepository),
+            schedules.clone(),
+            Arc::new(MockTunerRepository),
+            Arc::new(MockServiceRepository),
+        );
+
+        let preview = usecase.preview_rule("anime", now()).await?;
+        // 無効なルールも評価する
+        assert!(!preview.rule.enabled);
+        let matched: Vec<_> = preview.matched.iter().map(|m| m.program.event_id).collect();
+        assert_eq!(matched, vec![1, 3]);
+        // 番組を選ぶ条件に一致しない番組 (ニュース) は除外の一覧に含めない
+        assert_eq!(preview.excluded.len(), 1);
+        assert_eq!(preview.excluded[0].program.event_id, 2);
+        assert_eq!(
+            preview.excluded[0].reason,
+            RejectReason::ExcludedKeyword {
+                keyword: "再放送".to_string()
+            }
+        );
+        assert_eq!(preview.conflicts.len(), 1);
+        assert_eq!(
+            preview.conflicts[0].schedule.program_id,
+            program(1, "", 9).id
+        );
+        assert_eq!(
+            preview.conflicts[0].blocked_by[0].program_id,
+            news.program_id
+        );
+        // 録画予約は変更しない
+        assert_eq!(schedules.list_schedules().await?, vec![news]);
+
+        assert!(usecase.preview_rule("missing", now()).await.is_err());
+        Ok(())
+    }
+}
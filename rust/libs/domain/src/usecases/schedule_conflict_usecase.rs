@@ -0,0 +1,274 @@
+//! 録画予約の競合検出ユースケース
+//!
+//! 録画されるべき番組 (`DesiredSchedule`) が mirakc ごとのチューナーに収まるかを分析し、
+//! 新たに録画できなくなった録画予約を `ScheduleConflictDetectedEvent` として発行します。
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use tracing::warn;
+
+use crate::events::kurec_events::ScheduleConflictDetectedEvent;
+use crate::models::conflict::{analyze_conflicts, ConflictAnalysis, ScheduleConflict};
+use crate::ports::event_sink::EventSink;
+use crate::ports::repositories::desired_schedule_repository::DesiredScheduleRepository;
+use crate::ports::repositories::service_repository::ServiceRepository;
+use crate::ports::repositories::tuner_repository::TunerStatusRepository;
+
+/// 録画予約の競合検出ユースケース
+pub struct ScheduleConflictUseCase {
+    schedules: Arc<dyn DesiredScheduleRepository>,
+    tuners: Arc<dyn TunerStatusRepository>,
+    services: Arc<dyn ServiceRepository>,
+    sink: Arc<dyn EventSink<ScheduleConflictDetectedEvent>>,
+    /// 発行済みの競合 (mirakc のベースURL と Program ID)
+    reported: Mutex<HashSet<(String, i64)>>,
+}
+
+impl ScheduleConflictUseCase {
+    /// 新しいScheduleConflictUseCaseを作成
+    pub fn new(
+        schedules: Arc<dyn DesiredScheduleRepository>,
+        tuners: Arc<dyn TunerStatusRepository>,
+        services: Arc<dyn ServiceRepository>,
+        sink: Arc<dyn EventSink<ScheduleConflictDetectedEvent>>,
+    ) -> Self {
+        Self {
+            schedules,
+            tuners,
+            services,
+            sink,
+            reported: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// 終了していない録画予約をチューナーに割り当て、競合を分析する。
+    pub async fn analyze(&self, now: DateTime<Utc>) -> Result<ConflictAnalysis> {
+        let schedules: Vec<_> = self
+            .schedules
+            .list_schedules()
+            .await?
+            .into_iter()
+            .filter(|s| s.end_at() > now)
+            .collect();
+        let tuners = self.tuners.list_statuses().await?;
+        let services = self.services.list_all_services().await?;
+        Ok(analyze_conflicts(&schedules, &tuners, &services))
+    }
+
+    /// 競合を分析し、前回の分析以降に新たに見つかった競合を発行する。
+    pub async fn detect(&self, now: DateTime<Utc>) -> Result<ConflictAnalysis> {
+        let analysis = self.analyze(now).await?;
+        let current: HashSet<_> = analysis
+            .conflicts
+            .iter()
+            .map(|c| (c.schedule.mirakc_url.clone(), c.schedule.program_id))
+            .collect();
+        // 解消した競合は忘れ、再び競合したときに発行し直す
+        let new_conflicts: Vec<&ScheduleConflict> = {
+            let mut reported = self.reported.lock().unwrap();
+            reported.retain(|key| current.contains(key));
+            analysis
+                .conflicts
+                .iter()
+                .filter(|c| reported.insert((c.schedule.mirakc_url.clone(), c.schedule.program_id)))
+                .collect()
+        };
+
+        for conflict in new_conflicts {
+            let schedule = &conflict.schedule;
+            warn!(
+                mirakc_url = %schedule.mirakc_url,
+                program_id = schedule.program_id,
+                name = ?schedule.name,
+                suggested_mirakc_url = ?conflict.suggested_mirakc_url,
+                "チューナーが足りないため録画できない録画予約があります"
+            );
+            self.sink
+                .publish(ScheduleConflictDetectedEvent {
+                    mirakc_url: schedule.mirakc_url.clone(),
+                    program_id: schedule.program_id,
+                    program_name: schedule.name.clone(),
+                    channel_type: schedule.channel_type.clone(),
+                    start_at: schedule.start_at,
+                    end_at: schedule.end_at(),
+                    priority: schedule.priority(),
+                    blocked_by: conflict.blocked_by.clone(),
+                    suggested_mirakc_url: conflict.suggested_mirakc_url.clone(),
+                })
+                .await?;
+        }
+        Ok(analysis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::schedule::DesiredSchedule;
+    use crate::models::service::{Channel, Service};
+    use crate::models::tuner::TunerStatus;
+    use async_trait::async_trait;
+    use chrono::TimeZone;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 4, 0, 0, 0).unwrap()
+    }
+
+    fn schedule(program_id: i64, hour: u32) -> DesiredSchedule {
+        DesiredSchedule {
+            mirakc_url: MIRAKC_URL.to_string(),
+            program_id,
+            service_id: 3273601024,
+            channel_type: "GR".to_string(),
+            name: Some(format!("番組{}", program_id)),
+            start_at: Utc.with_ymd_and_hms(2025, 1, 4, hour, 0, 0).unwrap(),
+            duration_millis: 3600000,
+            rule_matches: vec![],
+            manual: false,
+            series: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct MockScheduleRepository {
+        schedules: Mutex<Vec<DesiredSchedule>>,
+    }
+
+    #[async_trait]
+    impl DesiredScheduleRepository for MockScheduleRepository {
+        async fn save_schedule(&self, schedule: &DesiredSchedule) -> Result<()> {
+            self.schedules.lock().unwrap().push(schedule.clone());
+            Ok(())
+        }
+
+        async fn get_schedule(
+            &self,
+            _mirakc_url: &str,
+            _program_id: i64,
+        ) -> Result<Option<DesiredSchedule>> {
+            Ok(None)
+        }
+
+        async fn list_schedules(&self) -> Result<Vec<DesiredSchedule>> {
+            Ok(self.schedules.lock().unwrap().clone())
+        }
+
+        async fn delete_schedule(&self, _mirakc_url: &str, program_id: i64) -> Result<()> {
+            self.schedules
+                .lock()
+                .unwrap()
+                .retain(|s| s.program_id != program_id);
+            Ok(())
+        }
+    }
+
+    struct MockTunerRepository;
+
+    #[async_trait]
+    impl TunerStatusRepository for MockTunerRepository {
+        async fn save_status(&self, _status: &TunerStatus) -> Result<()> {
+            Ok(())
+        }
+
+        async fn get_status(&self, _mirakc_url: &str, _index: i64) -> Result<Option<TunerStatus>> {
+            Ok(None)
+        }
+
+        async fn list_statuses(&self) -> Result<Vec<TunerStatus>> {
+            Ok(vec![TunerStatus {
+                mirakc_url: MIRAKC_URL.to_string(),
+                index: 0,
+                name: "tuner0".to_string(),
+                types: vec!["GR".to_string()],
+                in_use: false,
+                users: vec![],
+                command: None,
+                since: now(),
+                updated_at: now(),
+            }])
+        }
+    }
+
+    struct MockServiceRepository;
+
+    #[async_trait]
+    impl ServiceRepository for MockServiceRepository {
+        async fn save_services(&self, _mirakc_url: &str, _services: &[Service]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn list_services(&self, _mirakc_url: &str) -> Result<Vec<Service>> {
+            Ok(vec![])
+        }
+
+        async fn list_all_services(&self) -> Result<Vec<Service>> {
+            Ok(vec![])
+        }
+
+        async fn save_channels(&self, _mirakc_url: &str, _channels: &[Channel]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn list_channels(&self, _mirakc_url: &str) -> Result<Vec<Channel>> {
+            Ok(vec![])
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSink {
+        events: Mutex<Vec<ScheduleConflictDetectedEvent>>,
+    }
+
+    #[async_trait]
+    impl EventSink<ScheduleConflictDetectedEvent> for MockSink {
+        async fn publish(&self, event: ScheduleConflictDetectedEvent) -> Result<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_detect_publishes_new_conflicts_once() -> Result<()> {
+        let schedules = Arc::new(MockScheduleRepository::default());
+        let sink = Arc::new(MockSink::default());
+        let usecase = ScheduleConflictUseCase::new(
+            schedules.clone(),
+            Arc::new(MockTunerRepository),
+            Arc::new(MockServiceRepository),
+            sink.clone(),
+        );
+
+        schedules.save_schedule(&schedule(1, 9)).await?;
+        schedules.save_schedule(&schedule(2, 9)).await?;
+        // 終了した録画予約は分析しない
+        schedules.save_schedule(&schedule(3, 0)).await?;
+        let at = now() + chrono::Duration::hours(2);
+
+        let analysis = usecase.detect(at).await?;
+        assert_eq!(analysis.assignments.len(), 1);
+        assert_eq!(analysis.conflicts.len(), 1);
+        {
+            let events = sink.events.lock().unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].program_id, 2);
+            assert_eq!(events[0].blocked_by[0].program_id, 1);
+        }
+
+        // 同じ競合は発行し直さない
+        usecase.detect(at).await?;
+        assert_eq!(sink.events.lock().unwrap().len(), 1);
+
+        // 解消した後に再び競合した場合は発行する
+        schedules.delete_schedule(MIRAKC_URL, 2).await?;
+        assert!(usecase.detect(at).await?.conflicts.is_empty());
+        schedules.save_schedule(&schedule(2, 9)).await?;
+        usecase.detect(at).await?;
+        assert_eq!(sink.events.lock().unwrap().len(), 2);
+        Ok(())
+    }
+}
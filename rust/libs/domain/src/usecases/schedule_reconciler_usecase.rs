@@ -0,0 +1,516 @@
+//! 録画予約の調整 (リコンシリエーション) ユースケース
+//!
+//! 録画されるべき番組 (`DesiredScheduleRepository`) と mirakc の録画予約を比較し、差分を解消します。
+//! KuRec が作成した録画予約 (`kurec` タグ付き) だけを作成・削除し、ユーザーが作成した録画予約には触れません。
+//! 何度実行しても同じ結果になり、複数のインスタンスを起動した場合はリースを保持している1つだけが調整を行います。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use tracing::{debug, info, warn};
+
+use crate::events::kurec_events::ScheduleDriftDetectedEvent;
+use crate::models::schedule::{DesiredSchedule, MirakcSchedule, MirakcScheduleState};
+use crate::ports::event_sink::EventSink;
+use crate::ports::mirakc_api::MirakcSchedulesApi;
+use crate::ports::repositories::desired_schedule_repository::DesiredScheduleRepository;
+use crate::ports::repositories::lease_repository::LeaseRepository;
+
+/// 調整を行うインスタンスを選ぶためのリース名
+pub const RECONCILER_LEASE_NAME: &str = "schedule_reconciler";
+
+/// mirakc ごとの調整結果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReconcileReport {
+    /// 対象のmirakcのベースURL
+    pub mirakc_url: String,
+    /// 作成した録画予約の Program ID
+    pub created: Vec<i64>,
+    /// タグや優先度が異なっていたため作成し直した録画予約の Program ID
+    pub recreated: Vec<i64>,
+    /// 削除した録画予約の Program ID
+    pub deleted: Vec<i64>,
+    /// 作成または削除に失敗した録画予約の Program ID
+    pub failed: Vec<i64>,
+    /// 変更しなかった KuRec の録画予約の数
+    pub unchanged: usize,
+    /// ユーザーが作成した (変更しなかった) 録画予約の数
+    pub foreign: usize,
+}
+
+impl ReconcileReport {
+    /// 録画されるべき番組との差分があったかどうか
+    pub fn has_drift(&self) -> bool {
+        !(self.created.is_empty()
+            && self.recreated.is_empty()
+            && self.deleted.is_empty()
+            && self.failed.is_empty())
+    }
+}
+
+/// 録画予約の調整ユースケース
+pub struct ScheduleReconcilerUseCase {
+    api: Arc<dyn MirakcSchedulesApi>,
+    schedules: Arc<dyn DesiredScheduleRepository>,
+    leases: Arc<dyn LeaseRepository>,
+    sink: Arc<dyn EventSink<ScheduleDriftDetectedEvent>>,
+    /// このインスタンスの識別子
+    holder: String,
+    /// リースの有効期間。調整の間隔より長くする
+    lease_ttl: Duration,
+    /// 録画されるべき番組がなくても調整する mirakc のベースURL
+    mirakc_urls: Vec<String>,
+}
+
+impl ScheduleReconcilerUseCase {
+    /// 新しいScheduleReconcilerUseCaseを作成
+    pub fn new(
+        api: Arc<dyn MirakcSchedulesApi>,
+        schedules: Arc<dyn DesiredScheduleRepository>,
+        leases: Arc<dyn LeaseRepository>,
+        sink: Arc<dyn EventSink<ScheduleDriftDetectedEvent>>,
+        holder: String,
+        lease_ttl: Duration,
+        mirakc_urls: Vec<String>,
+    ) -> Self {
+        Self {
+            api,
+            schedules,
+            leases,
+            sink,
+            holder,
+            lease_ttl,
+            mirakc_urls,
+        }
+    }
+
+    /// リースを取得できた場合に、すべての mirakc の録画予約を調整する。
+    ///
+    /// # Returns
+    ///
+    /// mirakc ごとの調整結果。他のインスタンスがリースを保持している場合は `Ok(None)`。
+    /// 録画予約を取得できなかった mirakc は結果に含まれない。
+    pub async fn reconcile_all(&self, now: DateTime<Utc>) -> Result<Option<Vec<ReconcileReport>>> {
+        let acquired = self
+            .leases
+            .try_acquire(
+                RECONCILER_LEASE_NAME,
+                &self.holder,
+                now + self.lease_ttl,
+                now,
+            )
+            .await?;
+        if !acquired {
+            debug!(holder = %self.holder, "他のインスタンスが調整を行っているためスキップします");
+            return Ok(None);
+        }
+
+        let mut desired: BTreeMap<String, Vec<DesiredSchedule>> = self
+            .mirakc_urls
+            .iter()
+            .map(|url| (url.clone(), Vec::new()))
+            .collect();
+        for schedule in self.schedules.list_schedules().await? {
+            if schedule.end_at() > now {
+                desired
+                    .entry(schedule.mirakc_url.clone())
+                    .or_default()
+                    .push(schedule);
+            }
+        }
+
+        let mut reports = Vec::new();
+        for (mirakc_url, schedules) in desired {
+            let report = match self.reconcile(&mirakc_url, &schedules, now).await {
+                Ok(report) => report,
+                Err(e) => {
+                    warn!(%mirakc_url, "録画予約の調整に失敗しました: {:?}", e);
+                    continue;
+                }
+            };
+            if report.has_drift() {
+                info!(
+                    %mirakc_url,
+                    created = ?report.created,
+                    recreated = ?report.recreated,
+                    deleted = ?report.deleted,
+                    failed = ?report.failed,
+                    "録画予約の差分を解消しました"
+                );
+                self.sink
+                    .publish(ScheduleDriftDetectedEvent {
+                        mirakc_url: mirakc_url.clone(),
+                        created: report.created.clone(),
+                        recreated: report.recreated.clone(),
+                        deleted: report.deleted.clone(),
+                        failed: report.failed.clone(),
+                    })
+                    .await?;
+            }
+            reports.push(report);
+        }
+        Ok(Some(reports))
+    }
+
+    /// 保持しているリースを解放する。
+    pub async fn release(&self) -> Result<()> {
+        self.leases
+            .release(RECONCILER_LEASE_NAME, &self.holder)
+            .await
+    }
+
+    /// 1つの mirakc の録画予約を録画されるべき番組に合わせる。
+    async fn reconcile(
+        &self,
+        mirakc_url: &str,
+        desired: &[DesiredSchedule],
+        now: DateTime<Utc>,
+    ) -> Result<ReconcileReport> {
+        let actual: HashMap<i64, MirakcSchedule> = self
+            .api
+            .get_schedules(mirakc_url)
+            .await?
+            .into_iter()
+            .map(|s| (s.program_id, s))
+            .collect();
+        let mut report = ReconcileReport {
+            mirakc_url: mirakc_url.to_string(),
+            foreign: actual.values().filter(|s| !s.is_owned()).count(),
+            ..Default::default()
+        };
+
+        for schedule in desired {
+            match actual.get(&schedule.program_id) {
+                // 放送が始まった番組は録画予約を作成できない
+                None if schedule.start_at <= now => {}
+                None => {
+                    if self.create(mirakc_url, schedule).await {
+                        report.created.push(schedule.program_id);
+                    } else {
+                        report.failed.push(schedule.program_id);
+                    }
+                }
+                // ユーザーの録画予約で録画されるため何もしない
+                Some(existing) if !existing.is_owned() => {}
+                Some(existing)
+                    if !existing.matches(schedule)
+                        && existing.state == MirakcScheduleState::Scheduled =>
+                {
+                    // mirakc には録画予約を更新する API がないため作り直す
+                    if self.delete(mirakc_url, existing.program_id).await
+                        && self.create(mirakc_url, schedule).await
+                    {
+                        report.recreated.push(schedule.program_id);
+                    } else {
+                        report.failed.push(schedule.program_id);
+                    }
+                }
+                Some(_) => report.unchanged += 1,
+            }
+        }
+
+        let wanted: HashSet<i64> = desired.iter().map(|s| s.program_id).collect();
+        let mut orphaned: Vec<&MirakcSchedule> = actual
+            .values()
+            .filter(|s| s.is_owned() && s.state.is_pending() && !wanted.contains(&s.program_id))
+            .collect();
+        orphaned.sort_by_key(|s| s.program_id);
+        for schedule in orphaned {
+            if self.delete(mirakc_url, schedule.program_id).await {
+                report.deleted.push(schedule.program_id);
+            } else {
+                report.failed.push(schedule.program_id);
+            }
+        }
+        Ok(report)
+    }
+
+    async fn create(&self, mirakc_url: &str, schedule: &DesiredSchedule) -> bool {
+        match self.api.create_schedule(mirakc_url, schedule).await {
+            Ok(_) => true,
+            Err(e) => {
+                warn!(
+                    %mirakc_url,
+                    program_id = schedule.program_id,
+                    "録画予約の作成に失敗しました: {:?}",
+                    e
+                );
+                false
+            }
+        }
+    }
+
+    async fn delete(&self, mirakc_url: &str, program_id: i64) -> bool {
+        match self.api.delete_schedule(mirakc_url, program_id).await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!(
+                    %mirakc_url,
+                    program_id,
+                    "録画予約の削除に失敗しました: {:?}",
+                    e
+                );
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::lease::Lease;
+    use crate::models::schedule::KUREC_SCHEDULE_TAG;
+    use async_trait::async_trait;
+    use chrono::TimeZone;
+    use std::sync::Mutex;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 4, 0, 0, 0).unwrap()
+    }
+
+    fn desired(program_id: i64, hour: u32) -> DesiredSchedule {
+        DesiredSchedule {
+            mirakc_url: MIRAKC_URL.to_string(),
+            program_id,
+            service_id: 3273601024,
+            channel_type: "GR".to_string(),
+            name: Some(format!("番組{}", program_id)),
+            start_at: Utc.with_ymd_and_hms(2025, 1, 4, hour, 0, 0).unwrap(),
+            duration_millis: 3600000,
+            rule_matches: vec![],
+            manual: true,
+            series: None,
+        }
+    }
+
+    fn actual(program_id: i64, tags: &[&str], state: MirakcScheduleState) -> MirakcSchedule {
+        MirakcSchedule {
+            mirakc_url: MIRAKC_URL.to_string(),
+            program_id,
+            name: None,
+            start_at: Utc.with_ymd_and_hms(2025, 1, 4, 9, 0, 0).unwrap(),
+            state,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            priority: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct MockApi {
+        schedules: Mutex<Vec<MirakcSchedule>>,
+        calls: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl MirakcSchedulesApi for MockApi {
+        async fn get_schedules(&self, _mirakc_url: &str) -> Result<Vec<MirakcSchedule>> {
+            *self.calls.lock().unwrap() += 1;
+            Ok(self.schedules.lock().unwrap().clone())
+        }
+
+        async fn create_schedule(
+            &self,
+            mirakc_url: &str,
+            schedule: &DesiredSchedule,
+        ) -> Result<MirakcSchedule> {
+            let created = MirakcSchedule {
+                mirakc_url: mirakc_url.to_string(),
+                program_id: schedule.program_id,
+                name: schedule.name.clone(),
+                start_at: schedule.start_at,
+                state: MirakcScheduleState::Scheduled,
+                tags: schedule.tags(),
+                priority: Some(schedule.priority()),
+            };
+            self.schedules.lock().unwrap().push(created.clone());
+            Ok(created)
+        }
+
+        async fn delete_schedule(&self, _mirakc_url: &str, program_id: i64) -> Result<()> {
+            self.schedules
+                .lock()
+                .unwrap()
+                .retain(|s| s.program_id != program_id);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockScheduleRepository {
+        schedules: Mutex<Vec<DesiredSchedule>>,
+    }
+
+    #[async_trait]
+    impl DesiredScheduleRepository for MockScheduleRepository {
+        async fn save_schedule(&self, schedule: &DesiredSchedule) -> Result<()> {
+            self.schedules.lock().unwrap().push(schedule.clone());
+            Ok(())
+        }
+
+        async fn get_schedule(
+            &self,
+            _mirakc_url: &str,
+            _program_id: i64,
+        ) -> Result<Option<DesiredSchedule>> {
+            Ok(None)
+        }
+
+        async fn list_schedules(&self) -> Result<Vec<DesiredSchedule>> {
+            Ok(self.schedules.lock().unwrap().clone())
+        }
+
+        async fn delete_schedule(&self, _mirakc_url: &str, _program_id: i64) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockLeaseRepository {
+        lease: Mutex<Option<Lease>>,
+    }
+
+    #[async_trait]
+    impl LeaseRepository for MockLeaseRepository {
+        async fn try_acquire(
+            &self,
+            _name: &str,
+            holder: &str,
+            expires_at: DateTime<Utc>,
+            now: DateTime<Utc>,
+        ) -> Result<bool> {
+            let mut lease = self.lease.lock().unwrap();
+            if lease.as_ref().is_some_and(|l| !l.can_acquire(holder, now)) {
+                return Ok(false);
+            }
+            *lease = Some(Lease {
+                holder: holder.to_string(),
+                expires_at,
+            });
+            Ok(true)
+        }
+
+        async fn release(&self, _name: &str, holder: &str) -> Result<()> {
+            let mut lease = self.lease.lock().unwrap();
+            if lease.as_ref().is_some_and(|l| l.holder == holder) {
+                *lease = None;
+            }
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSink {
+        events: Mutex<Vec<ScheduleDriftDetectedEvent>>,
+    }
+
+    #[async_trait]
+    impl EventSink<ScheduleDriftDetectedEvent> for MockSink {
+        async fn publish(&self, event: ScheduleDriftDetectedEvent) -> Result<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        api: Arc<MockApi>,
+        schedules: Arc<MockScheduleRepository>,
+        leases: Arc<MockLeaseRepository>,
+        sink: Arc<MockSink>,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            Self {
+                api: Arc::new(MockApi::default()),
+                schedules: Arc::new(MockScheduleRepository::default()),
+                leases: Arc::new(MockLeaseRepository::default()),
+                sink: Arc::new(MockSink::default()),
+            }
+        }
+
+        fn usecase(&self, holder: &str) -> ScheduleReconcilerUseCase {
+            ScheduleReconcilerUseCase::new(
+                self.api.clone(),
+                self.schedules.clone(),
+                self.leases.clone(),
+                self.sink.clone(),
+                holder.to_string(),
+                Duration::minutes(5),
+                vec![MIRAKC_URL.to_string()],
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_is_idempotent() -> Result<()> {
+        let f = Fixture::new();
+        let usecase = f.usecase("worker-a");
+        for (id, hour) in [(1, 9), (2, 10), (3, 11), (4, 0)] {
+            f.schedules.save_schedule(&desired(id, hour)).await?;
+        }
+        *f.api.schedules.lock().unwrap() = vec![
+            // タグが異なるため作り直す
+            actual(
+                2,
+                &[KUREC_SCHEDULE_TAG, "rule:old"],
+                MirakcScheduleState::Scheduled,
+            ),
+            // ユーザーの録画予約はそのまま
+            actual(3, &["manual"], MirakcScheduleState::Scheduled),
+            actual(5, &["manual"], MirakcScheduleState::Scheduled),
+            // 録画されるべきでなくなった KuRec の録画予約は削除
+            actual(6, &[KUREC_SCHEDULE_TAG], MirakcScheduleState::Scheduled),
+            // 録画中の録画予約は削除しない
+            actual(7, &[KUREC_SCHEDULE_TAG], MirakcScheduleState::Recording),
+        ];
+
+        // 番組4は放送中のため作成しない
+        let at = now() + Duration::minutes(30);
+        let reports = usecase.reconcile_all(at).await?.unwrap();
+        assert_eq!(
+            reports,
+            vec![ReconcileReport {
+                mirakc_url: MIRAKC_URL.to_string(),
+                created: vec![1],
+                recreated: vec![2],
+                deleted: vec![6],
+                failed: vec![],
+                unchanged: 0,
+                foreign: 2,
+            }]
+        );
+        assert_eq!(f.sink.events.lock().unwrap().len(), 1);
+
+        let reports = usecase.reconcile_all(at).await?.unwrap();
+        assert!(!reports[0].has_drift());
+        assert_eq!(reports[0].unchanged, 2);
+        assert_eq!(f.sink.events.lock().unwrap().len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_only_lease_holder_reconciles() -> Result<()> {
+        let f = Fixture::new();
+        let leader = f.usecase("worker-a");
+        let follower = f.usecase("worker-b");
+        f.schedules.save_schedule(&desired(1, 9)).await?;
+
+        assert!(leader.reconcile_all(now()).await?.is_some());
+        assert!(follower.reconcile_all(now()).await?.is_none());
+        assert_eq!(*f.api.calls.lock().unwrap(), 1);
+
+        // 有効期限が切れるか解放されると他のインスタンスが引き継ぐ
+        assert!(follower
+            .reconcile_all(now() + Duration::minutes(5))
+            .await?
+            .is_some());
+        follower.release().await?;
+        assert!(leader.reconcile_all(now()).await?.is_some());
+        Ok(())
+    }
+}
@@ -0,0 +1,704 @@
+//! シリーズ録画ユースケース
+//!
+//! 購読しているシリーズの今後の放送を `DesiredScheduleRepository` に反映します。
+//! 再放送と録画済みの話数は録画せず、最終話の録画が保存されたら購読を完了にします。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use tracing::{debug, info};
+
+use crate::events::kurec_events::{EpgStoredEvent, SeriesCompletedEvent};
+use crate::events::mirakc_events::{RecordingRecordSavedEvent, RecordingStatus};
+use crate::models::epg::KurecProgram;
+use crate::models::schedule::DesiredSchedule;
+use crate::models::series::{SeriesStatus, SeriesSubscription, SeriesVerdict};
+use crate::ports::event_sink::EventSink;
+use crate::ports::mirakc_api::MirakcRecordsApi;
+use crate::ports::repositories::desired_schedule_repository::DesiredScheduleRepository;
+use crate::ports::repositories::kurec_program_repository::KurecProgramRepository;
+use crate::ports::repositories::record_repository::RecordRepository;
+use crate::ports::repositories::series_subscription_repository::SeriesSubscriptionRepository;
+
+/// シリーズ録画の適用結果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeriesApplySummary {
+    /// 新たに録画予約した放送の数
+    pub scheduled: usize,
+    /// 録画予約を取り消した放送の数
+    pub unscheduled: usize,
+}
+
+/// シリーズ録画ユースケース
+pub struct SeriesTrackingUseCase {
+    api: Arc<dyn MirakcRecordsApi>,
+    programs: Arc<dyn KurecProgramRepository>,
+    records: Arc<dyn RecordRepository>,
+    subscriptions: Arc<dyn SeriesSubscriptionRepository>,
+    schedules: Arc<dyn DesiredScheduleRepository>,
+    sink: Arc<dyn EventSink<SeriesCompletedEvent>>,
+}
+
+impl SeriesTrackingUseCase {
+    /// 新しいSeriesTrackingUseCaseを作成
+    pub fn new(
+        api: Arc<dyn MirakcRecordsApi>,
+        programs: Arc<dyn KurecProgramRepository>,
+        records: Arc<dyn RecordRepository>,
+        subscriptions: Arc<dyn SeriesSubscriptionRepository>,
+        schedules: Arc<dyn DesiredScheduleRepository>,
+        sink: Arc<dyn EventSink<SeriesCompletedEvent>>,
+    ) -> Self {
+        Self {
+            api,
+            programs,
+            records,
+            subscriptions,
+            schedules,
+            sink,
+        }
+    }
+
+    /// 番組のシリーズを購読し、保存済みの番組から今後の放送を録画予約する。
+    ///
+    /// 録画ライブラリにある同じシリーズの話数は録画済みとして扱う。
+    pub async fn subscribe(
+        &self,
+        mirakc_url: &str,
+        program_id: i64,
+        now: DateTime<Utc>,
+    ) -> Result<(SeriesSubscription, SeriesApplySummary)> {
+        let programs = self.programs.list_all_programs().await?;
+        let Some(program) = programs
+            .iter()
+            .find(|p| p.mirakc_url == mirakc_url && p.id == program_id)
+        else {
+            bail!("番組が見つかりません: {} ({})", program_id, mirakc_url);
+        };
+        let Some(mut subscription) = SeriesSubscription::from_program(program, now) else {
+            bail!("番組にシリーズ情報がありません: {}", program_id);
+        };
+        if let Some(existing) = self
+            .subscriptions
+            .get_subscription(&subscription.id)
+            .await?
+        {
+            subscription = existing;
+        }
+
+        for record in self.records.list_records().await? {
+            if record.recording.status != RecordingStatus::Finished {
+                continue;
+            }
+            if subscription.series_of(&record.program).is_some() {
+                subscription.mark_recorded(record.program.episode());
+            }
+        }
+        self.subscriptions.save_subscription(&subscription).await?;
+        info!(
+            subscription_id = %subscription.id,
+            name = %subscription.name,
+            recorded = ?subscription.recorded_episodes,
+            "シリーズを購読しました"
+        );
+
+        let summary = self.apply(programs, now).await?;
+        Ok((subscription, summary))
+    }
+
+    /// 購読を解除し、シリーズ録画による録画予約を取り消す。
+    pub async fn unsubscribe(&self, subscription_id: &str) -> Result<SeriesApplySummary> {
+        if self
+            .subscriptions
+            .get_subscription(subscription_id)
+            .await?
+            .is_none()
+        {
+            bail!("シリーズの購読が見つかりません: {}", subscription_id);
+        }
+        self.subscriptions
+            .delete_subscription(subscription_id)
+            .await?;
+        self.unschedule_series(subscription_id).await
+    }
+
+    /// EPG が保存されたサービスの番組から、購読しているシリーズの放送を録画予約する。
+    pub async fn handle_epg_stored(
+        &self,
+        event: &EpgStoredEvent,
+        now: DateTime<Utc>,
+    ) -> Result<SeriesApplySummary> {
+        let programs = self
+            .programs
+            .get_service_programs(&event.mirakc_url, event.service_id)
+            .await?
+            .unwrap_or_default();
+        self.apply(programs, now).await
+    }
+
+    /// 保存されているすべての番組から、購読しているシリーズの放送を録画予約する。
+    pub async fn apply_all(&self, now: DateTime<Utc>) -> Result<SeriesApplySummary> {
+        let programs = self.programs.list_all_programs().await?;
+        self.apply(programs, now).await
+    }
+
+    /// 録画が完了したシリーズの話数を録画済みにする。
+    ///
+    /// 最終話の場合は購読を完了にし、残りの録画予約を取り消して `SeriesCompletedEvent` を発行する。
+    pub async fn handle_record_saved(&self, event: &RecordingRecordSavedEvent) -> Result<()> {
+        if event.recording_status != RecordingStatus::Finished {
+            return Ok(());
+        }
+        let Some(record) = self
+            .api
+            .get_record(&event.mirakc_url, &event.record_id)
+            .await?
+        else {
+            return Ok(());
+        };
+        let Some(series) = record.program.series_info.as_ref() else {
+            return Ok(());
+        };
+        let id = SeriesSubscription::key(record.program.network_id, series.id);
+        let Some(mut subscription) = self.subscriptions.get_subscription(&id).await? else {
+            return Ok(());
+        };
+
+        let episode = record.program.episode();
+        let completed = subscription.mark_recorded(episode);
+        self.subscriptions.save_subscription(&subscription).await?;
+        info!(
+            subscription_id = %id,
+            episode,
+            "シリーズの話数を録画しました"
+        );
+        if completed {
+            info!(subscription_id = %id, name = %subscription.name, "シリーズ録画が完了しました");
+            self.unschedule_series(&id).await?;
+            self.sink
+                .publish(SeriesCompletedEvent {
+                    subscription_id: id,
+                    name: subscription.name,
+                    recorded_episodes: subscription.recorded_episodes,
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// 番組を購読中のシリーズで評価し、録画予約に反映する。
+    async fn apply(
+        &self,
+        programs: Vec<KurecProgram>,
+        now: DateTime<Utc>,
+    ) -> Result<SeriesApplySummary> {
+        let mut subscriptions: HashMap<String, SeriesSubscription> = self
+            .subscriptions
+            .list_subscriptions()
+            .await?
+            .into_iter()
+            .filter(|s| s.status == SeriesStatus::Active)
+            .map(|s| (s.id.clone(), s))
+            .collect();
+        let mut summary = SeriesApplySummary::default();
+        if subscriptions.is_empty() {
+            return Ok(summary);
+        }
+
+        let mut updated = Vec::new();
+        for program in programs {
+            let Some(series) = program.series_info.as_ref() else {
+                continue;
+            };
+            let id = SeriesSubscription::key(program.network_id, series.id);
+            let Some(subscription) = subscriptions.get_mut(&id) else {
+                continue;
+            };
+            let end_at = program.start_at + chrono::Duration::milliseconds(program.duration_millis);
+            if end_at <= now {
+                continue;
+            }
+            if subscription.update_from(series) && !updated.contains(&id) {
+                updated.push(id.clone());
+            }
+
+            let existing = self
+                .schedules
+                .get_schedule(&program.mirakc_url, program.id)
+                .await?;
+            let scheduled = existing
+                .as_ref()
+                .is_some_and(|s| s.series.as_deref() == Some(id.as_str()));
+            match subscription.evaluate(&program) {
+                SeriesVerdict::Record { episode } => {
+                    if scheduled {
+                        continue;
+                    }
+                    let mut desired =
+                        existing.unwrap_or_else(|| DesiredSchedule::from_program(&program));
+                    desired.series = Some(id.clone());
+                    self.schedules.save_schedule(&desired).await?;
+                    info!(
+                        subscription_id = %id,
+                        program_id = program.id,
+                        episode,
+                        "シリーズの放送を録画予約しました"
+                    );
+                    summary.scheduled += 1;
+                }
+                SeriesVerdict::Skip(reason) => {
+                    debug!(
+                        subscription_id = %id,
+                        program_id = program.id,
+                        %reason,
+                        "シリーズの放送を録画しません"
+                    );
+                    if let Some(schedule) = existing.filter(|_| scheduled) {
+                        self.remove_series(schedule).await?;
+                        summary.unscheduled += 1;
+                    }
+                }
+            }
+        }
+
+        for id in updated {
+            self.subscriptions
+                .save_subscription(&subscriptions[&id])
+                .await?;
+        }
+        Ok(summary)
+    }
+
+    /// シリーズ録画による録画予約をすべて取り消す。
+    async fn unschedule_series(&self, subscription_id: &str) -> Result<SeriesApplySummary> {
+        let mut summary = SeriesApplySummary::default();
+        for schedule in self.schedules.list_schedules().await? {
+            if schedule.series.as_deref() == Some(subscription_id) {
+                self.remove_series(schedule).await?;
+                summary.unscheduled += 1;
+            }
+        }
+        Ok(summary)
+    }
+
+    /// 録画予約からシリーズ録画を外す。ほかに録画する理由がなければ削除する。
+    async fn remove_series(&self, mut schedule: DesiredSchedule) -> Result<()> {
+        schedule.series = None;
+        if schedule.is_wanted() {
+            self.schedules.save_schedule(&schedule).await
+        } else {
+            self.schedules
+                .delete_schedule(&schedule.mirakc_url, schedule.program_id)
+                .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::epg::KurecSeriesInfo;
+    use crate::models::record::{Record, RecordContent, RecordingInfo};
+    use crate::ports::mirakc_api::RecordStream;
+    use async_trait::async_trait;
+    use chrono::TimeZone;
+    use std::sync::Mutex;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn program(event_id: i64, episode: i64, repeat: i64, day: u32) -> KurecProgram {
+        KurecProgram {
+            id: 327360102400000 + event_id,
+            mirakc_url: MIRAKC_URL.to_string(),
+            service_id: 3273601024,
+            network_id: 32736,
+            event_id,
+            channel_name: "ＮＨＫ総合１・東京".to_string(),
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+            name: Some(format!("機動戦士ガンダム #{}", episode)),
+            description: None,
+            extended: None,
+            start_at: Utc.with_ymd_and_hms(2025, 1, day, 9, 0, 0).unwrap(),
+            duration_millis: 1800000,
+            is_free: true,
+            genres: vec![],
+            video_info: None,
+            audio_infos: vec![],
+            series_info: Some(KurecSeriesInfo {
+                id: 100,
+                repeat,
+                pattern: 1,
+                expire_at: None,
+                episode,
+                last_episode: 3,
+                name: "機動戦士ガンダム".to_string(),
+            }),
+            markers: Default::default(),
+            episode_number: None,
+        }
+    }
+
+    fn record(id: &str, program: KurecProgram) -> Record {
+        Record {
+            id: id.to_string(),
+            mirakc_url: MIRAKC_URL.to_string(),
+            recording: RecordingInfo {
+                status: RecordingStatus::Finished,
+                start_time: program.start_at,
+                end_time: None,
+                duration_millis: None,
+                failed_reason: None,
+            },
+            program,
+            content: RecordContent {
+                path: format!("{}.m2ts", id),
+                content_type: "video/MP2T".to_string(),
+                length: Some(1),
+            },
+            tags: vec![],
+        }
+    }
+
+    #[derive(Default)]
+    struct MockApi {
+        records: Mutex<Vec<Record>>,
+    }
+
+    #[async_trait]
+    impl MirakcRecordsApi for MockApi {
+        async fn get_records(&self, _mirakc_url: &str) -> Result<Vec<Record>> {
+            Ok(self.records.lock().unwrap().clone())
+        }
+
+        async fn get_record(&self, _mirakc_url: &str, record_id: &str) -> Result<Option<Record>> {
+            Ok(self
+                .records
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|r| r.id == record_id)
+                .cloned())
+        }
+
+        async fn get_record_stream(
+            &self,
+            _mirakc_url: &str,
+            _record_id: &str,
+            _offset: u64,
+        ) -> Result<RecordStream> {
+            unimplemented!()
+        }
+
+        async fn remove_record(&self, _mirakc_url: &str, _record_id: &str) -> Result<()> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(Default)]
+    struct MockPrograms {
+        programs: Mutex<Vec<KurecProgram>>,
+    }
+
+    #[async_trait]
+    impl KurecProgramRepository for MockPrograms {
+        async fn save_service_programs(
+            &self,
+            _mirakc_url: &str,
+            _service_id: i64,
+            programs: Vec<KurecProgram>,
+        ) -> Result<()> {
+            *self.programs.lock().unwrap() = programs;
+            Ok(())
+        }
+
+        async fn get_service_programs(
+            &self,
+            _mirakc_url: &str,
+            _service_id: i64,
+        ) -> Result<Option<Vec<KurecProgram>>> {
+            Ok(Some(self.programs.lock().unwrap().clone()))
+        }
+
+        async fn list_all_programs(&self) -> Result<Vec<KurecProgram>> {
+            Ok(self.programs.lock().unwrap().clone())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockRecords {
+        records: Mutex<Vec<Record>>,
+    }
+
+    #[async_trait]
+    impl RecordRepository for MockRecords {
+        async fn save_record(&self, record: &Record) -> Result<()> {
+            self.records.lock().unwrap().push(record.clone());
+            Ok(())
+        }
+
+        async fn get_record(&self, _mirakc_url: &str, record_id: &str) -> Result<Option<Record>> {
+            Ok(self
+                .records
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|r| r.id == record_id)
+                .cloned())
+        }
+
+        async fn list_records(&self) -> Result<Vec<Record>> {
+            Ok(self.records.lock().unwrap().clone())
+        }
+
+        async fn remove_record(&self, _mirakc_url: &str, record_id: &str) -> Result<()> {
+            self.records.lock().unwrap().retain(|r| r.id != record_id);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSubscriptions {
+        subscriptions: Mutex<HashMap<String, SeriesSubscription>>,
+    }
+
+    #[async_trait]
+    impl SeriesSubscriptionRepository for MockSubscriptions {
+        async fn save_subscription(&self, subscription: &SeriesSubscription) -> Result<()> {
+            self.subscriptions
+                .lock()
+                .unwrap()
+                .insert(subscription.id.clone(), subscription.clone());
+            Ok(())
+        }
+
+        async fn get_subscription(&self, id: &str) -> Result<Option<SeriesSubscription>> {
+            Ok(self.subscriptions.lock().unwrap().get(id).cloned())
+        }
+
+        async fn list_subscriptions(&self) -> Result<Vec<SeriesSubscription>> {
+            Ok(self
+                .subscriptions
+                .lock()
+                .unwrap()
+                .values()
+                .cloned()
+                .collect())
+        }
+
+        async fn delete_subscription(&self, id: &str) -> Result<()> {
+            self.subscriptions.lock().unwrap().remove(id);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSchedules {
+        schedules: Mutex<HashMap<i64, DesiredSchedule>>,
+    }
+
+    #[async_trait]
+    impl DesiredScheduleRepository for MockSchedules {
+        async fn save_schedule(&self, schedule: &DesiredSchedule) -> Result<()> {
+            self.schedules
+                .lock()
+                .unwrap()
+                .insert(schedule.program_id, schedule.clone());
+            Ok(())
+        }
+
+        async fn get_schedule(
+            &self,
+            _mirakc_url: &str,
+            program_id: i64,
+        ) -> Result<Option<DesiredSchedule>> {
+            Ok(self.schedules.lock().unwrap().get(&program_id).cloned())
+        }
+
+        async fn list_schedules(&self) -> Result<Vec<DesiredSchedule>> {
+            Ok(self.schedules.lock().unwrap().values().cloned().collect())
+        }
+
+        async fn delete_schedule(&self, _mirakc_url: &str, program_id: i64) -> Result<()> {
+            self.schedules.lock().unwrap().remove(&program_id);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSink {
+        events: Mutex<Vec<SeriesCompletedEvent>>,
+    }
+
+    #[async_trait]
+    impl EventSink<SeriesCompletedEvent> for MockSink {
+        async fn publish(&self, event: SeriesCompletedEvent) -> Result<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        api: Arc<MockApi>,
+        programs: Arc<MockPrograms>,
+        records: Arc<MockRecords>,
+        subscriptions: Arc<MockSubscriptions>,
+        schedules: Arc<MockSchedules>,
+        sink: Arc<MockSink>,
+        usecase: SeriesTrackingUseCase,
+    }
+
+    fn fixture() -> Fixture {
+        let api = Arc::new(MockApi::default());
+        let programs = Arc::new(MockPrograms::default());
+        let records = Arc::new(MockRecords::default());
+        let subscriptions = Arc::new(MockSubscriptions::default());
+        let schedules = Arc::new(MockSchedules::default());
+        let sink = Arc::new(MockSink::default());
+        let usecase = SeriesTrackingUseCase::new(
+            api.clone(),
+            programs.clone(),
+            records.clone(),
+            subscriptions.clone(),
+            schedules.clone(),
+            sink.clone(),
+        );
+        Fixture {
+            api,
+            programs,
+            records,
+            subscriptions,
+            schedules,
+            sink,
+            usecase,
+        }
+    }
+
+    fn scheduled_ids(f: &Fixture) -> Vec<i64> {
+        let mut ids: Vec<_> = f
+            .schedules
+            .schedules
+            .lock()
+            .unwrap()
+            .keys()
+            .copied()
+            .collect();
+        ids.sort();
+        ids.into_iter().map(|id| id - 327360102400000).collect()
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_schedules_future_first_runs() {
+        let f = fixture();
+        // 第1話は録画済み
+        f.records
+            .save_record(&record("r1", program(1, 1, 0, 1)))
+            .await
+            .unwrap();
+        *f.programs.programs.lock().unwrap() = vec![
+            program(11, 1, 1, 2),  // 第1話の再放送
+            program(12, 2, 0, 8),  // 第2話
+            program(13, 2, 1, 9),  // 第2話の再放送
+            program(14, 3, 0, 15), // 第3話 (最終話)
+        ];
+
+        let (subscription, summary) = f
+            .usecase
+            .subscribe(MIRAKC_URL, 327360102400012, now())
+            .await
+            .unwrap();
+        assert_eq!(subscription.id, "32736_100");
+        assert_eq!(subscription.recorded_episodes, vec![1]);
+        assert_eq!(summary.scheduled, 2);
+        assert_eq!(scheduled_ids(&f), vec![12, 14]);
+
+        let schedule = f
+            .schedules
+            .get_schedule(MIRAKC_URL, 327360102400012)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(schedule.tags(), vec!["kurec", "series:32736_100"]);
+
+        // 同じ EPG の再評価では何も変わらない
+        let summary = f
+            .usecase
+            .handle_epg_stored(
+                &EpgStoredEvent {
+                    mirakc_url: MIRAKC_URL.to_string(),
+                    service_id: 3273601024,
+                },
+                now(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(summary, SeriesApplySummary::default());
+
+        assert!(f.usecase.subscribe(MIRAKC_URL, 999, now()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_final_episode_completes_subscription() {
+        let f = fixture();
+        *f.programs.programs.lock().unwrap() = vec![program(12, 2, 0, 8), program(14, 3, 0, 15)];
+        f.usecase
+            .subscribe(MIRAKC_URL, 327360102400012, now())
+            .await
+            .unwrap();
+
+        // 第3話が先に録画された (第2話は放送前)
+        f.api
+            .records
+            .lock()
+            .unwrap()
+            .push(record("r3", program(14, 3, 0, 15)));
+        let event = RecordingRecordSavedEvent {
+            mirakc_url: MIRAKC_URL.to_string(),
+            record_id: "r3".to_string(),
+            recording_status: RecordingStatus::Finished,
+            received_at: now(),
+        };
+        f.usecase.handle_record_saved(&event).await.unwrap();
+
+        let subscription = f
+            .subscriptions
+            .get_subscription("32736_100")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(subscription.status, SeriesStatus::Completed);
+        assert!(scheduled_ids(&f).is_empty());
+        let events = f.sink.events.lock().unwrap().clone();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].recorded_episodes, vec![3]);
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_keeps_rule_schedules() {
+        let f = fixture();
+        *f.programs.programs.lock().unwrap() = vec![program(12, 2, 0, 8), program(14, 3, 0, 15)];
+        f.usecase
+            .subscribe(MIRAKC_URL, 327360102400012, now())
+            .await
+            .unwrap();
+        let mut manual = f
+            .schedules
+            .get_schedule(MIRAKC_URL, 327360102400014)
+            .await
+            .unwrap()
+            .unwrap();
+        manual.manual = true;
+        f.schedules.save_schedule(&manual).await.unwrap();
+
+        let summary = f.usecase.unsubscribe("32736_100").await.unwrap();
+        assert_eq!(summary.unscheduled, 2);
+        assert_eq!(scheduled_ids(&f), vec![14]);
+        assert!(f.usecase.unsubscribe("32736_100").await.is_err());
+    }
+}
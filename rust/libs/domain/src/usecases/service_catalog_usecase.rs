@@ -0,0 +1,398 @@
+//! サービスカタログ同期ユースケース
+//!
+//! mirakc のサービス・チャンネル一覧とサービスロゴを `ServiceRepository` / `ServiceLogoRepository` に同期し、
+//! 前回の同期からの差分を `ServiceAddedEvent` / `ServiceRemovedEvent` / `ServiceRenamedEvent` として発行します。
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use tracing::{info, warn};
+
+use crate::events::kurec_events::{ServiceAddedEvent, ServiceRemovedEvent, ServiceRenamedEvent};
+use crate::models::service::{Service, ServiceCatalogDiff};
+use crate::ports::event_sink::EventSink;
+use crate::ports::mirakc_api::MirakcServicesApi;
+use crate::ports::repositories::service_repository::{ServiceLogoRepository, ServiceRepository};
+
+/// サービスカタログの変更イベントの発行先
+pub struct ServiceCatalogSinks {
+    pub service_added: Arc<dyn EventSink<ServiceAddedEvent>>,
+    pub service_removed: Arc<dyn EventSink<ServiceRemovedEvent>>,
+    pub service_renamed: Arc<dyn EventSink<ServiceRenamedEvent>>,
+}
+
+/// サービスカタログ同期ユースケース
+pub struct ServiceCatalogUseCase {
+    api: Arc<dyn MirakcServicesApi>,
+    repository: Arc<dyn ServiceRepository>,
+    logo_repository: Arc<dyn ServiceLogoRepository>,
+    sinks: ServiceCatalogSinks,
+}
+
+impl ServiceCatalogUseCase {
+    /// 新しいServiceCatalogUseCaseを作成
+    pub fn new(
+        api: Arc<dyn MirakcServicesApi>,
+        repository: Arc<dyn ServiceRepository>,
+        logo_repository: Arc<dyn ServiceLogoRepository>,
+        sinks: ServiceCatalogSinks,
+    ) -> Self {
+        Self {
+            api,
+            repository,
+            logo_repository,
+            sinks,
+        }
+    }
+
+    /// mirakc のサービスカタログを同期し、前回の同期からの差分をイベントとして発行する。
+    ///
+    /// ロゴ画像は追加されたサービスとロゴが変わったサービスについてのみ取得する。
+    /// ロゴの取得に失敗してもカタログの同期は続行する。
+    ///
+    /// # Returns
+    ///
+    /// 前回の同期からの差分
+    pub async fn sync(&self, mirakc_url: &str) -> Result<ServiceCatalogDiff> {
+        let services = self.api.get_services(mirakc_url).await?;
+        let channels = self.api.get_channels(mirakc_url).await?;
+        let previous = self.repository.list_services(mirakc_url).await?;
+        let diff = ServiceCatalogDiff::between(&previous, &services);
+
+        for service in &services {
+            let logo_changed = previous
+                .iter()
+                .find(|p| p.id == service.id)
+                .is_none_or(|p| {
+                    p.logo_id != service.logo_id || p.has_logo_data != service.has_logo_data
+                });
+            if service.has_logo_data && logo_changed {
+                self.sync_logo(service).await;
+            }
+        }
+        for service in &diff.removed {
+            if let Err(e) = self
+                .logo_repository
+                .delete_logo(mirakc_url, service.id)
+                .await
+            {
+                warn!(id = service.id, "ロゴ画像の削除に失敗しました: {:?}", e);
+            }
+        }
+
+        self.repository.save_services(mirakc_url, &services).await?;
+        self.repository.save_channels(mirakc_url, &channels).await?;
+
+        if !diff.is_empty() {
+            info!(
+                mirakc_url,
+                added = diff.added.len(),
+                removed = diff.removed.len(),
+                renamed = diff.renamed.len(),
+                "サービスカタログが変わりました"
+            );
+        }
+        for service in &diff.added {
+            self.sinks
+                .service_added
+                .publish(ServiceAddedEvent {
+                    service: service.clone(),
+                })
+                .await?;
+        }
+        for service in &diff.removed {
+            self.sinks
+                .service_removed
+                .publish(ServiceRemovedEvent {
+                    service: service.clone(),
+                })
+                .await?;
+        }
+        for rename in &diff.renamed {
+            self.sinks
+                .service_renamed
+                .publish(ServiceRenamedEvent {
+                    previous_name: rename.previous_name.clone(),
+                    service: rename.service.clone(),
+                })
+                .await?;
+        }
+        Ok(diff)
+    }
+
+    /// サービスのロゴ画像を取得して保存する。
+    async fn sync_logo(&self, service: &Service) {
+        let result = match self
+            .api
+            .get_logo_image(&service.mirakc_url, service.id)
+            .await
+        {
+            Ok(Some(image)) => {
+                self.logo_repository
+                    .save_logo(&service.mirakc_url, service.id, image)
+                    .await
+            }
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!(id = service.id, "ロゴ画像の同期に失敗しました: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::service::Channel;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+
+    fn service(service_id: i64, name: &str, logo_id: Option<i64>) -> Service {
+        Service {
+            mirakc_url: MIRAKC_URL.to_string(),
+            id: 32736 * 100000 + service_id,
+            service_id,
+            network_id: 32736,
+            name: name.to_string(),
+            service_type: 1,
+            logo_id,
+            remote_control_key_id: None,
+            has_logo_data: logo_id.is_some(),
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+        }
+    }
+
+    #[derive(Default)]
+    struct MockServicesApi {
+        services: Mutex<Vec<Service>>,
+        logo_requests: Mutex<Vec<i64>>,
+    }
+
+    #[async_trait]
+    impl MirakcServicesApi for MockServicesApi {
+        async fn get_services(&self, _mirakc_url: &str) -> Result<Vec<Service>> {
+            Ok(self.services.lock().unwrap().clone())
+        }
+
+        async fn get_channels(&self, mirakc_url: &str) -> Result<Vec<Channel>> {
+            Ok(vec![Channel {
+                mirakc_url: mirakc_url.to_string(),
+                channel_type: "GR".to_string(),
+                channel: "27".to_string(),
+                name: "NHK".to_string(),
+                service_ids: self.services.lock().unwrap().iter().map(|s| s.id).collect(),
+            }])
+        }
+
+        async fn get_logo_image(&self, _mirakc_url: &str, id: i64) -> Result<Option<Vec<u8>>> {
+            self.logo_requests.lock().unwrap().push(id);
+            Ok(Some(vec![0x89, 0x50, 0x4e, 0x47]))
+        }
+    }
+
+    #[derive(Default)]
+    struct MockServiceRepository {
+        services: Mutex<HashMap<String, Vec<Service>>>,
+        channels: Mutex<HashMap<String, Vec<Channel>>>,
+    }
+
+    #[async_trait]
+    impl ServiceRepository for MockServiceRepository {
+        async fn save_services(&self, mirakc_url: &str, services: &[Service]) -> Result<()> {
+            self.services
+                .lock()
+                .unwrap()
+                .insert(mirakc_url.to_string(), services.to_vec());
+            Ok(())
+        }
+
+        async fn list_services(&self, mirakc_url: &str) -> Result<Vec<Service>> {
+            Ok(self
+                .services
+                .lock()
+                .unwrap()
+                .get(mirakc_url)
+                .cloned()
+                .unwrap_or_default())
+        }
+
+        async fn list_all_services(&self) -> Result<Vec<Service>> {
+            Ok(self
+                .services
+                .lock()
+                .unwrap()
+                .values()
+                .flatten()
+                .cloned()
+                .collect())
+        }
+
+        async fn save_channels(&self, mirakc_url: &str, channels: &[Channel]) -> Result<()> {
+            self.channels
+                .lock()
+                .unwrap()
+                .insert(mirakc_url.to_string(), channels.to_vec());
+            Ok(())
+        }
+
+        async fn list_channels(&self, mirakc_url: &str) -> Result<Vec<Channel>> {
+            Ok(self
+                .channels
+                .lock()
+                .unwrap()
+                .get(mirakc_url)
+                .cloned()
+                .unwrap_or_default())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockLogoRepository {
+        logos: Mutex<HashMap<i64, Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl ServiceLogoRepository for MockLogoRepository {
+        async fn save_logo(&self, _mirakc_url: &str, id: i64, image: Vec<u8>) -> Result<()> {
+            self.logos.lock().unwrap().insert(id, image);
+            Ok(())
+        }
+
+        async fn get_logo(&self, _mirakc_url: &str, id: i64) -> Result<Option<Vec<u8>>> {
+            Ok(self.logos.lock().unwrap().get(&id).cloned())
+        }
+
+        async fn delete_logo(&self, _mirakc_url: &str, id: i64) -> Result<()> {
+            self.logos.lock().unwrap().remove(&id);
+            Ok(())
+        }
+    }
+
+    struct MockSink<E> {
+        events: Mutex<Vec<E>>,
+    }
+
+    impl<E> Default for MockSink<E> {
+        fn default() -> Self {
+            Self {
+                events: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl<E: crate::event::Event> EventSink<E> for MockSink<E> {
+        async fn publish(&self, event: E) -> Result<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        api: Arc<MockServicesApi>,
+        repository: Arc<MockServiceRepository>,
+        logos: Arc<MockLogoRepository>,
+        added: Arc<MockSink<ServiceAddedEvent>>,
+        removed: Arc<MockSink<ServiceRemovedEvent>>,
+        renamed: Arc<MockSink<ServiceRenamedEvent>>,
+        usecase: ServiceCatalogUseCase,
+    }
+
+    fn fixture() -> Fixture {
+        let api = Arc::new(MockServicesApi::default());
+        let repository = Arc::new(MockServiceRepository::default());
+        let logos = Arc::new(MockLogoRepository::default());
+        let added = Arc::new(MockSink::default());
+        let removed = Arc::new(MockSink::default());
+        let renamed = Arc::new(MockSink::default());
+        let usecase = ServiceCatalogUseCase::new(
+            api.clone(),
+            repository.clone(),
+            logos.clone(),
+            ServiceCatalogSinks {
+                service_added: added.clone(),
+                service_removed: removed.clone(),
+                service_renamed: renamed.clone(),
+            },
+        );
+        Fixture {
+            api,
+            repository,
+            logos,
+            added,
+            removed,
+            renamed,
+            usecase,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_publishes_catalog_changes() {
+        let f = fixture();
+
+        *f.api.services.lock().unwrap() = vec![
+            service(1024, "ＮＨＫ総合", Some(0)),
+            service(1025, "ＮＨＫ総合２", None),
+        ];
+        let diff = f.usecase.sync(MIRAKC_URL).await.unwrap();
+        assert_eq!(diff.added.len(), 2);
+        assert_eq!(f.added.events.lock().unwrap().len(), 2);
+        assert_eq!(
+            f.repository.list_channels(MIRAKC_URL).await.unwrap().len(),
+            1
+        );
+
+        // チャンネルスキャン後: 1025 が消え、1024 の名前が変わり、1032 が追加された
+        *f.api.services.lock().unwrap() = vec![
+            service(1024, "ＮＨＫ総合１・東京", Some(0)),
+            service(1032, "ＮＨＫＥテレ", Some(1)),
+        ];
+        let diff = f.usecase.sync(MIRAKC_URL).await.unwrap();
+        assert_eq!(diff.added.len(), 1);
+
+        assert_eq!(f.added.events.lock().unwrap()[2].service.service_id, 1032);
+        let removed = f.removed.events.lock().unwrap().clone();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].service.service_id, 1025);
+        let renamed = f.renamed.events.lock().unwrap().clone();
+        assert_eq!(renamed.len(), 1);
+        assert_eq!(renamed[0].previous_name, "ＮＨＫ総合");
+        assert_eq!(renamed[0].service.name, "ＮＨＫ総合１・東京");
+
+        let saved = f.repository.list_services(MIRAKC_URL).await.unwrap();
+        assert_eq!(saved.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sync_fetches_logos_only_when_changed() {
+        let f = fixture();
+        *f.api.services.lock().unwrap() = vec![
+            service(1024, "ＮＨＫ総合", Some(0)),
+            service(1025, "ＮＨＫ総合２", None),
+        ];
+
+        f.usecase.sync(MIRAKC_URL).await.unwrap();
+        f.usecase.sync(MIRAKC_URL).await.unwrap();
+        assert_eq!(*f.api.logo_requests.lock().unwrap(), vec![3273601024]);
+        assert!(f
+            .logos
+            .get_logo(MIRAKC_URL, 3273601024)
+            .await
+            .unwrap()
+            .is_some());
+
+        *f.api.services.lock().unwrap() = vec![service(1025, "ＮＨＫ総合２", None)];
+        f.usecase.sync(MIRAKC_URL).await.unwrap();
+        assert!(f
+            .logos
+            .get_logo(MIRAKC_URL, 3273601024)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
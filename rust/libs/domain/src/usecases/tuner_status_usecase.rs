@@ -0,0 +1,346 @@
+//! チューナー状態ユースケース
+//!
+//! mirakc のチューナー状態を `TunerStatusRepository` に投影し、
+//! 使用区間を `TunerUsageHistoryRepository` に記録します。
+//! また、記録した使用履歴に対する問い合わせを提供します。
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use tracing::debug;
+
+use crate::events::mirakc_events::TunerStatusChangedEvent;
+use crate::models::tuner::{Tuner, TunerStatus, TunerUsageInterval};
+use crate::ports::mirakc_api::MirakcTunersApi;
+use crate::ports::repositories::tuner_repository::{
+    TunerStatusRepository, TunerUsageHistoryRepository,
+};
+
+/// チューナー状態ユースケース
+pub struct TunerStatusUseCase {
+    api: Arc<dyn MirakcTunersApi>,
+    status_repository: Arc<dyn TunerStatusRepository>,
+    history_repository: Arc<dyn TunerUsageHistoryRepository>,
+}
+
+impl TunerStatusUseCase {
+    /// 新しいTunerStatusUseCaseを作成
+    pub fn new(
+        api: Arc<dyn MirakcTunersApi>,
+        status_repository: Arc<dyn TunerStatusRepository>,
+        history_repository: Arc<dyn TunerUsageHistoryRepository>,
+    ) -> Self {
+        Self {
+            api,
+            status_repository,
+            history_repository,
+        }
+    }
+
+    /// mirakc のすべてのチューナーの状態を取得して反映する。
+    pub async fn sync_all(&self, mirakc_url: &str, at: DateTime<Utc>) -> Result<usize> {
+        let tuners = self.api.get_tuners(mirakc_url).await?;
+        let count = tuners.len();
+        for tuner in tuners {
+            self.apply(mirakc_url, tuner, at).await?;
+        }
+        Ok(count)
+    }
+
+    /// チューナー状態変更イベントを反映する。
+    pub async fn handle_status_changed(&self, event: &TunerStatusChangedEvent) -> Result<()> {
+        let tuner = self
+            .api
+            .get_tuner(&event.mirakc_url, event.tuner_index as i64)
+            .await?;
+        self.apply(&event.mirakc_url, tuner, event.received_at)
+            .await
+    }
+
+    /// チューナー情報を現在の状態として保存し、使用区間が終了していれば履歴に追記する。
+    async fn apply(&self, mirakc_url: &str, tuner: Tuner, at: DateTime<Utc>) -> Result<()> {
+        let mut status = TunerStatus::from_tuner(mirakc_url, tuner, at);
+        let previous = self
+            .status_repository
+            .get_status(mirakc_url, status.index)
+            .await?;
+
+        if let Some(previous) = previous {
+            if previous.same_usage(&status) {
+                // 状態が変わっていなければ開始時刻を引き継ぐ
+                status.since = previous.since;
+            } else if previous.in_use {
+                let interval = TunerUsageInterval::from_status(&previous, Some(at));
+                debug!(
+                    tuner_index = previous.index,
+                    started_at = %interval.started_at,
+                    "チューナーの使用区間を記録します"
+                );
+                self.history_repository.append_interval(&interval).await?;
+            }
+        }
+
+        self.status_repository.save_status(&status).await
+    }
+
+    /// 期間 `[from, to)` と重なる使用区間を取得する。
+    ///
+    /// 現在使用中のチューナーは `ended_at` が `None` の区間として含まれる。
+    pub async fn usage_intervals(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<TunerUsageInterval>> {
+        let mut intervals = self.history_repository.list_intervals(from, to).await?;
+        intervals.extend(
+            self.status_repository
+                .list_statuses()
+                .await?
+                .iter()
+                .filter(|s| s.in_use)
+                .map(|s| TunerUsageInterval::from_status(s, None))
+                .filter(|i| i.overlaps(from, to)),
+        );
+        intervals.sort_by_key(|i| i.started_at);
+        Ok(intervals)
+    }
+
+    /// 指定時刻に使用中だったチューナーの使用区間を取得する。
+    ///
+    /// # Arguments
+    ///
+    /// * `at` - 対象の時刻
+    /// * `channel_type` - 指定した場合、そのチャンネルタイプに対応するチューナーに限定する
+    pub async fn busy_tuners_at(
+        &self,
+        at: DateTime<Utc>,
+        channel_type: Option<&str>,
+    ) -> Result<Vec<TunerUsageInterval>> {
+        let to = at + chrono::Duration::milliseconds(1);
+        Ok(self
+            .usage_intervals(at, to)
+            .await?
+            .into_iter()
+            .filter(|i| i.contains(at))
+            .filter(|i| channel_type.is_none_or(|t| i.supports(t)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::tuner::TunerUser;
+    use async_trait::async_trait;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    const MIRAKC_URL: &str = "http://tuner:40772";
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).unwrap()
+    }
+
+    fn tuner(index: i64, types: &[&str], user: Option<&str>) -> Tuner {
+        Tuner {
+            index,
+            name: format!("tuner{}", index),
+            types: types.iter().map(|t| t.to_string()).collect(),
+            is_using: user.is_some(),
+            users: user
+                .map(|id| TunerUser {
+                    id: id.to_string(),
+                    agent: None,
+                    priority: 0,
+                })
+                .into_iter()
+                .collect(),
+            command: user.map(|_| "recpt1 27 - -".to_string()),
+        }
+    }
+
+    #[derive(Default)]
+    struct MockTunersApi {
+        tuners: Mutex<HashMap<i64, Tuner>>,
+    }
+
+    impl MockTunersApi {
+        fn set(&self, tuner: Tuner) {
+            self.tuners.lock().unwrap().insert(tuner.index, tuner);
+        }
+    }
+
+    #[async_trait]
+    impl MirakcTunersApi for MockTunersApi {
+        async fn get_tuners(&self, _mirakc_url: &str) -> Result<Vec<Tuner>> {
+            Ok(self.tuners.lock().unwrap().values().cloned().collect())
+        }
+
+        async fn get_tuner(&self, _mirakc_url: &str, index: i64) -> Result<Tuner> {
+            self.tuners
+                .lock()
+                .unwrap()
+                .get(&index)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("tuner not found"))
+        }
+    }
+
+    #[derive(Default)]
+    struct MockStatusRepository {
+        statuses: Mutex<HashMap<(String, i64), TunerStatus>>,
+    }
+
+    #[async_trait]
+    impl TunerStatusRepository for MockStatusRepository {
+        async fn save_status(&self, status: &TunerStatus) -> Result<()> {
+            self.statuses
+                .lock()
+                .unwrap()
+                .insert((status.mirakc_url.clone(), status.index), status.clone());
+            Ok(())
+        }
+
+        async fn get_status(&self, mirakc_url: &str, index: i64) -> Result<Option<TunerStatus>> {
+            Ok(self
+                .statuses
+                .lock()
+                .unwrap()
+                .get(&(mirakc_url.to_string(), index))
+                .cloned())
+        }
+
+        async fn list_statuses(&self) -> Result<Vec<TunerStatus>> {
+            Ok(self.statuses.lock().unwrap().values().cloned().collect())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockHistoryRepository {
+        intervals: Mutex<Vec<TunerUsageInterval>>,
+    }
+
+    #[async_trait]
+    impl TunerUsageHistoryRepository for MockHistoryRepository {
+        async fn append_interval(&self, interval: &TunerUsageInterval) -> Result<()> {
+            self.intervals.lock().unwrap().push(interval.clone());
+            Ok(())
+        }
+
+        async fn list_intervals(
+            &self,
+            from: DateTime<Utc>,
+            to: DateTime<Utc>,
+        ) -> Result<Vec<TunerUsageInterval>> {
+            Ok(self
+                .intervals
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|i| i.overlaps(from, to))
+                .cloned()
+                .collect())
+        }
+    }
+
+    struct Fixture {
+        api: Arc<MockTunersApi>,
+        history: Arc<MockHistoryRepository>,
+        usecase: TunerStatusUseCase,
+    }
+
+    fn setup() -> Fixture {
+        let api = Arc::new(MockTunersApi::default());
+        let status = Arc::new(MockStatusRepository::default());
+        let history = Arc::new(MockHistoryRepository::default());
+        let usecase = TunerStatusUseCase::new(api.clone(), status, history.clone());
+        Fixture {
+            api,
+            history,
+            usecase,
+        }
+    }
+
+    fn event(index: usize, received_at: DateTime<Utc>) -> TunerStatusChangedEvent {
+        TunerStatusChangedEvent {
+            mirakc_url: MIRAKC_URL.to_string(),
+            tuner_index: index,
+            received_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_usage_interval_is_recorded_when_tuner_released() {
+        let f = setup();
+        f.api.set(tuner(0, &["GR"], None));
+        f.usecase.sync_all(MIRAKC_URL, at(18)).await.unwrap();
+
+        f.api.set(tuner(0, &["GR"], Some("rec1")));
+        f.usecase
+            .handle_status_changed(&event(0, at(20)))
+            .await
+            .unwrap();
+        // 同じ状態の通知では区間は分割されない
+        f.usecase
+            .handle_status_changed(&event(0, at(21)))
+            .await
+            .unwrap();
+        f.api.set(tuner(0, &["GR"], None));
+        f.usecase
+            .handle_status_changed(&event(0, at(22)))
+            .await
+            .unwrap();
+
+        let intervals = f.history.intervals.lock().unwrap().clone();
+        assert_eq!(intervals.len(), 1);
+        assert_eq!(intervals[0].started_at, at(20));
+        assert_eq!(intervals[0].ended_at, Some(at(22)));
+        assert_eq!(intervals[0].users[0].id, "rec1");
+    }
+
+    #[tokio::test]
+    async fn test_user_change_splits_interval() {
+        let f = setup();
+        f.api.set(tuner(0, &["GR"], Some("rec1")));
+        f.usecase.sync_all(MIRAKC_URL, at(20)).await.unwrap();
+
+        f.api.set(tuner(0, &["GR"], Some("rec2")));
+        f.usecase
+            .handle_status_changed(&event(0, at(21)))
+            .await
+            .unwrap();
+
+        let intervals = f.history.intervals.lock().unwrap().clone();
+        assert_eq!(intervals.len(), 1);
+        assert_eq!(intervals[0].ended_at, Some(at(21)));
+    }
+
+    #[tokio::test]
+    async fn test_busy_tuners_at_filters_by_channel_type() {
+        let f = setup();
+        f.api.set(tuner(0, &["GR"], Some("rec1")));
+        f.api.set(tuner(1, &["GR"], Some("rec2")));
+        f.api.set(tuner(2, &["BS", "CS"], Some("rec3")));
+        f.usecase.sync_all(MIRAKC_URL, at(20)).await.unwrap();
+
+        // tuner1 は 21時前に解放
+        f.api.set(tuner(1, &["GR"], None));
+        f.usecase
+            .handle_status_changed(&event(1, at(21)))
+            .await
+            .unwrap();
+
+        let busy_gr = f.usecase.busy_tuners_at(at(20), Some("GR")).await.unwrap();
+        assert_eq!(busy_gr.len(), 2);
+
+        let busy_gr_later = f.usecase.busy_tuners_at(at(22), Some("GR")).await.unwrap();
+        assert_eq!(busy_gr_later.len(), 1);
+        assert_eq!(busy_gr_later[0].tuner_index, 0);
+        assert_eq!(busy_gr_later[0].ended_at, None);
+
+        let busy_all = f.usecase.busy_tuners_at(at(22), None).await.unwrap();
+        assert_eq!(busy_all.len(), 2);
+    }
+}
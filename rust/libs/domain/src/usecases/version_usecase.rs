@@ -1,6 +1,8 @@
-use crate::models::version::{Version, VersionStatus};
+use crate::models::version::{Version, VersionGatePolicy, VersionStatus};
 use crate::ports::repositories::version_repository::VersionRepository;
-use anyhow::Result;
+use anyhow::{bail, Result};
+use semver::VersionReq;
+use tracing::warn;
 
 /// mirakcバージョン確認ユースケース
 pub struct VersionUseCase<R: VersionRepository> {
@@ -19,6 +21,40 @@ impl<R: VersionRepository> VersionUseCase<R> {
         let status = version.version_status()?;
         Ok((version, status))
     }
+
+    /// mirakcのバージョンがサポート範囲内かどうかを確認
+    pub async fn check_compatibility(&self, requirement: &VersionReq) -> Result<(Version, bool)> {
+        let version = self.repository.get_version().await?;
+        let compatible = version.is_compatible_with(requirement)?;
+        Ok((version, compatible))
+    }
+
+    /// 起動時のバージョンゲート
+    ///
+    /// mirakcのバージョンがサポート範囲外の場合、`Strict` ではエラーを返し、
+    /// `Warn` では警告を出力して `Ok` を返す。
+    /// バージョンを取得できない場合も同様に扱う。
+    pub async fn ensure_compatible(
+        &self,
+        requirement: &VersionReq,
+        policy: VersionGatePolicy,
+    ) -> Result<()> {
+        let reason = match self.check_compatibility(requirement).await {
+            Ok((_, true)) => return Ok(()),
+            Ok((version, false)) => format!(
+                "mirakc {} はサポート範囲 ({}) 外です",
+                version.current, requirement
+            ),
+            Err(e) => format!("mirakcのバージョンを確認できません: {:#}", e),
+        };
+        match policy {
+            VersionGatePolicy::Strict => bail!(reason),
+            VersionGatePolicy::Warn => {
+                warn!("{}。一部の機能が正常に動作しない可能性があります", reason);
+                Ok(())
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -28,6 +64,10 @@ mod tests {
     use std::sync::Arc;
     use std::sync::Mutex;
 
+    fn supported() -> VersionReq {
+        VersionReq::parse(">=4.0.0, <5.0.0").unwrap()
+    }
+
     // モックリポジトリ
     struct MockVersionRepository {
         version: Arc<Mutex<Version>>,
@@ -87,4 +127,35 @@ mod tests {
         assert_eq!(version.latest, "1.9.0");
         assert_eq!(status, VersionStatus::Development);
     }
+
+    #[tokio::test]
+    async fn test_check_compatibility() {
+        let usecase = VersionUseCase::new(MockVersionRepository::new("4.0.0-dev.0", "3.4.1"));
+        let (version, compatible) = usecase.check_compatibility(&supported()).await.unwrap();
+        assert_eq!(version.current, "4.0.0-dev.0");
+        assert!(compatible);
+
+        let usecase = VersionUseCase::new(MockVersionRepository::new("3.4.1", "3.4.1"));
+        let (_, compatible) = usecase.check_compatibility(&supported()).await.unwrap();
+        assert!(!compatible);
+    }
+
+    #[tokio::test]
+    async fn test_ensure_compatible_by_policy() {
+        let usecase = VersionUseCase::new(MockVersionRepository::new("3.4.1", "3.4.1"));
+        assert!(usecase
+            .ensure_compatible(&supported(), VersionGatePolicy::Strict)
+            .await
+            .is_err());
+        assert!(usecase
+            .ensure_compatible(&supported(), VersionGatePolicy::Warn)
+            .await
+            .is_ok());
+
+        let usecase = VersionUseCase::new(MockVersionRepository::new("4.1.0", "4.1.0"));
+        assert!(usecase
+            .ensure_compatible(&supported(), VersionGatePolicy::Strict)
+            .await
+            .is_ok());
+    }
 }
@@ -0,0 +1,188 @@
+//! mirakcバージョン監視ユースケース
+//!
+//! 稼働中の mirakc のバージョンを定期的に確認し、
+//! バージョンが変わった場合に `MirakcVersionChangedEvent` を発行します。
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use semver::VersionReq;
+use tracing::{error, info};
+
+use crate::events::kurec_events::MirakcVersionChangedEvent;
+use crate::ports::event_sink::EventSink;
+use crate::ports::repositories::version_repository::VersionRepository;
+
+/// mirakcバージョン監視ユースケース
+pub struct VersionWatchUseCase<R: VersionRepository> {
+    mirakc_url: String,
+    repository: R,
+    requirement: VersionReq,
+    sink: Arc<dyn EventSink<MirakcVersionChangedEvent>>,
+    last_version: Mutex<Option<String>>,
+}
+
+impl<R: VersionRepository> VersionWatchUseCase<R> {
+    /// 新しいVersionWatchUseCaseを作成
+    pub fn new(
+        mirakc_url: impl Into<String>,
+        repository: R,
+        requirement: VersionReq,
+        sink: Arc<dyn EventSink<MirakcVersionChangedEvent>>,
+    ) -> Self {
+        Self {
+            mirakc_url: mirakc_url.into(),
+            repository,
+            requirement,
+            sink,
+            last_version: Mutex::new(None),
+        }
+    }
+
+    /// mirakcのバージョンを確認し、前回から変わっていればイベントを発行する。
+    ///
+    /// 初回の確認では基準となるバージョンを記録するだけで、イベントは発行しない。
+    ///
+    /// # Returns
+    ///
+    /// 発行したイベント。バージョンが変わっていない場合は `None`
+    pub async fn check(&self) -> Result<Option<MirakcVersionChangedEvent>> {
+        let version = self.repository.get_version().await?;
+        let previous = self.last_version.lock().unwrap().clone();
+
+        // 基準バージョンはイベントの発行に成功してから更新する。
+        // 途中で失敗した場合は次回の確認で同じ変化を再検出する。
+        let previous = match previous {
+            Some(previous) if previous != version.current => previous,
+            Some(_) => return Ok(None),
+            None => {
+                info!(mirakc_url = %self.mirakc_url, version = %version.current, "mirakcのバージョンを記録しました");
+                *self.last_version.lock().unwrap() = Some(version.current);
+                return Ok(None);
+            }
+        };
+
+        let compatible = version.is_compatible_with(&self.requirement)?;
+        if compatible {
+            info!(
+                mirakc_url = %self.mirakc_url,
+                "mirakcのバージョンが {} から {} に変わりました",
+                previous, version.current
+            );
+        } else {
+            error!(
+                mirakc_url = %self.mirakc_url,
+                "mirakcのバージョンが {} から {} に変わりました。サポート範囲 ({}) 外です",
+                previous, version.current, self.requirement
+            );
+        }
+
+        let event = MirakcVersionChangedEvent {
+            mirakc_url: self.mirakc_url.clone(),
+            previous,
+            current: version.current,
+            compatible,
+        };
+        self.sink.publish(event.clone()).await?;
+        *self.last_version.lock().unwrap() = Some(event.current.clone());
+        Ok(Some(event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::version::Version;
+    use async_trait::async_trait;
+
+    #[derive(Clone, Default)]
+    struct MockVersionRepository {
+        current: Arc<Mutex<String>>,
+    }
+
+    impl MockVersionRepository {
+        fn set(&self, current: &str) {
+            *self.current.lock().unwrap() = current.to_string();
+        }
+    }
+
+    #[async_trait]
+    impl VersionRepository for MockVersionRepository {
+        async fn get_version(&self) -> Result<Version> {
+            let current = self.current.lock().unwrap().clone();
+            Ok(Version {
+                latest: current.clone(),
+                current,
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSink {
+        events: Mutex<Vec<MirakcVersionChangedEvent>>,
+        fail_next: Mutex<bool>,
+    }
+
+    #[async_trait]
+    impl EventSink<MirakcVersionChangedEvent> for MockSink {
+        async fn publish(&self, event: MirakcVersionChangedEvent) -> Result<()> {
+            if std::mem::take(&mut *self.fail_next.lock().unwrap()) {
+                anyhow::bail!("publish failed");
+            }
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publishes_when_version_changes() {
+        let repository = MockVersionRepository::default();
+        let sink = Arc::new(MockSink::default());
+        let usecase = VersionWatchUseCase::new(
+            "http://tuner:40772",
+            repository.clone(),
+            VersionReq::parse(">=4.0.0, <5.0.0").unwrap(),
+            sink.clone(),
+        );
+
+        repository.set("4.0.0");
+        assert!(usecase.check().await.unwrap().is_none());
+        assert!(usecase.check().await.unwrap().is_none());
+
+        repository.set("4.1.0");
+        let event = usecase.check().await.unwrap().unwrap();
+        assert_eq!(event.previous, "4.0.0");
+        assert_eq!(event.current, "4.1.0");
+        assert!(event.compatible);
+
+        repository.set("5.0.0");
+        let event = usecase.check().await.unwrap().unwrap();
+        assert!(!event.compatible);
+
+        assert_eq!(sink.events.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_republishes_after_publish_failure() {
+        let repository = MockVersionRepository::default();
+        let sink = Arc::new(MockSink::default());
+        let usecase = VersionWatchUseCase::new(
+            "http://tuner:40772",
+            repository.clone(),
+            VersionReq::parse(">=4.0.0, <5.0.0").unwrap(),
+            sink.clone(),
+        );
+
+        repository.set("4.0.0");
+        assert!(usecase.check().await.unwrap().is_none());
+
+        repository.set("4.1.0");
+        *sink.fail_next.lock().unwrap() = true;
+        assert!(usecase.check().await.is_err());
+
+        let event = usecase.check().await.unwrap().unwrap();
+        assert_eq!(event.previous, "4.0.0");
+        assert_eq!(event.current, "4.1.0");
+        assert_eq!(sink.events.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,118 @@
+//! XMLTVエクスポートユースケース
+//!
+//! `KurecProgramRepository` に保存されたすべての mirakc の番組情報から XMLTV 文書を生成します。
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::models::xmltv::{build_xmltv, XmltvOptions};
+use crate::ports::repositories::kurec_program_repository::KurecProgramRepository;
+
+/// XMLTVエクスポートユースケース
+pub struct XmltvExportUseCase {
+    repository: Arc<dyn KurecProgramRepository>,
+    options: XmltvOptions,
+}
+
+impl XmltvExportUseCase {
+    /// 新しいXmltvExportUseCaseを作成
+    pub fn new(repository: Arc<dyn KurecProgramRepository>, options: XmltvOptions) -> Self {
+        Self {
+            repository,
+            options,
+        }
+    }
+
+    /// `now` の時点で終了していない番組を XMLTV 文書として出力する。
+    pub async fn export(&self, now: DateTime<Utc>) -> Result<String> {
+        let programs = self
+            .repository
+            .list_all_programs()
+            .await?
+            .into_iter()
+            .filter(|p| p.start_at + chrono::Duration::milliseconds(p.duration_millis) > now)
+            .collect();
+        Ok(build_xmltv(programs, &self.options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::epg::KurecProgram;
+    use async_trait::async_trait;
+    use chrono::TimeZone;
+
+    struct MockKurecProgramRepository {
+        programs: Vec<KurecProgram>,
+    }
+
+    #[async_trait]
+    impl KurecProgramRepository for MockKurecProgramRepository {
+        async fn save_service_programs(
+            &self,
+            _mirakc_url: &str,
+            _service_id: i64,
+            _programs: Vec<KurecProgram>,
+        ) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn get_service_programs(
+            &self,
+            _mirakc_url: &str,
+            _service_id: i64,
+        ) -> Result<Option<Vec<KurecProgram>>> {
+            unimplemented!()
+        }
+
+        async fn list_all_programs(&self) -> Result<Vec<KurecProgram>> {
+            Ok(self.programs.clone())
+        }
+    }
+
+    fn program(id: i64, hour: u32) -> KurecProgram {
+        KurecProgram {
+            id,
+            mirakc_url: "http://tuner:40772".to_string(),
+            service_id: 1024,
+            network_id: 32736,
+            event_id: id,
+            channel_name: "テスト".to_string(),
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+            name: Some(format!("番組{}", id)),
+            description: None,
+            extended: None,
+            start_at: Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).unwrap(),
+            duration_millis: 3600000,
+            is_free: true,
+            genres: vec![],
+            video_info: None,
+            audio_infos: vec![],
+            series_info: None,
+            markers: Default::default(),
+            episode_number: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_skips_ended_programs() {
+        let repository = Arc::new(MockKurecProgramRepository {
+            programs: vec![program(1, 10), program(2, 11), program(3, 12)],
+        });
+        let usecase = XmltvExportUseCase::new(repository, XmltvOptions::default());
+
+        let xml = usecase
+            .export(Utc.with_ymd_and_hms(2025, 1, 1, 11, 30, 0).unwrap())
+            .await
+            .unwrap();
+
+        assert!(!xml.contains("番組1<"));
+        assert!(xml.contains("番組2<"));
+        assert!(xml.contains("番組3<"));
+        assert!(xml.contains("<channel id=\"32736.1024\">"));
+    }
+}
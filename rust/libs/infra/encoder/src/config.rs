@@ -0,0 +1,26 @@
+//! エンコードスクリプト実行の設定
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// エンコードスクリプト実行の設定
+#[derive(Debug, Clone)]
+pub struct EncoderConfig {
+    /// 作業ディレクトリを作成するディレクトリ
+    pub work_dir: PathBuf,
+    /// スクリプトを実行するシェル
+    pub shell: String,
+    /// 実行中のログを保存する間隔
+    pub log_interval: Duration,
+}
+
+/// シェルは旧実装 (`EncoderDomain`) と同じ bash。
+impl Default for EncoderConfig {
+    fn default() -> Self {
+        Self {
+            work_dir: PathBuf::from("./data/encode"),
+            shell: "bash".to_string(),
+            log_interval: Duration::from_secs(10),
+        }
+    }
+}
@@ -0,0 +1,474 @@
+//! シェルでエンコードスクリプトを実行する `EncodeExecutor` の実装
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::StreamExt;
+use tokio::fs::{self, File};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, BufWriter};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+use tokio_util::io::ReaderStream;
+use tracing::{debug, info, warn};
+
+use domain::models::encode::{EncodeExecution, EncodeTask, EncodedFile, LOG_FILE, SCRIPT_FILE};
+use domain::ports::encode_executor::{EncodeExecutor, EncodeInput, EncodeLogSink};
+use domain::ports::storage::ByteStream;
+
+use crate::config::EncoderConfig;
+
+/// スクリプトの終了後、子プロセスが出力を閉じるのを待つ時間
+const LOG_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// シェルでエンコードスクリプトを実行する `EncodeExecutor` の実装
+///
+/// 作業ディレクトリは `{work_dir}/{mirakc のホスト}_{record_id}/{job}` で、
+/// 実行後は入力とスクリプトを削除し、出力とログを残す。
+/// ログは `log_interval` ごとに、増えていれば `EncodeLogSink` に保存する。
+/// 出力をストレージに保存した後、`cleanup` で作業ディレクトリごと削除する。
+/// スクリプトは新しいプロセスグループで実行し、タイムアウトした場合はグループごと強制終了する。
+pub struct ScriptEncodeExecutor {
+    config: EncoderConfig,
+}
+
+impl ScriptEncodeExecutor {
+    /// 新しい `ScriptEncodeExecutor` を作成する。
+    pub fn new(config: EncoderConfig) -> Self {
+        Self { config }
+    }
+
+    fn job_dir(&self, task: &EncodeTask) -> PathBuf {
+        let host: String = task
+            .mirakc_url
+            .split("://")
+            .last()
+            .unwrap_or_default()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        self.config
+            .work_dir
+            .join(format!("{}_{}", host.trim_matches('_'), task.record_id))
+            .join(&task.job)
+    }
+}
+
+/// 入力の内容を書き出し、書き出したサイズを返す。
+async fn write_input(path: &Path, mut input: ByteStream) -> Result<u64> {
+    let file = File::create(path)
+        .await
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    let mut size = 0;
+    while let Some(chunk) = input.next().await {
+        let chunk = chunk?;
+        writer
+            .write_all(&chunk)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        size += chunk.len() as u64;
+    }
+    writer.flush().await?;
+    Ok(size)
+}
+
+/// 子プロセスの出力を読み、ログの書き込みタスクに送る。
+fn forward<R>(reader: Option<R>, tx: mpsc::Sender<Vec<u8>>) -> tokio::task::JoinHandle<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let Some(mut reader) = reader else {
+            return;
+        };
+        let mut buf = vec![0; 8192];
+        while let Ok(n) = reader.read(&mut buf).await {
+            if n == 0 || tx.send(buf[..n].to_vec()).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+/// 子プロセスの出力をログに書き込み、`log_interval` ごとと最後に `sink` に保存する。
+async fn write_log(
+    mut file: File,
+    mut rx: mpsc::Receiver<Vec<u8>>,
+    sink: Arc<dyn EncodeLogSink>,
+    log_interval: Duration,
+) -> std::io::Result<()> {
+    let mut log = Vec::new();
+    let mut saved = None;
+    let mut interval =
+        tokio::time::interval_at(tokio::time::Instant::now() + log_interval, log_interval);
+    loop {
+        tokio::select! {
+            chunk = rx.recv() => {
+                let Some(chunk) = chunk else {
+                    break;
+                };
+                file.write_all(&chunk).await?;
+                log.extend_from_slice(&chunk);
+            }
+            _ = interval.tick() => {
+                if saved != Some(log.len()) {
+                    save_log(sink.as_ref(), &log).await;
+                    saved = Some(log.len());
+                }
+            }
+        }
+    }
+    file.flush().await?;
+    if saved != Some(log.len()) {
+        save_log(sink.as_ref(), &log).await;
+    }
+    Ok(())
+}
+
+async fn save_log(sink: &dyn EncodeLogSink, log: &[u8]) {
+    if let Err(e) = sink.save(log).await {
+        warn!(size = log.len(), "ジョブのログを保存できません: {:?}", e);
+    }
+}
+
+/// スクリプトのプロセスグループを強制終了する。
+fn kill_group(child: &mut Child) {
+    if let Some(pid) = child.id() {
+        // SAFETY: killpg はシグナルを送るだけで、メモリを操作しない
+        unsafe {
+            libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+}
+
+#[async_trait]
+impl EncodeExecutor for ScriptEncodeExecutor {
+    async fn execute(
+        &self,
+        task: &EncodeTask,
+        inputs: Vec<EncodeInput>,
+        log: Arc<dyn EncodeLogSink>,
+    ) -> Result<EncodeExecution> {
+        let dir = self.job_dir(task);
+        if fs::try_exists(&dir).await? {
+            // 前回の実行が残っている場合は削除してやり直す
+            fs::remove_dir_all(&dir)
+                .await
+                .with_context(|| format!("Failed to remove {}", dir.display()))?;
+        }
+        fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let mut work_files = vec![SCRIPT_FILE.to_string()];
+        for input in inputs {
+            let input_size = write_input(&dir.join(&input.file_name), input.data).await?;
+            debug!(dir = %dir.display(), file = %input.file_name, input_size, "入力を書き出しました");
+            work_files.push(input.file_name);
+        }
+        fs::write(dir.join(SCRIPT_FILE), format!("set -e\n{}\n", task.script)).await?;
+
+        let mut child = Command::new(&self.config.shell)
+            .arg(SCRIPT_FILE)
+            .current_dir(&dir)
+            .envs(task.env.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to spawn {}", self.config.shell))?;
+        info!(job = %task.job, pid = ?child.id(), "スクリプトを実行します");
+
+        let file = File::create(dir.join(LOG_FILE)).await?;
+        let (tx, rx) = mpsc::channel::<Vec<u8>>(64);
+        let readers = [
+            forward(child.stdout.take(), tx.clone()),
+            forward(child.stderr.take(), tx),
+        ];
+        let writer = tokio::spawn(write_log(file, rx, log, self.config.log_interval));
+
+        let (exit_code, timed_out) = match tokio::time::timeout(task.timeout, child.wait()).await {
+            Ok(status) => (status?.code(), false),
+            Err(_) => {
+                warn!(job = %task.job, timeout = ?task.timeout, "スクリプトがタイムアウトしたため強制終了します");
+                kill_group(&mut child);
+                child.wait().await?;
+                (None, true)
+            }
+        };
+        // スクリプトが残した子プロセスが出力を閉じない場合は待たずに読むのをやめる
+        let aborts: Vec<_> = readers.iter().map(|reader| reader.abort_handle()).collect();
+        if tokio::time::timeout(LOG_DRAIN_TIMEOUT, futures::future::join_all(readers))
+            .await
+            .is_err()
+        {
+            warn!(job = %task.job, "スクリプトの子プロセスが出力を閉じていません");
+            aborts.iter().for_each(|abort| abort.abort());
+        }
+        match tokio::time::timeout(LOG_DRAIN_TIMEOUT, writer).await {
+            Ok(Ok(Err(e))) => warn!(job = %task.job, "ログを書き込めません: {}", e),
+            Ok(_) => {}
+            Err(_) => warn!(job = %task.job, "ログの書き込みが終わりません"),
+        }
+
+        for name in &work_files {
+            if let Err(e) = fs::remove_file(dir.join(name)).await {
+                warn!(file = %dir.join(name).display(), "作業ファイルを削除できません: {}", e);
+            }
+        }
+
+        let mut outputs = Vec::new();
+        for name in &task.outputs {
+            let path = dir.join(name);
+            match fs::metadata(&path).await {
+                Ok(metadata) if metadata.is_file() => outputs.push(EncodedFile {
+                    name: name.clone(),
+                    location: path.display().to_string(),
+                    size: metadata.len(),
+                }),
+                _ => debug!(file = %path.display(), "出力がありません"),
+            }
+        }
+
+        Ok(EncodeExecution {
+            exit_code,
+            timed_out,
+            outputs,
+        })
+    }
+
+    async fn read_output(&self, file: &EncodedFile) -> Result<ByteStream> {
+        let reader = File::open(&file.location)
+            .await
+            .with_context(|| format!("Failed to open {}", file.location))?;
+        Ok(ReaderStream::new(reader)
+            .map(|chunk| chunk.map_err(anyhow::Error::from))
+            .boxed())
+    }
+
+    async fn cleanup(&self, task: &EncodeTask) -> Result<()> {
+        let dir = self.job_dir(task);
+        match fs::remove_dir_all(&dir).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e).with_context(|| format!("Failed to remove {}", dir.display())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use domain::models::encode::ORIGINAL_INPUT_FILE;
+    use futures::stream;
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    fn executor(dir: &Path) -> ScriptEncodeExecutor {
+        ScriptEncodeExecutor::new(EncoderConfig {
+            work_dir: dir.to_path_buf(),
+            ..Default::default()
+        })
+    }
+
+    /// 保存されたログを順に記録する
+    #[derive(Default)]
+    struct MockLogSink {
+        saved: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl MockLogSink {
+        fn last(&self) -> Vec<u8> {
+            self.saved
+                .lock()
+                .unwrap()
+                .last()
+                .cloned()
+                .unwrap_or_default()
+        }
+    }
+
+    #[async_trait]
+    impl EncodeLogSink for MockLogSink {
+        async fn save(&self, log: &[u8]) -> Result<()> {
+            self.saved.lock().unwrap().push(log.to_vec());
+            Ok(())
+        }
+    }
+
+    fn task(script: &str, timeout: Duration) -> EncodeTask {
+        EncodeTask {
+            mirakc_url: "http://tuner:40772".to_string(),
+            record_id: "1".to_string(),
+            job: "mp4".to_string(),
+            script: script.to_string(),
+            env: vec![("KUREC_TITLE".to_string(), "番組".to_string())],
+            outputs: vec!["output.mp4".to_string()],
+            timeout,
+        }
+    }
+
+    fn input() -> Vec<EncodeInput> {
+        vec![EncodeInput::new(
+            "original",
+            stream::iter(vec![
+                Ok(Bytes::from_static(b"TS")),
+                Ok(Bytes::from_static(b"DATA")),
+            ])
+            .boxed(),
+        )]
+    }
+
+    #[tokio::test]
+    async fn test_execute_succeeded() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let task = task(
+            "cat input.ts > output.mp4\necho \"title=$KUREC_TITLE\"\necho warning >&2",
+            Duration::from_secs(10),
+        );
+        let sink = Arc::new(MockLogSink::default());
+        let execution = executor(dir.path())
+            .execute(&task, input(), sink.clone())
+            .await?;
+
+        assert!(execution.succeeded());
+        let log = String::from_utf8(sink.last())?;
+        assert!(log.contains("title=番組"));
+        assert!(log.contains("warning"));
+        let job_dir = dir.path().join("tuner_40772_1").join("mp4");
+        assert_eq!(
+            execution.outputs,
+            vec![EncodedFile {
+                name: "output.mp4".to_string(),
+                location: job_dir.join("output.mp4").display().to_string(),
+                size: 6,
+            }]
+        );
+        assert!(!job_dir.join(ORIGINAL_INPUT_FILE).exists());
+        assert!(job_dir.join(LOG_FILE).exists());
+
+        let executor = executor(dir.path());
+        let chunks: Vec<Bytes> = executor
+            .read_output(&execution.outputs[0])
+            .await?
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks.concat(), b"TSDATA");
+
+        executor.cleanup(&task).await?;
+        assert!(!job_dir.exists());
+        // 削除済みでもエラーにしない
+        executor.cleanup(&task).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_job_output_input() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut task = task("cat output.mp4 > thumbnail.jpg", Duration::from_secs(10));
+        task.job = "thumbnail".to_string();
+        task.outputs = vec!["thumbnail.jpg".to_string()];
+        let inputs = vec![EncodeInput::new(
+            "output.mp4",
+            stream::iter(vec![Ok(Bytes::from_static(b"MP4"))]).boxed(),
+        )];
+        let execution = executor(dir.path())
+            .execute(&task, inputs, Arc::new(MockLogSink::default()))
+            .await?;
+
+        assert!(execution.succeeded());
+        assert_eq!(execution.outputs[0].size, 3);
+        // 入力は実行後に削除する
+        let job_dir = dir.path().join("tuner_40772_1").join("thumbnail");
+        assert!(!job_dir.join("output.mp4").exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_failed() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let sink = Arc::new(MockLogSink::default());
+        let execution = executor(dir.path())
+            .execute(
+                &task("echo failed >&2\nexit 3", Duration::from_secs(10)),
+                input(),
+                sink.clone(),
+            )
+            .await?;
+        assert_eq!(execution.exit_code, Some(3));
+        assert_eq!(sink.last(), b"failed\n");
+
+        // 途中のコマンドが失敗したら止める
+        let execution = executor(dir.path())
+            .execute(
+                &task("false\ntouch output.mp4", Duration::from_secs(10)),
+                input(),
+                Arc::new(MockLogSink::default()),
+            )
+            .await?;
+        assert_eq!(execution.exit_code, Some(1));
+        assert!(execution.outputs.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_timeout() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let started = Instant::now();
+        let sink = Arc::new(MockLogSink::default());
+        // バックグラウンドの子プロセスもまとめて強制終了する
+        let execution = executor(dir.path())
+            .execute(
+                &task(
+                    "sleep 30 &\necho started\nsleep 30",
+                    Duration::from_millis(500),
+                ),
+                input(),
+                sink.clone(),
+            )
+            .await?;
+        assert!(execution.timed_out);
+        assert!(!execution.succeeded());
+        assert_eq!(execution.exit_code, None);
+        assert_eq!(sink.last(), b"started\n");
+        assert!(started.elapsed() < Duration::from_secs(5));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_saves_log_while_running() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let executor = ScriptEncodeExecutor::new(EncoderConfig {
+            work_dir: dir.path().to_path_buf(),
+            log_interval: Duration::from_millis(100),
+            ..Default::default()
+        });
+        let sink = Arc::new(MockLogSink::default());
+        let execution = executor
+            .execute(
+                &task(
+                    "echo started\nsleep 1\necho done\ntouch output.mp4",
+                    Duration::from_secs(10),
+                ),
+                input(),
+                sink.clone(),
+            )
+            .await?;
+
+        assert!(execution.succeeded());
+        let saved = sink.saved.lock().unwrap().clone();
+        // 実行中に途中までのログを保存し、増えていない間は保存しない
+        assert_eq!(saved[0], b"started\n");
+        assert_eq!(saved.len(), 2);
+        assert_eq!(saved[1], b"started\ndone\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,10 @@
+//! エンコードスクリプト実行のインフラクレート
+//!
+//! このクレートはエンコードスクリプトの実行 (`EncodeExecutor`) をローカルのシェルで実装します。
+//! 録画ファイルを作業ディレクトリに書き出し、スクリプトの標準出力と標準エラー出力をログファイルに書き込みます。
+
+mod config;
+mod executor;
+
+pub use config::EncoderConfig;
+pub use executor::ScriptEncodeExecutor;
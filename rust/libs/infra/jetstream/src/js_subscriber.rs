@@ -26,7 +26,7 @@ fn generate_durable_name<E: Event>() -> String {
 
 // ヘルパー関数: 型名からスネークケースのサブジェクト名を取得 (js_publisher.rs と同じもの)
 // TODO: クレート内の共通 util モジュールに移動する方が良い
-fn type_name_to_snake_case<T: ?Sized + Event>() -> String {
+fn type_name_to_snake_case<T: Event>() -> String {
     let type_name_full = type_name::<T>();
     let type_name_short = type_name_full.split("::").last().unwrap_or(type_name_full);
     type_name_short.to_snake_case()
@@ -36,6 +36,8 @@ fn type_name_to_snake_case<T: ?Sized + Event>() -> String {
 pub struct JsSubscriber<E: Event> {
     nats_client: Arc<NatsClient>,
     event_stream: crate::event_stream::EventStream,
+    /// コンシューマ名の上書き (未指定の場合は型名から生成)
+    durable_name: Option<String>,
     _phantom: std::marker::PhantomData<E>, // 型パラメータを保持するためのフィールド
 }
 
@@ -48,10 +50,20 @@ impl<E: Event> JsSubscriber<E> {
         Self {
             nats_client,
             event_stream,
+            durable_name: None,
             _phantom: std::marker::PhantomData, // 型パラメータを保持
         }
     }
 
+    /// コンシューマ名を指定する
+    ///
+    /// 同じイベント型を複数のワーカーがそれぞれ全件購読する場合に、
+    /// ワーカーごとに異なるコンシューマ名を指定する。
+    pub fn with_durable_name(mut self, durable_name: impl Into<String>) -> Self {
+        self.durable_name = Some(durable_name.into());
+        self
+    }
+
     /// イベントストリームを取得
     pub fn event_stream(&self) -> &crate::event_stream::EventStream {
         &self.event_stream
@@ -68,7 +80,10 @@ where
     async fn subscribe(&self) -> Result<BoxStream<'static, Result<E, anyhow::Error>>> {
         let stream_name = self.event_stream.stream_name();
         let subject_filter = type_name_to_snake_case::<E>(); // 型名からサブジェクト名を生成
-        let durable_name = generate_durable_name::<E>(); // コンシューマ名
+        let durable_name = self
+            .durable_name
+            .clone()
+            .unwrap_or_else(generate_durable_name::<E>); // コンシューマ名
 
         // 個別の定数から StreamConfig を構築
         // 注: 現在の実装では、これらの定数は実際には使用されていません
@@ -17,10 +17,12 @@ pub mod error;
 pub mod event_stream;
 mod js_publisher;
 mod js_subscriber;
+mod nats_query;
 
 pub use event_stream::EventStream;
 pub use js_publisher::JsPublisher;
 pub use js_subscriber::JsSubscriber;
+pub use nats_query::{NatsQueryClient, NatsQueryResponder};
 
 // Remove JetStreamCtx struct
 // Remove connect function
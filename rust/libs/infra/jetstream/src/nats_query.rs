@@ -0,0 +1,117 @@
+//! NATS のリクエスト・リプライによる問い合わせ
+//!
+//! 問い合わせ (`QueryRequest`) を JSON にして `Q::SUBJECT` に送り、応答を待ちます。
+//! 応答は成功した場合は `{"ok": <応答>}`、失敗した場合は `{"error": "<メッセージ>"}` です。
+//! Web UI など Rust 以外のクライアントも同じ形式で問い合わせできます。
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use domain::ports::query_bus::{QueryClient, QueryHandler, QueryRequest};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+use infra_nats::NatsClient;
+
+/// 問い合わせに応答するプロセス間で負荷を分散するキューグループ
+const QUERY_QUEUE_GROUP: &str = "kurec-query";
+/// 同時に処理する問い合わせの数
+const QUERY_CONCURRENCY: usize = 8;
+
+/// 問い合わせの応答
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum QueryReply<T> {
+    Ok(T),
+    Error(String),
+}
+
+/// NATS で問い合わせに応答するサーバー
+pub struct NatsQueryResponder<Q: QueryRequest> {
+    nats_client: Arc<NatsClient>,
+    handler: Arc<dyn QueryHandler<Q>>,
+}
+
+impl<Q: QueryRequest> NatsQueryResponder<Q> {
+    /// 新しいNatsQueryResponderを作成
+    pub fn new(nats_client: Arc<NatsClient>, handler: Arc<dyn QueryHandler<Q>>) -> Self {
+        Self {
+            nats_client,
+            handler,
+        }
+    }
+
+    /// 問い合わせに応答し続ける。購読が終了した場合に戻る。
+    pub async fn serve(&self) -> Result<()> {
+        let client = self.nats_client.client();
+        let subscriber = client
+            .queue_subscribe(Q::SUBJECT, QUERY_QUEUE_GROUP.to_string())
+            .await
+            .map_err(|e| anyhow!("{} の購読に失敗しました: {}", Q::SUBJECT, e))?;
+        info!(subject = Q::SUBJECT, "問い合わせの受付を開始しました");
+
+        subscriber
+            .for_each_concurrent(QUERY_CONCURRENCY, |message| async move {
+                let Some(reply_to) = message.reply else {
+                    warn!(subject = Q::SUBJECT, "返信先のない問い合わせを無視します");
+                    return;
+                };
+                let reply = match serde_json::from_slice::<Q>(&message.payload) {
+                    Ok(query) => match self.handler.handle(query).await {
+                        Ok(response) => QueryReply::Ok(response),
+                        Err(e) => QueryReply::Error(format!("{:#}", e)),
+                    },
+                    Err(e) => QueryReply::Error(format!("不正な問い合わせです: {}", e)),
+                };
+                if let QueryReply::Error(e) = &reply {
+                    debug!(subject = Q::SUBJECT, error = %e, "問い合わせに失敗しました");
+                }
+                let payload = match serde_json::to_vec(&reply) {
+                    Ok(payload) => payload,
+                    Err(e) => {
+                        warn!(subject = Q::SUBJECT, "応答を JSON にできません: {}", e);
+                        return;
+                    }
+                };
+                if let Err(e) = client.publish(reply_to, payload.into()).await {
+                    warn!(subject = Q::SUBJECT, "応答の送信に失敗しました: {}", e);
+                }
+            })
+            .await;
+        Ok(())
+    }
+}
+
+/// NATS で問い合わせを送るクライアント
+pub struct NatsQueryClient<Q: QueryRequest> {
+    nats_client: Arc<NatsClient>,
+    _phantom: std::marker::PhantomData<Q>,
+}
+
+impl<Q: QueryRequest> NatsQueryClient<Q> {
+    /// 新しいNatsQueryClientを作成
+    pub fn new(nats_client: Arc<NatsClient>) -> Self {
+        Self {
+            nats_client,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<Q: QueryRequest> QueryClient<Q> for NatsQueryClient<Q> {
+    async fn request(&self, query: Q) -> Result<Q::Response> {
+        let payload = serde_json::to_vec(&query).context("問い合わせを JSON にできません")?;
+        let message = self
+            .nats_client
+            .client()
+            .request(Q::SUBJECT, payload.into())
+            .await
+            .map_err(|e| anyhow!("{} への問い合わせに失敗しました: {}", Q::SUBJECT, e))?;
+        match serde_json::from_slice(&message.payload).context("応答を解析できません")? {
+            QueryReply::Ok(response) => Ok(response),
+            QueryReply::Error(e) => Err(anyhow!(e)),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use domain::ports::query_bus::{QueryClient, QueryHandler, QueryRequest};
+use infra_jetstream::{NatsQueryClient, NatsQueryResponder};
+use infra_nats::connect as nats_connect;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use testcontainers::{core::WaitFor, runners::AsyncRunner, ContainerAsync, GenericImage};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EchoQuery {
+    message: String,
+}
+
+impl QueryRequest for EchoQuery {
+    const SUBJECT: &'static str = "kurec.query.test.echo";
+    type Response = String;
+}
+
+struct EchoHandler;
+
+#[async_trait]
+impl QueryHandler<EchoQuery> for EchoHandler {
+    async fn handle(&self, query: EchoQuery) -> Result<String> {
+        if query.message.is_empty() {
+            bail!("空のメッセージです");
+        }
+        Ok(query.message.to_uppercase())
+    }
+}
+
+async fn ensure_docker() {
+    for _ in 0..20 {
+        if std::process::Command::new("docker")
+            .arg("info")
+            .output()
+            .is_ok()
+        {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+    panic!("Docker daemon not ready");
+}
+
+#[tokio::test]
+async fn test_query_request_reply() -> Result<()> {
+    let (_container, url) = setup_nats().await?;
+    let nats_client = nats_connect(&url).await?;
+
+    let responder = NatsQueryResponder::new(nats_client.clone(), Arc::new(EchoHandler));
+    let server = tokio::spawn(async move { responder.serve().await });
+    // 購読が登録されるまで待つ
+    nats_client.client().flush().await?;
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+    let client = NatsQueryClient::<EchoQuery>::new(nats_client.clone());
+    let response = client
+        .request(EchoQuery {
+            message: "hello".to_string(),
+        })
+        .await?;
+    assert_eq!(response, "HELLO");
+
+    // ハンドラのエラーは呼び出し元に返る
+    let err = client
+        .request(EchoQuery {
+            message: String::new(),
+        })
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("空のメッセージです"));
+
+    server.abort();
+    Ok(())
+}
+
+async fn setup_nats() -> Result<(ContainerAsync<GenericImage>, String)> {
+    ensure_docker().await;
+    let container = GenericImage::new("nats", "latest")
+        .with_exposed_port(4222u16.into())
+        .with_wait_for(WaitFor::message_on_stderr("Server is ready"))
+        .start()
+        .await?;
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(4222u16).await?;
+    Ok((container, format!("nats://{}:{}", host, port)))
+}
@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 use testcontainers::{core::WaitFor, runners::AsyncRunner, GenericImage, ImageExt};
 
 // イベント型の定義
+#[allow(dead_code)]
 #[derive(Serialize, Deserialize, Debug)]
 struct TestEvent;
 
@@ -1,11 +1,44 @@
 //! KVS (Key-Value Store) インフラストラクチャ実装
 //!
-//! このクレートは、ドメイン層で定義されたリポジトリトレイト (`KurecProgramRepository`) を
+//! このクレートは、ドメイン層で定義されたリポジトリトレイト (`KurecProgramRepository` など) を
 //! 具体的なKVS技術 (現在はNATS KVを想定) を用いて実装します。
 
 pub mod error;
+pub mod nats_deletion;
+pub mod nats_job;
+pub mod nats_job_log;
 pub mod nats_kv; // NATS KV実装モジュール
+pub mod nats_lease;
+pub mod nats_now_playing;
+pub mod nats_ogp;
+pub mod nats_record;
+pub mod nats_rule;
+pub mod nats_schedule;
+pub mod nats_series;
+pub mod nats_service;
+pub mod nats_storage;
+pub mod nats_tuner;
+pub mod store;
+#[cfg(test)]
+mod test_utils;
 
 // 必要に応じて他のKVS実装モジュールを追加 (例: redis, memory)
 
 // 設定に応じて実装を切り替えるファクトリ関数などを定義することも可能
+
+pub use nats_deletion::NatsKvRecordDeletionRepository;
+pub use nats_job::NatsKvJobRunRepository;
+pub use nats_job_log::{JobLogBucket, NatsObjectJobLogRepository};
+pub use nats_kv::NatsKvProgramRepository;
+pub use nats_lease::NatsKvLeaseRepository;
+pub use nats_now_playing::NatsKvNowPlayingRepository;
+pub use nats_ogp::{NatsObjectOgpImageRepository, OgpImageBucket};
+pub use nats_record::NatsKvRecordRepository;
+pub use nats_rule::NatsKvRecordingRuleRepository;
+pub use nats_schedule::NatsKvDesiredScheduleRepository;
+pub use nats_series::NatsKvSeriesSubscriptionRepository;
+pub use nats_service::{
+    NatsKvServiceRepository, NatsObjectServiceLogoRepository, ServiceLogoBucket,
+};
+pub use nats_storage::{NatsObjectStorage, StorageBucket};
+pub use nats_tuner::{NatsKvTunerHistoryRepository, NatsKvTunerStatusRepository};
@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::kv::{Config as KvConfig, Store};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::sync::Arc;
+use tracing::{debug, instrument};
+
+use infra_nats::NatsClient;
+
+use domain::models::deletion::RecordDeletion;
+use domain::ports::repositories::RecordDeletionRepository;
+
+use crate::store::{get_or_create_store, list_values, mirakc_host_key};
+
+/// mirakc の録画の削除の記録用の KV バケット名
+pub const RECORD_DELETION_BUCKET: &str = "kurec_record_deletions";
+
+/// NATS KVストアを使用して `RecordDeletionRepository` を実装する構造体。
+#[derive(Debug, Clone)]
+pub struct NatsKvRecordDeletionRepository {
+    store: Store,
+}
+
+impl NatsKvRecordDeletionRepository {
+    /// 新しい `NatsKvRecordDeletionRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_record_deletions" KV バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    ///
+    /// # Arguments
+    ///
+    /// * `nats_client` - 接続済みの `NatsClient`
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let kv_config = KvConfig {
+            bucket: RECORD_DELETION_BUCKET.to_string(),
+            history: 1,
+            ..Default::default()
+        };
+        let store = get_or_create_store(&nats_client, kv_config).await?;
+        Ok(Self { store })
+    }
+
+    /// KVSで使用するキーを生成する。
+    /// キーは `deletion_{host}_{record_id}` の形式。
+    fn generate_key(mirakc_url: &str, record_id: &str) -> String {
+        format!("deletion_{}_{}", mirakc_host_key(mirakc_url), record_id)
+    }
+}
+
+#[async_trait]
+impl RecordDeletionRepository for NatsKvRecordDeletionRepository {
+    #[instrument(skip(self, deletion), fields(key = %Self::generate_key(&deletion.mirakc_url, &deletion.record_id)))]
+    async fn save_deletion(&self, deletion: &RecordDeletion) -> Result<()> {
+        let key = Self::generate_key(&deletion.mirakc_url, &deletion.record_id);
+        let json_data =
+            serde_json::to_vec(deletion).context("Failed to serialize record deletion to JSON")?;
+        let revision = self
+            .store
+            .put(&key, Bytes::from(json_data))
+            .await
+            .with_context(|| format!("NATS KV put operation failed for key '{}'", key))?;
+        debug!(revision, "Successfully saved record deletion to NATS KV");
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(key = %Self::generate_key(mirakc_url, record_id)))]
+    async fn get_deletion(
+        &self,
+        mirakc_url: &str,
+        record_id: &str,
+    ) -> Result<Option<RecordDeletion>> {
+        let key = Self::generate_key(mirakc_url, record_id);
+        match self
+            .store
+            .get(&key)
+            .await
+            .with_context(|| format!("NATS KV get operation failed for key '{}'", key))?
+        {
+            Some(value) => {
+                let deletion = serde_json::from_slice(&value)
+                    .context("Failed to deserialize record deletion from JSON")?;
+                Ok(Some(deletion))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn list_deletions(&self) -> Result<Vec<RecordDeletion>> {
+        list_values(&self.store).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_nats_client;
+    use chrono::{TimeZone, Utc};
+    use domain::models::deletion::VerifiedCopy;
+
+    #[tokio::test]
+    async fn test_save_get_list_record_deletions() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsKvRecordDeletionRepository::new(nats_client).await?;
+        let now = Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap();
+        let mirakc_url = "http://test-mirakc:1234";
+
+        let mut deletion = RecordDeletion {
+            mirakc_url: mirakc_url.to_string(),
+            record_id: "0000000000000001".to_string(),
+            program_name: Some("ニュース".to_string()),
+            recording_start_time: Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap(),
+            content_path: "0000000000000001.m2ts".to_string(),
+            content_length: 1024,
+            jobs_succeeded_at: Utc.with_ymd_and_hms(2025, 1, 1, 13, 0, 0).unwrap(),
+            deleted_at: now,
+            dry_run: true,
+            pending: false,
+            reason: "すべてのジョブが成功しました".to_string(),
+            copies: vec![VerifiedCopy {
+                name: "original".to_string(),
+                storage: "local1".to_string(),
+                location: "2025/01/01/0000000000000001/original".to_string(),
+                size: 1024,
+                sha256: "5f70bf18a086007016e948b04aed3b82103a36bea41755b6cddfaf10ace3c6ef"
+                    .to_string(),
+            }],
+        };
+        repository.save_deletion(&deletion).await?;
+        deletion.dry_run = false;
+        repository.save_deletion(&deletion).await?;
+
+        assert_eq!(
+            repository
+                .get_deletion(mirakc_url, "0000000000000001")
+                .await?,
+            Some(deletion.clone())
+        );
+        assert!(repository
+            .get_deletion(mirakc_url, "missing")
+            .await?
+            .is_none());
+        assert_eq!(repository.list_deletions().await?, vec![deletion]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::kv::{Config as KvConfig, Store};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::sync::Arc;
+use tracing::{debug, instrument};
+
+use infra_nats::NatsClient;
+
+use domain::models::job::JobRun;
+use domain::ports::repositories::JobRunRepository;
+
+use crate::store::{get_or_create_store, list_values, mirakc_host_key};
+
+/// ジョブの実行状況用の KV バケット名
+pub const JOB_RUN_BUCKET: &str = "kurec_job_runs";
+
+/// NATS KVストアを使用して `JobRunRepository` を実装する構造体。
+#[derive(Debug, Clone)]
+pub struct NatsKvJobRunRepository {
+    store: Store,
+}
+
+impl NatsKvJobRunRepository {
+    /// 新しい `NatsKvJobRunRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_job_runs" KV バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    ///
+    /// # Arguments
+    ///
+    /// * `nats_client` - 接続済みの `NatsClient`
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let kv_config = KvConfig {
+            bucket: JOB_RUN_BUCKET.to_string(),
+            history: 1,
+            ..Default::default()
+        };
+        let store = get_or_create_store(&nats_client, kv_config).await?;
+        Ok(Self { store })
+    }
+
+    /// KVSで使用するキーを生成する。
+    /// キーは `job_{host}_{record_id}` の形式。
+    fn generate_key(mirakc_url: &str, record_id: &str) -> String {
+        format!("job_{}_{}", mirakc_host_key(mirakc_url), record_id)
+    }
+}
+
+#[async_trait]
+impl JobRunRepository for NatsKvJobRunRepository {
+    #[instrument(skip(self, run), fields(key = %Self::generate_key(&run.mirakc_url, &run.record_id)))]
+    async fn save_run(&self, run: &JobRun) -> Result<()> {
+        let key = Self::generate_key(&run.mirakc_url, &run.record_id);
+        let json_data = serde_json::to_vec(run).context("Failed to serialize job run to JSON")?;
+        let revision = self
+            .store
+            .put(&key, Bytes::from(json_data))
+            .await
+            .with_context(|| format!("NATS KV put operation failed for key '{}'", key))?;
+        debug!(revision, "Successfully saved job run to NATS KV");
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(key = %Self::generate_key(mirakc_url, record_id)))]
+    async fn get_run(&self, mirakc_url: &str, record_id: &str) -> Result<Option<JobRun>> {
+        let key = Self::generate_key(mirakc_url, record_id);
+        match self
+            .store
+            .get(&key)
+            .await
+            .with_context(|| format!("NATS KV get operation failed for key '{}'", key))?
+        {
+            Some(value) => {
+                let run = serde_json::from_slice(&value)
+                    .context("Failed to deserialize job run from JSON")?;
+                Ok(Some(run))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn list_runs(&self) -> Result<Vec<JobRun>> {
+        list_values(&self.store).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_nats_client;
+    use chrono::{TimeZone, Utc};
+    use domain::models::job::{JobGraph, JobsConfig};
+
+    #[tokio::test]
+    async fn test_save_get_list_job_runs() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsKvJobRunRepository::new(nats_client).await?;
+        let graph = JobGraph::new(&JobsConfig {
+            original_storage: Some("local1".to_string()),
+            delete_record: true,
+            jobs: vec![],
+        })?;
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let mirakc_url = "http://test-mirakc:1234";
+
+        let mut run = graph.instantiate(mirakc_url, "0000000000000001", now);
+        repository.save_run(&run).await?;
+        run.mark_queued("save-original", now);
+        repository.save_run(&run).await?;
+
+        assert_eq!(
+            repository.get_run(mirakc_url, "0000000000000001").await?,
+            Some(run.clone())
+        );
+        assert!(repository.get_run(mirakc_url, "missing").await?.is_none());
+        assert_eq!(repository.list_runs().await?, vec![run]);
+
+        Ok(())
+    }
+}
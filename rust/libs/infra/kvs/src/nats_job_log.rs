@@ -0,0 +1,121 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::object_store::{GetErrorKind, ObjectMetadata, ObjectStore};
+use async_trait::async_trait;
+use shared_macros::define_object_store_bucket;
+use shared_types::object_store::ObjectStoreBucket;
+use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+use tracing::{debug, instrument};
+
+use infra_nats::NatsClient;
+
+use domain::ports::repositories::JobLogRepository;
+
+use crate::store::{get_or_create_object_store, mirakc_host_key};
+
+/// ジョブのログの Object Store バケット
+#[define_object_store_bucket(bucket_name = "kurec_job_logs", description = "ジョブのログ")]
+pub struct JobLogBucket;
+
+/// NATS Object Store を使用して `JobLogRepository` を実装する構造体。
+///
+/// オブジェクト名は `{host}_{record_id}_{job}.log`。
+#[derive(Clone)]
+pub struct NatsObjectJobLogRepository {
+    store: ObjectStore,
+}
+
+impl NatsObjectJobLogRepository {
+    /// 新しい `NatsObjectJobLogRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_job_logs" Object Store バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let store = get_or_create_object_store(&nats_client, JobLogBucket::config()).await?;
+        Ok(Self { store })
+    }
+
+    fn object_name(mirakc_url: &str, record_id: &str, job: &str) -> String {
+        format!("{}_{}_{}.log", mirakc_host_key(mirakc_url), record_id, job)
+    }
+}
+
+#[async_trait]
+impl JobLogRepository for NatsObjectJobLogRepository {
+    #[instrument(skip(self, log), fields(size = log.len()))]
+    async fn save_log(
+        &self,
+        mirakc_url: &str,
+        record_id: &str,
+        job: &str,
+        log: Vec<u8>,
+    ) -> Result<String> {
+        let name = Self::object_name(mirakc_url, record_id, job);
+        let meta = ObjectMetadata {
+            name: name.clone(),
+            ..Default::default()
+        };
+        self.store
+            .put(meta, &mut log.as_slice())
+            .await
+            .with_context(|| format!("NATS Object Store put operation failed for '{}'", name))?;
+        debug!(name, "Successfully saved job log to NATS Object Store");
+        Ok(name)
+    }
+
+    #[instrument(skip(self))]
+    async fn get_log(
+        &self,
+        mirakc_url: &str,
+        record_id: &str,
+        job: &str,
+    ) -> Result<Option<Vec<u8>>> {
+        let name = Self::object_name(mirakc_url, record_id, job);
+        let mut object = match self.store.get(&name).await {
+            Ok(object) => object,
+            Err(e) if e.kind() == GetErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(anyhow::Error::new(e).context(format!(
+                    "NATS Object Store get operation failed for '{}'",
+                    name
+                )))
+            }
+        };
+        let mut log = Vec::new();
+        object
+            .read_to_end(&mut log)
+            .await
+            .with_context(|| format!("Failed to read job log '{}' from NATS Object Store", name))?;
+        Ok(Some(log))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_nats_client;
+
+    #[tokio::test]
+    async fn test_save_and_get_job_log() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsObjectJobLogRepository::new(nats_client).await?;
+        let mirakc_url = "http://test-mirakc:1234";
+
+        assert!(repository.get_log(mirakc_url, "1", "mp4").await?.is_none());
+
+        let name = repository
+            .save_log(mirakc_url, "1", "mp4", b"frame=1\n".to_vec())
+            .await?;
+        assert_eq!(name, "test-mirakc_1234_1_mp4.log");
+        // やり直した場合は上書きする
+        repository
+            .save_log(mirakc_url, "1", "mp4", b"frame=2\n".to_vec())
+            .await?;
+        assert_eq!(
+            repository.get_log(mirakc_url, "1", "mp4").await?,
+            Some(b"frame=2\n".to_vec())
+        );
+
+        Ok(())
+    }
+}
@@ -8,6 +8,8 @@ use tracing::{debug, error, info, instrument}; // info を追加
 // infra_nats クレートの NatsClient をインポート
 use infra_nats::NatsClient;
 
+use crate::store::{get_or_create_store, list_values, mirakc_host_key};
+
 use domain::models::epg::KurecProgram;
 use domain::ports::repositories::KurecProgramRepository;
 
@@ -39,32 +41,7 @@ impl NatsKvProgramRepository {
         };
 
         info!(bucket_name = %kv_config.bucket, "EPG 用 KV ストアを取得または作成します...");
-        // NatsClient から JetStream Context を取得し、KV ストアを作成/取得
-        let js_ctx = nats_client.jetstream_context();
-        let store = match js_ctx.get_key_value(&kv_config.bucket).await {
-            Ok(store) => {
-                info!(bucket_name = %kv_config.bucket, "既存の EPG 用 KV ストアを取得しました。");
-                // TODO: 設定が異なる場合に update_key_value を呼ぶべきか検討
-                store
-            }
-            Err(err)
-                if err.to_string().contains("no key value store named")
-                    || err.to_string().contains("stream not found") =>
-            {
-                info!(bucket_name = %kv_config.bucket, "EPG 用 KV ストアが存在しないか、関連ストリームが見つからないため、新規作成します。");
-                js_ctx
-                    .create_key_value(kv_config)
-                    .await
-                    .context("EPG 用 KV ストアの作成に失敗しました")?
-            }
-            Err(e) => {
-                // その他の予期せぬエラー
-                return Err(anyhow::Error::new(e).context(format!(
-                    "EPG 用 KV ストア '{}' の取得中にエラーが発生しました",
-                    kv_config.bucket
-                )));
-            }
-        };
+        let store = get_or_create_store(&nats_client, kv_config).await?;
 
         Ok(Self { store })
     }
@@ -72,19 +49,7 @@ impl NatsKvProgramRepository {
     /// KVSで使用するキーを生成する。
     /// キーは `epg:{mirakc_url}:{service_id}` の形式。
     fn generate_key(mirakc_url: &str, service_id: i64) -> String {
-        // i32 -> i64
-        // URLに含まれる可能性のある特殊文字をエスケープまたは置換する必要があるか検討
-        // NATSのキーとして安全な形式にする
-        // URLからスキーム (http:// など) を削除し、ホスト部分のみを使用
-        // URLからスキーム (http:// など) を削除し、ホスト部分のみを使用
-        // また、特殊文字（:や/など）をアンダースコアに置換
-        let host = mirakc_url
-            .replace("http://", "")
-            .replace("https://", "")
-            .replace(":", "_")
-            .replace("/", "_")
-            .trim_end_matches('_')
-            .to_string();
+        let host = mirakc_host_key(mirakc_url);
         format!("epg_{}_svc_{}", host, service_id)
     }
 }
@@ -162,6 +127,12 @@ impl KurecProgramRepository for NatsKvProgramRepository {
             }
         }
     }
+
+    #[instrument(skip(self))]
+    async fn list_all_programs(&self) -> Result<Vec<KurecProgram>> {
+        let services: Vec<Vec<KurecProgram>> = list_values(&self.store).await?;
+        Ok(services.into_iter().flatten().collect())
+    }
 } // impl ブロックの正しい閉じ括弧
 
 // --- 単体テスト ---
@@ -330,6 +301,8 @@ mod tests {
                     video_info: None,
                     audio_infos: vec!["ステレオ".to_string()],
                     series_info: None,
+                    markers: Default::default(),
+                    episode_number: None,
                 }
             })
             .collect()
@@ -387,6 +360,30 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_list_all_programs() -> anyhow::Result<()> {
+        let (_container, _nats_client, store, _cleaner) = setup_test_kv().await?;
+        let repository = NatsKvProgramRepository { store };
+
+        repository
+            .save_service_programs(
+                "http://tuner1:40772",
+                101,
+                create_dummy_programs("http://tuner1:40772", 101, 2),
+            )
+            .await?;
+        repository
+            .save_service_programs(
+                "http://tuner2:40772",
+                101,
+                create_dummy_programs("http://tuner2:40772", 101, 3),
+            )
+            .await?;
+
+        assert_eq!(repository.list_all_programs().await?.len(), 5);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_save_overwrite() -> anyhow::Result<()> {
         let (_container, _nats_client, store, _cleaner) = setup_test_kv().await?;
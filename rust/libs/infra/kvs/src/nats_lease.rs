@@ -0,0 +1,222 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::kv::{
+    Config as KvConfig, CreateErrorKind, Operation, Store, UpdateErrorKind,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tracing::{debug, instrument};
+
+use infra_nats::NatsClient;
+
+use domain::models::lease::Lease;
+use domain::ports::repositories::LeaseRepository;
+
+use crate::store::get_or_create_store;
+
+/// リーダー選出のリース用の KV バケット名
+pub const LEASES_BUCKET: &str = "kurec_leases";
+
+/// NATS KVストアを使用して `LeaseRepository` を実装する構造体。
+///
+/// リースの取得と更新は KV のリビジョンを指定した書き込み (compare-and-set) で行うため、
+/// 複数のインスタンスが同時に取得しようとしても1つだけが成功する。
+/// 解放も読み取ったリビジョンを指定して削除するため、読み取り後に他のインスタンスが
+/// 取得したリースを消してしまうことはない。
+#[derive(Debug, Clone)]
+pub struct NatsKvLeaseRepository {
+    store: Store,
+}
+
+impl NatsKvLeaseRepository {
+    /// 新しい `NatsKvLeaseRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_leases" KV バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let kv_config = KvConfig {
+            bucket: LEASES_BUCKET.to_string(),
+            history: 1,
+            ..Default::default()
+        };
+        let store = get_or_create_store(&nats_client, kv_config).await?;
+        Ok(Self { store })
+    }
+
+    /// KVSで使用するキーを生成する。キーは `lease_{name}` の形式。
+    fn generate_key(name: &str) -> String {
+        format!("lease_{}", name)
+    }
+
+    /// リースが `revision` から書き換えられていなければ削除する。
+    ///
+    /// 他のインスタンスが先に書き込んでいた場合は削除せずに `false` を返す。
+    async fn purge_if_unchanged(&self, key: &str, revision: u64) -> Result<bool> {
+        match self.store.purge_expect_revision(key, Some(revision)).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == UpdateErrorKind::WrongLastRevision => Ok(false),
+            Err(e) => Err(anyhow::Error::new(e)
+                .context(format!("NATS KV purge operation failed for key '{}'", key))),
+        }
+    }
+}
+
+#[async_trait]
+impl LeaseRepository for NatsKvLeaseRepository {
+    #[instrument(skip(self), fields(key = %Self::generate_key(name)))]
+    async fn try_acquire(
+        &self,
+        name: &str,
+        holder: &str,
+        expires_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        let key = Self::generate_key(name);
+        let lease = Lease {
+            holder: holder.to_string(),
+            expires_at,
+        };
+        let value = Bytes::from(serde_json::to_vec(&lease).context("Failed to serialize lease")?);
+
+        let entry = self
+            .store
+            .entry(&key)
+            .await
+            .with_context(|| format!("NATS KV entry operation failed for key '{}'", key))?;
+        let Some(entry) = entry else {
+            return match self.store.create(&key, value).await {
+                Ok(_) => Ok(true),
+                Err(e) if e.kind() == CreateErrorKind::AlreadyExists => Ok(false),
+                Err(e) => Err(anyhow::Error::new(e)
+                    .context(format!("NATS KV create operation failed for key '{}'", key))),
+            };
+        };
+
+        if entry.operation == Operation::Put {
+            let current: Lease = serde_json::from_slice(&entry.value)
+                .context("Failed to deserialize lease from JSON")?;
+            if !current.can_acquire(holder, now) {
+                debug!(holder = %current.holder, "Lease is held by another instance");
+                return Ok(false);
+            }
+        }
+        match self.store.update(&key, value, entry.revision).await {
+            Ok(_) => Ok(true),
+            // 他のインスタンスが先に書き込んだ
+            Err(e) if e.kind() == UpdateErrorKind::WrongLastRevision => Ok(false),
+            Err(e) => Err(anyhow::Error::new(e)
+                .context(format!("NATS KV update operation failed for key '{}'", key))),
+        }
+    }
+
+    #[instrument(skip(self), fields(key = %Self::generate_key(name)))]
+    async fn release(&self, name: &str, holder: &str) -> Result<()> {
+        let key = Self::generate_key(name);
+        let Some(entry) = self
+            .store
+            .entry(&key)
+            .await
+            .with_context(|| format!("NATS KV entry operation failed for key '{}'", key))?
+        else {
+            return Ok(());
+        };
+        if entry.operation != Operation::Put {
+            return Ok(());
+        }
+        let current: Lease = serde_json::from_slice(&entry.value)
+            .context("Failed to deserialize lease from JSON")?;
+        if current.holder != holder {
+            return Ok(());
+        }
+        if self.purge_if_unchanged(&key, entry.revision).await? {
+            debug!("Successfully released lease");
+        } else {
+            debug!("Lease was taken over before release");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_nats_client;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_acquire_renew_and_release_lease() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsKvLeaseRepository::new(nats_client).await?;
+        let now = Utc::now();
+        let ttl = Duration::seconds(30);
+
+        assert!(repository.try_acquire("test", "a", now + ttl, now).await?);
+        assert!(!repository.try_acquire("test", "b", now + ttl, now).await?);
+        // 保持しているインスタンスは更新できる
+        assert!(repository.try_acquire("test", "a", now + ttl, now).await?);
+
+        // 有効期限が切れると他のインスタンスが取得できる
+        let later = now + ttl;
+        assert!(
+            repository
+                .try_acquire("test", "b", later + ttl, later)
+                .await?
+        );
+        assert!(
+            !repository
+                .try_acquire("test", "a", later + ttl, later)
+                .await?
+        );
+
+        // 他のインスタンスのリースは解放しない
+        repository.release("test", "a").await?;
+        assert!(
+            !repository
+                .try_acquire("test", "a", later + ttl, later)
+                .await?
+        );
+        repository.release("test", "b").await?;
+        assert!(
+            repository
+                .try_acquire("test", "a", later + ttl, later)
+                .await?
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_release_after_lease_taken_over() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsKvLeaseRepository::new(nats_client).await?;
+        let now = Utc::now();
+        let ttl = Duration::seconds(30);
+        let key = NatsKvLeaseRepository::generate_key("test");
+
+        assert!(repository.try_acquire("test", "a", now + ttl, now).await?);
+        // a が解放のためにリースを読み取った後で、有効期限が切れて b が取得する
+        let stale = repository.store.entry(&key).await?.unwrap();
+        let later = now + ttl;
+        assert!(
+            repository
+                .try_acquire("test", "b", later + ttl, later)
+                .await?
+        );
+
+        // 読み取ったリビジョンから書き換えられているので削除しない
+        assert!(!repository.purge_if_unchanged(&key, stale.revision).await?);
+        assert!(
+            !repository
+                .try_acquire("test", "a", later + ttl, later)
+                .await?
+        );
+        assert!(
+            repository
+                .try_acquire("test", "b", later + ttl, later)
+                .await?
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::kv::{Config as KvConfig, Store};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::sync::Arc;
+use tracing::{debug, instrument};
+
+use infra_nats::NatsClient;
+
+use domain::models::onair::NowPlaying;
+use domain::ports::repositories::NowPlayingRepository;
+
+use crate::store::{get_or_create_store, list_values, mirakc_host_key};
+
+/// 放送中番組用の KV バケット名
+pub const NOW_PLAYING_BUCKET: &str = "kurec_now_playing";
+
+/// NATS KVストアを使用して `NowPlayingRepository` を実装する構造体。
+#[derive(Debug, Clone)]
+pub struct NatsKvNowPlayingRepository {
+    store: Store,
+}
+
+impl NatsKvNowPlayingRepository {
+    /// 新しい `NatsKvNowPlayingRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_now_playing" KV バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let kv_config = KvConfig {
+            bucket: NOW_PLAYING_BUCKET.to_string(),
+            history: 1,
+            ..Default::default()
+        };
+        let store = get_or_create_store(&nats_client, kv_config).await?;
+        Ok(Self { store })
+    }
+
+    /// KVSで使用するキーを生成する。
+    /// キーは `onair_{host}_svc_{service_id}` の形式。
+    fn generate_key(mirakc_url: &str, service_id: i64) -> String {
+        format!("onair_{}_svc_{}", mirakc_host_key(mirakc_url), service_id)
+    }
+}
+
+#[async_trait]
+impl NowPlayingRepository for NatsKvNowPlayingRepository {
+    #[instrument(skip(self, now_playing), fields(key = %Self::generate_key(&now_playing.mirakc_url, now_playing.service_id)))]
+    async fn save_now_playing(&self, now_playing: &NowPlaying) -> Result<()> {
+        let key = Self::generate_key(&now_playing.mirakc_url, now_playing.service_id);
+        let json_data =
+            serde_json::to_vec(now_playing).context("Failed to serialize now playing to JSON")?;
+        let revision = self
+            .store
+            .put(&key, Bytes::from(json_data))
+            .await
+            .with_context(|| format!("NATS KV put operation failed for key '{}'", key))?;
+        debug!(revision, "Successfully saved now playing to NATS KV");
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(key = %Self::generate_key(mirakc_url, service_id)))]
+    async fn get_now_playing(
+        &self,
+        mirakc_url: &str,
+        service_id: i64,
+    ) -> Result<Option<NowPlaying>> {
+        let key = Self::generate_key(mirakc_url, service_id);
+        match self
+            .store
+            .get(&key)
+            .await
+            .with_context(|| format!("NATS KV get operation failed for key '{}'", key))?
+        {
+            Some(value) => Ok(Some(
+                serde_json::from_slice(&value)
+                    .context("Failed to deserialize now playing from JSON")?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn list_now_playing(&self) -> Result<Vec<NowPlaying>> {
+        list_values(&self.store).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_nats_client;
+    use chrono::{TimeZone, Utc};
+    use domain::models::onair::OnairProgram;
+
+    fn now_playing(service_id: i64, event_id: i64) -> NowPlaying {
+        let start_at = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
+        NowPlaying {
+            mirakc_url: "http://test-mirakc:1234".to_string(),
+            service_id,
+            current: Some(OnairProgram {
+                program_id: service_id * 100000 + event_id,
+                event_id,
+                name: Some("テスト番組".to_string()),
+                start_at,
+                duration_millis: 1800000,
+            }),
+            next: None,
+            updated_at: start_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_and_get_now_playing() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsKvNowPlayingRepository::new(nats_client).await?;
+
+        repository.save_now_playing(&now_playing(1024, 1)).await?;
+        repository.save_now_playing(&now_playing(1024, 2)).await?;
+        repository.save_now_playing(&now_playing(1032, 1)).await?;
+
+        let saved = repository
+            .get_now_playing("http://test-mirakc:1234", 1024)
+            .await?
+            .unwrap();
+        assert_eq!(saved.current.unwrap().event_id, 2);
+        assert_eq!(repository.list_now_playing().await?.len(), 2);
+        assert!(repository
+            .get_now_playing("http://test-mirakc:1234", 9999)
+            .await?
+            .is_none());
+
+        Ok(())
+    }
+}
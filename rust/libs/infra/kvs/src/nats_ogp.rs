@@ -0,0 +1,163 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::object_store::{
+    GetErrorKind, InfoErrorKind, ObjectMetadata, ObjectStore,
+};
+use async_trait::async_trait;
+use shared_macros::define_object_store_bucket;
+use shared_types::object_store::ObjectStoreBucket;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+use tracing::{debug, instrument};
+
+use infra_nats::NatsClient;
+
+use domain::models::ogp::{OgpEntry, OgpStatus};
+use domain::ports::repositories::OgpImageRepository;
+
+use crate::store::get_or_create_object_store;
+
+/// OGP 画像の Object Store バケット
+#[define_object_store_bucket(bucket_name = "kurec_ogp_images", description = "OGP 画像")]
+pub struct OgpImageBucket;
+
+/// キャッシュエントリ (JSON) を保存するオブジェクトのメタデータのキー
+const ENTRY_METADATA_KEY: &str = "kurec-ogp-entry";
+
+/// NATS Object Store を使用して `OgpImageRepository` を実装する構造体。
+///
+/// オブジェクト名は URL のハッシュで、画像 (WebP) を本体に、キャッシュエントリをメタデータに保存する。
+/// 画像がなかった場合は空のオブジェクトを保存する。
+#[derive(Clone)]
+pub struct NatsObjectOgpImageRepository {
+    store: ObjectStore,
+}
+
+impl NatsObjectOgpImageRepository {
+    /// 新しい `NatsObjectOgpImageRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_ogp_images" Object Store バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let store = get_or_create_object_store(&nats_client, OgpImageBucket::config()).await?;
+        Ok(Self { store })
+    }
+}
+
+#[async_trait]
+impl OgpImageRepository for NatsObjectOgpImageRepository {
+    #[instrument(skip(self))]
+    async fn get_entry(&self, url_hash: &str) -> Result<Option<OgpEntry>> {
+        let info = match self.store.info(url_hash).await {
+            Ok(info) => info,
+            Err(e) if e.kind() == InfoErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(anyhow::Error::new(e).context(format!(
+                    "NATS Object Store info operation failed for '{}'",
+                    url_hash
+                )))
+            }
+        };
+        let Some(json) = info.metadata.get(ENTRY_METADATA_KEY) else {
+            return Ok(None);
+        };
+        let entry = serde_json::from_str(json)
+            .with_context(|| format!("Failed to parse OGP entry for '{}'", url_hash))?;
+        Ok(Some(entry))
+    }
+
+    #[instrument(skip(self, entry, webp), fields(url = %entry.url, size = webp.len()))]
+    async fn save(&self, entry: &OgpEntry, webp: Vec<u8>) -> Result<()> {
+        let meta = ObjectMetadata {
+            name: entry.url_hash.clone(),
+            description: Some(entry.url.clone()),
+            metadata: HashMap::from([(
+                ENTRY_METADATA_KEY.to_string(),
+                serde_json::to_string(entry)?,
+            )]),
+            ..Default::default()
+        };
+        self.store
+            .put(meta, &mut webp.as_slice())
+            .await
+            .with_context(|| {
+                format!(
+                    "NATS Object Store put operation failed for '{}'",
+                    entry.url_hash
+                )
+            })?;
+        debug!(
+            url_hash = entry.url_hash,
+            "Successfully saved OGP image to NATS Object Store"
+        );
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn get_image(&self, url_hash: &str) -> Result<Option<Vec<u8>>> {
+        match self.get_entry(url_hash).await? {
+            Some(entry) if entry.status == OgpStatus::Found => {}
+            _ => return Ok(None),
+        }
+        let mut object = match self.store.get(url_hash).await {
+            Ok(object) => object,
+            Err(e) if e.kind() == GetErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(anyhow::Error::new(e).context(format!(
+                    "NATS Object Store get operation failed for '{}'",
+                    url_hash
+                )))
+            }
+        };
+        let mut image = Vec::new();
+        object.read_to_end(&mut image).await.with_context(|| {
+            format!(
+                "Failed to read OGP image '{}' from NATS Object Store",
+                url_hash
+            )
+        })?;
+        Ok(Some(image))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_nats_client;
+    use chrono::{TimeZone, Utc};
+
+    fn entry(url_hash: &str, status: OgpStatus) -> OgpEntry {
+        OgpEntry {
+            url: format!("https://example.com/{}", url_hash),
+            url_hash: url_hash.to_string(),
+            status,
+            image_url: None,
+            reason: None,
+            fetched_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_and_get_ogp_image() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsObjectOgpImageRepository::new(nats_client).await?;
+        let webp = b"RIFF\x00\x00\x00\x00WEBP".to_vec();
+
+        assert!(repository.get_entry("found").await?.is_none());
+        assert!(repository.get_image("found").await?.is_none());
+
+        let found = entry("found", OgpStatus::Found);
+        repository.save(&found, webp.clone()).await?;
+        assert_eq!(repository.get_entry("found").await?, Some(found));
+        assert_eq!(repository.get_image("found").await?, Some(webp));
+
+        // 画像がなかった URL はエントリだけを返す
+        let mut not_found = entry("not_found", OgpStatus::NotFound);
+        not_found.reason = Some("og:image がありません".to_string());
+        repository.save(&not_found, Vec::new()).await?;
+        assert_eq!(repository.get_entry("not_found").await?, Some(not_found));
+        assert!(repository.get_image("not_found").await?.is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::kv::{Config as KvConfig, Store};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::sync::Arc;
+use tracing::{debug, instrument};
+
+use infra_nats::NatsClient;
+
+use domain::models::record::Record;
+use domain::ports::repositories::RecordRepository;
+
+use crate::store::{get_or_create_store, list_values, mirakc_host_key};
+
+/// 録画ライブラリ用の KV バケット名
+pub const RECORD_BUCKET: &str = "kurec_records";
+
+/// NATS KVストアを使用して `RecordRepository` を実装する構造体。
+#[derive(Debug, Clone)]
+pub struct NatsKvRecordRepository {
+    store: Store,
+}
+
+impl NatsKvRecordRepository {
+    /// 新しい `NatsKvRecordRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_records" KV バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    ///
+    /// # Arguments
+    ///
+    /// * `nats_client` - 接続済みの `NatsClient`
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let kv_config = KvConfig {
+            bucket: RECORD_BUCKET.to_string(),
+            history: 1,
+            ..Default::default()
+        };
+        let store = get_or_create_store(&nats_client, kv_config).await?;
+        Ok(Self { store })
+    }
+
+    /// KVSで使用するキーを生成する。
+    /// キーは `rec_{host}_{record_id}` の形式。
+    fn generate_key(mirakc_url: &str, record_id: &str) -> String {
+        format!("rec_{}_{}", mirakc_host_key(mirakc_url), record_id)
+    }
+}
+
+#[async_trait]
+impl RecordRepository for NatsKvRecordRepository {
+    #[instrument(skip(self, record), fields(key = %Self::generate_key(&record.mirakc_url, &record.id)))]
+    async fn save_record(&self, record: &Record) -> Result<()> {
+        let key = Self::generate_key(&record.mirakc_url, &record.id);
+        let json_data = serde_json::to_vec(record).context("Failed to serialize record to JSON")?;
+        let revision = self
+            .store
+            .put(&key, Bytes::from(json_data))
+            .await
+            .with_context(|| format!("NATS KV put operation failed for key '{}'", key))?;
+        debug!(revision, "Successfully saved record to NATS KV");
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(key = %Self::generate_key(mirakc_url, record_id)))]
+    async fn get_record(&self, mirakc_url: &str, record_id: &str) -> Result<Option<Record>> {
+        let key = Self::generate_key(mirakc_url, record_id);
+        match self
+            .store
+            .get(&key)
+            .await
+            .with_context(|| format!("NATS KV get operation failed for key '{}'", key))?
+        {
+            Some(value) => {
+                let record = serde_json::from_slice(&value)
+                    .context("Failed to deserialize record from JSON")?;
+                Ok(Some(record))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn list_records(&self) -> Result<Vec<Record>> {
+        list_values(&self.store).await
+    }
+
+    #[instrument(skip(self), fields(key = %Self::generate_key(mirakc_url, record_id)))]
+    async fn remove_record(&self, mirakc_url: &str, record_id: &str) -> Result<()> {
+        let key = Self::generate_key(mirakc_url, record_id);
+        // purge は存在しないキーに対しても成功する
+        self.store
+            .purge(&key)
+            .await
+            .with_context(|| format!("NATS KV purge operation failed for key '{}'", key))?;
+        debug!("Successfully removed record from NATS KV");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_nats_client;
+    use chrono::{TimeZone, Utc};
+    use domain::events::mirakc_events::RecordingStatus;
+    use domain::models::epg::KurecProgram;
+    use domain::models::record::{RecordContent, RecordingInfo};
+
+    fn create_dummy_record(mirakc_url: &str, id: &str) -> Record {
+        let start_at = Utc.timestamp_millis_opt(1678886400000).unwrap();
+        Record {
+            id: id.to_string(),
+            mirakc_url: mirakc_url.to_string(),
+            program: KurecProgram {
+                id: 1000,
+                mirakc_url: mirakc_url.to_string(),
+                service_id: 101,
+                network_id: 1,
+                event_id: 5000,
+                channel_name: "チャンネル101".to_string(),
+                channel_type: "GR".to_string(),
+                channel: "27".to_string(),
+                name: Some(format!("番組{}", id)),
+                description: None,
+                extended: None,
+                start_at,
+                duration_millis: 1800000,
+                is_free: true,
+                genres: vec![],
+                video_info: None,
+                audio_infos: vec![],
+                series_info: None,
+                markers: Default::default(),
+                episode_number: None,
+            },
+            recording: RecordingInfo {
+                status: RecordingStatus::Finished,
+                start_time: start_at,
+                end_time: None,
+                duration_millis: None,
+                failed_reason: None,
+            },
+            content: RecordContent {
+                path: format!("{}.m2ts", id),
+                content_type: "video/MP2T".to_string(),
+                length: Some(1024),
+            },
+            tags: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_get_list_remove_records() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsKvRecordRepository::new(nats_client).await?;
+        let mirakc_url = "http://test-mirakc:1234";
+
+        let record1 = create_dummy_record(mirakc_url, "0000000000000001");
+        let record2 = create_dummy_record(mirakc_url, "0000000000000002");
+        repository.save_record(&record1).await?;
+        repository.save_record(&record2).await?;
+
+        assert_eq!(
+            repository.get_record(mirakc_url, &record1.id).await?,
+            Some(record1.clone())
+        );
+        assert_eq!(repository.list_records().await?.len(), 2);
+
+        repository.remove_record(mirakc_url, &record1.id).await?;
+        assert!(repository
+            .get_record(mirakc_url, &record1.id)
+            .await?
+            .is_none());
+        assert_eq!(repository.list_records().await?, vec![record2]);
+
+        // 存在しないレコードの削除も成功する
+        repository.remove_record(mirakc_url, "missing").await?;
+
+        Ok(())
+    }
+}
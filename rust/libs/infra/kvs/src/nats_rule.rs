@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::kv::{Config as KvConfig, Store};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::sync::Arc;
+use tracing::{debug, instrument};
+
+use infra_nats::NatsClient;
+
+use domain::models::rule::RecordingRule;
+use domain::ports::repositories::RecordingRuleRepository;
+
+use crate::store::{get_or_create_store, list_values};
+
+/// 自動録画ルール用の KV バケット名
+pub const RECORDING_RULES_BUCKET: &str = "kurec_recording_rules";
+
+/// NATS KVストアを使用して `RecordingRuleRepository` を実装する構造体。
+#[derive(Debug, Clone)]
+pub struct NatsKvRecordingRuleRepository {
+    store: Store,
+}
+
+impl NatsKvRecordingRuleRepository {
+    /// 新しい `NatsKvRecordingRuleRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_recording_rules" KV バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let kv_config = KvConfig {
+            bucket: RECORDING_RULES_BUCKET.to_string(),
+            history: 1,
+            ..Default::default()
+        };
+        let store = get_or_create_store(&nats_client, kv_config).await?;
+        Ok(Self { store })
+    }
+
+    /// KVSで使用するキーを生成する。キーは `rule_{id}` の形式。
+    fn generate_key(rule_id: &str) -> String {
+        format!("rule_{}", rule_id)
+    }
+}
+
+#[async_trait]
+impl RecordingRuleRepository for NatsKvRecordingRuleRepository {
+    #[instrument(skip(self, rule), fields(key = %Self::generate_key(&rule.id)))]
+    async fn save_rule(&self, rule: &RecordingRule) -> Result<()> {
+        let key = Self::generate_key(&rule.id);
+        let json_data = serde_json::to_vec(rule).context("Failed to serialize rule to JSON")?;
+        let revision = self
+            .store
+            .put(&key, Bytes::from(json_data))
+            .await
+            .with_context(|| format!("NATS KV put operation failed for key '{}'", key))?;
+        debug!(revision, "Successfully saved recording rule to NATS KV");
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(key = %Self::generate_key(rule_id)))]
+    async fn get_rule(&self, rule_id: &str) -> Result<Option<RecordingRule>> {
+        let key = Self::generate_key(rule_id);
+        match self
+            .store
+            .get(&key)
+            .await
+            .with_context(|| format!("NATS KV get operation failed for key '{}'", key))?
+        {
+            Some(value) => {
+                let rule = serde_json::from_slice(&value)
+                    .context("Failed to deserialize rule from JSON")?;
+                Ok(Some(rule))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn list_rules(&self) -> Result<Vec<RecordingRule>> {
+        list_values(&self.store).await
+    }
+
+    #[instrument(skip(self), fields(key = %Self::generate_key(rule_id)))]
+    async fn delete_rule(&self, rule_id: &str) -> Result<()> {
+        let key = Self::generate_key(rule_id);
+        self.store
+            .purge(&key)
+            .await
+            .with_context(|| format!("NATS KV purge operation failed for key '{}'", key))?;
+        debug!("Successfully deleted recording rule from NATS KV");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_nats_client;
+    use domain::models::rule::RuleConditions;
+
+    fn rule(id: &str, keyword: &str) -> RecordingRule {
+        RecordingRule {
+            id: id.to_string(),
+            name: keyword.to_string(),
+            enabled: true,
+            priority: 0,
+            conditions: RuleConditions {
+                keywords: vec![keyword.to_string()],
+                ..Default::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_get_list_delete_rule() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsKvRecordingRuleRepository::new(nats_client).await?;
+
+        assert!(repository.get_rule("gundam").await?.is_none());
+
+        repository.save_rule(&rule("gundam", "ガンダム")).await?;
+        repository.save_rule(&rule("news", "ニュース")).await?;
+        repository.save_rule(&rule("gundam", "機動戦士")).await?;
+
+        let saved = repository.get_rule("gundam").await?.unwrap();
+        assert_eq!(saved.conditions.keywords, vec!["機動戦士"]);
+        assert_eq!(repository.list_rules().await?.len(), 2);
+
+        repository.delete_rule("gundam").await?;
+        assert!(repository.get_rule("gundam").await?.is_none());
+        assert_eq!(repository.list_rules().await?.len(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::kv::{Config as KvConfig, Store};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::sync::Arc;
+use tracing::{debug, instrument};
+
+use infra_nats::NatsClient;
+
+use domain::models::schedule::DesiredSchedule;
+use domain::ports::repositories::DesiredScheduleRepository;
+
+use crate::store::{get_or_create_store, list_values, mirakc_host_key};
+
+/// 録画予約の望ましい状態用の KV バケット名
+pub const DESIRED_SCHEDULES_BUCKET: &str = "kurec_desired_schedules";
+
+/// NATS KVストアを使用して `DesiredScheduleRepository` を実装する構造体。
+#[derive(Debug, Clone)]
+pub struct NatsKvDesiredScheduleRepository {
+    store: Store,
+}
+
+impl NatsKvDesiredScheduleRepository {
+    /// 新しい `NatsKvDesiredScheduleRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_desired_schedules" KV バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let kv_config = KvConfig {
+            bucket: DESIRED_SCHEDULES_BUCKET.to_string(),
+            history: 1,
+            ..Default::default()
+        };
+        let store = get_or_create_store(&nats_client, kv_config).await?;
+        Ok(Self { store })
+    }
+
+    /// KVSで使用するキーを生成する。キーは `desired_{host}_{program_id}` の形式。
+    fn generate_key(mirakc_url: &str, program_id: i64) -> String {
+        format!("desired_{}_{}", mirakc_host_key(mirakc_url), program_id)
+    }
+}
+
+#[async_trait]
+impl DesiredScheduleRepository for NatsKvDesiredScheduleRepository {
+    #[instrument(skip(self, schedule), fields(key = %Self::generate_key(&schedule.mirakc_url, schedule.program_id)))]
+    async fn save_schedule(&self, schedule: &DesiredSchedule) -> Result<()> {
+        let key = Self::generate_key(&schedule.mirakc_url, schedule.program_id);
+        let json_data =
+            serde_json::to_vec(schedule).context("Failed to serialize schedule to JSON")?;
+        let revision = self
+            .store
+            .put(&key, Bytes::from(json_data))
+            .await
+            .with_context(|| format!("NATS KV put operation failed for key '{}'", key))?;
+        debug!(revision, "Successfully saved desired schedule to NATS KV");
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(key = %Self::generate_key(mirakc_url, program_id)))]
+    async fn get_schedule(
+        &self,
+        mirakc_url: &str,
+        program_id: i64,
+    ) -> Result<Option<DesiredSchedule>> {
+        let key = Self::generate_key(mirakc_url, program_id);
+        match self
+            .store
+            .get(&key)
+            .await
+            .with_context(|| format!("NATS KV get operation failed for key '{}'", key))?
+        {
+            Some(value) => {
+                let schedule = serde_json::from_slice(&value)
+                    .context("Failed to deserialize schedule from JSON")?;
+                Ok(Some(schedule))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn list_schedules(&self) -> Result<Vec<DesiredSchedule>> {
+        list_values(&self.store).await
+    }
+
+    #[instrument(skip(self), fields(key = %Self::generate_key(mirakc_url, program_id)))]
+    async fn delete_schedule(&self, mirakc_url: &str, program_id: i64) -> Result<()> {
+        let key = Self::generate_key(mirakc_url, program_id);
+        self.store
+            .purge(&key)
+            .await
+            .with_context(|| format!("NATS KV purge operation failed for key '{}'", key))?;
+        debug!("Successfully deleted desired schedule from NATS KV");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_nats_client;
+    use chrono::{TimeZone, Utc};
+
+    fn schedule(mirakc_url: &str, program_id: i64) -> DesiredSchedule {
+        DesiredSchedule {
+            mirakc_url: mirakc_url.to_string(),
+            program_id,
+            service_id: 3273601024,
+            channel_type: "GR".to_string(),
+            name: Some("機動戦士ガンダム".to_string()),
+            start_at: Utc.with_ymd_and_hms(2025, 1, 4, 9, 0, 0).unwrap(),
+            duration_millis: 1800000,
+            rule_matches: vec![],
+            manual: true,
+            series: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_get_list_delete_schedule() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsKvDesiredScheduleRepository::new(nats_client).await?;
+        let url1 = "http://tuner1:40772";
+        let url2 = "http://tuner2:40772";
+
+        assert!(repository.get_schedule(url1, 1).await?.is_none());
+
+        repository.save_schedule(&schedule(url1, 1)).await?;
+        repository.save_schedule(&schedule(url2, 1)).await?;
+        assert_eq!(
+            repository.get_schedule(url1, 1).await?,
+            Some(schedule(url1, 1))
+        );
+        assert_eq!(repository.list_schedules().await?.len(), 2);
+
+        repository.delete_schedule(url1, 1).await?;
+        assert!(repository.get_schedule(url1, 1).await?.is_none());
+        assert_eq!(repository.list_schedules().await?.len(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::kv::{Config as KvConfig, Store};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::sync::Arc;
+use tracing::{debug, instrument};
+
+use infra_nats::NatsClient;
+
+use domain::models::series::SeriesSubscription;
+use domain::ports::repositories::SeriesSubscriptionRepository;
+
+use crate::store::{get_or_create_store, list_values};
+
+/// シリーズ録画の購読用の KV バケット名
+pub const SERIES_SUBSCRIPTIONS_BUCKET: &str = "kurec_series_subscriptions";
+
+/// NATS KVストアを使用して `SeriesSubscriptionRepository` を実装する構造体。
+#[derive(Debug, Clone)]
+pub struct NatsKvSeriesSubscriptionRepository {
+    store: Store,
+}
+
+impl NatsKvSeriesSubscriptionRepository {
+    /// 新しい `NatsKvSeriesSubscriptionRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_series_subscriptions" KV バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let kv_config = KvConfig {
+            bucket: SERIES_SUBSCRIPTIONS_BUCKET.to_string(),
+            history: 1,
+            ..Default::default()
+        };
+        let store = get_or_create_store(&nats_client, kv_config).await?;
+        Ok(Self { store })
+    }
+
+    /// KVSで使用するキーを生成する。キーは `series_{id}` の形式。
+    fn generate_key(id: &str) -> String {
+        format!("series_{}", id)
+    }
+}
+
+#[async_trait]
+impl SeriesSubscriptionRepository for NatsKvSeriesSubscriptionRepository {
+    #[instrument(skip(self, subscription), fields(key = %Self::generate_key(&subscription.id)))]
+    async fn save_subscription(&self, subscription: &SeriesSubscription) -> Result<()> {
+        let key = Self::generate_key(&subscription.id);
+        let json_data = serde_json::to_vec(subscription)
+            .context("Failed to serialize series subscription to JSON")?;
+        let revision = self
+            .store
+            .put(&key, Bytes::from(json_data))
+            .await
+            .with_context(|| format!("NATS KV put operation failed for key '{}'", key))?;
+        debug!(
+            revision,
+            "Successfully saved series subscription to NATS KV"
+        );
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(key = %Self::generate_key(id)))]
+    async fn get_subscription(&self, id: &str) -> Result<Option<SeriesSubscription>> {
+        let key = Self::generate_key(id);
+        match self
+            .store
+            .get(&key)
+            .await
+            .with_context(|| format!("NATS KV get operation failed for key '{}'", key))?
+        {
+            Some(value) => {
+                let subscription = serde_json::from_slice(&value)
+                    .context("Failed to deserialize series subscription from JSON")?;
+                Ok(Some(subscription))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn list_subscriptions(&self) -> Result<Vec<SeriesSubscription>> {
+        list_values(&self.store).await
+    }
+
+    #[instrument(skip(self), fields(key = %Self::generate_key(id)))]
+    async fn delete_subscription(&self, id: &str) -> Result<()> {
+        let key = Self::generate_key(id);
+        self.store
+            .purge(&key)
+            .await
+            .with_context(|| format!("NATS KV purge operation failed for key '{}'", key))?;
+        debug!("Successfully deleted series subscription from NATS KV");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_nats_client;
+    use chrono::Utc;
+    use domain::models::series::SeriesStatus;
+
+    fn subscription(series_id: i64) -> SeriesSubscription {
+        SeriesSubscription {
+            id: SeriesSubscription::key(32736, series_id),
+            network_id: 32736,
+            series_id,
+            name: "機動戦士ガンダム".to_string(),
+            expire_at: None,
+            last_episode: Some(12),
+            recorded_episodes: vec![1, 2],
+            status: SeriesStatus::Active,
+            subscribed_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_get_list_delete_subscription() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsKvSeriesSubscriptionRepository::new(nats_client).await?;
+
+        assert!(repository.get_subscription("32736_100").await?.is_none());
+
+        let first = subscription(100);
+        repository.save_subscription(&first).await?;
+        repository.save_subscription(&subscription(200)).await?;
+        assert_eq!(repository.get_subscription("32736_100").await?, Some(first));
+        assert_eq!(repository.list_subscriptions().await?.len(), 2);
+
+        repository.delete_subscription("32736_100").await?;
+        assert!(repository.get_subscription("32736_100").await?.is_none());
+        assert_eq!(repository.list_subscriptions().await?.len(), 1);
+
+        Ok(())
+    }
+}
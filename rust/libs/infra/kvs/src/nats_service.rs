@@ -0,0 +1,279 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::kv::{Config as KvConfig, Store};
+use async_nats::jetstream::object_store::{DeleteErrorKind, GetErrorKind, ObjectStore};
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+use shared_macros::define_object_store_bucket;
+use shared_types::object_store::ObjectStoreBucket;
+use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+use tracing::{debug, instrument};
+
+use infra_nats::NatsClient;
+
+use domain::models::service::{Channel, Service};
+use domain::ports::repositories::{ServiceLogoRepository, ServiceRepository};
+
+use crate::store::{
+    get_or_create_object_store, get_or_create_store, list_values_with_prefix, mirakc_host_key,
+};
+
+/// サービスカタログ用の KV バケット名
+pub const SERVICES_BUCKET: &str = "kurec_services";
+
+/// サービスのロゴの Object Store バケット
+#[define_object_store_bucket(bucket_name = "kurec_service_logos", description = "サービスのロゴ")]
+pub struct ServiceLogoBucket;
+
+/// NATS KVストアを使用して `ServiceRepository` を実装する構造体。
+///
+/// サービスとチャンネルは mirakc ごとに1つのキーへリストとして保存する。
+#[derive(Debug, Clone)]
+pub struct NatsKvServiceRepository {
+    store: Store,
+}
+
+impl NatsKvServiceRepository {
+    /// 新しい `NatsKvServiceRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_services" KV バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let kv_config = KvConfig {
+            bucket: SERVICES_BUCKET.to_string(),
+            history: 1,
+            ..Default::default()
+        };
+        let store = get_or_create_store(&nats_client, kv_config).await?;
+        Ok(Self { store })
+    }
+
+    /// サービス一覧のキーを生成する。キーは `services_{host}` の形式。
+    fn services_key(mirakc_url: &str) -> String {
+        format!("services_{}", mirakc_host_key(mirakc_url))
+    }
+
+    /// チャンネル一覧のキーを生成する。キーは `channels_{host}` の形式。
+    fn channels_key(mirakc_url: &str) -> String {
+        format!("channels_{}", mirakc_host_key(mirakc_url))
+    }
+
+    async fn put_list<T: serde::Serialize>(&self, key: &str, items: &[T]) -> Result<()> {
+        let json_data = serde_json::to_vec(items).context("Failed to serialize list to JSON")?;
+        let revision = self
+            .store
+            .put(key, Bytes::from(json_data))
+            .await
+            .with_context(|| format!("NATS KV put operation failed for key '{}'", key))?;
+        debug!(key, revision, "Successfully saved list to NATS KV");
+        Ok(())
+    }
+
+    async fn get_list<T: DeserializeOwned>(&self, key: &str) -> Result<Vec<T>> {
+        match self
+            .store
+            .get(key)
+            .await
+            .with_context(|| format!("NATS KV get operation failed for key '{}'", key))?
+        {
+            Some(value) => {
+                serde_json::from_slice(&value).context("Failed to deserialize list from JSON")
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl ServiceRepository for NatsKvServiceRepository {
+    #[instrument(skip(self, services), fields(count = services.len()))]
+    async fn save_services(&self, mirakc_url: &str, services: &[Service]) -> Result<()> {
+        self.put_list(&Self::services_key(mirakc_url), services)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn list_services(&self, mirakc_url: &str) -> Result<Vec<Service>> {
+        self.get_list(&Self::services_key(mirakc_url)).await
+    }
+
+    #[instrument(skip(self))]
+    async fn list_all_services(&self) -> Result<Vec<Service>> {
+        Ok(
+            list_values_with_prefix::<Vec<Service>>(&self.store, "services_")
+                .await?
+                .into_iter()
+                .flatten()
+                .collect(),
+        )
+    }
+
+    #[instrument(skip(self, channels), fields(count = channels.len()))]
+    async fn save_channels(&self, mirakc_url: &str, channels: &[Channel]) -> Result<()> {
+        self.put_list(&Self::channels_key(mirakc_url), channels)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn list_channels(&self, mirakc_url: &str) -> Result<Vec<Channel>> {
+        self.get_list(&Self::channels_key(mirakc_url)).await
+    }
+}
+
+/// NATS Object Store を使用して `ServiceLogoRepository` を実装する構造体。
+#[derive(Clone)]
+pub struct NatsObjectServiceLogoRepository {
+    store: ObjectStore,
+}
+
+impl NatsObjectServiceLogoRepository {
+    /// 新しい `NatsObjectServiceLogoRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_service_logos" Object Store バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let store = get_or_create_object_store(&nats_client, ServiceLogoBucket::config()).await?;
+        Ok(Self { store })
+    }
+
+    /// オブジェクト名を生成する。名前は `logo_{host}_{id}` の形式。
+    fn object_name(mirakc_url: &str, id: i64) -> String {
+        format!("logo_{}_{}", mirakc_host_key(mirakc_url), id)
+    }
+}
+
+#[async_trait]
+impl ServiceLogoRepository for NatsObjectServiceLogoRepository {
+    #[instrument(skip(self, image), fields(size = image.len()))]
+    async fn save_logo(&self, mirakc_url: &str, id: i64, image: Vec<u8>) -> Result<()> {
+        let name = Self::object_name(mirakc_url, id);
+        self.store
+            .put(name.as_str(), &mut image.as_slice())
+            .await
+            .with_context(|| format!("NATS Object Store put operation failed for '{}'", name))?;
+        debug!(name, "Successfully saved logo to NATS Object Store");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn get_logo(&self, mirakc_url: &str, id: i64) -> Result<Option<Vec<u8>>> {
+        let name = Self::object_name(mirakc_url, id);
+        let mut object = match self.store.get(&name).await {
+            Ok(object) => object,
+            Err(e) if e.kind() == GetErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(anyhow::Error::new(e).context(format!(
+                    "NATS Object Store get operation failed for '{}'",
+                    name
+                )))
+            }
+        };
+        let mut image = Vec::new();
+        object
+            .read_to_end(&mut image)
+            .await
+            .with_context(|| format!("Failed to read logo '{}' from NATS Object Store", name))?;
+        Ok(Some(image))
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_logo(&self, mirakc_url: &str, id: i64) -> Result<()> {
+        let name = Self::object_name(mirakc_url, id);
+        match self.store.delete(&name).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == DeleteErrorKind::NotFound => Ok(()),
+            Err(e) => Err(anyhow::Error::new(e).context(format!(
+                "NATS Object Store delete operation failed for '{}'",
+                name
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_nats_client;
+
+    fn service(mirakc_url: &str, service_id: i64, name: &str) -> Service {
+        Service {
+            mirakc_url: mirakc_url.to_string(),
+            id: 32736 * 100000 + service_id,
+            service_id,
+            network_id: 32736,
+            name: name.to_string(),
+            service_type: 1,
+            logo_id: Some(0),
+            remote_control_key_id: Some(1),
+            has_logo_data: true,
+            channel_type: "GR".to_string(),
+            channel: "27".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_and_list_services_and_channels() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsKvServiceRepository::new(nats_client).await?;
+        let url1 = "http://tuner1:40772";
+        let url2 = "http://tuner2:40772";
+
+        assert!(repository.list_services(url1).await?.is_empty());
+
+        repository
+            .save_services(
+                url1,
+                &[
+                    service(url1, 1024, "ＮＨＫ総合"),
+                    service(url1, 1032, "ＮＨＫＥテレ"),
+                ],
+            )
+            .await?;
+        repository
+            .save_services(url2, &[service(url2, 1024, "ＮＨＫ総合")])
+            .await?;
+        repository
+            .save_channels(
+                url1,
+                &[Channel {
+                    mirakc_url: url1.to_string(),
+                    channel_type: "GR".to_string(),
+                    channel: "27".to_string(),
+                    name: "NHK総合".to_string(),
+                    service_ids: vec![3273601024],
+                }],
+            )
+            .await?;
+
+        assert_eq!(repository.list_services(url1).await?.len(), 2);
+        assert_eq!(repository.list_all_services().await?.len(), 3);
+        assert_eq!(
+            repository.list_channels(url1).await?[0].service_ids,
+            vec![3273601024]
+        );
+        assert!(repository.list_channels(url2).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_save_get_delete_logo() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsObjectServiceLogoRepository::new(nats_client).await?;
+        let url = "http://tuner:40772";
+        let png = vec![0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a];
+
+        assert!(repository.get_logo(url, 3273601024).await?.is_none());
+
+        repository.save_logo(url, 3273601024, png.clone()).await?;
+        assert_eq!(repository.get_logo(url, 3273601024).await?, Some(png));
+
+        repository.delete_logo(url, 3273601024).await?;
+        assert!(repository.get_logo(url, 3273601024).await?.is_none());
+        // 存在しないロゴの削除はエラーにならない
+        repository.delete_logo(url, 3273601024).await?;
+
+        Ok(())
+    }
+}
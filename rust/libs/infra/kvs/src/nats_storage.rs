@@ -0,0 +1,466 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::context::Context as JetStreamContext;
+use async_nats::jetstream::object_store::{
+    Config as ObjectStoreConfig, InfoErrorKind, ObjectInfo, ObjectOptions, ObjectStore,
+};
+use async_nats::jetstream::stream::Stream;
+use async_nats::HeaderMap;
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE;
+use base64::Engine;
+use bytes::{Bytes, BytesMut};
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt};
+use sha2::{Digest, Sha256};
+use shared_macros::define_object_store_bucket;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio_util::io::ReaderStream;
+use tracing::{debug, instrument, warn};
+
+use infra_nats::NatsClient;
+
+use domain::models::record::parse_output_location;
+use domain::ports::storage::{validate_location, ByteStream, Storage, StoredObject};
+
+use crate::store::get_or_create_object_store;
+
+/// 内容の MIME タイプを保存するヘッダー
+pub const CONTENT_TYPE_HEADER: &str = "Content-Type";
+/// 録画の Record ID を保存するヘッダー
+pub const RECORD_ID_HEADER: &str = "Kurec-Record-Id";
+/// 出力名を保存するヘッダー
+pub const OUTPUT_NAME_HEADER: &str = "Kurec-Output-Name";
+
+/// チャンクのデフォルトのサイズ (NATS のメッセージの最大サイズより十分小さくする)
+const DEFAULT_CHUNK_SIZE: usize = 128 * 1024;
+
+/// 録画ファイルとジョブの出力を保存する Object Store バケット
+#[define_object_store_bucket(
+    bucket_name = "kurec_storage",
+    description = "録画ファイルとジョブの出力"
+)]
+pub struct StorageBucket;
+
+/// NATS Object Store を使用して `Storage` を実装する構造体。
+///
+/// オブジェクト名はストレージ内の位置で、内容はチャンクに分けて送受信する。
+/// MIME タイプと、位置が録画の出力の形式であれば Record ID と出力名をヘッダーに保存する。
+///
+/// async-nats の `ObjectStore::put` は失敗したアップロードのチャンクを削除しないため、
+/// 保存だけは同じ形式 (チャンクを `$O.{bucket}.C.{nuid}` に、情報を `$O.{bucket}.M.{name}` に送る) で行う。
+/// チャンクをすべて送ってから情報を送るので、途中で失敗した場合は同じ位置にあったオブジェクトを残し、
+/// 送信済みのチャンクを削除する。
+#[derive(Clone)]
+pub struct NatsObjectStorage {
+    name: String,
+    bucket: String,
+    store: ObjectStore,
+    context: JetStreamContext,
+    stream: Stream,
+    chunk_size: usize,
+}
+
+impl NatsObjectStorage {
+    /// 新しい `NatsObjectStorage` を作成する。
+    ///
+    /// `config` のバケットが存在しない場合は作成されます。
+    /// 通常は `StorageBucket::config()` を使います。
+    pub async fn new(
+        name: impl Into<String>,
+        nats_client: Arc<NatsClient>,
+        config: ObjectStoreConfig,
+    ) -> Result<Self> {
+        let bucket = config.bucket.clone();
+        let store = get_or_create_object_store(&nats_client, config).await?;
+        let context = nats_client.jetstream_context().clone();
+        let stream = context
+            .get_stream(format!("OBJ_{}", bucket))
+            .await
+            .with_context(|| format!("Object Store '{}' のストリームを取得できません", bucket))?;
+        Ok(Self {
+            name: name.into(),
+            bucket,
+            store,
+            context,
+            stream,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        })
+    }
+
+    /// 送信するチャンクのサイズを設定する。
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// オブジェクトの情報を取得する。存在しない場合や削除済みの場合は `None`。
+    async fn info(&self, location: &str) -> Result<Option<ObjectInfo>> {
+        validate_location(location)?;
+        match self.store.info(location).await {
+            Ok(info) if info.deleted => Ok(None),
+            Ok(info) => Ok(Some(info)),
+            Err(e) if e.kind() == InfoErrorKind::NotFound => Ok(None),
+            Err(e) => Err(anyhow::Error::new(e).context(format!(
+                "NATS Object Store info operation failed for '{}'",
+                location
+            ))),
+        }
+    }
+}
+
+impl NatsObjectStorage {
+    /// 内容をチャンクに分けて `chunk_subject` に送り、最後にオブジェクトの情報を送る。
+    async fn upload(
+        &self,
+        location: &str,
+        headers: HeaderMap,
+        nuid: &str,
+        chunk_subject: &str,
+        mut data: ByteStream,
+    ) -> Result<ObjectInfo> {
+        let mut buffer = BytesMut::with_capacity(self.chunk_size);
+        let mut sha256 = Sha256::new();
+        let mut size = 0;
+        let mut chunks = 0;
+        loop {
+            let chunk = data.next().await.transpose()?;
+            if let Some(chunk) = &chunk {
+                buffer.extend_from_slice(chunk);
+            }
+            while buffer.len() >= self.chunk_size || (chunk.is_none() && !buffer.is_empty()) {
+                let len = buffer.len().min(self.chunk_size);
+                let payload = buffer.split_to(len).freeze();
+                sha256.update(&payload);
+                size += payload.len();
+                chunks += 1;
+                self.publish(chunk_subject, HeaderMap::new(), payload)
+                    .await
+                    .context("チャンクを送れません")?;
+            }
+            if chunk.is_none() {
+                break;
+            }
+        }
+
+        let info = ObjectInfo {
+            name: location.to_string(),
+            description: None,
+            metadata: HashMap::new(),
+            headers: Some(headers),
+            options: Some(ObjectOptions {
+                max_chunk_size: Some(self.chunk_size),
+                link: None,
+            }),
+            bucket: self.bucket.clone(),
+            nuid: nuid.to_string(),
+            size,
+            chunks,
+            modified: Some(time::OffsetDateTime::now_utc()),
+            digest: Some(format!("SHA-256={}", URL_SAFE.encode(sha256.finalize()))),
+            deleted: false,
+        };
+        // 同じ位置の以前の情報を置き換える
+        let mut meta_headers = HeaderMap::new();
+        meta_headers.insert("Nats-Rollup", "sub");
+        self.publish(
+            &format!("$O.{}.M.{}", self.bucket, URL_SAFE.encode(location)),
+            meta_headers,
+            Bytes::from(serde_json::to_vec(&info)?),
+        )
+        .await
+        .context("オブジェクトの情報を送れません")?;
+        Ok(info)
+    }
+
+    async fn publish(&self, subject: &str, headers: HeaderMap, payload: Bytes) -> Result<()> {
+        self.context
+            .publish_with_headers(subject.to_string(), headers, payload)
+            .await?
+            .await?;
+        Ok(())
+    }
+
+    /// `subject` のチャンクを削除する。
+    async fn purge_chunks(&self, chunk_subject: &str) -> Result<()> {
+        self.stream
+            .purge()
+            .filter(chunk_subject)
+            .await
+            .with_context(|| format!("{} のチャンクを削除できません", chunk_subject))?;
+        Ok(())
+    }
+}
+
+fn stored_object(info: &ObjectInfo) -> StoredObject {
+    StoredObject {
+        location: info.name.clone(),
+        size: info.size as u64,
+        content_type: info
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.get(CONTENT_TYPE_HEADER))
+            .map(|value| value.as_str().to_string()),
+        modified_at: info.modified.and_then(|modified| {
+            DateTime::<Utc>::from_timestamp(modified.unix_timestamp(), modified.nanosecond())
+        }),
+    }
+}
+
+#[async_trait]
+impl Storage for NatsObjectStorage {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[instrument(skip(self, data))]
+    async fn put(
+        &self,
+        location: &str,
+        content_type: &str,
+        data: ByteStream,
+    ) -> Result<StoredObject> {
+        validate_location(location)?;
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE_HEADER, content_type);
+        if let Some((record_id, output_name)) = parse_output_location(location) {
+            headers.insert(RECORD_ID_HEADER, record_id);
+            headers.insert(OUTPUT_NAME_HEADER, output_name);
+        }
+        let existing = self.info(location).await?;
+        let nuid = nuid::next().to_string();
+        let chunk_subject = format!("$O.{}.C.{}", self.bucket, nuid);
+        let info = match self
+            .upload(location, headers, &nuid, &chunk_subject, data)
+            .await
+        {
+            Ok(info) => info,
+            Err(e) => {
+                if let Err(e) = self.purge_chunks(&chunk_subject).await {
+                    warn!(location, "送信済みのチャンクを削除できません: {:?}", e);
+                }
+                return Err(e.context(format!(
+                    "NATS Object Store put operation failed for '{}'",
+                    location
+                )));
+            }
+        };
+        // 置き換えたオブジェクトのチャンクを削除する
+        if let Some(existing) = existing {
+            let chunk_subject = format!("$O.{}.C.{}", self.bucket, existing.nuid);
+            if let Err(e) = self.purge_chunks(&chunk_subject).await {
+                warn!(location, "以前のチャンクを削除できません: {:?}", e);
+            }
+        }
+        debug!(
+            location,
+            size = info.size,
+            chunks = info.chunks,
+            "Successfully saved object to NATS Object Store"
+        );
+        Ok(stored_object(&info))
+    }
+
+    #[instrument(skip(self))]
+    async fn get(&self, location: &str) -> Result<Option<ByteStream>> {
+        if self.info(location).await?.is_none() {
+            return Ok(None);
+        }
+        let object = self.store.get(location).await.with_context(|| {
+            format!("NATS Object Store get operation failed for '{}'", location)
+        })?;
+        Ok(Some(
+            ReaderStream::with_capacity(object, self.chunk_size)
+                .map_err(anyhow::Error::from)
+                .boxed(),
+        ))
+    }
+
+    #[instrument(skip(self))]
+    async fn delete(&self, location: &str) -> Result<()> {
+        if self.info(location).await?.is_none() {
+            return Ok(());
+        }
+        self.store.delete(location).await.with_context(|| {
+            format!(
+                "NATS Object Store delete operation failed for '{}'",
+                location
+            )
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>> {
+        let mut objects: Vec<StoredObject> = self
+            .store
+            .list()
+            .await
+            .context("NATS Object Store list operation failed")?
+            .try_filter(|info| futures::future::ready(info.name.starts_with(prefix)))
+            .map_ok(|info| stored_object(&info))
+            .try_collect()
+            .await
+            .context("Failed to list objects from NATS Object Store")?;
+        objects.sort_by(|a, b| a.location.cmp(&b.location));
+        Ok(objects)
+    }
+
+    #[instrument(skip(self))]
+    async fn stat(&self, location: &str) -> Result<Option<StoredObject>> {
+        Ok(self.info(location).await?.as_ref().map(stored_object))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_nats_client;
+    use bytes::Bytes;
+    use futures::stream;
+    use shared_types::object_store::ObjectStoreBucket;
+
+    fn chunks(data: &[u8], size: usize) -> ByteStream {
+        let chunks: Vec<Result<Bytes>> = data
+            .chunks(size)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+        stream::iter(chunks).boxed()
+    }
+
+    /// バケットに残っているチャンクの数
+    async fn chunk_count(storage: &NatsObjectStorage) -> Result<usize> {
+        let counts: Vec<(String, usize)> = storage
+            .stream
+            .info_with_subjects(format!("$O.{}.C.>", storage.bucket))
+            .await?
+            .try_collect()
+            .await?;
+        Ok(counts.into_iter().map(|(_, count)| count).sum())
+    }
+
+    async fn read(storage: &NatsObjectStorage, location: &str) -> Result<Option<Vec<u8>>> {
+        let Some(stream) = storage.get(location).await? else {
+            return Ok(None);
+        };
+        let chunks: Vec<Bytes> = stream.try_collect().await?;
+        Ok(Some(chunks.concat()))
+    }
+
+    #[test]
+    fn test_storage_bucket() {
+        let config = StorageBucket::config();
+        assert_eq!(config.bucket, "kurec_storage");
+        assert_eq!(
+            config.description.as_deref(),
+            Some("録画ファイルとジョブの出力")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nats_object_storage() -> Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let storage = NatsObjectStorage::new("nats1", nats_client.clone(), StorageBucket::config())
+            .await?
+            .with_chunk_size(1024);
+        let location = "2025/01/01/0000000000000001/output.mp4";
+
+        assert_eq!(storage.stat(location).await?, None);
+        assert_eq!(read(&storage, location).await?, None);
+
+        // 複数のチャンクに分けて保存する
+        let data: Vec<u8> = (0..5000).map(|i| (i % 251) as u8).collect();
+        let object = storage
+            .put(location, "video/mp4", chunks(&data, 700))
+            .await?;
+        assert_eq!(object.location, location);
+        assert_eq!(object.size, 5000);
+        assert_eq!(object.content_type.as_deref(), Some("video/mp4"));
+        assert!(object.modified_at.is_some());
+        assert_eq!(read(&storage, location).await?, Some(data.clone()));
+
+        let info = storage.store.info(location).await?;
+        assert_eq!(info.chunks, 5);
+        let headers = info.headers.unwrap();
+        assert_eq!(
+            headers.get(RECORD_ID_HEADER).map(|v| v.as_str()),
+            Some("0000000000000001")
+        );
+        assert_eq!(
+            headers.get(OUTPUT_NAME_HEADER).map(|v| v.as_str()),
+            Some("output.mp4")
+        );
+
+        storage
+            .put("logs/1.log", "text/plain", chunks(b"log", 3))
+            .await?;
+        let locations: Vec<String> = storage
+            .list("2025/")
+            .await?
+            .into_iter()
+            .map(|object| object.location)
+            .collect();
+        assert_eq!(locations, vec![location]);
+        assert_eq!(storage.list("").await?.len(), 2);
+
+        // 置き換えた場合は以前のチャンクを削除する
+        let chunks_before = chunk_count(&storage).await?;
+        storage
+            .put(location, "video/mp4", chunks(&data, 700))
+            .await?;
+        assert_eq!(chunk_count(&storage).await?, chunks_before);
+
+        // 途中で失敗した場合は以前のオブジェクトを残し、送信済みのチャンクも残さない
+        let broken = chunks(&data[..3000], 700)
+            .chain(stream::iter(vec![Err(anyhow::anyhow!("接続が切れました"))]))
+            .boxed();
+        assert!(storage.put(location, "video/mp4", broken).await.is_err());
+        assert_eq!(read(&storage, location).await?, Some(data.clone()));
+        assert_eq!(chunk_count(&storage).await?, chunks_before);
+
+        let broken = chunks(&data[..3000], 700)
+            .chain(stream::iter(vec![Err(anyhow::anyhow!("接続が切れました"))]))
+            .boxed();
+        assert!(storage
+            .put("2025/01/01/2/input.ts", "video/mp2t", broken)
+            .await
+            .is_err());
+        assert_eq!(storage.stat("2025/01/01/2/input.ts").await?, None);
+        assert_eq!(chunk_count(&storage).await?, chunks_before);
+
+        storage.delete(location).await?;
+        assert_eq!(storage.stat(location).await?, None);
+        assert_eq!(read(&storage, location).await?, None);
+        assert_eq!(storage.list("").await?.len(), 1);
+        storage.delete(location).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nats_object_storage_publish_failed() -> Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        // チャンクの送信が途中で失敗するよう、バケットの容量を小さくする
+        let config = ObjectStoreConfig {
+            bucket: "kurec_storage_small".to_string(),
+            max_bytes: 8 * 1024,
+            ..Default::default()
+        };
+        let storage = NatsObjectStorage::new("nats1", nats_client, config)
+            .await?
+            .with_chunk_size(1024);
+        let location = "2025/01/01/0000000000000001/output.mp4";
+
+        storage
+            .put(location, "video/mp4", chunks(b"original", 8))
+            .await?;
+        let chunks_before = chunk_count(&storage).await?;
+
+        let data = vec![0u8; 32 * 1024];
+        assert!(storage
+            .put(location, "video/mp4", chunks(&data, 1024))
+            .await
+            .is_err());
+        assert_eq!(read(&storage, location).await?, Some(b"original".to_vec()));
+        assert_eq!(chunk_count(&storage).await?, chunks_before);
+        Ok(())
+    }
+}
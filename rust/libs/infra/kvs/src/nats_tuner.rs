@@ -0,0 +1,228 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::kv::{Config as KvConfig, Store};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, instrument};
+
+use infra_nats::NatsClient;
+
+use domain::models::tuner::{TunerStatus, TunerUsageInterval};
+use domain::ports::repositories::{TunerStatusRepository, TunerUsageHistoryRepository};
+
+use crate::store::{get_or_create_store, list_values, mirakc_host_key};
+
+/// チューナー状態用の KV バケット名
+pub const TUNER_STATUS_BUCKET: &str = "kurec_tuner_status";
+/// チューナー使用履歴用の KV バケット名
+pub const TUNER_HISTORY_BUCKET: &str = "kurec_tuner_history";
+/// チューナー使用履歴の保持期間
+pub const TUNER_HISTORY_MAX_AGE: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+/// NATS KVストアを使用して `TunerStatusRepository` を実装する構造体。
+#[derive(Debug, Clone)]
+pub struct NatsKvTunerStatusRepository {
+    store: Store,
+}
+
+impl NatsKvTunerStatusRepository {
+    /// 新しい `NatsKvTunerStatusRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_tuner_status" KV バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let kv_config = KvConfig {
+            bucket: TUNER_STATUS_BUCKET.to_string(),
+            history: 1,
+            ..Default::default()
+        };
+        let store = get_or_create_store(&nats_client, kv_config).await?;
+        Ok(Self { store })
+    }
+
+    /// KVSで使用するキーを生成する。
+    /// キーは `tuner_{host}_{index}` の形式。
+    fn generate_key(mirakc_url: &str, index: i64) -> String {
+        format!("tuner_{}_{}", mirakc_host_key(mirakc_url), index)
+    }
+}
+
+#[async_trait]
+impl TunerStatusRepository for NatsKvTunerStatusRepository {
+    #[instrument(skip(self, status), fields(key = %Self::generate_key(&status.mirakc_url, status.index)))]
+    async fn save_status(&self, status: &TunerStatus) -> Result<()> {
+        let key = Self::generate_key(&status.mirakc_url, status.index);
+        let json_data =
+            serde_json::to_vec(status).context("Failed to serialize tuner status to JSON")?;
+        let revision = self
+            .store
+            .put(&key, Bytes::from(json_data))
+            .await
+            .with_context(|| format!("NATS KV put operation failed for key '{}'", key))?;
+        debug!(revision, "Successfully saved tuner status to NATS KV");
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(key = %Self::generate_key(mirakc_url, index)))]
+    async fn get_status(&self, mirakc_url: &str, index: i64) -> Result<Option<TunerStatus>> {
+        let key = Self::generate_key(mirakc_url, index);
+        match self
+            .store
+            .get(&key)
+            .await
+            .with_context(|| format!("NATS KV get operation failed for key '{}'", key))?
+        {
+            Some(value) => Ok(Some(
+                serde_json::from_slice(&value)
+                    .context("Failed to deserialize tuner status from JSON")?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn list_statuses(&self) -> Result<Vec<TunerStatus>> {
+        list_values(&self.store).await
+    }
+}
+
+/// NATS KVストアを使用して `TunerUsageHistoryRepository` を実装する構造体。
+///
+/// 使用区間は開始時刻をキーに含めて1件ずつ保存し、
+/// 保持期間 (`TUNER_HISTORY_MAX_AGE`) を過ぎたものは NATS により削除される。
+#[derive(Debug, Clone)]
+pub struct NatsKvTunerHistoryRepository {
+    store: Store,
+}
+
+impl NatsKvTunerHistoryRepository {
+    /// 新しい `NatsKvTunerHistoryRepository` を作成する。
+    ///
+    /// このリポジトリは "kurec_tuner_history" KV バケットを使用します。
+    /// バケットが存在しない場合は作成されます。
+    pub async fn new(nats_client: Arc<NatsClient>) -> Result<Self> {
+        let kv_config = KvConfig {
+            bucket: TUNER_HISTORY_BUCKET.to_string(),
+            history: 1,
+            max_age: TUNER_HISTORY_MAX_AGE,
+            ..Default::default()
+        };
+        let store = get_or_create_store(&nats_client, kv_config).await?;
+        Ok(Self { store })
+    }
+
+    /// KVSで使用するキーを生成する。
+    /// キーは `hist_{host}_{index}_{started_at(ミリ秒)}` の形式。
+    fn generate_key(interval: &TunerUsageInterval) -> String {
+        format!(
+            "hist_{}_{}_{}",
+            mirakc_host_key(&interval.mirakc_url),
+            interval.tuner_index,
+            interval.started_at.timestamp_millis()
+        )
+    }
+}
+
+#[async_trait]
+impl TunerUsageHistoryRepository for NatsKvTunerHistoryRepository {
+    #[instrument(skip(self, interval), fields(key = %Self::generate_key(interval)))]
+    async fn append_interval(&self, interval: &TunerUsageInterval) -> Result<()> {
+        let key = Self::generate_key(interval);
+        let json_data = serde_json::to_vec(interval)
+            .context("Failed to serialize tuner usage interval to JSON")?;
+        self.store
+            .put(&key, Bytes::from(json_data))
+            .await
+            .with_context(|| format!("NATS KV put operation failed for key '{}'", key))?;
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn list_intervals(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<TunerUsageInterval>> {
+        let mut intervals: Vec<TunerUsageInterval> = list_values(&self.store)
+            .await?
+            .into_iter()
+            .filter(|i: &TunerUsageInterval| i.overlaps(from, to))
+            .collect();
+        intervals.sort_by_key(|i| i.started_at);
+        Ok(intervals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_nats_client;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).unwrap()
+    }
+
+    fn status(index: i64, in_use: bool) -> TunerStatus {
+        TunerStatus {
+            mirakc_url: "http://test-mirakc:1234".to_string(),
+            index,
+            name: format!("tuner{}", index),
+            types: vec!["GR".to_string()],
+            in_use,
+            users: vec![],
+            command: None,
+            since: at(10),
+            updated_at: at(10),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_and_list_tuner_statuses() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsKvTunerStatusRepository::new(nats_client).await?;
+
+        repository.save_status(&status(0, false)).await?;
+        repository.save_status(&status(1, true)).await?;
+        repository.save_status(&status(1, false)).await?;
+
+        let saved = repository
+            .get_status("http://test-mirakc:1234", 1)
+            .await?
+            .unwrap();
+        assert!(!saved.in_use);
+        assert_eq!(repository.list_statuses().await?.len(), 2);
+        assert!(repository
+            .get_status("http://test-mirakc:1234", 9)
+            .await?
+            .is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_append_and_list_intervals() -> anyhow::Result<()> {
+        let (_container, nats_client) = setup_nats_client().await?;
+        let repository = NatsKvTunerHistoryRepository::new(nats_client).await?;
+
+        let mut first = TunerUsageInterval::from_status(&status(0, true), Some(at(12)));
+        first.started_at = at(10);
+        let mut second = TunerUsageInterval::from_status(&status(0, true), Some(at(15)));
+        second.started_at = at(13);
+        repository.append_interval(&second).await?;
+        repository.append_interval(&first).await?;
+
+        assert_eq!(
+            repository.list_intervals(at(0), at(23)).await?,
+            vec![first.clone(), second]
+        );
+        assert_eq!(
+            repository.list_intervals(at(11), at(12)).await?,
+            vec![first]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+//! NATS KV ストアの共通ヘルパー
+
+use anyhow::{Context, Result};
+use async_nats::jetstream::context::ObjectStoreErrorKind;
+use async_nats::jetstream::kv::{Config as KvConfig, Store};
+use async_nats::jetstream::object_store::{Config as ObjectStoreConfig, ObjectStore};
+use futures::TryStreamExt;
+use serde::de::DeserializeOwned;
+use tracing::{info, warn};
+
+use infra_nats::NatsClient;
+
+/// KV ストアを取得し、存在しない場合は作成する。
+///
+/// # Arguments
+///
+/// * `nats_client` - 接続済みの `NatsClient`
+/// * `kv_config` - バケットが存在しない場合に使用する設定
+pub async fn get_or_create_store(nats_client: &NatsClient, kv_config: KvConfig) -> Result<Store> {
+    let js_ctx = nats_client.jetstream_context();
+    match js_ctx.get_key_value(&kv_config.bucket).await {
+        Ok(store) => {
+            info!(bucket_name = %kv_config.bucket, "既存の KV ストアを取得しました。");
+            Ok(store)
+        }
+        Err(err)
+            if err.to_string().contains("no key value store named")
+                || err.to_string().contains("stream not found") =>
+        {
+            info!(bucket_name = %kv_config.bucket, "KV ストアが存在しないため、新規作成します。");
+            let bucket = kv_config.bucket.clone();
+            js_ctx
+                .create_key_value(kv_config)
+                .await
+                .with_context(|| format!("KV ストア '{}' の作成に失敗しました", bucket))
+        }
+        Err(e) => Err(anyhow::Error::new(e).context(format!(
+            "KV ストア '{}' の取得中にエラーが発生しました",
+            kv_config.bucket
+        ))),
+    }
+}
+
+/// Object Store を取得し、存在しない場合は作成する。
+///
+/// # Arguments
+///
+/// * `nats_client` - 接続済みの `NatsClient`
+/// * `config` - バケットが存在しない場合に使用する設定
+pub async fn get_or_create_object_store(
+    nats_client: &NatsClient,
+    config: ObjectStoreConfig,
+) -> Result<ObjectStore> {
+    let js_ctx = nats_client.jetstream_context();
+    match js_ctx.get_object_store(&config.bucket).await {
+        Ok(store) => {
+            info!(bucket_name = %config.bucket, "既存の Object Store を取得しました。");
+            Ok(store)
+        }
+        Err(err) if err.kind() == ObjectStoreErrorKind::GetStore => {
+            info!(bucket_name = %config.bucket, "Object Store が存在しないため、新規作成します。");
+            let bucket = config.bucket.clone();
+            js_ctx
+                .create_object_store(config)
+                .await
+                .with_context(|| format!("Object Store '{}' の作成に失敗しました", bucket))
+        }
+        Err(e) => Err(anyhow::Error::new(e).context(format!(
+            "Object Store '{}' の取得中にエラーが発生しました",
+            config.bucket
+        ))),
+    }
+}
+
+/// mirakc の URL を KV のキーとして使用できる形式に変換する。
+///
+/// スキーム (http:// など) を削除し、`:` や `/` をアンダースコアに置換する。
+pub fn mirakc_host_key(mirakc_url: &str) -> String {
+    mirakc_url
+        .replace("http://", "")
+        .replace("https://", "")
+        .replace([':', '/'], "_")
+        .trim_end_matches('_')
+        .to_string()
+}
+
+/// バケット内のすべての値を取得する。デシリアライズできない値は読み飛ばす。
+pub async fn list_values<T: DeserializeOwned>(store: &Store) -> Result<Vec<T>> {
+    list_values_with_prefix(store, "").await
+}
+
+/// キーが `prefix` で始まる値をすべて取得する。デシリアライズできない値は読み飛ばす。
+pub async fn list_values_with_prefix<T: DeserializeOwned>(
+    store: &Store,
+    prefix: &str,
+) -> Result<Vec<T>> {
+    let keys: Vec<String> = store
+        .keys()
+        .await
+        .context("NATS KV keys operation failed")?
+        .try_filter(|key| futures::future::ready(key.starts_with(prefix)))
+        .try_collect()
+        .await
+        .context("Failed to list keys from NATS KV")?;
+
+    let mut values = Vec::with_capacity(keys.len());
+    for key in keys {
+        let Some(value) = store
+            .get(&key)
+            .await
+            .with_context(|| format!("NATS KV get operation failed for key '{}'", key))?
+        else {
+            continue;
+        };
+        match serde_json::from_slice::<T>(&value) {
+            Ok(v) => values.push(v),
+            Err(e) => warn!(key = %key, error = %e, "Failed to deserialize value, skipping"),
+        }
+    }
+    Ok(values)
+}
@@ -0,0 +1,46 @@
+//! テスト用の NATS サーバー起動ヘルパー
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use infra_nats::{connect as nats_connect, NatsClient};
+use testcontainers::{core::WaitFor, runners::AsyncRunner, ContainerAsync, GenericImage, ImageExt};
+
+async fn ensure_docker() {
+    for _ in 0..20 {
+        if std::process::Command::new("docker")
+            .arg("info")
+            .output()
+            .is_ok()
+        {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+    panic!("Docker daemon not ready");
+}
+
+/// JetStream を有効にした NATS コンテナを起動し、接続済みのクライアントを返す。
+///
+/// コンテナはテスト終了まで保持しておく必要がある。
+pub(crate) async fn setup_nats_client(
+) -> anyhow::Result<(ContainerAsync<GenericImage>, Arc<NatsClient>)> {
+    ensure_docker().await;
+    let container = GenericImage::new("nats", "latest")
+        .with_exposed_port(4222u16.into())
+        .with_wait_for(WaitFor::message_on_stderr("Server is ready"))
+        .with_cmd(vec!["--js"])
+        .start()
+        .await?;
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(4222u16).await?;
+    let url = format!("{}:{}", host, port);
+
+    // NATSサーバーが完全に起動するまで少し待機
+    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+
+    let nats_client = nats_connect(&url)
+        .await
+        .context("テスト用 NATS クライアントの接続に失敗")?;
+    Ok((container, nats_client))
+}
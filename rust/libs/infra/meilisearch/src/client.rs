@@ -0,0 +1,148 @@
+//! Meilisearch の REST API クライアント
+//!
+//! 書き込み系の API は非同期タスクとして受け付けられるため、タスクが完了するまで待ってから戻ります。
+
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::{Method, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::debug;
+
+/// タスクの完了を確認する間隔
+const TASK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// タスクの完了を待つ最大時間
+const TASK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 受け付けられたタスク
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskInfo {
+    task_uid: u64,
+}
+
+/// タスクの状態
+#[derive(Debug, Deserialize)]
+struct Task {
+    status: String,
+    #[serde(default)]
+    error: Option<TaskError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TaskError {
+    message: String,
+}
+
+/// Meilisearch の REST API クライアント
+#[derive(Clone)]
+pub(crate) struct MeilisearchClient {
+    http: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+}
+
+impl MeilisearchClient {
+    pub(crate) fn new(url: &str, api_key: Option<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+
+    fn request(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+        let builder = self.http.request(method, format!("{}{}", self.url, path));
+        match &self.api_key {
+            Some(key) => builder.bearer_auth(key),
+            None => builder,
+        }
+    }
+
+    /// リクエストを送り、応答を JSON として返す。404 の場合は `None`。
+    pub(crate) async fn send<B, T>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<Option<T>>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let mut builder = self.request(method.clone(), path);
+        if let Some(body) = body {
+            builder = builder.json(body);
+        }
+        let response = builder
+            .send()
+            .await
+            .with_context(|| format!("Meilisearch への接続に失敗しました: {} {}", method, path))?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            bail!(
+                "Meilisearch がエラーを返しました: {} {}: {} {}",
+                method,
+                path,
+                status,
+                text
+            );
+        }
+        let value = response
+            .json()
+            .await
+            .with_context(|| format!("Meilisearch の応答を解析できません: {} {}", method, path))?;
+        Ok(Some(value))
+    }
+
+    /// 書き込み系のリクエストを送り、タスクの完了を待つ。
+    pub(crate) async fn send_task<B>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<()>
+    where
+        B: Serialize + ?Sized,
+    {
+        let task: TaskInfo = self
+            .send(method.clone(), path, body)
+            .await?
+            .ok_or_else(|| anyhow!("インデックスが存在しません: {} {}", method, path))?;
+        self.wait_task(task.task_uid).await
+    }
+
+    async fn wait_task(&self, task_uid: u64) -> Result<()> {
+        let path = format!("/tasks/{}", task_uid);
+        let started = tokio::time::Instant::now();
+        loop {
+            let task: Task = self
+                .send::<(), _>(Method::GET, &path, None)
+                .await?
+                .ok_or_else(|| anyhow!("タスクが見つかりません: {}", task_uid))?;
+            match task.status.as_str() {
+                "succeeded" => {
+                    debug!(task_uid, "Meilisearch のタスクが完了しました");
+                    return Ok(());
+                }
+                "failed" | "canceled" => bail!(
+                    "Meilisearch のタスクが失敗しました: {} ({})",
+                    task_uid,
+                    task.error.map(|e| e.message).unwrap_or(task.status)
+                ),
+                _ => {}
+            }
+            if started.elapsed() > TASK_TIMEOUT {
+                bail!(
+                    "Meilisearch のタスクが時間内に完了しませんでした: {}",
+                    task_uid
+                );
+            }
+            tokio::time::sleep(TASK_POLL_INTERVAL).await;
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Meilisearch の接続とインデックスの設定
+
+/// Meilisearch の接続設定
+#[derive(Debug, Clone)]
+pub struct MeilisearchConfig {
+    /// Meilisearch の URL
+    pub url: String,
+    /// API キー (マスターキーを設定していない場合は不要)
+    pub api_key: Option<String>,
+    /// インデックス名の接頭辞 (`{prefix}-{index_base_name}`)
+    pub prefix: String,
+    /// EPG インデックスの設定
+    pub epg: MeilisearchIndexConfig,
+    /// 録画ライブラリのインデックスの設定
+    pub records: MeilisearchIndexConfig,
+}
+
+/// インデックスの設定
+#[derive(Debug, Clone)]
+pub struct MeilisearchIndexConfig {
+    pub index_base_name: String,
+    pub primary_key: String,
+    pub filterable_attributes: Vec<String>,
+    pub searchable_attributes: Vec<String>,
+    pub displayed_attributes: Vec<String>,
+    pub sortable_attributes: Vec<String>,
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+impl MeilisearchConfig {
+    /// 既定の設定で URL と API キーを指定して作成する。
+    pub fn new(url: impl Into<String>, api_key: Option<String>) -> Self {
+        Self {
+            url: url.into(),
+            api_key,
+            ..Default::default()
+        }
+    }
+
+    /// EPG インデックスの名前 (Web UI は `kurec-epg` を参照する)
+    pub fn epg_index_name(&self) -> String {
+        format!("{}-{}", self.prefix, self.epg.index_base_name)
+    }
+
+    /// 録画ライブラリのインデックスの名前
+    pub fn records_index_name(&self) -> String {
+        format!("{}-{}", self.prefix, self.records.index_base_name)
+    }
+}
+
+/// 旧実装 (`kurec-interface` の `MeilisearchConfig`) と同じ設定。
+///
+/// 差分の反映でサービスごとにドキュメントを取得するため `service_id` を、
+/// 開始時刻の範囲で絞り込むため `start_timestamp` を絞り込み対象に加え、
+/// `出演者` を表示・絞り込み対象に加えている。
+/// 録画ライブラリのインデックスは EPG インデックスの設定に録画の情報 (`record`) を加えたもの。
+impl Default for MeilisearchConfig {
+    fn default() -> Self {
+        let epg = MeilisearchIndexConfig {
+            index_base_name: "epg".to_string(),
+            primary_key: "program_id".to_string(),
+            filterable_attributes: strings(&[
+                "ジャンル",
+                "放送局",
+                "放送曜日",
+                "出演者",
+                "service_id",
+                "start_timestamp",
+            ]),
+            searchable_attributes: strings(&["タイトル", "番組情報", "その他情報"]),
+            displayed_attributes: strings(&[
+                "program_id",
+                "service_id",
+                "タイトル",
+                "番組情報",
+                "その他情報",
+                "開始時刻",
+                "start_timestamp",
+                "終了時刻",
+                "放送曜日",
+                "放送局",
+                "ジャンル",
+                "出演者",
+                "放送時間",
+                "公式サイト等",
+                "ogp_url",
+                "ogp_url_hash",
+            ]),
+            sortable_attributes: strings(&["開始時刻"]),
+        };
+        let mut records = epg.clone();
+        records.index_base_name = "records".to_string();
+        records.filterable_attributes.extend(strings(&[
+            "record.recorded_timestamp",
+            "record.storages",
+            "record.encode_statuses",
+        ]));
+        records.displayed_attributes.push("record".to_string());
+        records
+            .sortable_attributes
+            .push("record.recorded_timestamp".to_string());
+        Self {
+            url: "http://meilisearch:7700".to_string(),
+            api_key: None,
+            prefix: "kurec".to_string(),
+            epg,
+            records,
+        }
+    }
+}
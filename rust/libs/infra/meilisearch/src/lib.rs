@@ -0,0 +1,10 @@
+//! Meilisearch のインフラクレート
+//!
+//! このクレートは番組検索・録画ライブラリの検索インデックス (`ProgramSearchIndex`) を Meilisearch の REST API で実装します。
+
+mod client;
+mod config;
+mod program_index;
+
+pub use config::{MeilisearchConfig, MeilisearchIndexConfig};
+pub use program_index::MeilisearchProgramIndex;
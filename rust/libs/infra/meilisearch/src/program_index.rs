@@ -0,0 +1,324 @@
+//! Meilisearch による番組検索インデックス
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use domain::models::search::{
+    ProgramDocument, ProgramSearchHit, ProgramSearchQuery, ProgramSearchResult, SearchHighlights,
+    SearchSort,
+};
+use domain::ports::program_search_index::ProgramSearchIndex;
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::info;
+
+use crate::client::MeilisearchClient;
+use crate::config::{MeilisearchConfig, MeilisearchIndexConfig};
+
+/// ドキュメントを取得する際の1回あたりの件数
+const FETCH_LIMIT: usize = 1000;
+
+/// ドキュメントの取得結果
+#[derive(Debug, Deserialize)]
+struct DocumentsPage {
+    results: Vec<ProgramDocument>,
+    total: usize,
+}
+
+/// 検索結果
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResponse {
+    hits: Vec<SearchHit>,
+    estimated_total_hits: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchHit {
+    #[serde(flatten)]
+    document: ProgramDocument,
+    #[serde(rename = "_formatted", default)]
+    formatted: Option<FormattedHit>,
+}
+
+/// ハイライトを付けたフィールド
+#[derive(Debug, Deserialize)]
+struct FormattedHit {
+    #[serde(rename = "タイトル", default)]
+    title: Option<String>,
+    #[serde(rename = "番組情報", default)]
+    description: Option<String>,
+    #[serde(rename = "その他情報", default)]
+    extended: Option<String>,
+}
+
+const HIGHLIGHT_PRE_TAG: &str = "<em>";
+const HIGHLIGHT_POST_TAG: &str = "</em>";
+
+/// フィルター式の文字列リテラル
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// いずれかの値に一致する条件 (値がない場合は `None`)
+fn any_of(attribute: &str, values: &[String]) -> Option<String> {
+    (!values.is_empty()).then(|| {
+        let values: Vec<String> = values.iter().map(|v| quote(v)).collect();
+        format!("{} IN [{}]", attribute, values.join(", "))
+    })
+}
+
+/// 検索条件をフィルター式にする。
+fn search_filter(query: &ProgramSearchQuery) -> Vec<String> {
+    let mut filter: Vec<String> = [
+        any_of("ジャンル", &query.genres),
+        any_of("放送局", &query.channels),
+        any_of("放送曜日", &query.weekdays),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if let Some(from) = query.start_from {
+        filter.push(format!("start_timestamp >= {}", from.timestamp()));
+    }
+    if let Some(to) = query.start_to {
+        filter.push(format!("start_timestamp < {}", to.timestamp()));
+    }
+    filter.extend(
+        [
+            any_of("record.storages", &query.storages),
+            any_of("record.encode_statuses", &query.encode_statuses),
+        ]
+        .into_iter()
+        .flatten(),
+    );
+    if let Some(from) = query.recorded_from {
+        filter.push(format!("record.recorded_timestamp >= {}", from.timestamp()));
+    }
+    if let Some(to) = query.recorded_to {
+        filter.push(format!("record.recorded_timestamp < {}", to.timestamp()));
+    }
+    filter
+}
+
+/// 一致した箇所を含む場合だけハイライトとして扱う。
+fn highlighted(text: Option<String>) -> Option<String> {
+    text.filter(|t| t.contains(HIGHLIGHT_PRE_TAG))
+}
+
+/// Meilisearch の EPG インデックス、または録画ライブラリのインデックス
+pub struct MeilisearchProgramIndex {
+    client: MeilisearchClient,
+    index: MeilisearchIndexConfig,
+    index_name: String,
+}
+
+impl MeilisearchProgramIndex {
+    /// EPG インデックスを使うMeilisearchProgramIndexを作成
+    pub fn new(config: MeilisearchConfig) -> Self {
+        Self {
+            client: MeilisearchClient::new(&config.url, config.api_key.clone()),
+            index_name: config.epg_index_name(),
+            index: config.epg,
+        }
+    }
+
+    /// 録画ライブラリのインデックスを使うMeilisearchProgramIndexを作成
+    pub fn records(config: MeilisearchConfig) -> Self {
+        Self {
+            client: MeilisearchClient::new(&config.url, config.api_key.clone()),
+            index_name: config.records_index_name(),
+            index: config.records,
+        }
+    }
+
+    /// インデックス名
+    pub fn index_name(&self) -> &str {
+        &self.index_name
+    }
+}
+
+#[async_trait]
+impl ProgramSearchIndex for MeilisearchProgramIndex {
+    async fn ensure_index(&self) -> Result<()> {
+        let index = &self.index;
+        let path = format!("/indexes/{}", self.index_name);
+        if self
+            .client
+            .send::<(), serde_json::Value>(Method::GET, &path, None)
+            .await?
+            .is_none()
+        {
+            self.client
+                .send_task(
+                    Method::POST,
+                    "/indexes",
+                    Some(&json!({ "uid": self.index_name, "primaryKey": index.primary_key })),
+                )
+                .await?;
+            info!(index = %self.index_name, "Meilisearch のインデックスを作成しました");
+        }
+        // 設定が変わっている場合に備え、既存のインデックスにも毎回反映する
+        self.client
+            .send_task(
+                Method::PATCH,
+                &format!("{}/settings", path),
+                Some(&json!({
+                    "searchableAttributes": index.searchable_attributes,
+                    "displayedAttributes": index.displayed_attributes,
+                    "filterableAttributes": index.filterable_attributes,
+                    "sortableAttributes": index.sortable_attributes,
+                })),
+            )
+            .await
+    }
+
+    async fn get_service_documents(&self, service_id: i64) -> Result<Vec<ProgramDocument>> {
+        let path = format!("/indexes/{}/documents/fetch", self.index_name);
+        let mut documents = Vec::new();
+        loop {
+            let page: Option<DocumentsPage> = self
+                .client
+                .send(
+                    Method::POST,
+                    &path,
+                    Some(&json!({
+                        "filter": format!("service_id = {}", service_id),
+                        "offset": documents.len(),
+                        "limit": FETCH_LIMIT,
+                    })),
+                )
+                .await?;
+            let Some(page) = page else {
+                return Ok(documents);
+            };
+            let fetched = page.results.len();
+            documents.extend(page.results);
+            if fetched == 0 || documents.len() >= page.total {
+                return Ok(documents);
+            }
+        }
+    }
+
+    async fn upsert_documents(&self, documents: &[ProgramDocument]) -> Result<()> {
+        self.client
+            .send_task(
+                Method::POST,
+                &format!(
+                    "/indexes/{}/documents?primaryKey={}",
+                    self.index_name, self.index.primary_key
+                ),
+                Some(documents),
+            )
+            .await
+    }
+
+    async fn delete_documents(&self, program_ids: &[i64]) -> Result<()> {
+        self.client
+            .send_task(
+                Method::POST,
+                &format!("/indexes/{}/documents/delete-batch", self.index_name),
+                Some(program_ids),
+            )
+            .await
+    }
+
+    async fn delete_all_documents(&self) -> Result<()> {
+        self.client
+            .send_task::<()>(
+                Method::DELETE,
+                &format!("/indexes/{}/documents", self.index_name),
+                None,
+            )
+            .await
+    }
+
+    async fn search(&self, query: &ProgramSearchQuery) -> Result<ProgramSearchResult> {
+        let text = query.text.trim();
+        let sort = match query.sort {
+            SearchSort::RecordedAt => vec!["record.recorded_timestamp:desc"],
+            SearchSort::StartAt => vec!["開始時刻:asc"],
+            SearchSort::Relevance if text.is_empty() => vec!["開始時刻:asc"],
+            SearchSort::Relevance => vec![],
+        };
+        let response: SearchResponse = self
+            .client
+            .send(
+                Method::POST,
+                &format!("/indexes/{}/search", self.index_name),
+                Some(&json!({
+                    "q": text,
+                    "filter": search_filter(query),
+                    "sort": sort,
+                    "offset": query.offset,
+                    "limit": query.limit(),
+                    "attributesToHighlight": ["タイトル", "番組情報", "その他情報"],
+                    "highlightPreTag": HIGHLIGHT_PRE_TAG,
+                    "highlightPostTag": HIGHLIGHT_POST_TAG,
+                })),
+            )
+            .await?
+            .ok_or_else(|| anyhow!("インデックスが存在しません: {}", self.index_name))?;
+        let hits = response
+            .hits
+            .into_iter()
+            .map(|hit| {
+                let highlights = match hit.formatted {
+                    Some(formatted) => SearchHighlights {
+                        title: highlighted(formatted.title),
+                        description: highlighted(formatted.description),
+                        extended: highlighted(formatted.extended),
+                    },
+                    None => SearchHighlights::default(),
+                };
+                ProgramSearchHit {
+                    document: hit.document,
+                    highlights,
+                }
+            })
+            .collect();
+        Ok(ProgramSearchResult {
+            total: response.estimated_total_hits,
+            hits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_search_filter() {
+        let query = ProgramSearchQuery {
+            genres: vec!["アニメ・特撮／国内アニメ".to_string()],
+            channels: vec!["ＮＨＫ総合１・東京".to_string(), "a\"b".to_string()],
+            start_from: Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            search_filter(&query),
+            vec![
+                r#"ジャンル IN ["アニメ・特撮／国内アニメ"]"#,
+                r#"放送局 IN ["ＮＨＫ総合１・東京", "a\"b"]"#,
+                "start_timestamp >= 1735689600",
+            ]
+        );
+
+        let query = ProgramSearchQuery {
+            storages: vec!["mirakc".to_string()],
+            encode_statuses: vec!["encoded".to_string()],
+            recorded_to: Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            search_filter(&query),
+            vec![
+                r#"record.storages IN ["mirakc"]"#,
+                r#"record.encode_statuses IN ["encoded"]"#,
+                "record.recorded_timestamp < 1735689600",
+            ]
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{bail, Result};
+use chrono::{TimeZone, Utc};
+use domain::events::mirakc_events::RecordingStatus;
+use domain::models::search::{ProgramDocument, ProgramSearchQuery, RecordDocument, SearchSort};
+use domain::ports::program_search_index::ProgramSearchIndex;
+use infra_meilisearch::{MeilisearchConfig, MeilisearchProgramIndex};
+use testcontainers::{runners::AsyncRunner, ContainerAsync, GenericImage, ImageExt};
+
+async fn ensure_docker() {
+    for _ in 0..20 {
+        if std::process::Command::new("docker")
+            .arg("info")
+            .output()
+            .is_ok()
+        {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+    panic!("Docker daemon not ready");
+}
+
+async fn setup_meilisearch() -> Result<(ContainerAsync<GenericImage>, String)> {
+    ensure_docker().await;
+    let container = GenericImage::new("getmeili/meilisearch", "v1.12")
+        .with_exposed_port(7700u16.into())
+        .with_env_var("MEILI_ENV", "development")
+        .with_env_var("MEILI_NO_ANALYTICS", "true")
+        .start()
+        .await?;
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(7700u16).await?;
+    let url = format!("http://{}:{}", host, port);
+
+    // 起動ログの形式はバージョンで変わるため、ヘルスチェックで待つ
+    for _ in 0..60 {
+        if is_healthy(&url).await {
+            return Ok((container, url));
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
+    bail!("Meilisearch が起動しませんでした");
+}
+
+async fn is_healthy(url: &str) -> bool {
+    reqwest::get(format!("{}/health", url))
+        .await
+        .is_ok_and(|response| response.status().is_success())
+}
+
+fn document(program_id: i64, service_id: i64, title: &str) -> ProgramDocument {
+    ProgramDocument {
+        program_id,
+        service_id,
+        title: title.to_string(),
+        description: "番組の説明".to_string(),
+        extended: String::new(),
+        channel: "テスト".to_string(),
+        genres: vec!["アニメ・特撮／国内アニメ".to_string()],
+        cast: vec!["古谷徹".to_string()],
+        start_at: Utc.with_ymd_and_hms(2025, 1, 4, 9, 0, 0).unwrap(),
+        start_timestamp: Utc
+            .with_ymd_and_hms(2025, 1, 4, 9, 0, 0)
+            .unwrap()
+            .timestamp(),
+        end_at: Utc.with_ymd_and_hms(2025, 1, 4, 9, 30, 0).unwrap(),
+        day_of_week: "土".to_string(),
+        duration: 30,
+        urls: vec![],
+        ogp_url: None,
+        ogp_url_hash: None,
+        record: None,
+    }
+}
+
+fn recorded(program_id: i64, day: u32, storage: &str) -> ProgramDocument {
+    let recorded_at = Utc.with_ymd_and_hms(2025, 1, day, 9, 0, 0).unwrap();
+    ProgramDocument {
+        record: Some(RecordDocument {
+            record_id: format!("{:016}", program_id),
+            mirakc_url: "http://tuner:40772".to_string(),
+            recorded_at,
+            recorded_timestamp: recorded_at.timestamp(),
+            status: RecordingStatus::Finished,
+            outputs: vec![],
+            storages: vec![storage.to_string()],
+            encode_statuses: vec!["original".to_string()],
+            tags: vec![],
+        }),
+        ..document(program_id, 1024, &format!("録画{}", program_id))
+    }
+}
+
+#[tokio::test]
+async fn test_meilisearch_program_index() -> Result<()> {
+    let (_container, url) = setup_meilisearch().await?;
+    let index = MeilisearchProgramIndex::new(MeilisearchConfig::new(url.clone(), None));
+    assert_eq!(index.index_name(), "kurec-epg");
+
+    // 2回目は既存のインデックスに設定を反映するだけ
+    index.ensure_index().await?;
+    index.ensure_index().await?;
+    let settings: serde_json::Value = reqwest::get(format!("{}/indexes/kurec-epg/settings", url))
+        .await?
+        .json()
+        .await?;
+    assert_eq!(
+        settings["sortableAttributes"],
+        serde_json::json!(["開始時刻"])
+    );
+
+    index
+        .upsert_documents(&[
+            document(1, 1024, "番組1"),
+            document(2, 1024, "番組2"),
+            document(3, 1025, "番組3"),
+        ])
+        .await?;
+    let mut documents = index.get_service_documents(1024).await?;
+    documents.sort_by_key(|d| d.program_id);
+    assert_eq!(
+        documents,
+        vec![document(1, 1024, "番組1"), document(2, 1024, "番組2")]
+    );
+
+    index
+        .upsert_documents(&[document(2, 1024, "番組2 (変更)")])
+        .await?;
+    index.delete_documents(&[1]).await?;
+    assert_eq!(
+        index.get_service_documents(1024).await?,
+        vec![document(2, 1024, "番組2 (変更)")]
+    );
+    assert_eq!(index.get_service_documents(1025).await?.len(), 1);
+
+    let result = index
+        .search(&ProgramSearchQuery {
+            text: "番組2".to_string(),
+            channels: vec!["テスト".to_string()],
+            ..Default::default()
+        })
+        .await?;
+    assert_eq!(result.total, 1);
+    assert_eq!(result.hits[0].document.program_id, 2);
+    assert!(result.hits[0]
+        .highlights
+        .title
+        .as_deref()
+        .is_some_and(|t| t.contains("<em>")));
+
+    index.delete_all_documents().await?;
+    assert!(index.get_service_documents(1025).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_meilisearch_records_index() -> Result<()> {
+    let (_container, url) = setup_meilisearch().await?;
+    let index = MeilisearchProgramIndex::records(MeilisearchConfig::new(url, None));
+    assert_eq!(index.index_name(), "kurec-records");
+    index.ensure_index().await?;
+
+    index
+        .upsert_documents(&[
+            recorded(1, 1, "mirakc"),
+            recorded(2, 2, "local"),
+            recorded(3, 3, "mirakc"),
+        ])
+        .await?;
+    let result = index
+        .search(&ProgramSearchQuery {
+            storages: vec!["mirakc".to_string()],
+            recorded_from: Some(Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap()),
+            sort: SearchSort::RecordedAt,
+            ..Default::default()
+        })
+        .await?;
+    assert_eq!(result.total, 1);
+    assert_eq!(result.hits[0].document, recorded(3, 3, "mirakc"));
+
+    let result = index
+        .search(&ProgramSearchQuery {
+            sort: SearchSort::RecordedAt,
+            ..Default::default()
+        })
+        .await?;
+    let ids: Vec<i64> = result.hits.iter().map(|h| h.document.program_id).collect();
+    assert_eq!(ids, vec![3, 2, 1]);
+    Ok(())
+}
@@ -0,0 +1,313 @@
+//! mirakc API のレスポンスをドメインモデルに変換する関数群
+
+use chrono::{DateTime, TimeZone, Utc};
+use domain::events::mirakc_events::{RecordingFailedReason, RecordingStatus};
+use domain::models::epg::{KurecProgram, KurecSeriesInfo};
+use domain::models::genre::get_subgenre;
+use domain::models::onair::{NowPlaying, OnairProgram};
+use domain::models::record::{Record, RecordContent, RecordingInfo};
+use domain::models::schedule::{MirakcSchedule, MirakcScheduleState};
+use domain::models::service::{Channel, Service};
+use domain::models::title::parse_title;
+use domain::models::tuner::{Tuner, TunerUser};
+use mirakc_client::models::{
+    self, MirakurunChannel, MirakurunProgram, MirakurunService, MirakurunTuner,
+    RecordingScheduleState, WebOnairProgram, WebRecord, WebRecordingSchedule, WebRecordingStatus,
+};
+
+/// Unix 時刻 (ミリ秒) を `DateTime<Utc>` に変換する
+pub fn millis_to_datetime(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+/// mirakc の番組情報とサービス情報から `KurecProgram` を作成する。
+///
+/// `service_id` には mirakc のサービスID (`service.id`) を使用する。
+pub fn to_kurec_program(
+    mirakc_url: &str,
+    program: &MirakurunProgram,
+    service: &MirakurunService,
+) -> KurecProgram {
+    let title = program
+        .name
+        .clone()
+        .flatten()
+        .map(|name| parse_title(&name));
+    KurecProgram {
+        id: program.id,
+        mirakc_url: mirakc_url.to_string(),
+        service_id: service.id,
+        network_id: program.network_id as i64,
+        event_id: program.event_id as i64,
+        channel_name: service.name.clone(),
+        channel_type: service.channel.r#type.to_string(),
+        channel: service.channel.channel.clone(),
+        name: program.name.clone().flatten(),
+        description: program.description.clone().flatten(),
+        extended: program.extended.clone(),
+        start_at: millis_to_datetime(program.start_at),
+        duration_millis: program.duration,
+        is_free: program.is_free,
+        genres: program
+            .genres
+            .clone()
+            .flatten()
+            .unwrap_or_default()
+            .iter()
+            .map(|g| get_subgenre(g.lv1, g.lv2).to_string())
+            .collect(),
+        video_info: program
+            .video
+            .clone()
+            .flatten()
+            .and_then(|v| v.resolution.flatten()),
+        audio_infos: program
+            .audios
+            .clone()
+            .unwrap_or_default()
+            .iter()
+            .map(|a| audio_component_to_string(a.component_type).to_string())
+            .collect(),
+        series_info: program.series.clone().flatten().map(|s| KurecSeriesInfo {
+            id: s.id as i64,
+            repeat: s.repeat as i64,
+            pattern: s.pattern as i64,
+            expire_at: (s.expire_at > 0).then(|| millis_to_datetime(s.expire_at)),
+            episode: s.episode as i64,
+            last_episode: s.last_episode as i64,
+            name: s.name,
+        }),
+        markers: title.as_ref().map(|t| t.markers).unwrap_or_default(),
+        episode_number: title.and_then(|t| t.episode),
+    }
+}
+
+/// mirakc の録画レコードを `Record` に変換する。
+pub fn to_record(mirakc_url: &str, record: WebRecord) -> Record {
+    let program = to_kurec_program(mirakc_url, &record.program, &record.service);
+    let recording = *record.recording;
+    let content = *record.content;
+
+    Record {
+        id: record.id,
+        mirakc_url: mirakc_url.to_string(),
+        program,
+        recording: RecordingInfo {
+            status: to_recording_status(recording.status),
+            start_time: millis_to_datetime(recording.start_time),
+            end_time: recording.end_time.map(millis_to_datetime),
+            duration_millis: recording.duration,
+            failed_reason: recording
+                .failed_reason
+                .flatten()
+                .and_then(|r| to_failed_reason(&r)),
+        },
+        content: RecordContent {
+            path: content.path,
+            content_type: content.r#type,
+            length: content.length.flatten(),
+        },
+        tags: record.tags,
+    }
+}
+
+/// mirakc のチューナー情報を `Tuner` に変換する。
+pub fn to_tuner(tuner: MirakurunTuner) -> Tuner {
+    Tuner {
+        index: tuner.index as i64,
+        name: tuner.name,
+        types: tuner.types.iter().map(|t| t.to_string()).collect(),
+        is_using: tuner.is_using,
+        users: tuner
+            .users
+            .into_iter()
+            .map(|u| TunerUser {
+                id: u.id,
+                agent: u.agent.flatten(),
+                priority: u.priority as i64,
+            })
+            .collect(),
+        command: tuner.command.flatten(),
+    }
+}
+
+/// mirakc の放送中番組情報から `NowPlaying` を作成する。
+///
+/// `updated_at` には取得した時刻 `at` を設定する。
+pub fn to_now_playing(mirakc_url: &str, onair: WebOnairProgram, at: DateTime<Utc>) -> NowPlaying {
+    NowPlaying {
+        mirakc_url: mirakc_url.to_string(),
+        service_id: onair.service_id,
+        current: onair.current.flatten().map(|p| to_onair_program(*p)),
+        next: onair.next.flatten().map(|p| to_onair_program(*p)),
+        updated_at: at,
+    }
+}
+
+/// mirakc のサービス情報を `Service` に変換する。
+pub fn to_service(mirakc_url: &str, service: MirakurunService) -> Service {
+    Service {
+        mirakc_url: mirakc_url.to_string(),
+        id: service.id,
+        service_id: service.service_id as i64,
+        network_id: service.network_id as i64,
+        name: service.name,
+        service_type: service.r#type as i64,
+        logo_id: service.logo_id.map(i64::from),
+        remote_control_key_id: service.remote_control_key_id.map(i64::from),
+        has_logo_data: service.has_logo_data,
+        channel_type: service.channel.r#type.to_string(),
+        channel: service.channel.channel,
+    }
+}
+
+/// mirakc のチャンネル情報を `Channel` に変換する。
+pub fn to_channel(mirakc_url: &str, channel: MirakurunChannel) -> Channel {
+    Channel {
+        mirakc_url: mirakc_url.to_string(),
+        channel_type: channel.r#type.to_string(),
+        channel: channel.channel,
+        name: channel.name,
+        service_ids: channel.services.into_iter().map(|s| s.id).collect(),
+    }
+}
+
+/// mirakc の録画予約を `MirakcSchedule` に変換する。
+pub fn to_mirakc_schedule(mirakc_url: &str, schedule: WebRecordingSchedule) -> MirakcSchedule {
+    MirakcSchedule {
+        mirakc_url: mirakc_url.to_string(),
+        program_id: schedule.program.id,
+        name: schedule.program.name.flatten(),
+        start_at: millis_to_datetime(schedule.program.start_at),
+        state: match schedule.state {
+            RecordingScheduleState::Scheduled => MirakcScheduleState::Scheduled,
+            RecordingScheduleState::Tracking => MirakcScheduleState::Tracking,
+            RecordingScheduleState::Recording => MirakcScheduleState::Recording,
+            RecordingScheduleState::Rescheduling => MirakcScheduleState::Rescheduling,
+            RecordingScheduleState::Finished => MirakcScheduleState::Finished,
+            RecordingScheduleState::Failed => MirakcScheduleState::Failed,
+        },
+        tags: schedule.tags,
+        priority: schedule.options.priority,
+    }
+}
+
+fn to_onair_program(program: MirakurunProgram) -> OnairProgram {
+    OnairProgram {
+        program_id: program.id,
+        event_id: program.event_id as i64,
+        name: program.name.flatten(),
+        start_at: millis_to_datetime(program.start_at),
+        duration_millis: program.duration,
+    }
+}
+
+fn to_recording_status(status: WebRecordingStatus) -> RecordingStatus {
+    match status {
+        WebRecordingStatus::Recording => RecordingStatus::Recording,
+        WebRecordingStatus::Finished => RecordingStatus::Finished,
+        WebRecordingStatus::Canceled => RecordingStatus::Canceled,
+        WebRecordingStatus::Failed => RecordingStatus::Failed,
+    }
+}
+
+fn to_failed_reason(reason: &models::RecordingFailedReason) -> Option<RecordingFailedReason> {
+    // どちらも "type" タグ付きの同じ JSON 表現を持つため、JSON を経由して変換する
+    serde_json::to_value(reason)
+        .and_then(serde_json::from_value)
+        .ok()
+}
+
+/// ARIB STD-B10 の音声コンポーネント種別を文字列に変換する
+fn audio_component_to_string(component_type: i32) -> &'static str {
+    match component_type {
+        0x01 => "モノラル",
+        0x02 => "デュアルモノ",
+        0x03 => "ステレオ",
+        0x04 => "2/1モード",
+        0x05 => "3/0モード",
+        0x06 => "2/2モード",
+        0x07 => "3/1モード",
+        0x08 => "3/2モード",
+        0x09 => "3/2.1モード",
+        _ => "不明",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_web_record() -> WebRecord {
+        serde_json::from_value(json!({
+            "id": "0000000000000001",
+            "program": {
+                "id": 327360817301010i64,
+                "eventId": 1010,
+                "serviceId": 1024,
+                "networkId": 32736,
+                "startAt": 1700000000000i64,
+                "duration": 1800000,
+                "isFree": true,
+                "name": "ニュース【字】",
+                "description": "最新ニュース",
+                "genres": [{"lv1": 0, "lv2": 0, "un1": 15, "un2": 15}],
+                "video": {"type": "mpeg2", "resolution": "1080i", "streamContent": 1, "componentType": 179},
+                "audios": [{"componentType": 3, "isMain": true, "samplingRate": 48000, "langs": ["jpn"]}],
+                "series": {"id": 1, "repeat": 0, "pattern": 1, "expireAt": 1710000000000i64, "episode": 3, "lastEpisode": 12, "name": "シリーズ"}
+            },
+            "service": {
+                "id": 3273601024i64,
+                "serviceId": 1024,
+                "networkId": 32736,
+                "type": 1,
+                "logoId": 0,
+                "remoteControlKeyId": 1,
+                "name": "ＮＨＫ総合１・東京",
+                "channel": {"type": "GR", "channel": "27"},
+                "hasLogoData": false
+            },
+            "options": {"contentPath": "0000000000000001.m2ts"},
+            "tags": ["kurec"],
+            "recording": {
+                "options": {"contentPath": "0000000000000001.m2ts", "priority": 0},
+                "status": "failed",
+                "startTime": 1700000000000i64,
+                "endTime": 1700001800000i64,
+                "duration": 1800000,
+                "failedReason": {"type": "pipeline-error", "exitCode": 1}
+            },
+            "content": {"path": "0000000000000001.m2ts", "type": "video/MP2T", "length": 1024}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_to_record() {
+        let record = to_record("http://tuner:40772", sample_web_record());
+
+        assert_eq!(record.id, "0000000000000001");
+        assert_eq!(record.mirakc_url, "http://tuner:40772");
+        assert_eq!(record.program.service_id, 3273601024);
+        assert_eq!(record.program.name.as_deref(), Some("ニュース【字】"));
+        assert!(record.program.markers.subtitled);
+        assert_eq!(record.program.channel_type, "GR");
+        assert_eq!(record.program.genres, vec!["ニュース・報道／定時・総合"]);
+        assert_eq!(record.program.video_info.as_deref(), Some("1080i"));
+        assert_eq!(record.program.audio_infos, vec!["ステレオ"]);
+        assert_eq!(
+            record.program.series_info.as_ref().unwrap().last_episode,
+            12
+        );
+        assert_eq!(record.recording.status, RecordingStatus::Failed);
+        assert_eq!(
+            record.recording.failed_reason,
+            Some(RecordingFailedReason::PipelineError { exit_code: 1 })
+        );
+        assert_eq!(record.content.length, Some(1024));
+        assert_eq!(record.tags, vec!["kurec"]);
+    }
+}
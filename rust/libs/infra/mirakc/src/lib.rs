@@ -2,6 +2,7 @@
 //!
 //! このクレートはmirakcのAPIにアクセスするためのインフラ層の実装を提供します。
 
+pub mod converters;
 pub mod mirakc_api_impl;
 pub mod mirakc_client;
 pub mod mirakc_sse_source; // 追加
@@ -2,10 +2,30 @@
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use domain::ports::mirakc_api::MirakcApi;
+use domain::models::onair::NowPlaying;
+use domain::models::record::Record;
+use domain::models::schedule::{DesiredSchedule, MirakcSchedule};
+use domain::models::service::{Channel, Service};
+use domain::models::tuner::Tuner;
+use domain::ports::mirakc_api::{
+    MirakcApi, MirakcOnairApi, MirakcRecordsApi, MirakcSchedulesApi, MirakcServicesApi,
+    MirakcTunersApi, RecordStream,
+};
+use futures::StreamExt;
 use mirakc_client::apis::configuration::Configuration; // mirakc_client:: を使用
-use mirakc_client::apis::services_api; // mirakc_client:: を使用
+use mirakc_client::apis::{
+    channels_api, onair_api, recording_records_api, recording_schedules_api, services_api,
+    tuners_api, Error as ApiError,
+};
+use mirakc_client::models::{RecordingOptions, WebRecordingScheduleInput};
+use reqwest::header::{CONTENT_RANGE, RANGE};
 use reqwest::Client;
+use reqwest::StatusCode;
+
+use crate::converters::{
+    to_channel, to_mirakc_schedule, to_now_playing, to_record, to_service, to_tuner,
+};
+use chrono::Utc;
 use serde_json::Value;
 
 /// reqwest を使用した MirakcApi の実装
@@ -22,6 +42,23 @@ impl MirakcApiClientImpl {
             client: Client::new(), // reqwest::Client を初期化
         }
     }
+
+    /// 接続先ごとの mirakc_client の Configuration を作成する。
+    ///
+    /// mirakc の API は `{mirakc_url}/api` 以下で提供される。
+    fn configuration(&self, mirakc_url: &str) -> Configuration {
+        Configuration {
+            base_path: format!("{}/api", mirakc_url.trim_end_matches('/')),
+            user_agent: Some("kurec/0.1.0".to_string()),
+            client: self.client.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// mirakc API のエラーが 404 Not Found かどうかを判定する。
+fn is_not_found<T>(err: &ApiError<T>) -> bool {
+    matches!(err, ApiError::ResponseError(res) if res.status == StatusCode::NOT_FOUND)
 }
 
 impl Default for MirakcApiClientImpl {
@@ -33,14 +70,8 @@ impl Default for MirakcApiClientImpl {
 #[async_trait]
 impl MirakcApi for MirakcApiClientImpl {
     async fn get_service(&self, mirakc_url: &str, service_id: i64) -> Result<Value> {
-        // u64 -> i64 に戻す
         // mirakc_client の Configuration を動的に作成
-        let config = Configuration {
-            base_path: mirakc_url.to_string(),
-            user_agent: Some("kurec/0.1.0".to_string()),
-            client: self.client.clone(),
-            ..Default::default()
-        };
+        let config = self.configuration(mirakc_url);
 
         let service = services_api::get_service(&config, service_id) // キャスト不要
             .await
@@ -59,12 +90,7 @@ impl MirakcApi for MirakcApiClientImpl {
         service_id: i64, // u64 -> i64 に戻す
     ) -> Result<Vec<Value>> {
         // mirakc_client の Configuration を動的に作成
-        let config = Configuration {
-            base_path: mirakc_url.to_string(),
-            user_agent: Some("kurec/0.1.0".to_string()),
-            client: self.client.clone(),
-            ..Default::default()
-        };
+        let config = self.configuration(mirakc_url);
 
         // services_api を使うように修正
         let programs = services_api::get_programs_of_service(&config, service_id) // キャスト不要
@@ -81,3 +107,258 @@ impl MirakcApi for MirakcApiClientImpl {
             .collect()
     }
 }
+
+#[async_trait]
+impl MirakcRecordsApi for MirakcApiClientImpl {
+    async fn get_records(&self, mirakc_url: &str) -> Result<Vec<Record>> {
+        let config = self.configuration(mirakc_url);
+        let records = recording_records_api::get_records(&config)
+            .await
+            .context(format!("Failed to get records from {}", mirakc_url))?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| to_record(mirakc_url, record))
+            .collect())
+    }
+
+    async fn get_record(&self, mirakc_url: &str, record_id: &str) -> Result<Option<Record>> {
+        let config = self.configuration(mirakc_url);
+        match recording_records_api::get_record(&config, record_id).await {
+            Ok(record) => Ok(Some(to_record(mirakc_url, record))),
+            Err(e) if is_not_found(&e) => Ok(None),
+            Err(e) => Err(anyhow::Error::new(e).context(format!(
+                "Failed to get record {} from {}",
+                record_id, mirakc_url
+            ))),
+        }
+    }
+
+    async fn get_record_stream(
+        &self,
+        mirakc_url: &str,
+        record_id: &str,
+        offset: u64,
+    ) -> Result<RecordStream> {
+        // 生成されたクライアントはレスポンスをすべて読み込むため、reqwest で直接ストリームを取得する
+        let url = format!(
+            "{}/recording/records/{}/stream",
+            self.configuration(mirakc_url).base_path,
+            record_id
+        );
+        let mut request = self.client.get(&url);
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+        }
+        let response = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| {
+                format!(
+                    "Failed to get record stream {} from {}",
+                    record_id, mirakc_url
+                )
+            })?;
+        if offset > 0 {
+            if response.status() != StatusCode::PARTIAL_CONTENT {
+                anyhow::bail!(
+                    "mirakc {} が録画ファイル {} の Range リクエストに対応していません",
+                    mirakc_url,
+                    record_id
+                );
+            }
+            let content_range = response
+                .headers()
+                .get(CONTENT_RANGE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+            if content_range_start(content_range) != Some(offset) {
+                anyhow::bail!(
+                    "mirakc {} の録画ファイル {} が {} から始まっていません (Content-Range: {:?})",
+                    mirakc_url,
+                    record_id,
+                    offset,
+                    content_range
+                );
+            }
+        }
+        Ok(response
+            .bytes_stream()
+            .map(|chunk| chunk.context("Failed to read record stream"))
+            .boxed())
+    }
+
+    async fn remove_record(&self, mirakc_url: &str, record_id: &str) -> Result<()> {
+        let config = self.configuration(mirakc_url);
+        match recording_records_api::remove_record(&config, record_id, Some(true)).await {
+            Ok(()) => Ok(()),
+            Err(e) if is_not_found(&e) => Ok(()),
+            Err(e) => Err(anyhow::Error::new(e).context(format!(
+                "Failed to remove record {} from {}",
+                record_id, mirakc_url
+            ))),
+        }
+    }
+}
+
+/// `Content-Range` (`bytes 2-5/6`) の開始位置を返す。
+fn content_range_start(content_range: &str) -> Option<u64> {
+    let (start, _) = content_range.strip_prefix("bytes ")?.split_once('-')?;
+    start.trim().parse().ok()
+}
+
+#[async_trait]
+impl MirakcSchedulesApi for MirakcApiClientImpl {
+    async fn get_schedules(&self, mirakc_url: &str) -> Result<Vec<MirakcSchedule>> {
+        let config = self.configuration(mirakc_url);
+        let schedules = recording_schedules_api::get_recording_schedules(&config)
+            .await
+            .context(format!(
+                "Failed to get recording schedules from {}",
+                mirakc_url
+            ))?;
+        Ok(schedules
+            .into_iter()
+            .map(|schedule| to_mirakc_schedule(mirakc_url, schedule))
+            .collect())
+    }
+
+    async fn create_schedule(
+        &self,
+        mirakc_url: &str,
+        schedule: &DesiredSchedule,
+    ) -> Result<MirakcSchedule> {
+        let config = self.configuration(mirakc_url);
+        let input = WebRecordingScheduleInput {
+            options: Box::new(RecordingOptions {
+                priority: Some(schedule.priority()),
+                ..RecordingOptions::new()
+            }),
+            program_id: schedule.program_id,
+            tags: Some(schedule.tags()),
+        };
+        let created = recording_schedules_api::create_recording_schedule(&config, input)
+            .await
+            .context(format!(
+                "Failed to create recording schedule for program {} on {}",
+                schedule.program_id, mirakc_url
+            ))?;
+        Ok(to_mirakc_schedule(mirakc_url, created))
+    }
+
+    async fn delete_schedule(&self, mirakc_url: &str, program_id: i64) -> Result<()> {
+        let config = self.configuration(mirakc_url);
+        match recording_schedules_api::delete_recording_schedule(&config, program_id).await {
+            Ok(()) => Ok(()),
+            Err(e) if is_not_found(&e) => Ok(()),
+            Err(e) => Err(anyhow::Error::new(e).context(format!(
+                "Failed to delete recording schedule for program {} on {}",
+                program_id, mirakc_url
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl MirakcTunersApi for MirakcApiClientImpl {
+    async fn get_tuners(&self, mirakc_url: &str) -> Result<Vec<Tuner>> {
+        let config = self.configuration(mirakc_url);
+        let tuners = tuners_api::get_tuners(&config)
+            .await
+            .context(format!("Failed to get tuners from {}", mirakc_url))?;
+        Ok(tuners.into_iter().map(to_tuner).collect())
+    }
+
+    async fn get_tuner(&self, mirakc_url: &str, index: i64) -> Result<Tuner> {
+        let config = self.configuration(mirakc_url);
+        let tuner = tuners_api::get_tuner(&config, index as i32)
+            .await
+            .context(format!("Failed to get tuner {} from {}", index, mirakc_url))?;
+        Ok(to_tuner(tuner))
+    }
+}
+
+#[async_trait]
+impl MirakcOnairApi for MirakcApiClientImpl {
+    async fn get_onair_programs(&self, mirakc_url: &str) -> Result<Vec<NowPlaying>> {
+        let config = self.configuration(mirakc_url);
+        let programs = onair_api::get_onair_programs(&config)
+            .await
+            .context(format!("Failed to get on-air programs from {}", mirakc_url))?;
+        let now = Utc::now();
+        Ok(programs
+            .into_iter()
+            .map(|onair| to_now_playing(mirakc_url, onair, now))
+            .collect())
+    }
+
+    async fn get_onair_program(
+        &self,
+        mirakc_url: &str,
+        service_id: i64,
+    ) -> Result<Option<NowPlaying>> {
+        let config = self.configuration(mirakc_url);
+        // mirakc はサービスIDを指定した場合も配列で返す
+        match onair_api::get_onair_program(&config, service_id).await {
+            Ok(programs) => Ok(programs
+                .into_iter()
+                .next()
+                .map(|onair| to_now_playing(mirakc_url, onair, Utc::now()))),
+            Err(e) if is_not_found(&e) => Ok(None),
+            Err(e) => Err(anyhow::Error::new(e).context(format!(
+                "Failed to get on-air program of service {} from {}",
+                service_id, mirakc_url
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl MirakcServicesApi for MirakcApiClientImpl {
+    async fn get_services(&self, mirakc_url: &str) -> Result<Vec<Service>> {
+        let config = self.configuration(mirakc_url);
+        let services = services_api::get_services(&config)
+            .await
+            .context(format!("Failed to get services from {}", mirakc_url))?;
+        Ok(services
+            .into_iter()
+            .map(|service| to_service(mirakc_url, service))
+            .collect())
+    }
+
+    async fn get_channels(&self, mirakc_url: &str) -> Result<Vec<Channel>> {
+        let config = self.configuration(mirakc_url);
+        let channels = channels_api::get_channels(&config)
+            .await
+            .context(format!("Failed to get channels from {}", mirakc_url))?;
+        Ok(channels
+            .into_iter()
+            .map(|channel| to_channel(mirakc_url, channel))
+            .collect())
+    }
+
+    async fn get_logo_image(&self, mirakc_url: &str, id: i64) -> Result<Option<Vec<u8>>> {
+        // 生成された services_api::get_logo_image はレスポンスボディを捨てるため、直接リクエストする
+        let config = self.configuration(mirakc_url);
+        let url = format!("{}/services/{}/logo", config.base_path, id);
+        let mut request = self.client.get(&url);
+        if let Some(user_agent) = &config.user_agent {
+            request = request.header(reqwest::header::USER_AGENT, user_agent);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to get logo of service {} from {}", id, mirakc_url))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let image = response
+            .error_for_status()
+            .with_context(|| format!("Failed to get logo of service {} from {}", id, mirakc_url))?
+            .bytes()
+            .await
+            .context("Failed to read logo image")?;
+        Ok(Some(image.to_vec()))
+    }
+}
@@ -164,7 +164,7 @@ impl EventSource<MirakcEventInput> for MirakcSseSource {
         let event_stream = self.event_stream().await?;
 
         // Result<MirakcEventInput, anyhow::Error> を返すストリームに変換
-        let result_stream = event_stream.map(|event| Ok(event)).boxed();
+        let result_stream = event_stream.map(Ok).boxed();
 
         Ok(result_stream)
     }
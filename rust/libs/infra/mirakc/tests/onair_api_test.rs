@@ -0,0 +1,87 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use domain::ports::mirakc_api::MirakcOnairApi;
+use infra_mirakc::MirakcApiClientImpl;
+
+fn program(event_id: i32, start_at: i64, name: &str) -> Value {
+    json!({
+        "id": 327360102400000i64 + event_id as i64,
+        "eventId": event_id,
+        "serviceId": 1024,
+        "networkId": 32736,
+        "startAt": start_at,
+        "duration": 1800000,
+        "isFree": true,
+        "name": name
+    })
+}
+
+fn onair(service_id: i64, with_next: bool) -> Value {
+    json!({
+        "serviceId": service_id,
+        "current": program(100, 1735722000000, "ニュース7"),
+        "next": if with_next { program(101, 1735723800000, "クローズアップ現代") } else { Value::Null }
+    })
+}
+
+#[tokio::test]
+async fn test_get_onair_programs() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/onair"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!([onair(3273601024, true), onair(3273701032, false)])),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let api = MirakcApiClientImpl::new();
+    let programs = api.get_onair_programs(&mock_server.uri()).await?;
+
+    assert_eq!(programs.len(), 2);
+    assert_eq!(programs[0].mirakc_url, mock_server.uri());
+    assert_eq!(programs[0].service_id, 3273601024);
+    let current = programs[0].current.as_ref().unwrap();
+    assert_eq!(current.event_id, 100);
+    assert_eq!(current.name.as_deref(), Some("ニュース7"));
+    assert_eq!(current.end_at().timestamp_millis(), 1735723800000);
+    assert_eq!(programs[0].next.as_ref().unwrap().event_id, 101);
+    assert!(programs[1].next.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_onair_program() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/onair/3273601024"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([onair(3273601024, true)])))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/onair/1"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&mock_server)
+        .await;
+
+    let api = MirakcApiClientImpl::new();
+    let now_playing = api
+        .get_onair_program(&mock_server.uri(), 3273601024)
+        .await?
+        .unwrap();
+    assert_eq!(now_playing.current.unwrap().program_id, 327360102400100);
+
+    assert!(api
+        .get_onair_program(&mock_server.uri(), 1)
+        .await?
+        .is_none());
+
+    Ok(())
+}
@@ -1,5 +1,4 @@
 use anyhow::Result;
-use reqwest;
 use serde_json::{json, Value};
 use std::fs;
 use std::path::Path;
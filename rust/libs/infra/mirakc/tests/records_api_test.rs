@@ -0,0 +1,209 @@
+use anyhow::Result;
+use futures::TryStreamExt;
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{header, method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use domain::events::mirakc_events::RecordingStatus;
+use domain::ports::mirakc_api::MirakcRecordsApi;
+use infra_mirakc::MirakcApiClientImpl;
+
+fn web_record(id: &str) -> Value {
+    json!({
+        "id": id,
+        "program": {
+            "id": 327360817301010i64,
+            "eventId": 1010,
+            "serviceId": 1024,
+            "networkId": 32736,
+            "startAt": 1700000000000i64,
+            "duration": 1800000,
+            "isFree": true,
+            "name": "ニュース"
+        },
+        "service": {
+            "id": 3273601024i64,
+            "serviceId": 1024,
+            "networkId": 32736,
+            "type": 1,
+            "name": "ＮＨＫ総合１・東京",
+            "channel": {"type": "GR", "channel": "27"},
+            "hasLogoData": false
+        },
+        "tags": [],
+        "recording": {
+            "options": {"contentPath": format!("{}.m2ts", id)},
+            "status": "finished",
+            "startTime": 1700000000000i64,
+            "endTime": 1700001800000i64,
+            "duration": 1800000
+        },
+        "content": {"path": format!("{}.m2ts", id), "type": "video/MP2T", "length": 1024}
+    })
+}
+
+#[tokio::test]
+async fn test_get_records() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/recording/records"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!([web_record("1"), web_record("2")])),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let api = MirakcApiClientImpl::new();
+    let records = api.get_records(&mock_server.uri()).await?;
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].id, "1");
+    assert_eq!(records[0].mirakc_url, mock_server.uri());
+    assert_eq!(records[0].recording.status, RecordingStatus::Finished);
+    assert_eq!(records[1].content.path, "2.m2ts");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_record_not_found() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/recording/records/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(web_record("1")))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/recording/records/404"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&mock_server)
+        .await;
+
+    let api = MirakcApiClientImpl::new();
+    let record = api.get_record(&mock_server.uri(), "1").await?;
+    assert_eq!(record.map(|r| r.id), Some("1".to_string()));
+
+    let missing = api.get_record(&mock_server.uri(), "404").await?;
+    assert!(missing.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_record_server_error() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/recording/records/1"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&mock_server)
+        .await;
+
+    let api = MirakcApiClientImpl::new();
+    assert!(api.get_record(&mock_server.uri(), "1").await.is_err());
+}
+
+#[tokio::test]
+async fn test_get_record_stream() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/recording/records/1/stream"))
+        .and(header("range", "bytes=2-"))
+        .respond_with(
+            ResponseTemplate::new(206)
+                .insert_header("content-range", "bytes 2-5/6")
+                .set_body_bytes(b"CDEF".to_vec()),
+        )
+        .mount(&mock_server)
+        .await;
+    // 要求した位置と違う位置から返すサーバー
+    Mock::given(method("GET"))
+        .and(path("/api/recording/records/1/stream"))
+        .and(header("range", "bytes=4-"))
+        .respond_with(
+            ResponseTemplate::new(206)
+                .insert_header("content-range", "bytes 0-5/6")
+                .set_body_bytes(b"ABCDEF".to_vec()),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/recording/records/1/stream"))
+        .and(header("range", "bytes=5-"))
+        .respond_with(ResponseTemplate::new(206).set_body_bytes(b"F".to_vec()))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/recording/records/1/stream"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"ABCDEF".to_vec()))
+        .mount(&mock_server)
+        .await;
+
+    let api = MirakcApiClientImpl::new();
+    let read = |offset| {
+        let api = api.clone();
+        let uri = mock_server.uri();
+        async move {
+            let chunks: Vec<_> = api
+                .get_record_stream(&uri, "1", offset)
+                .await?
+                .try_collect()
+                .await?;
+            anyhow::Ok(chunks.concat())
+        }
+    };
+    assert_eq!(read(0).await?, b"ABCDEF");
+    assert_eq!(read(2).await?, b"CDEF");
+
+    // Range に対応していない場合は最初から返ってくるためエラーにする
+    assert!(api
+        .get_record_stream(&mock_server.uri(), "1", 3)
+        .await
+        .is_err());
+    // Content-Range が要求した位置から始まっていない場合もエラーにする
+    assert!(api
+        .get_record_stream(&mock_server.uri(), "1", 4)
+        .await
+        .is_err());
+    assert!(api
+        .get_record_stream(&mock_server.uri(), "1", 5)
+        .await
+        .is_err());
+    assert!(api
+        .get_record_stream(&mock_server.uri(), "404", 0)
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_remove_record() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path("/api/recording/records/1"))
+        .and(query_param("purge", "true"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/api/recording/records/404"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/api/recording/records/2"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&mock_server)
+        .await;
+
+    let api = MirakcApiClientImpl::new();
+    api.remove_record(&mock_server.uri(), "1").await?;
+    // 削除済みの場合は何もしない
+    api.remove_record(&mock_server.uri(), "404").await?;
+    assert!(api.remove_record(&mock_server.uri(), "2").await.is_err());
+
+    Ok(())
+}
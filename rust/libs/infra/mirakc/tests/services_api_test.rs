@@ -0,0 +1,117 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use domain::ports::mirakc_api::MirakcServicesApi;
+use infra_mirakc::MirakcApiClientImpl;
+
+fn service(service_id: i64, name: &str, has_logo_data: bool) -> Value {
+    json!({
+        "id": 3273600000i64 + service_id,
+        "serviceId": service_id,
+        "networkId": 32736,
+        "type": 1,
+        "logoId": if has_logo_data { json!(0) } else { json!(-1) },
+        "remoteControlKeyId": 1,
+        "name": name,
+        "channel": {"type": "GR", "channel": "27"},
+        "hasLogoData": has_logo_data
+    })
+}
+
+#[tokio::test]
+async fn test_get_services() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/services"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            service(1024, "ＮＨＫ総合１・東京", true),
+            service(1025, "ＮＨＫ総合２・東京", false)
+        ])))
+        .mount(&mock_server)
+        .await;
+
+    let api = MirakcApiClientImpl::new();
+    let services = api.get_services(&mock_server.uri()).await?;
+
+    assert_eq!(services.len(), 2);
+    assert_eq!(services[0].id, 3273601024);
+    assert_eq!(services[0].service_id, 1024);
+    assert_eq!(services[0].name, "ＮＨＫ総合１・東京");
+    assert_eq!(services[0].channel_type, "GR");
+    assert_eq!(services[0].channel, "27");
+    assert_eq!(services[0].mirakc_url, mock_server.uri());
+    assert!(services[0].has_logo_data);
+    assert!(!services[1].has_logo_data);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_channels() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/channels"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+            "type": "GR",
+            "channel": "27",
+            "name": "NHK総合",
+            "services": [
+                {"id": 3273601024i64, "serviceId": 1024, "networkId": 32736, "name": "ＮＨＫ総合１・東京"},
+                {"id": 3273601025i64, "serviceId": 1025, "networkId": 32736, "name": "ＮＨＫ総合２・東京"}
+            ]
+        }])))
+        .mount(&mock_server)
+        .await;
+
+    let api = MirakcApiClientImpl::new();
+    let channels = api.get_channels(&mock_server.uri()).await?;
+
+    assert_eq!(channels.len(), 1);
+    assert_eq!(channels[0].channel_type, "GR");
+    assert_eq!(channels[0].name, "NHK総合");
+    assert_eq!(channels[0].service_ids, vec![3273601024, 3273601025]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_logo_image() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    let png = vec![0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a];
+    Mock::given(method("GET"))
+        .and(path("/api/services/3273601024/logo"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "image/png")
+                .set_body_bytes(png.clone()),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/services/3273601025/logo"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/services/3273601026/logo"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&mock_server)
+        .await;
+
+    let api = MirakcApiClientImpl::new();
+    assert_eq!(
+        api.get_logo_image(&mock_server.uri(), 3273601024).await?,
+        Some(png)
+    );
+    assert_eq!(
+        api.get_logo_image(&mock_server.uri(), 3273601025).await?,
+        None
+    );
+    assert!(api
+        .get_logo_image(&mock_server.uri(), 3273601026)
+        .await
+        .is_err());
+    Ok(())
+}
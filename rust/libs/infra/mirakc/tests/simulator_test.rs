@@ -0,0 +1,189 @@
+//! mirakc シミュレーターを使用したオフラインの結合テスト
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+
+use domain::events::mirakc_events::{
+    EpgProgramsUpdatedEvent, OnairProgramChangedEvent, TunerStatusChangedEvent,
+};
+use domain::handlers::mirakc_event_handler::{MirakcEventHandler, MirakcEventSinks};
+use domain::models::schedule::{DesiredSchedule, MirakcScheduleState};
+use domain::ports::event_sink::EventSink;
+use domain::ports::event_source::EventSource;
+use domain::ports::mirakc_api::{
+    MirakcOnairApi, MirakcRecordsApi, MirakcSchedulesApi, MirakcServicesApi, MirakcTunersApi,
+};
+use domain::ports::repositories::version_repository::VersionRepository;
+use infra_mirakc::{DomainVersionRepositoryImpl, MirakcApiClientImpl, MirakcSseSource};
+use testing_mirakc::{MirakcSimulator, LOGO_PNG};
+
+struct RecordingSink<E> {
+    events: Mutex<Vec<E>>,
+}
+
+impl<E> RecordingSink<E> {
+    fn new() -> Arc<Self> {
+        Arc::new(Self {
+            events: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.events.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl<E: Serialize + DeserializeOwned + Send + Sync + 'static> EventSink<E> for RecordingSink<E> {
+    async fn publish(&self, event: E) -> Result<()> {
+        self.events.lock().unwrap().push(event);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_api_client_against_simulator() -> Result<()> {
+    let simulator = MirakcSimulator::from_fixture("basic").await?;
+    let api = MirakcApiClientImpl::new();
+
+    let records = api.get_records(&simulator.url()).await?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].program.channel_type, "GR");
+    assert_eq!(records[0].content.length, Some(1048576));
+
+    let tuners = api.get_tuners(&simulator.url()).await?;
+    assert!(tuners[0].is_using);
+    assert_eq!(tuners[1].types, vec!["BS", "CS"]);
+
+    let onair = api
+        .get_onair_program(&simulator.url(), 3273601024)
+        .await?
+        .unwrap();
+    assert_eq!(onair.next.unwrap().event_id, 102);
+
+    let version = DomainVersionRepositoryImpl::new(&simulator.url())
+        .get_version()
+        .await?;
+    assert_eq!(version.current, "4.0.0");
+
+    let services = api.get_services(&simulator.url()).await?;
+    assert_eq!(services.len(), 2);
+    let channels = api.get_channels(&simulator.url()).await?;
+    assert_eq!(channels[0].service_ids, vec![3273601024]);
+    assert_eq!(channels[1].channel_type, "BS");
+    assert_eq!(
+        api.get_logo_image(&simulator.url(), 3273601024).await?,
+        Some(LOGO_PNG.to_vec())
+    );
+    assert_eq!(api.get_logo_image(&simulator.url(), 400101).await?, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_schedules_api_against_simulator() -> Result<()> {
+    let simulator = MirakcSimulator::from_fixture("basic").await?;
+    let api = MirakcApiClientImpl::new();
+
+    let schedules = api.get_schedules(&simulator.url()).await?;
+    assert_eq!(schedules.len(), 1);
+    assert_eq!(schedules[0].program_id, 40010100201);
+    assert_eq!(schedules[0].state, MirakcScheduleState::Scheduled);
+    assert_eq!(schedules[0].priority, Some(1));
+    assert!(schedules[0].is_owned());
+
+    let desired = DesiredSchedule {
+        mirakc_url: simulator.url(),
+        program_id: 327360102400102,
+        service_id: 3273601024,
+        channel_type: "GR".to_string(),
+        name: None,
+        start_at: chrono::Utc::now(),
+        duration_millis: 1800000,
+        rule_matches: vec![],
+        manual: true,
+        series: None,
+    };
+    let created = api.create_schedule(&simulator.url(), &desired).await?;
+    assert_eq!(created.name.as_deref(), Some("クローズアップ現代【字】"));
+    assert!(created.matches(&desired));
+    assert_eq!(api.get_schedules(&simulator.url()).await?.len(), 2);
+    // 同じ番組の録画予約は作成できない
+    assert!(api
+        .create_schedule(&simulator.url(), &desired)
+        .await
+        .is_err());
+
+    api.delete_schedule(&simulator.url(), 327360102400102)
+        .await?;
+    // 存在しない録画予約の削除はエラーにしない
+    api.delete_schedule(&simulator.url(), 327360102400102)
+        .await?;
+    assert_eq!(api.get_schedules(&simulator.url()).await?.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sse_source_reconnects_after_drop() -> Result<()> {
+    let simulator = MirakcSimulator::from_fixture("basic").await?;
+    let source = MirakcSseSource::new(simulator.url());
+
+    // 1回目の接続は台本の2件を配信して切断される
+    let mut stream = source.subscribe().await?;
+    let first = stream.next().await.unwrap()?;
+    assert_eq!(first.event_type, "epg.programs-updated");
+    assert_eq!(first.mirakc_url, simulator.url());
+    let second = stream.next().await.unwrap()?;
+    assert_eq!(second.event_type, "tuner.status-changed");
+    assert!(stream.next().await.is_none());
+
+    // 2回目は 503 で拒否され、バックオフ後の3回目で接続できる
+    let mut stream = source.subscribe().await?;
+    let third = stream.next().await.unwrap()?;
+    assert_eq!(third.event_type, "onair.program-changed");
+    assert_eq!(simulator.sse_connections(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sse_events_are_dispatched_to_sinks() -> Result<()> {
+    let simulator = MirakcSimulator::from_fixture("basic").await?;
+    let epg_sink = RecordingSink::<EpgProgramsUpdatedEvent>::new();
+    let tuner_sink = RecordingSink::<TunerStatusChangedEvent>::new();
+    let onair_sink = RecordingSink::<OnairProgramChangedEvent>::new();
+    let handler = MirakcEventHandler::new(MirakcEventSinks {
+        epg_programs_updated: Some(epg_sink.clone()),
+        tuner_status_changed: Some(tuner_sink.clone()),
+        onair_program_changed: Some(onair_sink.clone()),
+        ..Default::default()
+    });
+
+    let source = MirakcSseSource::new(simulator.url());
+    let mut stream = source.subscribe().await?;
+    while let Some(event) = stream.next().await {
+        handler.handle(event?).await?;
+    }
+
+    let mut stream = source.subscribe().await?;
+    handler.handle(stream.next().await.unwrap()?).await?;
+    simulator.emit("tuner.status-changed", json!({"tunerIndex": 1}));
+    let event = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await?
+        .unwrap()?;
+    handler.handle(event).await?;
+
+    assert_eq!(epg_sink.len(), 1);
+    assert_eq!(tuner_sink.len(), 2);
+    assert_eq!(onair_sink.len(), 1);
+    assert_eq!(tuner_sink.events.lock().unwrap()[1].tuner_index, 1);
+
+    Ok(())
+}
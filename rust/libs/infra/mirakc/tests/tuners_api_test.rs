@@ -0,0 +1,72 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use domain::ports::mirakc_api::MirakcTunersApi;
+use infra_mirakc::MirakcApiClientImpl;
+
+fn tuner(index: i32, using: bool) -> Value {
+    json!({
+        "index": index,
+        "name": format!("PX4-T{}", index),
+        "types": ["GR"],
+        "command": if using { json!("recpt1 --device /dev/px4video2 27 - -") } else { Value::Null },
+        "pid": if using { json!(1234) } else { Value::Null },
+        "users": if using {
+            json!([{"id": "recorder", "agent": "mirakc", "priority": 1}])
+        } else {
+            json!([])
+        },
+        "isAvailable": true,
+        "isRemote": false,
+        "isFree": !using,
+        "isUsing": using,
+        "isFault": false
+    })
+}
+
+#[tokio::test]
+async fn test_get_tuners() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/tuners"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!([tuner(0, false), tuner(1, true)])),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let api = MirakcApiClientImpl::new();
+    let tuners = api.get_tuners(&mock_server.uri()).await?;
+
+    assert_eq!(tuners.len(), 2);
+    assert!(!tuners[0].is_using);
+    assert_eq!(tuners[0].command, None);
+    assert!(tuners[1].is_using);
+    assert_eq!(tuners[1].types, vec!["GR"]);
+    assert_eq!(tuners[1].users[0].agent.as_deref(), Some("mirakc"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_tuner() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/tuners/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(tuner(1, true)))
+        .mount(&mock_server)
+        .await;
+
+    let api = MirakcApiClientImpl::new();
+    let tuner = api.get_tuner(&mock_server.uri(), 1).await?;
+
+    assert_eq!(tuner.index, 1);
+    assert_eq!(tuner.name, "PX4-T1");
+    assert_eq!(tuner.users[0].id, "recorder");
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! OGP 画像取得の設定
+
+use std::time::Duration;
+
+/// OGP 画像取得の設定
+#[derive(Debug, Clone)]
+pub struct OgpConfig {
+    /// 縮小後の幅 (これより小さい画像は拡大しない)
+    pub width: u32,
+    /// WebP の品質 (0〜100)
+    pub quality: f32,
+    /// 1回のリクエストのタイムアウト
+    pub timeout: Duration,
+    /// ページ (HTML) の最大サイズ (バイト)
+    pub max_page_bytes: usize,
+    /// 画像の最大サイズ (バイト)
+    pub max_image_bytes: usize,
+    /// リクエストの User-Agent
+    pub user_agent: String,
+}
+
+/// 幅と品質は旧実装 (`OgpAdapter`) と同じ。
+impl Default for OgpConfig {
+    fn default() -> Self {
+        Self {
+            width: 300,
+            quality: 75.0,
+            timeout: Duration::from_secs(10),
+            max_page_bytes: 2 * 1024 * 1024,
+            max_image_bytes: 10 * 1024 * 1024,
+            user_agent: concat!("KuRec/", env!("CARGO_PKG_VERSION")).to_string(),
+        }
+    }
+}
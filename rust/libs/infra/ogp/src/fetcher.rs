@@ -0,0 +1,187 @@
+//! HTTP による OGP 画像の取得
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use domain::models::ogp::OgpImage;
+use domain::ports::ogp_fetcher::OgpFetcher;
+use image::imageops::FilterType;
+use image::DynamicImage;
+use scraper::{Html, Selector};
+use tracing::debug;
+use url::Url;
+
+use crate::config::OgpConfig;
+
+/// og:image を探す `<meta>` の属性 (先に見つかったものを使う)
+const OG_IMAGE_PROPERTIES: &[&str] = &["og:image", "og:image:url", "og:image:secure_url"];
+
+/// ページから og:image の URL を取り出す。相対 URL はページの URL で解決する。
+fn og_image_url(html: &str, page_url: &Url) -> Option<Url> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("meta[property], meta[name]").expect("valid selector");
+    let metas: Vec<_> = document.select(&selector).collect();
+    OG_IMAGE_PROPERTIES.iter().find_map(|property| {
+        metas
+            .iter()
+            .filter(|meta| {
+                let element = meta.value();
+                element.attr("property").or(element.attr("name")) == Some(property)
+            })
+            .filter_map(|meta| meta.value().attr("content"))
+            .map(str::trim)
+            .find(|content| !content.is_empty())
+            .and_then(|content| page_url.join(content).ok())
+    })
+}
+
+/// 画像を幅 `width` に縮小して WebP にする。
+fn to_webp(bytes: &[u8], width: u32, quality: f32) -> Result<Vec<u8>> {
+    let image = image::load_from_memory(bytes).context("画像を読み込めません")?;
+    let image = if image.width() > width {
+        let height =
+            (u64::from(image.height()) * u64::from(width) / u64::from(image.width())).max(1) as u32;
+        image.resize_exact(width, height, FilterType::Lanczos3)
+    } else {
+        image
+    };
+    // libwebp が扱える RGB / RGBA にそろえる
+    let image = match image {
+        DynamicImage::ImageRgb8(_) | DynamicImage::ImageRgba8(_) => image,
+        image if image.color().has_alpha() => DynamicImage::ImageRgba8(image.to_rgba8()),
+        image => DynamicImage::ImageRgb8(image.to_rgb8()),
+    };
+    let encoder =
+        webp::Encoder::from_image(&image).map_err(|e| anyhow!("WebP に変換できません: {}", e))?;
+    Ok(encoder.encode(quality).to_vec())
+}
+
+/// HTTP で og:image を取得する `OgpFetcher`
+#[derive(Clone)]
+pub struct HttpOgpFetcher {
+    client: reqwest::Client,
+    config: OgpConfig,
+}
+
+impl HttpOgpFetcher {
+    /// 新しいHttpOgpFetcherを作成
+    pub fn new(config: OgpConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .user_agent(config.user_agent.clone())
+            .build()
+            .context("HTTP クライアントを作成できません")?;
+        Ok(Self { client, config })
+    }
+
+    /// `max_bytes` までの本文を取得する。超える場合はエラー。
+    async fn get_limited(&self, url: &Url, max_bytes: usize) -> Result<Vec<u8>> {
+        let mut response = self
+            .client
+            .get(url.clone())
+            .send()
+            .await
+            .with_context(|| format!("取得できません: {}", url))?
+            .error_for_status()
+            .with_context(|| format!("取得できません: {}", url))?;
+        if response
+            .content_length()
+            .is_some_and(|length| length > max_bytes as u64)
+        {
+            bail!(
+                "サイズが上限 ({} バイト) を超えています: {}",
+                max_bytes,
+                url
+            );
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .with_context(|| format!("取得できません: {}", url))?
+        {
+            body.extend_from_slice(&chunk);
+            if body.len() > max_bytes {
+                bail!(
+                    "サイズが上限 ({} バイト) を超えています: {}",
+                    max_bytes,
+                    url
+                );
+            }
+        }
+        Ok(body)
+    }
+}
+
+#[async_trait]
+impl OgpFetcher for HttpOgpFetcher {
+    async fn fetch(&self, url: &str) -> Result<Option<OgpImage>> {
+        let page_url =
+            Url::parse(url).with_context(|| format!("URL が正しくありません: {}", url))?;
+        let html = self
+            .get_limited(&page_url, self.config.max_page_bytes)
+            .await?;
+        let Some(image_url) = og_image_url(&String::from_utf8_lossy(&html), &page_url) else {
+            debug!(url, "og:image がありません");
+            return Ok(None);
+        };
+
+        let bytes = self
+            .get_limited(&image_url, self.config.max_image_bytes)
+            .await?;
+        let (width, quality) = (self.config.width, self.config.quality);
+        let webp = tokio::task::spawn_blocking(move || to_webp(&bytes, width, quality))
+            .await?
+            .with_context(|| format!("OGP 画像を変換できません: {}", image_url))?;
+        Ok(Some(OgpImage {
+            image_url: image_url.to_string(),
+            webp,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_og_image_url() {
+        let page = Url::parse("https://example.com/program/1/").unwrap();
+        let html = r#"<html><head>
+            <meta property="og:title" content="番組">
+            <meta property="og:image:secure_url" content="https://cdn.example.com/secure.png">
+            <meta property="og:image" content="  ">
+            <meta property="og:image" content="../og.png">
+        </head></html>"#;
+        assert_eq!(
+            og_image_url(html, &page).unwrap().as_str(),
+            "https://example.com/program/og.png"
+        );
+
+        // og:image がない場合は og:image:secure_url を使う
+        let html =
+            r#"<meta name="og:image:secure_url" content="https://cdn.example.com/secure.png">"#;
+        assert_eq!(
+            og_image_url(html, &page).unwrap().as_str(),
+            "https://cdn.example.com/secure.png"
+        );
+
+        assert!(og_image_url("<title>番組</title>", &page).is_none());
+    }
+
+    #[test]
+    fn test_to_webp() {
+        let mut png = Vec::new();
+        DynamicImage::new_luma8(600, 200)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let webp = to_webp(&png, 300, 75.0).unwrap();
+        let image = image::load_from_memory(&webp).unwrap();
+        assert_eq!((image.width(), image.height()), (300, 100));
+
+        // 幅が小さい画像は拡大しない
+        let webp = to_webp(&png, 1000, 75.0).unwrap();
+        assert_eq!(image::load_from_memory(&webp).unwrap().width(), 600);
+
+        assert!(to_webp(b"not an image", 300, 75.0).is_err());
+    }
+}
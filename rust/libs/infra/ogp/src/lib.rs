@@ -0,0 +1,10 @@
+//! OGP 画像取得のインフラクレート
+//!
+//! このクレートは OGP 画像の取得 (`OgpFetcher`) を HTTP で実装します。
+//! ページの og:image を取得し、指定した幅に縮小して WebP に変換します。
+
+mod config;
+mod fetcher;
+
+pub use config::OgpConfig;
+pub use fetcher::HttpOgpFetcher;
@@ -0,0 +1,89 @@
+//! ローカルの HTTP サーバーを相手にした `HttpOgpFetcher` のテスト
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use axum::http::header;
+use axum::response::{Html, IntoResponse};
+use axum::routing::get;
+use axum::Router;
+use domain::ports::ogp_fetcher::OgpFetcher;
+use image::{DynamicImage, ImageFormat};
+use infra_ogp::{HttpOgpFetcher, OgpConfig};
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut png = Vec::new();
+    DynamicImage::new_rgb8(width, height)
+        .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+        .unwrap();
+    png
+}
+
+fn page(image: &str) -> Html<String> {
+    Html(format!(
+        r#"<!DOCTYPE html><html><head><meta property="og:image" content="{}"></head></html>"#,
+        image
+    ))
+}
+
+/// OGP を返すページと画像を提供するサーバーを起動する。
+async fn start_server() -> SocketAddr {
+    let app = Router::new()
+        .route("/program", get(|| async { page("/images/og.png") }))
+        .route(
+            "/images/og.png",
+            get(|| async { ([(header::CONTENT_TYPE, "image/png")], png(600, 400)) }),
+        )
+        .route(
+            "/plain",
+            get(|| async { Html("<html><head><title>番組</title></head></html>") }),
+        )
+        .route("/large", get(|| async { page("/images/large.bin") }))
+        .route(
+            "/images/large.bin",
+            get(|| async { vec![0u8; 64 * 1024].into_response() }),
+        )
+        .route("/broken", get(|| async { page("/images/broken.png") }))
+        .route("/images/broken.png", get(|| async { "not an image" }))
+        .route("/missing", get(|| async { page("/images/missing.png") }))
+        .route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                page("/images/og.png")
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    addr
+}
+
+#[tokio::test]
+async fn test_fetch_ogp_image() {
+    let addr = start_server().await;
+    let fetcher = HttpOgpFetcher::new(OgpConfig {
+        timeout: Duration::from_millis(500),
+        max_image_bytes: 32 * 1024,
+        ..Default::default()
+    })
+    .unwrap();
+    let url = |path: &str| format!("http://{}{}", addr, path);
+
+    let image = fetcher.fetch(&url("/program")).await.unwrap().unwrap();
+    assert_eq!(image.image_url, url("/images/og.png"));
+    let webp = image::load_from_memory_with_format(&image.webp, ImageFormat::WebP).unwrap();
+    assert_eq!((webp.width(), webp.height()), (300, 200));
+
+    // og:image がない
+    assert!(fetcher.fetch(&url("/plain")).await.unwrap().is_none());
+
+    // サイズの上限・画像でない・画像がない・タイムアウト
+    for path in ["/large", "/broken", "/missing", "/slow"] {
+        assert!(
+            fetcher.fetch(&url(path)).await.is_err(),
+            "{} should fail",
+            path
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! ストレージの設定
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use domain::ports::storage::{Storage, StorageRegistry};
+use infra_kvs::{NatsObjectStorage, StorageBucket};
+use infra_nats::NatsClient;
+use serde::Deserialize;
+use shared_types::object_store::ObjectStoreBucket;
+
+use crate::local::LocalStorage;
+use crate::s3::S3Storage;
+
+/// S3 のマルチパートアップロードで許される最小のパートサイズ
+const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+
+/// ストレージの設定ファイル (JSON)
+///
+/// `kurec.yml` の `storage` と同じ形式で、名前を付けたストレージを並べる。
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct StoragesConfig {
+    #[serde(rename = "storage")]
+    pub storages: Vec<StorageConfig>,
+}
+
+impl StoragesConfig {
+    /// ストレージの設定ファイル (JSON) を読み込む。
+    pub fn parse(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("ストレージの設定ファイルを解析できません")
+    }
+}
+
+/// ストレージの設定
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum StorageConfig {
+    Local(LocalStorageConfig),
+    S3(S3StorageConfig),
+    Nats(NatsStorageConfig),
+}
+
+impl StorageConfig {
+    /// ストレージ名
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Local(config) => &config.name,
+            Self::S3(config) => &config.name,
+            Self::Nats(config) => &config.name,
+        }
+    }
+}
+
+/// ローカルストレージの設定
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LocalStorageConfig {
+    /// ストレージ名
+    pub name: String,
+    /// ストレージのルートディレクトリ
+    pub directory: PathBuf,
+}
+
+/// S3 互換ストレージの設定
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct S3StorageConfig {
+    /// ストレージ名
+    pub name: String,
+    /// エンドポイント (例: `http://minio:9000`)。バケットはパス形式で指定する。
+    pub endpoint: String,
+    /// バケット名
+    pub bucket: String,
+    /// リージョン
+    #[serde(default = "default_region")]
+    pub region: String,
+    /// アクセスキー (省略した場合は環境変数 `AWS_ACCESS_KEY_ID`)
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+    /// シークレットキー (省略した場合は環境変数 `AWS_SECRET_ACCESS_KEY`)
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+    /// バケット内でストレージのルートにするキーの接頭辞
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// これより大きい内容はマルチパートアップロードで、このサイズごとに送る (バイト)
+    #[serde(default = "default_part_size")]
+    pub part_size: u64,
+}
+
+/// NATS Object Store ストレージの設定
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct NatsStorageConfig {
+    /// ストレージ名
+    pub name: String,
+    /// バケット名 (省略した場合は `kurec_storage`)。存在しない場合は作成する。
+    #[serde(default)]
+    pub bucket: Option<String>,
+    /// 送信するチャンクのサイズ (バイト)
+    #[serde(default)]
+    pub chunk_size: Option<usize>,
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_part_size() -> u64 {
+    16 * 1024 * 1024
+}
+
+/// 設定からストレージを作成する。
+///
+/// NATS Object Store のストレージには接続済みの `nats_client` が必要。
+pub async fn open_storages(
+    config: &StoragesConfig,
+    nats_client: Option<Arc<NatsClient>>,
+) -> Result<StorageRegistry> {
+    let mut storages: Vec<Arc<dyn Storage>> = Vec::with_capacity(config.storages.len());
+    for storage in &config.storages {
+        storages.push(match storage {
+            StorageConfig::Local(config) => Arc::new(LocalStorage::new(
+                config.name.clone(),
+                config.directory.clone(),
+            )),
+            StorageConfig::S3(config) => Arc::new(open_s3(config)?),
+            StorageConfig::Nats(config) => {
+                let Some(nats_client) = &nats_client else {
+                    bail!("ストレージ {} には NATS への接続が必要です", config.name);
+                };
+                Arc::new(open_nats(config, nats_client.clone()).await?)
+            }
+        });
+    }
+    StorageRegistry::new(storages)
+}
+
+async fn open_nats(
+    config: &NatsStorageConfig,
+    nats_client: Arc<NatsClient>,
+) -> Result<NatsObjectStorage> {
+    let mut bucket = StorageBucket::config();
+    if let Some(name) = &config.bucket {
+        bucket.bucket = name.clone();
+    }
+    let storage = NatsObjectStorage::new(config.name.clone(), nats_client, bucket).await?;
+    Ok(match config.chunk_size {
+        Some(chunk_size) => storage.with_chunk_size(chunk_size),
+        None => storage,
+    })
+}
+
+fn open_s3(config: &S3StorageConfig) -> Result<S3Storage> {
+    if config.part_size < MIN_PART_SIZE {
+        bail!(
+            "ストレージ {} の part_size は {} バイト以上にしてください",
+            config.name,
+            MIN_PART_SIZE
+        );
+    }
+    let access_key_id = credential(&config.access_key_id, "AWS_ACCESS_KEY_ID", &config.name)?;
+    let secret_access_key = credential(
+        &config.secret_access_key,
+        "AWS_SECRET_ACCESS_KEY",
+        &config.name,
+    )?;
+    S3Storage::new(S3StorageConfig {
+        access_key_id: Some(access_key_id),
+        secret_access_key: Some(secret_access_key),
+        ..config.clone()
+    })
+}
+
+fn credential(value: &Option<String>, env: &str, name: &str) -> Result<String> {
+    match value {
+        Some(value) => Ok(value.clone()),
+        None => std::env::var(env).with_context(|| {
+            format!(
+                "ストレージ {} の認証情報がありません (設定または環境変数 {})",
+                name, env
+            )
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_parse() {
+        let config = StoragesConfig::parse(
+            r#"{
+                "storage": [
+                    {"name": "local1", "type": "local", "directory": "/videos"},
+                    {
+                        "name": "archive",
+                        "type": "s3",
+                        "endpoint": "http://minio:9000",
+                        "bucket": "kurec",
+                        "access_key_id": "minio",
+                        "secret_access_key": "minio123"
+                    },
+                    {"name": "nats1", "type": "nats"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.storages[0],
+            StorageConfig::Local(LocalStorageConfig {
+                name: "local1".to_string(),
+                directory: PathBuf::from("/videos"),
+            })
+        );
+        let StorageConfig::S3(s3) = &config.storages[1] else {
+            panic!("S3 ではありません");
+        };
+        assert_eq!(s3.region, "us-east-1");
+        assert_eq!(s3.part_size, 16 * 1024 * 1024);
+        assert_eq!(s3.prefix, None);
+        assert_eq!(
+            config.storages[2],
+            StorageConfig::Nats(NatsStorageConfig {
+                name: "nats1".to_string(),
+                bucket: None,
+                chunk_size: None,
+            })
+        );
+
+        let local = StoragesConfig {
+            storages: config.storages[..2].to_vec(),
+        };
+        let registry = open_storages(&local, None).await.unwrap();
+        assert_eq!(registry.names(), vec!["archive", "local1"]);
+        // NATS に接続していない場合は NATS Object Store を使えない
+        assert!(open_storages(&config, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_open_storages_invalid() {
+        let duplicated = StoragesConfig::parse(
+            r#"{"storage": [
+                {"name": "local1", "type": "local", "directory": "/videos"},
+                {"name": "local1", "type": "local", "directory": "/backup"}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(open_storages(&duplicated, None).await.is_err());
+
+        let small_part = StoragesConfig::parse(
+            r#"{"storage": [{
+                "name": "archive", "type": "s3", "endpoint": "http://minio:9000", "bucket": "kurec",
+                "access_key_id": "minio", "secret_access_key": "minio123", "part_size": 1024
+            }]}"#,
+        )
+        .unwrap();
+        assert!(open_storages(&small_part, None).await.is_err());
+
+        assert!(StoragesConfig::parse(r#"{"storage": [{"name": "x", "type": "nfs"}]}"#).is_err());
+    }
+}
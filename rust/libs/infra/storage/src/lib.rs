@@ -0,0 +1,18 @@
+//! ストレージのインフラクレート
+//!
+//! このクレートはストレージのポート (`Storage`) を実装します。
+//! ローカルのディレクトリ (`LocalStorage`)、S3 互換のオブジェクトストレージ (`S3Storage`)、
+//! NATS Object Store (`infra_kvs::NatsObjectStorage`) を設定ファイル (`StoragesConfig`) で
+//! 名前を付けて定義し、`open_storages` でまとめて作成します。
+
+mod config;
+mod local;
+mod s3;
+mod sigv4;
+
+pub use config::{
+    open_storages, LocalStorageConfig, NatsStorageConfig, S3StorageConfig, StorageConfig,
+    StoragesConfig,
+};
+pub use local::LocalStorage;
+pub use s3::S3Storage;
@@ -0,0 +1,357 @@
+//! ローカルディレクトリのストレージ
+
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::ports::storage::{validate_location, ByteStream, Storage, StoredObject};
+use futures::StreamExt;
+use tokio::fs::{self, File};
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio_util::io::ReaderStream;
+use tracing::warn;
+
+/// 書き込み中のファイルに付ける接尾辞
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// ローカルディレクトリのストレージ
+///
+/// 位置はルートディレクトリからの相対パスで、書き込み中の内容は同じディレクトリの
+/// `.{ファイル名}.partial` に書き、書き終えてから名前を変える。
+/// `.` で始まるファイルは一覧に含めない。
+pub struct LocalStorage {
+    name: String,
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    /// 新しい `LocalStorage` を作成する。ルートディレクトリは最初の書き込みで作成する。
+    pub fn new(name: impl Into<String>, root: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            root: root.into(),
+        }
+    }
+
+    fn path(&self, location: &str) -> Result<PathBuf> {
+        validate_location(location)?;
+        Ok(self.root.join(location))
+    }
+
+    /// ファイルの情報を取得する。存在しない場合やファイルでない場合は `None`。
+    async fn object(&self, location: &str, path: &Path) -> Result<Option<StoredObject>> {
+        let metadata = match fs::metadata(path).await {
+            Ok(metadata) if metadata.is_file() => metadata,
+            Ok(_) => return Ok(None),
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to stat {}", path.display())),
+        };
+        Ok(Some(StoredObject {
+            location: location.to_string(),
+            size: metadata.len(),
+            content_type: None,
+            modified_at: metadata.modified().ok().map(DateTime::<Utc>::from),
+        }))
+    }
+
+    /// 空になった親ディレクトリをルートまで削除する。
+    async fn remove_empty_dirs(&self, path: &Path) {
+        let mut dir = path.parent();
+        while let Some(current) = dir {
+            if current == self.root || !current.starts_with(&self.root) {
+                break;
+            }
+            // 空でない場合は失敗するので、そこで止める
+            if fs::remove_dir(current).await.is_err() {
+                break;
+            }
+            dir = current.parent();
+        }
+    }
+}
+
+/// 内容をファイルに書き出し、書き出したサイズを返す。
+async fn write_file(path: &Path, mut data: ByteStream) -> Result<u64> {
+    let file = File::create(path)
+        .await
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    let mut size = 0;
+    while let Some(chunk) = data.next().await {
+        let chunk = chunk?;
+        writer
+            .write_all(&chunk)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        size += chunk.len() as u64;
+    }
+    writer.flush().await?;
+    writer.get_ref().sync_all().await?;
+    Ok(size)
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn put(
+        &self,
+        location: &str,
+        content_type: &str,
+        data: ByteStream,
+    ) -> Result<StoredObject> {
+        let path = self.path(location)?;
+        let dir = path.parent().unwrap_or(&self.root);
+        fs::create_dir_all(dir)
+            .await
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let file_name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let partial = dir.join(format!(".{}{}", file_name, PARTIAL_SUFFIX));
+
+        let size = match write_file(&partial, data).await {
+            Ok(size) => size,
+            Err(e) => {
+                if let Err(e) = fs::remove_file(&partial).await {
+                    warn!(file = %partial.display(), "書き込み中のファイルを削除できません: {}", e);
+                }
+                return Err(e);
+            }
+        };
+        fs::rename(&partial, &path)
+            .await
+            .with_context(|| format!("Failed to rename to {}", path.display()))?;
+
+        let object = self.object(location, &path).await?;
+        Ok(StoredObject {
+            size,
+            content_type: Some(content_type.to_string()),
+            ..object.with_context(|| format!("{} がありません", path.display()))?
+        })
+    }
+
+    async fn get(&self, location: &str) -> Result<Option<ByteStream>> {
+        let path = self.path(location)?;
+        if self.object(location, &path).await?.is_none() {
+            return Ok(None);
+        }
+        let file = File::open(&path)
+            .await
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        Ok(Some(
+            ReaderStream::new(file)
+                .map(|chunk| chunk.map_err(anyhow::Error::from))
+                .boxed(),
+        ))
+    }
+
+    async fn delete(&self, location: &str) -> Result<()> {
+        let path = self.path(location)?;
+        match fs::remove_file(&path).await {
+            Ok(()) => {
+                self.remove_empty_dirs(&path).await;
+                Ok(())
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e).with_context(|| format!("Failed to remove {}", path.display())),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>> {
+        // 接頭辞のディレクトリ部分から探す
+        let start = match prefix.rsplit_once('/') {
+            Some((dir, _)) => {
+                validate_location(dir)?;
+                dir.to_string()
+            }
+            None => String::new(),
+        };
+        let mut dirs = vec![start];
+        let mut objects = Vec::new();
+        while let Some(dir) = dirs.pop() {
+            let path = self.root.join(&dir);
+            let mut entries = match fs::read_dir(&path).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to read {}", path.display()))
+                }
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if name.starts_with('.') {
+                    continue;
+                }
+                let location = if dir.is_empty() {
+                    name
+                } else {
+                    format!("{}/{}", dir, name)
+                };
+                let file_type = entry.file_type().await?;
+                if file_type.is_dir() {
+                    dirs.push(location);
+                } else if file_type.is_file() && location.starts_with(prefix) {
+                    if let Some(object) = self.object(&location, &entry.path()).await? {
+                        objects.push(object);
+                    }
+                }
+            }
+        }
+        objects.sort_by(|a, b| a.location.cmp(&b.location));
+        Ok(objects)
+    }
+
+    async fn stat(&self, location: &str) -> Result<Option<StoredObject>> {
+        let path = self.path(location)?;
+        self.object(location, &path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use futures::stream;
+
+    fn data(chunks: &[&'static [u8]]) -> ByteStream {
+        stream::iter(
+            chunks
+                .iter()
+                .map(|chunk| Ok(Bytes::from_static(chunk)))
+                .collect::<Vec<_>>(),
+        )
+        .boxed()
+    }
+
+    async fn read(storage: &LocalStorage, location: &str) -> Option<Vec<u8>> {
+        let stream = storage.get(location).await.unwrap()?;
+        let chunks: Vec<Bytes> = stream.map(|chunk| chunk.unwrap()).collect().await;
+        Some(chunks.concat())
+    }
+
+    #[tokio::test]
+    async fn test_put_get_delete() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let storage = LocalStorage::new("local1", dir.path().join("videos"));
+
+        let object = storage
+            .put(
+                "2025/01/01/1/output.mp4",
+                "video/mp4",
+                data(&[b"MP4", b"DATA"]),
+            )
+            .await?;
+        assert_eq!(object.location, "2025/01/01/1/output.mp4");
+        assert_eq!(object.size, 7);
+        assert_eq!(object.content_type.as_deref(), Some("video/mp4"));
+        assert!(object.modified_at.is_some());
+        assert_eq!(
+            read(&storage, "2025/01/01/1/output.mp4").await.as_deref(),
+            Some(&b"MP4DATA"[..])
+        );
+        assert!(dir.path().join("videos/2025/01/01/1/output.mp4").exists());
+
+        // 上書き
+        storage
+            .put("2025/01/01/1/output.mp4", "video/mp4", data(&[b"NEW"]))
+            .await?;
+        assert_eq!(
+            storage.stat("2025/01/01/1/output.mp4").await?.unwrap().size,
+            3
+        );
+
+        storage.delete("2025/01/01/1/output.mp4").await?;
+        assert_eq!(read(&storage, "2025/01/01/1/output.mp4").await, None);
+        assert_eq!(storage.stat("2025/01/01/1/output.mp4").await?, None);
+        // 空になったディレクトリも削除する
+        assert!(!dir.path().join("videos/2025").exists());
+        assert!(dir.path().join("videos").exists());
+        storage.delete("2025/01/01/1/output.mp4").await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_put_failed() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let storage = LocalStorage::new("local1", dir.path());
+        storage
+            .put("1/output.mp4", "video/mp4", data(&[b"OLD"]))
+            .await?;
+
+        let broken = stream::iter(vec![
+            Ok(Bytes::from_static(b"NEW")),
+            Err(anyhow::anyhow!("接続が切れました")),
+        ])
+        .boxed();
+        assert!(storage
+            .put("1/output.mp4", "video/mp4", broken)
+            .await
+            .is_err());
+
+        // 書きかけの内容は残さず、既存の内容も壊さない
+        assert_eq!(
+            read(&storage, "1/output.mp4").await.as_deref(),
+            Some(&b"OLD"[..])
+        );
+        assert_eq!(std::fs::read_dir(dir.path().join("1"))?.count(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let storage = LocalStorage::new("local1", dir.path());
+        for location in [
+            "2025/01/02/2/output.mp4",
+            "2025/01/01/1/output.mp4",
+            "2025/01/01/1/input.ts",
+            "2024/12/31/0/input.ts",
+        ] {
+            storage.put(location, "video/mp4", data(&[b"DATA"])).await?;
+        }
+        std::fs::write(dir.path().join("2025/01/01/1/.input.ts.partial"), b"")?;
+
+        let locations = |objects: Vec<StoredObject>| {
+            objects
+                .into_iter()
+                .map(|object| object.location)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            locations(storage.list("2025/").await?),
+            vec![
+                "2025/01/01/1/input.ts",
+                "2025/01/01/1/output.mp4",
+                "2025/01/02/2/output.mp4",
+            ]
+        );
+        assert_eq!(
+            locations(storage.list("2025/01/01/1/in").await?),
+            vec!["2025/01/01/1/input.ts"]
+        );
+        assert_eq!(storage.list("").await?.len(), 4);
+        assert!(storage.list("2026/").await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_location() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new("local1", dir.path());
+        for location in ["", "../escape", "2025//1", "/absolute", "2025/./1"] {
+            assert!(storage.stat(location).await.is_err(), "{}", location);
+            assert!(storage
+                .put(location, "video/mp4", data(&[b"DATA"]))
+                .await
+                .is_err());
+        }
+        assert!(storage.list("../").await.is_err());
+    }
+}
@@ -0,0 +1,573 @@
+//! S3 互換のオブジェクトストレージ
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use chrono::{DateTime, Utc};
+use domain::ports::storage::{validate_location, ByteStream, Storage, StoredObject};
+use futures::StreamExt;
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE, ETAG, LAST_MODIFIED};
+use reqwest::{Method, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::config::S3StorageConfig;
+use crate::sigv4::{
+    amz_date, canonical_query, uri_encode_path, CanonicalRequest, Signer, UNSIGNED_PAYLOAD,
+};
+
+/// S3 互換のオブジェクトストレージ
+///
+/// バケットはパス形式 (`{endpoint}/{bucket}/{key}`) で指定するため、MinIO などでも使える。
+/// 位置の前に設定の `prefix` を付けたものをキーにする。
+/// `part_size` より大きい内容はマルチパートアップロードで送り、失敗した場合はアップロードを中止する。
+pub struct S3Storage {
+    name: String,
+    client: reqwest::Client,
+    endpoint: String,
+    host: String,
+    base_path: String,
+    bucket: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    prefix: String,
+    part_size: usize,
+}
+
+impl S3Storage {
+    /// 新しい `S3Storage` を作成する。認証情報は設定済みである必要がある。
+    pub fn new(config: S3StorageConfig) -> Result<Self> {
+        let url = Url::parse(&config.endpoint)
+            .with_context(|| format!("S3 のエンドポイントが不正です: {}", config.endpoint))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => bail!(
+                "S3 のエンドポイントにホストがありません: {}",
+                config.endpoint
+            ),
+        };
+        let prefix = config
+            .prefix
+            .as_deref()
+            .map(|prefix| prefix.trim_matches('/'))
+            .filter(|prefix| !prefix.is_empty())
+            .map(|prefix| format!("{}/", prefix))
+            .unwrap_or_default();
+        Ok(Self {
+            name: config.name,
+            client: reqwest::Client::new(),
+            endpoint: format!("{}://{}", url.scheme(), host),
+            host,
+            base_path: url.path().trim_end_matches('/').to_string(),
+            bucket: config.bucket,
+            region: config.region,
+            access_key_id: config
+                .access_key_id
+                .ok_or_else(|| anyhow!("S3 のアクセスキーがありません"))?,
+            secret_access_key: config
+                .secret_access_key
+                .ok_or_else(|| anyhow!("S3 のシークレットキーがありません"))?,
+            prefix,
+            part_size: usize::try_from(config.part_size)?,
+        })
+    }
+
+    /// バケットがなければ作成する。
+    pub async fn ensure_bucket(&self) -> Result<()> {
+        let response = self.send(Method::PUT, None, &[], &[], Bytes::new()).await?;
+        if response.status() == StatusCode::CONFLICT {
+            let body = response.text().await.unwrap_or_default();
+            if body.contains("BucketAlreadyOwnedByYou") {
+                return Ok(());
+            }
+            bail!("バケット {} を作成できません: {}", self.bucket, body);
+        }
+        check(response, "CreateBucket").await?;
+        Ok(())
+    }
+
+    fn key(&self, location: &str) -> Result<String> {
+        validate_location(location)?;
+        Ok(format!("{}{}", self.prefix, location))
+    }
+
+    /// 署名したリクエストを送る。`key` が `None` の場合はバケットへのリクエスト。
+    async fn send(
+        &self,
+        method: Method,
+        key: Option<&str>,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        body: Bytes,
+    ) -> Result<Response> {
+        let path = match key {
+            Some(key) => format!("{}/{}/{}", self.base_path, self.bucket, key),
+            None => format!("{}/{}", self.base_path, self.bucket),
+        };
+        let path = uri_encode_path(&path);
+        let query = canonical_query(query);
+        let url = if query.is_empty() {
+            format!("{}{}", self.endpoint, path)
+        } else {
+            format!("{}{}?{}", self.endpoint, path, query)
+        };
+
+        let time = Utc::now();
+        let date = amz_date(time);
+        let signer = Signer {
+            access_key_id: &self.access_key_id,
+            secret_access_key: &self.secret_access_key,
+            region: &self.region,
+            service: "s3",
+        };
+        let authorization = signer.authorization(
+            &CanonicalRequest {
+                method: method.as_str(),
+                path: &path,
+                query: &query,
+                headers: &[
+                    ("host", &self.host),
+                    ("x-amz-content-sha256", UNSIGNED_PAYLOAD),
+                    ("x-amz-date", &date),
+                ],
+                payload_hash: UNSIGNED_PAYLOAD,
+            },
+            time,
+        );
+
+        let mut request = self
+            .client
+            .request(method.clone(), &url)
+            .header("x-amz-content-sha256", UNSIGNED_PAYLOAD)
+            .header("x-amz-date", date)
+            .header("authorization", authorization);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        if method == Method::PUT || method == Method::POST {
+            request = request.header(CONTENT_LENGTH, body.len()).body(body);
+        }
+        request
+            .send()
+            .await
+            .with_context(|| format!("S3 にリクエストを送れません: {} {}", method, url))
+    }
+
+    async fn put_object(&self, key: &str, content_type: &str, body: Bytes) -> Result<()> {
+        let response = self
+            .send(
+                Method::PUT,
+                Some(key),
+                &[],
+                &[(CONTENT_TYPE.as_str(), content_type)],
+                body,
+            )
+            .await?;
+        check(response, "PutObject").await?;
+        Ok(())
+    }
+
+    /// マルチパートアップロードで送る。`buffer` には最初のパートが入っている。
+    async fn put_multipart(
+        &self,
+        key: &str,
+        content_type: &str,
+        buffer: &mut BytesMut,
+        data: &mut ByteStream,
+    ) -> Result<u64> {
+        let response = self
+            .send(
+                Method::POST,
+                Some(key),
+                &[("uploads", "")],
+                &[(CONTENT_TYPE.as_str(), content_type)],
+                Bytes::new(),
+            )
+            .await?;
+        let body = check(response, "CreateMultipartUpload")
+            .await?
+            .text()
+            .await?;
+        let upload_id = quick_xml::de::from_str::<InitiateMultipartUploadResult>(&body)
+            .with_context(|| format!("UploadId がありません: {}", body))?
+            .upload_id;
+        debug!(key, upload_id = %upload_id, "マルチパートアップロードを開始します");
+
+        let result = self.upload_parts(key, &upload_id, buffer, data).await;
+        if result.is_err() {
+            if let Err(e) = self.abort_multipart(key, &upload_id).await {
+                warn!(key, upload_id = %upload_id, "マルチパートアップロードを中止できません: {:?}", e);
+            }
+        }
+        result
+    }
+
+    async fn abort_multipart(&self, key: &str, upload_id: &str) -> Result<()> {
+        let response = self
+            .send(
+                Method::DELETE,
+                Some(key),
+                &[("uploadId", upload_id)],
+                &[],
+                Bytes::new(),
+            )
+            .await?;
+        check(response, "AbortMultipartUpload").await?;
+        Ok(())
+    }
+
+    async fn upload_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        buffer: &mut BytesMut,
+        data: &mut ByteStream,
+    ) -> Result<u64> {
+        let mut etags = Vec::new();
+        let mut size = 0;
+        loop {
+            let part = buffer.split().freeze();
+            size += part.len() as u64;
+            let part_number = (etags.len() + 1).to_string();
+            let response = self
+                .send(
+                    Method::PUT,
+                    Some(key),
+                    &[("partNumber", &part_number), ("uploadId", upload_id)],
+                    &[],
+                    part,
+                )
+                .await?;
+            let response = check(response, "UploadPart").await?;
+            let etag = response
+                .headers()
+                .get(ETAG)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| anyhow!("パート {} の ETag がありません", part_number))?;
+            etags.push(etag.to_string());
+
+            // 最後のパートは part_size より小さくてよい
+            fill(data, buffer, self.part_size).await?;
+            if buffer.is_empty() {
+                break;
+            }
+        }
+
+        let body = quick_xml::se::to_string(&CompleteMultipartUpload {
+            parts: etags
+                .into_iter()
+                .enumerate()
+                .map(|(i, etag)| CompletedPart {
+                    part_number: i + 1,
+                    etag,
+                })
+                .collect(),
+        })?;
+        let response = self
+            .send(
+                Method::POST,
+                Some(key),
+                &[("uploadId", upload_id)],
+                &[(CONTENT_TYPE.as_str(), "application/xml")],
+                Bytes::from(body),
+            )
+            .await?;
+        // 200 でもエラーを返すことがある
+        let body = check(response, "CompleteMultipartUpload")
+            .await?
+            .text()
+            .await?;
+        if body.contains("<Error>") {
+            bail!("CompleteMultipartUpload に失敗しました: {}", body);
+        }
+        Ok(size)
+    }
+}
+
+/// `buffer` が `part_size` になるまで読み込む。内容の終わりに達した場合は `true`。
+async fn fill(data: &mut ByteStream, buffer: &mut BytesMut, part_size: usize) -> Result<bool> {
+    while buffer.len() < part_size {
+        match data.next().await {
+            Some(chunk) => buffer.extend_from_slice(&chunk?),
+            None => return Ok(true),
+        }
+    }
+    Ok(false)
+}
+
+/// 失敗したレスポンスをエラーにする。
+async fn check(response: Response, operation: &str) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    bail!("S3 の {} に失敗しました: {} {}", operation, status, body)
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn put(
+        &self,
+        location: &str,
+        content_type: &str,
+        mut data: ByteStream,
+    ) -> Result<StoredObject> {
+        let key = self.key(location)?;
+        let mut buffer = BytesMut::with_capacity(self.part_size);
+        let size = if fill(&mut data, &mut buffer, self.part_size).await? {
+            let size = buffer.len() as u64;
+            self.put_object(&key, content_type, buffer.freeze()).await?;
+            size
+        } else {
+            self.put_multipart(&key, content_type, &mut buffer, &mut data)
+                .await?
+        };
+        Ok(StoredObject {
+            location: location.to_string(),
+            size,
+            content_type: Some(content_type.to_string()),
+            modified_at: None,
+        })
+    }
+
+    async fn get(&self, location: &str) -> Result<Option<ByteStream>> {
+        let key = self.key(location)?;
+        let response = self
+            .send(Method::GET, Some(&key), &[], &[], Bytes::new())
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = check(response, "GetObject").await?;
+        Ok(Some(
+            response
+                .bytes_stream()
+                .map(|chunk| chunk.map_err(anyhow::Error::from))
+                .boxed(),
+        ))
+    }
+
+    async fn delete(&self, location: &str) -> Result<()> {
+        let key = self.key(location)?;
+        let response = self
+            .send(Method::DELETE, Some(&key), &[], &[], Bytes::new())
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        check(response, "DeleteObject").await?;
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>> {
+        let key_prefix = format!("{}{}", self.prefix, prefix);
+        let mut objects = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", key_prefix.as_str())];
+            if let Some(token) = &token {
+                query.push(("continuation-token", token));
+            }
+            let response = self
+                .send(Method::GET, None, &query, &[], Bytes::new())
+                .await?;
+            let body = check(response, "ListObjectsV2").await?.text().await?;
+            let result: ListBucketResult = quick_xml::de::from_str(&body)
+                .with_context(|| format!("ListObjectsV2 の結果を読めません: {}", body))?;
+            for contents in result.contents {
+                let Some(location) = contents.key.strip_prefix(&self.prefix) else {
+                    continue;
+                };
+                objects.push(StoredObject {
+                    location: location.to_string(),
+                    size: contents.size,
+                    content_type: None,
+                    modified_at: contents
+                        .last_modified
+                        .and_then(|time| DateTime::parse_from_rfc3339(&time).ok())
+                        .map(|time| time.with_timezone(&Utc)),
+                });
+            }
+            token = result.next_continuation_token;
+            if !result.is_truncated || token.is_none() {
+                break;
+            }
+        }
+        objects.sort_by(|a, b| a.location.cmp(&b.location));
+        Ok(objects)
+    }
+
+    async fn stat(&self, location: &str) -> Result<Option<StoredObject>> {
+        let key = self.key(location)?;
+        let response = self
+            .send(Method::HEAD, Some(&key), &[], &[], Bytes::new())
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = check(response, "HeadObject").await?;
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        Ok(Some(StoredObject {
+            location: location.to_string(),
+            size: header(CONTENT_LENGTH)
+                .and_then(|size| size.parse().ok())
+                .ok_or_else(|| anyhow!("{} の Content-Length がありません", key))?,
+            content_type: header(CONTENT_TYPE).map(str::to_string),
+            modified_at: header(LAST_MODIFIED)
+                .and_then(|time| DateTime::parse_from_rfc2822(time).ok())
+                .map(|time| time.with_timezone(&Utc)),
+        }))
+    }
+}
+
+/// CreateMultipartUpload の結果
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InitiateMultipartUploadResult {
+    upload_id: String,
+}
+
+/// CompleteMultipartUpload のリクエスト
+#[derive(Serialize)]
+struct CompleteMultipartUpload {
+    #[serde(rename = "Part")]
+    parts: Vec<CompletedPart>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct CompletedPart {
+    part_number: usize,
+    #[serde(rename = "ETag")]
+    etag: String,
+}
+
+/// ListObjectsV2 の結果
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListBucketResult {
+    #[serde(default)]
+    is_truncated: bool,
+    next_continuation_token: Option<String>,
+    #[serde(default)]
+    contents: Vec<ListContents>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListContents {
+    key: String,
+    #[serde(default)]
+    size: u64,
+    last_modified: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Name>kurec</Name><Prefix>videos/2025/</Prefix><KeyCount>2</KeyCount>
+  <IsTruncated>true</IsTruncated>
+  <NextContinuationToken>1ueGcxLPRx1Tr/XYExHnhbYLgveDs2J/wm36Hy4vbOwM=</NextContinuationToken>
+  <Contents>
+    <Key>videos/2025/01/01/1/Tom &amp; Jerry.mp4</Key>
+    <LastModified>2025-01-01T12:30:00.000Z</LastModified>
+    <ETag>&quot;fba9dede5f27731c9771645a39863328&quot;</ETag>
+    <Size>434234</Size>
+  </Contents>
+  <Contents>
+    <Key>videos/2025/01/01/1/input.ts</Key>
+    <Size>1024</Size>
+  </Contents>
+</ListBucketResult>"#;
+        let result: ListBucketResult = quick_xml::de::from_str(xml).unwrap();
+        assert!(result.is_truncated);
+        assert_eq!(
+            result.next_continuation_token.as_deref(),
+            Some("1ueGcxLPRx1Tr/XYExHnhbYLgveDs2J/wm36Hy4vbOwM=")
+        );
+        assert_eq!(result.contents.len(), 2);
+        assert_eq!(
+            result.contents[0].key,
+            "videos/2025/01/01/1/Tom & Jerry.mp4"
+        );
+        assert_eq!(result.contents[0].size, 434234);
+        assert_eq!(
+            result.contents[0].last_modified.as_deref(),
+            Some("2025-01-01T12:30:00.000Z")
+        );
+        assert_eq!(result.contents[1].size, 1024);
+        assert_eq!(result.contents[1].last_modified, None);
+
+        let empty: ListBucketResult = quick_xml::de::from_str(
+            "<ListBucketResult><Name>kurec</Name><IsTruncated>false</IsTruncated></ListBucketResult>",
+        )
+        .unwrap();
+        assert!(!empty.is_truncated);
+        assert!(empty.contents.is_empty());
+    }
+
+    #[test]
+    fn test_complete_multipart_upload() {
+        let body = quick_xml::se::to_string(&CompleteMultipartUpload {
+            parts: vec![
+                CompletedPart {
+                    part_number: 1,
+                    etag: "\"a&b\"".to_string(),
+                },
+                CompletedPart {
+                    part_number: 2,
+                    etag: "\"c\"".to_string(),
+                },
+            ],
+        })
+        .unwrap();
+        assert_eq!(
+            body,
+            "<CompleteMultipartUpload>\
+             <Part><PartNumber>1</PartNumber><ETag>\"a&amp;b\"</ETag></Part>\
+             <Part><PartNumber>2</PartNumber><ETag>\"c\"</ETag></Part>\
+             </CompleteMultipartUpload>"
+        );
+    }
+
+    #[test]
+    fn test_new() {
+        let storage = S3Storage::new(S3StorageConfig {
+            name: "archive".to_string(),
+            endpoint: "http://minio:9000/".to_string(),
+            bucket: "kurec".to_string(),
+            region: "us-east-1".to_string(),
+            access_key_id: Some("minio".to_string()),
+            secret_access_key: Some("minio123".to_string()),
+            prefix: Some("/videos/".to_string()),
+            part_size: 5 * 1024 * 1024,
+        })
+        .unwrap();
+        assert_eq!(storage.endpoint, "http://minio:9000");
+        assert_eq!(storage.host, "minio:9000");
+        assert_eq!(storage.base_path, "");
+        assert_eq!(
+            storage.key("2025/01/01/1/input.ts").unwrap(),
+            "videos/2025/01/01/1/input.ts"
+        );
+        assert!(storage.key("../input.ts").is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{bail, Result};
+use bytes::Bytes;
+use domain::ports::storage::{ByteStream, Storage};
+use futures::{stream, StreamExt};
+use infra_storage::{S3Storage, S3StorageConfig};
+use testcontainers::core::WaitFor;
+use testcontainers::{runners::AsyncRunner, ContainerAsync, GenericImage, ImageExt};
+
+const PART_SIZE: usize = 5 * 1024 * 1024;
+
+async fn ensure_docker() {
+    for _ in 0..20 {
+        if std::process::Command::new("docker")
+            .arg("info")
+            .output()
+            .is_ok()
+        {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+    panic!("Docker daemon not ready");
+}
+
+async fn setup_minio() -> Result<(ContainerAsync<GenericImage>, S3Storage)> {
+    ensure_docker().await;
+    let container = GenericImage::new("minio/minio", "latest")
+        .with_exposed_port(9000u16.into())
+        .with_wait_for(WaitFor::message_on_stderr("API:"))
+        .with_env_var("MINIO_ROOT_USER", "minio")
+        .with_env_var("MINIO_ROOT_PASSWORD", "minio123")
+        .with_cmd(["server", "/data"])
+        .start()
+        .await?;
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(9000u16).await?;
+    let storage = S3Storage::new(S3StorageConfig {
+        name: "archive".to_string(),
+        endpoint: format!("http://{}:{}", host, port),
+        bucket: "kurec".to_string(),
+        region: "us-east-1".to_string(),
+        access_key_id: Some("minio".to_string()),
+        secret_access_key: Some("minio123".to_string()),
+        prefix: Some("videos".to_string()),
+        part_size: PART_SIZE as u64,
+    })?;
+
+    // 起動直後は準備ができていないことがあるため、バケットを作成できるまで待つ
+    for _ in 0..30 {
+        if storage.ensure_bucket().await.is_ok() {
+            return Ok((container, storage));
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
+    bail!("MinIO が起動しませんでした");
+}
+
+fn chunks(data: &[u8], size: usize) -> ByteStream {
+    let chunks: Vec<Result<Bytes>> = data
+        .chunks(size)
+        .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+        .collect();
+    stream::iter(chunks).boxed()
+}
+
+async fn read(storage: &S3Storage, location: &str) -> Result<Option<Vec<u8>>> {
+    let Some(stream) = storage.get(location).await? else {
+        return Ok(None);
+    };
+    let chunks: Vec<Bytes> = stream.map(|chunk| chunk.unwrap()).collect().await;
+    Ok(Some(chunks.concat()))
+}
+
+#[tokio::test]
+async fn test_s3_put_get_list_delete() -> Result<()> {
+    let (_container, storage) = setup_minio().await?;
+    storage.ensure_bucket().await?;
+
+    let object = storage
+        .put(
+            "2025/01/01/1/番組 #1.mp4",
+            "video/mp4",
+            chunks(b"MP4DATA", 3),
+        )
+        .await?;
+    assert_eq!(object.size, 7);
+    assert_eq!(
+        read(&storage, "2025/01/01/1/番組 #1.mp4").await?.as_deref(),
+        Some(&b"MP4DATA"[..])
+    );
+    let stat = storage.stat("2025/01/01/1/番組 #1.mp4").await?.unwrap();
+    assert_eq!(stat.size, 7);
+    assert_eq!(stat.content_type.as_deref(), Some("video/mp4"));
+    assert!(stat.modified_at.is_some());
+
+    storage
+        .put("2025/01/02/2/input.ts", "video/mp2t", chunks(b"TS", 2))
+        .await?;
+    let locations: Vec<String> = storage
+        .list("2025/01/")
+        .await?
+        .into_iter()
+        .map(|object| object.location)
+        .collect();
+    assert_eq!(
+        locations,
+        vec!["2025/01/01/1/番組 #1.mp4", "2025/01/02/2/input.ts"]
+    );
+
+    storage.delete("2025/01/01/1/番組 #1.mp4").await?;
+    assert_eq!(storage.stat("2025/01/01/1/番組 #1.mp4").await?, None);
+    assert_eq!(read(&storage, "2025/01/01/1/番組 #1.mp4").await?, None);
+    storage.delete("2025/01/01/1/番組 #1.mp4").await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_s3_multipart_upload() -> Result<()> {
+    let (_container, storage) = setup_minio().await?;
+
+    // 2 パート半の内容を半端なチャンクで送る
+    let data: Vec<u8> = (0..PART_SIZE * 5 / 2).map(|i| (i % 251) as u8).collect();
+    let object = storage
+        .put(
+            "2025/01/01/1/input.ts",
+            "video/mp2t",
+            chunks(&data, 1_000_003),
+        )
+        .await?;
+    assert_eq!(object.size, data.len() as u64);
+    assert_eq!(
+        storage.stat("2025/01/01/1/input.ts").await?.unwrap().size,
+        data.len() as u64
+    );
+    assert_eq!(read(&storage, "2025/01/01/1/input.ts").await?, Some(data));
+
+    // 途中で失敗した場合はオブジェクトを作らない
+    let broken = chunks(&vec![0; PART_SIZE + 1], PART_SIZE)
+        .chain(stream::iter(vec![Err(anyhow::anyhow!("接続が切れました"))]))
+        .boxed();
+    assert!(storage
+        .put("2025/01/01/1/broken.ts", "video/mp2t", broken)
+        .await
+        .is_err());
+    assert_eq!(storage.stat("2025/01/01/1/broken.ts").await?, None);
+    Ok(())
+}
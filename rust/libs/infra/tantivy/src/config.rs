@@ -0,0 +1,28 @@
+//! 組み込みの番組検索インデックスの設定
+
+use std::path::PathBuf;
+
+/// 組み込みの番組検索インデックスの設定
+#[derive(Debug, Clone)]
+pub struct TantivyConfig {
+    /// インデックスを保存するディレクトリ
+    pub path: PathBuf,
+    /// lindera の辞書のディレクトリ (指定しない場合は bi-gram で分割する)
+    pub dictionary: Option<PathBuf>,
+}
+
+impl TantivyConfig {
+    /// インデックスを保存するディレクトリを指定して作成する。
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            dictionary: None,
+        }
+    }
+
+    /// lindera の辞書のディレクトリを設定する。
+    pub fn with_dictionary(mut self, dictionary: Option<PathBuf>) -> Self {
+        self.dictionary = dictionary;
+        self
+    }
+}
@@ -0,0 +1,12 @@
+//! tantivy のインフラクレート
+//!
+//! このクレートは番組検索・録画ライブラリの検索インデックス (`ProgramSearchIndex`) を tantivy で実装します。
+//! 外部の検索サーバーを使わず、インデックスをローカルディスクに保存します。
+
+mod config;
+mod program_index;
+mod tokenizer;
+
+pub use config::TantivyConfig;
+pub use program_index::TantivyProgramIndex;
+pub use tokenizer::JapaneseTokenizer;
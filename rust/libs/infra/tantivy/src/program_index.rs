@@ -0,0 +1,752 @@
+//! tantivy による番組検索インデックス
+//!
+//! 検索・絞り込み用のフィールドとは別に、`ProgramDocument` を JSON のまま保存しておき、
+//! 検索結果や差分の比較にはそれを返します。
+//! 録画ライブラリのドキュメントは録画開始時刻・ストレージ名・エンコード状況も絞り込み用のフィールドに持ちます。
+//! tantivy の処理はブロッキングするため `spawn_blocking` で実行します。
+
+use std::ops::{Bound, Range};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::models::search::{
+    ProgramDocument, ProgramSearchHit, ProgramSearchQuery, ProgramSearchResult, SearchHighlights,
+    SearchSort,
+};
+use domain::ports::program_search_index::ProgramSearchIndex;
+use tantivy::collector::{Count, DocSetCollector, TopDocs};
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{
+    AllQuery, BooleanQuery, BoostQuery, Occur, PhraseQuery, Query, RangeQuery, TermQuery,
+};
+use tantivy::schema::{
+    Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, FAST, INDEXED, STORED,
+    STRING,
+};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::tokenizer::{TextAnalyzer, TokenStream};
+use tantivy::{
+    DocAddress, Index, IndexReader, IndexWriter, Order, ReloadPolicy, Searcher, TantivyDocument,
+    Term,
+};
+use tracing::info;
+
+use crate::config::TantivyConfig;
+use crate::tokenizer::{JapaneseTokenizer, TOKENIZER_NAME};
+
+/// インデックス作成に使うメモリ量 (バイト)
+const WRITER_MEMORY_BUDGET: usize = 50_000_000;
+
+/// 番組名のハイライトの最大長 (tantivy はバイト数で数える。番組名は全体を含める)
+const TITLE_SNIPPET_CHARS: usize = 1000;
+/// 番組説明・詳細情報のハイライトの最大長 (バイト数)
+const BODY_SNIPPET_CHARS: usize = 400;
+/// 番組名に一致した場合の重み
+const TITLE_BOOST: f32 = 2.0;
+
+const HIGHLIGHT_PRE_TAG: &str = "<em>";
+const HIGHLIGHT_POST_TAG: &str = "</em>";
+
+/// インデックスのフィールド
+struct Fields {
+    program_id: Field,
+    service_id: Field,
+    title: Field,
+    description: Field,
+    extended: Field,
+    genre: Field,
+    channel: Field,
+    weekday: Field,
+    start_at: Field,
+    recorded_at: Field,
+    storage: Field,
+    encode_status: Field,
+    document: Field,
+}
+
+/// 開始時刻のフィールド名 (並べ替えに使う)
+const START_AT_FIELD: &str = "start_at";
+/// 録画開始時刻のフィールド名 (並べ替えに使う)
+const RECORDED_AT_FIELD: &str = "recorded_at";
+
+fn schema() -> (Schema, Fields) {
+    let text = TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer(TOKENIZER_NAME)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+    );
+    let mut builder = Schema::builder();
+    let fields = Fields {
+        program_id: builder.add_i64_field("program_id", INDEXED),
+        service_id: builder.add_i64_field("service_id", INDEXED),
+        title: builder.add_text_field("title", text.clone()),
+        description: builder.add_text_field("description", text.clone()),
+        extended: builder.add_text_field("extended", text),
+        genre: builder.add_text_field("genre", STRING),
+        channel: builder.add_text_field("channel", STRING),
+        weekday: builder.add_text_field("weekday", STRING),
+        start_at: builder.add_i64_field(START_AT_FIELD, INDEXED | FAST),
+        recorded_at: builder.add_i64_field(RECORDED_AT_FIELD, INDEXED | FAST),
+        storage: builder.add_text_field("storage", STRING),
+        encode_status: builder.add_text_field("encode_status", STRING),
+        document: builder.add_text_field("document", STORED),
+    };
+    (builder.build(), fields)
+}
+
+/// 一致した範囲を `<em>` で囲む。重なっている範囲 (bi-gram など) はまとめる。
+fn highlight(text: &str, ranges: &[Range<usize>]) -> Option<String> {
+    let mut ranges = ranges.to_vec();
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    if merged.is_empty() {
+        return None;
+    }
+    let mut result = String::new();
+    let mut cursor = 0;
+    for range in merged {
+        result.push_str(&text[cursor..range.start]);
+        result.push_str(HIGHLIGHT_PRE_TAG);
+        result.push_str(&text[range.clone()]);
+        result.push_str(HIGHLIGHT_POST_TAG);
+        cursor = range.end;
+    }
+    result.push_str(&text[cursor..]);
+    Some(result)
+}
+
+/// インデックスと書き込み・読み込みのハンドル
+struct Inner {
+    index: Index,
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+    fields: Fields,
+}
+
+impl Inner {
+    /// 書き込んでコミットし、検索に反映する。
+    fn write(&self, f: impl FnOnce(&IndexWriter, &Fields) -> Result<()>) -> Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| anyhow!("インデックスの書き込みロックを取得できません"))?;
+        f(&writer, &self.fields)?;
+        writer.commit()?;
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    fn to_tantivy(&self, document: &ProgramDocument) -> Result<TantivyDocument> {
+        let fields = &self.fields;
+        let mut doc = TantivyDocument::default();
+        doc.add_i64(fields.program_id, document.program_id);
+        doc.add_i64(fields.service_id, document.service_id);
+        doc.add_text(fields.title, &document.title);
+        doc.add_text(fields.description, &document.description);
+        doc.add_text(fields.extended, &document.extended);
+        for genre in &document.genres {
+            doc.add_text(fields.genre, genre);
+        }
+        doc.add_text(fields.channel, &document.channel);
+        doc.add_text(fields.weekday, &document.day_of_week);
+        doc.add_i64(fields.start_at, document.start_at.timestamp());
+        if let Some(record) = &document.record {
+            doc.add_i64(fields.recorded_at, record.recorded_timestamp);
+            for storage in &record.storages {
+                doc.add_text(fields.storage, storage);
+            }
+            for encode_status in &record.encode_statuses {
+                doc.add_text(fields.encode_status, encode_status);
+            }
+        }
+        doc.add_text(fields.document, serde_json::to_string(document)?);
+        Ok(doc)
+    }
+
+    fn load(&self, searcher: &Searcher, address: DocAddress) -> Result<ProgramDocument> {
+        let doc: TantivyDocument = searcher.doc(address)?;
+        let json = doc
+            .get_first(self.fields.document)
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| anyhow!("ドキュメントが保存されていません: {:?}", address))?;
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// キーワードの検索条件 (キーワードがない場合は `None`)
+    ///
+    /// 空白で区切ったキーワードごとに、番組名・番組説明・詳細情報のいずれかにトークンが連続して含まれる番組に一致する。
+    fn text_query(&self, text: &str) -> Result<Option<Box<dyn Query>>> {
+        let mut analyzer: TextAnalyzer = self.index.tokenizer_for_field(self.fields.title)?;
+        let fields = &self.fields;
+        let mut keywords: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for keyword in text.split_whitespace() {
+            let mut tokens = Vec::new();
+            let mut stream = analyzer.token_stream(keyword);
+            while stream.advance() {
+                let token = stream.token();
+                tokens.push((token.position, token.text.clone()));
+            }
+            if tokens.is_empty() {
+                continue;
+            }
+            let field_query = |field: Field| -> Box<dyn Query> {
+                if let [(_, token)] = tokens.as_slice() {
+                    Box::new(TermQuery::new(
+                        Term::from_field_text(field, token),
+                        IndexRecordOption::WithFreqsAndPositions,
+                    ))
+                } else {
+                    Box::new(PhraseQuery::new_with_offset(
+                        tokens
+                            .iter()
+                            .map(|(position, token)| {
+                                (*position, Term::from_field_text(field, token))
+                            })
+                            .collect(),
+                    ))
+                }
+            };
+            let any_field = BooleanQuery::new(vec![
+                (
+                    Occur::Should,
+                    Box::new(BoostQuery::new(field_query(fields.title), TITLE_BOOST)),
+                ),
+                (Occur::Should, field_query(fields.description)),
+                (Occur::Should, field_query(fields.extended)),
+            ]);
+            keywords.push((Occur::Must, Box::new(any_field)));
+        }
+        Ok((!keywords.is_empty()).then(|| Box::new(BooleanQuery::new(keywords)) as Box<dyn Query>))
+    }
+
+    fn search(&self, query: &ProgramSearchQuery) -> Result<ProgramSearchResult> {
+        let fields = &self.fields;
+        let text_query = self.text_query(&query.text)?;
+
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        if let Some(text_query) = &text_query {
+            clauses.push((Occur::Must, text_query.box_clone()));
+        }
+        for (field, values) in [
+            (fields.genre, &query.genres),
+            (fields.channel, &query.channels),
+            (fields.weekday, &query.weekdays),
+            (fields.storage, &query.storages),
+            (fields.encode_status, &query.encode_statuses),
+        ] {
+            if let Some(filter) = any_of(field, values) {
+                clauses.push((Occur::Must, filter));
+            }
+        }
+        for (field, from, to) in [
+            (fields.start_at, query.start_from, query.start_to),
+            (fields.recorded_at, query.recorded_from, query.recorded_to),
+        ] {
+            if let Some(filter) = time_range(field, from, to) {
+                clauses.push((Occur::Must, filter));
+            }
+        }
+        let search_query: Box<dyn Query> = if clauses.is_empty() {
+            Box::new(AllQuery)
+        } else {
+            Box::new(BooleanQuery::new(clauses))
+        };
+
+        let searcher = self.reader.searcher();
+        let top = TopDocs::with_limit(query.limit()).and_offset(query.offset);
+        let (total, addresses): (usize, Vec<DocAddress>) =
+            if text_query.is_some() && query.sort == SearchSort::Relevance {
+                let (total, docs) = searcher.search(&search_query, &(Count, top))?;
+                (
+                    total,
+                    docs.into_iter().map(|(_, address)| address).collect(),
+                )
+            } else {
+                let (field, order) = match query.sort {
+                    SearchSort::RecordedAt => (RECORDED_AT_FIELD, Order::Desc),
+                    _ => (START_AT_FIELD, Order::Asc),
+                };
+                let (total, docs) = searcher.search(
+                    &search_query,
+                    &(Count, top.order_by_fast_field::<i64>(field, order)),
+                )?;
+                (
+                    total,
+                    docs.into_iter().map(|(_, address)| address).collect(),
+                )
+            };
+
+        let generators = match &text_query {
+            Some(text_query) => {
+                let generator = |field: Field, max_num_chars: usize| -> Result<SnippetGenerator> {
+                    let mut generator = SnippetGenerator::create(&searcher, text_query, field)?;
+                    generator.set_max_num_chars(max_num_chars);
+                    Ok(generator)
+                };
+                Some([
+                    generator(fields.title, TITLE_SNIPPET_CHARS)?,
+                    generator(fields.description, BODY_SNIPPET_CHARS)?,
+                    generator(fields.extended, BODY_SNIPPET_CHARS)?,
+                ])
+            }
+            None => None,
+        };
+        let hits = addresses
+            .into_iter()
+            .map(|address| {
+                let document = self.load(&searcher, address)?;
+                let highlights = match &generators {
+                    Some([title, description, extended]) => {
+                        let snippet = |generator: &SnippetGenerator, text: &str| {
+                            let snippet = generator.snippet(text);
+                            highlight(snippet.fragment(), snippet.highlighted())
+                        };
+                        SearchHighlights {
+                            title: snippet(title, &document.title),
+                            description: snippet(description, &document.description),
+                            extended: snippet(extended, &document.extended),
+                        }
+                    }
+                    None => SearchHighlights::default(),
+                };
+                Ok(ProgramSearchHit {
+                    document,
+                    highlights,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(ProgramSearchResult { total, hits })
+    }
+}
+
+/// いずれかの値に一致する条件 (値がない場合は `None`)
+fn any_of(field: Field, values: &[String]) -> Option<Box<dyn Query>> {
+    (!values.is_empty()).then(|| {
+        Box::new(BooleanQuery::new(
+            values
+                .iter()
+                .map(|value| {
+                    (
+                        Occur::Should,
+                        Box::new(TermQuery::new(
+                            Term::from_field_text(field, value),
+                            IndexRecordOption::Basic,
+                        )) as Box<dyn Query>,
+                    )
+                })
+                .collect(),
+        )) as Box<dyn Query>
+    })
+}
+
+/// 時刻の範囲の条件 (`from` 以上 `to` 未満。どちらもない場合は `None`)
+fn time_range(
+    field: Field,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Option<Box<dyn Query>> {
+    if from.is_none() && to.is_none() {
+        return None;
+    }
+    let bound = |time: Option<DateTime<Utc>>, inclusive: bool| match time {
+        Some(t) if inclusive => Bound::Included(Term::from_field_i64(field, t.timestamp())),
+        Some(t) => Bound::Excluded(Term::from_field_i64(field, t.timestamp())),
+        None => Bound::Unbounded,
+    };
+    Some(Box::new(RangeQuery::new(
+        bound(from, true),
+        bound(to, false),
+    )))
+}
+
+/// tantivy の番組検索インデックス (番組検索・録画ライブラリで別のディレクトリを使う)
+#[derive(Clone)]
+pub struct TantivyProgramIndex {
+    inner: Arc<Inner>,
+}
+
+impl TantivyProgramIndex {
+    /// インデックスを開く。ディレクトリにインデックスがない場合は作成する。
+    pub fn open(config: &TantivyConfig) -> Result<Self> {
+        std::fs::create_dir_all(&config.path).with_context(|| {
+            format!(
+                "インデックスのディレクトリを作成できません: {}",
+                config.path.display()
+            )
+        })?;
+        let (schema, fields) = schema();
+        let directory = MmapDirectory::open(&config.path).with_context(|| {
+            format!(
+                "インデックスのディレクトリを開けません: {}",
+                config.path.display()
+            )
+        })?;
+        let index = Index::open_or_create(directory, schema).with_context(|| {
+            format!(
+                "インデックスを開けません (スキーマが変わった場合はディレクトリを削除し、作り直してください): {}",
+                config.path.display()
+            )
+        })?;
+        let tokenizer = match &config.dictionary {
+            Some(dictionary) => JapaneseTokenizer::lindera(dictionary)?,
+            None => JapaneseTokenizer::Bigram,
+        };
+        index
+            .tokenizers()
+            .register(TOKENIZER_NAME, TextAnalyzer::from(tokenizer));
+        let writer = index.writer(WRITER_MEMORY_BUDGET)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        info!(path = %config.path.display(), "番組検索インデックスを開きました");
+        Ok(Self {
+            inner: Arc::new(Inner {
+                index,
+                reader,
+                writer: Mutex::new(writer),
+                fields,
+            }),
+        })
+    }
+
+    async fn run<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Inner) -> Result<T> + Send + 'static,
+    {
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || f(&inner)).await?
+    }
+}
+
+#[async_trait]
+impl ProgramSearchIndex for TantivyProgramIndex {
+    async fn ensure_index(&self) -> Result<()> {
+        // インデックスは開いたときに作成している
+        Ok(())
+    }
+
+    async fn get_service_documents(&self, service_id: i64) -> Result<Vec<ProgramDocument>> {
+        self.run(move |inner| {
+            let searcher = inner.reader.searcher();
+            let query = TermQuery::new(
+                Term::from_field_i64(inner.fields.service_id, service_id),
+                IndexRecordOption::Basic,
+            );
+            searcher
+                .search(&query, &DocSetCollector)?
+                .into_iter()
+                .map(|address| inner.load(&searcher, address))
+                .collect()
+        })
+        .await
+    }
+
+    async fn upsert_documents(&self, documents: &[ProgramDocument]) -> Result<()> {
+        let documents = documents.to_vec();
+        self.run(move |inner| {
+            let docs = documents
+                .iter()
+                .map(|document| Ok((document.program_id, inner.to_tantivy(document)?)))
+                .collect::<Result<Vec<_>>>()?;
+            inner.write(|writer, fields| {
+                for (program_id, doc) in docs {
+                    writer.delete_term(Term::from_field_i64(fields.program_id, program_id));
+                    writer.add_document(doc)?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn delete_documents(&self, program_ids: &[i64]) -> Result<()> {
+        let program_ids = program_ids.to_vec();
+        self.run(move |inner| {
+            inner.write(|writer, fields| {
+                for program_id in program_ids {
+                    writer.delete_term(Term::from_field_i64(fields.program_id, program_id));
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn delete_all_documents(&self) -> Result<()> {
+        self.run(|inner| {
+            inner.write(|writer, _| {
+                writer.delete_all_documents()?;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn search(&self, query: &ProgramSearchQuery) -> Result<ProgramSearchResult> {
+        let query = query.clone();
+        self.run(move |inner| inner.search(&query)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use domain::events::mirakc_events::RecordingStatus;
+    use domain::models::search::RecordDocument;
+
+    fn document(program_id: i64, title: &str, hour: u32) -> ProgramDocument {
+        let start_at = Utc.with_ymd_and_hms(2025, 1, 6, hour, 0, 0).unwrap();
+        ProgramDocument {
+            program_id,
+            service_id: 1024,
+            title: title.to_string(),
+            description: "番組の説明".to_string(),
+            extended: String::new(),
+            channel: "ＮＨＫ総合１・東京".to_string(),
+            genres: vec!["アニメ・特撮／国内アニメ".to_string()],
+            cast: vec![],
+            start_at,
+            start_timestamp: start_at.timestamp(),
+            end_at: start_at + chrono::Duration::hours(1),
+            day_of_week: "月".to_string(),
+            duration: 60,
+            urls: vec![],
+            ogp_url: None,
+            ogp_url_hash: None,
+            record: None,
+        }
+    }
+
+    fn recorded(program_id: i64, hour: u32, storage: &str, encode_status: &str) -> ProgramDocument {
+        ProgramDocument {
+            record: Some(RecordDocument {
+                record_id: format!("{:016}", program_id),
+                mirakc_url: "http://tuner:40772".to_string(),
+                recorded_at: time(hour),
+                recorded_timestamp: time(hour).timestamp(),
+                status: RecordingStatus::Finished,
+                outputs: vec![],
+                storages: vec![storage.to_string()],
+                encode_statuses: vec![encode_status.to_string()],
+                tags: vec![],
+            }),
+            ..document(program_id, &format!("録画{}", program_id), hour)
+        }
+    }
+
+    fn time(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 6, hour, 0, 0).unwrap()
+    }
+
+    async fn search(index: &TantivyProgramIndex, query: ProgramSearchQuery) -> Vec<i64> {
+        index
+            .search(&query)
+            .await
+            .unwrap()
+            .hits
+            .iter()
+            .map(|hit| hit.document.program_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_highlight() {
+        assert_eq!(
+            highlight("機動戦士ガンダム", &[12..18, 15..21, 18..24]),
+            Some("機動戦士<em>ガンダム</em>".to_string())
+        );
+        assert_eq!(highlight("ニュース", &[]), None);
+    }
+
+    #[tokio::test]
+    async fn test_program_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = TantivyConfig::new(dir.path());
+        let index = TantivyProgramIndex::open(&config).unwrap();
+        index.ensure_index().await.unwrap();
+
+        let mut news = document(3, "ＮＨＫニュース７", 10);
+        news.genres = vec!["ニュース／報道".to_string()];
+        news.day_of_week = "火".to_string();
+        let mut other_service = document(4, "機動戦士ガンダム 特別編", 8);
+        other_service.service_id = 1025;
+        other_service.channel = "ＴＢＳ".to_string();
+        index
+            .upsert_documents(&[
+                document(1, "機動戦士ガンダムＳＥＥＤ", 12),
+                document(2, "ドラゴンボール", 11),
+                news,
+                other_service,
+            ])
+            .await
+            .unwrap();
+
+        // 全角・半角やカタカナ・ひらがなの違いを区別しない
+        let result = index
+            .search(&ProgramSearchQuery {
+                text: "がんだむ seed".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(
+            result.hits[0].highlights.title.as_deref(),
+            Some("機動戦士<em>ガンダムＳＥＥＤ</em>")
+        );
+        assert_eq!(result.hits[0].highlights.description, None);
+
+        // 連続していない部分には一致しない
+        let query = |text: &str| ProgramSearchQuery {
+            text: text.to_string(),
+            ..Default::default()
+        };
+        assert!(search(&index, query("ガンボール")).await.is_empty());
+
+        // キーワードがない場合は開始時刻の順
+        assert_eq!(search(&index, query("")).await, vec![4, 3, 2, 1]);
+        assert_eq!(
+            search(
+                &index,
+                ProgramSearchQuery {
+                    text: "ガンダム".to_string(),
+                    sort: SearchSort::StartAt,
+                    ..Default::default()
+                }
+            )
+            .await,
+            vec![4, 1]
+        );
+
+        // 絞り込み
+        let filtered = |query: ProgramSearchQuery| search(&index, query);
+        assert_eq!(
+            filtered(ProgramSearchQuery {
+                genres: vec!["ニュース／報道".to_string()],
+                ..Default::default()
+            })
+            .await,
+            vec![3]
+        );
+        assert_eq!(
+            filtered(ProgramSearchQuery {
+                channels: vec!["ＴＢＳ".to_string()],
+                ..Default::default()
+            })
+            .await,
+            vec![4]
+        );
+        assert_eq!(
+            filtered(ProgramSearchQuery {
+                weekdays: vec!["月".to_string()],
+                start_from: Some(time(10)),
+                start_to: Some(time(12)),
+                ..Default::default()
+            })
+            .await,
+            vec![2]
+        );
+        assert_eq!(
+            filtered(ProgramSearchQuery {
+                start_from: Some(time(11)),
+                offset: 1,
+                limit: 1,
+                ..Default::default()
+            })
+            .await,
+            vec![1]
+        );
+
+        // 更新と削除
+        let mut updated = document(2, "ドラゴンボール超", 11);
+        updated.extended = "出演者: 野沢雅子".to_string();
+        index.upsert_documents(&[updated.clone()]).await.unwrap();
+        index.delete_documents(&[3]).await.unwrap();
+        let mut documents = index.get_service_documents(1024).await.unwrap();
+        documents.sort_by_key(|d| d.program_id);
+        assert_eq!(
+            documents.iter().map(|d| d.program_id).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(documents[1], updated);
+        let result = index.search(&query("野沢")).await.unwrap();
+        assert_eq!(
+            result.hits[0].highlights.extended.as_deref(),
+            Some("出演者: <em>野沢</em>雅子")
+        );
+
+        // 開き直しても残っている
+        drop(index);
+        let index = TantivyProgramIndex::open(&config).unwrap();
+        assert_eq!(index.get_service_documents(1025).await.unwrap().len(), 1);
+
+        index.delete_all_documents().await.unwrap();
+        assert_eq!(index.search(&query("")).await.unwrap().total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_records_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = TantivyProgramIndex::open(&TantivyConfig::new(dir.path())).unwrap();
+        index
+            .upsert_documents(&[
+                recorded(1, 8, "mirakc", "original"),
+                recorded(2, 9, "local", "encoded"),
+                recorded(3, 10, "mirakc", "encoded"),
+            ])
+            .await
+            .unwrap();
+
+        // 録画開始時刻の新しい順
+        let sorted = ProgramSearchQuery {
+            sort: SearchSort::RecordedAt,
+            ..Default::default()
+        };
+        assert_eq!(search(&index, sorted.clone()).await, vec![3, 2, 1]);
+        assert_eq!(
+            search(
+                &index,
+                ProgramSearchQuery {
+                    storages: vec!["mirakc".to_string()],
+                    ..sorted.clone()
+                }
+            )
+            .await,
+            vec![3, 1]
+        );
+        assert_eq!(
+            search(
+                &index,
+                ProgramSearchQuery {
+                    encode_statuses: vec!["encoded".to_string()],
+                    recorded_to: Some(time(10)),
+                    ..sorted.clone()
+                }
+            )
+            .await,
+            vec![2]
+        );
+        let result = index
+            .search(&ProgramSearchQuery {
+                text: "録画3".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            result.hits[0].document,
+            recorded(3, 10, "mirakc", "encoded")
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! 日本語のトークナイザー
+//!
+//! 辞書を指定した場合は lindera で形態素解析し、指定しない場合は日本語の文字を2文字ずつ (bi-gram) に区切ります。
+//! どちらの場合もトークンは `normalize_text` で正規化するため、全角・半角やカタカナ・ひらがなの違いを区別せずに検索できます。
+
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use domain::models::title::normalize_text;
+use lindera::dictionary::load_dictionary;
+use lindera::mode::Mode;
+use lindera::segmenter::Segmenter;
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+
+/// インデックスに登録するトークナイザーの名前
+pub(crate) const TOKENIZER_NAME: &str = "kurec_ja";
+
+/// 日本語のトークナイザー
+#[derive(Clone)]
+pub enum JapaneseTokenizer {
+    /// 日本語の文字を2文字ずつに区切る
+    Bigram,
+    /// lindera で形態素解析する
+    Lindera(Arc<Segmenter>),
+}
+
+impl JapaneseTokenizer {
+    /// 辞書のディレクトリを指定して lindera のトークナイザーを作成する。
+    pub fn lindera(dictionary: &Path) -> Result<Self> {
+        let dictionary = load_dictionary(&dictionary.to_string_lossy()).map_err(|e| {
+            anyhow!(
+                "lindera の辞書を読み込めません: {}: {}",
+                dictionary.display(),
+                e
+            )
+        })?;
+        Ok(Self::Lindera(Arc::new(Segmenter::new(
+            Mode::Normal,
+            dictionary,
+            None,
+        ))))
+    }
+
+    /// テキストをトークンに分割する。
+    fn tokens(&self, text: &str) -> Vec<Token> {
+        let spans = match self {
+            Self::Bigram => bigram_spans(text),
+            Self::Lindera(segmenter) => match segmenter.segment(Cow::Borrowed(text)) {
+                Ok(tokens) => tokens
+                    .iter()
+                    .map(|token| (token.byte_start, token.byte_end))
+                    .filter(|&(start, end)| text[start..end].chars().any(char::is_alphanumeric))
+                    .collect(),
+                // 解析できない場合は bi-gram にする
+                Err(_) => bigram_spans(text),
+            },
+        };
+        spans
+            .into_iter()
+            .enumerate()
+            .map(|(position, (offset_from, offset_to))| Token {
+                offset_from,
+                offset_to,
+                position,
+                text: normalize_text(&text[offset_from..offset_to]),
+                position_length: 1,
+            })
+            .collect()
+    }
+}
+
+/// 日本語の文字 (かな・漢字) か
+fn is_japanese(c: char) -> bool {
+    matches!(c,
+        '\u{3005}'                  // 々
+        | '\u{3040}'..='\u{30FA}'   // ひらがな・カタカナ (中黒を除く)
+        | '\u{30FC}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'   // CJK 統合漢字拡張 A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK 統合漢字
+        | '\u{F900}'..='\u{FAFF}'   // CJK 互換漢字
+        | '\u{FF66}'..='\u{FF9F}'   // 半角カタカナ
+    )
+}
+
+/// 文字の種類
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharKind {
+    Japanese,
+    Word,
+    Other,
+}
+
+fn char_kind(c: char) -> CharKind {
+    if is_japanese(c) {
+        CharKind::Japanese
+    } else if c.is_alphanumeric() {
+        CharKind::Word
+    } else {
+        CharKind::Other
+    }
+}
+
+/// 英数字の並びは1つのトークンに、日本語の文字の並びは2文字ずつのトークンにする。
+///
+/// 日本語の文字が1文字だけの場合はその1文字をトークンにする。
+fn bigram_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let kind = char_kind(c);
+        // 同じ種類の文字の並び (バイト位置)
+        let mut run = vec![start];
+        while let Some(&(index, next)) = chars.peek() {
+            if char_kind(next) != kind {
+                break;
+            }
+            run.push(index);
+            chars.next();
+        }
+        let end = chars.peek().map_or(text.len(), |&(index, _)| index);
+        match kind {
+            CharKind::Other => {}
+            CharKind::Word => spans.push((start, end)),
+            CharKind::Japanese if run.len() == 1 => spans.push((start, end)),
+            CharKind::Japanese => {
+                run.push(end);
+                spans.extend(run.windows(3).map(|w| (w[0], w[2])));
+            }
+        }
+    }
+    spans
+}
+
+impl Tokenizer for JapaneseTokenizer {
+    type TokenStream<'a> = JapaneseTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        JapaneseTokenStream {
+            tokens: self.tokens(text),
+            index: 0,
+        }
+    }
+}
+
+/// 分割済みのトークンを順に返すトークンストリーム
+pub struct JapaneseTokenStream {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl TokenStream for JapaneseTokenStream {
+    fn advance(&mut self) -> bool {
+        if self.index < self.tokens.len() {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.index - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.index - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(text: &str) -> Vec<String> {
+        let mut tokenizer = JapaneseTokenizer::Bigram;
+        let mut stream = tokenizer.token_stream(text);
+        let mut texts = Vec::new();
+        while stream.advance() {
+            texts.push(stream.token().text.clone());
+        }
+        texts
+    }
+
+    #[test]
+    fn test_bigram() {
+        assert_eq!(
+            texts("機動戦士ガンダムＳＥＥＤ 第1話"),
+            vec![
+                "機動", "動戦", "戦士", "士が", "がん", "んだ", "だむ", "seed", "第", "1", "話"
+            ]
+        );
+        assert_eq!(
+            texts("「ニュース」・天気"),
+            vec!["にゅ", "ゅー", "ーす", "天気"]
+        );
+        assert!(texts("　！？").is_empty());
+    }
+
+    #[test]
+    fn test_bigram_offsets() {
+        let mut tokenizer = JapaneseTokenizer::Bigram;
+        let mut stream = tokenizer.token_stream("あいう");
+        let mut tokens = Vec::new();
+        while stream.advance() {
+            let token = stream.token();
+            tokens.push((token.offset_from, token.offset_to, token.position));
+        }
+        assert_eq!(tokens, vec![(0, 6, 0), (3, 9, 1)]);
+    }
+}
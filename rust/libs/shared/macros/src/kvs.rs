@@ -17,7 +17,7 @@ use syn::{
 };
 
 // Helper function to parse string literal
-fn parse_lit_str(lit: &Lit, attr_name: &str) -> syn::Result<String> {
+pub(crate) fn parse_lit_str(lit: &Lit, attr_name: &str) -> syn::Result<String> {
     match lit {
         Lit::Str(s) => Ok(s.value()),
         _ => Err(syn::Error::new_spanned(
@@ -28,7 +28,7 @@ fn parse_lit_str(lit: &Lit, attr_name: &str) -> syn::Result<String> {
 }
 
 // Helper function to parse integer literal
-fn parse_lit_int<T: std::str::FromStr>(lit: &Lit, attr_name: &str) -> syn::Result<T>
+pub(crate) fn parse_lit_int<T: std::str::FromStr>(lit: &Lit, attr_name: &str) -> syn::Result<T>
 where
     <T as std::str::FromStr>::Err: std::fmt::Display,
 {
@@ -47,7 +47,7 @@ where
 }
 
 // Helper function to parse boolean literal
-fn parse_lit_bool(lit: &Lit, attr_name: &str) -> syn::Result<bool> {
+pub(crate) fn parse_lit_bool(lit: &Lit, attr_name: &str) -> syn::Result<bool> {
     match lit {
         Lit::Bool(b) => Ok(b.value()),
         _ => Err(syn::Error::new_spanned(
@@ -58,7 +58,7 @@ fn parse_lit_bool(lit: &Lit, attr_name: &str) -> syn::Result<bool> {
 }
 
 // Helper function to parse array of string literals
-fn parse_lit_str_array(lit: &Lit, attr_name: &str) -> syn::Result<Vec<String>> {
+pub(crate) fn parse_lit_str_array(lit: &Lit, attr_name: &str) -> syn::Result<Vec<String>> {
     match lit {
         Lit::Verbatim(v) => {
             // Try parsing as an array expression: `["tag1", "tag2"]`
@@ -2,6 +2,7 @@ use proc_macro::TokenStream;
 
 // event, stream モジュールは削除
 mod kvs;
+mod object_store;
 
 // parse_duration は stream マクロで使われていたので削除
 
@@ -37,6 +38,36 @@ pub fn define_kvs_bucket(attr: TokenStream, item: TokenStream) -> TokenStream {
     kvs::kvs_bucket_impl(attr, item)
 }
 
+/// Object Store バケットを定義するマクロ
+///
+/// このマクロは、型に ObjectStoreBucket トレイトを実装します。
+/// 大きなファイルをチャンクに分けて保存するバケットを、KVS バケットと同じ形式で宣言できます。
+///
+/// # 属性パラメータ
+///
+/// - `bucket_name = "バケット名"`: (必須) Object Store バケットの名前を指定します。
+/// - `description = "説明"`: バケットの説明。
+/// - `max_age = "期間"`: オブジェクトの最大生存期間 (例: "30d")。
+/// - `max_bytes = 数値`: バケットの最大バイト数 (i64)。
+/// - `storage = "タイプ"`: ストレージタイプ ("file" または "memory")。
+/// - `num_replicas = 数値`: レプリカ数 (usize)。
+/// - `compression = 真偽値`: 圧縮の有効/無効。
+/// - `placement_cluster = "クラスタ名"`: 配置クラスタ名。
+/// - `placement_tags = ["タグ1", "タグ2"]`: 配置タグ。
+///
+/// # 使用例
+///
+/// ```ignore
+/// use shared_macros::define_object_store_bucket;
+///
+/// #[define_object_store_bucket(bucket_name = "my_objects", max_bytes = 1073741824)]
+/// struct MyObjects;
+/// ```
+#[proc_macro_attribute]
+pub fn define_object_store_bucket(attr: TokenStream, item: TokenStream) -> TokenStream {
+    object_store::object_store_bucket_impl(attr, item)
+}
+
 // tests モジュール内の parse_duration テストも削除
 #[cfg(test)]
 mod tests {
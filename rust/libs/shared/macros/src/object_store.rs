@@ -0,0 +1,161 @@
+//! Object Store バケット定義マクロの実装
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    parse::Parser, parse_macro_input, punctuated::Punctuated, DeriveInput, Expr, Meta,
+    MetaNameValue, Token,
+};
+
+use crate::kvs::{parse_lit_bool, parse_lit_int, parse_lit_str, parse_lit_str_array};
+
+pub fn object_store_bucket_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    let type_name = &input.ident;
+    let attr_args = match Punctuated::<Meta, Token![,]>::parse_terminated.parse(attr) {
+        Ok(args) => args,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    // --- Attribute Parsing ---
+    let mut bucket_name: Option<String> = None;
+    let mut description: Option<String> = None;
+    let mut max_age: Option<String> = None;
+    let mut max_bytes: Option<i64> = None;
+    let mut storage: Option<String> = None;
+    let mut num_replicas: Option<usize> = None;
+    let mut compression: Option<bool> = None;
+    let mut placement_cluster: Option<String> = None;
+    let mut placement_tags: Option<Vec<String>> = None;
+
+    for meta in attr_args {
+        let Meta::NameValue(MetaNameValue { path, value, .. }) = meta else {
+            return syn::Error::new_spanned(
+                meta,
+                "Expected key-value attribute like `key = \"value\"`",
+            )
+            .to_compile_error()
+            .into();
+        };
+        let Some(ident) = path.get_ident() else {
+            return syn::Error::new_spanned(path, "Expected identifier for attribute name")
+                .to_compile_error()
+                .into();
+        };
+        let Expr::Lit(expr_lit) = value else {
+            return syn::Error::new_spanned(value, "Attribute value must be a literal")
+                .to_compile_error()
+                .into();
+        };
+        let lit = &expr_lit.lit;
+        let result = match ident.to_string().as_str() {
+            "bucket_name" => parse_lit_str(lit, "bucket_name").map(|val| bucket_name = Some(val)),
+            "description" => parse_lit_str(lit, "description").map(|val| description = Some(val)),
+            "max_age" => parse_lit_str(lit, "max_age").and_then(|val| {
+                if ::humantime::parse_duration(&val).is_err() {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        format!("Invalid duration format for max_age: '{}'. Expected format like '7d', '24h', etc.", val),
+                    ));
+                }
+                max_age = Some(val);
+                Ok(())
+            }),
+            "max_bytes" => {
+                parse_lit_int::<i64>(lit, "max_bytes").map(|val| max_bytes = Some(val))
+            }
+            "storage" => parse_lit_str(lit, "storage").and_then(|val| {
+                if val != "file" && val != "memory" {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        format!(
+                            "Invalid storage type: '{}'. Expected 'file' or 'memory'.",
+                            val
+                        ),
+                    ));
+                }
+                storage = Some(val);
+                Ok(())
+            }),
+            "num_replicas" => {
+                parse_lit_int::<usize>(lit, "num_replicas").map(|val| num_replicas = Some(val))
+            }
+            "compression" => parse_lit_bool(lit, "compression").map(|val| compression = Some(val)),
+            "placement_cluster" => parse_lit_str(lit, "placement_cluster")
+                .map(|val| placement_cluster = Some(val)),
+            "placement_tags" => parse_lit_str_array(lit, "placement_tags")
+                .map(|val| placement_tags = Some(val)),
+            _ => Err(syn::Error::new_spanned(path, "Unknown attribute")),
+        };
+        if let Err(e) = result {
+            return e.to_compile_error().into();
+        }
+    }
+
+    let Some(bucket_name) = bucket_name else {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "Missing required attribute `bucket_name`",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    // --- Code Generation ---
+    let description_expr =
+        description.map(|val| quote! { config.description = Some(#val.to_string()); });
+    let max_age_expr = max_age.map(|val| {
+        quote! { config.max_age = ::humantime::parse_duration(#val).expect("Duration already validated"); }
+    });
+    let max_bytes_expr = max_bytes.map(|val| quote! { config.max_bytes = #val; });
+    let storage_expr = storage.map(|val| match val.as_str() {
+        "file" => quote! { config.storage = async_nats::jetstream::stream::StorageType::File; },
+        "memory" => quote! { config.storage = async_nats::jetstream::stream::StorageType::Memory; },
+        _ => unreachable!("Storage type already validated"),
+    });
+    let num_replicas_expr = num_replicas.map(|val| quote! { config.num_replicas = #val; });
+    let compression_expr = compression.map(|val| quote! { config.compression = #val; });
+    let placement_expr = if placement_cluster.is_some() || placement_tags.is_some() {
+        let cluster_expr = placement_cluster
+            .map(|c| quote! { cluster: Some(#c.to_string()), })
+            .unwrap_or_else(|| quote! { cluster: None, });
+        let tags_expr = placement_tags
+            .map(|t| quote! { tags: Some(vec![#(#t.to_string()),*]), })
+            .unwrap_or_else(|| quote! { tags: None, });
+        quote! {
+            config.placement = Some(async_nats::jetstream::stream::Placement {
+                #cluster_expr
+                #tags_expr
+            });
+        }
+    } else {
+        quote! {}
+    };
+
+    let expanded = quote! {
+        #input
+
+        impl shared_types::object_store::ObjectStoreBucket for #type_name {
+            const BUCKET_NAME: &'static str = #bucket_name;
+
+            fn config() -> async_nats::jetstream::object_store::Config {
+                let mut config = async_nats::jetstream::object_store::Config {
+                    bucket: #bucket_name.to_string(),
+                    ..Default::default()
+                };
+
+                #description_expr
+                #max_age_expr
+                #max_bytes_expr
+                #storage_expr
+                #num_replicas_expr
+                #compression_expr
+                #placement_expr
+
+                config
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
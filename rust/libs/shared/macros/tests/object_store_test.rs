@@ -0,0 +1,10 @@
+//! define_object_store_bucket マクロの trybuild テスト
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    // マクロが正しく適用され、コンパイルが成功するケース
+    t.pass("tests/ui/object_store_pass/*.rs");
+    // マクロの適用が不正で、コンパイルが失敗するケース
+    t.compile_fail("tests/ui/object_store_fail/*.rs");
+}
@@ -0,0 +1,8 @@
+//! bucket_name 属性がない場合にコンパイルエラーになることを確認
+
+use shared_macros::define_object_store_bucket;
+
+#[define_object_store_bucket(max_bytes = 1024)] // bucket_name がない
+struct MissingNameObjects;
+
+fn main() {}
@@ -0,0 +1,25 @@
+//! 属性を指定した成功ケース
+
+use shared_macros::define_object_store_bucket;
+use shared_types::object_store::ObjectStoreBucket;
+
+#[define_object_store_bucket(
+    bucket_name = "basic_objects",
+    description = "テスト用",
+    max_bytes = 1073741824,
+    storage = "memory"
+)]
+struct BasicObjects;
+
+fn main() {
+    assert_eq!(BasicObjects::BUCKET_NAME, "basic_objects");
+    let config = BasicObjects::config();
+    assert_eq!(config.bucket, "basic_objects");
+    assert_eq!(config.description.as_deref(), Some("テスト用"));
+    assert_eq!(config.max_bytes, 1073741824);
+    assert_eq!(
+        config.storage,
+        async_nats::jetstream::stream::StorageType::Memory
+    );
+    assert!(!config.compression);
+}
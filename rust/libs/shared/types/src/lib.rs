@@ -4,4 +4,5 @@
 
 // pub mod event_metadata; // 削除
 pub mod kvs;
+pub mod object_store;
 // pub mod stream; // 削除
@@ -0,0 +1,17 @@
+//! JetStream Object Store バケット定義用トレイト
+
+use async_nats::jetstream::object_store::Config;
+
+/// JetStream Object Store バケットを表すトレイト。
+///
+/// このトレイトは `shared_macros::define_object_store_bucket` マクロによって実装されます。
+pub trait ObjectStoreBucket {
+    /// Object Store バケットの名前。
+    const BUCKET_NAME: &'static str;
+
+    /// Object Store バケットの設定を生成します。
+    ///
+    /// マクロ属性で指定されなかった項目は `async_nats::jetstream::object_store::Config` の
+    /// デフォルト値になります。
+    fn config() -> Config;
+}
@@ -0,0 +1,24 @@
+//! オフラインテスト用の mirakc シミュレーター
+//!
+//! 実機のチューナーサーバーを使わずに `infra_mirakc` やワーカーの結合テストを行うため、
+//! フィクスチャファイルに書かれたシナリオどおりに振る舞う mirakc を提供します。
+//!
+//! - REST API: サービス (ロゴを含む)、チャンネル、番組、チューナー、放送中番組、録画レコード、録画予約
+//! - `/events` SSE: 接続ごとの台本 (配信タイミング、切断、接続拒否) と、テストからの任意のイベント配信
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use testing_mirakc::MirakcSimulator;
+//!
+//! let simulator = MirakcSimulator::from_fixture("basic").await?;
+//! let mirakc_url = simulator.url();
+//! simulator.emit("tuner.status-changed", serde_json::json!({ "tunerIndex": 0 }));
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod scenario;
+pub mod server;
+
+pub use scenario::{fixture_path, Scenario, ScriptedEvent, SessionEnd, SseScript, SseSession};
+pub use server::{MirakcSimulator, LOGO_PNG};
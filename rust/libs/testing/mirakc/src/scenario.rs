@@ -0,0 +1,103 @@
+//! シミュレーターのシナリオ定義
+//!
+//! シナリオは mirakc が返すデータ (サービス、番組、チューナー、録画レコード、録画予約など) と、
+//! `/events` SSE で配信するイベントの台本をまとめたもので、JSON のフィクスチャファイルから読み込む。
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use mirakc_client::models::{
+    MirakurunProgram, MirakurunService, MirakurunTuner, Version, WebOnairProgram, WebRecord,
+    WebRecordingSchedule,
+};
+use serde::{Deserialize, Serialize};
+
+/// シミュレーターが提供するデータと SSE の台本
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scenario {
+    /// `/api/version` が返すバージョン
+    pub version: Option<Version>,
+    /// `/api/services` が返すサービス
+    pub services: Vec<MirakurunService>,
+    /// `/api/programs` が返す番組
+    pub programs: Vec<MirakurunProgram>,
+    /// `/api/tuners` が返すチューナー
+    pub tuners: Vec<MirakurunTuner>,
+    /// `/api/onair` が返す放送中番組
+    pub onair: Vec<WebOnairProgram>,
+    /// `/api/recording/records` が返す録画レコード
+    pub records: Vec<WebRecord>,
+    /// `/api/recording/schedules` が返す録画予約
+    pub schedules: Vec<WebRecordingSchedule>,
+    /// `/events` の台本
+    pub events: SseScript,
+}
+
+impl Scenario {
+    /// JSON ファイルからシナリオを読み込む
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scenario file: {}", path.display()))?;
+        serde_json::from_str(&data)
+            .with_context(|| format!("Failed to parse scenario file: {}", path.display()))
+    }
+
+    /// このクレートに同梱されたフィクスチャ (`fixtures/{name}.json`) を読み込む
+    pub fn fixture(name: &str) -> Result<Self> {
+        Self::from_file(fixture_path(name))
+    }
+}
+
+/// 同梱フィクスチャのパスを取得する
+pub fn fixture_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(format!("{}.json", name))
+}
+
+/// `/events` の台本
+///
+/// SSE の接続ごとに `sessions` を先頭から1つずつ消費する。
+/// すべて消費した後の接続は、台本のイベントを持たず切断もされないセッションとして扱う。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SseScript {
+    pub sessions: Vec<SseSession>,
+}
+
+/// 1回の SSE 接続の台本
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SseSession {
+    /// 指定した場合、接続をこのステータスコードで拒否する (再接続の試験用)
+    pub status: Option<u16>,
+    /// 接続直後から順に配信するイベント
+    pub events: Vec<ScriptedEvent>,
+    /// 台本のイベントを配信し終えた後の動作
+    pub end: SessionEnd,
+}
+
+/// 台本のイベントを配信し終えた後の動作
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SessionEnd {
+    /// 接続を維持し、`MirakcSimulator::emit` で送られたイベントを配信し続ける
+    #[default]
+    Hold,
+    /// 接続を切断する
+    Close,
+}
+
+/// 台本の SSE イベント
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptedEvent {
+    /// 直前のイベント (または接続) からの待ち時間 (ミリ秒)
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// イベント種別 (例: `epg.programs-updated`)
+    pub event: String,
+    /// イベントデータ
+    pub data: serde_json::Value,
+}
@@ -0,0 +1,474 @@
+//! mirakc シミュレーターの HTTP サーバー
+
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use mirakc_client::models::{
+    MirakurunChannel, MirakurunChannelServicesInner, RecordingScheduleState, Version,
+    WebRecordingSchedule, WebRecordingScheduleInput,
+};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::CancellationToken;
+
+use crate::scenario::{Scenario, ScriptedEvent, SessionEnd, SseSession};
+
+/// `Scenario::version` が指定されていない場合に返すバージョン
+pub const DEFAULT_VERSION: &str = "4.0.0";
+
+/// `hasLogoData` が `true` のサービスについて `/api/services/{id}/logo` が返す画像 (1x1 の PNG)
+pub const LOGO_PNG: &[u8] = &[
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f, 0x15, 0xc4,
+    0x89, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0x60, 0x00, 0x02, 0x00,
+    0x00, 0x05, 0x00, 0x01, 0xe9, 0xfa, 0xdc, 0xd8, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44,
+    0xae, 0x42, 0x60, 0x82,
+];
+
+/// ローカルポートで待ち受ける mirakc シミュレーター
+///
+/// 破棄するとサーバーは停止する。
+pub struct MirakcSimulator {
+    addr: SocketAddr,
+    state: Arc<SimState>,
+    shutdown: CancellationToken,
+}
+
+struct SimState {
+    data: RwLock<Scenario>,
+    sessions: Mutex<VecDeque<SseSession>>,
+    live: broadcast::Sender<ScriptedEvent>,
+    drops: watch::Sender<u64>,
+    sse_connections: AtomicUsize,
+}
+
+impl MirakcSimulator {
+    /// シナリオを読み込んだシミュレーターを起動する
+    pub async fn start(scenario: Scenario) -> Result<Self> {
+        let sessions = scenario.events.sessions.iter().cloned().collect();
+        let state = Arc::new(SimState {
+            data: RwLock::new(scenario),
+            sessions: Mutex::new(sessions),
+            live: broadcast::channel(256).0,
+            drops: watch::channel(0).0,
+            sse_connections: AtomicUsize::new(0),
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .context("Failed to bind mirakc simulator")?;
+        let addr = listener.local_addr()?;
+        let shutdown = CancellationToken::new();
+
+        let app = router(state.clone());
+        let server_shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app)
+                .with_graceful_shutdown(server_shutdown.cancelled_owned())
+                .await;
+        });
+        tracing::debug!(%addr, "mirakc simulator started");
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown,
+        })
+    }
+
+    /// 同梱フィクスチャ (`fixtures/{name}.json`) のシナリオでシミュレーターを起動する
+    pub async fn from_fixture(name: &str) -> Result<Self> {
+        Self::start(Scenario::fixture(name)?).await
+    }
+
+    /// mirakc のベースURL (`http://127.0.0.1:{port}`)
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// 接続中の SSE クライアントにイベントを配信する
+    ///
+    /// 台本のイベントを配信中の接続には、台本を配信し終えた後に届く。
+    pub fn emit(&self, event: &str, data: serde_json::Value) {
+        let _ = self.state.live.send(ScriptedEvent {
+            delay_ms: 0,
+            event: event.to_string(),
+            data,
+        });
+    }
+
+    /// 接続中の SSE クライアントをすべて切断する
+    pub fn drop_connections(&self) {
+        self.state.drops.send_modify(|generation| *generation += 1);
+    }
+
+    /// これまでに受け付けた `/events` への接続の数 (拒否したものを含む)
+    pub fn sse_connections(&self) -> usize {
+        self.state.sse_connections.load(Ordering::SeqCst)
+    }
+
+    /// 現在のシナリオのデータを参照する
+    pub fn read<T>(&self, f: impl FnOnce(&Scenario) -> T) -> T {
+        f(&self.state.data.read().unwrap())
+    }
+
+    /// シナリオのデータを変更する (チューナーの状態変化などの再現用)
+    pub fn modify<T>(&self, f: impl FnOnce(&mut Scenario) -> T) -> T {
+        f(&mut self.state.data.write().unwrap())
+    }
+}
+
+impl Drop for MirakcSimulator {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+        self.drop_connections();
+    }
+}
+
+fn router(state: Arc<SimState>) -> Router {
+    Router::new()
+        .route("/api/version", get(version))
+        .route("/api/services", get(services))
+        .route("/api/services/{id}", get(service))
+        .route("/api/services/{id}/programs", get(programs_of_service))
+        .route("/api/services/{id}/logo", get(logo))
+        .route("/api/channels", get(channels))
+        .route("/api/programs", get(programs))
+        .route("/api/programs/{id}", get(program))
+        .route("/api/tuners", get(tuners))
+        .route("/api/tuners/{index}", get(tuner))
+        .route("/api/onair", get(onair_programs))
+        .route("/api/onair/{service_id}", get(onair_program))
+        .route("/api/recording/records", get(records))
+        .route(
+            "/api/recording/records/{id}",
+            get(record).delete(remove_record),
+        )
+        .route(
+            "/api/recording/schedules",
+            get(schedules)
+                .post(create_schedule)
+                .delete(delete_schedules),
+        )
+        .route(
+            "/api/recording/schedules/{program_id}",
+            get(schedule).delete(delete_schedule),
+        )
+        .route("/events", get(events))
+        .with_state(state)
+}
+
+type SimResult<T> = std::result::Result<Json<T>, StatusCode>;
+
+fn found<T>(value: Option<T>) -> SimResult<T> {
+    value.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn version(State(state): State<Arc<SimState>>) -> Json<Version> {
+    let data = state.data.read().unwrap();
+    Json(data.version.clone().unwrap_or_else(|| Version {
+        current: DEFAULT_VERSION.to_string(),
+        latest: DEFAULT_VERSION.to_string(),
+    }))
+}
+
+async fn services(State(state): State<Arc<SimState>>) -> impl IntoResponse {
+    Json(state.data.read().unwrap().services.clone())
+}
+
+async fn service(
+    State(state): State<Arc<SimState>>,
+    Path(id): Path<i64>,
+) -> SimResult<impl serde::Serialize> {
+    let data = state.data.read().unwrap();
+    found(data.services.iter().find(|s| s.id == id).cloned())
+}
+
+async fn logo(State(state): State<Arc<SimState>>, Path(id): Path<i64>) -> Response {
+    let data = state.data.read().unwrap();
+    match data.services.iter().find(|s| s.id == id) {
+        Some(service) if service.has_logo_data => {
+            ([(axum::http::header::CONTENT_TYPE, "image/png")], LOGO_PNG).into_response()
+        }
+        _ => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// サービスの `channel` からチャンネル一覧を組み立てる
+async fn channels(State(state): State<Arc<SimState>>) -> impl IntoResponse {
+    let data = state.data.read().unwrap();
+    let mut channels: Vec<MirakurunChannel> = Vec::new();
+    for service in &data.services {
+        let inner = MirakurunChannelServicesInner::new(
+            service.id,
+            service.name.clone(),
+            service.network_id,
+            service.service_id,
+        );
+        match channels
+            .iter_mut()
+            .find(|c| c.r#type == service.channel.r#type && c.channel == service.channel.channel)
+        {
+            Some(channel) => channel.services.push(inner),
+            None => channels.push(MirakurunChannel::new(
+                service.channel.channel.clone(),
+                service.name.clone(),
+                vec![inner],
+                service.channel.r#type,
+            )),
+        }
+    }
+    Json(channels)
+}
+
+async fn programs_of_service(
+    State(state): State<Arc<SimState>>,
+    Path(id): Path<i64>,
+) -> SimResult<impl serde::Serialize> {
+    let data = state.data.read().unwrap();
+    let service = data
+        .services
+        .iter()
+        .find(|s| s.id == id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(
+        data.programs
+            .iter()
+            .filter(|p| p.network_id == service.network_id && p.service_id == service.service_id)
+            .cloned()
+            .collect::<Vec<_>>(),
+    ))
+}
+
+async fn programs(State(state): State<Arc<SimState>>) -> impl IntoResponse {
+    Json(state.data.read().unwrap().programs.clone())
+}
+
+async fn program(
+    State(state): State<Arc<SimState>>,
+    Path(id): Path<i64>,
+) -> SimResult<impl serde::Serialize> {
+    let data = state.data.read().unwrap();
+    found(data.programs.iter().find(|p| p.id == id).cloned())
+}
+
+async fn tuners(State(state): State<Arc<SimState>>) -> impl IntoResponse {
+    Json(state.data.read().unwrap().tuners.clone())
+}
+
+async fn tuner(
+    State(state): State<Arc<SimState>>,
+    Path(index): Path<i32>,
+) -> SimResult<impl serde::Serialize> {
+    let data = state.data.read().unwrap();
+    found(data.tuners.iter().find(|t| t.index == index).cloned())
+}
+
+async fn onair_programs(State(state): State<Arc<SimState>>) -> impl IntoResponse {
+    Json(state.data.read().unwrap().onair.clone())
+}
+
+async fn onair_program(
+    State(state): State<Arc<SimState>>,
+    Path(service_id): Path<i64>,
+) -> SimResult<impl serde::Serialize> {
+    let data = state.data.read().unwrap();
+    // mirakc はサービスを指定した場合も配列で返す
+    let onair: Vec<_> = data
+        .onair
+        .iter()
+        .filter(|o| o.service_id == service_id)
+        .cloned()
+        .collect();
+    found((!onair.is_empty()).then_some(onair))
+}
+
+async fn records(State(state): State<Arc<SimState>>) -> impl IntoResponse {
+    Json(state.data.read().unwrap().records.clone())
+}
+
+async fn record(
+    State(state): State<Arc<SimState>>,
+    Path(id): Path<String>,
+) -> SimResult<impl serde::Serialize> {
+    let data = state.data.read().unwrap();
+    found(data.records.iter().find(|r| r.id == id).cloned())
+}
+
+async fn remove_record(State(state): State<Arc<SimState>>, Path(id): Path<String>) -> StatusCode {
+    let removed = {
+        let mut data = state.data.write().unwrap();
+        let before = data.records.len();
+        data.records.retain(|r| r.id != id);
+        before != data.records.len()
+    };
+    if !removed {
+        return StatusCode::NOT_FOUND;
+    }
+    let _ = state.live.send(ScriptedEvent {
+        delay_ms: 0,
+        event: "recording.record-removed".to_string(),
+        data: json!({ "recordId": id }),
+    });
+    StatusCode::OK
+}
+
+async fn schedules(State(state): State<Arc<SimState>>) -> impl IntoResponse {
+    Json(state.data.read().unwrap().schedules.clone())
+}
+
+async fn schedule(
+    State(state): State<Arc<SimState>>,
+    Path(program_id): Path<i64>,
+) -> SimResult<impl serde::Serialize> {
+    let data = state.data.read().unwrap();
+    found(
+        data.schedules
+            .iter()
+            .find(|s| s.program.id == program_id)
+            .cloned(),
+    )
+}
+
+async fn create_schedule(
+    State(state): State<Arc<SimState>>,
+    Json(input): Json<WebRecordingScheduleInput>,
+) -> Response {
+    let mut data = state.data.write().unwrap();
+    if data
+        .schedules
+        .iter()
+        .any(|s| s.program.id == input.program_id)
+    {
+        return StatusCode::CONFLICT.into_response();
+    }
+    let Some(program) = data.programs.iter().find(|p| p.id == input.program_id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let schedule = WebRecordingSchedule {
+        failed_reason: None,
+        options: input.options,
+        program: Box::new(program.clone()),
+        state: RecordingScheduleState::Scheduled,
+        tags: input.tags.unwrap_or_default(),
+    };
+    data.schedules.push(schedule.clone());
+    (StatusCode::CREATED, Json(schedule)).into_response()
+}
+
+#[derive(Deserialize)]
+struct DeleteSchedulesQuery {
+    tag: Option<String>,
+}
+
+async fn delete_schedules(
+    State(state): State<Arc<SimState>>,
+    Query(query): Query<DeleteSchedulesQuery>,
+) -> StatusCode {
+    let mut data = state.data.write().unwrap();
+    match query.tag {
+        // タグ指定時は録画中などの予約を残す (mirakc と同じ)
+        Some(tag) => data.schedules.retain(|s| {
+            !s.tags.contains(&tag)
+                || matches!(
+                    s.state,
+                    RecordingScheduleState::Tracking | RecordingScheduleState::Recording
+                )
+        }),
+        None => data.schedules.clear(),
+    }
+    StatusCode::OK
+}
+
+async fn delete_schedule(
+    State(state): State<Arc<SimState>>,
+    Path(program_id): Path<i64>,
+) -> StatusCode {
+    let mut data = state.data.write().unwrap();
+    let before = data.schedules.len();
+    data.schedules.retain(|s| s.program.id != program_id);
+    if before == data.schedules.len() {
+        StatusCode::NOT_FOUND
+    } else {
+        StatusCode::OK
+    }
+}
+
+async fn events(State(state): State<Arc<SimState>>) -> Response {
+    state.sse_connections.fetch_add(1, Ordering::SeqCst);
+    let session = state
+        .sessions
+        .lock()
+        .unwrap()
+        .pop_front()
+        .unwrap_or_default();
+
+    if let Some(status) = session.status {
+        return StatusCode::from_u16(status)
+            .unwrap_or(StatusCode::SERVICE_UNAVAILABLE)
+            .into_response();
+    }
+
+    // 台本の配信中に emit されたイベントも取りこぼさないよう、先に購読しておく
+    let live = state.live.subscribe();
+    let mut drops = state.drops.subscribe();
+    drops.mark_unchanged();
+
+    let (tx, rx) = mpsc::channel(16);
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = play_session(session, live, &tx) => {}
+            _ = drops.changed() => {}
+            _ = tx.closed() => {}
+        }
+    });
+
+    Sse::new(ReceiverStream::new(rx)).into_response()
+}
+
+/// 1回の接続の台本を配信する。戻ると接続は切断される。
+async fn play_session(
+    session: SseSession,
+    mut live: broadcast::Receiver<ScriptedEvent>,
+    tx: &mpsc::Sender<std::result::Result<Event, Infallible>>,
+) {
+    for event in session.events {
+        tokio::time::sleep(Duration::from_millis(event.delay_ms)).await;
+        if tx.send(Ok(to_sse_event(&event))).await.is_err() {
+            return;
+        }
+    }
+    if session.end == SessionEnd::Close {
+        return;
+    }
+    loop {
+        match live.recv().await {
+            Ok(event) => {
+                if tx.send(Ok(to_sse_event(&event))).await.is_err() {
+                    return;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+fn to_sse_event(event: &ScriptedEvent) -> Event {
+    Event::default()
+        .event(&event.event)
+        .data(event.data.to_string())
+}
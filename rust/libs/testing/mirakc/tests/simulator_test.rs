@@ -0,0 +1,142 @@
+use anyhow::Result;
+use futures::StreamExt;
+use serde_json::{json, Value};
+
+use testing_mirakc::{MirakcSimulator, Scenario, SessionEnd, SseSession};
+
+/// SSE のレスポンスボディを最後まで読み、`event:` 行の一覧を返す
+async fn read_event_names(response: reqwest::Response) -> Result<Vec<String>> {
+    let body = response.text().await?;
+    Ok(body
+        .lines()
+        .filter_map(|line| line.strip_prefix("event: "))
+        .map(str::to_string)
+        .collect())
+}
+
+#[tokio::test]
+async fn test_rest_api_serves_fixture() -> Result<()> {
+    let simulator = MirakcSimulator::from_fixture("basic").await?;
+    let client = reqwest::Client::new();
+    let api = format!("{}/api", simulator.url());
+
+    let version: Value = client
+        .get(format!("{}/version", api))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(version["current"], "4.0.0");
+
+    let programs: Vec<Value> = client
+        .get(format!("{}/services/3273601024/programs", api))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(programs.len(), 2);
+
+    let onair: Vec<Value> = client
+        .get(format!("{}/onair/3273601024", api))
+        .send()
+        .await?
+        .json()
+        .await?;
+    assert_eq!(onair[0]["next"]["eventId"], 102);
+
+    let status = client
+        .get(format!("{}/tuners/9", api))
+        .send()
+        .await?
+        .status();
+    assert_eq!(status, reqwest::StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_schedules_and_records_are_mutable() -> Result<()> {
+    let simulator = MirakcSimulator::from_fixture("basic").await?;
+    let client = reqwest::Client::new();
+    let api = format!("{}/api", simulator.url());
+
+    let created = client
+        .post(format!("{}/recording/schedules", api))
+        .json(&json!({
+            "programId": 327360102400102i64,
+            "options": {"priority": 0},
+            "tags": ["kurec"]
+        }))
+        .send()
+        .await?;
+    assert_eq!(created.status(), reqwest::StatusCode::CREATED);
+    assert_eq!(simulator.read(|s| s.schedules.len()), 2);
+
+    // 録画中の予約はタグ指定の一括削除では残る
+    simulator.modify(|s| {
+        s.schedules[0].state = mirakc_client::models::RecordingScheduleState::Recording
+    });
+    client
+        .delete(format!("{}/recording/schedules?tag=kurec", api))
+        .send()
+        .await?;
+    assert_eq!(simulator.read(|s| s.schedules.len()), 1);
+
+    let removed = client
+        .delete(format!("{}/recording/records/0000000000000001", api))
+        .send()
+        .await?;
+    assert!(removed.status().is_success());
+    assert!(simulator.read(|s| s.records.is_empty()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sse_sessions_follow_script() -> Result<()> {
+    let simulator = MirakcSimulator::from_fixture("basic").await?;
+    let client = reqwest::Client::new();
+    let events_url = format!("{}/events", simulator.url());
+
+    // 1回目: 台本の2件を配信して切断
+    let first = client.get(&events_url).send().await?;
+    assert_eq!(
+        read_event_names(first).await?,
+        vec!["epg.programs-updated", "tuner.status-changed"]
+    );
+
+    // 2回目: 接続拒否
+    let second = client.get(&events_url).send().await?;
+    assert_eq!(second.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+
+    // 3回目: 台本の1件の後、emit したイベントを配信し続ける
+    let third = client.get(&events_url).send().await?;
+    let mut body = third.bytes_stream();
+    let chunk = body.next().await.unwrap()?;
+    assert!(String::from_utf8_lossy(&chunk).contains("onair.program-changed"));
+
+    simulator.emit("recording.record-saved", json!({"recordId": "2"}));
+    let chunk = body.next().await.unwrap()?;
+    assert!(String::from_utf8_lossy(&chunk).contains("recording.record-saved"));
+
+    simulator.drop_connections();
+    assert!(body.next().await.is_none());
+    assert_eq!(simulator.sse_connections(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scenario_without_sessions_holds_connection() -> Result<()> {
+    let scenario = Scenario::default();
+    assert_eq!(SseSession::default().end, SessionEnd::Hold);
+    let simulator = MirakcSimulator::start(scenario).await?;
+
+    let response = reqwest::get(format!("{}/events", simulator.url())).await?;
+    let mut body = response.bytes_stream();
+    simulator.emit("epg.programs-updated", json!({"serviceId": 1}));
+    let chunk = body.next().await.unwrap()?;
+    assert!(String::from_utf8_lossy(&chunk).contains("\"serviceId\":1"));
+
+    Ok(())
+}
@@ -1,17 +1,18 @@
-import { getConfig } from '@/lib/config';
-import { getKvsValue } from '@/lib/nats';
+import { getObjectValue } from '@/lib/nats';
 import { type NextRequest, NextResponse } from 'next/server';
 
+// OGP ワーカーが URL のハッシュをキーに WebP を保存する Object Store バケット
+const OGP_IMAGES_BUCKET = 'kurec_ogp_images';
+
 export async function GET(
   req: NextRequest,
   { params }: { params: { hash: string } },
 ) {
   const { hash } = await params;
-  const config = getConfig();
-  console.log('hash:', hash);
-  const value = await getKvsValue(`${config.prefix}-ogp`, hash);
-  if (value) {
-    return new NextResponse(value.value, {
+  const value = await getObjectValue(OGP_IMAGES_BUCKET, hash);
+  // 画像がなかった URL は空のオブジェクトとして保存されている
+  if (value && value.length > 0) {
+    return new NextResponse(value, {
       headers: { 'Content-Type': 'image/webp' },
     });
   }
@@ -1,5 +1,6 @@
 import { jetstream } from '@nats-io/jetstream';
 import { KvEntry, Kvm } from '@nats-io/kv';
+import { Objm } from '@nats-io/obj';
 import { connect } from '@nats-io/transport-node';
 import { getConfig } from './config';
 
@@ -44,3 +45,10 @@ export async function updateKvsValue(
   const kv = await kvm.open(bucket);
   return await kv.update(key, value, version);
 }
+
+export async function getObjectValue(bucket: string, name: string) {
+  const js = await getJetstreamConnection();
+  const objm = new Objm(js);
+  const os = await objm.open(bucket);
+  return await os.getBlob(name);
+}